@@ -0,0 +1,470 @@
+use anyhow::{anyhow, Result};
+use futures::{
+    future::BoxFuture, io::BufReader, stream::BoxStream, AsyncBufReadExt, AsyncReadExt, FutureExt,
+    StreamExt,
+};
+use gpui::{AppContext, BackgroundExecutor};
+use isahc::{http::StatusCode, Request, RequestExt};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{env, io, sync::Arc};
+use util::ResultExt;
+
+use crate::{
+    auth::{CredentialProvider, ProviderCredential},
+    completion::{CompletionProvider, CompletionRequest},
+    models::LanguageModel,
+    providers::open_ai::{OpenAiLanguageModel, OpenAiRequest, Role},
+};
+
+pub const ANTHROPIC_API_URL: &'static str = "https://api.anthropic.com/v1";
+
+const ANTHROPIC_VERSION: &'static str = "2023-06-01";
+
+/// Anthropic's API requires a limit on the number of tokens in a response.
+const MAX_OUTPUT_TOKENS: u32 = 4096;
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct AnthropicMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnthropicRequest {
+    pub model: String,
+    pub max_tokens: u32,
+    pub messages: Vec<AnthropicMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    pub stream: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+    pub temperature: f32,
+}
+
+impl AnthropicRequest {
+    /// Translates a request in OpenAI's chat format. Anthropic takes the system prompt on
+    /// its own and needs the roles of the other messages to alternate, starting with the
+    /// user, so system messages are gathered and neighboring messages of one role merged.
+    pub fn from_open_ai(request: OpenAiRequest) -> Self {
+        let mut system = Vec::new();
+        let mut messages: Vec<AnthropicMessage> = Vec::new();
+        for message in request.messages {
+            if message.content.is_empty() {
+                continue;
+            }
+            match message.role {
+                Role::System => system.push(message.content),
+                role => match messages.last_mut() {
+                    Some(last) if last.role == role => {
+                        last.content.push_str("\n\n");
+                        last.content.push_str(&message.content);
+                    }
+                    None if role == Role::Assistant => {}
+                    _ => messages.push(AnthropicMessage {
+                        role,
+                        content: message.content,
+                    }),
+                },
+            }
+        }
+
+        Self {
+            model: request.model,
+            max_tokens: MAX_OUTPUT_TOKENS,
+            messages,
+            system: (!system.is_empty()).then(|| system.join("\n\n")),
+            stream: request.stream,
+            stop_sequences: request.stop,
+            // Anthropic's temperatures go up to 1 rather than 2.
+            temperature: request.temperature.min(1.0),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AnthropicTextDelta {
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AnthropicError {
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnthropicStreamEvent {
+    ContentBlockDelta {
+        delta: AnthropicTextDelta,
+    },
+    MessageStop,
+    Error {
+        error: AnthropicError,
+    },
+    #[serde(other)]
+    Other,
+}
+
+pub async fn stream_completion(
+    api_url: String,
+    credential: ProviderCredential,
+    executor: BackgroundExecutor,
+    request: Box<dyn CompletionRequest>,
+) -> Result<BoxStream<'static, Result<String>>> {
+    let api_key = match credential {
+        ProviderCredential::Credentials { api_key } => api_key,
+        _ => {
+            return Err(anyhow!("no credentials provider for completion"));
+        }
+    };
+
+    let request = AnthropicRequest::from_open_ai(serde_json::from_str(&request.data()?)?);
+    let (tx, rx) = futures::channel::mpsc::unbounded::<Result<String>>();
+
+    let mut response = Request::post(format!("{api_url}/messages"))
+        .header("Content-Type", "application/json")
+        .header("X-Api-Key", api_key)
+        .header("Anthropic-Version", ANTHROPIC_VERSION)
+        .body(serde_json::to_string(&request)?)?
+        .send_async()
+        .await?;
+
+    if response.status() == StatusCode::OK {
+        executor
+            .spawn(async move {
+                let mut lines = BufReader::new(response.body_mut()).lines();
+
+                fn parse_line(
+                    line: Result<String, io::Error>,
+                ) -> Result<Option<AnthropicStreamEvent>> {
+                    if let Some(data) = line?.strip_prefix("data: ") {
+                        let event = serde_json::from_str(data)?;
+                        Ok(Some(event))
+                    } else {
+                        Ok(None)
+                    }
+                }
+
+                while let Some(line) = lines.next().await {
+                    let text = match parse_line(line) {
+                        Ok(Some(AnthropicStreamEvent::ContentBlockDelta { delta })) => {
+                            Ok(delta.text)
+                        }
+                        Ok(Some(AnthropicStreamEvent::MessageStop)) => break,
+                        Ok(Some(AnthropicStreamEvent::Error { error })) => {
+                            Err(anyhow!("Anthropic API error: {}", error.message))
+                        }
+                        Ok(Some(AnthropicStreamEvent::Other) | None) => continue,
+                        Err(error) => Err(error),
+                    };
+                    let is_error = text.is_err();
+                    if tx.unbounded_send(text).is_err() || is_error {
+                        break;
+                    }
+                }
+
+                anyhow::Ok(())
+            })
+            .detach();
+
+        Ok(rx.boxed())
+    } else {
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        #[derive(Deserialize)]
+        struct AnthropicResponse {
+            error: AnthropicError,
+        }
+
+        match serde_json::from_str::<AnthropicResponse>(&body) {
+            Ok(response) if !response.error.message.is_empty() => Err(anyhow!(
+                "Failed to connect to Anthropic API: {}",
+                response.error.message,
+            )),
+
+            _ => Err(anyhow!(
+                "Failed to connect to Anthropic API: {} {}",
+                response.status(),
+                body,
+            )),
+        }
+    }
+}
+
+/// The context size of a model, which is larger for Claude 3.
+pub fn context_size(model_name: &str) -> usize {
+    if model_name.starts_with("claude-3") {
+        200_000
+    } else {
+        100_000
+    }
+}
+
+#[derive(Clone)]
+pub struct AnthropicCompletionProvider {
+    api_url: String,
+    model: OpenAiLanguageModel,
+    credential: Arc<RwLock<ProviderCredential>>,
+    executor: BackgroundExecutor,
+}
+
+impl AnthropicCompletionProvider {
+    pub async fn new(api_url: String, model_name: String, executor: BackgroundExecutor) -> Self {
+        let model = executor
+            .spawn(async move {
+                OpenAiLanguageModel::load_with_capacity(&model_name, context_size(&model_name))
+            })
+            .await;
+        let credential = Arc::new(RwLock::new(ProviderCredential::NoCredentials));
+        Self {
+            api_url,
+            model,
+            credential,
+            executor,
+        }
+    }
+}
+
+impl CredentialProvider for AnthropicCompletionProvider {
+    fn has_credentials(&self) -> bool {
+        match *self.credential.read() {
+            ProviderCredential::Credentials { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn retrieve_credentials(&self, cx: &mut AppContext) -> BoxFuture<ProviderCredential> {
+        let existing_credential = self.credential.read().clone();
+        let retrieved_credential = match existing_credential {
+            ProviderCredential::Credentials { .. } => {
+                return async move { existing_credential }.boxed()
+            }
+            _ => {
+                if let Some(api_key) = env::var("ANTHROPIC_API_KEY").log_err() {
+                    async move { ProviderCredential::Credentials { api_key } }.boxed()
+                } else {
+                    let credentials = cx.read_credentials(&self.api_url);
+                    async move {
+                        if let Some(Some((_, api_key))) = credentials.await.log_err() {
+                            if let Some(api_key) = String::from_utf8(api_key).log_err() {
+                                ProviderCredential::Credentials { api_key }
+                            } else {
+                                ProviderCredential::NoCredentials
+                            }
+                        } else {
+                            ProviderCredential::NoCredentials
+                        }
+                    }
+                    .boxed()
+                }
+            }
+        };
+
+        async move {
+            let retrieved_credential = retrieved_credential.await;
+            *self.credential.write() = retrieved_credential.clone();
+            retrieved_credential
+        }
+        .boxed()
+    }
+
+    fn save_credentials(
+        &self,
+        cx: &mut AppContext,
+        credential: ProviderCredential,
+    ) -> BoxFuture<()> {
+        *self.credential.write() = credential.clone();
+        let write_credentials = match credential {
+            ProviderCredential::Credentials { api_key } => {
+                Some(cx.write_credentials(&self.api_url, "X-Api-Key", api_key.as_bytes()))
+            }
+            _ => None,
+        };
+
+        async move {
+            if let Some(write_credentials) = write_credentials {
+                write_credentials.await.log_err();
+            }
+        }
+        .boxed()
+    }
+
+    fn delete_credentials(&self, cx: &mut AppContext) -> BoxFuture<()> {
+        *self.credential.write() = ProviderCredential::NoCredentials;
+        let delete_credentials = cx.delete_credentials(&self.api_url);
+        async move {
+            delete_credentials.await.log_err();
+        }
+        .boxed()
+    }
+}
+
+impl CompletionProvider for AnthropicCompletionProvider {
+    fn base_model(&self) -> Box<dyn LanguageModel> {
+        let model: Box<dyn LanguageModel> = Box::new(self.model.clone());
+        model
+    }
+
+    fn complete(
+        &self,
+        prompt: Box<dyn CompletionRequest>,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let credential = self.credential.read().clone();
+        stream_completion(
+            self.api_url.clone(),
+            credential,
+            self.executor.clone(),
+            prompt,
+        )
+        .boxed()
+    }
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider> {
+        Box::new((*self).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{providers::open_ai::RequestMessage, test::MockServer};
+    use gpui::TestAppContext;
+
+    #[test]
+    fn test_request_from_open_ai() {
+        let request = AnthropicRequest::from_open_ai(OpenAiRequest {
+            model: "claude-3-opus-20240229".into(),
+            messages: vec![
+                RequestMessage {
+                    role: Role::Assistant,
+                    content: "Hello".into(),
+                },
+                RequestMessage {
+                    role: Role::System,
+                    content: "Be brief".into(),
+                },
+                RequestMessage {
+                    role: Role::User,
+                    content: "one".into(),
+                },
+                RequestMessage {
+                    role: Role::User,
+                    content: "two".into(),
+                },
+                RequestMessage {
+                    role: Role::Assistant,
+                    content: "three".into(),
+                },
+            ],
+            stream: true,
+            stop: Vec::new(),
+            temperature: 1.5,
+        });
+
+        assert_eq!(request.system.as_deref(), Some("Be brief"));
+        assert_eq!(
+            request.messages,
+            vec![
+                AnthropicMessage {
+                    role: Role::User,
+                    content: "one\n\ntwo".into(),
+                },
+                AnthropicMessage {
+                    role: Role::Assistant,
+                    content: "three".into(),
+                },
+            ]
+        );
+        assert_eq!(request.temperature, 1.0);
+    }
+
+    #[gpui::test]
+    async fn test_stream_completion(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let server = MockServer::start(
+            200,
+            "text/event-stream",
+            concat!(
+                "event: message_start\n",
+                "data: {\"type\":\"message_start\",\"message\":{}}\n\n",
+                "event: content_block_delta\n",
+                "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hello\"}}\n\n",
+                "event: content_block_delta\n",
+                "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\" world\"}}\n\n",
+                "event: message_stop\n",
+                "data: {\"type\":\"message_stop\"}\n\n",
+            ),
+        );
+        let provider = AnthropicCompletionProvider::new(
+            server.url().to_string(),
+            "claude-3-haiku-20240307".into(),
+            cx.executor(),
+        )
+        .await;
+        cx.update(|cx| {
+            provider.save_credentials(
+                cx,
+                ProviderCredential::Credentials {
+                    api_key: "secret".into(),
+                },
+            )
+        })
+        .await;
+
+        let request = OpenAiRequest {
+            model: "claude-3-haiku-20240307".into(),
+            messages: vec![RequestMessage {
+                role: Role::User,
+                content: "Hi".into(),
+            }],
+            stream: true,
+            stop: Vec::new(),
+            temperature: 1.0,
+        };
+        let chunks = provider
+            .complete(Box::new(request))
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        let text = chunks.into_iter().collect::<Result<String>>().unwrap();
+        assert_eq!(text, "Hello world");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/messages");
+        assert_eq!(requests[0].header("x-api-key"), Some("secret"));
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(body["model"], "claude-3-haiku-20240307");
+        assert_eq!(body["messages"][0]["content"], "Hi");
+    }
+
+    #[gpui::test]
+    async fn test_stream_completion_error(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let server = MockServer::start(
+            401,
+            "application/json",
+            r#"{"type":"error","error":{"type":"authentication_error","message":"invalid x-api-key"}}"#,
+        );
+        let error = stream_completion(
+            server.url().to_string(),
+            ProviderCredential::Credentials {
+                api_key: "wrong".into(),
+            },
+            cx.executor(),
+            Box::new(OpenAiRequest::default()),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Failed to connect to Anthropic API: invalid x-api-key"
+        );
+    }
+}
@@ -1 +1,4 @@
+pub mod anthropic;
+pub mod ollama;
 pub mod open_ai;
+pub mod open_ai_compatible;
@@ -0,0 +1,289 @@
+use anyhow::{anyhow, Result};
+use futures::{
+    future::BoxFuture, io::BufReader, stream::BoxStream, AsyncBufReadExt, AsyncReadExt, FutureExt,
+    StreamExt,
+};
+use gpui::{AppContext, BackgroundExecutor};
+use isahc::{http::StatusCode, Request, RequestExt};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+use crate::{
+    auth::{CredentialProvider, ProviderCredential},
+    completion::{CompletionProvider, CompletionRequest},
+    models::LanguageModel,
+    providers::open_ai::{OpenAiLanguageModel, OpenAiRequest, RequestMessage},
+};
+
+#[derive(Debug, Serialize)]
+pub struct OllamaOptions {
+    pub temperature: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OllamaRequest {
+    pub model: String,
+    pub messages: Vec<RequestMessage>,
+    pub stream: bool,
+    pub options: OllamaOptions,
+}
+
+impl OllamaRequest {
+    pub fn from_open_ai(request: OpenAiRequest) -> Self {
+        Self {
+            model: request.model,
+            messages: request.messages,
+            stream: request.stream,
+            options: OllamaOptions {
+                temperature: request.temperature,
+                stop: request.stop,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OllamaResponseMessage {
+    #[serde(default)]
+    pub content: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OllamaResponseStreamEvent {
+    pub message: Option<OllamaResponseMessage>,
+    #[serde(default)]
+    pub done: bool,
+    pub error: Option<String>,
+}
+
+pub async fn stream_completion(
+    api_url: String,
+    executor: BackgroundExecutor,
+    request: Box<dyn CompletionRequest>,
+) -> Result<BoxStream<'static, Result<String>>> {
+    let request = OllamaRequest::from_open_ai(serde_json::from_str(&request.data()?)?);
+    let (tx, rx) = futures::channel::mpsc::unbounded::<Result<String>>();
+
+    let mut response = Request::post(format!("{api_url}/chat"))
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_string(&request)?)?
+        .send_async()
+        .await
+        .map_err(|error| anyhow!("Failed to connect to Ollama at {api_url}: {error}"))?;
+
+    if response.status() == StatusCode::OK {
+        executor
+            .spawn(async move {
+                let mut lines = BufReader::new(response.body_mut()).lines();
+
+                fn parse_line(
+                    line: Result<String, io::Error>,
+                ) -> Result<Option<OllamaResponseStreamEvent>> {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        return Ok(None);
+                    }
+                    Ok(Some(serde_json::from_str(&line)?))
+                }
+
+                while let Some(line) = lines.next().await {
+                    let event = match parse_line(line) {
+                        Ok(Some(event)) => event,
+                        Ok(None) => continue,
+                        Err(error) => {
+                            tx.unbounded_send(Err(error)).ok();
+                            break;
+                        }
+                    };
+
+                    if let Some(error) = event.error {
+                        tx.unbounded_send(Err(anyhow!("Ollama error: {error}")))
+                            .ok();
+                        break;
+                    }
+                    if let Some(message) = event.message {
+                        if tx.unbounded_send(Ok(message.content)).is_err() {
+                            break;
+                        }
+                    }
+                    if event.done {
+                        break;
+                    }
+                }
+
+                anyhow::Ok(())
+            })
+            .detach();
+
+        Ok(rx.boxed())
+    } else {
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        #[derive(Deserialize)]
+        struct OllamaResponse {
+            error: String,
+        }
+
+        match serde_json::from_str::<OllamaResponse>(&body) {
+            Ok(response) if !response.error.is_empty() => {
+                Err(anyhow!("Failed to connect to Ollama: {}", response.error))
+            }
+
+            _ => Err(anyhow!(
+                "Failed to connect to Ollama: {} {}",
+                response.status(),
+                body,
+            )),
+        }
+    }
+}
+
+/// Completes prompts with models running locally in Ollama, which needs no API key.
+#[derive(Clone)]
+pub struct OllamaCompletionProvider {
+    api_url: String,
+    model: OpenAiLanguageModel,
+    executor: BackgroundExecutor,
+}
+
+impl OllamaCompletionProvider {
+    pub async fn new(
+        api_url: String,
+        model_name: String,
+        capacity: usize,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        let model = executor
+            .spawn(async move { OpenAiLanguageModel::load_with_capacity(&model_name, capacity) })
+            .await;
+        Self {
+            api_url,
+            model,
+            executor,
+        }
+    }
+}
+
+impl CredentialProvider for OllamaCompletionProvider {
+    fn has_credentials(&self) -> bool {
+        true
+    }
+
+    fn retrieve_credentials(&self, _cx: &mut AppContext) -> BoxFuture<ProviderCredential> {
+        async { ProviderCredential::NotNeeded }.boxed()
+    }
+
+    fn save_credentials(
+        &self,
+        _cx: &mut AppContext,
+        _credential: ProviderCredential,
+    ) -> BoxFuture<()> {
+        async {}.boxed()
+    }
+
+    fn delete_credentials(&self, _cx: &mut AppContext) -> BoxFuture<()> {
+        async {}.boxed()
+    }
+}
+
+impl CompletionProvider for OllamaCompletionProvider {
+    fn base_model(&self) -> Box<dyn LanguageModel> {
+        let model: Box<dyn LanguageModel> = Box::new(self.model.clone());
+        model
+    }
+
+    fn complete(
+        &self,
+        prompt: Box<dyn CompletionRequest>,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        stream_completion(self.api_url.clone(), self.executor.clone(), prompt).boxed()
+    }
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider> {
+        Box::new((*self).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{providers::open_ai::Role, test::MockServer};
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_stream_completion(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let server = MockServer::start(
+            200,
+            "application/x-ndjson",
+            concat!(
+                "{\"model\":\"llama2\",\"message\":{\"role\":\"assistant\",\"content\":\"Hello\"},\"done\":false}\n",
+                "{\"model\":\"llama2\",\"message\":{\"role\":\"assistant\",\"content\":\" world\"},\"done\":false}\n",
+                "{\"model\":\"llama2\",\"done\":true,\"eval_count\":2}\n",
+            ),
+        );
+        let provider = OllamaCompletionProvider::new(
+            server.url().to_string(),
+            "llama2".into(),
+            4096,
+            cx.executor(),
+        )
+        .await;
+        assert!(provider.has_credentials());
+        assert_eq!(provider.base_model().capacity().unwrap(), 4096);
+
+        let request = OpenAiRequest {
+            model: "llama2".into(),
+            messages: vec![RequestMessage {
+                role: Role::User,
+                content: "Hi".into(),
+            }],
+            stream: true,
+            stop: vec!["\n\n".into()],
+            temperature: 0.5,
+        };
+        let chunks = provider
+            .complete(Box::new(request))
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        let text = chunks.into_iter().collect::<Result<String>>().unwrap();
+        assert_eq!(text, "Hello world");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/chat");
+        assert_eq!(requests[0].header("authorization"), None);
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(body["model"], "llama2");
+        assert_eq!(body["messages"][0]["content"], "Hi");
+        assert_eq!(body["options"]["temperature"], 0.5);
+        assert_eq!(body["options"]["stop"][0], "\n\n");
+    }
+
+    #[gpui::test]
+    async fn test_stream_completion_error(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let server = MockServer::start(
+            404,
+            "application/json",
+            r#"{"error":"model 'llama2' not found, try pulling it first"}"#,
+        );
+        let error = stream_completion(
+            server.url().to_string(),
+            cx.executor(),
+            Box::new(OpenAiRequest::default()),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Failed to connect to Ollama: model 'llama2' not found, try pulling it first"
+        );
+    }
+}
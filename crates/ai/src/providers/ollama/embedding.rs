@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::{future::BoxFuture, AsyncReadExt, FutureExt};
+use gpui::{AppContext, BackgroundExecutor};
+use isahc::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Instant};
+use util::http::HttpClient;
+
+use crate::{
+    auth::{CredentialProvider, ProviderCredential},
+    embedding::{Embedding, EmbeddingProvider},
+    models::LanguageModel,
+    providers::open_ai::OpenAiLanguageModel,
+};
+
+/// The number of spans embedded between two updates of the indexing progress. Ollama
+/// embeds one span per request, so a batch holds at most this many full-length spans.
+const SPANS_PER_BATCH: usize = 16;
+
+#[derive(Serialize)]
+struct OllamaEmbeddingRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct OllamaErrorResponse {
+    error: String,
+}
+
+/// Embeds spans with a model running locally in Ollama, so that indexing doesn't send
+/// any code over the network.
+#[derive(Clone)]
+pub struct OllamaEmbeddingProvider {
+    api_url: String,
+    model: OpenAiLanguageModel,
+    client: Arc<dyn HttpClient>,
+}
+
+impl OllamaEmbeddingProvider {
+    /// Creates a provider for `model_name`, whose spans are truncated to `capacity`
+    /// tokens before being embedded.
+    pub async fn new(
+        api_url: String,
+        model_name: String,
+        capacity: usize,
+        client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        // Loading the tokenizer is expensive, so ensure this runs off the main thread.
+        let model = executor
+            .spawn(async move { OpenAiLanguageModel::load_with_capacity(&model_name, capacity) })
+            .await;
+        Self {
+            api_url,
+            model,
+            client,
+        }
+    }
+
+    async fn embed(&self, model_name: &str, span: &str) -> Result<Embedding> {
+        let request = OllamaEmbeddingRequest {
+            model: model_name,
+            prompt: span,
+        };
+        let mut response = self
+            .client
+            .post_json(
+                &format!("{}/embeddings", self.api_url),
+                serde_json::to_string(&request)?.into(),
+            )
+            .await
+            .map_err(|error| anyhow!("Failed to connect to Ollama at {}: {error}", self.api_url))?;
+
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+        if response.status() == StatusCode::OK {
+            let response: OllamaEmbeddingResponse = serde_json::from_str(&body)?;
+            Ok(Embedding::from(response.embedding))
+        } else {
+            match serde_json::from_str::<OllamaErrorResponse>(&body) {
+                Ok(response) => Err(anyhow!("Ollama embedding error: {}", response.error)),
+                Err(_) => Err(anyhow!(
+                    "Ollama embedding error: {} {}",
+                    response.status(),
+                    body
+                )),
+            }
+        }
+    }
+}
+
+impl CredentialProvider for OllamaEmbeddingProvider {
+    fn has_credentials(&self) -> bool {
+        true
+    }
+
+    fn retrieve_credentials(&self, _cx: &mut AppContext) -> BoxFuture<ProviderCredential> {
+        async { ProviderCredential::NotNeeded }.boxed()
+    }
+
+    fn save_credentials(
+        &self,
+        _cx: &mut AppContext,
+        _credential: ProviderCredential,
+    ) -> BoxFuture<()> {
+        async {}.boxed()
+    }
+
+    fn delete_credentials(&self, _cx: &mut AppContext) -> BoxFuture<()> {
+        async {}.boxed()
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbeddingProvider {
+    fn base_model(&self) -> Box<dyn LanguageModel> {
+        let model: Box<dyn LanguageModel> = Box::new(self.model.clone());
+        model
+    }
+
+    fn max_tokens_per_batch(&self) -> usize {
+        self.model.capacity().unwrap_or(0).max(1) * SPANS_PER_BATCH
+    }
+
+    fn rate_limit_expiration(&self) -> Option<Instant> {
+        None
+    }
+
+    async fn embed_batch(&self, spans: Vec<String>) -> Result<Vec<Embedding>> {
+        let model_name = self.model.name();
+        let mut embeddings = Vec::with_capacity(spans.len());
+        for span in &spans {
+            embeddings.push(self.embed(&model_name, span).await?);
+        }
+        Ok(embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::MockServer;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_embed_batch(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let server = MockServer::start(200, "application/json", r#"{"embedding":[0.5,-0.25]}"#);
+        let provider = OllamaEmbeddingProvider::new(
+            server.url().to_string(),
+            "nomic-embed-text".into(),
+            2048,
+            util::http::client(),
+            cx.executor(),
+        )
+        .await;
+        assert!(provider.has_credentials());
+        assert_eq!(provider.max_tokens_per_batch(), 2048 * SPANS_PER_BATCH);
+
+        let embeddings = provider
+            .embed_batch(vec!["fn main() {}".into(), "struct Foo;".into()])
+            .await
+            .unwrap();
+        assert_eq!(
+            embeddings,
+            vec![Embedding(vec![0.5, -0.25]), Embedding(vec![0.5, -0.25])]
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].path, "/embeddings");
+        let body: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+        assert_eq!(body["model"], "nomic-embed-text");
+        assert_eq!(body["prompt"], "struct Foo;");
+    }
+
+    #[gpui::test]
+    async fn test_embed_batch_error(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let server = MockServer::start(
+            404,
+            "application/json",
+            r#"{"error":"model 'nomic-embed-text' not found, try pulling it first"}"#,
+        );
+        let provider = OllamaEmbeddingProvider::new(
+            server.url().to_string(),
+            "nomic-embed-text".into(),
+            2048,
+            util::http::client(),
+            cx.executor(),
+        )
+        .await;
+
+        let error = provider
+            .embed_batch(vec!["fn main() {}".into()])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Ollama embedding error: model 'nomic-embed-text' not found, try pulling it first"
+        );
+    }
+}
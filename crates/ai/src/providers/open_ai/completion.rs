@@ -57,11 +57,14 @@ pub struct RequestMessage {
     pub content: String,
 }
 
-#[derive(Debug, Default, Serialize)]
+/// A request in OpenAI's chat format. The assistant writes every request this way, and
+/// providers with other APIs translate it.
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct OpenAiRequest {
     pub model: String,
     pub messages: Vec<RequestMessage>,
     pub stream: bool,
+    #[serde(default)]
     pub stop: Vec<String>,
     pub temperature: f32,
 }
@@ -109,8 +112,9 @@ pub async fn stream_completion(
     request: Box<dyn CompletionRequest>,
 ) -> Result<impl Stream<Item = Result<OpenAiResponseStreamEvent>>> {
     let api_key = match credential {
-        ProviderCredential::Credentials { api_key } => api_key,
-        _ => {
+        ProviderCredential::Credentials { api_key } => Some(api_key),
+        ProviderCredential::NotNeeded => None,
+        ProviderCredential::NoCredentials => {
             return Err(anyhow!("no credentials provider for completion"));
         }
     };
@@ -118,12 +122,12 @@ pub async fn stream_completion(
     let (tx, rx) = futures::channel::mpsc::unbounded::<Result<OpenAiResponseStreamEvent>>();
 
     let json_data = request.data()?;
-    let mut response = Request::post(format!("{api_url}/chat/completions"))
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .body(json_data)?
-        .send_async()
-        .await?;
+    let mut request = Request::post(format!("{api_url}/chat/completions"))
+        .header("Content-Type", "application/json");
+    if let Some(api_key) = api_key {
+        request = request.header("Authorization", format!("Bearer {}", api_key));
+    }
+    let mut response = request.body(json_data)?.send_async().await?;
 
     let status = response.status();
     if status == StatusCode::OK {
@@ -306,23 +310,37 @@ impl CompletionProvider for OpenAiCompletionProvider {
         // which is currently model based, due to the language model.
         // At some point in the future we should rectify this.
         let credential = self.credential.read().clone();
-        let api_url = self.api_url.clone();
-        let request = stream_completion(api_url, credential, self.executor.clone(), prompt);
-        async move {
-            let response = request.await?;
-            let stream = response
-                .filter_map(|response| async move {
-                    match response {
-                        Ok(mut response) => Some(Ok(response.choices.pop()?.delta.content?)),
-                        Err(error) => Some(Err(error)),
-                    }
-                })
-                .boxed();
-            Ok(stream)
-        }
-        .boxed()
+        complete_chat(
+            self.api_url.clone(),
+            credential,
+            self.executor.clone(),
+            prompt,
+        )
     }
     fn box_clone(&self) -> Box<dyn CompletionProvider> {
         Box::new((*self).clone())
     }
 }
+
+/// Streams the text of a chat completion from an API that works like OpenAI's.
+pub(crate) fn complete_chat(
+    api_url: String,
+    credential: ProviderCredential,
+    executor: BackgroundExecutor,
+    prompt: Box<dyn CompletionRequest>,
+) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+    let request = stream_completion(api_url, credential, executor, prompt);
+    async move {
+        let response = request.await?;
+        let stream = response
+            .filter_map(|response| async move {
+                match response {
+                    Ok(mut response) => Some(Ok(response.choices.pop()?.delta.content?)),
+                    Err(error) => Some(Err(error)),
+                }
+            })
+            .boxed();
+        Ok(stream)
+    }
+    .boxed()
+}
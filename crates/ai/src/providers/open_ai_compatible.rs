@@ -0,0 +1,190 @@
+use anyhow::Result;
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt};
+use gpui::{AppContext, BackgroundExecutor};
+use parking_lot::RwLock;
+use std::sync::Arc;
+use util::ResultExt;
+
+use crate::{
+    auth::{CredentialProvider, ProviderCredential},
+    completion::{CompletionProvider, CompletionRequest},
+    models::LanguageModel,
+    providers::open_ai::{complete_chat, OpenAiLanguageModel},
+};
+
+/// Completes prompts with a server that implements OpenAI's chat completions API, such
+/// as a local inference server. An API key is only sent once one has been saved for the
+/// server's URL.
+#[derive(Clone)]
+pub struct OpenAiCompatibleCompletionProvider {
+    api_url: String,
+    model: OpenAiLanguageModel,
+    credential: Arc<RwLock<ProviderCredential>>,
+    executor: BackgroundExecutor,
+}
+
+impl OpenAiCompatibleCompletionProvider {
+    pub async fn new(
+        api_url: String,
+        model_name: String,
+        capacity: usize,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        let model = executor
+            .spawn(async move { OpenAiLanguageModel::load_with_capacity(&model_name, capacity) })
+            .await;
+        let credential = Arc::new(RwLock::new(ProviderCredential::NotNeeded));
+        Self {
+            api_url,
+            model,
+            credential,
+            executor,
+        }
+    }
+}
+
+impl CredentialProvider for OpenAiCompatibleCompletionProvider {
+    fn has_credentials(&self) -> bool {
+        match *self.credential.read() {
+            ProviderCredential::Credentials { .. } | ProviderCredential::NotNeeded => true,
+            ProviderCredential::NoCredentials => false,
+        }
+    }
+
+    fn retrieve_credentials(&self, cx: &mut AppContext) -> BoxFuture<ProviderCredential> {
+        let existing_credential = self.credential.read().clone();
+        if let ProviderCredential::Credentials { .. } = existing_credential {
+            return async move { existing_credential }.boxed();
+        }
+
+        let credentials = cx.read_credentials(&self.api_url);
+        async move {
+            let retrieved_credential = match credentials.await.log_err() {
+                Some(Some((_, api_key))) => match String::from_utf8(api_key).log_err() {
+                    Some(api_key) => ProviderCredential::Credentials { api_key },
+                    None => ProviderCredential::NotNeeded,
+                },
+                _ => ProviderCredential::NotNeeded,
+            };
+            *self.credential.write() = retrieved_credential.clone();
+            retrieved_credential
+        }
+        .boxed()
+    }
+
+    fn save_credentials(
+        &self,
+        cx: &mut AppContext,
+        credential: ProviderCredential,
+    ) -> BoxFuture<()> {
+        *self.credential.write() = credential.clone();
+        let write_credentials = match credential {
+            ProviderCredential::Credentials { api_key } => {
+                Some(cx.write_credentials(&self.api_url, "Bearer", api_key.as_bytes()))
+            }
+            _ => None,
+        };
+
+        async move {
+            if let Some(write_credentials) = write_credentials {
+                write_credentials.await.log_err();
+            }
+        }
+        .boxed()
+    }
+
+    fn delete_credentials(&self, cx: &mut AppContext) -> BoxFuture<()> {
+        *self.credential.write() = ProviderCredential::NotNeeded;
+        let delete_credentials = cx.delete_credentials(&self.api_url);
+        async move {
+            delete_credentials.await.log_err();
+        }
+        .boxed()
+    }
+}
+
+impl CompletionProvider for OpenAiCompatibleCompletionProvider {
+    fn base_model(&self) -> Box<dyn LanguageModel> {
+        let model: Box<dyn LanguageModel> = Box::new(self.model.clone());
+        model
+    }
+
+    fn complete(
+        &self,
+        prompt: Box<dyn CompletionRequest>,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let credential = self.credential.read().clone();
+        complete_chat(
+            self.api_url.clone(),
+            credential,
+            self.executor.clone(),
+            prompt,
+        )
+    }
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider> {
+        Box::new((*self).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        providers::open_ai::{OpenAiRequest, RequestMessage, Role},
+        test::MockServer,
+    };
+    use futures::StreamExt;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_stream_completion(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let server = MockServer::start(
+            200,
+            "text/event-stream",
+            concat!(
+                "data: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"local\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hello\"},\"finish_reason\":null}]}\n\n",
+                "data: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"local\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\" world\"},\"finish_reason\":\"stop\"}]}\n\n",
+                "data: [DONE]\n\n",
+            ),
+        );
+        let provider = OpenAiCompatibleCompletionProvider::new(
+            server.url().to_string(),
+            "local".into(),
+            8192,
+            cx.executor(),
+        )
+        .await;
+        let credential = cx.update(|cx| provider.retrieve_credentials(cx)).await;
+        assert!(matches!(credential, ProviderCredential::NotNeeded));
+        assert!(provider.has_credentials());
+
+        let request = OpenAiRequest {
+            model: "local".into(),
+            messages: vec![RequestMessage {
+                role: Role::User,
+                content: "Hi".into(),
+            }],
+            stream: true,
+            stop: Vec::new(),
+            temperature: 1.0,
+        };
+        let chunks = provider
+            .complete(Box::new(request))
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        let text = chunks.into_iter().collect::<Result<String>>().unwrap();
+        assert_eq!(text, "Hello world");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/chat/completions");
+        assert_eq!(requests[0].header("authorization"), None);
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(body["model"], "local");
+    }
+}
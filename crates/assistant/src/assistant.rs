@@ -1,15 +1,18 @@
 pub mod assistant_panel;
 pub mod assistant_settings;
 mod codegen;
+mod completion_provider;
+mod context;
 mod prompts;
 mod streaming_diff;
 
 use ai::providers::open_ai::Role;
 use anyhow::Result;
 pub use assistant_panel::AssistantPanel;
-use assistant_settings::OpenAiModel;
+use assistant_settings::AssistantModel;
 use chrono::{DateTime, Local};
 use collections::HashMap;
+use context::ContextItem;
 use fs::Fs;
 use futures::StreamExt;
 use gpui::{actions, AppContext, SharedString};
@@ -31,6 +34,11 @@ actions!(
         InlineAssist,
         ToggleIncludeConversation,
         ToggleRetrieveContext,
+        AttachFile,
+        AttachSymbol,
+        AttachDiagnostics,
+        AttachTerminalOutput,
+        AttachSearchResults,
     ]
 );
 
@@ -69,7 +77,9 @@ struct SavedConversation {
     message_metadata: HashMap<MessageId, MessageMetadata>,
     summary: String,
     api_url: Option<String>,
-    model: OpenAiModel,
+    model: AssistantModel,
+    #[serde(default)]
+    context: Vec<ContextItem>,
 }
 
 impl SavedConversation {
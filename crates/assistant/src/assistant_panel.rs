@@ -1,17 +1,19 @@
 use crate::{
-    assistant_settings::{AssistantDockPosition, AssistantSettings, OpenAiModel},
+    assistant_settings::{AssistantDockPosition, AssistantModel, AssistantSettings, ModelProvider},
     codegen::{self, Codegen, CodegenKind},
+    completion_provider::build_completion_provider,
+    context::{context_prompt, ContextItem},
     prompts::generate_content_prompt,
-    Assist, CycleMessageRole, InlineAssist, MessageId, MessageMetadata, MessageStatus,
-    NewConversation, QuoteSelection, ResetKey, Role, SavedConversation, SavedConversationMetadata,
-    SavedMessage, Split, ToggleFocus, ToggleIncludeConversation, ToggleRetrieveContext,
+    Assist, AttachDiagnostics, AttachFile, AttachSearchResults, AttachSymbol, AttachTerminalOutput,
+    CycleMessageRole, InlineAssist, MessageId, MessageMetadata, MessageStatus, NewConversation,
+    QuoteSelection, ResetKey, Role, SavedConversation, SavedConversationMetadata, SavedMessage,
+    Split, ToggleFocus, ToggleIncludeConversation, ToggleRetrieveContext,
 };
 use ai::prompts::repository_context::PromptCodeSnippet;
-use ai::providers::open_ai::OPEN_AI_API_URL;
 use ai::{
     auth::ProviderCredential,
     completion::{CompletionProvider, CompletionRequest},
-    providers::open_ai::{OpenAiCompletionProvider, OpenAiRequest, RequestMessage},
+    providers::open_ai::{OpenAiRequest, RequestMessage},
 };
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Local};
@@ -26,7 +28,7 @@ use editor::{
     ToPoint,
 };
 use fs::Fs;
-use futures::StreamExt;
+use futures::{future, FutureExt, StreamExt};
 use gpui::{
     canvas, div, point, relative, rems, uniform_list, Action, AnyElement, AppContext,
     AsyncAppContext, AsyncWindowContext, AvailableSpace, ClipboardItem, Context, EventEmitter,
@@ -44,6 +46,7 @@ use std::{
     cell::Cell,
     cmp,
     fmt::Write,
+    future::Future,
     iter,
     ops::Range,
     path::{Path, PathBuf},
@@ -52,11 +55,13 @@ use std::{
     time::{Duration, Instant},
 };
 use telemetry_events::AssistantKind;
+use terminal_view::{terminal_panel::TerminalPanel, TerminalView};
 use theme::ThemeSettings;
 use ui::{
+    popover_menu,
     prelude::*,
     utils::{DateTimeType, FormatDistance},
-    ButtonLike, Tab, TabBar, Tooltip,
+    ButtonLike, ContextMenu, Tab, TabBar, Tooltip,
 };
 use util::{paths::CONVERSATIONS_DIR, post_inc, ResultExt, TryFutureExt};
 use uuid::Uuid;
@@ -66,6 +71,8 @@ use workspace::{
     Save, Toast, ToggleZoom, Toolbar, Workspace,
 };
 
+const ATTACH_CONTEXT_TOAST_ID: usize = 0x6a77ac4;
+
 pub fn init(cx: &mut AppContext) {
     AssistantSettings::register(cx);
     cx.observe_new_views(
@@ -76,7 +83,12 @@ pub fn init(cx: &mut AppContext) {
                 })
                 .register_action(AssistantPanel::inline_assist)
                 .register_action(AssistantPanel::cancel_last_inline_assist)
-                .register_action(ConversationEditor::quote_selection);
+                .register_action(ConversationEditor::quote_selection)
+                .register_action(ConversationEditor::attach_file)
+                .register_action(ConversationEditor::attach_symbol)
+                .register_action(ConversationEditor::attach_diagnostics)
+                .register_action(ConversationEditor::attach_terminal_output)
+                .register_action(ConversationEditor::attach_search_results);
         },
     )
     .detach();
@@ -95,7 +107,9 @@ pub struct AssistantPanel {
     focus_handle: FocusHandle,
     toolbar: View<Toolbar>,
     completion_provider: Arc<dyn CompletionProvider>,
+    completion_providers: HashMap<ModelProvider, Arc<dyn CompletionProvider>>,
     api_key_editor: Option<View<Editor>>,
+    api_key_provider: ModelProvider,
     languages: Arc<LanguageRegistry>,
     fs: Arc<dyn Fs>,
     subscriptions: Vec<Subscription>,
@@ -122,19 +136,26 @@ impl AssistantPanel {
                 .await
                 .log_err()
                 .unwrap_or_default();
-            let (api_url, model_name) = cx.update(|cx| {
+            let (completion_provider, other_completion_providers) = cx.update(|cx| {
                 let settings = AssistantSettings::get_global(cx);
-                (
-                    settings.openai_api_url.clone(),
-                    settings.default_open_ai_model.full_name().to_string(),
-                )
+                let completion_provider =
+                    build_completion_provider(&ModelProvider::OpenAi, None, cx);
+                let other_completion_providers = settings
+                    .configured_providers()
+                    .into_iter()
+                    .filter(|provider| *provider != ModelProvider::OpenAi)
+                    .map(|provider| {
+                        let completion_provider = build_completion_provider(&provider, None, cx);
+                        async move { (provider, completion_provider.await) }
+                    })
+                    .collect::<Vec<_>>();
+                (completion_provider, other_completion_providers)
             })?;
-            let completion_provider = OpenAiCompletionProvider::new(
-                api_url,
-                model_name,
-                cx.background_executor().clone(),
-            )
-            .await;
+            let completion_provider = completion_provider.await;
+            let completion_providers = future::join_all(other_completion_providers)
+                .await
+                .into_iter()
+                .collect();
 
             // TODO: deserialize state.
             let workspace_handle = workspace.clone();
@@ -183,8 +204,10 @@ impl AssistantPanel {
                         zoomed: false,
                         focus_handle,
                         toolbar,
-                        completion_provider: Arc::new(completion_provider),
+                        completion_provider,
+                        completion_providers,
                         api_key_editor: None,
+                        api_key_provider: ModelProvider::OpenAi,
                         languages: workspace.app_state().languages.clone(),
                         fs: workspace.app_state().fs.clone(),
                         width: None,
@@ -209,7 +232,7 @@ impl AssistantPanel {
             .update(cx, |toolbar, cx| toolbar.focus_changed(true, cx));
         cx.notify();
         if self.focus_handle.is_focused(cx) {
-            if self.has_credentials() {
+            if self.has_credentials(cx) {
                 if let Some(editor) = self.active_editor() {
                     cx.focus_view(editor);
                 }
@@ -245,7 +268,7 @@ impl AssistantPanel {
         };
         let project = workspace.project().clone();
 
-        if assistant.update(cx, |assistant, _| assistant.has_credentials()) {
+        if assistant.update(cx, |assistant, cx| assistant.has_credentials(cx)) {
             assistant.update(cx, |assistant, cx| {
                 assistant.new_inline_assist(&active_editor, cx, &project)
             });
@@ -255,7 +278,7 @@ impl AssistantPanel {
                 assistant
                     .update(&mut cx, |assistant, cx| assistant.load_credentials(cx))?
                     .await;
-                if assistant.update(&mut cx, |assistant, _| assistant.has_credentials())? {
+                if assistant.update(&mut cx, |assistant, cx| assistant.has_credentials(cx))? {
                     assistant.update(&mut cx, |assistant, cx| {
                         assistant.new_inline_assist(&active_editor, cx, &project)
                     })?;
@@ -306,7 +329,7 @@ impl AssistantPanel {
         };
 
         let inline_assist_id = post_inc(&mut self.next_inline_assist_id);
-        let provider = self.completion_provider.clone();
+        let provider = self.completion_provider_for(&self.active_model(cx).provider());
 
         let codegen = cx.new_model(|cx| {
             Codegen::new(editor.read(cx).buffer().clone(), codegen_kind, provider, cx)
@@ -690,10 +713,8 @@ impl AssistantPanel {
             Task::ready(Ok(Vec::new()))
         };
 
-        let mut model = AssistantSettings::get_global(cx)
-            .default_open_ai_model
-            .clone();
-        let model_name = model.full_name();
+        let mut model = AssistantSettings::get_global(cx).default_model();
+        let model_name = model.tokenizer_model().to_string();
 
         let prompt = cx.background_executor().spawn(async move {
             let snippets = snippets.await?;
@@ -705,7 +726,7 @@ impl AssistantPanel {
                 buffer,
                 range,
                 snippets,
-                model_name,
+                &model_name,
                 project_name,
             )
         });
@@ -721,6 +742,8 @@ impl AssistantPanel {
             );
             model = conversation.model.clone();
         }
+        let provider = self.completion_provider_for(&model.provider());
+        codegen.update(cx, |codegen, _| codegen.set_provider(provider));
 
         cx.spawn(|_, mut cx| async move {
             // I Don't know if we want to return a ? here.
@@ -791,14 +814,16 @@ impl AssistantPanel {
         });
     }
 
-    fn build_api_key_editor(&mut self, cx: &mut WindowContext<'_>) {
-        self.api_key_editor = Some(build_api_key_editor(cx));
+    fn build_api_key_editor(&mut self, cx: &mut ViewContext<Self>) {
+        self.api_key_provider = self.active_model(cx).provider();
+        self.api_key_editor = Some(build_api_key_editor(&self.api_key_provider, cx));
     }
 
     fn new_conversation(&mut self, cx: &mut ViewContext<Self>) -> View<ConversationEditor> {
+        let provider = AssistantSettings::get_global(cx).default_model().provider();
         let editor = cx.new_view(|cx| {
             ConversationEditor::new(
-                self.completion_provider.clone(),
+                self.completion_provider_for(&provider),
                 self.languages.clone(),
                 self.fs.clone(),
                 self.workspace.clone(),
@@ -844,12 +869,34 @@ impl AssistantPanel {
 
     fn handle_conversation_editor_event(
         &mut self,
-        _: View<ConversationEditor>,
+        editor: View<ConversationEditor>,
         event: &ConversationEditorEvent,
         cx: &mut ViewContext<Self>,
     ) {
         match event {
             ConversationEditorEvent::TabContentChanged => cx.notify(),
+            ConversationEditorEvent::ModelSelected(model) => {
+                let model = model.clone();
+                let provider = self.completion_provider_for(&model.provider());
+                editor.update(cx, |editor, cx| {
+                    editor.conversation.update(cx, |conversation, cx| {
+                        conversation.set_model(model, provider, cx)
+                    })
+                });
+
+                let load_credentials = self.load_credentials(cx);
+                cx.spawn(|this, mut cx| async move {
+                    load_credentials.await;
+                    this.update(&mut cx, |this, cx| {
+                        if !this.has_credentials(cx) {
+                            this.build_api_key_editor(cx);
+                            this.focus_handle.focus(cx);
+                        }
+                        cx.notify();
+                    })
+                })
+                .detach_and_log_err(cx);
+            }
         }
     }
 
@@ -864,7 +911,7 @@ impl AssistantPanel {
                     api_key: api_key.clone(),
                 };
 
-                let completion_provider = self.completion_provider.clone();
+                let completion_provider = self.completion_provider_for(&self.api_key_provider);
                 cx.spawn(|this, mut cx| async move {
                     cx.update(|cx| completion_provider.save_credentials(cx, credential))?
                         .await;
@@ -883,7 +930,7 @@ impl AssistantPanel {
     }
 
     fn reset_credentials(&mut self, _: &ResetKey, cx: &mut ViewContext<Self>) {
-        let completion_provider = self.completion_provider.clone();
+        let completion_provider = self.completion_provider_for(&self.active_model(cx).provider());
         cx.spawn(|this, mut cx| async move {
             cx.update(|cx| completion_provider.delete_credentials(cx))?
                 .await;
@@ -1138,12 +1185,27 @@ impl AssistantPanel {
             .position(|editor| editor.read(cx).conversation.read(cx).path.as_deref() == Some(path))
     }
 
-    fn has_credentials(&mut self) -> bool {
-        self.completion_provider.has_credentials()
+    /// The model of the active conversation, or of new conversations when there's none.
+    fn active_model(&self, cx: &AppContext) -> AssistantModel {
+        self.active_editor()
+            .map(|editor| editor.read(cx).conversation.read(cx).model.clone())
+            .unwrap_or_else(|| AssistantSettings::get_global(cx).default_model())
+    }
+
+    fn completion_provider_for(&self, provider: &ModelProvider) -> Arc<dyn CompletionProvider> {
+        self.completion_providers
+            .get(provider)
+            .cloned()
+            .unwrap_or_else(|| self.completion_provider.clone())
+    }
+
+    fn has_credentials(&self, cx: &AppContext) -> bool {
+        self.completion_provider_for(&self.active_model(cx).provider())
+            .has_credentials()
     }
 
     fn load_credentials(&mut self, cx: &mut ViewContext<Self>) -> Task<()> {
-        let completion_provider = self.completion_provider.clone();
+        let completion_provider = self.completion_provider_for(&self.active_model(cx).provider());
         cx.spawn(|_, mut cx| async move {
             if let Some(retrieve_credentials) = cx
                 .update(|cx| completion_provider.retrieve_credentials(cx))
@@ -1155,33 +1217,55 @@ impl AssistantPanel {
     }
 }
 
-fn build_api_key_editor(cx: &mut WindowContext) -> View<Editor> {
+fn build_api_key_editor(provider: &ModelProvider, cx: &mut WindowContext) -> View<Editor> {
+    let placeholder = match provider {
+        ModelProvider::Anthropic => "sk-ant-REDACTED",
+        _ => "sk-000000000000000000000000000000000000000000000000",
+    };
     cx.new_view(|cx| {
         let mut editor = Editor::single_line(cx);
-        editor.set_placeholder_text("sk-000000000000000000000000000000000000000000000000", cx);
+        editor.set_placeholder_text(placeholder, cx);
         editor
     })
 }
 
+fn api_key_instructions(provider: &ModelProvider) -> Vec<String> {
+    match provider {
+        ModelProvider::Anthropic => vec![
+            "To use Anthropic's models, you need to add your Anthropic API key.".into(),
+            " - You can create an API key at: console.anthropic.com/settings/keys".into(),
+            " ".into(),
+            "Paste your Anthropic API key and press Enter to use the assistant:".into(),
+        ],
+        ModelProvider::OpenAiCompatible(name) => vec![
+            format!("{name} needs an API key."),
+            " ".into(),
+            format!("Paste the API key for {name} and press Enter to use the assistant:"),
+        ],
+        ModelProvider::OpenAi | ModelProvider::Ollama => vec![
+            "To use the assistant panel or inline assistant, you need to add your OpenAI API key."
+                .into(),
+            " - You can create an API key at: platform.openai.com/api-keys".into(),
+            " - Make sure your OpenAI account has credits".into(),
+            " - Having a subscription for another service like GitHub Copilot won't work.".into(),
+            " ".into(),
+            "Paste your OpenAI API key and press Enter to use the assistant:".into(),
+        ],
+    }
+}
+
 impl Render for AssistantPanel {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         if let Some(api_key_editor) = self.api_key_editor.clone() {
-            const INSTRUCTIONS: [&'static str; 6] = [
-                "To use the assistant panel or inline assistant, you need to add your OpenAI API key.",
-                " - You can create an API key at: platform.openai.com/api-keys",
-                " - Make sure your OpenAI account has credits",
-                " - Having a subscription for another service like GitHub Copilot won't work.",
-                " ",
-                "Paste your OpenAI API key and press Enter to use the assistant:"
-            ];
-
             v_flex()
                 .p_4()
                 .size_full()
                 .on_action(cx.listener(AssistantPanel::save_credentials))
                 .track_focus(&self.focus_handle)
                 .children(
-                    INSTRUCTIONS.map(|instruction| Label::new(instruction).size(LabelSize::Small)),
+                    api_key_instructions(&self.api_key_provider)
+                        .into_iter()
+                        .map(|instruction| Label::new(instruction).size(LabelSize::Small)),
                 )
                 .child(
                     h_flex()
@@ -1362,7 +1446,7 @@ impl Panel for AssistantPanel {
             cx.spawn(|this, mut cx| async move {
                 load_credentials.await;
                 this.update(&mut cx, |this, cx| {
-                    if !this.has_credentials() {
+                    if !this.has_credentials(cx) {
                         this.build_api_key_editor(cx);
                     } else if this.editors.is_empty() {
                         this.new_conversation(cx);
@@ -1396,6 +1480,7 @@ impl FocusableView for AssistantPanel {
 
 enum ConversationEvent {
     MessagesEdited,
+    ContextEdited,
     SummaryChanged,
     StreamedCompletion,
 }
@@ -1416,13 +1501,17 @@ struct Conversation {
     pending_summary: Task<Option<()>>,
     completion_count: usize,
     pending_completions: Vec<PendingCompletion>,
-    model: OpenAiModel,
+    model: AssistantModel,
     api_url: Option<String>,
     token_count: Option<usize>,
     max_token_count: usize,
     pending_token_count: Task<Option<()>>,
     pending_save: Task<Result<()>>,
     path: Option<PathBuf>,
+    context: Vec<ContextItem>,
+    /// Incremented whenever context is attached or removed, so that token counts are only
+    /// assigned to the context they were counted for.
+    context_version: usize,
     _subscriptions: Vec<Subscription>,
     completion_provider: Arc<dyn CompletionProvider>,
 }
@@ -1451,8 +1540,9 @@ impl Conversation {
         });
 
         let settings = AssistantSettings::get_global(cx);
-        let model = settings.default_open_ai_model.clone();
-        let api_url = settings.openai_api_url.clone();
+        let model = settings.default_model();
+        let api_url = settings.api_url(&model.provider());
+        let max_token_count = model.max_token_count(settings);
 
         let mut this = Self {
             id: Some(Uuid::new_v4().to_string()),
@@ -1464,13 +1554,15 @@ impl Conversation {
             completion_count: Default::default(),
             pending_completions: Default::default(),
             token_count: None,
-            max_token_count: tiktoken_rs::model::get_context_size(&model.full_name()),
+            max_token_count,
             pending_token_count: Task::ready(None),
-            api_url: Some(api_url),
+            api_url,
             model: model.clone(),
             _subscriptions: vec![cx.subscribe(&buffer, Self::handle_buffer_event)],
             pending_save: Task::ready(Ok(())),
             path: None,
+            context: Vec::new(),
+            context_version: 0,
             buffer,
             completion_provider,
         };
@@ -1513,6 +1605,7 @@ impl Conversation {
                 .unwrap_or_default(),
             model: self.model.clone(),
             api_url: self.api_url.clone(),
+            context: self.context.clone(),
         }
     }
 
@@ -1528,16 +1621,13 @@ impl Conversation {
         };
         let model = saved_conversation.model;
         let api_url = saved_conversation.api_url;
-        let completion_provider: Arc<dyn CompletionProvider> = Arc::new(
-            OpenAiCompletionProvider::new(
-                api_url
-                    .clone()
-                    .unwrap_or_else(|| OPEN_AI_API_URL.to_string()),
-                model.full_name().into(),
-                cx.background_executor().clone(),
+        let (completion_provider, max_token_count) = cx.update(|cx| {
+            (
+                build_completion_provider(&model.provider(), api_url.clone(), cx),
+                model.max_token_count(AssistantSettings::get_global(cx)),
             )
-            .await,
-        );
+        })?;
+        let completion_provider = completion_provider.await;
         cx.update(|cx| completion_provider.retrieve_credentials(cx))?
             .await;
 
@@ -1583,13 +1673,15 @@ impl Conversation {
                 completion_count: Default::default(),
                 pending_completions: Default::default(),
                 token_count: None,
-                max_token_count: tiktoken_rs::model::get_context_size(&model.full_name()),
+                max_token_count,
                 pending_token_count: Task::ready(None),
                 api_url,
                 model,
                 _subscriptions: vec![cx.subscribe(&buffer, Self::handle_buffer_event)],
                 pending_save: Task::ready(Ok(())),
                 path: Some(path),
+                context: saved_conversation.context,
+                context_version: 0,
                 buffer,
                 completion_provider,
             };
@@ -1614,10 +1706,22 @@ impl Conversation {
     }
 
     fn count_remaining_tokens(&mut self, cx: &mut ModelContext<Self>) {
-        let messages = self
-            .messages(cx)
+        let context =
+            context_prompt(&self.context).map(|prompt| tiktoken_rs::ChatCompletionRequestMessage {
+                role: "system".into(),
+                content: Some(prompt),
+                name: None,
+                function_call: None,
+            });
+        let context_version = self.context_version;
+        let context_texts = self
+            .context
+            .iter()
+            .map(|item| item.text.clone())
+            .collect::<Vec<_>>();
+        let messages = context
             .into_iter()
-            .filter_map(|message| {
+            .chain(self.messages(cx).into_iter().filter_map(|message| {
                 Some(tiktoken_rs::ChatCompletionRequestMessage {
                     role: match message.role {
                         Role::User => "user".into(),
@@ -1633,7 +1737,7 @@ impl Conversation {
                     name: None,
                     function_call: None,
                 })
-            })
+            }))
             .collect::<Vec<_>>();
         let model = self.model.clone();
         self.pending_token_count = cx.spawn(|this, mut cx| {
@@ -1641,17 +1745,34 @@ impl Conversation {
                 cx.background_executor()
                     .timer(Duration::from_millis(200))
                     .await;
-                let token_count = cx
+                let (token_count, context_token_counts) = cx
                     .background_executor()
                     .spawn(async move {
-                        tiktoken_rs::num_tokens_from_messages(&model.full_name(), &messages)
+                        let token_count = tiktoken_rs::num_tokens_from_messages(
+                            model.tokenizer_model(),
+                            &messages,
+                        )?;
+                        let bpe = tiktoken_rs::get_bpe_from_model(model.tokenizer_model())?;
+                        let context_token_counts = context_texts
+                            .iter()
+                            .map(|text| bpe.encode_with_special_tokens(text).len())
+                            .collect::<Vec<_>>();
+                        anyhow::Ok((token_count, context_token_counts))
                     })
                     .await?;
 
                 this.update(&mut cx, |this, cx| {
-                    this.max_token_count =
-                        tiktoken_rs::model::get_context_size(&this.model.full_name());
+                    this.max_token_count = this
+                        .model
+                        .max_token_count(AssistantSettings::get_global(cx));
                     this.token_count = Some(token_count);
+                    // The context may have changed while the tokens were being counted.
+                    if this.context_version == context_version {
+                        for (item, token_count) in this.context.iter_mut().zip(context_token_counts)
+                        {
+                            item.token_count = Some(token_count);
+                        }
+                    }
                     cx.notify()
                 })?;
                 anyhow::Ok(())
@@ -1664,10 +1785,73 @@ impl Conversation {
         Some(self.max_token_count as isize - self.token_count? as isize)
     }
 
-    fn set_model(&mut self, model: OpenAiModel, cx: &mut ModelContext<Self>) {
+    fn set_model(
+        &mut self,
+        model: AssistantModel,
+        completion_provider: Arc<dyn CompletionProvider>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.api_url = AssistantSettings::get_global(cx).api_url(&model.provider());
         self.model = model;
+        self.completion_provider = completion_provider;
+        self.count_remaining_tokens(cx);
+        cx.notify();
+    }
+
+    /// Counts the tokens of context that is about to be attached to the conversation.
+    fn count_context_tokens(
+        &self,
+        mut items: Vec<ContextItem>,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<ContextItem>>> {
+        let model = self.model.clone();
+        cx.background_executor().spawn(async move {
+            let bpe = tiktoken_rs::get_bpe_from_model(model.tokenizer_model())?;
+            for item in &mut items {
+                item.token_count = Some(bpe.encode_with_special_tokens(&item.text).len());
+            }
+            Ok(items)
+        })
+    }
+
+    /// Attaches context to the conversation, unless its counted tokens exceed the tokens
+    /// that remain for the model.
+    fn add_context(
+        &mut self,
+        items: impl IntoIterator<Item = ContextItem>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let items = items.into_iter().collect::<Vec<_>>();
+        let token_count = items
+            .iter()
+            .filter_map(|item| item.token_count)
+            .sum::<usize>();
+        let remaining_tokens = self
+            .max_token_count
+            .saturating_sub(self.token_count.unwrap_or(0));
+        if token_count > remaining_tokens {
+            return Err(anyhow!(
+                "it uses {token_count} tokens, but only {remaining_tokens} of the model's {} remain",
+                self.max_token_count
+            ));
+        }
+
+        self.context.extend(items);
+        self.context_version += 1;
         self.count_remaining_tokens(cx);
+        cx.emit(ConversationEvent::ContextEdited);
         cx.notify();
+        Ok(())
+    }
+
+    fn remove_context(&mut self, ix: usize, cx: &mut ModelContext<Self>) {
+        if ix < self.context.len() {
+            self.context.remove(ix);
+            self.context_version += 1;
+            self.count_remaining_tokens(cx);
+            cx.emit(ConversationEvent::ContextEdited);
+            cx.notify();
+        }
     }
 
     fn assist(
@@ -1718,19 +1902,38 @@ impl Conversation {
                 return Default::default();
             }
 
+            let context = context_prompt(&self.context).map(|prompt| RequestMessage {
+                role: Role::System,
+                content: prompt,
+            });
             let request: Box<dyn CompletionRequest> = Box::new(OpenAiRequest {
                 model: self.model.full_name().to_string(),
-                messages: self
-                    .messages(cx)
-                    .filter(|message| matches!(message.status, MessageStatus::Done))
-                    .map(|message| message.to_open_ai_message(self.buffer.read(cx)))
+                messages: context
+                    .into_iter()
+                    .chain(
+                        self.messages(cx)
+                            .filter(|message| matches!(message.status, MessageStatus::Done))
+                            .map(|message| message.to_open_ai_message(self.buffer.read(cx))),
+                    )
                     .collect(),
                 stream: true,
                 stop: vec![],
                 temperature: 1.0,
             });
 
-            let stream = self.completion_provider.complete(request);
+            // Attached context may push the conversation over the model's limit, for example
+            // after switching to a model with a smaller context window.
+            let stream = match self.remaining_tokens() {
+                Some(remaining_tokens) if remaining_tokens < 0 => {
+                    let error = anyhow!(
+                        "the conversation is {} tokens over the model's limit of {}",
+                        -remaining_tokens,
+                        self.max_token_count
+                    );
+                    future::ready(Err(error)).boxed()
+                }
+                _ => self.completion_provider.complete(request),
+            };
             let assistant_message = self
                 .insert_message_after(last_message_id, Role::Assistant, MessageStatus::Pending, cx)
                 .unwrap();
@@ -2076,6 +2279,22 @@ impl Conversation {
         result
     }
 
+    /// Returns the text of the last message the user wrote.
+    fn last_user_message(&self, cx: &AppContext) -> Option<String> {
+        let buffer = self.buffer.read(cx);
+        self.messages(cx)
+            .filter(|message| message.role == Role::User)
+            .map(|message| {
+                buffer
+                    .text_for_range(message.offset_range)
+                    .collect::<String>()
+                    .trim()
+                    .to_string()
+            })
+            .filter(|text| !text.is_empty())
+            .last()
+    }
+
     fn messages<'a>(&'a self, cx: &'a AppContext) -> impl 'a + Iterator<Item = Message> {
         let buffer = self.buffer.read(cx);
         let mut message_anchors = self.message_anchors.iter().enumerate().peekable();
@@ -2176,6 +2395,7 @@ struct PendingCompletion {
 
 enum ConversationEditorEvent {
     TabContentChanged,
+    ModelSelected(AssistantModel),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -2322,6 +2542,11 @@ impl ConversationEditor {
                     conversation.save(Some(Duration::from_millis(500)), self.fs.clone(), cx);
                 });
             }
+            ConversationEvent::ContextEdited => {
+                self.conversation.update(cx, |conversation, cx| {
+                    conversation.save(Some(Duration::from_millis(500)), self.fs.clone(), cx);
+                });
+            }
             ConversationEvent::SummaryChanged => {
                 cx.emit(ConversationEditorEvent::TabContentChanged);
                 self.conversation.update(cx, |conversation, cx| {
@@ -2539,6 +2764,154 @@ impl ConversationEditor {
         }
     }
 
+    fn attach_file(workspace: &mut Workspace, _: &AttachFile, cx: &mut ViewContext<Workspace>) {
+        let buffer = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton());
+        let context = match buffer {
+            Some(buffer) => ContextItem::file(&buffer, cx).map(|item| vec![item]),
+            None => Err(anyhow!("open a file to attach it to the conversation")),
+        };
+        Self::attach_context(workspace, future::ready(context), cx);
+    }
+
+    fn attach_symbol(workspace: &mut Workspace, _: &AttachSymbol, cx: &mut ViewContext<Workspace>) {
+        let name = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+            .and_then(|editor| {
+                let editor = editor.read(cx);
+                let selection = editor.selections.newest::<usize>(cx);
+                let buffer = editor.buffer().read(cx).snapshot(cx);
+                let range = if selection.is_empty() {
+                    buffer.surrounding_word(selection.head()).0
+                } else {
+                    selection.range()
+                };
+                let name = buffer.text_for_range(range).collect::<String>();
+                let name = name.trim();
+                (!name.is_empty()).then(|| name.to_string())
+            });
+        let Some(name) = name else {
+            let error = anyhow!("place the cursor on a symbol to attach it to the conversation");
+            Self::attach_context(workspace, future::ready(Err(error)), cx);
+            return;
+        };
+
+        let symbol = ContextItem::symbol(workspace.project().clone(), name, cx);
+        Self::attach_context(workspace, async move { Ok(vec![symbol.await?]) }, cx);
+    }
+
+    fn attach_diagnostics(
+        workspace: &mut Workspace,
+        _: &AttachDiagnostics,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let diagnostics = ContextItem::diagnostics(workspace.project().clone(), cx);
+        Self::attach_context(workspace, async move { Ok(vec![diagnostics.await?]) }, cx);
+    }
+
+    fn attach_terminal_output(
+        workspace: &mut Workspace,
+        _: &AttachTerminalOutput,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let terminal = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<TerminalView>())
+            .or_else(|| {
+                workspace
+                    .panel::<TerminalPanel>(cx)?
+                    .read(cx)
+                    .active_terminal(cx)
+            });
+        let context = match terminal {
+            Some(terminal) => Ok(vec![ContextItem::terminal(&terminal, cx)]),
+            None => Err(anyhow!(
+                "open a terminal to attach its output to the conversation"
+            )),
+        };
+        Self::attach_context(workspace, future::ready(context), cx);
+    }
+
+    fn attach_search_results(
+        workspace: &mut Workspace,
+        _: &AttachSearchResults,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(panel) = workspace.panel::<AssistantPanel>(cx) else {
+            return;
+        };
+        let panel = panel.read(cx);
+        let semantic_index = panel.semantic_index.clone();
+        let query = panel
+            .active_editor()
+            .and_then(|editor| editor.read(cx).conversation.read(cx).last_user_message(cx));
+        let (Some(semantic_index), Some(query)) = (semantic_index, query) else {
+            let error = anyhow!("write a message to search the project for code relevant to it");
+            Self::attach_context(workspace, future::ready(Err(error)), cx);
+            return;
+        };
+
+        let results =
+            ContextItem::search_results(semantic_index, workspace.project().clone(), query, cx);
+        Self::attach_context(workspace, results, cx);
+    }
+
+    /// Adds the context to the active conversation once it has loaded, creating a
+    /// conversation if there is none.
+    fn attach_context(
+        workspace: &mut Workspace,
+        context: impl 'static + Future<Output = Result<Vec<ContextItem>>>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(panel) = workspace.panel::<AssistantPanel>(cx) else {
+            return;
+        };
+
+        // Activate the panel
+        if !panel.focus_handle(cx).contains_focused(cx) {
+            workspace.toggle_panel_focus::<AssistantPanel>(cx);
+        }
+
+        let conversation_editor = panel.update(cx, |panel, cx| {
+            panel
+                .active_editor()
+                .cloned()
+                .unwrap_or_else(|| panel.new_conversation(cx))
+        });
+        cx.spawn(|workspace, mut cx| async move {
+            let result = async {
+                let items = context.await?;
+                let items = conversation_editor
+                    .update(&mut cx, |editor, cx| {
+                        editor.conversation.read(cx).count_context_tokens(items, cx)
+                    })?
+                    .await?;
+                conversation_editor.update(&mut cx, |editor, cx| {
+                    editor
+                        .conversation
+                        .update(cx, |conversation, cx| conversation.add_context(items, cx))
+                })?
+            }
+            .await;
+            if let Err(error) = result {
+                workspace.update(&mut cx, |workspace, cx| {
+                    workspace.show_toast(
+                        Toast::new(
+                            ATTACH_CONTEXT_TOAST_ID,
+                            format!("Couldn't attach context: {error}"),
+                        ),
+                        cx,
+                    )
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn copy(&mut self, _: &editor::actions::Copy, cx: &mut ViewContext<Self>) {
         let editor = self.editor.read(cx);
         let conversation = self.conversation.read(cx);
@@ -2591,13 +2964,6 @@ impl ConversationEditor {
         });
     }
 
-    fn cycle_model(&mut self, cx: &mut ViewContext<Self>) {
-        self.conversation.update(cx, |conversation, cx| {
-            let new_model = conversation.model.cycle();
-            conversation.set_model(new_model, cx);
-        });
-    }
-
     fn title(&self, cx: &AppContext) -> String {
         self.conversation
             .read(cx)
@@ -2608,13 +2974,95 @@ impl ConversationEditor {
     }
 
     fn render_current_model(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        Button::new(
-            "current_model",
-            self.conversation.read(cx).model.short_name(),
+        let this = cx.view().clone();
+        popover_menu("current_model")
+            .trigger(
+                Button::new(
+                    "current_model_button",
+                    self.conversation.read(cx).model.short_name().to_string(),
+                )
+                .style(ButtonStyle::Filled)
+                .tooltip(move |cx| Tooltip::text("Change Model", cx)),
+            )
+            .menu(move |cx| {
+                let models = AssistantSettings::get_global(cx).available_models();
+                let this = this.clone();
+                Some(ContextMenu::build(cx, move |mut menu, cx| {
+                    let mut provider = None;
+                    for model in models {
+                        if provider.as_ref() != Some(&model.provider()) {
+                            menu = menu.header(model.provider().display_name().to_string());
+                            provider = Some(model.provider());
+                        }
+                        menu = menu.entry(
+                            model.short_name().to_string(),
+                            None,
+                            cx.handler_for(&this, move |_, cx| {
+                                cx.emit(ConversationEditorEvent::ModelSelected(model.clone()))
+                            }),
+                        );
+                    }
+                    menu
+                }))
+            })
+    }
+
+    fn render_attach_context_button(&self) -> impl IntoElement {
+        popover_menu("attach_context")
+            .trigger(
+                IconButton::new("attach_context_button", IconName::Plus)
+                    .tooltip(|cx| Tooltip::text("Attach Context", cx)),
+            )
+            .menu(|cx| {
+                Some(ContextMenu::build(cx, |menu, _| {
+                    menu.action("Active File", Box::new(AttachFile))
+                        .action("Symbol Under Cursor", Box::new(AttachSymbol))
+                        .action("Diagnostics", Box::new(AttachDiagnostics))
+                        .action("Terminal Output", Box::new(AttachTerminalOutput))
+                        .action("Search Results", Box::new(AttachSearchResults))
+                }))
+            })
+    }
+
+    fn render_context(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let context = &self.conversation.read(cx).context;
+        if context.is_empty() {
+            return None;
+        }
+
+        Some(
+            h_flex()
+                .flex_wrap()
+                .gap_1()
+                .px_4()
+                .py_1()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .children(context.iter().enumerate().map(|(ix, item)| {
+                    let label = match item.token_count {
+                        Some(token_count) => format!("{} ({token_count})", item.label),
+                        None => item.label.clone(),
+                    };
+                    h_flex()
+                        .id(("context", ix))
+                        .gap_1()
+                        .pl_1()
+                        .rounded_md()
+                        .bg(cx.theme().colors().element_background)
+                        .child(Icon::new(item.kind.icon()).size(IconSize::XSmall))
+                        .child(Label::new(label).size(LabelSize::Small))
+                        .child(
+                            IconButton::new(("remove_context", ix), IconName::Close)
+                                .icon_size(IconSize::XSmall)
+                                .tooltip(|cx| Tooltip::text("Remove Context", cx))
+                                .on_click(cx.listener(move |this, _, cx| {
+                                    this.conversation.update(cx, |conversation, cx| {
+                                        conversation.remove_context(ix, cx)
+                                    })
+                                })),
+                        )
+                })),
         )
-        .style(ButtonStyle::Filled)
-        .tooltip(move |cx| Tooltip::text("Change Model", cx))
-        .on_click(cx.listener(|this, _, cx| this.cycle_model(cx)))
     }
 
     fn render_remaining_tokens(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
@@ -2645,11 +3093,11 @@ impl Render for ConversationEditor {
             .size_full()
             .relative()
             .child(
-                div()
+                v_flex()
                     .size_full()
-                    .pl_4()
                     .bg(cx.theme().colors().editor_background)
-                    .child(self.editor.clone()),
+                    .child(div().flex_1().pl_4().child(self.editor.clone()))
+                    .children(self.render_context(cx)),
             )
             .child(
                 h_flex()
@@ -2657,6 +3105,7 @@ impl Render for ConversationEditor {
                     .gap_1()
                     .top_3()
                     .right_5()
+                    .child(self.render_attach_context_button())
                     .child(self.render_current_model(cx))
                     .children(self.render_remaining_tokens(cx)),
             )
@@ -3241,7 +3690,7 @@ fn merge_ranges(ranges: &mut Vec<Range<Anchor>>, buffer: &MultiBufferSnapshot) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::MessageId;
+    use crate::{context::ContextKind, MessageId};
     use ai::test::FakeCompletionProvider;
     use gpui::{AppContext, TestAppContext};
     use settings::SettingsStore;
@@ -3626,6 +4075,104 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_context(cx: &mut TestAppContext) {
+        let settings_store = cx.update(SettingsStore::test);
+        cx.set_global(settings_store);
+        cx.update(init);
+        let registry = Arc::new(LanguageRegistry::test());
+        let completion_provider = Arc::new(FakeCompletionProvider::new());
+        let conversation =
+            cx.new_model(|cx| Conversation::new(registry.clone(), cx, completion_provider));
+        conversation
+            .update(cx, |conversation, cx| {
+                conversation.add_context(
+                    [
+                        ContextItem::new(
+                            ContextKind::File,
+                            "src/main.rs".into(),
+                            "```rust\nfn main() {}\n```".into(),
+                        ),
+                        ContextItem::new(
+                            ContextKind::Terminal,
+                            "cargo test".into(),
+                            "```\ntest result: ok\n```".into(),
+                        ),
+                    ],
+                    cx,
+                )
+            })
+            .unwrap();
+        conversation.update(cx, |conversation, cx| conversation.remove_context(0, cx));
+        assert_eq!(context_labels(&conversation, cx), ["cargo test"]);
+
+        // Token counts are assigned to the context they were counted for.
+        cx.executor().advance_clock(Duration::from_millis(200));
+        cx.executor().run_until_parked();
+        let tokenizer_model = conversation.read_with(cx, |conversation, _| {
+            conversation.model.tokenizer_model().to_string()
+        });
+        let expected_token_count = tiktoken_rs::get_bpe_from_model(&tokenizer_model)
+            .unwrap()
+            .encode_with_special_tokens("```\ntest result: ok\n```")
+            .len();
+        assert_eq!(
+            conversation.read_with(cx, |conversation, _| conversation.context[0].token_count),
+            Some(expected_token_count)
+        );
+
+        // Context that doesn't fit in the tokens that remain for the model is refused.
+        let max_token_count =
+            conversation.read_with(cx, |conversation, _| conversation.max_token_count);
+        let result = conversation.update(cx, |conversation, cx| {
+            conversation.add_context(
+                [ContextItem {
+                    token_count: Some(max_token_count),
+                    ..ContextItem::new(
+                        ContextKind::File,
+                        "Cargo.lock".into(),
+                        "# This file is automatically @generated by Cargo.".into(),
+                    )
+                }],
+                cx,
+            )
+        });
+        assert!(result.is_err());
+        assert_eq!(context_labels(&conversation, cx), ["cargo test"]);
+
+        let buffer = conversation.read_with(cx, |conversation, _| conversation.buffer.clone());
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, " why did it pass?\n")], None, cx)
+        });
+        assert_eq!(
+            conversation.read_with(cx, |conversation, cx| conversation.last_user_message(cx)),
+            Some("why did it pass?".to_string())
+        );
+
+        let deserialized_conversation = Conversation::deserialize(
+            conversation.read_with(cx, |conversation, cx| conversation.serialize(cx)),
+            Default::default(),
+            registry.clone(),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            context_labels(&deserialized_conversation, cx),
+            ["cargo test"]
+        );
+    }
+
+    fn context_labels(conversation: &Model<Conversation>, cx: &TestAppContext) -> Vec<String> {
+        conversation.read_with(cx, |conversation, _| {
+            conversation
+                .context
+                .iter()
+                .map(|item| item.label.clone())
+                .collect()
+        })
+    }
+
     fn messages(
         conversation: &Model<Conversation>,
         cx: &AppContext,
@@ -3651,9 +4198,7 @@ fn report_assistant_event(
     let client = workspace.read(cx).project().read(cx).client();
     let telemetry = client.telemetry();
 
-    let model = AssistantSettings::get_global(cx)
-        .default_open_ai_model
-        .clone();
+    let model = AssistantSettings::get_global(cx).default_model();
 
     telemetry.report_assistant_event(conversation_id, assistant_kind, model.full_name())
 }
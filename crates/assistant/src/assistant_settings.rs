@@ -1,10 +1,20 @@
+use ai::providers::{
+    anthropic::{self, ANTHROPIC_API_URL},
+    ollama::OLLAMA_API_URL,
+};
 use anyhow;
 use gpui::Pixels;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
 
-#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+/// The context size assumed for models running locally when the settings don't give one.
+const DEFAULT_LOCAL_MAX_TOKENS: usize = 4096;
+
+/// The model used for counting the tokens of models that aren't OpenAI's.
+const APPROXIMATE_TOKENIZER_MODEL: &str = "gpt-4";
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum OpenAiModel {
     #[serde(rename = "gpt-3.5-turbo-0613")]
     ThreePointFiveTurbo,
@@ -31,15 +41,175 @@ impl OpenAiModel {
         }
     }
 
-    pub fn cycle(&self) -> Self {
+    pub fn all() -> [Self; 3] {
+        [
+            OpenAiModel::ThreePointFiveTurbo,
+            OpenAiModel::Four,
+            OpenAiModel::FourTurbo,
+        ]
+    }
+}
+
+/// A service that completes conversations.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ModelProvider {
+    OpenAi,
+    Anthropic,
+    Ollama,
+    /// A server implementing OpenAI's API, identified by its name in the settings.
+    OpenAiCompatible(String),
+}
+
+impl ModelProvider {
+    fn from_id(id: &str) -> Self {
+        match id {
+            "openai" => ModelProvider::OpenAi,
+            "anthropic" => ModelProvider::Anthropic,
+            "ollama" => ModelProvider::Ollama,
+            name => ModelProvider::OpenAiCompatible(name.to_string()),
+        }
+    }
+
+    fn id(&self) -> &str {
         match self {
-            OpenAiModel::ThreePointFiveTurbo => OpenAiModel::Four,
-            OpenAiModel::Four => OpenAiModel::FourTurbo,
-            OpenAiModel::FourTurbo => OpenAiModel::ThreePointFiveTurbo,
+            ModelProvider::OpenAi => "openai",
+            ModelProvider::Anthropic => "anthropic",
+            ModelProvider::Ollama => "ollama",
+            ModelProvider::OpenAiCompatible(name) => name,
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            ModelProvider::OpenAi => "OpenAI",
+            ModelProvider::Anthropic => "Anthropic",
+            ModelProvider::Ollama => "Ollama",
+            ModelProvider::OpenAiCompatible(name) => name,
         }
     }
 }
 
+/// A model of any provider. OpenAI's models are written by name, for compatibility with
+/// saved conversations, and other models along with their provider:
+///
+/// ```json
+/// { "provider": "anthropic", "model": "claude-3-opus-20240229" }
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum AssistantModel {
+    OpenAi(OpenAiModel),
+    Other { provider: String, model: String },
+}
+
+impl AssistantModel {
+    pub fn new(provider: &ModelProvider, model: &str) -> Self {
+        AssistantModel::Other {
+            provider: provider.id().to_string(),
+            model: model.to_string(),
+        }
+    }
+
+    pub fn provider(&self) -> ModelProvider {
+        match self {
+            AssistantModel::OpenAi(_) => ModelProvider::OpenAi,
+            AssistantModel::Other { provider, .. } => ModelProvider::from_id(provider),
+        }
+    }
+
+    pub fn full_name(&self) -> &str {
+        match self {
+            AssistantModel::OpenAi(model) => model.full_name(),
+            AssistantModel::Other { model, .. } => model,
+        }
+    }
+
+    pub fn short_name(&self) -> &str {
+        match self {
+            AssistantModel::OpenAi(model) => model.short_name(),
+            AssistantModel::Other { model, .. } => model,
+        }
+    }
+
+    /// The name of the model whose tokenizer counts this model's tokens.
+    pub fn tokenizer_model(&self) -> &str {
+        match self {
+            AssistantModel::OpenAi(model) => model.full_name(),
+            AssistantModel::Other { .. } => APPROXIMATE_TOKENIZER_MODEL,
+        }
+    }
+
+    pub fn max_token_count(&self, settings: &AssistantSettings) -> usize {
+        match self.provider() {
+            ModelProvider::OpenAi => tiktoken_rs::model::get_context_size(self.full_name()),
+            ModelProvider::Anthropic => anthropic::context_size(self.full_name()),
+            ModelProvider::Ollama => settings
+                .ollama
+                .as_ref()
+                .and_then(|ollama| ollama.max_tokens)
+                .unwrap_or(DEFAULT_LOCAL_MAX_TOKENS),
+            ModelProvider::OpenAiCompatible(name) => settings
+                .openai_compatible(&name)
+                .and_then(|server| server.max_tokens)
+                .unwrap_or(DEFAULT_LOCAL_MAX_TOKENS),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct AnthropicSettings {
+    /// Anthropic API base URL.
+    ///
+    /// Default: https://api.anthropic.com/v1
+    pub api_url: Option<String>,
+    /// The models to offer in the model selector.
+    ///
+    /// Default: the Claude 3 models
+    pub models: Option<Vec<String>>,
+}
+
+impl AnthropicSettings {
+    pub fn models(&self) -> Vec<String> {
+        self.models.clone().unwrap_or_else(|| {
+            vec![
+                "claude-3-opus-20240229".into(),
+                "claude-3-sonnet-20240229".into(),
+                "claude-3-haiku-20240307".into(),
+            ]
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct OllamaSettings {
+    /// Ollama API base URL.
+    ///
+    /// Default: http://localhost:11434/api
+    pub api_url: Option<String>,
+    /// The models to offer in the model selector, which must have been pulled into Ollama.
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// The context size of the models.
+    ///
+    /// Default: 4096
+    pub max_tokens: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct OpenAiCompatibleSettings {
+    /// The name of the server, shown in the model selector.
+    pub name: String,
+    /// The base URL of the server's API, under which `/chat/completions` is found.
+    pub api_url: String,
+    /// The models to offer in the model selector.
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// The context size of the models.
+    ///
+    /// Default: 4096
+    pub max_tokens: Option<usize>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AssistantDockPosition {
@@ -56,6 +226,104 @@ pub struct AssistantSettings {
     pub default_height: Pixels,
     pub default_open_ai_model: OpenAiModel,
     pub openai_api_url: String,
+    pub default_model: Option<AssistantModel>,
+    pub anthropic: Option<AnthropicSettings>,
+    pub ollama: Option<OllamaSettings>,
+    #[serde(default)]
+    pub openai_compatible: Vec<OpenAiCompatibleSettings>,
+}
+
+impl AssistantSettings {
+    /// The model of new conversations, which is the default OpenAI model unless another
+    /// model of a configured provider was chosen.
+    pub fn default_model(&self) -> AssistantModel {
+        match &self.default_model {
+            Some(model) if self.configured_providers().contains(&model.provider()) => model.clone(),
+            _ => AssistantModel::OpenAi(self.default_open_ai_model.clone()),
+        }
+    }
+
+    /// OpenAI, followed by the providers that have settings.
+    pub fn configured_providers(&self) -> Vec<ModelProvider> {
+        let mut providers = vec![ModelProvider::OpenAi];
+        if self.anthropic.is_some() {
+            providers.push(ModelProvider::Anthropic);
+        }
+        if self.ollama.is_some() {
+            providers.push(ModelProvider::Ollama);
+        }
+        providers.extend(
+            self.openai_compatible
+                .iter()
+                .map(|server| ModelProvider::OpenAiCompatible(server.name.clone())),
+        );
+        providers
+    }
+
+    /// The models to offer in the model selector, grouped by provider.
+    pub fn available_models(&self) -> Vec<AssistantModel> {
+        let mut models = OpenAiModel::all()
+            .into_iter()
+            .map(AssistantModel::OpenAi)
+            .collect::<Vec<_>>();
+        for provider in self.configured_providers() {
+            let names = match &provider {
+                ModelProvider::OpenAi => Vec::new(),
+                ModelProvider::Anthropic => self
+                    .anthropic
+                    .as_ref()
+                    .map(AnthropicSettings::models)
+                    .unwrap_or_default(),
+                ModelProvider::Ollama => self
+                    .ollama
+                    .as_ref()
+                    .map(|ollama| ollama.models.clone())
+                    .unwrap_or_default(),
+                ModelProvider::OpenAiCompatible(name) => self
+                    .openai_compatible(name)
+                    .map(|server| server.models.clone())
+                    .unwrap_or_default(),
+            };
+            models.extend(
+                names
+                    .iter()
+                    .map(|name| AssistantModel::new(&provider, name)),
+            );
+        }
+
+        let default_model = self.default_model();
+        if !models.contains(&default_model) {
+            models.push(default_model);
+        }
+        models
+    }
+
+    pub fn api_url(&self, provider: &ModelProvider) -> Option<String> {
+        match provider {
+            ModelProvider::OpenAi => Some(self.openai_api_url.clone()),
+            ModelProvider::Anthropic => Some(
+                self.anthropic
+                    .as_ref()
+                    .and_then(|anthropic| anthropic.api_url.clone())
+                    .unwrap_or_else(|| ANTHROPIC_API_URL.to_string()),
+            ),
+            ModelProvider::Ollama => Some(
+                self.ollama
+                    .as_ref()
+                    .and_then(|ollama| ollama.api_url.clone())
+                    .unwrap_or_else(|| OLLAMA_API_URL.to_string()),
+            ),
+            ModelProvider::OpenAiCompatible(name) => self
+                .openai_compatible(name)
+                .map(|server| server.api_url.clone()),
+        }
+    }
+
+    fn openai_compatible(&self, name: &str) -> Option<&OpenAiCompatibleSettings> {
+        self.openai_compatible
+            .iter()
+            .find(|server| server.name == name)
+    }
 }
 
 /// Assistant panel settings
@@ -85,6 +353,17 @@ pub struct AssistantSettingsContent {
     ///
     /// Default: https://api.openai.com/v1
     pub openai_api_url: Option<String>,
+    /// The default model to use when starting new conversations, if it isn't one of
+    /// OpenAI's.
+    pub default_model: Option<AssistantModel>,
+    /// Settings for Anthropic's models, which are offered when present.
+    pub anthropic: Option<AnthropicSettings>,
+    /// Settings for models running in Ollama, which are offered when present.
+    pub ollama: Option<OllamaSettings>,
+    /// Servers implementing OpenAI's API whose models should be offered.
+    ///
+    /// Default: []
+    pub openai_compatible: Option<Vec<OpenAiCompatibleSettings>>,
 }
 
 impl Settings for AssistantSettings {
@@ -71,6 +71,10 @@ impl Codegen {
         }
     }
 
+    pub fn set_provider(&mut self, provider: Arc<dyn CompletionProvider>) {
+        self.provider = provider;
+    }
+
     pub fn range(&self) -> Range<Anchor> {
         match &self.kind {
             CodegenKind::Transform { range } => range.clone(),
@@ -0,0 +1,63 @@
+use crate::assistant_settings::{AssistantModel, AssistantSettings, ModelProvider};
+use ai::{
+    completion::CompletionProvider,
+    providers::{
+        anthropic::AnthropicCompletionProvider,
+        ollama::OllamaCompletionProvider,
+        open_ai::{OpenAiCompletionProvider, OPEN_AI_API_URL},
+        open_ai_compatible::OpenAiCompatibleCompletionProvider,
+    },
+};
+use futures::{future::BoxFuture, FutureExt};
+use gpui::AppContext;
+use settings::Settings;
+use std::sync::Arc;
+
+/// Builds the completion provider for one of the configured providers. It talks to
+/// `api_url` when one is given, and to the URL in the settings otherwise.
+pub(crate) fn build_completion_provider(
+    provider: &ModelProvider,
+    api_url: Option<String>,
+    cx: &AppContext,
+) -> BoxFuture<'static, Arc<dyn CompletionProvider>> {
+    let settings = AssistantSettings::get_global(cx);
+    let api_url = api_url
+        .or_else(|| settings.api_url(provider))
+        .unwrap_or_else(|| OPEN_AI_API_URL.to_string());
+    let model = match provider {
+        ModelProvider::OpenAi => AssistantModel::OpenAi(settings.default_open_ai_model.clone()),
+        _ => settings
+            .available_models()
+            .into_iter()
+            .find(|model| model.provider() == *provider)
+            .unwrap_or_else(|| AssistantModel::new(provider, "")),
+    };
+    let model_name = model.full_name().to_string();
+    let capacity = model.max_token_count(settings);
+    let executor = cx.background_executor().clone();
+
+    match provider {
+        ModelProvider::OpenAi => async move {
+            Arc::new(OpenAiCompletionProvider::new(api_url, model_name, executor).await)
+                as Arc<dyn CompletionProvider>
+        }
+        .boxed(),
+        ModelProvider::Anthropic => async move {
+            Arc::new(AnthropicCompletionProvider::new(api_url, model_name, executor).await)
+                as Arc<dyn CompletionProvider>
+        }
+        .boxed(),
+        ModelProvider::Ollama => async move {
+            Arc::new(OllamaCompletionProvider::new(api_url, model_name, capacity, executor).await)
+                as Arc<dyn CompletionProvider>
+        }
+        .boxed(),
+        ModelProvider::OpenAiCompatible(_) => async move {
+            Arc::new(
+                OpenAiCompatibleCompletionProvider::new(api_url, model_name, capacity, executor)
+                    .await,
+            ) as Arc<dyn CompletionProvider>
+        }
+        .boxed(),
+    }
+}
@@ -0,0 +1,277 @@
+use ai::prompts::repository_context::PromptCodeSnippet;
+use anyhow::{anyhow, Result};
+use gpui::{AppContext, Model, Task, View};
+use language::{Bias, Buffer, DiagnosticSeverity, OffsetRangeExt, ToOffset, ToPoint};
+use project::Project;
+use semantic_index::SemanticIndex;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use terminal_view::TerminalView;
+use ui::IconName;
+
+/// The number of lines of terminal output attached to a conversation.
+const TERMINAL_CONTEXT_LINES: usize = 200;
+/// The number of semantic search hits attached to a conversation.
+const SEARCH_CONTEXT_RESULTS: usize = 5;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ContextKind {
+    File,
+    Symbol,
+    Diagnostics,
+    Terminal,
+    SearchResult,
+}
+
+impl ContextKind {
+    pub fn icon(&self) -> IconName {
+        match self {
+            ContextKind::File => IconName::File,
+            ContextKind::Symbol => IconName::Hash,
+            ContextKind::Diagnostics => IconName::ExclamationTriangle,
+            ContextKind::Terminal => IconName::Terminal,
+            ContextKind::SearchResult => IconName::MagnifyingGlass,
+        }
+    }
+}
+
+/// A piece of context attached to a conversation, which is sent to the model ahead of the
+/// conversation's messages. The text is captured when the context is attached.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ContextItem {
+    pub kind: ContextKind,
+    pub label: String,
+    pub text: String,
+    #[serde(skip)]
+    pub token_count: Option<usize>,
+}
+
+impl ContextItem {
+    pub fn new(kind: ContextKind, label: String, text: String) -> Self {
+        Self {
+            kind,
+            label,
+            text,
+            token_count: None,
+        }
+    }
+
+    /// Attaches the whole contents of a file.
+    pub fn file(buffer: &Model<Buffer>, cx: &AppContext) -> Result<Self> {
+        let buffer = buffer.read(cx);
+        let path = buffer
+            .file()
+            .map(|file| file.path().to_string_lossy().to_string())
+            .ok_or_else(|| anyhow!("only files can be attached to a conversation"))?;
+        let language_name = buffer
+            .language()
+            .map(|language| language.name().to_lowercase())
+            .unwrap_or_default();
+        let text = format!("```{language_name}\n{}\n```", buffer.text().trim_end());
+        Ok(Self::new(ContextKind::File, path, text))
+    }
+
+    /// Attaches the definition of the project symbol named `name`, as reported by the
+    /// project's language servers.
+    pub fn symbol(
+        project: Model<Project>,
+        name: String,
+        cx: &mut AppContext,
+    ) -> Task<Result<Self>> {
+        let symbols = project.update(cx, |project, cx| project.symbols(&name, cx));
+        cx.spawn(|mut cx| async move {
+            let symbols = symbols.await?;
+            let symbol = symbols
+                .iter()
+                .find(|symbol| symbol.name == name)
+                .or_else(|| symbols.first())
+                .ok_or_else(|| anyhow!("no symbol named {name:?} was found"))?;
+            let buffer = project
+                .update(&mut cx, |project, cx| {
+                    project.open_buffer_for_symbol(symbol, cx)
+                })?
+                .await?;
+
+            buffer.read_with(&cx, |buffer, _| {
+                let snapshot = buffer.snapshot();
+                let start = snapshot.clip_point_utf16(symbol.range.start, Bias::Left);
+                let end = snapshot.clip_point_utf16(symbol.range.end, Bias::Right);
+                let start = start.to_offset(&snapshot);
+                let end = end.to_offset(&snapshot);
+
+                // Language servers often only report the range of the symbol's name, so
+                // expand it to the innermost item of the outline containing it.
+                let range = snapshot
+                    .outline(None)
+                    .and_then(|outline| {
+                        outline
+                            .items
+                            .into_iter()
+                            .map(|item| item.range.to_offset(&snapshot))
+                            .filter(|range| range.start <= start && range.end >= end)
+                            .last()
+                    })
+                    .unwrap_or(start..end);
+
+                let path = symbol.path.path.to_string_lossy();
+                let row = range.start.to_point(&snapshot).row + 1;
+                let language_name = snapshot
+                    .language()
+                    .map(|language| language.name().to_lowercase())
+                    .unwrap_or_default();
+                let content = snapshot.text_for_range(range).collect::<String>();
+                let text = format!("Defined in {path}:{row}\n```{language_name}\n{content}\n```");
+                Self::new(ContextKind::Symbol, symbol.name.clone(), text)
+            })
+        })
+    }
+
+    /// Attaches the errors and warnings currently reported in the project.
+    pub fn diagnostics(project: Model<Project>, cx: &mut AppContext) -> Task<Result<Self>> {
+        let buffers = project.update(cx, |project, cx| {
+            let paths = project
+                .diagnostic_summaries(false, cx)
+                .filter(|(_, _, summary)| summary.error_count + summary.warning_count > 0)
+                .map(|(path, _, _)| path)
+                .collect::<Vec<_>>();
+            paths
+                .into_iter()
+                .map(|path| project.open_buffer(path, cx))
+                .collect::<Vec<_>>()
+        });
+
+        cx.spawn(|cx| async move {
+            let mut text = String::new();
+            let mut count = 0;
+            for buffer in buffers {
+                let buffer = buffer.await?;
+                buffer.read_with(&cx, |buffer, cx| {
+                    let snapshot = buffer.snapshot();
+                    let path = buffer
+                        .file()
+                        .map(|file| file.full_path(cx).to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    for (_, group) in snapshot.diagnostic_groups(None) {
+                        let entry = &group.entries[group.primary_ix];
+                        let severity = match entry.diagnostic.severity {
+                            DiagnosticSeverity::ERROR => "error",
+                            DiagnosticSeverity::WARNING => "warning",
+                            _ => continue,
+                        };
+                        let point = entry.range.start.to_point(&snapshot);
+                        writeln!(
+                            text,
+                            "{path}:{}:{}: {severity}: {}",
+                            point.row + 1,
+                            point.column + 1,
+                            entry.diagnostic.message
+                        )
+                        .unwrap();
+                        count += 1;
+                    }
+                })?;
+            }
+
+            if count == 0 {
+                return Err(anyhow!("the project has no diagnostics"));
+            }
+            Ok(Self::new(
+                ContextKind::Diagnostics,
+                format!("Diagnostics ({count})"),
+                text,
+            ))
+        })
+    }
+
+    /// Attaches the last lines of output of a terminal.
+    pub fn terminal(terminal: &View<TerminalView>, cx: &AppContext) -> Self {
+        let terminal = terminal.read(cx).model().read(cx);
+        let text = format!("```\n{}\n```", terminal.last_lines(TERMINAL_CONTEXT_LINES));
+        Self::new(ContextKind::Terminal, terminal.title(true), text)
+    }
+
+    /// Attaches the code most relevant to `query` in the project's semantic index.
+    pub fn search_results(
+        semantic_index: Model<SemanticIndex>,
+        project: Model<Project>,
+        query: String,
+        cx: &mut AppContext,
+    ) -> Task<Result<Vec<Self>>> {
+        let results = semantic_index.update(cx, |index, cx| {
+            index.search_project(
+                project,
+                query,
+                SEARCH_CONTEXT_RESULTS,
+                Vec::new(),
+                Vec::new(),
+                cx,
+            )
+        });
+        cx.spawn(|mut cx| async move {
+            let mut items = Vec::new();
+            for result in results.await? {
+                let label = result.buffer.read_with(&cx, |buffer, cx| {
+                    let path = buffer
+                        .file()
+                        .map(|file| file.path().to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let row = result.range.start.to_point(buffer).row + 1;
+                    format!("{path}:{row}")
+                })?;
+                let snippet = PromptCodeSnippet::new(result.buffer, result.range, &mut cx)?;
+                items.push(Self::new(
+                    ContextKind::SearchResult,
+                    label,
+                    snippet.to_string(),
+                ));
+            }
+            Ok(items)
+        })
+    }
+}
+
+/// Renders the context attached to a conversation as the content of a system message.
+pub(crate) fn context_prompt(items: &[ContextItem]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+
+    let mut prompt =
+        "The user has attached the following context to this conversation.\n".to_string();
+    for item in items {
+        write!(prompt, "\n## {}\n{}\n", item.label, item.text).unwrap();
+    }
+    Some(prompt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_prompt() {
+        assert_eq!(context_prompt(&[]), None);
+
+        let items = [
+            ContextItem::new(
+                ContextKind::File,
+                "src/main.rs".into(),
+                "```rust\nfn main() {}\n```".into(),
+            ),
+            ContextItem::new(
+                ContextKind::Diagnostics,
+                "Diagnostics (1)".into(),
+                "src/main.rs:1:4: warning: unused function\n".into(),
+            ),
+        ];
+        assert_eq!(
+            context_prompt(&items).unwrap(),
+            concat!(
+                "The user has attached the following context to this conversation.\n",
+                "\n## src/main.rs\n```rust\nfn main() {}\n```\n",
+                "\n## Diagnostics (1)\nsrc/main.rs:1:4: warning: unused function\n\n",
+            )
+        );
+    }
+}
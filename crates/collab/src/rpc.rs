@@ -216,6 +216,15 @@ impl Server {
             .add_message_handler(update_diagnostic_summary)
             .add_message_handler(update_worktree_settings)
             .add_request_handler(forward_read_only_project_request::<proto::GetHover>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetSignatureHelp>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetSemanticTokens>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetCodeLens>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetFoldingRanges>)
+            .add_request_handler(forward_read_only_project_request::<proto::ResolveCodeLens>)
+            .add_request_handler(forward_read_only_project_request::<proto::PrepareCallHierarchy>)
+            .add_request_handler(forward_read_only_project_request::<proto::PrepareTypeHierarchy>)
+            .add_request_handler(forward_read_only_project_request::<proto::GetHierarchyChildren>)
+            .add_request_handler(forward_read_only_project_request::<proto::BlameBuffer>)
             .add_request_handler(forward_read_only_project_request::<proto::GetDefinition>)
             .add_request_handler(forward_read_only_project_request::<proto::GetTypeDefinition>)
             .add_request_handler(forward_read_only_project_request::<proto::GetReferences>)
@@ -236,6 +245,7 @@ impl Server {
             )
             .add_request_handler(forward_mutating_project_request::<proto::GetCodeActions>)
             .add_request_handler(forward_mutating_project_request::<proto::ApplyCodeAction>)
+            .add_request_handler(forward_mutating_project_request::<proto::ApplyCodeLens>)
             .add_request_handler(forward_mutating_project_request::<proto::PrepareRename>)
             .add_request_handler(forward_mutating_project_request::<proto::PerformRename>)
             .add_request_handler(forward_mutating_project_request::<proto::ReloadBuffers>)
@@ -244,12 +254,20 @@ impl Server {
             .add_request_handler(forward_mutating_project_request::<proto::RenameProjectEntry>)
             .add_request_handler(forward_mutating_project_request::<proto::CopyProjectEntry>)
             .add_request_handler(forward_mutating_project_request::<proto::DeleteProjectEntry>)
+            .add_request_handler(forward_mutating_project_request::<proto::RestoreProjectEntry>)
             .add_request_handler(forward_mutating_project_request::<proto::ExpandProjectEntry>)
             .add_request_handler(forward_mutating_project_request::<proto::OnTypeFormatting>)
             .add_request_handler(forward_mutating_project_request::<proto::SaveBuffer>)
+            .add_request_handler(forward_mutating_project_request::<proto::GetProjectTasks>)
+            .add_request_handler(forward_mutating_project_request::<proto::RunProjectTask>)
             .add_message_handler(create_buffer_for_peer)
+            .add_message_handler(update_task_terminal)
             .add_request_handler(update_buffer)
             .add_message_handler(broadcast_project_message_from_host::<proto::RefreshInlayHints>)
+            .add_message_handler(
+                broadcast_project_message_from_host::<proto::RefreshSemanticTokens>,
+            )
+            .add_message_handler(broadcast_project_message_from_host::<proto::RefreshCodeLens>)
             .add_message_handler(broadcast_project_message_from_host::<proto::UpdateBufferFile>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferReloaded>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferSaved>)
@@ -284,6 +302,7 @@ impl Server {
             .add_request_handler(move_channel)
             .add_request_handler(follow)
             .add_message_handler(unfollow)
+            .add_message_handler(follower_input)
             .add_message_handler(update_followers)
             .add_request_handler(get_private_user_info)
             .add_message_handler(acknowledge_channel_message)
@@ -1937,6 +1956,33 @@ async fn create_buffer_for_peer(
     Ok(())
 }
 
+/// Send the output of a task run on the host to the guest that requested it
+async fn update_task_terminal(request: proto::UpdateTaskTerminal, session: Session) -> Result<()> {
+    let project_id = ProjectId::from_proto(request.project_id);
+    let peer_id: ConnectionId = request
+        .peer_id
+        .ok_or_else(|| anyhow!("invalid peer id"))?
+        .into();
+    session
+        .db()
+        .await
+        .check_user_is_project_host(project_id, session.connection_id)
+        .await?;
+    let is_collaborator = session
+        .db()
+        .await
+        .project_connection_ids(project_id, session.connection_id)
+        .await?
+        .contains(&peer_id);
+    if !is_collaborator {
+        Err(anyhow!("task terminals can only be sent to collaborators"))?;
+    }
+    session
+        .peer
+        .forward_send(session.connection_id, peer_id, request)?;
+    Ok(())
+}
+
 /// Notify other participants that a buffer has been updated. This is
 /// allowed for guests as long as the update is limited to selections.
 async fn update_buffer(
@@ -2092,6 +2138,40 @@ async fn unfollow(request: proto::Unfollow, session: Session) -> Result<()> {
     Ok(())
 }
 
+/// Forward input typed into a followed view, such as a shared terminal, to the view's leader.
+/// Only participants who can edit the project may send input, and only to its host.
+async fn follower_input(request: proto::FollowerInput, session: Session) -> Result<()> {
+    let room_id = RoomId::from_proto(request.room_id);
+    let project_id = ProjectId::from_proto(request.project_id);
+    let leader_id: ConnectionId = request
+        .view_id
+        .as_ref()
+        .and_then(|view_id| view_id.creator)
+        .ok_or_else(|| anyhow!("invalid view id"))?
+        .into();
+
+    session
+        .db()
+        .await
+        .check_room_participants(room_id, leader_id, session.connection_id)
+        .await?;
+    let host_connection_id = session
+        .db()
+        .await
+        .host_for_mutating_project_request(project_id, session.connection_id)
+        .await?;
+    if host_connection_id != leader_id {
+        Err(anyhow!(
+            "follower input can only be sent to the project host"
+        ))?;
+    }
+
+    session
+        .peer
+        .forward_send(session.connection_id, leader_id, request)?;
+    Ok(())
+}
+
 /// Notify everyone following you of your current location.
 async fn update_followers(request: proto::UpdateFollowers, session: Session) -> Result<()> {
     let room_id = RoomId::from_proto(request.room_id);
@@ -1,8 +1,20 @@
 use crate::{db::ChannelId, tests::TestServer};
 use call::ActiveCall;
 use editor::Editor;
-use gpui::{BackgroundExecutor, TestAppContext};
+use futures::channel::mpsc;
+use gpui::{BackgroundExecutor, Context, Empty, Model, TestAppContext};
+use project::{StaticTestSource, TaskSourceKind};
 use rpc::proto;
+use std::{cell::RefCell, rc::Rc, time::Duration};
+use task::Task as _;
+use terminal::{
+    alacritty_terminal::{
+        index::{Column, Line, Point as AlacPoint},
+        term::{cell::Cell, RenderableCursor, TermMode},
+        vte::ansi::CursorShape,
+    },
+    SpawnTask, Terminal, TerminalScreen,
+};
 
 #[gpui::test]
 async fn test_channel_guests(
@@ -286,3 +298,377 @@ async fn test_channel_requires_zed_cla(cx_a: &mut TestAppContext, cx_b: &mut Tes
     assert!(room_b.read_with(cx_b, |room, _| room.can_share_projects()));
     assert!(room_b.read_with(cx_b, |room, _| room.can_use_microphone()));
 }
+
+#[gpui::test]
+async fn test_channel_guest_tasks(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    let mut server = TestServer::start(cx_a.executor()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    let channel_id = server
+        .make_public_channel("the-channel", &client_a, cx_a)
+        .await;
+
+    // Client A shares a project with a task in the channel
+    let project_a = client_a.build_test_project(cx_a).await;
+    project_a.update(cx_a, |project, cx| {
+        project.task_inventory().update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::AbsPath("/a/.zed/tasks.json".into()),
+                |cx| StaticTestSource::new(vec!["build".to_string()], cx),
+                cx,
+            )
+        })
+    });
+    let task_id = project_a.update(cx_a, |project, cx| {
+        project.task_inventory().update(cx, |inventory, cx| {
+            inventory.list_tasks(None, None, false, cx)[0]
+                .1
+                .id()
+                .clone()
+        })
+    });
+    cx_a.update(|cx| workspace::join_channel(channel_id, client_a.app_state.clone(), None, cx))
+        .await
+        .unwrap();
+    active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+
+    // Client A declines every task run requested by guests
+    let requested_task_runs = Rc::new(RefCell::new(Vec::new()));
+    let _subscription = cx_a.update(|cx| {
+        let requested_task_runs = requested_task_runs.clone();
+        cx.subscribe(&project_a, move |project, event, cx| {
+            if let project::Event::TaskRunRequested {
+                task_run_id, label, ..
+            } = event
+            {
+                requested_task_runs.borrow_mut().push(label.clone());
+                project.update(cx, |project, _| project.decline_task_run(*task_run_id));
+            }
+        })
+    });
+
+    // Client B joins channel A as a guest
+    cx_b.update(|cx| workspace::join_channel(channel_id, client_b.app_state.clone(), None, cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+
+    let active_call_b = cx_b.read(ActiveCall::global);
+    let project_b =
+        active_call_b.read_with(cx_b, |call, _| call.location().unwrap().upgrade().unwrap());
+    assert!(project_b.read_with(cx_b, |project, _| project.is_read_only()));
+
+    // Read-only guests can neither list nor run the host's tasks
+    assert!(project_b
+        .update(cx_b, |project, cx| project.remote_tasks(cx))
+        .await
+        .is_err());
+    assert!(project_b
+        .update(cx_b, |project, cx| project
+            .run_remote_task(task_id.clone(), cx))
+        .await
+        .is_err());
+    cx_a.run_until_parked();
+    assert!(requested_task_runs.borrow().is_empty());
+
+    // B is promoted
+    active_call_a
+        .update(cx_a, |call, cx| {
+            call.room().unwrap().update(cx, |room, cx| {
+                room.set_participant_role(
+                    client_b.user_id().unwrap(),
+                    proto::ChannelRole::Member,
+                    cx,
+                )
+            })
+        })
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    assert!(project_b.read_with(cx_b, |project, _| !project.is_read_only()));
+
+    // B can list the host's tasks and ask to run one, which the host can decline
+    let remote_tasks = project_b
+        .update(cx_b, |project, cx| project.remote_tasks(cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        remote_tasks
+            .iter()
+            .map(|task| (&task.id, task.label.as_str()))
+            .collect::<Vec<_>>(),
+        [(&task_id, "build")]
+    );
+    let run_task = project_b.update(cx_b, |project, cx| project.run_remote_task(task_id, cx));
+    cx_a.run_until_parked();
+    assert!(run_task.await.is_err());
+    assert_eq!(*requested_task_runs.borrow(), ["build"]);
+}
+
+#[gpui::test]
+async fn test_channel_guest_accepted_task_runs(
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(cx_a.executor()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    let channel_id = server
+        .make_public_channel("the-channel", &client_a, cx_a)
+        .await;
+
+    // Client A shares a project with a task in the channel
+    let project_a = client_a.build_test_project(cx_a).await;
+    project_a.update(cx_a, |project, cx| {
+        project.task_inventory().update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::AbsPath("/a/.zed/tasks.json".into()),
+                |cx| StaticTestSource::new(vec!["build".to_string()], cx),
+                cx,
+            )
+        })
+    });
+    let task_id = project_a.update(cx_a, |project, cx| {
+        project.task_inventory().update(cx, |inventory, cx| {
+            inventory.list_tasks(None, None, false, cx)[0]
+                .1
+                .id()
+                .clone()
+        })
+    });
+    cx_a.update(|cx| workspace::join_channel(channel_id, client_a.app_state.clone(), None, cx))
+        .await
+        .unwrap();
+    active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+
+    let requested_task_runs = Rc::new(RefCell::new(Vec::new()));
+    let _subscription = cx_a.update(|cx| {
+        let requested_task_runs = requested_task_runs.clone();
+        cx.subscribe(&project_a, move |_, event, _| {
+            if let project::Event::TaskRunRequested { task_run_id, .. } = event {
+                requested_task_runs.borrow_mut().push(*task_run_id);
+            }
+        })
+    });
+
+    // Client B joins channel A and is promoted to a member
+    cx_b.update(|cx| workspace::join_channel(channel_id, client_b.app_state.clone(), None, cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    active_call_a
+        .update(cx_a, |call, cx| {
+            call.room().unwrap().update(cx, |room, cx| {
+                room.set_participant_role(
+                    client_b.user_id().unwrap(),
+                    proto::ChannelRole::Member,
+                    cx,
+                )
+            })
+        })
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    let active_call_b = cx_b.read(ActiveCall::global);
+    let project_b =
+        active_call_b.read_with(cx_b, |call, _| call.location().unwrap().upgrade().unwrap());
+    assert!(project_b.read_with(cx_b, |project, _| !project.is_read_only()));
+
+    // B asks to run the same task twice, and each request gets its own id
+    let run_1 = project_b.update(cx_b, |project, cx| {
+        project.run_remote_task(task_id.clone(), cx)
+    });
+    let run_2 = project_b.update(cx_b, |project, cx| {
+        project.run_remote_task(task_id.clone(), cx)
+    });
+    cx_a.run_until_parked();
+    let [task_run_1, task_run_2] = requested_task_runs.borrow()[..] else {
+        panic!("expected two task runs to be requested");
+    };
+    assert_ne!(task_run_1, task_run_2);
+
+    // A accepts both runs, and spawns their terminals in the opposite order
+    let screen = |text: &str| TerminalScreen {
+        rows: 1,
+        columns: text.len(),
+        cells: text
+            .chars()
+            .map(|c| {
+                let mut cell = Cell::default();
+                cell.c = c;
+                cell
+            })
+            .collect(),
+        cursor: RenderableCursor {
+            shape: CursorShape::Block,
+            point: AlacPoint::new(Line(0), Column(0)),
+        },
+        mode: TermMode::default(),
+    };
+    let screen_text = |terminal: &Model<Terminal>, cx: &mut TestAppContext| {
+        terminal.read_with(cx, |terminal, _| {
+            terminal
+                .screen()
+                .cells
+                .iter()
+                .map(|cell| cell.c)
+                .collect::<String>()
+        })
+    };
+    let task_terminal = |text: &str, cx: &mut TestAppContext| {
+        let screen = screen(text);
+        let task_id = task_id.clone();
+        cx.new_model(|_| {
+            let (input_tx, _) = mpsc::unbounded();
+            let mut terminal = Terminal::remote("build".into(), false, screen, input_tx);
+            terminal.set_remote_task(task_id, "build".into());
+            terminal
+        })
+    };
+    let terminal_a_1 = task_terminal("one", cx_a);
+    let terminal_a_2 = task_terminal("two", cx_a);
+    project_a.update(cx_a, |project, _| {
+        project.accept_task_run(task_run_1);
+        project.accept_task_run(task_run_2);
+        project.task_run_started(task_run_2, &terminal_a_2);
+        project.task_run_started(task_run_1, &terminal_a_1);
+    });
+    let terminal_b_1 = run_1.await.unwrap();
+    let terminal_b_2 = run_2.await.unwrap();
+    assert_eq!(screen_text(&terminal_b_1, cx_b), "one");
+    assert_eq!(screen_text(&terminal_b_2, cx_b), "two");
+
+    // The output of each run reaches B's terminal for that run
+    terminal_a_1.update(cx_a, |terminal, cx| {
+        terminal.set_remote_screen("build".into(), false, screen("1!!"), cx)
+    });
+    cx_a.executor().advance_clock(Duration::from_millis(100));
+    cx_a.run_until_parked();
+    assert_eq!(screen_text(&terminal_b_1, cx_b), "1!!");
+    assert_eq!(screen_text(&terminal_b_2, cx_b), "two");
+
+    // B's terminal is marked as completed once the task completes on A
+    terminal_a_1.update(cx_a, |terminal, _| terminal.complete_remote_task());
+    cx_a.run_until_parked();
+    assert!(terminal_b_1.read_with(cx_b, |terminal, _| terminal.task().unwrap().completed));
+    assert!(!terminal_b_2.read_with(cx_b, |terminal, _| terminal.task().unwrap().completed));
+}
+
+#[gpui::test]
+async fn test_channel_guest_task_run_spawn_failure(
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(cx_a.executor()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+    cx_a.update(terminal::init);
+
+    let channel_id = server
+        .make_public_channel("the-channel", &client_a, cx_a)
+        .await;
+
+    // Client A shares a project with a task in the channel
+    let project_a = client_a.build_test_project(cx_a).await;
+    project_a.update(cx_a, |project, cx| {
+        project.task_inventory().update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::AbsPath("/a/.zed/tasks.json".into()),
+                |cx| StaticTestSource::new(vec!["build".to_string()], cx),
+                cx,
+            )
+        })
+    });
+    let task_id = project_a.update(cx_a, |project, cx| {
+        project.task_inventory().update(cx, |inventory, cx| {
+            inventory.list_tasks(None, None, false, cx)[0]
+                .1
+                .id()
+                .clone()
+        })
+    });
+    cx_a.update(|cx| workspace::join_channel(channel_id, client_a.app_state.clone(), None, cx))
+        .await
+        .unwrap();
+    active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+
+    let requested_task_runs = Rc::new(RefCell::new(Vec::new()));
+    let _subscription = cx_a.update(|cx| {
+        let requested_task_runs = requested_task_runs.clone();
+        cx.subscribe(&project_a, move |_, event, _| {
+            if let project::Event::TaskRunRequested { task_run_id, .. } = event {
+                requested_task_runs.borrow_mut().push(*task_run_id);
+            }
+        })
+    });
+
+    // Client B joins channel A and is promoted to a member
+    cx_b.update(|cx| workspace::join_channel(channel_id, client_b.app_state.clone(), None, cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    active_call_a
+        .update(cx_a, |call, cx| {
+            call.room().unwrap().update(cx, |room, cx| {
+                room.set_participant_role(
+                    client_b.user_id().unwrap(),
+                    proto::ChannelRole::Member,
+                    cx,
+                )
+            })
+        })
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    let active_call_b = cx_b.read(ActiveCall::global);
+    let project_b =
+        active_call_b.read_with(cx_b, |call, _| call.location().unwrap().upgrade().unwrap());
+
+    // A accepts B's run, but the task's terminal can't be spawned
+    let run = project_b.update(cx_b, |project, cx| {
+        project.run_remote_task(task_id.clone(), cx)
+    });
+    cx_a.run_until_parked();
+    let [task_run_id] = requested_task_runs.borrow()[..] else {
+        panic!("expected one task run to be requested");
+    };
+    let window = cx_a.add_window(|_| Empty);
+    let spawned = project_a.update(cx_a, |project, cx| {
+        project.accept_task_run(task_run_id);
+        project.create_terminal(
+            None,
+            Some(SpawnTask {
+                id: task_id.clone(),
+                label: "build".into(),
+                command: "/this/command/does/not/exist".into(),
+                args: Vec::new(),
+                env: Default::default(),
+                task_run_id: Some(task_run_id),
+            }),
+            window.into(),
+            cx,
+        )
+    });
+    assert!(spawned.is_err());
+
+    // B's request fails instead of waiting for the terminal forever
+    cx_a.run_until_parked();
+    assert!(run.await.is_err());
+}
@@ -6,19 +6,30 @@ use collab_ui::{
     notifications::project_shared_notification::ProjectSharedNotification,
 };
 use editor::{Editor, ExcerptRange, MultiBuffer};
+use futures::channel::mpsc;
 use gpui::{
-    point, BackgroundExecutor, Context, Entity, SharedString, TestAppContext, View, VisualContext,
-    VisualTestContext,
+    point, BackgroundExecutor, Context, Entity, Model, SharedString, TestAppContext, View,
+    VisualContext, VisualTestContext,
 };
 use language::Capability;
 use live_kit_client::MacOSDisplay;
 use project::project_settings::ProjectSettings;
-use rpc::proto::PeerId;
+use rpc::proto::{self, PeerId};
 use serde_json::json;
 use settings::SettingsStore;
+use std::time::Duration;
+use terminal::{
+    alacritty_terminal::{
+        index::{Column, Line, Point as AlacPoint},
+        term::{cell::Cell, RenderableCursor, TermMode},
+        vte::ansi::CursorShape,
+    },
+    Terminal, TerminalScreen,
+};
+use terminal_view::{terminal_panel::TerminalPanel, TerminalView, ToggleFollowerInput};
 use workspace::{
-    dock::{test::TestPanel, DockPosition},
-    item::{test::TestItem, ItemHandle as _},
+    dock::{test::TestPanel, DockPosition, Panel as _},
+    item::{test::TestItem, FollowableItem as _, ItemHandle as _},
     shared_screen::SharedScreen,
     SplitDirection, Workspace,
 };
@@ -2141,3 +2152,122 @@ async fn test_following_while_deactivated(cx_a: &mut TestAppContext, cx_b: &mut
         assert_eq!(editor.tab_description(0, cx).unwrap(), "2.js");
     });
 }
+
+#[gpui::test]
+async fn test_following_into_terminal(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    let mut server = TestServer::start(cx_a.executor()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    cx_a.update(terminal_view::init);
+    cx_b.update(terminal_view::init);
+    let channel_id = server
+        .make_public_channel("the-channel", &client_a, cx_a)
+        .await;
+
+    let (workspace_a, cx_a) = client_a.build_test_workspace(cx_a).await;
+    client_a
+        .host_workspace(&workspace_a, channel_id, cx_a)
+        .await;
+
+    let screen = |text: &str| TerminalScreen {
+        rows: 1,
+        columns: text.len(),
+        cells: text
+            .chars()
+            .map(|c| {
+                let mut cell = Cell::default();
+                cell.c = c;
+                cell
+            })
+            .collect(),
+        cursor: RenderableCursor {
+            shape: CursorShape::Block,
+            point: AlacPoint::new(Line(0), Column(0)),
+        },
+        mode: TermMode::default(),
+    };
+    let screen_text = |terminal: &Model<Terminal>, cx: &mut VisualTestContext| {
+        terminal.read_with(cx, |terminal, _| {
+            terminal
+                .screen()
+                .cells
+                .iter()
+                .map(|cell| cell.c)
+                .collect::<String>()
+        })
+    };
+
+    // a opens a terminal in the terminal panel. It stands in for a shell, recording the input
+    // written to it.
+    let (input_tx, mut input_rx) = mpsc::unbounded();
+    let terminal_a =
+        cx_a.new_model(|_| Terminal::remote("shell".into(), false, screen("$   "), input_tx));
+    workspace_a.update(cx_a, |workspace, cx| {
+        let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
+        workspace.add_panel(panel.clone(), cx);
+        let terminal_view = cx.new_view(|cx| {
+            TerminalView::new(
+                terminal_a.clone(),
+                workspace.weak_handle(),
+                workspace.database_id(),
+                cx,
+            )
+        });
+        panel.read(cx).pane().unwrap().update(cx, |pane, cx| {
+            pane.add_item(Box::new(terminal_view), true, true, None, cx)
+        });
+        workspace.focus_panel::<TerminalPanel>(cx);
+    });
+    cx_a.run_until_parked();
+
+    // b joins the channel as a guest and follows a into the terminal, which is read-only.
+    let (workspace_b, cx_b) = client_b.join_workspace(channel_id, cx_b).await;
+    let terminal_view_b = workspace_b.update(cx_b, |workspace, cx| {
+        workspace.active_item_as::<TerminalView>(cx).unwrap()
+    });
+    let terminal_b = terminal_view_b.read_with(cx_b, |view, _| view.terminal().clone());
+    let view_id = terminal_view_b.read_with(cx_b, |view, _| view.remote_id().unwrap());
+    assert_eq!(screen_text(&terminal_b, cx_b), "$   ");
+    assert!(terminal_b.read_with(cx_b, |terminal, _| terminal.is_read_only()));
+
+    // a's output is mirrored to b.
+    terminal_a.update(cx_a, |terminal, cx| {
+        terminal.set_remote_screen("shell".into(), false, screen("$ ls"), cx)
+    });
+    cx_a.executor().advance_clock(Duration::from_millis(100));
+    cx_a.run_until_parked();
+    assert_eq!(screen_text(&terminal_b, cx_b), "$ ls");
+
+    // a lets followers type, but b is a guest, so its input is dropped.
+    cx_a.dispatch_action(ToggleFollowerInput);
+    cx_a.executor().advance_clock(Duration::from_millis(100));
+    cx_a.run_until_parked();
+    workspace_b.update(cx_b, |workspace, cx| {
+        workspace.send_follower_input(view_id, b"pwd".to_vec(), cx)
+    });
+    cx_b.run_until_parked();
+    cx_a.run_until_parked();
+    assert!(input_rx.try_next().is_err());
+
+    // Once b is promoted to a member, b's input reaches a's terminal.
+    cx_a.update(|cx| {
+        ActiveCall::global(cx).update(cx, |call, cx| {
+            call.room().unwrap().update(cx, |room, cx| {
+                room.set_participant_role(
+                    client_b.user_id().unwrap(),
+                    proto::ChannelRole::Member,
+                    cx,
+                )
+            })
+        })
+    })
+    .await
+    .unwrap();
+    cx_a.run_until_parked();
+    cx_b.run_until_parked();
+    assert!(!terminal_b.read_with(cx_b, |terminal, _| terminal.is_read_only()));
+    terminal_b.update(cx_b, |terminal, _| terminal.input_bytes(b"pwd".to_vec()));
+    cx_b.run_until_parked();
+    cx_a.run_until_parked();
+    assert_eq!(input_rx.try_next().unwrap(), Some(b"pwd".to_vec()));
+}
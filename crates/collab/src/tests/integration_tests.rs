@@ -14,12 +14,13 @@ use gpui::{
 use language::{
     language_settings::{AllLanguageSettings, Formatter},
     tree_sitter_rust, Diagnostic, DiagnosticEntry, FakeLspAdapter, Language, LanguageConfig,
-    LanguageMatcher, LineEnding, OffsetRangeExt, Point, Rope,
+    LanguageMatcher, LineEnding, OffsetRangeExt, Point, PointUtf16, Rope,
 };
 use live_kit_client::MacOSDisplay;
 use lsp::LanguageServerId;
 use project::{
-    search::SearchQuery, DiagnosticSummary, FormatTrigger, HoverBlockKind, Project, ProjectPath,
+    search::SearchQuery, DiagnosticSummary, FormatTrigger, HierarchyDirection, HoverBlockKind,
+    Project, ProjectPath,
 };
 use rand::prelude::*;
 use rpc::proto::ChannelRole;
@@ -2706,6 +2707,169 @@ async fn test_git_diff_base_change(
     });
 }
 
+#[gpui::test]
+async fn test_git_blame(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "one\ntwo\nthree\n",
+            }),
+        )
+        .await;
+
+    let blame = git::blame::Blame {
+        entries: vec![
+            git::blame::BlameEntry {
+                sha: "1b1a1e4b0e2d3c4f5a6b7c8d9e0f1a2b3c4d5e6f".into(),
+                range: 0..2,
+                original_line_number: 1,
+                author: Some("Alice".into()),
+                author_time: Some(1700000000),
+                author_tz: Some("+0100".into()),
+                summary: Some("Add the first lines".into()),
+                ..Default::default()
+            },
+            git::blame::BlameEntry {
+                sha: "0000000000000000000000000000000000000000".into(),
+                range: 2..3,
+                original_line_number: 3,
+                ..Default::default()
+            },
+        ],
+    };
+    client_a.fs().set_blame_for_repo(
+        Path::new("/dir/.git"),
+        vec![(Path::new("a.txt"), blame.clone())],
+    );
+
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    let buffer_a = project_a
+        .update(cx_a, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let blame_a = project_a
+        .update(cx_a, |p, cx| p.blame_buffer(&buffer_a, cx))
+        .await
+        .unwrap();
+    assert_eq!(blame_a, blame);
+
+    let buffer_b = project_b
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let blame_b = project_b
+        .update(cx_b, |p, cx| p.blame_buffer(&buffer_b, cx))
+        .await
+        .unwrap();
+    assert_eq!(blame_b, blame);
+    assert!(blame_b.entries[1].is_uncommitted());
+}
+
+#[gpui::test]
+async fn test_git_set_index_text(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "one\nTWO\nthree\n",
+            }),
+        )
+        .await;
+    client_a.fs().set_head_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), "one\ntwo\nthree\n".into())],
+    );
+    client_a.fs().set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), "one\ntwo\nthree\n".into())],
+    );
+
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    let buffer_a = project_a
+        .update(cx_a, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let buffer_b = project_b
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    let head_text = project_a
+        .update(cx_a, |p, cx| p.load_head_text(&buffer_a, cx))
+        .await
+        .unwrap();
+    assert_eq!(head_text.as_deref(), Some("one\ntwo\nthree\n"));
+
+    // Staging the change updates the diff base of the host and the guest.
+    project_a
+        .update(cx_a, |p, cx| {
+            p.set_index_text(&buffer_a, Some("one\nTWO\nthree\n".into()), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    buffer_a.read_with(cx_a, |buffer, _| {
+        assert_eq!(buffer.diff_base(), Some("one\nTWO\nthree\n"));
+        assert_eq!(
+            buffer.snapshot().git_diff_hunks_in_row_range(0..4).count(),
+            0
+        );
+    });
+    buffer_b.read_with(cx_b, |buffer, _| {
+        assert_eq!(buffer.diff_base(), Some("one\nTWO\nthree\n"));
+    });
+
+    // Guests cannot write to the host's index.
+    project_b
+        .update(cx_b, |p, cx| {
+            p.set_index_text(&buffer_b, Some("one\ntwo\nthree\n".into()), cx)
+        })
+        .await
+        .unwrap_err();
+}
+
 #[gpui::test]
 async fn test_git_branch_name(
     executor: BackgroundExecutor,
@@ -3160,7 +3324,7 @@ async fn test_fs_operations(
 
     project_b
         .update(cx_b, |project, cx| {
-            project.delete_entry(dir_entry.id, cx).unwrap()
+            project.delete_entry(dir_entry.id, false, cx).unwrap()
         })
         .await
         .unwrap();
@@ -3188,7 +3352,7 @@ async fn test_fs_operations(
 
     project_b
         .update(cx_b, |project, cx| {
-            project.delete_entry(entry.id, cx).unwrap()
+            project.delete_entry(entry.id, false, cx).unwrap()
         })
         .await
         .unwrap();
@@ -5063,6 +5227,572 @@ async fn test_lsp_hover(
     });
 }
 
+#[gpui::test(iterations = 10)]
+async fn test_lsp_signature_help(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root-1",
+            json!({
+                "main.rs": "fn main() { add(1, ",
+            }),
+        )
+        .await;
+
+    // Set up a fake language server.
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_language_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                signature_help_provider: Some(lsp::SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), "<".to_string()]),
+                    retrigger_characters: Some(vec![",".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .await;
+    client_a.language_registry().add(Arc::new(language));
+
+    let (project_a, worktree_id) = client_a.build_local_project("/root-1", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    // Open the file as the guest
+    let open_buffer = project_b.update(cx_b, |p, cx| p.open_buffer((worktree_id, "main.rs"), cx));
+    let buffer_b = cx_b.executor().spawn(open_buffer).await.unwrap();
+
+    // The guest's buffer is triggered by the characters of the host's language server.
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    executor.run_until_parked();
+    buffer_b.read_with(cx_b, |buffer, _| {
+        assert_eq!(buffer.signature_help_triggers(), ["(", "<", ","]);
+    });
+    project_b.read_with(cx_b, |project, cx| {
+        assert!(project.is_signature_help_trigger(&buffer_b, "<", cx));
+        assert!(!project.is_signature_help_trigger(&buffer_b, "[", cx));
+    });
+
+    // Request signature help as the guest.
+    fake_language_server.handle_request::<lsp::request::SignatureHelpRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params
+                    .text_document_position_params
+                    .text_document
+                    .uri
+                    .as_str(),
+                "file:///root-1/main.rs"
+            );
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 19)
+            );
+            Ok(Some(lsp::SignatureHelp {
+                signatures: vec![lsp::SignatureInformation {
+                    label: "fn add(a: i32, b: i32) -> i32".to_string(),
+                    documentation: Some(lsp::Documentation::MarkupContent(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "Adds two numbers.".to_string(),
+                    })),
+                    parameters: Some(vec![
+                        lsp::ParameterInformation {
+                            label: lsp::ParameterLabel::Simple("a: i32".to_string()),
+                            documentation: None,
+                        },
+                        lsp::ParameterInformation {
+                            label: lsp::ParameterLabel::LabelOffsets([15, 21]),
+                            documentation: None,
+                        },
+                    ]),
+                    active_parameter: None,
+                }],
+                active_signature: Some(0),
+                active_parameter: Some(1),
+            }))
+        },
+    );
+
+    let signature_help = project_b
+        .update(cx_b, |p, cx| p.signature_help(&buffer_b, 19, cx))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(signature_help.signatures.len(), 1);
+    let signature = signature_help.active_signature().unwrap();
+    assert_eq!(signature.label, "fn add(a: i32, b: i32) -> i32");
+    assert_eq!(
+        signature.documentation,
+        Some(project::MarkupContent {
+            kind: HoverBlockKind::Markdown,
+            value: "Adds two numbers.".to_string(),
+        })
+    );
+    assert_eq!(
+        signature
+            .parameters
+            .iter()
+            .map(|parameter| parameter.label.clone())
+            .collect::<Vec<_>>(),
+        vec![7..13, 15..21]
+    );
+    assert_eq!(signature_help.active_parameter().unwrap().label, 15..21);
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_lsp_code_lens(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root-1",
+            json!({
+                "main.rs": "fn main() {}\n\n#[test]\nfn it_works() {}\n",
+            }),
+        )
+        .await;
+
+    // Set up a fake language server.
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_language_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .await;
+    client_a.language_registry().add(Arc::new(language));
+
+    let (project_a, worktree_id) = client_a.build_local_project("/root-1", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    // Open the file as the guest
+    let open_buffer = project_b.update(cx_b, |p, cx| p.open_buffer((worktree_id, "main.rs"), cx));
+    let buffer_b = cx_b.executor().spawn(open_buffer).await.unwrap();
+
+    // Request code lenses as the guest.
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    fake_language_server.handle_request::<lsp::request::CodeLensRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.text_document.uri.as_str(), "file:///root-1/main.rs");
+            Ok(Some(vec![
+                lsp::CodeLens {
+                    range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 7)),
+                    command: None,
+                    data: Some(json!({ "references": "main" })),
+                },
+                lsp::CodeLens {
+                    range: lsp::Range::new(lsp::Position::new(3, 3), lsp::Position::new(3, 11)),
+                    command: Some(lsp::Command {
+                        title: "Run test".to_string(),
+                        command: "rust-analyzer.runSingle".to_string(),
+                        arguments: None,
+                    }),
+                    data: None,
+                },
+            ]))
+        },
+    );
+    fake_language_server.handle_request::<lsp::request::CodeLensResolve, _, _>(
+        |lens, _| async move {
+            assert_eq!(lens.data, Some(json!({ "references": "main" })));
+            Ok(lsp::CodeLens {
+                command: Some(lsp::Command {
+                    title: "0 references".to_string(),
+                    command: "rust-analyzer.showReferences".to_string(),
+                    arguments: None,
+                }),
+                ..lens
+            })
+        },
+    );
+
+    let lenses = project_b
+        .update(cx_b, |p, cx| p.code_lens(&buffer_b, cx))
+        .await
+        .unwrap();
+    assert_eq!(lenses.len(), 2);
+    buffer_b.read_with(cx_b, |buffer, _| {
+        assert_eq!(
+            lenses
+                .iter()
+                .map(|lens| lens.range.to_point(buffer))
+                .collect::<Vec<_>>(),
+            vec![
+                Point::new(0, 3)..Point::new(0, 7),
+                Point::new(3, 3)..Point::new(3, 11)
+            ]
+        );
+    });
+    assert!(lenses[0].lsp_lens.command.is_none());
+
+    let resolved = project_b
+        .update(cx_b, |p, cx| {
+            p.resolve_code_lens(&buffer_b, lenses[0].clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        resolved.lsp_lens.command.map(|command| command.title),
+        Some("0 references".to_string())
+    );
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_lsp_call_hierarchy(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root-1",
+            json!({
+                "main.rs": "fn main() {\n    one();\n}\n\nfn one() {}\n",
+            }),
+        )
+        .await;
+
+    // Set up a fake language server.
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_language_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .await;
+    client_a.language_registry().add(Arc::new(language));
+
+    let (project_a, worktree_id) = client_a.build_local_project("/root-1", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    // Open the file as the guest
+    let open_buffer = project_b.update(cx_b, |p, cx| p.open_buffer((worktree_id, "main.rs"), cx));
+    let buffer_b = cx_b.executor().spawn(open_buffer).await.unwrap();
+
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    let uri = lsp::Url::from_file_path("/root-1/main.rs").unwrap();
+    let call_hierarchy_item = |name: &str, line: u32, column: u32| lsp::CallHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: Some(format!("fn {name}()")),
+        uri: uri.clone(),
+        range: lsp::Range::new(lsp::Position::new(line, 0), lsp::Position::new(line + 2, 1)),
+        selection_range: lsp::Range::new(
+            lsp::Position::new(line, column),
+            lsp::Position::new(line, column + name.len() as u32),
+        ),
+        data: Some(json!({ "name": name })),
+    };
+    fake_language_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+        let one = call_hierarchy_item("one", 4, 3);
+        move |params, _| {
+            let one = one.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(4, 4)
+                );
+                Ok(Some(vec![one]))
+            }
+        }
+    });
+    fake_language_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>({
+        let main = call_hierarchy_item("main", 0, 3);
+        move |params, _| {
+            let main = main.clone();
+            async move {
+                assert_eq!(params.item.data, Some(json!({ "name": "one" })));
+                Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                    from: main,
+                    from_ranges: vec![lsp::Range::new(
+                        lsp::Position::new(1, 4),
+                        lsp::Position::new(1, 7),
+                    )],
+                }]))
+            }
+        }
+    });
+
+    // Request the callers of `one` as the guest.
+    let roots = project_b
+        .update(cx_b, |p, cx| {
+            p.prepare_call_hierarchy(&buffer_b, Point::new(4, 4), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].symbol.name, "one");
+    assert_eq!(&*roots[0].symbol.path.path, Path::new("main.rs"));
+    assert_eq!(roots[0].detail.as_deref(), Some("fn one()"));
+
+    let calls = project_b
+        .update(cx_b, |p, cx| {
+            p.hierarchy_children(&roots[0], HierarchyDirection::IncomingCalls, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].item.symbol.name, "main");
+    assert_eq!(
+        calls[0]
+            .ranges
+            .iter()
+            .map(|range| range.start.0..range.end.0)
+            .collect::<Vec<_>>(),
+        vec![PointUtf16::new(1, 4)..PointUtf16::new(1, 7)]
+    );
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_lsp_type_hierarchy(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root-1",
+            json!({
+                "main.rs": "struct Base;\nstruct Derived;\n",
+            }),
+        )
+        .await;
+
+    // Set up a fake language server.
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_language_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: serde_json::from_value(json!({ "typeHierarchyProvider": {} })).unwrap(),
+            ..Default::default()
+        }))
+        .await;
+    client_a.language_registry().add(Arc::new(language));
+
+    let (project_a, worktree_id) = client_a.build_local_project("/root-1", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    // Open the file as the guest
+    let open_buffer = project_b.update(cx_b, |p, cx| p.open_buffer((worktree_id, "main.rs"), cx));
+    let buffer_b = cx_b.executor().spawn(open_buffer).await.unwrap();
+
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    let uri = lsp::Url::from_file_path("/root-1/main.rs").unwrap();
+    let type_hierarchy_item = |name: &str, line: u32| lsp::TypeHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::STRUCT,
+        tags: None,
+        detail: Some(format!("struct {name}")),
+        uri: uri.clone(),
+        range: lsp::Range::new(
+            lsp::Position::new(line, 0),
+            lsp::Position::new(line, 8 + name.len() as u32),
+        ),
+        selection_range: lsp::Range::new(
+            lsp::Position::new(line, 7),
+            lsp::Position::new(line, 7 + name.len() as u32),
+        ),
+        data: Some(json!({ "name": name })),
+    };
+    fake_language_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>({
+        let derived = type_hierarchy_item("Derived", 1);
+        move |params, _| {
+            let derived = derived.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(1, 8)
+                );
+                Ok(Some(vec![derived]))
+            }
+        }
+    });
+    fake_language_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>({
+        let base = type_hierarchy_item("Base", 0);
+        let uri = uri.clone();
+        move |params, _| {
+            let base = base.clone();
+            let uri = uri.clone();
+            async move {
+                // The host points the item at the symbol, whatever the guest sent.
+                assert_eq!(params.item.uri, uri);
+                assert_eq!(
+                    params.item.selection_range,
+                    lsp::Range::new(lsp::Position::new(1, 7), lsp::Position::new(1, 14))
+                );
+                Ok(Some(vec![base]))
+            }
+        }
+    });
+
+    // Request the supertypes of `Derived` as the guest.
+    let roots = project_b
+        .update(cx_b, |p, cx| {
+            p.prepare_type_hierarchy(&buffer_b, Point::new(1, 8), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].symbol.name, "Derived");
+    assert_eq!(roots[0].detail.as_deref(), Some("struct Derived"));
+
+    let supertypes = project_b
+        .update(cx_b, |p, cx| {
+            p.hierarchy_children(&roots[0], HierarchyDirection::Supertypes, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].item.symbol.name, "Base");
+    assert_eq!(&*supertypes[0].item.symbol.path.path, Path::new("main.rs"));
+
+    // A guest can't make the host's language server look at files outside of the project.
+    let symbol = &roots[0].symbol;
+    let mut lsp_item = serde_json::to_value(type_hierarchy_item("Derived", 1)).unwrap();
+    lsp_item["uri"] = json!("file:///etc/passwd");
+    lsp_item["selectionRange"] = json!({
+        "start": { "line": 0, "character": 0 },
+        "end": { "line": 0, "character": 4 },
+    });
+    let response = client_b
+        .client()
+        .request(rpc::proto::GetHierarchyChildren {
+            project_id,
+            item: Some(rpc::proto::HierarchyItem {
+                symbol: Some(rpc::proto::Symbol {
+                    language_server_name: symbol.language_server_name.0.to_string(),
+                    source_worktree_id: symbol.source_worktree_id.to_proto(),
+                    worktree_id: symbol.path.worktree_id.to_proto(),
+                    path: symbol.path.path.to_string_lossy().to_string(),
+                    name: symbol.name.clone(),
+                    kind: serde_json::to_value(symbol.kind).unwrap().as_i64().unwrap() as i32,
+                    start: Some(rpc::proto::PointUtf16 {
+                        row: symbol.range.start.0.row,
+                        column: symbol.range.start.0.column,
+                    }),
+                    end: Some(rpc::proto::PointUtf16 {
+                        row: symbol.range.end.0.row,
+                        column: symbol.range.end.0.column,
+                    }),
+                    signature: symbol.signature.to_vec(),
+                }),
+                detail: None,
+                lsp_item: serde_json::to_vec(&lsp_item).unwrap(),
+            }),
+            direction: rpc::proto::get_hierarchy_children::Direction::Supertypes as i32,
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.calls.len(), 1);
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_project_symbols(
     executor: BackgroundExecutor,
@@ -39,19 +39,24 @@ pub struct CommandPalette {
 
 impl CommandPalette {
     fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
-        workspace.register_action(|workspace, _: &Toggle, cx| {
-            let Some(previous_focus_handle) = cx.focused() else {
-                return;
-            };
-            let telemetry = workspace.client().telemetry().clone();
-            workspace.toggle_modal(cx, move |cx| {
-                CommandPalette::new(previous_focus_handle, telemetry, cx)
-            });
+        workspace.register_action(|workspace, _: &Toggle, cx| Self::toggle(workspace, "", cx));
+    }
+
+    /// Opens the command palette with `query` already typed into it, or closes it.
+    pub fn toggle(workspace: &mut Workspace, query: &str, cx: &mut ViewContext<Workspace>) {
+        let Some(previous_focus_handle) = cx.focused() else {
+            return;
+        };
+        let telemetry = workspace.client().telemetry().clone();
+        let query = query.to_string();
+        workspace.toggle_modal(cx, move |cx| {
+            CommandPalette::new(previous_focus_handle, &query, telemetry, cx)
         });
     }
 
     fn new(
         previous_focus_handle: FocusHandle,
+        query: &str,
         telemetry: Arc<Telemetry>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
@@ -85,6 +90,9 @@ impl CommandPalette {
         );
 
         let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        if !query.is_empty() {
+            picker.update(cx, |picker, cx| picker.set_query(query, cx));
+        }
         Self { picker }
     }
 }
@@ -167,39 +175,41 @@ impl CommandPaletteDelegate {
     ) {
         self.updating_matches.take();
 
-        let mut intercept_result =
+        let mut intercept_results =
             if let Some(interceptor) = cx.try_global::<CommandPaletteInterceptor>() {
                 (interceptor.0)(&query, cx)
             } else {
-                None
+                Vec::new()
             };
 
         if parse_zed_link(&query).is_some() {
-            intercept_result = Some(CommandInterceptResult {
+            intercept_results = vec![CommandInterceptResult {
                 action: OpenZedUrl { url: query.clone() }.boxed_clone(),
                 string: query.clone(),
                 positions: vec![],
-            })
+            }]
         }
 
-        if let Some(CommandInterceptResult {
-            action,
-            string,
-            positions,
-        }) = intercept_result
-        {
+        for (ix, intercept_result) in intercept_results.into_iter().enumerate() {
+            let CommandInterceptResult {
+                action,
+                string,
+                positions,
+            } = intercept_result;
+            // the results before this one were intercepted as well, so they are kept
             if let Some(idx) = matches
                 .iter()
+                .skip(ix)
                 .position(|m| commands[m.candidate_id].action.type_id() == action.type_id())
             {
-                matches.remove(idx);
+                matches.remove(ix + idx);
             }
             commands.push(Command {
                 name: string.clone(),
                 action,
             });
             matches.insert(
-                0,
+                ix,
                 StringMatch {
                     candidate_id: commands.len() - 1,
                     string,
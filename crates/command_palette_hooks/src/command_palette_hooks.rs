@@ -11,8 +11,10 @@ pub struct CommandPaletteFilter {
 
 impl Global for CommandPaletteFilter {}
 
+/// Turns the query typed into the command palette into commands that are listed
+/// first, such as a vim command and the completions of its arguments.
 pub struct CommandPaletteInterceptor(
-    pub Box<dyn Fn(&str, &AppContext) -> Option<CommandInterceptResult>>,
+    pub Box<dyn Fn(&str, &AppContext) -> Vec<CommandInterceptResult>>,
 );
 
 impl Global for CommandPaletteInterceptor {}
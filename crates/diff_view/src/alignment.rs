@@ -0,0 +1,226 @@
+use git::diff::{BufferDiff, DiffHunkStatus};
+use similar::{ChangeTag, TextDiff};
+use std::ops::Range;
+use text::{BufferSnapshot, Point};
+
+/// A hunk of a side-by-side diff, with the rows it spans on both sides.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AlignedHunk {
+    /// The rows of the hunk in the base text.
+    pub base_rows: Range<u32>,
+    /// The rows of the hunk in the buffer.
+    pub buffer_rows: Range<u32>,
+    /// The byte ranges of the words that changed in the base text.
+    pub base_word_ranges: Vec<Range<usize>>,
+    /// The byte ranges of the words that changed in the buffer.
+    pub buffer_word_ranges: Vec<Range<usize>>,
+}
+
+impl AlignedHunk {
+    /// Returns the number of blank rows to insert after the hunk in the base text
+    /// and in the buffer, so that the rows following the hunk line up.
+    pub fn padding(&self) -> (u32, u32) {
+        let base_len = self.base_rows.end - self.base_rows.start;
+        let buffer_len = self.buffer_rows.end - self.buffer_rows.start;
+        (
+            buffer_len.saturating_sub(base_len),
+            base_len.saturating_sub(buffer_len),
+        )
+    }
+}
+
+/// Returns the hunks of `diff`, which was computed between `base_text` and
+/// `buffer`, with their rows on both sides and the words that changed in
+/// modified hunks.
+pub fn align_hunks(
+    diff: &BufferDiff,
+    base_text: &str,
+    buffer: &BufferSnapshot,
+) -> Vec<AlignedHunk> {
+    let range = buffer.anchor_before(0)..buffer.anchor_after(buffer.len());
+
+    // Hunks are ordered in the same way in the base text and in the buffer, so
+    // rows in the base text can be counted incrementally.
+    let mut base_offset = 0;
+    let mut base_row = 0;
+    diff.hunks_intersecting_range(range, buffer)
+        .map(|hunk| {
+            let base_range = hunk.diff_base_byte_range.clone();
+            base_row += newline_count(&base_text[base_offset..base_range.start]);
+            base_offset = base_range.start;
+
+            let base_hunk_text = &base_text[base_range.clone()];
+            let mut base_end_row = base_row + newline_count(base_hunk_text);
+            if !base_hunk_text.is_empty() && !base_hunk_text.ends_with('\n') {
+                base_end_row += 1;
+            }
+
+            let mut aligned_hunk = AlignedHunk {
+                base_rows: base_row..base_end_row,
+                buffer_rows: hunk.buffer_range.clone(),
+                ..Default::default()
+            };
+
+            if hunk.status() == DiffHunkStatus::Modified {
+                let buffer_range = offset_range_for_rows(&hunk.buffer_range, buffer);
+                let buffer_hunk_text = buffer
+                    .text_for_range(buffer_range.clone())
+                    .collect::<String>();
+                let (base_word_ranges, buffer_word_ranges) =
+                    changed_words(base_hunk_text, &buffer_hunk_text);
+                aligned_hunk.base_word_ranges = base_word_ranges
+                    .into_iter()
+                    .map(|range| base_range.start + range.start..base_range.start + range.end)
+                    .collect();
+                aligned_hunk.buffer_word_ranges = buffer_word_ranges
+                    .into_iter()
+                    .map(|range| buffer_range.start + range.start..buffer_range.start + range.end)
+                    .collect();
+            }
+
+            aligned_hunk
+        })
+        .collect()
+}
+
+/// Returns the byte range spanned by the given rows of the buffer.
+pub fn offset_range_for_rows(rows: &Range<u32>, buffer: &BufferSnapshot) -> Range<usize> {
+    let offset_for_row = |row: u32| {
+        if row > buffer.max_point().row {
+            buffer.len()
+        } else {
+            buffer.point_to_offset(Point::new(row, 0))
+        }
+    };
+    offset_for_row(rows.start)..offset_for_row(rows.end)
+}
+
+/// Returns the byte ranges of the words that differ between `old_text` and
+/// `new_text`, in each of them.
+fn changed_words(old_text: &str, new_text: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let diff = TextDiff::from_words(old_text, new_text);
+    let mut old_ranges = Vec::new();
+    let mut new_ranges = Vec::new();
+    let mut old_offset = 0;
+    let mut new_offset = 0;
+    for change in diff.iter_all_changes() {
+        let len = change.value().len();
+        match change.tag() {
+            ChangeTag::Equal => {
+                old_offset += len;
+                new_offset += len;
+            }
+            ChangeTag::Delete => {
+                push_range(&mut old_ranges, old_offset..old_offset + len);
+                old_offset += len;
+            }
+            ChangeTag::Insert => {
+                push_range(&mut new_ranges, new_offset..new_offset + len);
+                new_offset += len;
+            }
+        }
+    }
+    (old_ranges, new_ranges)
+}
+
+fn push_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    if let Some(last_range) = ranges.last_mut() {
+        if last_range.end == range.start {
+            last_range.end = range.end;
+            return;
+        }
+    }
+    ranges.push(range);
+}
+
+fn newline_count(text: &str) -> u32 {
+    text.bytes().filter(|byte| *byte == b'\n').count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text::{Buffer, BufferId};
+    use unindent::Unindent as _;
+
+    #[test]
+    fn test_align_hunks() {
+        let base_text = "
+            fn main() {
+                let x = 1;
+                println!(\"{x}\");
+                one();
+                two();
+            }
+        "
+        .unindent();
+        let buffer_text = "
+            fn main() {
+                let y = 1;
+                println!(\"{x}\");
+                three();
+                four();
+                five();
+            }
+        "
+        .unindent();
+
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), buffer_text);
+        let snapshot = buffer.snapshot();
+        let mut diff = BufferDiff::new();
+        smol::block_on(diff.update(&base_text, &snapshot));
+        let hunks = align_hunks(&diff, &base_text, &snapshot);
+
+        assert_eq!(
+            hunks
+                .iter()
+                .map(|hunk| (
+                    hunk.base_rows.clone(),
+                    hunk.buffer_rows.clone(),
+                    hunk.padding()
+                ))
+                .collect::<Vec<_>>(),
+            [(1..2, 1..2, (0, 0)), (3..5, 3..6, (1, 0))]
+        );
+
+        let base_words = |hunk: &AlignedHunk| {
+            hunk.base_word_ranges
+                .iter()
+                .map(|range| &base_text[range.clone()])
+                .collect::<Vec<_>>()
+        };
+        let buffer_words = |hunk: &AlignedHunk| {
+            hunk.buffer_word_ranges
+                .iter()
+                .map(|range| snapshot.text_for_range(range.clone()).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(base_words(&hunks[0]), ["x"]);
+        assert_eq!(buffer_words(&hunks[0]), ["y"]);
+    }
+
+    #[test]
+    fn test_align_added_and_removed_hunks() {
+        let base_text = "a\nb\nc\nd\n".to_string();
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), "a\nnew\nb\nd\n".to_string());
+        let snapshot = buffer.snapshot();
+        let mut diff = BufferDiff::new();
+        smol::block_on(diff.update(&base_text, &snapshot));
+        let hunks = align_hunks(&diff, &base_text, &snapshot);
+
+        assert_eq!(
+            hunks
+                .iter()
+                .map(|hunk| (
+                    hunk.base_rows.clone(),
+                    hunk.buffer_rows.clone(),
+                    hunk.padding()
+                ))
+                .collect::<Vec<_>>(),
+            [(1..1, 1..2, (1, 0)), (2..3, 3..3, (0, 1))]
+        );
+        assert!(hunks
+            .iter()
+            .all(|hunk| hunk.base_word_ranges.is_empty() && hunk.buffer_word_ranges.is_empty()));
+    }
+}
@@ -0,0 +1,188 @@
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render, View,
+    WeakView,
+};
+use language::Buffer;
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{DiffBase, DiffView};
+
+/// A modal that picks the open buffer to compare a buffer with.
+pub struct BufferPicker {
+    picker: View<Picker<BufferPickerDelegate>>,
+}
+
+impl BufferPicker {
+    pub fn new(
+        buffer: Model<Buffer>,
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut candidates = project
+            .read(cx)
+            .opened_buffers()
+            .into_iter()
+            .filter(|candidate| candidate != &buffer)
+            .map(|candidate| {
+                let path = match candidate.read(cx).file() {
+                    Some(file) => file.full_path(cx).to_string_lossy().to_string(),
+                    None => "untitled".to_string(),
+                };
+                (candidate, path)
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+        let delegate = BufferPickerDelegate {
+            buffer_picker: cx.view().downgrade(),
+            workspace,
+            buffer,
+            string_candidates: candidates
+                .iter()
+                .enumerate()
+                .map(|(id, (_, path))| StringMatchCandidate::new(id, path.clone()))
+                .collect(),
+            candidates: candidates
+                .into_iter()
+                .map(|(candidate, _)| candidate)
+                .collect(),
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for BufferPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for BufferPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for BufferPicker {}
+impl ModalView for BufferPicker {}
+
+pub struct BufferPickerDelegate {
+    buffer_picker: WeakView<BufferPicker>,
+    workspace: WeakView<Workspace>,
+    buffer: Model<Buffer>,
+    candidates: Vec<Model<Buffer>>,
+    string_candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for BufferPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Compare with buffer...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let base_buffer = self.candidates[mat.candidate_id].clone();
+            let buffer = self.buffer.clone();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    DiffView::deploy(buffer, DiffBase::Buffer(base_buffer), workspace, cx);
+                })
+                .log_err();
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.buffer_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.string_candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
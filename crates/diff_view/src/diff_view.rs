@@ -0,0 +1,775 @@
+mod alignment;
+mod buffer_picker;
+
+use alignment::{align_hunks, offset_range_for_rows, AlignedHunk};
+use anyhow::Result;
+use buffer_picker::BufferPicker;
+use collections::HashSet;
+use editor::{
+    display_map::{BlockContext, BlockDisposition, BlockId, BlockProperties, BlockStyle},
+    Editor, EditorEvent,
+};
+use git::diff::BufferDiff;
+use gpui::{
+    actions, AnyElement, AnyView, AppContext, EventEmitter, FocusHandle, FocusableView,
+    HighlightStyle, Hsla, Model, Render, Subscription, Task, View, VisualContext,
+};
+use language::{language_settings::SoftWrap, Buffer, BufferId};
+use project::Project;
+use std::{
+    any::{Any, TypeId},
+    ops::Range,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+use text::Point;
+use ui::prelude::*;
+use workspace::{
+    item::{Item, ItemEvent, ItemHandle},
+    ItemNavHistory, Workspace,
+};
+
+actions!(diff_view, [DiffWithHead, DiffWithIndex, DiffWithBuffer]);
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(DiffView::register).detach();
+}
+
+/// What the buffer shown in a [`DiffView`] is compared with.
+#[derive(Clone)]
+pub enum DiffBase {
+    /// The contents of the buffer's file in the HEAD commit.
+    Head,
+    /// The contents of the buffer's file in the git index.
+    Index,
+    /// The contents of another buffer.
+    Buffer(Model<Buffer>),
+    /// A fixed text, such as a past version of the buffer's file.
+    Text { title: SharedString, text: Arc<str> },
+}
+
+enum DiffLineHighlight {}
+enum DiffWordHighlight {}
+
+/// Shows a buffer next to the text it is compared with, with the changed lines
+/// and words highlighted and the unchanged lines aligned.
+pub struct DiffView {
+    project: Model<Project>,
+    buffer: Model<Buffer>,
+    base: DiffBase,
+    base_buffer: Model<Buffer>,
+    base_editor: View<Editor>,
+    editor: View<Editor>,
+    base_blocks: HashSet<BlockId>,
+    blocks: HashSet<BlockId>,
+    error: Option<SharedString>,
+    update_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DiffView {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &DiffWithHead, cx| {
+            if let Some(buffer) = active_buffer(workspace, cx) {
+                Self::deploy(buffer, DiffBase::Head, workspace, cx);
+            }
+        });
+        workspace.register_action(|workspace, _: &DiffWithIndex, cx| {
+            if let Some(buffer) = active_buffer(workspace, cx) {
+                Self::deploy(buffer, DiffBase::Index, workspace, cx);
+            }
+        });
+        workspace.register_action(|workspace, _: &DiffWithBuffer, cx| {
+            if let Some(buffer) = active_buffer(workspace, cx) {
+                let project = workspace.project().clone();
+                let workspace_handle = cx.view().downgrade();
+                workspace.toggle_modal(cx, move |cx| {
+                    BufferPicker::new(buffer, project, workspace_handle, cx)
+                });
+            }
+        });
+    }
+
+    /// Opens a diff of the given buffer in the active pane.
+    pub fn deploy(
+        buffer: Model<Buffer>,
+        base: DiffBase,
+        workspace: &mut Workspace,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let project = workspace.project().clone();
+        let diff_view = cx.new_view(|cx| DiffView::new(buffer, base, project, cx));
+        workspace.add_item_to_active_pane(Box::new(diff_view), cx);
+    }
+
+    pub fn new(
+        buffer: Model<Buffer>,
+        base: DiffBase,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let base_buffer = match &base {
+            DiffBase::Buffer(base_buffer) => base_buffer.clone(),
+            DiffBase::Head | DiffBase::Index | DiffBase::Text { .. } => {
+                let language = buffer.read(cx).language().cloned();
+                let language_registry = project.read(cx).languages().clone();
+                cx.new_model(|cx| {
+                    let mut base_buffer =
+                        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), "");
+                    base_buffer.set_language_registry(language_registry);
+                    base_buffer.set_language(language, cx);
+                    base_buffer
+                })
+            }
+        };
+
+        // The text from git, or a fixed text, is only shown for reference, so
+        // it can't be edited and doesn't belong to the project.
+        let base_is_buffer = matches!(base, DiffBase::Buffer(_));
+        let base_editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_buffer(
+                base_buffer.clone(),
+                base_is_buffer.then(|| project.clone()),
+                cx,
+            );
+            editor.set_read_only(!base_is_buffer);
+            editor.set_soft_wrap_mode(SoftWrap::None, cx);
+            editor
+        });
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_buffer(buffer.clone(), Some(project.clone()), cx);
+            editor.set_soft_wrap_mode(SoftWrap::None, cx);
+            editor
+        });
+
+        let subscriptions = vec![
+            cx.subscribe(&buffer, |this, buffer, event, cx| match event {
+                language::Event::Edited => this.update_diff(true, cx),
+                language::Event::DiffBaseChanged => {
+                    if let DiffBase::Index = this.base {
+                        this.update_diff(false, cx);
+                    }
+                }
+                language::Event::LanguageChanged => {
+                    if !matches!(this.base, DiffBase::Buffer(_)) {
+                        let language = buffer.read(cx).language().cloned();
+                        this.base_buffer
+                            .update(cx, |base_buffer, cx| base_buffer.set_language(language, cx));
+                    }
+                }
+                _ => {}
+            }),
+            cx.subscribe(&base_buffer, |this, _, event, cx| {
+                if let (language::Event::Edited, DiffBase::Buffer(_)) = (event, &this.base) {
+                    this.update_diff(true, cx);
+                }
+            }),
+            cx.subscribe(&project, |this, _, event, cx| {
+                if let (project::Event::WorktreeUpdatedGitRepositories, DiffBase::Head) =
+                    (event, &this.base)
+                {
+                    this.update_diff(false, cx);
+                }
+            }),
+            cx.subscribe(&editor, |this, editor, event: &EditorEvent, cx| {
+                if let EditorEvent::ScrollPositionChanged { .. } = event {
+                    this.sync_scroll_position(&editor, cx);
+                }
+                cx.emit(event.clone());
+            }),
+            cx.subscribe(
+                &base_editor,
+                |this, base_editor, event: &EditorEvent, cx| {
+                    if let EditorEvent::ScrollPositionChanged { .. } = event {
+                        this.sync_scroll_position(&base_editor, cx);
+                    }
+                },
+            ),
+        ];
+
+        let mut this = Self {
+            project,
+            buffer,
+            base,
+            base_buffer,
+            base_editor,
+            editor,
+            base_blocks: HashSet::default(),
+            blocks: HashSet::default(),
+            error: None,
+            update_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.update_diff(false, cx);
+        this
+    }
+
+    /// The editor of the text the buffer is compared with.
+    pub fn base_editor(&self) -> &View<Editor> {
+        &self.base_editor
+    }
+
+    /// The editor of the buffer.
+    pub fn editor(&self) -> &View<Editor> {
+        &self.editor
+    }
+
+    fn update_diff(&mut self, debounce: bool, cx: &mut ViewContext<Self>) {
+        // When comparing with another buffer, the hunks are anchored in a
+        // snapshot of it taken at the same time as its text.
+        let mut base_snapshot = None;
+        let base_text = match &self.base {
+            DiffBase::Head => self.project.read(cx).load_head_text(&self.buffer, cx),
+            DiffBase::Index => {
+                Task::ready(Ok(self.buffer.read(cx).diff_base().map(str::to_string)))
+            }
+            DiffBase::Buffer(base_buffer) => {
+                let snapshot = base_buffer.read(cx).text_snapshot();
+                let text = snapshot.text();
+                base_snapshot = Some(snapshot);
+                Task::ready(Ok(Some(text)))
+            }
+            DiffBase::Text { text, .. } => Task::ready(Ok(Some(text.to_string()))),
+        };
+        let snapshot = self.buffer.read(cx).text_snapshot();
+
+        self.update_task = cx.spawn(|this, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            }
+
+            let base_text = match base_text.await {
+                // A file that isn't in git yet is compared with an empty file.
+                Ok(base_text) => base_text.unwrap_or_default(),
+                Err(error) => {
+                    this.update(&mut cx, |this, cx| {
+                        this.error = Some(format!("{error:#}").into());
+                        cx.notify();
+                    })
+                    .ok();
+                    return;
+                }
+            };
+
+            let hunks = cx
+                .background_executor()
+                .spawn({
+                    let base_text = base_text.clone();
+                    let snapshot = snapshot.clone();
+                    async move {
+                        let mut diff = BufferDiff::new();
+                        diff.update(&base_text, &snapshot).await;
+                        align_hunks(&diff, &base_text, &snapshot)
+                    }
+                })
+                .await;
+
+            this.update(&mut cx, |this, cx| {
+                let base_snapshot = base_snapshot.unwrap_or_else(|| {
+                    this.base_buffer.update(cx, |base_buffer, cx| {
+                        if base_buffer.text() != base_text {
+                            base_buffer.set_text(base_text, cx);
+                        }
+                        base_buffer.text_snapshot()
+                    })
+                });
+                this.set_hunks(&hunks, &base_snapshot, &snapshot, cx);
+            })
+            .ok();
+        });
+    }
+
+    fn set_hunks(
+        &mut self,
+        hunks: &[AlignedHunk],
+        base_snapshot: &text::BufferSnapshot,
+        snapshot: &text::BufferSnapshot,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.error = None;
+
+        let status = cx.theme().status();
+        let (deleted_line, deleted_word) = (status.deleted_background, word_color(status.deleted));
+        let (created_line, created_word) = (status.created_background, word_color(status.created));
+
+        update_editor(
+            &self.base_editor,
+            base_snapshot,
+            hunks.iter().map(|hunk| {
+                (
+                    hunk.base_rows.clone(),
+                    hunk.base_word_ranges.as_slice(),
+                    hunk.padding().0,
+                )
+            }),
+            (deleted_line, deleted_word),
+            &mut self.base_blocks,
+            cx,
+        );
+        update_editor(
+            &self.editor,
+            snapshot,
+            hunks.iter().map(|hunk| {
+                (
+                    hunk.buffer_rows.clone(),
+                    hunk.buffer_word_ranges.as_slice(),
+                    hunk.padding().1,
+                )
+            }),
+            (created_line, created_word),
+            &mut self.blocks,
+            cx,
+        );
+        cx.notify();
+    }
+
+    fn sync_scroll_position(&mut self, source: &View<Editor>, cx: &mut ViewContext<Self>) {
+        let target = if source == &self.editor {
+            &self.base_editor
+        } else {
+            &self.editor
+        };
+        let position = source.update(cx, |editor, cx| editor.scroll_position(cx));
+        target.update(cx, |editor, cx| {
+            if editor.scroll_position(cx) != position {
+                editor.set_scroll_position(position, cx);
+            }
+        });
+    }
+
+    fn base_title(&self, cx: &AppContext) -> SharedString {
+        match &self.base {
+            DiffBase::Head => "HEAD".into(),
+            DiffBase::Index => "Index".into(),
+            DiffBase::Buffer(base_buffer) => buffer_title(base_buffer, cx),
+            DiffBase::Text { title, .. } => title.clone(),
+        }
+    }
+}
+
+/// Highlights the changed lines and words of one side of the diff, and pads it
+/// so that the lines after each hunk are aligned with the other side.
+fn update_editor<'a>(
+    editor: &View<Editor>,
+    buffer: &text::BufferSnapshot,
+    hunks: impl Iterator<Item = (Range<u32>, &'a [Range<usize>], u32)>,
+    (line_color, word_color): (Hsla, Hsla),
+    blocks: &mut HashSet<BlockId>,
+    cx: &mut WindowContext,
+) {
+    editor.update(cx, |editor, cx| {
+        let multibuffer = editor.buffer().read(cx).snapshot(cx);
+        let Some((&excerpt_id, _, _)) = multibuffer.as_singleton() else {
+            return;
+        };
+        let anchor_range = |range: Range<usize>| {
+            multibuffer.anchor_in_excerpt(excerpt_id, buffer.anchor_before(range.start))
+                ..multibuffer.anchor_in_excerpt(excerpt_id, buffer.anchor_before(range.end))
+        };
+
+        let max_row = buffer.max_point().row;
+        let mut line_ranges = Vec::new();
+        let mut word_ranges = Vec::new();
+        let mut new_blocks = Vec::new();
+        for (rows, hunk_word_ranges, padding) in hunks {
+            // Word highlights are cut out of the line highlights, since they
+            // would be drawn on top of each other otherwise.
+            let range = offset_range_for_rows(&rows, buffer);
+            let mut start = range.start;
+            for word_range in hunk_word_ranges {
+                line_ranges.push(start..word_range.start);
+                word_ranges.push(word_range.clone());
+                start = word_range.end;
+            }
+            line_ranges.push(start..range.end);
+
+            if padding > 0 {
+                let (position, disposition) = if rows.end > max_row {
+                    (buffer.anchor_after(buffer.len()), BlockDisposition::Below)
+                } else {
+                    (
+                        buffer.anchor_before(Point::new(rows.end, 0)),
+                        BlockDisposition::Above,
+                    )
+                };
+                let position = multibuffer.anchor_in_excerpt(excerpt_id, position);
+
+                let mut remaining_padding = padding;
+                while remaining_padding > 0 {
+                    let height = remaining_padding.min(u8::MAX as u32) as u8;
+                    remaining_padding -= height as u32;
+                    new_blocks.push(BlockProperties {
+                        position,
+                        height,
+                        style: BlockStyle::Flex,
+                        render: Arc::new(|cx: &mut BlockContext| {
+                            div()
+                                .size_full()
+                                .bg(cx.theme().colors().editor_subheader_background)
+                                .into_any_element()
+                        }),
+                        disposition,
+                    });
+                }
+            }
+        }
+
+        let line_ranges = line_ranges
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .map(anchor_range)
+            .collect();
+        let word_ranges = word_ranges.into_iter().map(anchor_range).collect();
+        editor.highlight_text::<DiffLineHighlight>(
+            line_ranges,
+            HighlightStyle {
+                background_color: Some(line_color),
+                ..Default::default()
+            },
+            cx,
+        );
+        editor.highlight_text::<DiffWordHighlight>(
+            word_ranges,
+            HighlightStyle {
+                background_color: Some(word_color),
+                ..Default::default()
+            },
+            cx,
+        );
+
+        editor.remove_blocks(std::mem::take(blocks), None, cx);
+        blocks.extend(editor.insert_blocks(new_blocks, None, cx));
+    });
+}
+
+fn word_color(mut color: Hsla) -> Hsla {
+    color.fade_out(0.6);
+    color
+}
+
+fn active_buffer(workspace: &Workspace, cx: &AppContext) -> Option<Model<Buffer>> {
+    workspace
+        .active_item_as::<Editor>(cx)?
+        .read(cx)
+        .buffer()
+        .read(cx)
+        .as_singleton()
+}
+
+fn buffer_title(buffer: &Model<Buffer>, cx: &AppContext) -> SharedString {
+    match buffer.read(cx).file() {
+        Some(file) => file.file_name(cx).to_string_lossy().to_string().into(),
+        None => "untitled".into(),
+    }
+}
+
+impl EventEmitter<EditorEvent> for DiffView {}
+
+impl FocusableView for DiffView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for DiffView {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.base_editor
+            .update(cx, |editor, cx| editor.deactivated(cx));
+        self.editor.update(cx, |editor, cx| editor.deactivated(cx));
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        let path = self
+            .buffer
+            .read(cx)
+            .file()
+            .map(|file| file.full_path(cx).to_string_lossy().to_string())
+            .unwrap_or_else(|| "untitled".to_string());
+        Some(format!("{} ↔ {}", self.base_title(cx), path).into())
+    }
+
+    fn tab_content(
+        &self,
+        _detail: Option<usize>,
+        selected: bool,
+        cx: &WindowContext,
+    ) -> AnyElement {
+        Label::new(format!(
+            "{} ↔ {}",
+            self.base_title(cx),
+            buffer_title(&self.buffer, cx)
+        ))
+        .color(if selected {
+            Color::Default
+        } else {
+            Color::Muted
+        })
+        .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("diff view")
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::Item),
+    ) {
+        self.editor.for_each_project_item(cx, f);
+        if let DiffBase::Buffer(_) = self.base {
+            self.base_editor.for_each_project_item(cx, f);
+        }
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        false
+    }
+
+    fn set_nav_history(&mut self, nav_history: ItemNavHistory, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.buffer.read(cx).is_dirty()
+            || matches!(self.base, DiffBase::Buffer(_)) && self.base_buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.buffer.read(cx).has_conflict()
+            || matches!(self.base, DiffBase::Buffer(_)) && self.base_buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn save(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let save = self.editor.save(project.clone(), cx);
+        let base_save = match self.base {
+            DiffBase::Buffer(_) => Some(self.base_editor.save(project, cx)),
+            DiffBase::Head | DiffBase::Index | DiffBase::Text { .. } => None,
+        };
+        cx.spawn(|_, _| async move {
+            save.await?;
+            if let Some(base_save) = base_save {
+                base_save.await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn save_as(
+        &mut self,
+        _: Model<Project>,
+        _: PathBuf,
+        _: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        unreachable!()
+    }
+
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        self.editor.reload(project, cx)
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+}
+
+impl Render for DiffView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let base_title = self.base_title(cx);
+        let title = buffer_title(&self.buffer, cx);
+        let column = |title: SharedString, editor: View<Editor>| {
+            v_flex()
+                .flex_1()
+                .h_full()
+                .child(
+                    h_flex()
+                        .px_2()
+                        .py_1()
+                        .border_b_1()
+                        .border_color(cx.theme().colors().border)
+                        .child(Label::new(title).color(Color::Muted)),
+                )
+                .child(div().flex_1().child(editor))
+        };
+
+        v_flex()
+            .key_context("DiffView")
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    h_flex()
+                        .px_2()
+                        .py_1()
+                        .child(Label::new(error).color(Color::Error)),
+                )
+            })
+            .child(
+                h_flex()
+                    .flex_1()
+                    .w_full()
+                    .child(column(base_title, self.base_editor.clone()))
+                    .child(
+                        div()
+                            .h_full()
+                            .border_l_1()
+                            .border_color(cx.theme().colors().border),
+                    )
+                    .child(column(title, self.editor.clone())),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use unindent::Unindent as _;
+
+    #[gpui::test]
+    async fn test_diff_with_buffer(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/test",
+            json!({
+                "old.txt": "
+                    one
+                    two
+                    three
+                    four
+                "
+                .unindent(),
+                "new.txt": "
+                    one
+                    2
+                    three
+                    3.5
+                    3.75
+                    four
+                "
+                .unindent(),
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/test".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+
+        let base_buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/test/old.txt", cx)
+            })
+            .await
+            .unwrap();
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/test/new.txt", cx)
+            })
+            .await
+            .unwrap();
+
+        let diff_view = cx.new_view(|cx| {
+            DiffView::new(
+                buffer.clone(),
+                DiffBase::Buffer(base_buffer.clone()),
+                project,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        // The padding below the hunk that adds two lines aligns the end of both sides.
+        let max_rows = |cx: &mut VisualTestContext| {
+            diff_view.update(cx, |diff_view, cx| {
+                (
+                    diff_view
+                        .base_editor()
+                        .update(cx, |editor, cx| editor.max_point(cx).row()),
+                    diff_view
+                        .editor()
+                        .update(cx, |editor, cx| editor.max_point(cx).row()),
+                )
+            })
+        };
+        assert_eq!(max_rows(cx), (6, 6));
+
+        // Editing either buffer updates the diff.
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+        cx.executor().advance_clock(UPDATE_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(max_rows(cx), (7, 7));
+
+        base_buffer.update(cx, |buffer, cx| {
+            let len = buffer.len();
+            buffer.edit([(len..len, "five\nsix\nseven\n")], None, cx)
+        });
+        cx.executor().advance_clock(UPDATE_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(max_rows(cx), (10, 10));
+
+        // Scrolling one side scrolls the other.
+        diff_view.update(cx, |diff_view, cx| {
+            diff_view.editor().update(cx, |editor, cx| {
+                editor.set_scroll_position(gpui::Point::new(0., 3.), cx)
+            });
+        });
+        cx.run_until_parked();
+        let base_scroll_position = diff_view.update(cx, |diff_view, cx| {
+            diff_view
+                .base_editor()
+                .update(cx, |editor, cx| editor.scroll_position(cx))
+        });
+        assert_eq!(base_scroll_position, gpui::Point::new(0., 3.));
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            release_channel::init("0.0.0", cx);
+            language::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            editor::init(cx);
+            crate::init(cx);
+        });
+    }
+}
@@ -66,6 +66,11 @@ pub struct FoldAt {
     pub buffer_row: u32,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct FoldAtLevel {
+    pub level: u32,
+}
+
 #[derive(PartialEq, Clone, Deserialize, Default)]
 pub struct UnfoldAt {
     pub buffer_row: u32,
@@ -108,6 +113,7 @@ impl_actions!(
         ConfirmCodeAction,
         ToggleComments,
         FoldAt,
+        FoldAtLevel,
         UnfoldAt,
         MoveUpByLines,
         MoveDownByLines,
@@ -155,6 +161,8 @@ gpui::actions!(
         ExpandMacroRecursively,
         FindAllReferences,
         Fold,
+        FoldComments,
+        FoldImports,
         FoldSelectedRanges,
         Format,
         GoToDefinition,
@@ -209,12 +217,15 @@ gpui::actions!(
         RestartLanguageServer,
         RevealInFinder,
         ReverseLines,
+        RevertHunk,
         ScrollCursorBottom,
         ScrollCursorCenter,
         ScrollCursorTop,
         SelectAll,
         SelectAllMatches,
         SelectDown,
+        SelectEnclosingClass,
+        SelectEnclosingFunction,
         SelectLargerSyntaxNode,
         SelectLeft,
         SelectLine,
@@ -231,12 +242,16 @@ gpui::actions!(
         SelectUp,
         ShowCharacterPalette,
         ShowCompletions,
+        ShowSignatureHelp,
         ShuffleLines,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        StageHunk,
         Tab,
         TabPrev,
+        ToggleGitBlame,
+        ToggleGitBlameInline,
         ToggleInlayHints,
         ToggleSoftWrap,
         Transpose,
@@ -244,6 +259,7 @@ gpui::actions!(
         UndoSelection,
         UnfoldLines,
         UniqueLinesCaseSensitive,
-        UniqueLinesCaseInsensitive
+        UniqueLinesCaseInsensitive,
+        UnstageHunk
     ]
 );
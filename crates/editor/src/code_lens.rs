@@ -0,0 +1,551 @@
+use crate::{
+    display_map::{
+        BlockContext, BlockDisposition, BlockId, BlockProperties, BlockStyle, RenderBlock,
+    },
+    rust_analyzer_ext, Anchor, Editor, EditorMode, EditorSettings, ToPoint,
+};
+use collections::{BTreeMap, HashMap, HashSet};
+use futures::future;
+use gpui::{
+    div, InteractiveElement, IntoElement, Model, ParentElement, Styled, Task, ViewContext, WeakView,
+};
+use language::{Bias, Buffer, BufferId, Point, ToOffset};
+use project::CodeLens;
+use settings::Settings;
+use std::{mem, ops::Range, sync::Arc, time::Duration};
+use ui::{h_flex, Color, Label, LabelCommon, LabelSize};
+use util::ResultExt;
+
+const CODE_LENS_DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[derive(Default)]
+pub struct CodeLensState {
+    /// The lenses of each buffer, along with the buffer version they were requested for.
+    lenses_by_buffer: HashMap<BufferId, BufferCodeLenses>,
+    blocks: Vec<CodeLensBlock>,
+    refresh_task: Option<Task<Option<()>>>,
+}
+
+struct BufferCodeLenses {
+    version: clock::Global,
+    lenses: Vec<CachedCodeLens>,
+}
+
+struct CachedCodeLens {
+    lens: CodeLens,
+    /// Whether the lens has already been resolved, so that lenses the server could
+    /// not resolve aren't asked for again on every refresh.
+    resolved: bool,
+}
+
+/// A block showing the lenses of a line, along with their titles, which tell whether
+/// the block needs to be replaced when the lenses change.
+struct CodeLensBlock {
+    id: BlockId,
+    position: Anchor,
+    titles: Vec<String>,
+}
+
+/// Forgets the cached lenses of every buffer, e.g. when the language server asks for
+/// them to be refreshed, and requests them again.
+pub fn invalidate_code_lenses(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    editor.code_lens_state.lenses_by_buffer.clear();
+    refresh_code_lenses(editor, cx);
+}
+
+/// Requests the code lenses of the buffers that changed since their lenses were last
+/// requested, resolves the ones in the visible range that come without a command, and
+/// shows them in blocks above the lines they refer to.
+pub fn refresh_code_lenses(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full || !EditorSettings::get_global(cx).code_lens {
+        editor.code_lens_state.refresh_task = None;
+        editor.code_lens_state.lenses_by_buffer.clear();
+        let block_ids = mem::take(&mut editor.code_lens_state.blocks)
+            .into_iter()
+            .map(|block| block.id)
+            .collect::<HashSet<_>>();
+        if !block_ids.is_empty() {
+            editor.remove_blocks(block_ids, None, cx);
+        }
+        return;
+    }
+    if editor.project.is_none() {
+        return;
+    }
+
+    editor.code_lens_state.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(CODE_LENS_DEBOUNCE).await;
+
+        let requests = editor
+            .update(&mut cx, |editor, cx| {
+                let project = editor.project.clone()?;
+                let buffers = editor.buffer.read(cx).all_buffers();
+                let buffer_ids = buffers
+                    .iter()
+                    .map(|buffer| buffer.read(cx).remote_id())
+                    .collect::<HashSet<_>>();
+                let cached_lenses = &mut editor.code_lens_state.lenses_by_buffer;
+                cached_lenses.retain(|buffer_id, _| buffer_ids.contains(buffer_id));
+
+                let requests = buffers
+                    .into_iter()
+                    .filter_map(|buffer| {
+                        let buffer_id = buffer.read(cx).remote_id();
+                        let version = buffer.read(cx).version();
+                        if cached_lenses
+                            .get(&buffer_id)
+                            .map_or(false, |cached| cached.version == version)
+                        {
+                            return None;
+                        }
+                        let lenses =
+                            project.update(cx, |project, cx| project.code_lens(&buffer, cx));
+                        Some(async move { (buffer_id, version, lenses.await) })
+                    })
+                    .collect::<Vec<_>>();
+                Some(requests)
+            })
+            .ok()??;
+
+        let responses = future::join_all(requests).await;
+        let resolve_tasks = editor
+            .update(&mut cx, |editor, cx| {
+                for (buffer_id, version, lenses) in responses {
+                    let Some(lenses) = lenses.log_err() else {
+                        continue;
+                    };
+                    let lenses = lenses
+                        .into_iter()
+                        .map(|lens| CachedCodeLens {
+                            resolved: lens.lsp_lens.command.is_some(),
+                            lens,
+                        })
+                        .collect();
+                    editor
+                        .code_lens_state
+                        .lenses_by_buffer
+                        .insert(buffer_id, BufferCodeLenses { version, lenses });
+                }
+                resolve_visible_code_lenses(editor, cx)
+            })
+            .ok()?;
+
+        let resolved_lenses = future::join_all(resolve_tasks).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                for (buffer_id, version, ix, lens) in resolved_lenses {
+                    let Some(cached) = editor
+                        .code_lens_state
+                        .lenses_by_buffer
+                        .get_mut(&buffer_id)
+                        .filter(|cached| cached.version == version)
+                    else {
+                        continue;
+                    };
+                    if let Some(cached_lens) = cached.lenses.get_mut(ix) {
+                        cached_lens.resolved = true;
+                        if let Some(lens) = lens.log_err() {
+                            cached_lens.lens = lens;
+                        }
+                    }
+                }
+                display_code_lenses(editor, cx)
+            })
+            .ok()
+    }));
+}
+
+/// Resolves the cached lenses that start in the visible part of the editor and haven't
+/// been resolved yet, returning the resolved lenses along with their position in the cache.
+fn resolve_visible_code_lenses(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Vec<Task<(BufferId, clock::Global, usize, anyhow::Result<CodeLens>)>> {
+    let Some(project) = editor.project.clone() else {
+        return Vec::new();
+    };
+
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+    let visible_start = editor.scroll_manager.anchor().anchor.to_point(&snapshot);
+    let visible_end = snapshot.clip_point(
+        visible_start + Point::new(editor.visible_line_count().unwrap_or(0.).ceil() as u32, 0),
+        Bias::Left,
+    );
+    let mut visible_ranges = HashMap::<BufferId, (Model<Buffer>, Vec<Range<usize>>)>::default();
+    for (buffer, range, _) in multi_buffer.range_to_buffer_ranges(visible_start..visible_end, cx) {
+        visible_ranges
+            .entry(buffer.read(cx).remote_id())
+            .or_insert_with(|| (buffer.clone(), Vec::new()))
+            .1
+            .push(range);
+    }
+
+    let mut tasks = Vec::new();
+    for (buffer_id, (buffer, ranges)) in visible_ranges {
+        let Some(cached) = editor.code_lens_state.lenses_by_buffer.get(&buffer_id) else {
+            continue;
+        };
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        for (ix, cached_lens) in cached.lenses.iter().enumerate() {
+            if cached_lens.resolved {
+                continue;
+            }
+            let start = cached_lens.lens.range.start.to_offset(&buffer_snapshot);
+            if !ranges
+                .iter()
+                .any(|range| range.start <= start && start <= range.end)
+            {
+                continue;
+            }
+            let resolve = project.update(cx, |project, cx| {
+                project.resolve_code_lens(&buffer, cached_lens.lens.clone(), cx)
+            });
+            let version = cached.version.clone();
+            tasks.push(
+                cx.background_executor()
+                    .spawn(async move { (buffer_id, version, ix, resolve.await) }),
+            );
+        }
+    }
+    tasks
+}
+
+/// Shows the resolved lenses in blocks above the lines they refer to, only touching the
+/// blocks whose lenses changed.
+fn display_code_lenses(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+
+    let mut lenses_by_row = BTreeMap::<u32, Vec<(Model<Buffer>, CodeLens)>>::new();
+    for buffer in multi_buffer.all_buffers() {
+        let Some(cached) = editor
+            .code_lens_state
+            .lenses_by_buffer
+            .get(&buffer.read(cx).remote_id())
+        else {
+            continue;
+        };
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx) {
+            let context = excerpt_range.context;
+            for CachedCodeLens { lens, .. } in &cached.lenses {
+                if lens.lsp_lens.command.is_none() {
+                    continue;
+                }
+                let start = &lens.range.start;
+                if start.cmp(&context.start, &buffer_snapshot).is_lt()
+                    || start.cmp(&context.end, &buffer_snapshot).is_ge()
+                {
+                    continue;
+                }
+                let row = snapshot
+                    .anchor_in_excerpt(excerpt_id, *start)
+                    .to_point(&snapshot)
+                    .row;
+                lenses_by_row
+                    .entry(row)
+                    .or_default()
+                    .push((buffer.clone(), lens.clone()));
+            }
+        }
+    }
+
+    let mut blocks_to_remove = HashSet::default();
+    let mut old_blocks_by_row = HashMap::<u32, CodeLensBlock>::default();
+    for block in mem::take(&mut editor.code_lens_state.blocks) {
+        let row = block.position.to_point(&snapshot).row;
+        if let Some(duplicate) = old_blocks_by_row.insert(row, block) {
+            blocks_to_remove.insert(duplicate.id);
+        }
+    }
+
+    let editor_handle = cx.view().downgrade();
+    let mut blocks_to_replace = HashMap::default();
+    let mut blocks_to_insert = Vec::new();
+    let mut new_block_titles = Vec::new();
+    for (row, lenses) in lenses_by_row {
+        let titles = lenses
+            .iter()
+            .filter_map(|(_, lens)| Some(lens.lsp_lens.command.as_ref()?.title.clone()))
+            .collect::<Vec<_>>();
+        let render = render_code_lenses(editor_handle.clone(), lenses);
+        match old_blocks_by_row.remove(&row) {
+            Some(block) if block.titles == titles => {
+                // The lenses may still carry different arguments, so refresh what they run.
+                blocks_to_replace.insert(block.id, render);
+                editor.code_lens_state.blocks.push(block);
+            }
+            old_block => {
+                blocks_to_remove.extend(old_block.map(|block| block.id));
+                let indent = snapshot.indent_size_for_line(row);
+                let position = snapshot.anchor_before(Point::new(row, indent.len));
+                blocks_to_insert.push(BlockProperties {
+                    position,
+                    height: 1,
+                    style: BlockStyle::Flex,
+                    render,
+                    disposition: BlockDisposition::Above,
+                });
+                new_block_titles.push((position, titles));
+            }
+        }
+    }
+    blocks_to_remove.extend(old_blocks_by_row.into_values().map(|block| block.id));
+
+    if !blocks_to_remove.is_empty() {
+        editor.remove_blocks(blocks_to_remove, None, cx);
+    }
+    if !blocks_to_replace.is_empty() {
+        editor.replace_blocks(blocks_to_replace, None, cx);
+    }
+    if !blocks_to_insert.is_empty() {
+        let block_ids = editor.insert_blocks(blocks_to_insert, None, cx);
+        editor
+            .code_lens_state
+            .blocks
+            .extend(
+                block_ids
+                    .into_iter()
+                    .zip(new_block_titles)
+                    .map(|(id, (position, titles))| CodeLensBlock {
+                        id,
+                        position,
+                        titles,
+                    }),
+            );
+    }
+}
+
+fn render_code_lenses(
+    editor: WeakView<Editor>,
+    lenses: Vec<(Model<Buffer>, CodeLens)>,
+) -> RenderBlock {
+    Arc::new(move |cx: &mut BlockContext| {
+        let mut children = Vec::new();
+        for (ix, (buffer, lens)) in lenses.iter().enumerate() {
+            let Some(command) = lens.lsp_lens.command.as_ref() else {
+                continue;
+            };
+            if ix > 0 {
+                children.push(
+                    Label::new("|")
+                        .size(LabelSize::Small)
+                        .color(Color::Disabled)
+                        .into_any_element(),
+                );
+            }
+            children.push(
+                div()
+                    .id(ix)
+                    .cursor_pointer()
+                    .child(
+                        Label::new(command.title.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .on_click({
+                        let editor = editor.clone();
+                        let buffer = buffer.clone();
+                        let lens = lens.clone();
+                        move |_, cx| {
+                            editor
+                                .update(cx, |editor, cx| {
+                                    run_code_lens(editor, buffer.clone(), lens.clone(), cx)
+                                })
+                                .ok();
+                        }
+                    })
+                    .into_any_element(),
+            );
+        }
+
+        h_flex()
+            .id(cx.block_id)
+            .size_full()
+            .pl(cx.anchor_x)
+            .gap_1()
+            .children(children)
+            .into_any_element()
+    })
+}
+
+/// Runs the command of a lens, either on the client for the commands we know how to
+/// handle, or on the language server that provided it.
+pub fn run_code_lens(
+    editor: &mut Editor,
+    buffer: Model<Buffer>,
+    lens: CodeLens,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some(command) = lens.lsp_lens.command.clone() else {
+        return;
+    };
+    if rust_analyzer_ext::run_client_command(editor, &lens, &command, cx) {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let Some(workspace) = editor.workspace() else {
+        return;
+    };
+
+    let apply_code_lens = project.update(cx, |project, cx| {
+        project.apply_code_lens(buffer, lens, true, cx)
+    });
+    let workspace = workspace.downgrade();
+    cx.spawn(|editor, cx| async move {
+        let project_transaction = apply_code_lens.await?;
+        Editor::open_project_transaction(&editor, workspace, project_transaction, command.title, cx)
+            .await
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext};
+    use gpui::point;
+    use smol::stream::StreamExt;
+    use std::sync::atomic::{self, AtomicUsize};
+
+    fn lsp_lens(row: u32, command: Option<&str>) -> lsp::CodeLens {
+        lsp::CodeLens {
+            range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 2)),
+            command: command.map(|title| lsp::Command {
+                title: title.to_string(),
+                command: "test.run".to_string(),
+                arguments: Some(vec![row.into()]),
+            }),
+            data: Some(row.into()),
+        }
+    }
+
+    fn displayed_lenses(
+        editor: &mut Editor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Vec<(u32, Vec<String>)> {
+        let snapshot = editor.buffer.read(cx).snapshot(cx);
+        let mut lenses = editor
+            .code_lens_state
+            .blocks
+            .iter()
+            .map(|block| (block.position.to_point(&snapshot).row, block.titles.clone()))
+            .collect::<Vec<_>>();
+        lenses.sort();
+        lenses
+    }
+
+    fn block_ids(editor: &Editor) -> HashSet<BlockId> {
+        editor
+            .code_lens_state
+            .blocks
+            .iter()
+            .map(|block| block.id)
+            .collect()
+    }
+
+    #[gpui::test]
+    async fn test_code_lenses(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        let lens_requests = Arc::new(AtomicUsize::new(0));
+        cx.handle_request::<lsp::request::CodeLensRequest, _, _>({
+            let lens_requests = lens_requests.clone();
+            move |_, _, _| {
+                lens_requests.fetch_add(1, atomic::Ordering::SeqCst);
+                async move {
+                    Ok(Some(vec![
+                        lsp_lens(0, Some("Run")),
+                        lsp_lens(0, None),
+                        lsp_lens(400, None),
+                    ]))
+                }
+            }
+        });
+        let resolved_rows = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        cx.handle_request::<lsp::request::CodeLensResolve, _, _>({
+            let resolved_rows = resolved_rows.clone();
+            move |_, lens, _| {
+                let row = lens.data.as_ref().unwrap().as_u64().unwrap() as u32;
+                resolved_rows.lock().push(row);
+                async move { Ok(lsp_lens(row, Some(format!("References {row}").as_str()))) }
+            }
+        });
+
+        cx.set_state(&format!("ˇfn main() {{}}\n{}", "\n".repeat(500)));
+        cx.executor().advance_clock(CODE_LENS_DEBOUNCE);
+        cx.run_until_parked();
+
+        // Only the lens in the visible range gets resolved.
+        assert_eq!(lens_requests.load(atomic::Ordering::SeqCst), 1);
+        assert_eq!(*resolved_rows.lock(), [0]);
+        let first_block_ids = cx.update_editor(|editor, cx| {
+            assert_eq!(
+                displayed_lenses(editor, cx),
+                [(0, vec!["Run".to_string(), "References 0".to_string()])]
+            );
+            block_ids(editor)
+        });
+
+        // Scrolling to the other lens resolves it, reusing the lenses of the unchanged buffer
+        // and keeping the block that is already shown.
+        cx.update_editor(|editor, cx| editor.set_scroll_position(point(0., 395.), cx));
+        cx.executor().advance_clock(CODE_LENS_DEBOUNCE);
+        cx.run_until_parked();
+
+        assert_eq!(lens_requests.load(atomic::Ordering::SeqCst), 1);
+        assert_eq!(*resolved_rows.lock(), [0, 400]);
+        cx.update_editor(|editor, cx| {
+            assert_eq!(
+                displayed_lenses(editor, cx),
+                [
+                    (0, vec!["Run".to_string(), "References 0".to_string()]),
+                    (400, vec!["References 400".to_string()]),
+                ]
+            );
+            assert!(block_ids(editor).is_superset(&first_block_ids));
+        });
+
+        // Editing the buffer requests its lenses again.
+        cx.update_editor(|editor, cx| {
+            editor.change_selections(None, cx, |s| s.select_ranges([0..0]));
+            editor.handle_input("// ", cx);
+        });
+        cx.executor().advance_clock(CODE_LENS_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(lens_requests.load(atomic::Ordering::SeqCst), 2);
+
+        // Running a lens executes its command on the language server.
+        let mut commands =
+            cx.handle_request::<lsp::request::ExecuteCommand, _, _>(|_, params, _| async move {
+                assert_eq!(params.command, "test.run");
+                assert_eq!(params.arguments, [serde_json::json!(0)]);
+                Ok(None)
+            });
+        cx.update_editor(|editor, cx| {
+            let buffer = editor.buffer.read(cx).as_singleton().unwrap();
+            let lens = editor.code_lens_state.lenses_by_buffer[&buffer.read(cx).remote_id()].lenses
+                [0]
+            .lens
+            .clone();
+            assert_eq!(lens.lsp_lens.command.as_ref().unwrap().title, "Run");
+            run_code_lens(editor, buffer, lens, cx);
+        });
+        commands.next().await;
+        cx.run_until_parked();
+    }
+}
@@ -31,10 +31,12 @@ use fold_map::FoldMap;
 use gpui::{Font, HighlightStyle, Hsla, LineLayout, Model, ModelContext, Pixels, UnderlineStyle};
 use inlay_map::InlayMap;
 use language::{
-    language_settings::language_settings, OffsetUtf16, Point, Subscription as BufferSubscription,
+    language_settings::{language_settings, FoldingRanges},
+    FoldKind, FoldRange, OffsetUtf16, Point, Subscription as BufferSubscription,
 };
 use lsp::DiagnosticSeverity;
 use multi_buffer::{Anchor, AnchorRangeExt, MultiBuffer, MultiBufferSnapshot, ToOffset, ToPoint};
+use parking_lot::Mutex;
 use std::{any::TypeId, borrow::Cow, fmt::Debug, num::NonZeroU32, ops::Range, sync::Arc};
 use sum_tree::{Bias, TreeMap};
 use tab_map::TabMap;
@@ -64,6 +66,11 @@ pub trait ToDisplayPoint {
 
 type TextHighlights = TreeMap<Option<TypeId>, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>;
 type InlayHighlights = BTreeMap<TypeId, HashMap<InlayId, (HighlightStyle, InlayHighlight)>>;
+/// Non-overlapping styled ranges, sorted by their start, that are layered
+/// over the syntax highlighting of the text they cover.
+type SemanticHighlights = Arc<[(Range<Anchor>, HighlightStyle)]>;
+/// Folding ranges reported by language servers, sorted by their start.
+type LspFoldRanges = Arc<[FoldRange<Anchor>]>;
 
 /// Decides how text in a [`MultiBuffer`] should be displayed in a buffer, handling inlay hints,
 /// folding, hard tabs, soft wrapping, custom blocks (like diagnostics), and highlighting.
@@ -87,9 +94,23 @@ pub struct DisplayMap {
     text_highlights: TextHighlights,
     /// Regions of inlays that should be highlighted.
     inlay_highlights: InlayHighlights,
+    /// Highlights derived from language server semantic tokens.
+    semantic_highlights: Option<SemanticHighlights>,
+    /// Folding ranges reported by language servers.
+    lsp_fold_ranges: Option<LspFoldRanges>,
+    /// Which buffer rows can be folded, shared with the snapshots of the map.
+    foldable_rows: Arc<Mutex<FoldableRows>>,
     pub clip_at_line_ends: bool,
 }
 
+/// Whether buffer rows can be folded, computed as rows are laid out and kept until the
+/// buffer, its syntax tree or the folding ranges setting change.
+#[derive(Default)]
+struct FoldableRows {
+    key: Option<(usize, usize, FoldingRanges)>,
+    rows: HashMap<u32, bool>,
+}
+
 impl DisplayMap {
     pub fn new(
         buffer: Model<MultiBuffer>,
@@ -119,6 +140,9 @@ impl DisplayMap {
             block_map,
             text_highlights: Default::default(),
             inlay_highlights: Default::default(),
+            semantic_highlights: None,
+            lsp_fold_ranges: None,
+            foldable_rows: Default::default(),
             clip_at_line_ends: false,
         }
     }
@@ -144,6 +168,10 @@ impl DisplayMap {
             block_snapshot,
             text_highlights: self.text_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
+            semantic_highlights: self.semantic_highlights.clone(),
+            lsp_fold_ranges: self.lsp_fold_ranges.clone(),
+            foldable_rows: self.foldable_rows.clone(),
+            folding_ranges: Self::folding_ranges(&self.buffer, cx),
             clip_at_line_ends: self.clip_at_line_ends,
         }
     }
@@ -264,6 +292,20 @@ impl DisplayMap {
         }
     }
 
+    /// Replaces the semantic token highlights. The ranges must be sorted and must not overlap.
+    pub(crate) fn set_semantic_highlights(
+        &mut self,
+        highlights: Option<Arc<[(Range<Anchor>, HighlightStyle)]>>,
+    ) {
+        self.semantic_highlights = highlights;
+    }
+
+    /// Replaces the folding ranges reported by language servers. The ranges must be sorted by their start.
+    pub(crate) fn set_lsp_fold_ranges(&mut self, ranges: Option<Arc<[FoldRange<Anchor>]>>) {
+        self.lsp_fold_ranges = ranges;
+        self.foldable_rows = Default::default();
+    }
+
     pub fn text_highlights(&self, type_id: TypeId) -> Option<(HighlightStyle, &[Range<Anchor>])> {
         let highlights = self.text_highlights.get(&Some(type_id))?;
         Some((highlights.0, &highlights.1))
@@ -329,6 +371,14 @@ impl DisplayMap {
         language_settings(language.as_deref(), None, cx).tab_size
     }
 
+    fn folding_ranges(buffer: &Model<MultiBuffer>, cx: &mut ModelContext<Self>) -> FoldingRanges {
+        let language = buffer
+            .read(cx)
+            .as_singleton()
+            .and_then(|buffer| buffer.read(cx).language());
+        language_settings(language.as_deref(), None, cx).folding_ranges
+    }
+
     #[cfg(test)]
     pub fn is_rewrapping(&self, cx: &gpui::AppContext) -> bool {
         self.wrap_map.read(cx).is_rewrapping()
@@ -339,6 +389,7 @@ impl DisplayMap {
 pub(crate) struct Highlights<'a> {
     pub text_highlights: Option<&'a TextHighlights>,
     pub inlay_highlights: Option<&'a InlayHighlights>,
+    pub semantic_highlights: Option<&'a SemanticHighlights>,
     pub inlay_highlight_style: Option<HighlightStyle>,
     pub suggestion_highlight_style: Option<HighlightStyle>,
 }
@@ -358,6 +409,10 @@ pub struct DisplaySnapshot {
     block_snapshot: block_map::BlockSnapshot,
     text_highlights: TextHighlights,
     inlay_highlights: InlayHighlights,
+    semantic_highlights: Option<SemanticHighlights>,
+    lsp_fold_ranges: Option<LspFoldRanges>,
+    foldable_rows: Arc<Mutex<FoldableRows>>,
+    folding_ranges: FoldingRanges,
     clip_at_line_ends: bool,
 }
 
@@ -525,6 +580,11 @@ impl DisplaySnapshot {
             Highlights {
                 text_highlights: Some(&self.text_highlights),
                 inlay_highlights: Some(&self.inlay_highlights),
+                semantic_highlights: if language_aware {
+                    self.semantic_highlights.as_ref()
+                } else {
+                    None
+                },
                 inlay_highlight_style,
                 suggestion_highlight_style,
             },
@@ -858,51 +918,168 @@ impl DisplaySnapshot {
     }
 
     pub fn is_foldable(self: &Self, buffer_row: u32) -> bool {
-        let max_row = self.buffer_snapshot.max_buffer_row();
-        if buffer_row >= max_row {
-            return false;
+        let key = (
+            self.buffer_snapshot.edit_count(),
+            self.buffer_snapshot.parse_count(),
+            self.folding_ranges,
+        );
+        let mut foldable_rows = self.foldable_rows.lock();
+        if foldable_rows.key != Some(key) {
+            foldable_rows.key = Some(key);
+            foldable_rows.rows.clear();
         }
+        *foldable_rows
+            .rows
+            .entry(buffer_row)
+            .or_insert_with(|| self.fold_range_for_row(buffer_row).is_some())
+    }
 
-        let (indent_size, is_blank) = self.line_indent_for_buffer_row(buffer_row);
-        if is_blank {
-            return false;
+    pub fn foldable_range(self: &Self, buffer_row: u32) -> Option<Range<Point>> {
+        if self.is_line_folded(buffer_row) {
+            None
+        } else {
+            Some(self.fold_range_for_row(buffer_row)?.range)
         }
+    }
 
-        for next_row in (buffer_row + 1)..=max_row {
-            let (next_indent_size, next_line_is_blank) = self.line_indent_for_buffer_row(next_row);
-            if next_indent_size > indent_size {
-                return true;
-            } else if !next_line_is_blank {
-                break;
-            }
+    /// Returns the largest range that can be folded starting on the given buffer row,
+    /// taken from the folds query, the language server or the indentation, depending on
+    /// the `folding_ranges` setting.
+    pub fn fold_range_for_row(&self, buffer_row: u32) -> Option<FoldRange<Point>> {
+        if buffer_row >= self.buffer_snapshot.max_buffer_row() {
+            return None;
         }
 
-        false
+        match self.folding_ranges {
+            FoldingRanges::Auto => self
+                .syntax_fold_range(buffer_row)
+                .or_else(|| self.lsp_fold_range(buffer_row))
+                .unwrap_or_else(|| self.indent_fold_range(buffer_row)),
+            FoldingRanges::LanguageServer => self
+                .lsp_fold_range(buffer_row)
+                .or_else(|| self.syntax_fold_range(buffer_row))
+                .unwrap_or_else(|| self.indent_fold_range(buffer_row)),
+            FoldingRanges::Indentation => self.indent_fold_range(buffer_row),
+        }
     }
 
-    pub fn foldable_range(self: &Self, buffer_row: u32) -> Option<Range<Point>> {
-        let start = Point::new(buffer_row, self.buffer_snapshot.line_len(buffer_row));
-        if self.is_foldable(start.row) && !self.is_line_folded(start.row) {
-            let (start_indent, _) = self.line_indent_for_buffer_row(buffer_row);
-            let max_point = self.buffer_snapshot.max_point();
-            let mut end = None;
-
-            for row in (buffer_row + 1)..=max_point.row {
-                let (indent, is_blank) = self.line_indent_for_buffer_row(row);
-                if !is_blank && indent <= start_indent {
-                    let prev_row = row - 1;
-                    end = Some(Point::new(
-                        prev_row,
-                        self.buffer_snapshot.line_len(prev_row),
-                    ));
-                    break;
+    /// Returns the ranges that can be folded in the buffer, ordered by their start, along
+    /// with the number of other foldable ranges that contain each of them.
+    pub fn foldable_ranges_with_depth(&self) -> Vec<(usize, FoldRange<Point>)> {
+        let mut enclosing_ends = Vec::<Point>::new();
+        (0..self.buffer_snapshot.max_buffer_row())
+            .filter_map(|row| self.fold_range_for_row(row))
+            .map(|fold_range| {
+                while enclosing_ends
+                    .last()
+                    .map_or(false, |end| *end < fold_range.range.start)
+                {
+                    enclosing_ends.pop();
                 }
+                let depth = enclosing_ends.len();
+                enclosing_ends.push(fold_range.range.end);
+                (depth, fold_range)
+            })
+            .collect()
+    }
+
+    /// Returns `None` if the language at the given row has no folds query, so that
+    /// another source of folding ranges can be used instead.
+    fn syntax_fold_range(&self, buffer_row: u32) -> Option<Option<FoldRange<Point>>> {
+        let line_start = Point::new(buffer_row, 0);
+        let line_end = Point::new(buffer_row, self.buffer_snapshot.line_len(buffer_row));
+        let fold_ranges = self
+            .buffer_snapshot
+            .syntax_fold_ranges(line_start..line_end)?;
+        Some(
+            fold_ranges
+                .into_iter()
+                .filter_map(|fold_range| {
+                    let range = fold_range.range.start.to_point(&self.buffer_snapshot)
+                        ..fold_range.range.end.to_point(&self.buffer_snapshot);
+                    Some(FoldRange {
+                        range: self.fold_range_for_code(range)?,
+                        kind: fold_range.kind,
+                    })
+                })
+                .max_by_key(|fold_range| fold_range.range.end),
+        )
+    }
+
+    /// Returns `None` if no language server reported folding ranges, so that another
+    /// source of folding ranges can be used instead.
+    fn lsp_fold_range(&self, buffer_row: u32) -> Option<Option<FoldRange<Point>>> {
+        let lsp_fold_ranges = self.lsp_fold_ranges.as_ref()?;
+        let start_ix = lsp_fold_ranges.partition_point(|fold_range| {
+            fold_range.range.start.to_point(&self.buffer_snapshot).row < buffer_row
+        });
+        Some(
+            lsp_fold_ranges[start_ix..]
+                .iter()
+                .map(|fold_range| FoldRange {
+                    range: fold_range.range.to_point(&self.buffer_snapshot),
+                    kind: fold_range.kind,
+                })
+                .take_while(|fold_range| fold_range.range.start.row == buffer_row)
+                .filter_map(|fold_range| {
+                    Some(FoldRange {
+                        range: self.fold_range_for_code(fold_range.range)?,
+                        kind: fold_range.kind,
+                    })
+                })
+                .max_by_key(|fold_range| fold_range.range.end),
+        )
+    }
+
+    /// Returns the range to fold to hide the given code, which keeps the first line of the
+    /// code and any closing delimiters on its last line visible.
+    fn fold_range_for_code(&self, range: Range<Point>) -> Option<Range<Point>> {
+        let start = Point::new(
+            range.start.row,
+            self.buffer_snapshot.line_len(range.start.row),
+        );
+        let mut end = range.end;
+        let last_line_text = self
+            .buffer_snapshot
+            .text_for_range(Point::new(end.row, 0)..end)
+            .collect::<String>();
+        if last_line_text
+            .chars()
+            .all(|c| c.is_whitespace() || (c.is_ascii_punctuation() && c != '_'))
+        {
+            let row = end.row.checked_sub(1)?;
+            end = Point::new(row, self.buffer_snapshot.line_len(row));
+        }
+        (end > start).then_some(start..end)
+    }
+
+    fn indent_fold_range(&self, buffer_row: u32) -> Option<FoldRange<Point>> {
+        let max_point = self.buffer_snapshot.max_point();
+        let (start_indent, is_blank) = self.line_indent_for_buffer_row(buffer_row);
+        if is_blank {
+            return None;
+        }
+
+        let next_non_blank_row = ((buffer_row + 1)..=max_point.row)
+            .find(|row| !self.line_indent_for_buffer_row(*row).1)?;
+        if self.line_indent_for_buffer_row(next_non_blank_row).0 <= start_indent {
+            return None;
+        }
+
+        let start = Point::new(buffer_row, self.buffer_snapshot.line_len(buffer_row));
+        let mut end = max_point;
+        for row in (next_non_blank_row + 1)..=max_point.row {
+            let (indent, is_blank) = self.line_indent_for_buffer_row(row);
+            if !is_blank && indent <= start_indent {
+                let prev_row = row - 1;
+                end = Point::new(prev_row, self.buffer_snapshot.line_len(prev_row));
+                break;
             }
-            let end = end.unwrap_or(max_point);
-            Some(start..end)
-        } else {
-            None
         }
+        Some(FoldRange {
+            range: start..end,
+            kind: FoldKind::Region,
+        })
     }
 
     #[cfg(any(test, feature = "test-support"))]
@@ -14,7 +14,7 @@ use std::{
 use sum_tree::{Bias, Cursor, SumTree, TreeMap};
 use text::{Patch, Rope};
 
-use super::Highlights;
+use super::{Highlights, SemanticHighlights};
 
 /// Decides where the [`Inlay`]s should be displayed.
 ///
@@ -189,10 +189,21 @@ pub struct InlayBufferRows<'a> {
 struct HighlightEndpoint {
     offset: InlayOffset,
     is_start: bool,
-    tag: Option<TypeId>,
+    tag: HighlightTag,
     style: HighlightStyle,
 }
 
+/// Identifies an active highlight while iterating over chunks.
+///
+/// Semantic token highlights are ordered first, so that text highlights
+/// such as search matches are layered on top of them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+enum HighlightTag {
+    /// The index of a token in the [`SemanticHighlights`].
+    Semantic(usize),
+    Text(Option<TypeId>),
+}
+
 impl PartialOrd for HighlightEndpoint {
     fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
         Some(self.cmp(other))
@@ -218,7 +229,7 @@ pub struct InlayChunks<'a> {
     inlay_highlight_style: Option<HighlightStyle>,
     suggestion_highlight_style: Option<HighlightStyle>,
     highlight_endpoints: Peekable<vec::IntoIter<HighlightEndpoint>>,
-    active_highlights: BTreeMap<Option<TypeId>, HighlightStyle>,
+    active_highlights: BTreeMap<HighlightTag, HighlightStyle>,
     highlights: Highlights<'a>,
     snapshot: &'a InlaySnapshot,
 }
@@ -1029,6 +1040,9 @@ impl InlaySnapshot {
         cursor.seek(&range.start, Bias::Right, &());
 
         let mut highlight_endpoints = Vec::new();
+        if let Some(semantic_highlights) = highlights.semantic_highlights {
+            self.apply_semantic_highlights(&range, semantic_highlights, &mut highlight_endpoints);
+        }
         if let Some(text_highlights) = highlights.text_highlights {
             if !text_highlights.is_empty() {
                 self.apply_text_highlights(
@@ -1103,13 +1117,13 @@ impl InlaySnapshot {
                     highlight_endpoints.push(HighlightEndpoint {
                         offset: self.to_inlay_offset(range.start.to_offset(&self.buffer)),
                         is_start: true,
-                        tag: *tag,
+                        tag: HighlightTag::Text(*tag),
                         style,
                     });
                     highlight_endpoints.push(HighlightEndpoint {
                         offset: self.to_inlay_offset(range.end.to_offset(&self.buffer)),
                         is_start: false,
-                        tag: *tag,
+                        tag: HighlightTag::Text(*tag),
                         style,
                     });
                 }
@@ -1119,6 +1133,45 @@ impl InlaySnapshot {
         }
     }
 
+    fn apply_semantic_highlights(
+        &self,
+        range: &Range<InlayOffset>,
+        semantic_highlights: &SemanticHighlights,
+        highlight_endpoints: &mut Vec<HighlightEndpoint>,
+    ) {
+        let buffer_start = self.buffer.anchor_after(self.to_buffer_offset(range.start));
+        let buffer_end = self.buffer.anchor_before(self.to_buffer_offset(range.end));
+
+        // Semantic tokens don't overlap, so their ends are sorted like their starts.
+        let start_ix = match semantic_highlights.binary_search_by(|(probe, _)| {
+            if probe.end.cmp(&buffer_start, &self.buffer).is_gt() {
+                cmp::Ordering::Greater
+            } else {
+                cmp::Ordering::Less
+            }
+        }) {
+            Ok(i) | Err(i) => i,
+        };
+        for (ix, (token_range, style)) in semantic_highlights.iter().enumerate().skip(start_ix) {
+            if token_range.start.cmp(&buffer_end, &self.buffer).is_ge() {
+                break;
+            }
+
+            highlight_endpoints.push(HighlightEndpoint {
+                offset: self.to_inlay_offset(token_range.start.to_offset(&self.buffer)),
+                is_start: true,
+                tag: HighlightTag::Semantic(ix),
+                style: *style,
+            });
+            highlight_endpoints.push(HighlightEndpoint {
+                offset: self.to_inlay_offset(token_range.end.to_offset(&self.buffer)),
+                is_start: false,
+                tag: HighlightTag::Semantic(ix),
+                style: *style,
+            });
+        }
+    }
+
     #[cfg(test)]
     pub fn text(&self) -> String {
         self.chunks(Default::default()..self.len(), false, Highlights::default())
@@ -14,14 +14,17 @@
 //! If you're looking to improve Vim mode, you should check out Vim crate that wraps Editor and overrides it's behaviour.
 pub mod actions;
 mod blink_manager;
+mod code_lens;
 pub mod display_map;
 mod editor_settings;
 mod element;
+mod folding_ranges;
 mod inlay_hint_cache;
 
 mod debounced_delay;
 mod git;
 mod highlight_matching_bracket;
+mod hot_exit;
 mod hover_links;
 mod hover_popover;
 pub mod items;
@@ -31,6 +34,9 @@ mod persistence;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod semantic_tokens;
+mod signature_help_popover;
+mod snippet_variables;
 
 #[cfg(test)]
 mod editor_tests;
@@ -54,7 +60,7 @@ use element::LineWithInvisibles;
 pub use element::{Cursor, EditorElement, HighlightedRange, HighlightedRangeLine};
 use futures::FutureExt;
 use fuzzy::{StringMatch, StringMatchCandidate};
-use git::diff_hunk_to_display;
+use git::{blame::GitBlame, diff_hunk_to_display};
 use gpui::{
     div, impl_actions, point, prelude::*, px, relative, rems, size, uniform_list, Action,
     AnyElement, AppContext, AsyncWindowContext, BackgroundExecutor, Bounds, ClipboardItem, Context,
@@ -73,10 +79,14 @@ use language::{char_kind, CharKind};
 use language::{
     language_settings::{self, all_language_settings, InlayHintSettings},
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CodeAction,
-    CodeLabel, Completion, CursorShape, Diagnostic, Documentation, IndentKind, IndentSize,
-    Language, OffsetRangeExt, Point, Selection, SelectionGoal, TransactionId,
+    CodeLabel, Completion, CursorShape, Diagnostic, Documentation, FoldKind, IndentKind,
+    IndentSize, Language, OffsetRangeExt, Point, Selection, SelectionGoal, TextObjectKind,
+    TransactionId,
 };
 
+use code_lens::{invalidate_code_lenses, refresh_code_lenses, CodeLensState};
+use folding_ranges::{refresh_folding_ranges, FoldingRangesState};
+use hot_exit::HotExitState;
 use hover_links::{HoverLink, HoveredLinkState, InlayHighlight};
 use lsp::{DiagnosticSeverity, LanguageServerId};
 use mouse_context_menu::MouseContextMenu;
@@ -94,10 +104,16 @@ use rand::prelude::*;
 use rpc::proto::*;
 use scroll::{Autoscroll, OngoingScroll, ScrollAnchor, ScrollManager, ScrollbarAutoHide};
 use selections_collection::{resolve_multiple, MutableSelectionsCollection, SelectionsCollection};
+use semantic_tokens::{refresh_semantic_tokens, SemanticTokensState};
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
+use signature_help_popover::{
+    hide_signature_help, refresh_signature_help, trigger_signature_help_on_input,
+    SignatureHelpState,
+};
 use smallvec::SmallVec;
 use snippet::Snippet;
+use snippet_provider::SnippetRegistry;
 use std::{
     any::TypeId,
     borrow::Cow,
@@ -130,6 +146,7 @@ const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 const MAX_LINE_LEN: usize = 1024;
 const MIN_NAVIGATION_HISTORY_ROW_DELTA: i64 = 10;
 const MAX_SELECTION_HISTORY_LEN: usize = 1024;
+const GIT_BLAME_GUTTER_WIDTH_CHARS: f32 = 40.;
 const COPILOT_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(75);
 pub(crate) const CURSORS_VISIBLE_FOR: Duration = Duration::from_millis(2000);
 #[doc(hidden)]
@@ -385,6 +402,12 @@ pub struct Editor {
     mode: EditorMode,
     show_breadcrumbs: bool,
     show_gutter: bool,
+    show_line_numbers: Option<bool>,
+    use_relative_line_numbers: Option<bool>,
+    show_git_blame_gutter: bool,
+    show_git_blame_inline: bool,
+    blame: Option<Model<GitBlame>>,
+    blame_subscription: Option<Subscription>,
     show_wrap_guides: Option<bool>,
     placeholder_text: Option<Arc<str>>,
     highlighted_rows: Option<Range<u32>>,
@@ -412,6 +435,11 @@ pub struct Editor {
     leader_peer_id: Option<PeerId>,
     remote_id: Option<ViewId>,
     hover_state: HoverState,
+    signature_help_state: SignatureHelpState,
+    semantic_tokens_state: SemanticTokensState,
+    code_lens_state: CodeLensState,
+    folding_ranges_state: FoldingRangesState,
+    hot_exit_state: HotExitState,
     gutter_hovered: bool,
     hovered_link_state: Option<HoveredLinkState>,
     copilot_state: CopilotState,
@@ -435,6 +463,9 @@ pub struct Editor {
 pub struct EditorSnapshot {
     pub mode: EditorMode,
     show_gutter: bool,
+    show_line_numbers: Option<bool>,
+    use_relative_line_numbers: Option<bool>,
+    show_git_blame_gutter: bool,
     pub display_snapshot: DisplaySnapshot,
     pub placeholder_text: Option<Arc<str>>,
     is_focused: bool,
@@ -447,6 +478,8 @@ pub struct GutterDimensions {
     pub right_padding: Pixels,
     pub width: Pixels,
     pub margin: Pixels,
+    /// The width of the git blame column at the start of the gutter, if shown.
+    pub git_blame_entries_width: Option<Pixels>,
 }
 
 impl Default for GutterDimensions {
@@ -456,6 +489,7 @@ impl Default for GutterDimensions {
             right_padding: Pixels::ZERO,
             width: Pixels::ZERO,
             margin: Pixels::ZERO,
+            git_blame_entries_width: None,
         }
     }
 }
@@ -605,9 +639,27 @@ struct AutocloseRegion {
 #[derive(Debug)]
 struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
+    choices: Vec<Option<Vec<String>>>,
+    transforms: Vec<Vec<SnippetTransform>>,
     active_index: usize,
 }
 
+/// A mirror of a snippet tab stop that is rewritten with a transform of the tab stop's text.
+#[derive(Debug)]
+struct SnippetTransform {
+    /// Anchored so that text typed right before or after the mirror, in an adjacent tab
+    /// stop, stays out of it.
+    range: Range<Anchor>,
+    /// The index, in the ranges of the tab stop, of the range that was inserted at the same
+    /// cursor as the mirror.
+    source: usize,
+    /// The side of the text typed at the position of an empty mirror that the mirror keeps
+    /// to: after it when a tab stop ends there, so that the text belongs to that tab stop,
+    /// and before it otherwise.
+    empty_bias: Bias,
+    transform: snippet::Transform,
+}
+
 #[doc(hidden)]
 pub struct RenameState {
     pub range: Range<Anchor>,
@@ -621,6 +673,7 @@ struct InvalidationStack<T>(Vec<T>);
 enum ContextMenu {
     Completions(CompletionsMenu),
     CodeActions(CodeActionsMenu),
+    SnippetChoices(SnippetChoicesMenu),
 }
 
 impl ContextMenu {
@@ -633,6 +686,7 @@ impl ContextMenu {
             match self {
                 ContextMenu::Completions(menu) => menu.select_first(project, cx),
                 ContextMenu::CodeActions(menu) => menu.select_first(cx),
+                ContextMenu::SnippetChoices(menu) => menu.select_first(cx),
             }
             true
         } else {
@@ -649,6 +703,7 @@ impl ContextMenu {
             match self {
                 ContextMenu::Completions(menu) => menu.select_prev(project, cx),
                 ContextMenu::CodeActions(menu) => menu.select_prev(cx),
+                ContextMenu::SnippetChoices(menu) => menu.select_prev(cx),
             }
             true
         } else {
@@ -665,6 +720,7 @@ impl ContextMenu {
             match self {
                 ContextMenu::Completions(menu) => menu.select_next(project, cx),
                 ContextMenu::CodeActions(menu) => menu.select_next(cx),
+                ContextMenu::SnippetChoices(menu) => menu.select_next(cx),
             }
             true
         } else {
@@ -681,6 +737,7 @@ impl ContextMenu {
             match self {
                 ContextMenu::Completions(menu) => menu.select_last(project, cx),
                 ContextMenu::CodeActions(menu) => menu.select_last(cx),
+                ContextMenu::SnippetChoices(menu) => menu.select_last(cx),
             }
             true
         } else {
@@ -692,6 +749,7 @@ impl ContextMenu {
         match self {
             ContextMenu::Completions(menu) => menu.visible(),
             ContextMenu::CodeActions(menu) => menu.visible(),
+            ContextMenu::SnippetChoices(menu) => menu.visible(),
         }
     }
 
@@ -709,6 +767,9 @@ impl ContextMenu {
                 menu.render(style, max_height, workspace, cx),
             ),
             ContextMenu::CodeActions(menu) => menu.render(cursor_position, style, max_height, cx),
+            ContextMenu::SnippetChoices(menu) => {
+                (cursor_position, menu.render(style, max_height, cx))
+            }
         }
     }
 }
@@ -1213,6 +1274,119 @@ impl CodeActionsMenu {
     }
 }
 
+#[derive(Clone)]
+struct SnippetChoicesMenu {
+    choices: Arc<[String]>,
+    selected_item: usize,
+    scroll_handle: UniformListScrollHandle,
+}
+
+impl SnippetChoicesMenu {
+    fn select_first(&mut self, cx: &mut ViewContext<Editor>) {
+        self.selected_item = 0;
+        self.scroll_handle.scroll_to_item(self.selected_item);
+        cx.notify()
+    }
+
+    fn select_prev(&mut self, cx: &mut ViewContext<Editor>) {
+        if self.selected_item > 0 {
+            self.selected_item -= 1;
+        } else {
+            self.selected_item = self.choices.len() - 1;
+        }
+        self.scroll_handle.scroll_to_item(self.selected_item);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, cx: &mut ViewContext<Editor>) {
+        if self.selected_item + 1 < self.choices.len() {
+            self.selected_item += 1;
+        } else {
+            self.selected_item = 0;
+        }
+        self.scroll_handle.scroll_to_item(self.selected_item);
+        cx.notify();
+    }
+
+    fn select_last(&mut self, cx: &mut ViewContext<Editor>) {
+        self.selected_item = self.choices.len() - 1;
+        self.scroll_handle.scroll_to_item(self.selected_item);
+        cx.notify()
+    }
+
+    fn visible(&self) -> bool {
+        !self.choices.is_empty()
+    }
+
+    fn render(
+        &self,
+        _style: &EditorStyle,
+        max_height: Pixels,
+        cx: &mut ViewContext<Editor>,
+    ) -> AnyElement {
+        let choices = self.choices.clone();
+        let selected_item = self.selected_item;
+
+        uniform_list(
+            cx.view().clone(),
+            "snippet_choices_menu",
+            self.choices.len(),
+            move |_this, range, cx| {
+                choices[range.clone()]
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, choice)| {
+                        let item_ix = range.start + ix;
+                        let selected = selected_item == item_ix;
+                        let colors = cx.theme().colors();
+                        div()
+                            .px_2()
+                            .text_color(colors.text)
+                            .when(selected, |style| {
+                                style
+                                    .bg(colors.element_active)
+                                    .text_color(colors.text_accent)
+                            })
+                            .hover(|style| {
+                                style
+                                    .bg(colors.element_hover)
+                                    .text_color(colors.text_accent)
+                            })
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(move |editor, _, cx| {
+                                    cx.stop_propagation();
+                                    editor
+                                        .confirm_completion(
+                                            &ConfirmCompletion {
+                                                item_ix: Some(item_ix),
+                                            },
+                                            cx,
+                                        )
+                                        .map(|task| task.detach_and_log_err(cx));
+                                }),
+                            )
+                            .child(SharedString::from(choice.clone()))
+                    })
+                    .collect()
+            },
+        )
+        .elevation_1(cx)
+        .px_2()
+        .py_1()
+        .max_h(max_height)
+        .track_scroll(self.scroll_handle.clone())
+        .with_width_from_item(
+            self.choices
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, choice)| choice.chars().count())
+                .map(|(ix, _)| ix),
+        )
+        .into_any_element()
+    }
+}
+
 pub(crate) struct CopilotState {
     excerpt_id: Option<ExcerptId>,
     pending_refresh: Task<Option<()>>,
@@ -1469,11 +1643,26 @@ impl Editor {
                         cx.emit(EditorEvent::TitleChanged);
                     }));
                 }
-                project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
-                    if let project::Event::RefreshInlayHints = event {
-                        editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
-                    };
-                }));
+                project_subscriptions.push(cx.subscribe(
+                    project,
+                    |editor, _, event, cx| match event {
+                        project::Event::RefreshInlayHints => {
+                            editor
+                                .refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                        }
+                        project::Event::RefreshSemanticTokens
+                        | project::Event::DiskBasedDiagnosticsFinished { .. } => {
+                            refresh_semantic_tokens(editor, cx);
+                        }
+                        project::Event::RefreshCodeLens => {
+                            invalidate_code_lenses(editor, cx);
+                        }
+                        project::Event::LanguageServerAdded(_) => {
+                            refresh_folding_ranges(editor, cx);
+                        }
+                        _ => {}
+                    },
+                ));
             }
         }
 
@@ -1512,6 +1701,12 @@ impl Editor {
             mode,
             show_breadcrumbs: EditorSettings::get_global(cx).toolbar.breadcrumbs,
             show_gutter: mode == EditorMode::Full,
+            show_line_numbers: None,
+            use_relative_line_numbers: None,
+            show_git_blame_gutter: false,
+            show_git_blame_inline: false,
+            blame: None,
+            blame_subscription: None,
             show_wrap_guides: None,
             placeholder_text: None,
             highlighted_rows: None,
@@ -1541,6 +1736,11 @@ impl Editor {
             leader_peer_id: None,
             remote_id: None,
             hover_state: Default::default(),
+            signature_help_state: Default::default(),
+            semantic_tokens_state: Default::default(),
+            code_lens_state: Default::default(),
+            folding_ranges_state: Default::default(),
+            hot_exit_state: Default::default(),
             hovered_link_state: Default::default(),
             copilot_state: Default::default(),
             inlay_hint_cache: InlayHintCache::new(inlay_hint_settings),
@@ -1578,6 +1778,14 @@ impl Editor {
 
         this.end_selection(cx);
         this.scroll_manager.show_scrollbar(cx);
+        refresh_semantic_tokens(&mut this, cx);
+        refresh_code_lenses(&mut this, cx);
+        refresh_folding_ranges(&mut this, cx);
+
+        if mode == EditorMode::Full && ProjectSettings::get_global(cx).git.inline_blame_enabled() {
+            this.show_git_blame_inline = true;
+            this.start_git_blame(cx);
+        }
 
         if mode == EditorMode::Full {
             let should_auto_hide_scrollbars = cx.should_auto_hide_scrollbars();
@@ -1602,7 +1810,7 @@ impl Editor {
         }
         if self.context_menu_visible() {
             match self.context_menu.read().as_ref() {
-                Some(ContextMenu::Completions(_)) => {
+                Some(ContextMenu::Completions(_)) | Some(ContextMenu::SnippetChoices(_)) => {
                     key_context.add("menu");
                     key_context.add("showing_completions")
                 }
@@ -1693,6 +1901,9 @@ impl Editor {
         EditorSnapshot {
             mode: self.mode,
             show_gutter: self.show_gutter,
+            show_line_numbers: self.show_line_numbers,
+            use_relative_line_numbers: self.use_relative_line_numbers,
+            show_git_blame_gutter: self.show_git_blame_gutter && self.blame.is_some(),
             display_snapshot: self.display_map.update(cx, |map, cx| map.snapshot(cx)),
             scroll_anchor: self.scroll_manager.anchor(),
             ongoing_scroll: self.scroll_manager.ongoing_scroll(),
@@ -1866,7 +2077,8 @@ impl Editor {
         self.select_prev_state = None;
         self.select_larger_syntax_node_stack.clear();
         self.invalidate_autoclose_regions(&self.selections.disjoint_anchors(), buffer);
-        self.snippet_stack
+        let ended_snippets = self
+            .snippet_stack
             .invalidate(&self.selections.disjoint_anchors(), buffer);
         self.take_rename(false, cx);
 
@@ -1883,7 +2095,6 @@ impl Editor {
             let mut context_menu = self.context_menu.write();
             let completion_menu = match context_menu.as_ref() {
                 Some(ContextMenu::Completions(menu)) => Some(menu),
-
                 _ => {
                     *context_menu = None;
                     None
@@ -1933,6 +2144,7 @@ impl Editor {
             }
 
             hide_hover(self, cx);
+            refresh_signature_help(self, cx);
 
             if old_cursor_position.to_display_point(&display_map).row()
                 != new_cursor_position.to_display_point(&display_map).row()
@@ -1945,6 +2157,11 @@ impl Editor {
             self.discard_copilot_suggestion(cx);
         }
 
+        for mut snippet in ended_snippets {
+            let tabstop_index = snippet.active_index;
+            self.apply_snippet_transforms(&mut snippet, tabstop_index, cx);
+        }
+
         self.blink_manager.update(cx, BlinkManager::pause_blinking);
         cx.emit(EditorEvent::SelectionsChanged { local });
 
@@ -2364,6 +2581,10 @@ impl Editor {
             return true;
         }
 
+        if hide_signature_help(self, cx) {
+            return true;
+        }
+
         if self.hide_context_menu(cx).is_some() {
             return true;
         }
@@ -2372,7 +2593,9 @@ impl Editor {
             return true;
         }
 
-        if self.snippet_stack.pop().is_some() {
+        if let Some(mut snippet) = self.snippet_stack.pop() {
+            let tabstop_index = snippet.active_index;
+            self.apply_snippet_transforms(&mut snippet, tabstop_index, cx);
             return true;
         }
 
@@ -2585,6 +2808,7 @@ impl Editor {
                 this.trigger_completion_on_input(&text, cx);
                 this.refresh_copilot_suggestions(true, cx);
             }
+            trigger_signature_help_on_input(this, &text, cx);
         });
     }
 
@@ -3373,10 +3597,17 @@ impl Editor {
     ) -> Option<Task<Result<()>>> {
         use language::ToOffset as _;
 
-        let completions_menu = if let ContextMenu::Completions(menu) = self.hide_context_menu(cx)? {
-            menu
-        } else {
-            return None;
+        let completions_menu = match self.hide_context_menu(cx)? {
+            ContextMenu::Completions(menu) => menu,
+            ContextMenu::SnippetChoices(menu) => {
+                let choice = menu
+                    .choices
+                    .get(action.item_ix.unwrap_or(menu.selected_item))?;
+                cx.stop_propagation();
+                self.insert_snippet_choice(choice, cx);
+                return Some(Task::ready(Ok(())));
+            }
+            ContextMenu::CodeActions(_) => return None,
         };
 
         let mat = completions_menu
@@ -3390,7 +3621,12 @@ impl Editor {
         let snippet;
         let text;
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            snippet = Some(
+                Snippet::parse_with_variables(&completion.new_text, &|name| {
+                    snippet_variables::resolve_snippet_variable(&*self, name, &*cx)
+                })
+                .log_err()?,
+            );
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -3463,9 +3699,15 @@ impl Editor {
         self.transact(cx, |this, cx| {
             if let Some(mut snippet) = snippet {
                 snippet.text = text.to_string();
-                for tabstop in snippet.tabstops.iter_mut().flatten() {
-                    tabstop.start -= common_prefix_len as isize;
-                    tabstop.end -= common_prefix_len as isize;
+                for tabstop in &mut snippet.tabstops {
+                    let transform_ranges = tabstop
+                        .transforms
+                        .iter_mut()
+                        .map(|mirror| &mut mirror.range);
+                    for range in tabstop.ranges.iter_mut().chain(transform_ranges) {
+                        range.start -= common_prefix_len as isize;
+                        range.end -= common_prefix_len as isize;
+                    }
                 }
 
                 this.insert_snippet(&ranges, snippet, cx).log_err();
@@ -4174,7 +4416,7 @@ impl Editor {
         snippet: Snippet,
         cx: &mut ViewContext<Self>,
     ) -> Result<()> {
-        let tabstops = self.buffer.update(cx, |buffer, cx| {
+        let snippet_state = self.buffer.update(cx, |buffer, cx| {
             let snippet_text: Arc<str> = snippet.text.clone().into();
             buffer.edit(
                 insertion_ranges
@@ -4187,42 +4429,80 @@ impl Editor {
 
             let snapshot = &*buffer.read(cx);
             let snippet = &snippet;
-            snippet
-                .tabstops
+            let mut delta = 0_isize;
+            let insertion_starts = insertion_ranges
                 .iter()
-                .map(|tabstop| {
-                    let mut tabstop_ranges = tabstop
-                        .iter()
-                        .flat_map(|tabstop_range| {
-                            let mut delta = 0_isize;
-                            insertion_ranges.iter().map(move |insertion_range| {
-                                let insertion_start = insertion_range.start as isize + delta;
-                                delta +=
-                                    snippet.text.len() as isize - insertion_range.len() as isize;
-
-                                let start = snapshot.anchor_before(
-                                    (insertion_start + tabstop_range.start) as usize,
-                                );
-                                let end = snapshot
-                                    .anchor_after((insertion_start + tabstop_range.end) as usize);
-                                start..end
-                            })
-                        })
-                        .collect::<Vec<_>>();
-                    tabstop_ranges.sort_unstable_by(|a, b| a.start.cmp(&b.start, snapshot));
-                    tabstop_ranges
+                .map(|insertion_range| {
+                    let insertion_start = insertion_range.start as isize + delta;
+                    delta += snippet.text.len() as isize - insertion_range.len() as isize;
+                    insertion_start
+                })
+                .collect::<Vec<_>>();
+            let anchor_ranges = |tabstop_range: &Range<isize>| {
+                let tabstop_range = tabstop_range.clone();
+                insertion_starts.iter().map(move |insertion_start| {
+                    let start =
+                        snapshot.anchor_before((insertion_start + tabstop_range.start) as usize);
+                    let end = snapshot.anchor_after((insertion_start + tabstop_range.end) as usize);
+                    start..end
                 })
-                .collect::<Vec<_>>()
+            };
+
+            let mut state = SnippetState {
+                ranges: Vec::new(),
+                choices: Vec::new(),
+                transforms: Vec::new(),
+                active_index: 0,
+            };
+            for tabstop in &snippet.tabstops {
+                let mut tabstop_ranges = tabstop
+                    .ranges
+                    .iter()
+                    .flat_map(&anchor_ranges)
+                    .collect::<Vec<_>>();
+                tabstop_ranges.sort_unstable_by(|a, b| a.start.cmp(&b.start, snapshot));
+
+                // Each mirror is transformed from the tab stop inserted at its own cursor.
+                let sources = tabstop
+                    .ranges
+                    .first()
+                    .map(|range| anchor_ranges(range).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                let mut transforms = Vec::new();
+                for mirror in &tabstop.transforms {
+                    let empty_bias = if mirror.follows_tabstop {
+                        Bias::Right
+                    } else {
+                        Bias::Left
+                    };
+                    for (insertion_start, source) in insertion_starts.iter().zip(&sources) {
+                        let Some(source) = tabstop_ranges.iter().position(|range| range == source)
+                        else {
+                            continue;
+                        };
+                        let range = (insertion_start + mirror.range.start) as usize
+                            ..(insertion_start + mirror.range.end) as usize;
+                        transforms.push(SnippetTransform {
+                            range: snippet_mirror_range(snapshot, range, empty_bias),
+                            source,
+                            empty_bias,
+                            transform: mirror.transform.clone(),
+                        });
+                    }
+                }
+                state.ranges.push(tabstop_ranges);
+                state.choices.push(tabstop.choices.clone());
+                state.transforms.push(transforms);
+            }
+            state
         });
 
-        if let Some(tabstop) = tabstops.first() {
+        if let Some(tabstop) = snippet_state.ranges.first() {
             self.change_selections(Some(Autoscroll::fit()), cx, |s| {
                 s.select_ranges(tabstop.iter().cloned());
             });
-            self.snippet_stack.push(SnippetState {
-                active_index: 0,
-                ranges: tabstops,
-            });
+            let choices = snippet_state.choices.first().cloned().flatten();
+            self.snippet_stack.push(snippet_state);
 
             // Check whether the just-entered snippet ends with an auto-closable bracket.
             if self.autoclose_regions.is_empty() {
@@ -4259,10 +4539,141 @@ impl Editor {
                     }
                 }
             }
+
+            if let Some(choices) = choices {
+                self.show_snippet_choices(choices, cx);
+            }
         }
         Ok(())
     }
 
+    /// Rewrites the transformed mirrors of a snippet tab stop, each from the text of the tab
+    /// stop at the same cursor, in one transaction.
+    fn apply_snippet_transforms(
+        &mut self,
+        snippet: &mut SnippetState,
+        tabstop_index: usize,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let SnippetState {
+            ranges, transforms, ..
+        } = snippet;
+        let Some(transforms) = transforms.get_mut(tabstop_index) else {
+            return;
+        };
+        if transforms.is_empty() {
+            return;
+        }
+
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.start_transaction(cx);
+            for mirror in transforms.iter_mut() {
+                let snapshot = buffer.snapshot(cx);
+                let Some(source) = ranges[tabstop_index].get(mirror.source) else {
+                    continue;
+                };
+                let text = snapshot.text_for_range(source.clone()).collect::<String>();
+                let start = mirror.range.start.to_offset(&snapshot);
+                let end = mirror.range.end.to_offset(&snapshot);
+                // When the text of a mirror was deleted and text was typed in its place,
+                // its anchors end up on both sides of that text, which isn't the mirror's.
+                let range = if start <= end {
+                    start..end
+                } else if mirror.empty_bias == Bias::Left {
+                    end..end
+                } else {
+                    start..start
+                };
+                let new_text = mirror.transform.apply(&text);
+                if snapshot.text_for_range(range.clone()).collect::<String>() == new_text {
+                    continue;
+                }
+
+                // New text is inserted before the replaced text, so a tab stop anchored to
+                // the mirror's edges would take it in. Remember where the tab stops touching
+                // the mirror were, and put them back on the same side of it after the edit.
+                let tabstop_offsets = ranges
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(tabstop_ix, tabstop_ranges)| {
+                        tabstop_ranges
+                            .iter()
+                            .enumerate()
+                            .map(move |(range_ix, tabstop_range)| {
+                                (tabstop_ix, range_ix, tabstop_range)
+                            })
+                    })
+                    .filter_map(|(tabstop_ix, range_ix, tabstop_range)| {
+                        let offsets = tabstop_range.start.to_offset(&snapshot)
+                            ..tabstop_range.end.to_offset(&snapshot);
+                        let touches_mirror = (range.start..=range.end).contains(&offsets.start)
+                            || (range.start..=range.end).contains(&offsets.end);
+                        touches_mirror.then_some((tabstop_ix, range_ix, offsets))
+                    })
+                    .collect::<Vec<_>>();
+
+                buffer.edit([(range.clone(), new_text.as_str())], None, cx);
+                let snapshot = buffer.snapshot(cx);
+                let new_end = range.start + new_text.len();
+                mirror.range =
+                    snippet_mirror_range(&snapshot, range.start..new_end, mirror.empty_bias);
+
+                let old_len = range.end - range.start;
+                let map_offset = |offset: usize, before_mirror: bool| {
+                    if offset < range.start || (offset == range.start && before_mirror) {
+                        offset
+                    } else if offset > range.end {
+                        offset + new_text.len() - old_len
+                    } else if offset == range.start && old_len > 0 {
+                        range.start
+                    } else {
+                        new_end
+                    }
+                };
+                for (tabstop_ix, range_ix, offsets) in tabstop_offsets {
+                    // Only the side of an empty mirror is ambiguous: a tab stop ending there
+                    // is before it, one starting there is after it, and an empty one is
+                    // before it only when the mirror follows a tab stop.
+                    let before_mirror = offsets.is_empty() && mirror.empty_bias == Bias::Right;
+                    let start = map_offset(offsets.start, before_mirror);
+                    let end = map_offset(offsets.end, offsets.start < offsets.end || before_mirror);
+                    ranges[tabstop_ix][range_ix] =
+                        snapshot.anchor_before(start)..snapshot.anchor_after(end.max(start));
+                }
+            }
+            buffer.end_transaction(cx);
+        });
+    }
+
+    /// Offers the choices of the active snippet tab stop in a menu.
+    fn show_snippet_choices(&mut self, choices: Vec<String>, cx: &mut ViewContext<Self>) {
+        let menu = SnippetChoicesMenu {
+            choices: choices.into(),
+            selected_item: 0,
+            scroll_handle: UniformListScrollHandle::new(),
+        };
+
+        self.completion_tasks.clear();
+        *self.context_menu.write() = Some(ContextMenu::SnippetChoices(menu));
+        self.discard_copilot_suggestion(cx);
+        cx.notify();
+    }
+
+    /// Replaces the text of the active snippet tab stop with one of its choices.
+    fn insert_snippet_choice(&mut self, choice: &str, cx: &mut ViewContext<Self>) {
+        self.transact(cx, |this, cx| {
+            let ranges = this
+                .selections
+                .all::<usize>(cx)
+                .into_iter()
+                .map(|selection| selection.range())
+                .collect::<Vec<_>>();
+            this.buffer.update(cx, |buffer, cx| {
+                buffer.edit(ranges.into_iter().map(|range| (range, choice)), None, cx);
+            });
+        });
+    }
+
     pub fn move_to_next_snippet_tabstop(&mut self, cx: &mut ViewContext<Self>) -> bool {
         self.move_to_snippet_tabstop(Bias::Right, cx)
     }
@@ -4273,6 +4684,8 @@ impl Editor {
 
     pub fn move_to_snippet_tabstop(&mut self, bias: Bias, cx: &mut ViewContext<Self>) -> bool {
         if let Some(mut snippet) = self.snippet_stack.pop() {
+            let tabstop_index = snippet.active_index;
+            self.apply_snippet_transforms(&mut snippet, tabstop_index, cx);
             match bias {
                 Bias::Left => {
                     if snippet.active_index > 0 {
@@ -4295,10 +4708,14 @@ impl Editor {
                 self.change_selections(Some(Autoscroll::fit()), cx, |s| {
                     s.select_anchor_ranges(current_ranges.iter().cloned())
                 });
+                let choices = snippet.choices[snippet.active_index].clone();
                 // If snippet state is not at the last tabstop, push it back on the stack
                 if snippet.active_index + 1 < snippet.ranges.len() {
                     self.snippet_stack.push(snippet);
                 }
+                if let Some(choices) = choices {
+                    self.show_snippet_choices(choices, cx);
+                }
                 return true;
             }
         }
@@ -6193,7 +6610,9 @@ impl Editor {
         self.nav_history.as_ref()
     }
 
-    fn push_to_nav_history(
+    /// Records the cursor position in the navigation history. When `new_position` is given,
+    /// the position is only recorded if the cursor moves far enough from it.
+    pub fn push_to_nav_history(
         &mut self,
         cursor_anchor: Anchor,
         new_position: Option<Point>,
@@ -7104,6 +7523,54 @@ impl Editor {
         self.select_larger_syntax_node_stack = stack;
     }
 
+    pub fn select_enclosing_function(
+        &mut self,
+        _: &SelectEnclosingFunction,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.select_enclosing_text_object(TextObjectKind::Function, cx);
+    }
+
+    pub fn select_enclosing_class(&mut self, _: &SelectEnclosingClass, cx: &mut ViewContext<Self>) {
+        self.select_enclosing_text_object(TextObjectKind::Class, cx);
+    }
+
+    /// Selects the innermost text object of the given kind around each selection. The
+    /// previous selections are restored by selecting a smaller syntax node.
+    fn select_enclosing_text_object(&mut self, kind: TextObjectKind, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
+
+        let mut stack = mem::take(&mut self.select_larger_syntax_node_stack);
+        let mut selected_enclosing_object = false;
+        let new_selections = old_selections
+            .iter()
+            .map(|selection| {
+                let old_range = selection.start..selection.end;
+                let new_range = buffer
+                    .enclosing_text_object(old_range.clone(), kind)
+                    .map_or(old_range.clone(), |text_object| text_object.around);
+
+                selected_enclosing_object |= new_range != old_range;
+                Selection {
+                    id: selection.id,
+                    start: new_range.start,
+                    end: new_range.end,
+                    goal: SelectionGoal::None,
+                    reversed: selection.reversed,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if selected_enclosing_object {
+            stack.push(old_selections);
+            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        }
+        self.select_larger_syntax_node_stack = stack;
+    }
+
     pub fn move_to_enclosing_bracket(
         &mut self,
         _: &MoveToEnclosingBracket,
@@ -7341,6 +7808,175 @@ impl Editor {
         }
     }
 
+    pub fn stage_hunk(&mut self, _: &StageHunk, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+
+        for (buffer, rows) in self.selected_buffer_rows(cx) {
+            let snapshot = buffer.read(cx).snapshot();
+            let Some(diff_base) = buffer.read(cx).diff_base() else {
+                continue;
+            };
+            let hunks = hunks_intersecting_rows(
+                snapshot.git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1),
+                &rows,
+            );
+            if hunks.is_empty() {
+                continue;
+            }
+
+            let index_text = ::git::diff::apply_hunks_to_diff_base(diff_base, &snapshot, hunks);
+            project
+                .update(cx, |project, cx| {
+                    project.set_index_text(&buffer, Some(index_text), cx)
+                })
+                .detach_and_log_err(cx);
+        }
+    }
+
+    pub fn unstage_hunk(&mut self, _: &UnstageHunk, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+
+        for (buffer, rows) in self.selected_buffer_rows(cx) {
+            let snapshot = buffer.read(cx).snapshot();
+            let Some(index_text) = buffer.read(cx).diff_base().map(str::to_string) else {
+                continue;
+            };
+
+            // Staged changes are the differences between HEAD and the index, so find
+            // the rows of the index that correspond to the selected rows.
+            let buffer_hunks = snapshot
+                .git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1)
+                .collect::<Vec<_>>();
+            let index_rows = rows
+                .iter()
+                .map(|rows| {
+                    let start = ::git::diff::diff_base_row_for_buffer_row(
+                        rows.start,
+                        &index_text,
+                        buffer_hunks.iter().cloned(),
+                    );
+                    let end = ::git::diff::diff_base_row_for_buffer_row(
+                        rows.end - 1,
+                        &index_text,
+                        buffer_hunks.iter().cloned(),
+                    );
+                    start..end + 1
+                })
+                .collect::<Vec<_>>();
+
+            let head_text = project.read(cx).load_head_text(&buffer, cx);
+            let project = project.downgrade();
+            cx.spawn(|_, mut cx| async move {
+                // Unstaging a file that is not in HEAD removes it from the index.
+                let new_index_text = match head_text.await? {
+                    Some(head_text) => {
+                        let index_buffer =
+                            text::Buffer::new(0, BufferId::new(1).unwrap(), index_text.clone());
+                        let index_snapshot = index_buffer.snapshot();
+                        let mut diff = ::git::diff::BufferDiff::new();
+                        diff.update(&head_text, &index_snapshot).await;
+                        let hunks = hunks_intersecting_rows(
+                            diff.hunks_intersecting_range(
+                                text::Anchor::MIN..text::Anchor::MAX,
+                                &index_snapshot,
+                            ),
+                            &index_rows,
+                        );
+                        if hunks.is_empty() {
+                            return Ok(());
+                        }
+                        Some(::git::diff::revert_hunks_to_diff_base(
+                            &head_text,
+                            &index_snapshot,
+                            hunks,
+                        ))
+                    }
+                    None => None,
+                };
+
+                project
+                    .update(&mut cx, |project, cx| {
+                        project.set_index_text(&buffer, new_index_text, cx)
+                    })?
+                    .await
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+
+    pub fn revert_hunk(&mut self, _: &RevertHunk, cx: &mut ViewContext<Self>) {
+        let mut edits_by_buffer = Vec::new();
+        for (buffer, rows) in self.selected_buffer_rows(cx) {
+            let snapshot = buffer.read(cx).snapshot();
+            let Some(diff_base) = buffer.read(cx).diff_base() else {
+                continue;
+            };
+            let max_point = snapshot.max_point();
+            let point_for_row = |row: u32| {
+                if row > max_point.row {
+                    max_point
+                } else {
+                    Point::new(row, 0)
+                }
+            };
+            let edits = hunks_intersecting_rows(
+                snapshot.git_diff_hunks_in_row_range(0..max_point.row + 1),
+                &rows,
+            )
+            .into_iter()
+            .map(|hunk| {
+                let range =
+                    point_for_row(hunk.buffer_range.start)..point_for_row(hunk.buffer_range.end);
+                (range, diff_base[hunk.diff_base_byte_range].to_string())
+            })
+            .collect::<Vec<_>>();
+            if !edits.is_empty() {
+                edits_by_buffer.push((buffer, edits));
+            }
+        }
+
+        if edits_by_buffer.is_empty() {
+            return;
+        }
+        self.transact(cx, |_, cx| {
+            for (buffer, edits) in edits_by_buffer {
+                buffer.update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+            }
+        });
+    }
+
+    /// Returns the rows spanned by the selections, grouped by the buffers that they
+    /// intersect.
+    fn selected_buffer_rows(
+        &self,
+        cx: &mut ViewContext<Self>,
+    ) -> Vec<(Model<Buffer>, Vec<Range<u32>>)> {
+        let selections = self.selections.all::<usize>(cx);
+        let multi_buffer = self.buffer.read(cx);
+        let mut rows_by_buffer: Vec<(Model<Buffer>, Vec<Range<u32>>)> = Vec::new();
+        for selection in selections {
+            for (buffer, range, _) in multi_buffer.range_to_buffer_ranges(selection.range(), cx) {
+                let rows = {
+                    let buffer = buffer.read(cx);
+                    buffer.offset_to_point(range.start).row
+                        ..buffer.offset_to_point(range.end).row + 1
+                };
+                match rows_by_buffer
+                    .iter_mut()
+                    .find(|(existing_buffer, _)| *existing_buffer == buffer)
+                {
+                    Some((_, buffer_rows)) => buffer_rows.push(rows),
+                    None => rows_by_buffer.push((buffer, vec![rows])),
+                }
+            }
+        }
+        rows_by_buffer
+    }
+
     fn seek_in_direction(
         &mut self,
         snapshot: &DisplaySnapshot,
@@ -8326,6 +8962,36 @@ impl Editor {
         }
     }
 
+    /// Folds every range nested at the given level, where level 1 is the outermost one.
+    pub fn fold_at_level(&mut self, fold_at_level: &FoldAtLevel, cx: &mut ViewContext<Self>) {
+        let depth = fold_at_level.level.saturating_sub(1) as usize;
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let fold_ranges = display_map
+            .foldable_ranges_with_depth()
+            .into_iter()
+            .filter(|(range_depth, _)| *range_depth == depth)
+            .map(|(_, fold_range)| fold_range.range);
+        self.fold_ranges(fold_ranges, true, cx);
+    }
+
+    pub fn fold_comments(&mut self, _: &FoldComments, cx: &mut ViewContext<Self>) {
+        self.fold_ranges_of_kind(FoldKind::Comment, cx);
+    }
+
+    pub fn fold_imports(&mut self, _: &FoldImports, cx: &mut ViewContext<Self>) {
+        self.fold_ranges_of_kind(FoldKind::Imports, cx);
+    }
+
+    fn fold_ranges_of_kind(&mut self, kind: FoldKind, cx: &mut ViewContext<Self>) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let fold_ranges = display_map
+            .foldable_ranges_with_depth()
+            .into_iter()
+            .filter(|(_, fold_range)| fold_range.kind == kind)
+            .map(|(_, fold_range)| fold_range.range);
+        self.fold_ranges(fold_ranges, true, cx);
+    }
+
     pub fn unfold_lines(&mut self, _: &UnfoldLines, cx: &mut ViewContext<Self>) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = &display_map.buffer_snapshot;
@@ -8393,6 +9059,7 @@ impl Editor {
                 self.request_autoscroll(Autoscroll::fit(), cx);
             }
 
+            self.save_folds(cx);
             cx.notify();
         }
     }
@@ -8412,6 +9079,7 @@ impl Editor {
                 self.request_autoscroll(Autoscroll::fit(), cx);
             }
 
+            self.save_folds(cx);
             cx.notify();
         }
     }
@@ -8591,6 +9259,73 @@ impl Editor {
         cx.notify();
     }
 
+    pub fn set_show_line_numbers(&mut self, show_line_numbers: bool, cx: &mut ViewContext<Self>) {
+        self.show_line_numbers = Some(show_line_numbers);
+        cx.notify();
+    }
+
+    pub fn set_relative_line_numbers(&mut self, is_relative: bool, cx: &mut ViewContext<Self>) {
+        self.use_relative_line_numbers = Some(is_relative);
+        cx.notify();
+    }
+
+    pub fn toggle_git_blame(&mut self, _: &ToggleGitBlame, cx: &mut ViewContext<Self>) {
+        self.show_git_blame_gutter = !self.show_git_blame_gutter;
+        self.update_git_blame(cx);
+    }
+
+    pub fn toggle_git_blame_inline(
+        &mut self,
+        _: &ToggleGitBlameInline,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.show_git_blame_inline = !self.show_git_blame_inline;
+        self.update_git_blame(cx);
+    }
+
+    fn update_git_blame(&mut self, cx: &mut ViewContext<Self>) {
+        if self.show_git_blame_gutter || self.show_git_blame_inline {
+            self.start_git_blame(cx);
+        } else {
+            self.blame.take();
+            self.blame_subscription.take();
+        }
+        cx.notify();
+    }
+
+    fn start_git_blame(&mut self, cx: &mut ViewContext<Self>) {
+        if self.blame.is_some() {
+            return;
+        }
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+
+        let blame = cx.new_model(|cx| GitBlame::new(buffer, project, cx));
+        self.blame_subscription = Some(cx.observe(&blame, |_, _, cx| cx.notify()));
+        self.blame = Some(blame);
+    }
+
+    pub fn blame(&self) -> Option<&Model<GitBlame>> {
+        self.blame.as_ref()
+    }
+
+    fn render_git_blame_gutter(&self) -> bool {
+        self.show_git_blame_gutter && self.blame.is_some()
+    }
+
+    fn render_git_blame_inline(&self, cx: &WindowContext) -> bool {
+        self.show_git_blame_inline
+            && self.focus_handle.is_focused(cx)
+            && self
+                .blame
+                .as_ref()
+                .map_or(false, |blame| blame.read(cx).has_generated_entries())
+    }
+
     pub fn set_show_wrap_guides(&mut self, show_gutter: bool, cx: &mut ViewContext<Self>) {
         self.show_wrap_guides = Some(show_gutter);
         cx.notify();
@@ -9023,6 +9758,9 @@ impl Editor {
             } => {
                 self.refresh_active_diagnostics(cx);
                 self.refresh_code_actions(cx);
+                refresh_semantic_tokens(self, cx);
+                refresh_code_lenses(self, cx);
+                refresh_folding_ranges(self, cx);
                 if self.has_active_copilot_suggestion(cx) {
                     self.update_visible_copilot_suggestion(cx);
                 }
@@ -9073,6 +9811,9 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                refresh_semantic_tokens(self, cx);
+                refresh_code_lenses(self, cx);
+                refresh_folding_ranges(self, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
@@ -9084,7 +9825,10 @@ impl Editor {
                 cx.notify();
             }
             multi_buffer::Event::DirtyChanged => cx.emit(EditorEvent::DirtyChanged),
-            multi_buffer::Event::Saved => cx.emit(EditorEvent::Saved),
+            multi_buffer::Event::Saved => {
+                self.save_folds(cx);
+                cx.emit(EditorEvent::Saved);
+            }
             multi_buffer::Event::FileHandleChanged | multi_buffer::Event::Reloaded => {
                 cx.emit(EditorEvent::TitleChanged)
             }
@@ -9114,6 +9858,8 @@ impl Editor {
         let editor_settings = EditorSettings::get_global(cx);
         self.scroll_manager.vertical_scroll_margin = editor_settings.vertical_scroll_margin;
         self.show_breadcrumbs = editor_settings.toolbar.breadcrumbs;
+        refresh_semantic_tokens(self, cx);
+        refresh_code_lenses(self, cx);
         cx.notify();
     }
 
@@ -9516,6 +10262,7 @@ impl Editor {
             .update(cx, |buffer, cx| buffer.remove_active_selections(cx));
         self.hide_context_menu(cx);
         hide_hover(self, cx);
+        hide_signature_help(self, cx);
         cx.emit(EditorEvent::Blurred);
         cx.notify();
     }
@@ -9602,8 +10349,25 @@ impl CompletionProvider for Model<Project> {
         buffer_position: text::Anchor,
         cx: &mut ViewContext<Editor>,
     ) -> Task<Result<Vec<Completion>>> {
-        self.update(cx, |project, cx| {
+        let project_completions = self.update(cx, |project, cx| {
             project.completions(&buffer, buffer_position, cx)
+        });
+        let snippet_completions = snippet_completions(buffer, buffer_position, cx);
+        if snippet_completions.is_empty() {
+            return project_completions;
+        }
+
+        cx.foreground_executor().spawn(async move {
+            // Snippets are still offered when the language server fails to provide completions.
+            let mut completions = match project_completions.await {
+                Ok(completions) => completions,
+                Err(error) => {
+                    log::error!("failed to fetch completions: {error:#}");
+                    Vec::new()
+                }
+            };
+            completions.extend(snippet_completions);
+            Ok(completions)
         })
     }
 
@@ -9631,6 +10395,56 @@ impl CompletionProvider for Model<Project> {
     }
 }
 
+/// Returns completions for the user snippets whose language matches the buffer, replacing
+/// the word before the given position.
+fn snippet_completions(
+    buffer: &Model<Buffer>,
+    buffer_position: text::Anchor,
+    cx: &mut ViewContext<Editor>,
+) -> Vec<Completion> {
+    use language::ToOffset as _;
+
+    let snippet_registry = SnippetRegistry::default_global(cx);
+    let buffer = buffer.read(cx);
+    let snapshot = buffer.snapshot();
+    let position = buffer_position.to_offset(&snapshot);
+    let (word_range, kind) = snapshot.surrounding_word(position);
+    if kind != Some(CharKind::Word) || word_range.start >= position {
+        return Vec::new();
+    }
+
+    let language_name = snapshot
+        .language_at(position)
+        .map(|language| language.name());
+    let worktree_id = buffer.file().map(|file| file.worktree_id());
+    let snippets = snippet_registry.snippets_for(language_name.as_deref(), worktree_id);
+    let old_range = snapshot.anchor_before(word_range.start)..buffer_position;
+    snippets
+        .iter()
+        .flat_map(|snippet| {
+            let old_range = old_range.clone();
+            snippet.prefixes.iter().map(move |prefix| Completion {
+                old_range: old_range.clone(),
+                new_text: snippet.body.clone(),
+                label: CodeLabel::plain(prefix.clone(), None),
+                server_id: LanguageServerId(usize::MAX),
+                documentation: Some(match &snippet.description {
+                    Some(description) => Documentation::SingleLine(description.clone()),
+                    None => Documentation::MultiLinePlainText(snippet.body.clone()),
+                }),
+                lsp_completion: lsp::CompletionItem {
+                    label: prefix.clone(),
+                    kind: Some(lsp::CompletionItemKind::SNIPPET),
+                    detail: Some(snippet.name.clone()),
+                    filter_text: Some(prefix.clone()),
+                    insert_text_format: Some(lsp::InsertTextFormat::SNIPPET),
+                    ..Default::default()
+                },
+            })
+        })
+        .collect()
+}
+
 fn inlay_hint_settings(
     location: Anchor,
     snapshot: &MultiBufferSnapshot,
@@ -9673,6 +10487,22 @@ fn ending_row(next_selection: &Selection<Point>, display_map: &DisplaySnapshot)
     }
 }
 
+/// Returns the hunks that intersect any of the given row ranges. Hunks that only
+/// delete lines are considered to span the row that follows the deletion.
+fn hunks_intersecting_rows(
+    hunks: impl IntoIterator<Item = DiffHunk<u32>>,
+    rows: &[Range<u32>],
+) -> Vec<DiffHunk<u32>> {
+    hunks
+        .into_iter()
+        .filter(|hunk| {
+            let start = hunk.buffer_range.start;
+            let end = hunk.buffer_range.end.max(start + 1);
+            rows.iter().any(|rows| start < rows.end && rows.start < end)
+        })
+        .collect()
+}
+
 impl EditorSnapshot {
     pub fn remote_selections_in_range<'a>(
         &'a self,
@@ -9721,6 +10551,16 @@ impl EditorSnapshot {
         self.scroll_anchor.scroll_position(&self.display_snapshot)
     }
 
+    pub fn show_line_numbers(&self, cx: &AppContext) -> bool {
+        self.show_line_numbers
+            .unwrap_or(EditorSettings::get_global(cx).gutter.line_numbers)
+    }
+
+    pub fn use_relative_line_numbers(&self, cx: &AppContext) -> bool {
+        self.use_relative_line_numbers
+            .unwrap_or(EditorSettings::get_global(cx).relative_line_numbers)
+    }
+
     pub fn gutter_dimensions(
         &self,
         font_id: FontId,
@@ -9739,8 +10579,9 @@ impl EditorSnapshot {
             Some(GitGutterSetting::TrackedFiles)
         );
         let gutter_settings = EditorSettings::get_global(cx).gutter;
+        let show_line_numbers = self.show_line_numbers(cx);
 
-        let line_gutter_width = if gutter_settings.line_numbers {
+        let line_gutter_width = if show_line_numbers {
             // Avoid flicker-like gutter resizes when the line number gains another digit and only resize the gutter on files with N*10^5 lines.
             let min_width_for_number_on_gutter = em_width * 4.0;
             max_line_number_width.max(min_width_for_number_on_gutter)
@@ -9750,19 +10591,23 @@ impl EditorSnapshot {
 
         let left_padding = if gutter_settings.code_actions {
             em_width * 3.0
-        } else if show_git_gutter && gutter_settings.line_numbers {
+        } else if show_git_gutter && show_line_numbers {
             em_width * 2.0
-        } else if show_git_gutter || gutter_settings.line_numbers {
+        } else if show_git_gutter || show_line_numbers {
             em_width
         } else {
             px(0.)
         };
 
-        let right_padding = if gutter_settings.folds && gutter_settings.line_numbers {
+        let git_blame_entries_width = self
+            .show_git_blame_gutter
+            .then_some(em_width * GIT_BLAME_GUTTER_WIDTH_CHARS);
+
+        let right_padding = if gutter_settings.folds && show_line_numbers {
             em_width * 4.0
         } else if gutter_settings.folds {
             em_width * 3.0
-        } else if gutter_settings.line_numbers {
+        } else if show_line_numbers {
             em_width
         } else {
             px(0.)
@@ -9771,8 +10616,12 @@ impl EditorSnapshot {
         GutterDimensions {
             left_padding,
             right_padding,
-            width: line_gutter_width + left_padding + right_padding,
+            width: line_gutter_width
+                + left_padding
+                + right_padding
+                + git_blame_entries_width.unwrap_or_default(),
             margin: -descent,
+            git_blame_entries_width,
         }
     }
 }
@@ -10211,10 +11060,12 @@ impl<T: ToPoint + ToOffset> SelectionExt for Selection<T> {
 }
 
 impl<T: InvalidationRegion> InvalidationStack<T> {
-    fn invalidate<S>(&mut self, selections: &[Selection<S>], buffer: &MultiBufferSnapshot)
+    /// Pops the regions that no longer contain the selections, returning them.
+    fn invalidate<S>(&mut self, selections: &[Selection<S>], buffer: &MultiBufferSnapshot) -> Vec<T>
     where
         S: Clone + ToOffset,
     {
+        let mut invalidated = Vec::new();
         while let Some(region) = self.last() {
             let all_selections_inside_invalidation_ranges =
                 if selections.len() == region.ranges().len() {
@@ -10232,9 +11083,10 @@ impl<T: InvalidationRegion> InvalidationStack<T> {
             if all_selections_inside_invalidation_ranges {
                 break;
             } else {
-                self.pop();
+                invalidated.extend(self.pop());
             }
         }
+        invalidated
     }
 }
 
@@ -10258,6 +11110,20 @@ impl<T> DerefMut for InvalidationStack<T> {
     }
 }
 
+/// Anchors a snippet mirror so that text typed right before or after it stays out of it.
+fn snippet_mirror_range(
+    snapshot: &MultiBufferSnapshot,
+    range: Range<usize>,
+    empty_bias: Bias,
+) -> Range<Anchor> {
+    if range.is_empty() {
+        let anchor = snapshot.anchor_at(range.start, empty_bias);
+        anchor..anchor
+    } else {
+        snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end)
+    }
+}
+
 impl InvalidationRegion for SnippetState {
     fn ranges(&self) -> &[Range<Anchor>] {
         &self.ranges[self.active_index]
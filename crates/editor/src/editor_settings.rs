@@ -1,3 +1,4 @@
+use collections::HashMap;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
@@ -7,12 +8,15 @@ pub struct EditorSettings {
     pub cursor_blink: bool,
     pub hover_popover_enabled: bool,
     pub show_completions_on_input: bool,
+    pub show_signature_help_on_input: bool,
     pub show_completion_documentation: bool,
     pub completion_documentation_secondary_query_debounce: u64,
     pub use_on_type_format: bool,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
     pub gutter: Gutter,
+    pub semantic_tokens: SemanticTokens,
+    pub code_lens: bool,
     pub vertical_scroll_margin: f32,
     pub relative_line_numbers: bool,
     pub seed_search_query_from_cursor: SeedQuerySetting,
@@ -53,6 +57,22 @@ pub struct Gutter {
     pub folds: bool,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct SemanticTokens {
+    pub enabled: bool,
+    pub modifiers: HashMap<String, SemanticTokenModifierStyle>,
+}
+
+/// Extra styling for semantic tokens carrying a given modifier, applied on top
+/// of the color the theme gives to the token's type.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(default)]
+pub struct SemanticTokenModifierStyle {
+    pub underline: bool,
+    pub italic: bool,
+    pub bold: bool,
+}
+
 /// When to show the scrollbar in the editor.
 ///
 /// Default: auto
@@ -86,6 +106,11 @@ pub struct EditorSettingsContent {
     ///
     /// Default: true
     pub show_completions_on_input: Option<bool>,
+    /// Whether to show the signature of the function being called while typing
+    /// its arguments, when the language server reports a trigger character.
+    ///
+    /// Default: true
+    pub show_signature_help_on_input: Option<bool>,
     /// Whether to display inline and alongside documentation for items in the
     /// completions menu.
     ///
@@ -107,6 +132,13 @@ pub struct EditorSettingsContent {
     pub scrollbar: Option<ScrollbarContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
+    /// Semantic token related settings
+    pub semantic_tokens: Option<SemanticTokensContent>,
+    /// Whether to show the code lenses provided by language servers, such as
+    /// "Run test" or "3 references", above the code they refer to.
+    ///
+    /// Default: true
+    pub code_lens: Option<bool>,
 
     /// The number of lines to keep above/below the cursor when auto-scrolling.
     ///
@@ -184,6 +216,18 @@ pub struct GutterContent {
     pub folds: Option<bool>,
 }
 
+/// Semantic token related settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct SemanticTokensContent {
+    /// Whether to highlight the semantic tokens reported by language servers
+    /// on top of the syntax highlighting.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// Extra styling for tokens with the given modifiers, such as `mutable`.
+    pub modifiers: Option<HashMap<String, SemanticTokenModifierStyle>>,
+}
+
 impl Settings for EditorSettings {
     const KEY: Option<&'static str> = None;
 
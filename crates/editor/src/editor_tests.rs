@@ -805,6 +805,108 @@ fn test_fold_action(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_fold_at_level_comments_and_imports(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        )
+        .with_folds_query(
+            r#"
+                (_ "{" "}") @fold
+                (_ "(" ")") @fold
+                (line_comment) @fold.comment
+                (use_declaration) @fold.imports
+                "#,
+        )
+        .unwrap(),
+    );
+
+    let text = "
+        use a::b;
+        use c::d;
+
+        // one
+        // two
+        impl Foo {
+            fn a() {
+                1
+            }
+
+            fn b() {
+                2
+            }
+        }
+    "
+    .unindent();
+
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(language, cx)
+    });
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (view, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+    view.condition::<crate::EditorEvent>(&cx, |view, cx| !view.buffer.read(cx).is_parsing(cx))
+        .await;
+
+    _ = view.update(cx, |view, cx| {
+        view.fold_at_level(&FoldAtLevel { level: 2 }, cx);
+        assert_eq!(
+            view.display_text(cx),
+            "
+                use a::b;
+                use c::d;
+
+                // one
+                // two
+                impl Foo {
+                    fn a() {⋯
+                    }
+
+                    fn b() {⋯
+                    }
+                }
+            "
+            .unindent(),
+        );
+
+        view.fold_comments(&FoldComments, cx);
+        view.fold_imports(&FoldImports, cx);
+        assert_eq!(
+            view.display_text(cx),
+            "
+                use a::b;⋯
+
+                // one⋯
+                impl Foo {
+                    fn a() {⋯
+                    }
+
+                    fn b() {⋯
+                    }
+                }
+            "
+            .unindent(),
+        );
+
+        view.fold_at_level(&FoldAtLevel { level: 1 }, cx);
+        assert_eq!(
+            view.display_text(cx),
+            "
+                use a::b;⋯
+
+                // one⋯
+                impl Foo {⋯
+                }
+            "
+            .unindent(),
+        );
+    });
+}
+
 #[gpui::test]
 fn test_move_cursor(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -4284,6 +4386,83 @@ async fn test_select_larger_smaller_syntax_node(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_select_enclosing_function_and_class(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        )
+        .with_text_object_query(
+            r#"
+                (function_item body: (_) @function.inside) @function.around
+                (impl_item body: (_) @class.inside) @class.around
+                "#,
+        )
+        .unwrap(),
+    );
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+
+    cx.set_state(indoc! {"
+        impl A {
+            fn b() {
+                fn c() {
+                    dˇ();
+                }
+            }
+        }
+    "});
+    cx.executor().run_until_parked();
+
+    cx.update_editor(|e, cx| e.select_enclosing_function(&SelectEnclosingFunction, cx));
+    cx.assert_editor_state(indoc! {"
+        impl A {
+            fn b() {
+                «fn c() {
+                    d();
+                }ˇ»
+            }
+        }
+    "});
+
+    // Selecting the enclosing function again selects the next enclosing one.
+    cx.update_editor(|e, cx| e.select_enclosing_function(&SelectEnclosingFunction, cx));
+    cx.assert_editor_state(indoc! {"
+        impl A {
+            «fn b() {
+                fn c() {
+                    d();
+                }
+            }ˇ»
+        }
+    "});
+
+    cx.update_editor(|e, cx| e.select_enclosing_class(&SelectEnclosingClass, cx));
+    cx.assert_editor_state(indoc! {"
+        «impl A {
+            fn b() {
+                fn c() {
+                    d();
+                }
+            }
+        }ˇ»
+    "});
+
+    // The previous selections are restored by selecting a smaller syntax node.
+    cx.update_editor(|e, cx| e.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {"
+        impl A {
+            «fn b() {
+                fn c() {
+                    d();
+                }
+            }ˇ»
+        }
+    "});
+}
+
 #[gpui::test]
 async fn test_autoindent_selections(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -5229,6 +5408,131 @@ async fn test_snippets(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_snippet_choices_and_transforms(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let (text, insertion_ranges) = marked_text_ranges("let ˇ", false);
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+
+    _ = editor.update(cx, |editor, cx| {
+        let snippet = Snippet::parse("${1|x,y|} = ${1/(.*)/${1:/upcase}/};$0").unwrap();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, cx)
+            .unwrap();
+
+        // Transformed mirrors are computed as soon as the snippet is inserted.
+        assert_eq!(editor.text(cx), "let x = X;");
+        assert_eq!(editor.selections.ranges::<usize>(cx), &[4..5]);
+        {
+            let context_menu = editor.context_menu.read();
+            let Some(ContextMenu::SnippetChoices(menu)) = context_menu.as_ref() else {
+                panic!("expected the choices of the tab stop to be shown");
+            };
+            assert_eq!(menu.choices.as_ref(), &["x", "y"]);
+        }
+
+        editor
+            .confirm_completion(&ConfirmCompletion { item_ix: Some(1) }, cx)
+            .unwrap()
+            .detach();
+        assert_eq!(editor.text(cx), "let y = X;");
+
+        // Leaving the tab stop updates its transformed mirrors.
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert_eq!(editor.text(cx), "let y = Y;");
+        assert_eq!(editor.selections.ranges::<usize>(cx), &[10..10]);
+
+        // Exiting the snippet with escape updates the transformed mirrors.
+        let snippet = Snippet::parse("${1|x,y|} = ${1/(.*)/${1:/upcase}/};").unwrap();
+        editor.insert_snippet(&[10..10], snippet, cx).unwrap();
+        assert_eq!(editor.text(cx), "let y = Y;x = X;");
+        editor
+            .confirm_completion(&ConfirmCompletion { item_ix: Some(1) }, cx)
+            .unwrap()
+            .detach();
+        assert_eq!(editor.text(cx), "let y = Y;y = X;");
+        editor.cancel(&Cancel, cx);
+        assert_eq!(editor.text(cx), "let y = Y;y = Y;");
+
+        // So does moving the cursor out of the snippet.
+        editor.change_selections(None, cx, |s| s.select_ranges([16..16]));
+        let snippet = Snippet::parse("${1|x,y|} = ${1/(.*)/${1:/upcase}/};").unwrap();
+        editor.insert_snippet(&[16..16], snippet, cx).unwrap();
+        editor
+            .confirm_completion(&ConfirmCompletion { item_ix: Some(1) }, cx)
+            .unwrap()
+            .detach();
+        assert_eq!(editor.text(cx), "let y = Y;y = Y;y = X;");
+        editor.change_selections(None, cx, |s| s.select_ranges([0..0]));
+        assert_eq!(editor.text(cx), "let y = Y;y = Y;y = Y;");
+        assert!(!editor.move_to_next_snippet_tabstop(cx));
+    });
+}
+
+#[gpui::test]
+async fn test_snippet_transforms_with_multiple_cursors(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let (text, insertion_ranges) = marked_text_ranges("a ˇ\nb ˇ", false);
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+
+    _ = editor.update(cx, |editor, cx| {
+        let snippet = Snippet::parse("$1=${1/(.*)/${1:/upcase}/}$2").unwrap();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, cx)
+            .unwrap();
+        assert_eq!(editor.text(cx), "a =\nb =");
+
+        editor.handle_input("ab", cx);
+        editor.buffer.update(cx, |buffer, cx| {
+            buffer.edit([(10..10, "c")], None, cx);
+        });
+        assert_eq!(editor.text(cx), "a ab=\nb abc=");
+
+        // Each mirror is transformed from the tab stop inserted at its own cursor.
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert_eq!(editor.text(cx), "a ab=AB\nb abc=ABC");
+        assert_eq!(editor.selections.ranges::<usize>(cx), &[7..7, 17..17]);
+
+        // Text typed in the tab stop right after a mirror stays out of it.
+        editor.handle_input("!", cx);
+        assert_eq!(editor.text(cx), "a ab=AB!\nb abc=ABC!");
+        assert!(editor.move_to_prev_snippet_tabstop(cx));
+        assert_eq!(editor.selections.ranges::<usize>(cx), &[2..4, 11..14]);
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert_eq!(editor.text(cx), "a ab=AB!\nb abc=ABC!");
+    });
+
+    let (text, insertion_ranges) = marked_text_ranges("ˇ", false);
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+
+    _ = editor.update(cx, |editor, cx| {
+        let snippet = Snippet::parse("$1${1/(.*)/${1:/upcase}/}=$2").unwrap();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, cx)
+            .unwrap();
+
+        // Text typed in the tab stop right before a mirror stays out of it, before and
+        // after the mirror is rewritten.
+        editor.handle_input("ab", cx);
+        assert_eq!(editor.text(cx), "ab=");
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert_eq!(editor.text(cx), "abAB=");
+        assert_eq!(editor.selections.ranges::<usize>(cx), &[5..5]);
+        assert!(editor.move_to_prev_snippet_tabstop(cx));
+        assert_eq!(editor.selections.ranges::<usize>(cx), &[0..2]);
+
+        editor.handle_input("x", cx);
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert_eq!(editor.text(cx), "xX=");
+        assert_eq!(editor.selections.ranges::<usize>(cx), &[3..3]);
+    });
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -5747,6 +6051,63 @@ async fn test_strip_whitespace_and_format_via_lsp(cx: &mut gpui::TestAppContext)
     );
 }
 
+#[gpui::test]
+async fn test_snippet_completions(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            completion_provider: Some(lsp::CompletionOptions::default()),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+    cx.update(|cx| {
+        SnippetRegistry::default_global(cx)
+            .insert_file(
+                Path::new("/config/snippets/rust.json").into(),
+                None,
+                r##"{
+                    "Test function": {
+                        "prefix": "tst",
+                        "body": ["#[test]", "fn ${1:name}() {", "    $0", "}"]
+                    },
+                    "Test": { "prefix": "tso", "body": "test($0)", "scope": "javascript" }
+                }"##,
+            )
+            .unwrap();
+    });
+
+    // User snippets are offered even when the language server fails to provide completions.
+    let mut requests = cx.handle_request::<lsp::request::Completion, _, _>(|_, _, _| async move {
+        Err(anyhow!("the server failed"))
+    });
+    cx.set_state("tsˇ");
+    cx.update_editor(|editor, cx| editor.show_completions(&ShowCompletions, cx));
+    requests.next().await;
+    cx.executor().run_until_parked();
+    cx.update_editor(|editor, _| {
+        let context_menu = editor.context_menu.read();
+        let Some(ContextMenu::Completions(menu)) = context_menu.as_ref() else {
+            panic!("expected the snippet completions to be shown");
+        };
+        assert_eq!(
+            menu.matches.iter().map(|m| &m.string).collect::<Vec<_>>(),
+            &["tst"]
+        );
+    });
+
+    // Confirming a snippet completion expands it, selecting its first tab stop.
+    cx.update_editor(|editor, cx| {
+        editor
+            .confirm_completion(&ConfirmCompletion::default(), cx)
+            .unwrap()
+            .detach();
+    });
+    cx.assert_editor_state("#[test]\nfn «nameˇ»() {\n    \n}");
+}
+
 #[gpui::test]
 async fn test_completion(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -7324,6 +7685,108 @@ async fn go_to_hunk(executor: BackgroundExecutor, cx: &mut gpui::TestAppContext)
     );
 }
 
+#[gpui::test]
+async fn test_revert_hunk(executor: BackgroundExecutor, cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    let diff_base = r#"
+        use some::mod;
+
+        const A: u32 = 42;
+        const B: u32 = 42;
+
+        fn main() {
+            println!("hello");
+
+            println!("world");
+        }
+        "#
+    .unindent();
+
+    cx.set_state(
+        &r#"
+        use some::modified;
+
+        ˇconst B: u32 = 42;
+
+        fn main() {
+            println!("hello there");
+
+            println!("around the");
+            println!("world");
+        }
+        "#
+        .unindent(),
+    );
+    cx.set_diff_base(Some(&diff_base));
+    executor.run_until_parked();
+
+    // A cursor on the row following removed lines restores them.
+    cx.update_editor(|editor, cx| editor.revert_hunk(&RevertHunk, cx));
+    executor.run_until_parked();
+    cx.assert_editor_state(
+        &r#"
+        use some::modified;
+
+        const A: u32 = 42;
+        ˇconst B: u32 = 42;
+
+        fn main() {
+            println!("hello there");
+
+            println!("around the");
+            println!("world");
+        }
+        "#
+        .unindent(),
+    );
+
+    // Selections spanning several hunks revert all of them.
+    cx.set_state(
+        &r#"
+        «use some::modified;
+
+        const A: u32 = 42;
+        const B: u32 = 42;
+
+        fn main() {
+            println!("hello there");
+
+            println!("around the");ˇ»
+            println!("world");
+        }
+        "#
+        .unindent(),
+    );
+    executor.run_until_parked();
+    cx.update_editor(|editor, cx| editor.revert_hunk(&RevertHunk, cx));
+    executor.run_until_parked();
+    assert_eq!(cx.buffer_text(), diff_base);
+
+    // Reverting hunks can be undone.
+    cx.update_editor(|editor, cx| editor.undo(&Undo, cx));
+    executor.run_until_parked();
+    assert_eq!(
+        cx.buffer_text(),
+        r#"
+        use some::modified;
+
+        const A: u32 = 42;
+        const B: u32 = 42;
+
+        fn main() {
+            println!("hello there");
+
+            println!("around the");
+            println!("world");
+        }
+        "#
+        .unindent()
+    );
+}
+
 #[test]
 fn test_split_words() {
     fn split<'a>(text: &'a str) -> Vec<&'a str> {
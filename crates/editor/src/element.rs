@@ -11,14 +11,15 @@ use crate::{
     items::BufferSearchHighlights,
     mouse_context_menu,
     scroll::scroll_amount::ScrollAmount,
-    CursorShape, DisplayPoint, DocumentHighlightRead, DocumentHighlightWrite, Editor, EditorMode,
-    EditorSettings, EditorSnapshot, EditorStyle, GutterDimensions, HalfPageDown, HalfPageUp,
-    HoveredCursor, LineDown, LineUp, OpenExcerpts, PageDown, PageUp, Point, SelectPhase, Selection,
-    SoftWrap, ToPoint, CURSORS_VISIBLE_FOR, MAX_LINE_LEN,
+    signature_help_popover, CursorShape, DisplayPoint, DocumentHighlightRead,
+    DocumentHighlightWrite, Editor, EditorMode, EditorSettings, EditorSnapshot, EditorStyle,
+    GutterDimensions, HalfPageDown, HalfPageUp, HoveredCursor, LineDown, LineUp, OpenExcerpts,
+    PageDown, PageUp, Point, SelectPhase, Selection, SoftWrap, ToPoint, CURSORS_VISIBLE_FOR,
+    MAX_LINE_LEN,
 };
 use anyhow::Result;
 use collections::{BTreeMap, HashMap};
-use git::diff::DiffHunkStatus;
+use git::{blame::BlameEntry, diff::DiffHunkStatus};
 use gpui::{
     div, fill, outline, overlay, point, px, quad, relative, size, transparent_black, Action,
     AnchorCorner, AnyElement, AvailableSpace, Bounds, ContentMask, Corners, CursorStyle,
@@ -45,10 +46,12 @@ use std::{
     fmt::Write,
     iter,
     ops::Range,
+    ptr,
     sync::Arc,
 };
 use sum_tree::Bias;
 use theme::{ActiveTheme, PlayerColor};
+use time::OffsetDateTime;
 use ui::prelude::*;
 use ui::{h_flex, ButtonLike, ButtonStyle, IconButton, Tooltip};
 use util::ResultExt;
@@ -251,6 +254,8 @@ impl EditorElement {
         register_action(view, cx, Editor::toggle_comments);
         register_action(view, cx, Editor::select_larger_syntax_node);
         register_action(view, cx, Editor::select_smaller_syntax_node);
+        register_action(view, cx, Editor::select_enclosing_function);
+        register_action(view, cx, Editor::select_enclosing_class);
         register_action(view, cx, Editor::move_to_enclosing_bracket);
         register_action(view, cx, Editor::undo_selection);
         register_action(view, cx, Editor::redo_selection);
@@ -258,6 +263,9 @@ impl EditorElement {
         register_action(view, cx, Editor::go_to_prev_diagnostic);
         register_action(view, cx, Editor::go_to_hunk);
         register_action(view, cx, Editor::go_to_prev_hunk);
+        register_action(view, cx, Editor::stage_hunk);
+        register_action(view, cx, Editor::unstage_hunk);
+        register_action(view, cx, Editor::revert_hunk);
         register_action(view, cx, Editor::go_to_definition);
         register_action(view, cx, Editor::go_to_definition_split);
         register_action(view, cx, Editor::go_to_implementation);
@@ -267,6 +275,9 @@ impl EditorElement {
         register_action(view, cx, Editor::open_url);
         register_action(view, cx, Editor::fold);
         register_action(view, cx, Editor::fold_at);
+        register_action(view, cx, Editor::fold_at_level);
+        register_action(view, cx, Editor::fold_comments);
+        register_action(view, cx, Editor::fold_imports);
         register_action(view, cx, Editor::unfold_lines);
         register_action(view, cx, Editor::unfold_at);
         register_action(view, cx, Editor::fold_selected_ranges);
@@ -276,7 +287,10 @@ impl EditorElement {
         register_action(view, cx, Editor::open_excerpts_in_split);
         register_action(view, cx, Editor::toggle_soft_wrap);
         register_action(view, cx, Editor::toggle_inlay_hints);
+        register_action(view, cx, Editor::toggle_git_blame);
+        register_action(view, cx, Editor::toggle_git_blame_inline);
         register_action(view, cx, hover_popover::hover);
+        register_action(view, cx, signature_help_popover::show_signature_help);
         register_action(view, cx, Editor::reveal_in_finder);
         register_action(view, cx, Editor::copy_path);
         register_action(view, cx, Editor::copy_relative_path);
@@ -727,8 +741,17 @@ impl EditorElement {
             Some(GitGutterSetting::TrackedFiles)
         );
 
+        let git_blame_entries_width = layout
+            .gutter_dimensions
+            .git_blame_entries_width
+            .unwrap_or_default();
+
         if show_git_gutter {
-            Self::paint_diff_hunks(bounds, layout, cx);
+            let diff_hunks_bounds = Bounds {
+                origin: bounds.origin + point(git_blame_entries_width, Pixels::ZERO),
+                size: bounds.size,
+            };
+            Self::paint_diff_hunks(diff_hunks_bounds, layout, cx);
         }
 
         let gutter_settings = EditorSettings::get_global(cx).gutter;
@@ -780,7 +803,7 @@ impl EditorElement {
                 );
                 let indicator_size = button.measure(available_space, cx);
 
-                let mut x = Pixels::ZERO;
+                let mut x = git_blame_entries_width;
                 let mut y = indicator.row as f32 * line_height - scroll_top;
                 // Center indicator.
                 x += (layout.gutter_dimensions.margin + layout.gutter_dimensions.left_padding
@@ -790,6 +813,23 @@ impl EditorElement {
 
                 button.draw(bounds.origin + point(x, y), available_space, cx);
             }
+
+            if let Some(blamed_display_rows) = layout.blamed_display_rows.take() {
+                let available_space = size(
+                    AvailableSpace::Definite(git_blame_entries_width),
+                    AvailableSpace::Definite(line_height),
+                );
+                for (ix, element) in blamed_display_rows.into_iter().enumerate() {
+                    if let Some(mut element) = element {
+                        let origin = bounds.origin
+                            + point(
+                                Pixels::ZERO,
+                                ix as f32 * line_height - (scroll_top % line_height),
+                            );
+                        element.draw(origin, available_space, cx);
+                    }
+                }
+            }
         });
     }
 
@@ -1160,6 +1200,20 @@ impl EditorElement {
                     )
                 }
 
+                if let Some(mut inline_blame) = layout.inline_blame.take() {
+                    let origin = content_origin
+                        + point(
+                            inline_blame.x - layout.position_map.scroll_position.x,
+                            inline_blame.row as f32 * layout.position_map.line_height
+                                - layout.position_map.scroll_position.y,
+                        );
+                    let available_space = size(
+                        AvailableSpace::MinContent,
+                        AvailableSpace::Definite(layout.position_map.line_height),
+                    );
+                    inline_blame.element.draw(origin, available_space, cx);
+                }
+
                 cx.with_z_index(0, |cx| self.paint_redactions(text_bounds, &layout, cx));
 
                 cx.with_z_index(1, |cx| {
@@ -1759,6 +1813,70 @@ impl EditorElement {
         self.column_pixels(digit_count, cx)
     }
 
+    fn layout_blame_entries(
+        &self,
+        rows: Range<u32>,
+        snapshot: &EditorSnapshot,
+        width: Option<Pixels>,
+        line_height: Pixels,
+        editor: &Editor,
+        cx: &WindowContext,
+    ) -> Option<Vec<Option<AnyElement>>> {
+        let width = width?;
+        if !editor.render_git_blame_gutter() {
+            return None;
+        }
+
+        let blame = editor.blame()?.read(cx);
+        let buffer_rows = snapshot
+            .buffer_rows(rows.start)
+            .take((rows.end - rows.start) as usize);
+        let mut previous_entry = None;
+        let mut elements = Vec::new();
+        for (ix, entry) in blame.blame_for_rows(buffer_rows).enumerate() {
+            // Only the first row of each blamed hunk is annotated.
+            let is_new_hunk = match (entry, previous_entry) {
+                (Some(entry), Some(previous_entry)) => !ptr::eq(entry, previous_entry),
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            elements.push(
+                entry
+                    .filter(|_| is_new_hunk)
+                    .map(|entry| render_blame_entry(ix, entry, width, line_height, cx)),
+            );
+            if entry.is_some() {
+                previous_entry = entry;
+            }
+        }
+        Some(elements)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_inline_blame(
+        &self,
+        cursor_position: DisplayPoint,
+        rows: Range<u32>,
+        line_layouts: &[LineWithInvisibles],
+        em_width: Pixels,
+        snapshot: &EditorSnapshot,
+        editor: &Editor,
+        cx: &WindowContext,
+    ) -> Option<InlineBlameLayout> {
+        if !editor.render_git_blame_inline(cx) || !rows.contains(&cursor_position.row()) {
+            return None;
+        }
+
+        let buffer_row = cursor_position.to_point(&snapshot.display_snapshot).row;
+        let entry = editor.blame()?.read(cx).blame_for_row(buffer_row)?;
+        let line_layout = line_layouts.get((cursor_position.row() - rows.start) as usize)?;
+        Some(InlineBlameLayout {
+            row: cursor_position.row(),
+            x: line_layout.line.width + INLINE_BLAME_PADDING_EM_WIDTHS * em_width,
+            element: render_inline_blame_entry(entry, &self.style, cx),
+        })
+    }
+
     //Folds contained in a hunk are ignored apart from shrinking visual size
     //If a fold contains any hunks then that fold line is marked as modified
     fn layout_git_gutters(
@@ -1846,13 +1964,13 @@ impl EditorElement {
     ) {
         let font_size = self.style.text.font_size.to_pixels(cx.rem_size());
         let include_line_numbers =
-            EditorSettings::get_global(cx).gutter.line_numbers && snapshot.mode == EditorMode::Full;
+            snapshot.show_line_numbers(cx) && snapshot.mode == EditorMode::Full;
         let include_fold_statuses =
             EditorSettings::get_global(cx).gutter.folds && snapshot.mode == EditorMode::Full;
         let mut shaped_line_numbers = Vec::with_capacity(rows.len());
         let mut fold_statuses = Vec::with_capacity(rows.len());
         let mut line_number = String::new();
-        let is_relative = EditorSettings::get_global(cx).relative_line_numbers;
+        let is_relative = snapshot.use_relative_line_numbers(cx);
         let relative_to = if is_relative {
             Some(newest_selection_head.row())
         } else {
@@ -2335,12 +2453,24 @@ impl EditorElement {
                 editor.hover_state.render(
                     &snapshot,
                     &style,
-                    visible_rows,
+                    visible_rows.clone(),
                     max_size,
                     editor.workspace.as_ref().map(|(w, _)| w.clone()),
                     cx,
                 )
             };
+            // Signature help stays visible alongside the completions menu, but
+            // gives way to hover popovers since they share the same position.
+            let hover = hover.or_else(|| {
+                editor.signature_help_state.render(
+                    &snapshot,
+                    &style,
+                    visible_rows,
+                    max_size,
+                    editor.workspace.as_ref().map(|(w, _)| w.clone()),
+                    cx,
+                )
+            });
 
             let editor_view = cx.view().clone();
             let fold_indicators = if gutter_settings.folds {
@@ -2360,6 +2490,25 @@ impl EditorElement {
                 Vec::new()
             };
 
+            let blamed_display_rows = self.layout_blame_entries(
+                start_row..end_row,
+                &snapshot,
+                gutter_dimensions.git_blame_entries_width,
+                line_height,
+                editor,
+                cx,
+            );
+
+            let inline_blame = self.layout_inline_blame(
+                head_for_relative,
+                start_row..end_row,
+                &line_layouts,
+                em_width,
+                &snapshot,
+                editor,
+                cx,
+            );
+
             let invisible_symbol_font_size = font_size / 2.;
             let tab_invisible = cx
                 .text_system()
@@ -2428,6 +2577,8 @@ impl EditorElement {
                 context_menu,
                 code_actions_indicator,
                 fold_indicators,
+                blamed_display_rows,
+                inline_blame,
                 tab_invisible,
                 space_invisible,
                 hover_popovers: hover,
@@ -3213,6 +3364,8 @@ pub struct LayoutState {
     code_actions_indicator: Option<CodeActionsIndicator>,
     hover_popovers: Option<(DisplayPoint, Vec<AnyElement>)>,
     fold_indicators: Vec<Option<IconButton>>,
+    blamed_display_rows: Option<Vec<Option<AnyElement>>>,
+    inline_blame: Option<InlineBlameLayout>,
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
 }
@@ -3228,6 +3381,12 @@ struct CodeActionsIndicator {
     button: IconButton,
 }
 
+struct InlineBlameLayout {
+    row: u32,
+    x: Pixels,
+    element: AnyElement,
+}
+
 struct PositionMap {
     size: Size<Pixels>,
     line_height: Pixels,
@@ -3305,6 +3464,71 @@ struct BlockLayout {
     style: BlockStyle,
 }
 
+const INLINE_BLAME_PADDING_EM_WIDTHS: f32 = 6.;
+
+fn blame_entry_timestamp(entry: &BlameEntry, cx: &WindowContext) -> Option<String> {
+    let timestamp = entry.author_offset_date_time().ok()?;
+    Some(time_format::format_localized_timestamp(
+        OffsetDateTime::now_utc(),
+        timestamp,
+        cx.local_timezone(),
+    ))
+}
+
+/// Returns a one-line description of a blame entry: its author, date and summary.
+fn blame_entry_description(entry: &BlameEntry, cx: &WindowContext) -> String {
+    if entry.is_uncommitted() {
+        return "Uncommitted changes".to_string();
+    }
+
+    let mut description = entry.author.clone().unwrap_or_default();
+    if let Some(timestamp) = blame_entry_timestamp(entry, cx) {
+        write!(description, ", {timestamp}").ok();
+    }
+    if let Some(summary) = &entry.summary {
+        write!(description, " - {summary}").ok();
+    }
+    description
+}
+
+fn render_blame_entry(
+    ix: usize,
+    entry: &BlameEntry,
+    width: Pixels,
+    line_height: Pixels,
+    cx: &WindowContext,
+) -> AnyElement {
+    let description = blame_entry_description(entry, cx);
+    let short_sha = entry.sha.chars().take(8).collect::<String>();
+    let author_mail = entry.author_mail.clone().unwrap_or_default();
+    let tooltip_meta = format!("{short_sha} {author_mail}");
+
+    h_flex()
+        .id(("blame", ix))
+        .w(width)
+        .h(line_height)
+        .pl_2()
+        .overflow_hidden()
+        .whitespace_nowrap()
+        .text_color(cx.theme().colors().editor_line_number)
+        .child(description.clone())
+        .tooltip(move |cx| Tooltip::with_meta(description.clone(), None, tooltip_meta.clone(), cx))
+        .into_any()
+}
+
+fn render_inline_blame_entry(
+    entry: &BlameEntry,
+    style: &EditorStyle,
+    cx: &WindowContext,
+) -> AnyElement {
+    div()
+        .font(style.text.font_family.clone())
+        .whitespace_nowrap()
+        .text_color(cx.theme().status().hint)
+        .child(blame_entry_description(entry, cx))
+        .into_any()
+}
+
 fn layout_line(
     row: u32,
     snapshot: &EditorSnapshot,
@@ -0,0 +1,256 @@
+use crate::{persistence::DB, Anchor, AnchorRangeExt, Editor, EditorMode};
+use futures::future;
+use gpui::{Task, ViewContext};
+use language::FoldRange;
+use sha2::{Digest, Sha256};
+use std::{ops::Range, time::Duration};
+use util::ResultExt;
+use workspace::{ItemId, WorkspaceId};
+
+const FOLDING_RANGES_DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[derive(Default)]
+pub struct FoldingRangesState {
+    refresh_task: Option<Task<Option<()>>>,
+}
+
+/// Requests the folding ranges of every buffer in the editor from its language server.
+/// The display map falls back to the folds query or to indentation when none are reported.
+pub fn refresh_folding_ranges(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let buffers = editor.buffer.read(cx).all_buffers();
+    editor.folding_ranges_state.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(FOLDING_RANGES_DEBOUNCE)
+            .await;
+
+        let requests = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let ranges = project.folding_ranges(&buffer, cx);
+                        async move { (buffer, ranges.await) }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let responses = future::join_all(requests).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let multi_buffer = editor.buffer.read(cx);
+                let snapshot = multi_buffer.snapshot(cx);
+
+                let mut fold_ranges = Vec::<FoldRange<Anchor>>::new();
+                for (buffer, ranges) in responses {
+                    let Some(ranges) = ranges.log_err() else {
+                        continue;
+                    };
+                    let buffer_snapshot = buffer.read(cx).snapshot();
+                    for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx)
+                    {
+                        let context = excerpt_range.context;
+                        let ranges_in_excerpt = ranges.iter().filter(|fold_range| {
+                            let range = &fold_range.range;
+                            range.start.cmp(&context.start, &buffer_snapshot).is_ge()
+                                && range.end.cmp(&context.end, &buffer_snapshot).is_le()
+                        });
+                        for fold_range in ranges_in_excerpt {
+                            let start =
+                                snapshot.anchor_in_excerpt(excerpt_id, fold_range.range.start);
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, fold_range.range.end);
+                            fold_ranges.push(FoldRange {
+                                range: start..end,
+                                kind: fold_range.kind,
+                            });
+                        }
+                    }
+                }
+                fold_ranges.sort_by(|a, b| a.range.start.cmp(&b.range.start, &snapshot));
+
+                editor.display_map.update(cx, |display_map, _| {
+                    display_map.set_lsp_fold_ranges(if fold_ranges.is_empty() {
+                        None
+                    } else {
+                        Some(fold_ranges.into())
+                    })
+                });
+                cx.notify();
+            })
+            .ok()
+    }));
+}
+
+impl Editor {
+    /// Stores the folds of a singleton buffer, so that they can be restored when the
+    /// workspace is reopened.
+    pub(crate) fn save_folds(&self, cx: &mut ViewContext<Self>) {
+        let Some((_, workspace_id)) = self.workspace else {
+            return;
+        };
+        if !self.buffer.read(cx).is_singleton() {
+            return;
+        }
+
+        let item_id = cx.view().entity_id().as_u64() as ItemId;
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let folds = serialize_folds(
+            display_map
+                .folds_in_range(0..display_map.buffer_snapshot.len())
+                .map(|fold| fold.range.to_offset(&display_map.buffer_snapshot)),
+        );
+        let buffer_snapshot = display_map.buffer_snapshot;
+        cx.background_executor()
+            .spawn(async move {
+                let digest = text_digest(buffer_snapshot.text_for_range(0..buffer_snapshot.len()));
+                DB.save_folds(item_id, workspace_id, folds, digest)
+                    .await
+                    .log_err()
+            })
+            .detach();
+    }
+
+    pub fn read_folds_from_db(
+        &mut self,
+        item_id: u64,
+        workspace_id: WorkspaceId,
+        cx: &mut ViewContext<Editor>,
+    ) {
+        if let Ok(Some((Some(folds), Some(digest)))) = DB.get_folds(item_id, workspace_id) {
+            // The offsets of the folds only make sense in the text they were saved for.
+            let snapshot = self.buffer.read(cx).snapshot(cx);
+            if text_digest(snapshot.text_for_range(0..snapshot.len())) != digest {
+                return;
+            }
+            self.fold_ranges(deserialize_folds(&folds), false, cx);
+        }
+    }
+}
+
+/// Identifies the text that folds were saved for.
+fn text_digest<'a>(chunks: impl IntoIterator<Item = &'a str>) -> String {
+    let mut hasher = Sha256::new();
+    for chunk in chunks {
+        hasher.update(chunk.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Encodes fold ranges as a list of comma-separated `start..end` offsets.
+fn serialize_folds(ranges: impl Iterator<Item = Range<usize>>) -> String {
+    ranges
+        .map(|range| format!("{}..{}", range.start, range.end))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn deserialize_folds(folds: &str) -> Vec<Range<usize>> {
+    folds
+        .split(',')
+        .filter_map(|range| {
+            let (start, end) = range.split_once("..")?;
+            let start = start.parse().ok()?;
+            let end = end.parse().ok()?;
+            (start < end).then_some(start..end)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor_tests::init_test;
+    use gpui::{Model, TestAppContext, View, VisualTestContext};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use workspace::{Workspace, WORKSPACE_DB};
+
+    #[test]
+    fn test_serialize_folds() {
+        let folds = serialize_folds([3..10, 15..42].into_iter());
+        assert_eq!(folds, "3..10,15..42");
+        assert_eq!(deserialize_folds(&folds), [3..10, 15..42]);
+        assert_eq!(deserialize_folds(""), []);
+        assert_eq!(deserialize_folds("5..2,x..4,7..9"), [7..9]);
+    }
+
+    #[gpui::test]
+    async fn test_restore_folds(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "a.txt": "one\ntwo\nthree\n" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace_id = WORKSPACE_DB.next_id().await.unwrap();
+
+        DB.save_path(1, workspace_id, "/root/a.txt".into())
+            .await
+            .unwrap();
+        DB.save_folds(
+            1,
+            workspace_id,
+            "4..7".into(),
+            text_digest(["one\ntwo\nthree\n"]),
+        )
+        .await
+        .unwrap();
+        let editor = deserialize_editor(&workspace, &project, workspace_id, 1, cx).await;
+        editor.update(cx, |editor, cx| {
+            let display_map = editor.display_map.update(cx, |map, cx| map.snapshot(cx));
+            assert_eq!(display_map.fold_count(), 1);
+        });
+
+        // Folds saved for other contents of the file are discarded.
+        fs.insert_file("/root/a.txt", "changed\n".into()).await;
+        cx.run_until_parked();
+        DB.save_path(2, workspace_id, "/root/a.txt".into())
+            .await
+            .unwrap();
+        DB.save_folds(
+            2,
+            workspace_id,
+            "4..7".into(),
+            text_digest(["one\ntwo\nthree\n"]),
+        )
+        .await
+        .unwrap();
+        let editor = deserialize_editor(&workspace, &project, workspace_id, 2, cx).await;
+        editor.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "changed\n");
+            let display_map = editor.display_map.update(cx, |map, cx| map.snapshot(cx));
+            assert_eq!(display_map.fold_count(), 0);
+        });
+    }
+
+    async fn deserialize_editor(
+        workspace: &View<Workspace>,
+        project: &Model<Project>,
+        workspace_id: WorkspaceId,
+        item_id: ItemId,
+        cx: &mut VisualTestContext,
+    ) -> View<Editor> {
+        let pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+        let editor = pane
+            .update(cx, |_, cx| {
+                <Editor as workspace::item::Item>::deserialize(
+                    project.clone(),
+                    workspace.downgrade(),
+                    workspace_id,
+                    item_id,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        editor
+    }
+}
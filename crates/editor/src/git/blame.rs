@@ -0,0 +1,156 @@
+use git::blame::{Blame, BlameEntry};
+use gpui::{Model, ModelContext, Subscription, Task};
+use language::{Bias, Buffer, BufferSnapshot, Point, ToPoint};
+use project::Project;
+use std::{ops::Range, time::Duration};
+use text::Anchor;
+
+const REGENERATE_ON_EDIT_DEBOUNCE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A blame entry, anchored in the blamed buffer so that it follows edits.
+struct GitBlameEntry {
+    range: Range<Anchor>,
+    /// The rows of `range` as of the last edit of the buffer.
+    rows: Range<u32>,
+    entry: BlameEntry,
+}
+
+/// The git blame of a buffer, which is regenerated whenever the buffer or its
+/// repository changes.
+pub struct GitBlame {
+    project: Model<Project>,
+    buffer: Model<Buffer>,
+    entries: Vec<GitBlameEntry>,
+    generated: bool,
+    generate_task: Task<()>,
+    regenerate_on_edit_task: Task<()>,
+    _subscriptions: [Subscription; 2],
+}
+
+impl GitBlame {
+    pub fn new(
+        buffer: Model<Buffer>,
+        project: Model<Project>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let buffer_subscription = cx.subscribe(&buffer, |this, _, event, cx| match event {
+            language::Event::Edited => {
+                this.refresh_rows(cx);
+                this.regenerate_on_edit(cx);
+            }
+            language::Event::Saved
+            | language::Event::Reloaded
+            | language::Event::DiffBaseChanged
+            | language::Event::FileHandleChanged => this.generate(cx),
+            _ => {}
+        });
+        let project_subscription = cx.subscribe(&project, |this, _, event, cx| {
+            if let project::Event::WorktreeUpdatedGitRepositories = event {
+                this.generate(cx);
+            }
+        });
+
+        let mut this = Self {
+            project,
+            buffer,
+            entries: Vec::new(),
+            generated: false,
+            generate_task: Task::ready(()),
+            regenerate_on_edit_task: Task::ready(()),
+            _subscriptions: [buffer_subscription, project_subscription],
+        };
+        this.generate(cx);
+        this
+    }
+
+    /// Returns whether blame data has been computed for the buffer.
+    pub fn has_generated_entries(&self) -> bool {
+        self.generated
+    }
+
+    /// Returns the blame entry of each of the given buffer rows, if any.
+    pub fn blame_for_rows<'a>(
+        &'a self,
+        rows: impl 'a + IntoIterator<Item = Option<u32>>,
+    ) -> impl 'a + Iterator<Item = Option<&'a BlameEntry>> {
+        rows.into_iter()
+            .map(move |row| row.and_then(|row| self.blame_for_row(row)))
+    }
+
+    pub fn blame_for_row(&self, row: u32) -> Option<&BlameEntry> {
+        let ix = self.entries.partition_point(|entry| entry.rows.end <= row);
+        let entry = self.entries.get(ix)?;
+        entry.rows.contains(&row).then_some(&entry.entry)
+    }
+
+    fn refresh_rows(&mut self, cx: &mut ModelContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot();
+        for entry in &mut self.entries {
+            let start = entry.range.start.to_point(&snapshot).row;
+            let end = entry.range.end.to_point(&snapshot);
+            // The end anchor is at the start of the row after the entry, unless the
+            // entry ends at the end of the buffer.
+            let end = if end.column == 0 {
+                end.row
+            } else {
+                end.row + 1
+            };
+            entry.rows = start..end.max(start);
+        }
+        cx.notify();
+    }
+
+    fn regenerate_on_edit(&mut self, cx: &mut ModelContext<Self>) {
+        self.regenerate_on_edit_task = cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(REGENERATE_ON_EDIT_DEBOUNCE_INTERVAL)
+                .await;
+            this.update(&mut cx, |this, cx| this.generate(cx)).ok();
+        });
+    }
+
+    fn generate(&mut self, cx: &mut ModelContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot();
+        let blame = self.project.read(cx).blame_buffer(&self.buffer, cx);
+
+        self.generate_task = cx.spawn(|this, mut cx| async move {
+            let blame = blame.await;
+            this.update(&mut cx, |this, cx| {
+                match blame {
+                    Ok(blame) => this.set_blame(blame, &snapshot),
+                    Err(error) => {
+                        log::debug!("failed to blame buffer: {error:?}");
+                        this.entries.clear();
+                    }
+                }
+                this.generated = true;
+                this.refresh_rows(cx);
+            })
+            .ok();
+        });
+    }
+
+    fn set_blame(&mut self, blame: Blame, snapshot: &BufferSnapshot) {
+        let max_row = snapshot.max_point().row;
+        self.entries = blame
+            .entries
+            .into_iter()
+            .filter(|entry| entry.range.start <= max_row)
+            .map(|entry| {
+                let start = snapshot.anchor_before(Point::new(entry.range.start, 0));
+                let end = if entry.range.end > max_row {
+                    snapshot.anchor_after(snapshot.max_point())
+                } else {
+                    snapshot.anchor_before(
+                        snapshot.clip_point(Point::new(entry.range.end, 0), Bias::Left),
+                    )
+                };
+                GitBlameEntry {
+                    range: start..end,
+                    rows: entry.range.clone(),
+                    entry,
+                }
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{persistence::DB, Editor};
+use gpui::{AppContext, Model, Subscription, Task, ViewContext};
+use language::Buffer;
+use settings::Settings;
+use std::{cell::Cell, rc::Rc, time::Duration};
+use util::ResultExt;
+use workspace::{ItemId, WorkspaceId, WorkspaceSettings};
+
+const SAVE_UNSAVED_CONTENTS_DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Default)]
+pub struct HotExitState {
+    save_task: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl Editor {
+    /// Keeps the unsaved contents of the editor's buffer in the database, so that they can
+    /// be restored when the workspace is reopened after quitting or crashing.
+    pub(crate) fn track_unsaved_contents(
+        &mut self,
+        buffer: Model<Buffer>,
+        workspace_id: WorkspaceId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let item_id = cx.view().item_id().as_u64() as ItemId;
+        let quitting = Rc::new(Cell::new(false));
+
+        self.hot_exit_state._subscriptions = vec![
+            cx.subscribe(&buffer, move |editor, buffer, event, cx| match event {
+                language::Event::Edited
+                | language::Event::DirtyChanged
+                | language::Event::Saved
+                | language::Event::Reloaded => {
+                    editor.hot_exit_state.save_task = Some(cx.spawn(|_, mut cx| async move {
+                        cx.background_executor()
+                            .timer(SAVE_UNSAVED_CONTENTS_DEBOUNCE)
+                            .await;
+                        if let Some(task) = cx
+                            .update(|cx| save_unsaved_contents(&buffer, item_id, workspace_id, cx))
+                            .log_err()
+                        {
+                            task.await;
+                        }
+                    }));
+                }
+                _ => {}
+            }),
+            cx.on_app_quit({
+                let buffer = buffer.clone();
+                let quitting = quitting.clone();
+                move |cx| {
+                    quitting.set(true);
+                    save_unsaved_contents(&buffer, item_id, workspace_id, cx)
+                }
+            }),
+            // Editors closed before quitting have had their changes saved or discarded.
+            cx.on_release(move |_, _, cx| {
+                if !quitting.get() {
+                    cx.background_executor()
+                        .spawn(async move {
+                            DB.delete_unsaved_contents(item_id, workspace_id)
+                                .await
+                                .log_err();
+                        })
+                        .detach();
+                }
+            }),
+        ];
+
+        save_unsaved_contents(&buffer, item_id, workspace_id, cx).detach();
+    }
+}
+
+/// Stores the contents of the buffer if it is dirty and hot exit is enabled, or clears
+/// the stored contents otherwise.
+fn save_unsaved_contents(
+    buffer: &Model<Buffer>,
+    item_id: ItemId,
+    workspace_id: WorkspaceId,
+    cx: &AppContext,
+) -> Task<()> {
+    let buffer = buffer.read(cx);
+    let unsaved_contents =
+        (WorkspaceSettings::get_global(cx).hot_exit && buffer.is_dirty()).then(|| {
+            (
+                buffer.text(),
+                buffer.text_for_version(buffer.saved_version()).to_string(),
+                buffer
+                    .language()
+                    .map(|language| language.name().to_string()),
+            )
+        });
+
+    cx.background_executor().spawn(async move {
+        if let Some((contents, base_text, language)) = unsaved_contents {
+            DB.save_unsaved_contents(item_id, workspace_id, contents, base_text, language)
+                .await
+                .log_err();
+        } else {
+            DB.delete_unsaved_contents(item_id, workspace_id)
+                .await
+                .log_err();
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, items::UNSAVED_CONTENTS_CONFLICT_TOAST_ID, Undo};
+    use gpui::{TestAppContext, View, VisualTestContext};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use workspace::{Workspace, WORKSPACE_DB};
+
+    #[gpui::test]
+    async fn test_restore_unsaved_contents(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "a.txt": "saved" })).await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace_id = WORKSPACE_DB.next_id().await.unwrap();
+
+        // The unsaved changes of a file are applied over the text on disk.
+        DB.save_path(1, workspace_id, "/root/a.txt".into())
+            .await
+            .unwrap();
+        DB.save_unsaved_contents(1, workspace_id, "unsaved".into(), "saved".into(), None)
+            .await
+            .unwrap();
+        let editor = deserialize_editor(&workspace, &project, workspace_id, 1, cx).await;
+        editor.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "unsaved");
+            assert!(editor.buffer().read(cx).read(cx).is_dirty());
+            editor.undo(&Undo, cx);
+            assert_eq!(editor.text(cx), "saved");
+            assert!(!editor.buffer().read(cx).read(cx).is_dirty());
+        });
+        assert_eq!(DB.get_unsaved_contents(1, workspace_id).unwrap(), None);
+
+        // Untitled buffers are recreated from their unsaved contents.
+        DB.save_unsaved_contents(2, workspace_id, "untitled".into(), "".into(), None)
+            .await
+            .unwrap();
+        let editor = deserialize_editor(&workspace, &project, workspace_id, 2, cx).await;
+        editor.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "untitled");
+            assert!(editor.buffer().read(cx).read(cx).is_dirty());
+        });
+        assert_eq!(DB.get_unsaved_contents(2, workspace_id).unwrap(), None);
+    }
+
+    #[gpui::test]
+    async fn test_restore_unsaved_contents_changed_on_disk(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "a.txt": "changed" })).await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace_id = WORKSPACE_DB.next_id().await.unwrap();
+
+        DB.save_path(1, workspace_id, "/root/a.txt".into())
+            .await
+            .unwrap();
+        DB.save_unsaved_contents(1, workspace_id, "unsaved".into(), "saved".into(), None)
+            .await
+            .unwrap();
+        let editor = deserialize_editor(&workspace, &project, workspace_id, 1, cx).await;
+        workspace.update(cx, |workspace, _| {
+            assert_eq!(
+                workspace.notification_ids(),
+                [UNSAVED_CONTENTS_CONFLICT_TOAST_ID]
+            );
+        });
+
+        // Undoing brings back the text the changes were based on, then the text on disk.
+        editor.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "unsaved");
+            editor.undo(&Undo, cx);
+            assert_eq!(editor.text(cx), "saved");
+            editor.undo(&Undo, cx);
+            assert_eq!(editor.text(cx), "changed");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_quitting_with_unsaved_contents(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "a.txt": "saved" })).await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "a.txt"), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        editor.update(cx, |editor, cx| editor.set_text("unsaved", cx));
+
+        // Quitting doesn't ask to save changes that are restored on the next launch.
+        let close = workspace.update(cx, |workspace, cx| workspace.prepare_to_close(true, cx));
+        cx.run_until_parked();
+        assert!(!cx.has_pending_prompt());
+        assert!(close.await.unwrap());
+        editor.update(cx, |editor, cx| assert_eq!(editor.text(cx), "unsaved"));
+
+        // Closing the window without quitting still asks.
+        let close = workspace.update(cx, |workspace, cx| workspace.prepare_to_close(false, cx));
+        cx.run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(2);
+        assert!(!close.await.unwrap());
+    }
+
+    async fn deserialize_editor(
+        workspace: &View<Workspace>,
+        project: &Model<Project>,
+        workspace_id: WorkspaceId,
+        item_id: ItemId,
+        cx: &mut VisualTestContext,
+    ) -> View<Editor> {
+        let pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+        let editor = pane
+            .update(cx, |_, cx| {
+                <Editor as workspace::item::Item>::deserialize(
+                    project.clone(),
+                    workspace.downgrade(),
+                    workspace_id,
+                    item_id,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        editor
+    }
+}
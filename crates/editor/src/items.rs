@@ -41,10 +41,12 @@ use workspace::{
 use workspace::{
     item::{FollowableItem, Item, ItemEvent, ItemHandle, ProjectItem},
     searchable::{Direction, SearchEvent, SearchableItem, SearchableItemHandle},
-    ItemId, ItemNavHistory, Pane, ToolbarItemLocation, ViewId, Workspace, WorkspaceId,
+    ItemId, ItemNavHistory, NavigationMode, Pane, Toast, ToolbarItemLocation, ViewId, Workspace,
+    WorkspaceId, WorkspaceSettings,
 };
 
 pub const MAX_TAB_TITLE_LEN: usize = 24;
+pub(crate) const UNSAVED_CONTENTS_CONFLICT_TOAST_ID: usize = 0x2f1c4e7a;
 
 impl FollowableItem for Editor {
     fn remote_id(&self) -> Option<ViewId> {
@@ -310,6 +312,7 @@ impl FollowableItem for Editor {
                 }
                 _ => false,
             },
+            _ => false,
         }
     }
 
@@ -319,7 +322,9 @@ impl FollowableItem for Editor {
         message: update_view::Variant,
         cx: &mut ViewContext<Self>,
     ) -> Task<Result<()>> {
-        let update_view::Variant::Editor(message) = message;
+        let update_view::Variant::Editor(message) = message else {
+            return Task::ready(Err(anyhow!("invalid editor update")));
+        };
         let project = project.clone();
         cx.spawn(|this, mut cx| async move {
             update_editor_from_message(this, project, message, &mut cx).await
@@ -561,6 +566,18 @@ impl Item for Editor {
             if newest_selection.head() == offset {
                 false
             } else {
+                // Record the position being left when going back or forward, so that
+                // navigating in the opposite direction returns to it.
+                if self.nav_history.as_ref().is_some_and(|nav_history| {
+                    matches!(
+                        nav_history.mode(),
+                        NavigationMode::GoingBack | NavigationMode::GoingForward
+                    )
+                }) {
+                    let cursor_anchor = self.selections.newest_anchor().head();
+                    self.push_to_nav_history(cursor_anchor, None, cx);
+                }
+
                 let nav_history = self.nav_history.take();
                 self.set_scroll_anchor(scroll_anchor, cx);
                 self.change_selections(Some(Autoscroll::fit()), cx, |s| {
@@ -693,6 +710,16 @@ impl Item for Editor {
         self.buffer().read(cx).read(cx).has_conflict()
     }
 
+    fn preserves_unsaved_changes(&self, cx: &AppContext) -> bool {
+        WorkspaceSettings::get_global(cx).hot_exit
+            && self.workspace.is_some()
+            && self.buffer().read(cx).is_singleton()
+            && self
+                .project
+                .as_ref()
+                .map_or(false, |project| project.read(cx).is_local())
+    }
+
     fn can_save(&self, cx: &AppContext) -> bool {
         let buffer = &self.buffer().read(cx);
         if let Some(buffer) = buffer.as_singleton() {
@@ -866,6 +893,10 @@ impl Item for Editor {
 
         if let Some(buffer) = self.buffer().read(cx).as_singleton() {
             serialize(buffer.clone(), workspace_id, item_id, cx);
+            self.save_folds(cx);
+            if workspace.project().read(cx).is_local() {
+                self.track_unsaved_contents(buffer.clone(), workspace_id, cx);
+            }
 
             cx.subscribe(&buffer, |this, buffer, event, cx| {
                 if let Some((_, workspace_id)) = this.workspace.as_ref() {
@@ -923,16 +954,24 @@ impl Item for Editor {
 
     fn deserialize(
         project: Model<Project>,
-        _workspace: WeakView<Workspace>,
+        workspace: WeakView<Workspace>,
         workspace_id: workspace::WorkspaceId,
         item_id: ItemId,
         cx: &mut ViewContext<Pane>,
     ) -> Task<Result<View<Self>>> {
+        let unsaved_contents = DB
+            .get_unsaved_contents(item_id, workspace_id)
+            .log_err()
+            .flatten();
         let project_item: Result<_> = project.update(cx, |project, cx| {
             // Look up the path with this key associated, create a self with that path
-            let path = DB
-                .get_path(item_id, workspace_id)?
-                .context("No path stored for this editor")?;
+            let Some(path) = DB.get_path(item_id, workspace_id)? else {
+                // Untitled buffers are restored from their unsaved contents
+                if unsaved_contents.is_none() {
+                    return Err(anyhow!("No path stored for this editor"));
+                }
+                return Ok(None);
+            };
 
             let (worktree, path) = project
                 .find_local_worktree(&path, cx)
@@ -942,21 +981,47 @@ impl Item for Editor {
                 path: path.into(),
             };
 
-            Ok(project.open_path(project_path, cx))
+            Ok(Some(project.open_path(project_path, cx)))
         });
 
         project_item
             .map(|project_item| {
                 cx.spawn(|pane, mut cx| async move {
-                    let (_, project_item) = project_item.await?;
-                    let buffer = project_item
-                        .downcast::<Buffer>()
-                        .map_err(|_| anyhow!("Project item at stored path was not a buffer"))?;
+                    let buffer = if let Some(project_item) = project_item {
+                        let (_, project_item) = project_item.await?;
+                        project_item
+                            .downcast::<Buffer>()
+                            .map_err(|_| anyhow!("Project item at stored path was not a buffer"))?
+                    } else {
+                        let languages =
+                            project.read_with(&cx, |project, _| project.languages().clone())?;
+                        let language = match unsaved_contents
+                            .as_ref()
+                            .and_then(|(_, _, language)| language.as_deref())
+                        {
+                            Some(language) => languages.language_for_name(language).await.log_err(),
+                            None => None,
+                        };
+                        project.update(&mut cx, |project, cx| {
+                            project.create_buffer("", language, cx)
+                        })??
+                    };
+
+                    if let Some((contents, base_text, _)) = unsaved_contents {
+                        restore_unsaved_contents(&buffer, contents, base_text, &workspace, &mut cx)
+                            .await?;
+                        // The restored editor stores its unsaved contents under its own item id.
+                        DB.delete_unsaved_contents(item_id, workspace_id)
+                            .await
+                            .log_err();
+                    }
+
                     Ok(pane.update(&mut cx, |_, cx| {
                         cx.new_view(|cx| {
                             let mut editor = Editor::for_buffer(buffer, Some(project), cx);
 
                             editor.read_scroll_position_from_db(item_id, workspace_id, cx);
+                            editor.read_folds_from_db(item_id, workspace_id, cx);
                             editor
                         })
                     })?)
@@ -966,6 +1031,63 @@ impl Item for Editor {
     }
 }
 
+/// Applies the unsaved contents stored for a buffer as an edit, so that the buffer is dirty
+/// and undoing brings back the text it was last saved with. Shows a notification when the
+/// file has changed on disk since then.
+async fn restore_unsaved_contents(
+    buffer: &Model<Buffer>,
+    contents: String,
+    base_text: String,
+    workspace: &WeakView<Workspace>,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    // The buffer may already have been restored by another editor.
+    let Some(changed_on_disk) = buffer.update(cx, |buffer, _| {
+        (!buffer.is_dirty()).then(|| buffer.text() != base_text)
+    })?
+    else {
+        return Ok(());
+    };
+
+    // The text the changes were based on is restored as its own undo step, so that undoing
+    // the changes brings it back rather than the text on disk.
+    if changed_on_disk {
+        let diff = buffer
+            .update(cx, |buffer, cx| buffer.diff(base_text, cx))?
+            .await;
+        buffer.update(cx, |buffer, cx| {
+            buffer.apply_diff(diff, cx);
+            buffer.finalize_last_transaction();
+        })?;
+    }
+    let diff = buffer
+        .update(cx, |buffer, cx| buffer.diff(contents, cx))?
+        .await;
+    buffer.update(cx, |buffer, cx| buffer.apply_diff(diff, cx))?;
+
+    if changed_on_disk {
+        let path = buffer.read_with(cx, |buffer, cx| {
+            buffer
+                .file()
+                .map(|file| file.full_path(cx).to_string_lossy().to_string())
+        })?;
+        if let Some(path) = path {
+            let message = format!(
+                "{path} has changed on disk since its unsaved changes were stored. \
+                Saving it will overwrite the changes on disk."
+            );
+            workspace
+                .update(cx, |workspace, cx| {
+                    workspace
+                        .show_toast(Toast::new(UNSAVED_CONTENTS_CONFLICT_TOAST_ID, message), cx)
+                })
+                .log_err();
+        }
+    }
+
+    Ok(())
+}
+
 impl ProjectItem for Editor {
     type Item = Buffer;
 
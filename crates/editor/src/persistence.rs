@@ -14,6 +14,16 @@ define_connection!(
     //   scroll_top_row: usize,
     //   scroll_vertical_offset: f32,
     //   scroll_horizontal_offset: f32,
+    //   folds: Option<String>,
+    //   folds_digest: Option<String>,
+    // )
+    //
+    // editor_contents(
+    //   item_id: usize,
+    //   workspace_id: usize,
+    //   contents: String,
+    //   base_text: String,
+    //   language: Option<String>,
     // )
     pub static ref DB: EditorDb<WorkspaceDb> =
         &[sql! (
@@ -31,6 +41,25 @@ define_connection!(
             ALTER TABLE editors ADD COLUMN scroll_top_row INTEGER NOT NULL DEFAULT 0;
             ALTER TABLE editors ADD COLUMN scroll_horizontal_offset REAL NOT NULL DEFAULT 0;
             ALTER TABLE editors ADD COLUMN scroll_vertical_offset REAL NOT NULL DEFAULT 0;
+        ),
+        sql! (
+            ALTER TABLE editors ADD COLUMN folds TEXT;
+        ),
+        sql! (
+            CREATE TABLE editor_contents(
+                item_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                contents TEXT NOT NULL,
+                base_text TEXT NOT NULL,
+                language TEXT,
+                PRIMARY KEY(item_id, workspace_id),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        ),
+        sql! (
+            ALTER TABLE editors ADD COLUMN folds_digest TEXT;
         )];
 );
 
@@ -80,4 +109,54 @@ impl EditorDb {
             WHERE item_id = ?1 AND workspace_id = ?2
         }
     }
+
+    query! {
+        pub fn get_folds(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<(Option<String>, Option<String>)>> {
+            SELECT folds, folds_digest FROM editors
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_folds(item_id: ItemId, workspace_id: WorkspaceId, folds: String, folds_digest: String) -> Result<()> {
+            UPDATE OR IGNORE editors
+            SET folds = ?3, folds_digest = ?4
+            WHERE item_id = ?1 AND workspace_id = ?2
+        }
+    }
+
+    // Returns the unsaved contents of the buffer, the text it was last saved with, and its language
+    query! {
+        pub fn get_unsaved_contents(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<(String, String, Option<String>)>> {
+            SELECT contents, base_text, language
+            FROM editor_contents
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_unsaved_contents(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            contents: String,
+            base_text: String,
+            language: Option<String>
+        ) -> Result<()> {
+            INSERT INTO editor_contents
+                (item_id, workspace_id, contents, base_text, language)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT DO UPDATE SET
+                contents = ?3,
+                base_text = ?4,
+                language = ?5
+        }
+    }
+
+    query! {
+        pub async fn delete_unsaved_contents(item_id: ItemId, workspace_id: WorkspaceId) -> Result<()> {
+            DELETE FROM editor_contents
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
 }
@@ -1,11 +1,16 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use anyhow::Context as _;
+use collections::HashMap;
+use futures::future;
 use gpui::{Context, View, ViewContext, VisualContext, WindowContext};
 use language::Language;
 use multi_buffer::MultiBuffer;
-use project::lsp_ext_command::ExpandMacro;
+use project::{lsp_ext_command::ExpandMacro, CodeLens};
+use serde::Deserialize;
+use task::{SpawnInTerminal, TaskId};
 use text::ToPointUtf16;
+use util::ResultExt;
 
 use crate::{element::register_action, Editor, ExpandMacroRecursively};
 
@@ -114,6 +119,149 @@ pub fn expand_macro_recursively(
     .detach_and_log_err(cx);
 }
 
+/// Runs the client-side commands that rust-analyzer attaches to its code lenses,
+/// returning `false` for commands that should be executed by the server instead.
+pub fn run_client_command(
+    editor: &mut Editor,
+    lens: &CodeLens,
+    command: &lsp::Command,
+    cx: &mut ViewContext<'_, Editor>,
+) -> bool {
+    match command.command.as_str() {
+        "rust-analyzer.runSingle" => {
+            run_single(editor, command, cx).log_err();
+            true
+        }
+        "rust-analyzer.showReferences" => {
+            show_references(editor, lens, command, cx).log_err();
+            true
+        }
+        _ => false,
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Runnable {
+    label: String,
+    kind: String,
+    args: CargoRunnableArgs,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CargoRunnableArgs {
+    workspace_root: Option<PathBuf>,
+    override_cargo: Option<String>,
+    #[serde(default)]
+    cargo_args: Vec<String>,
+    #[serde(default)]
+    cargo_extra_args: Vec<String>,
+    #[serde(default)]
+    executable_args: Vec<String>,
+}
+
+impl Runnable {
+    fn into_spawn_in_terminal(self) -> SpawnInTerminal {
+        let mut args = self.args.cargo_args;
+        args.extend(self.args.cargo_extra_args);
+        if !self.args.executable_args.is_empty() {
+            args.push("--".to_string());
+            args.extend(self.args.executable_args);
+        }
+        SpawnInTerminal {
+            id: TaskId(format!("rust-analyzer: {}", self.label)),
+            label: self.label,
+            command: self
+                .args
+                .override_cargo
+                .unwrap_or_else(|| "cargo".to_string()),
+            args,
+            cwd: self.args.workspace_root,
+            env: HashMap::default(),
+            use_new_terminal: false,
+            allow_concurrent_runs: false,
+            separate_shell: false,
+            task_run_id: None,
+        }
+    }
+}
+
+fn run_single(
+    editor: &mut Editor,
+    command: &lsp::Command,
+    cx: &mut ViewContext<'_, Editor>,
+) -> anyhow::Result<()> {
+    let Some(workspace) = editor.workspace() else {
+        return Ok(());
+    };
+    anyhow::ensure!(
+        workspace.read(cx).project().read(cx).is_local(),
+        "cannot run tasks in a remote project"
+    );
+    let runnable = command
+        .arguments
+        .as_ref()
+        .and_then(|arguments| arguments.first())
+        .context("missing runnable argument")?;
+    let runnable = serde_json::from_value::<Runnable>(runnable.clone())?;
+    anyhow::ensure!(
+        runnable.kind == "cargo",
+        "unsupported runnable kind {}",
+        runnable.kind
+    );
+    let spawn_in_terminal = runnable.into_spawn_in_terminal();
+    workspace.update(cx, |_, cx| {
+        cx.emit(workspace::Event::SpawnTask(spawn_in_terminal));
+    });
+    Ok(())
+}
+
+fn show_references(
+    editor: &mut Editor,
+    lens: &CodeLens,
+    command: &lsp::Command,
+    cx: &mut ViewContext<'_, Editor>,
+) -> anyhow::Result<()> {
+    let Some(workspace) = editor.workspace() else {
+        return Ok(());
+    };
+    // The arguments are the document URI, the position of the lens and the locations to show.
+    let locations = command
+        .arguments
+        .as_ref()
+        .and_then(|arguments| arguments.get(2))
+        .context("missing locations argument")?;
+    let locations = serde_json::from_value::<Vec<lsp::Location>>(locations.clone())?;
+    if locations.is_empty() {
+        return Ok(());
+    }
+
+    let location_tasks = locations
+        .into_iter()
+        .map(|location| editor.compute_target_location(location, lens.server_id, cx))
+        .collect::<Vec<_>>();
+    let title = command.title.clone();
+    let replica_id = editor.replica_id(cx);
+    cx.spawn(|_, mut cx| async move {
+        let locations = future::join_all(location_tasks)
+            .await
+            .into_iter()
+            .filter_map(|location| location.transpose())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if locations.is_empty() {
+            return Ok(());
+        }
+        workspace.update(&mut cx, |workspace, cx| {
+            Editor::open_locations_in_multibuffer(
+                workspace, locations, replica_id, title, false, cx,
+            );
+        })
+    })
+    .detach_and_log_err(cx);
+    Ok(())
+}
+
 fn is_rust_language(language: &Language) -> bool {
     language.name().as_ref() == "Rust"
 }
@@ -0,0 +1,217 @@
+use crate::{
+    editor_settings::SemanticTokenModifierStyle, Anchor, Editor, EditorMode, EditorSettings,
+};
+use collections::HashMap;
+use futures::future;
+use gpui::{px, FontStyle, FontWeight, HighlightStyle, Task, UnderlineStyle, ViewContext};
+use project::SemanticToken;
+use settings::Settings;
+use std::{ops::Range, time::Duration};
+use theme::{ActiveTheme, SyntaxTheme};
+
+const SEMANTIC_TOKENS_DEBOUNCE: Duration = Duration::from_millis(50);
+
+#[derive(Default)]
+pub struct SemanticTokensState {
+    refresh_task: Option<Task<Option<()>>>,
+}
+
+/// Requests the semantic tokens of every buffer in the editor and layers them over the
+/// syntax highlighting. Buffers whose version hasn't changed are served from the project's cache.
+pub fn refresh_semantic_tokens(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full || !EditorSettings::get_global(cx).semantic_tokens.enabled {
+        editor.semantic_tokens_state.refresh_task = None;
+        editor.display_map.update(cx, |display_map, _| {
+            display_map.set_semantic_highlights(None)
+        });
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let buffers = editor.buffer.read(cx).all_buffers();
+    editor.semantic_tokens_state.refresh_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(SEMANTIC_TOKENS_DEBOUNCE)
+            .await;
+
+        let requests = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let tokens = project.semantic_tokens(&buffer, cx);
+                        async move { (buffer, tokens.await) }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let responses = future::join_all(requests).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let syntax_theme = cx.theme().syntax().clone();
+                let modifier_styles = &EditorSettings::get_global(cx).semantic_tokens.modifiers;
+                let multi_buffer = editor.buffer.read(cx);
+                let snapshot = multi_buffer.snapshot(cx);
+
+                let mut highlights = Vec::<(Range<Anchor>, HighlightStyle)>::new();
+                for (buffer, tokens) in responses {
+                    let tokens = match tokens {
+                        Ok(tokens) => tokens,
+                        Err(error) => {
+                            log::error!("failed to fetch semantic tokens: {error:#}");
+                            continue;
+                        }
+                    };
+                    let buffer_snapshot = buffer.read(cx).snapshot();
+                    for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx)
+                    {
+                        let context = excerpt_range.context;
+                        let visible_tokens = tokens.iter().filter(|token| {
+                            let range = &token.range;
+                            range.end.cmp(&context.start, &buffer_snapshot).is_gt()
+                                && range.start.cmp(&context.end, &buffer_snapshot).is_lt()
+                        });
+                        for token in visible_tokens {
+                            let Some(style) = token_style(token, &syntax_theme, modifier_styles)
+                            else {
+                                continue;
+                            };
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, token.range.start);
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, token.range.end);
+                            highlights.push((start..end, style));
+                        }
+                    }
+                }
+                highlights.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start, &snapshot));
+
+                editor.display_map.update(cx, |display_map, _| {
+                    display_map.set_semantic_highlights(Some(highlights.into()))
+                });
+                cx.notify();
+            })
+            .ok()
+    }));
+}
+
+/// Styles a token with the theme's syntax style whose dot-separated key best matches the
+/// token's type and modifiers, the same way tree-sitter captures are matched to the theme,
+/// then applies the configured modifier styles on top.
+fn token_style(
+    token: &SemanticToken,
+    theme: &SyntaxTheme,
+    modifier_styles: &HashMap<String, SemanticTokenModifierStyle>,
+) -> Option<HighlightStyle> {
+    let token_parts = syntax_capture_for_token_type(&token.token_type)
+        .split('.')
+        .chain(
+            token
+                .token_modifiers
+                .iter()
+                .map(|modifier| modifier.as_ref()),
+        );
+    let mut style = theme
+        .highlights
+        .iter()
+        .filter_map(|(key, style)| {
+            let mut len = 0;
+            for key_part in key.split('.') {
+                if token_parts.clone().any(|part| part == key_part) {
+                    len += 1;
+                } else {
+                    return None;
+                }
+            }
+            Some((*style, len))
+        })
+        .max_by_key(|(_, len)| *len)
+        .map(|(style, _)| style);
+
+    for modifier in &token.token_modifiers {
+        let Some(modifier_style) = modifier_styles.get(modifier.as_ref()) else {
+            continue;
+        };
+        let style = style.get_or_insert_with(HighlightStyle::default);
+        if modifier_style.underline {
+            style.underline = Some(UnderlineStyle {
+                thickness: px(1.),
+                color: style.color,
+                wavy: false,
+            });
+        }
+        if modifier_style.italic {
+            style.font_style = Some(FontStyle::Italic);
+        }
+        if modifier_style.bold {
+            style.font_weight = Some(FontWeight::BOLD);
+        }
+    }
+    style
+}
+
+/// Maps the standard LSP token types onto the names used by tree-sitter highlight queries.
+/// Other token types, such as rust-analyzer's `lifetime`, are looked up in the theme as is.
+fn syntax_capture_for_token_type(token_type: &str) -> &str {
+    match token_type {
+        "class" | "interface" | "struct" | "type" | "typeAlias" | "builtinType" => "type",
+        "typeParameter" => "type.parameter",
+        "enumMember" => "variant",
+        "method" => "function.method",
+        "macro" => "function.macro",
+        "parameter" => "variable.parameter",
+        "modifier" => "keyword",
+        "regexp" => "string.regex",
+        "decorator" => "attribute",
+        token_type => token_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{hsla, Hsla};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_token_style() {
+        let red = hsla(0., 1., 0.5, 1.);
+        let blue = hsla(0.6, 1., 0.5, 1.);
+        let green = hsla(0.3, 1., 0.5, 1.);
+        let theme = SyntaxTheme::new_test([
+            ("function", red),
+            ("variable", blue),
+            ("variable.mutable", green),
+        ]);
+        let modifier_styles = HashMap::from_iter([(
+            "unsafe".to_string(),
+            SemanticTokenModifierStyle {
+                italic: true,
+                ..Default::default()
+            },
+        )]);
+        let style = |token_type: &str, modifiers: &[&str]| {
+            let token = SemanticToken {
+                range: text::Anchor::MIN..text::Anchor::MAX,
+                token_type: token_type.into(),
+                token_modifiers: modifiers
+                    .iter()
+                    .map(|modifier| Arc::from(*modifier))
+                    .collect(),
+            };
+            token_style(&token, &theme, &modifier_styles)
+        };
+        let color = |style: Option<HighlightStyle>| -> Option<Hsla> { style?.color };
+
+        assert_eq!(color(style("variable", &[])), Some(blue));
+        assert_eq!(color(style("parameter", &[])), Some(blue));
+        assert_eq!(color(style("variable", &["mutable"])), Some(green));
+        assert_eq!(color(style("method", &["declaration"])), Some(red));
+        assert_eq!(style("namespace", &[]), None);
+
+        let unsafe_call = style("function", &["unsafe"]).unwrap();
+        assert_eq!(unsafe_call.color, Some(red));
+        assert_eq!(unsafe_call.font_style, Some(FontStyle::Italic));
+    }
+}
@@ -0,0 +1,457 @@
+use crate::{
+    display_map::ToDisplayPoint, hover_popover::parse_blocks, Anchor, DisplayPoint, Editor,
+    EditorSettings, EditorSnapshot, EditorStyle, ShowSignatureHelp,
+};
+use gpui::{
+    div, AnyElement, FontWeight, HighlightStyle, InteractiveElement, IntoElement, ParentElement,
+    Pixels, Size, StatefulInteractiveElement, Styled, StyledText, Task, ViewContext, WeakView,
+};
+use language::ParsedMarkdown;
+use project::{HoverBlock, SignatureHelp};
+use settings::Settings;
+use std::{ops::Range, time::Duration};
+use ui::{prelude::*, LabelSize};
+use util::TryFutureExt;
+use workspace::Workspace;
+
+/// How long to wait for the cursor to settle before re-querying an open popover.
+const SIGNATURE_HELP_DEBOUNCE: Duration = Duration::from_millis(75);
+
+/// Bindable action which requests signature help for the call surrounding the newest cursor.
+pub fn show_signature_help(
+    editor: &mut Editor,
+    _: &ShowSignatureHelp,
+    cx: &mut ViewContext<Editor>,
+) {
+    request_signature_help(editor, None, cx);
+}
+
+/// Opens the signature help popover when `text` is one of the language server's
+/// trigger characters, e.g. typing `(` after a function name.
+pub fn trigger_signature_help_on_input(
+    editor: &mut Editor,
+    text: &str,
+    cx: &mut ViewContext<Editor>,
+) {
+    if !EditorSettings::get_global(cx).show_signature_help_on_input
+        || editor.signature_help_state.is_shown()
+    {
+        return;
+    }
+
+    let Some(project) = editor.project.as_ref() else {
+        return;
+    };
+    let position = editor.selections.newest_anchor().head();
+    let Some((buffer, _)) = editor
+        .buffer
+        .read(cx)
+        .text_anchor_for_position(position, cx)
+    else {
+        return;
+    };
+
+    if project
+        .read(cx)
+        .is_signature_help_trigger(&buffer, text, cx)
+    {
+        request_signature_help(editor, None, cx);
+    }
+}
+
+/// Re-queries an open popover once the cursor stops moving, so that the active parameter
+/// follows the cursor and the popover closes once it leaves the call.
+pub fn refresh_signature_help(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.signature_help_state.is_shown() {
+        request_signature_help(editor, Some(SIGNATURE_HELP_DEBOUNCE), cx);
+    }
+}
+
+/// Hides the signature help popover and cancels any pending request for it.
+pub fn hide_signature_help(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> bool {
+    editor.signature_help_state.task = None;
+    let did_hide = editor.signature_help_state.popover.take().is_some();
+    if did_hide {
+        cx.notify();
+    }
+    did_hide
+}
+
+/// Replaces any pending request, so that only the latest position is queried.
+fn request_signature_help(
+    editor: &mut Editor,
+    debounce: Option<Duration>,
+    cx: &mut ViewContext<Editor>,
+) {
+    if editor.pending_rename.is_some() {
+        return;
+    }
+
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let position = editor.selections.newest_anchor().head();
+    let Some((buffer, buffer_position)) = editor
+        .buffer
+        .read(cx)
+        .text_anchor_for_position(position, cx)
+    else {
+        return;
+    };
+
+    let task = cx.spawn(|this, mut cx| {
+        async move {
+            if let Some(debounce) = debounce {
+                cx.background_executor().timer(debounce).await;
+            }
+
+            let signature_help = project
+                .update(&mut cx, |project, cx| {
+                    project.signature_help(&buffer, buffer_position, cx)
+                })?
+                .await
+                .ok()
+                .flatten();
+
+            let Some(signature_help) = signature_help else {
+                this.update(&mut cx, |this, cx| {
+                    this.signature_help_state.popover = None;
+                    cx.notify();
+                })?;
+                return Ok(());
+            };
+
+            let language_registry = project.update(&mut cx, |p, _| p.languages().clone())?;
+            let language = buffer.update(&mut cx, |buffer, _| buffer.language().cloned())?;
+            let documentation_blocks = documentation_blocks(&signature_help);
+            let parsed_documentation = if documentation_blocks.is_empty() {
+                None
+            } else {
+                Some(parse_blocks(&documentation_blocks, &language_registry, language).await)
+            };
+
+            this.update(&mut cx, |this, cx| {
+                this.signature_help_state.popover = Some(SignatureHelpPopover {
+                    signature_help,
+                    anchor: position,
+                    parsed_documentation,
+                });
+                cx.notify();
+            })?;
+
+            anyhow::Ok(())
+        }
+        .log_err()
+    });
+
+    editor.signature_help_state.task = Some(task);
+}
+
+fn documentation_blocks(signature_help: &SignatureHelp) -> Vec<HoverBlock> {
+    signature_help
+        .active_parameter()
+        .and_then(|parameter| parameter.documentation.as_ref())
+        .into_iter()
+        .chain(
+            signature_help
+                .active_signature()
+                .and_then(|signature| signature.documentation.as_ref()),
+        )
+        .filter(|documentation| !documentation.value.trim().is_empty())
+        .map(|documentation| HoverBlock {
+            text: documentation.value.clone(),
+            kind: documentation.kind.clone(),
+        })
+        .collect()
+}
+
+#[derive(Default)]
+pub struct SignatureHelpState {
+    pub popover: Option<SignatureHelpPopover>,
+    task: Option<Task<Option<()>>>,
+}
+
+impl SignatureHelpState {
+    pub fn is_shown(&self) -> bool {
+        self.popover.is_some()
+    }
+
+    pub fn render(
+        &mut self,
+        snapshot: &EditorSnapshot,
+        style: &EditorStyle,
+        visible_rows: Range<u32>,
+        max_size: Size<Pixels>,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Option<(DisplayPoint, Vec<AnyElement>)> {
+        let popover = self.popover.as_mut()?;
+        let point = popover.anchor.to_display_point(&snapshot.display_snapshot);
+
+        // Don't render if the cursor isn't on screen
+        if !visible_rows.contains(&point.row()) {
+            return None;
+        }
+
+        Some((point, vec![popover.render(style, max_size, workspace, cx)]))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SignatureHelpPopover {
+    pub signature_help: SignatureHelp,
+    anchor: Anchor,
+    parsed_documentation: Option<ParsedMarkdown>,
+}
+
+impl SignatureHelpPopover {
+    pub fn render(
+        &mut self,
+        style: &EditorStyle,
+        max_size: Size<Pixels>,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut ViewContext<Editor>,
+    ) -> AnyElement {
+        let signature_count = self.signature_help.signatures.len();
+        let active_signature = self.signature_help.active_signature;
+        let label = self
+            .signature_help
+            .active_signature()
+            .map(|signature| signature.label.clone())
+            .unwrap_or_default();
+        let highlights = self.signature_help.active_parameter().map(|parameter| {
+            (
+                parameter.label.clone(),
+                HighlightStyle {
+                    font_weight: Some(FontWeight::BOLD),
+                    ..Default::default()
+                },
+            )
+        });
+
+        div()
+            .id("signature_help_popover")
+            .elevation_2(cx)
+            .p_2()
+            .overflow_y_scroll()
+            .max_w(max_size.width)
+            .max_h(max_size.height)
+            // Prevent a mouse move on the popover from being propagated to the editor,
+            // because that would dismiss the hover popover.
+            .on_mouse_move(|_, cx| cx.stop_propagation())
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(StyledText::new(label).with_highlights(&style.text, highlights))
+                    .when(signature_count > 1, |this| {
+                        this.child(
+                            Label::new(format!("{}/{}", active_signature + 1, signature_count))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    }),
+            )
+            .when_some(self.parsed_documentation.as_ref(), |this, documentation| {
+                this.child(div().pt_1().child(crate::render_parsed_markdown(
+                    "signature_documentation",
+                    documentation,
+                    style,
+                    workspace,
+                    cx,
+                )))
+            })
+            .into_any_element()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext};
+    use indoc::indoc;
+    use project::{ParameterInformation, SignatureInformation};
+    use smol::stream::StreamExt;
+    use std::sync::{
+        atomic::{self, AtomicUsize},
+        Arc,
+    };
+
+    fn signature_help_capabilities() -> lsp::ServerCapabilities {
+        lsp::ServerCapabilities {
+            signature_help_provider: Some(lsp::SignatureHelpOptions {
+                trigger_characters: Some(vec!["(".to_string()]),
+                retrigger_characters: Some(vec![",".to_string()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn lsp_signature_help(active_parameter: u32) -> lsp::SignatureHelp {
+        lsp::SignatureHelp {
+            signatures: vec![lsp::SignatureInformation {
+                label: "fn sum(a: u32, b: u32) -> u32".to_string(),
+                documentation: None,
+                parameters: Some(vec![
+                    lsp::ParameterInformation {
+                        label: lsp::ParameterLabel::LabelOffsets([7, 13]),
+                        documentation: None,
+                    },
+                    lsp::ParameterInformation {
+                        label: lsp::ParameterLabel::Simple("b: u32".to_string()),
+                        documentation: Some(lsp::Documentation::String(
+                            "the second addend".to_string(),
+                        )),
+                    },
+                ]),
+                active_parameter: None,
+            }],
+            active_signature: Some(0),
+            active_parameter: Some(active_parameter),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_signature_help_on_trigger_character(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(signature_help_capabilities(), cx).await;
+
+        cx.set_state(indoc! {"
+            fn main() { sumˇ }
+        "});
+
+        let mut requests =
+            cx.handle_request::<lsp::request::SignatureHelpRequest, _, _>(|_, _, _| async move {
+                Ok(Some(lsp_signature_help(0)))
+            });
+        cx.update_editor(|editor, cx| editor.handle_input("(", cx));
+        requests.next().await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, _| {
+            let popover = editor.signature_help_state.popover.as_ref().unwrap();
+            assert_eq!(
+                popover.signature_help.active_parameter(),
+                Some(&ParameterInformation {
+                    label: 7..13,
+                    documentation: None,
+                })
+            );
+        });
+
+        // Typing a retrigger character moves the highlight to the next parameter.
+        let mut requests =
+            cx.handle_request::<lsp::request::SignatureHelpRequest, _, _>(|_, _, _| async move {
+                Ok(Some(lsp_signature_help(1)))
+            });
+        cx.update_editor(|editor, cx| editor.handle_input("1,", cx));
+        cx.executor().advance_clock(SIGNATURE_HELP_DEBOUNCE);
+        requests.next().await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, _| {
+            let signature_help = &editor
+                .signature_help_state
+                .popover
+                .as_ref()
+                .unwrap()
+                .signature_help;
+            assert_eq!(
+                signature_help.active_signature(),
+                Some(&SignatureInformation {
+                    label: "fn sum(a: u32, b: u32) -> u32".to_string(),
+                    documentation: None,
+                    parameters: vec![
+                        ParameterInformation {
+                            label: 7..13,
+                            documentation: None,
+                        },
+                        ParameterInformation {
+                            label: 15..21,
+                            documentation: Some(project::MarkupContent {
+                                kind: project::HoverBlockKind::PlainText,
+                                value: "the second addend".to_string(),
+                            }),
+                        },
+                    ],
+                    active_parameter: None,
+                })
+            );
+            assert_eq!(signature_help.active_parameter().unwrap().label, 15..21);
+        });
+
+        // Leaving the call dismisses the popover.
+        let mut requests =
+            cx.handle_request::<lsp::request::SignatureHelpRequest, _, _>(|_, _, _| async move {
+                Ok(None)
+            });
+        cx.update_editor(|editor, cx| editor.handle_input("2)", cx));
+        cx.executor().advance_clock(SIGNATURE_HELP_DEBOUNCE);
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(!editor.signature_help_state.is_shown()));
+    }
+
+    #[gpui::test]
+    async fn test_signature_help_not_triggered_by_other_input(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(signature_help_capabilities(), cx).await;
+
+        cx.set_state(indoc! {"
+            fn main() { sumˇ }
+        "});
+        cx.update_editor(|editor, cx| editor.handle_input("x", cx));
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(!editor.signature_help_state.is_shown()));
+
+        let mut requests =
+            cx.handle_request::<lsp::request::SignatureHelpRequest, _, _>(|_, _, _| async move {
+                Ok(Some(lsp_signature_help(0)))
+            });
+        cx.update_editor(|editor, cx| show_signature_help(editor, &ShowSignatureHelp, cx));
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(editor.signature_help_state.is_shown()));
+
+        cx.update_editor(|editor, cx| editor.cancel(&crate::Cancel, cx));
+        cx.editor(|editor, _| assert!(!editor.signature_help_state.is_shown()));
+    }
+
+    #[gpui::test]
+    async fn test_signature_help_refresh_is_debounced(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(signature_help_capabilities(), cx).await;
+
+        cx.set_state(indoc! {"
+            fn main() { sum(1, 2ˇ) }
+        "});
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let mut requests = cx.handle_request::<lsp::request::SignatureHelpRequest, _, _>({
+            let request_count = request_count.clone();
+            move |_, _, _| {
+                request_count.fetch_add(1, atomic::Ordering::SeqCst);
+                async move { Ok(Some(lsp_signature_help(1))) }
+            }
+        });
+        cx.update_editor(|editor, cx| show_signature_help(editor, &ShowSignatureHelp, cx));
+        requests.next().await;
+        cx.run_until_parked();
+        assert_eq!(request_count.load(atomic::Ordering::SeqCst), 1);
+
+        // Moving the cursor repeatedly only queries the language server once it settles.
+        for _ in 0..3 {
+            cx.update_editor(|editor, cx| editor.move_left(&crate::MoveLeft, cx));
+            cx.run_until_parked();
+        }
+        assert_eq!(request_count.load(atomic::Ordering::SeqCst), 1);
+
+        cx.executor().advance_clock(SIGNATURE_HELP_DEBOUNCE);
+        requests.next().await;
+        cx.run_until_parked();
+        assert_eq!(request_count.load(atomic::Ordering::SeqCst), 2);
+        cx.editor(|editor, _| assert!(editor.signature_help_state.is_shown()));
+    }
+}
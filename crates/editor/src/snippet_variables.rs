@@ -0,0 +1,105 @@
+use crate::Editor;
+use gpui::AppContext;
+use language::{CharKind, File, Point};
+use rand::Rng;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Resolves a snippet variable such as `$TM_FILENAME` for the newest selection, using
+/// the names defined by TextMate and VS Code. Returns `None` for variables that we
+/// don't know, or that can't be resolved in this editor.
+pub(crate) fn resolve_snippet_variable(
+    editor: &Editor,
+    name: &str,
+    cx: &AppContext,
+) -> Option<String> {
+    let multi_buffer = editor.buffer.read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+    let selection = editor.selections.newest::<Point>(cx);
+    let head = selection.head();
+    let file = multi_buffer
+        .point_to_buffer_offset(head, cx)
+        .and_then(|(buffer, _, _)| buffer.read(cx).file().cloned());
+
+    match name {
+        "TM_SELECTED_TEXT" => Some(snapshot.text_for_range(selection.range()).collect()),
+        "TM_CURRENT_LINE" => Some(
+            snapshot
+                .text_for_range(
+                    Point::new(head.row, 0)..Point::new(head.row, snapshot.line_len(head.row)),
+                )
+                .collect(),
+        ),
+        "TM_CURRENT_WORD" => {
+            let (word_range, kind) = snapshot.surrounding_word(head);
+            if kind == Some(CharKind::Word) {
+                Some(snapshot.text_for_range(word_range).collect())
+            } else {
+                Some(String::new())
+            }
+        }
+        "TM_LINE_INDEX" => Some(head.row.to_string()),
+        "TM_LINE_NUMBER" => Some((head.row + 1).to_string()),
+        "TM_FILENAME" => Some(file?.file_name(cx).to_string_lossy().into_owned()),
+        "TM_FILENAME_BASE" => {
+            let file = file?;
+            let file_name = Path::new(file.file_name(cx));
+            Some(file_name.file_stem()?.to_string_lossy().into_owned())
+        }
+        "TM_DIRECTORY" => Some(
+            file_path(file?.as_ref(), cx)
+                .parent()?
+                .to_string_lossy()
+                .into_owned(),
+        ),
+        "TM_FILEPATH" => Some(file_path(file?.as_ref(), cx).to_string_lossy().into_owned()),
+        "RELATIVE_FILEPATH" => Some(file?.path().to_string_lossy().into_owned()),
+        "WORKSPACE_NAME" => {
+            let full_path = file?.full_path(cx);
+            let root_name = full_path.components().next()?;
+            Some(root_name.as_os_str().to_string_lossy().into_owned())
+        }
+        "CLIPBOARD" => Some(
+            cx.read_from_clipboard()
+                .map(|item| item.text().clone())
+                .unwrap_or_default(),
+        ),
+        "CURRENT_YEAR" => Some(now(cx).year().to_string()),
+        "CURRENT_YEAR_SHORT" => Some(format!("{:02}", now(cx).year() % 100)),
+        "CURRENT_MONTH" => Some(format!("{:02}", u8::from(now(cx).month()))),
+        "CURRENT_MONTH_NAME" => Some(now(cx).month().to_string()),
+        "CURRENT_MONTH_NAME_SHORT" => Some(now(cx).month().to_string()[..3].to_string()),
+        "CURRENT_DATE" => Some(format!("{:02}", now(cx).day())),
+        "CURRENT_DAY_NAME" => Some(now(cx).weekday().to_string()),
+        "CURRENT_DAY_NAME_SHORT" => Some(now(cx).weekday().to_string()[..3].to_string()),
+        "CURRENT_HOUR" => Some(format!("{:02}", now(cx).hour())),
+        "CURRENT_MINUTE" => Some(format!("{:02}", now(cx).minute())),
+        "CURRENT_SECOND" => Some(format!("{:02}", now(cx).second())),
+        "CURRENT_SECONDS_UNIX" => Some(now(cx).unix_timestamp().to_string()),
+        "UUID" => Some(Uuid::new_v4().to_string()),
+        "RANDOM" => Some(format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))),
+        "RANDOM_HEX" => Some(format!(
+            "{:06x}",
+            rand::thread_rng().gen_range(0..0x1000000)
+        )),
+        "LINE_COMMENT" => {
+            let scope = snapshot.language_scope_at(head)?;
+            let prefix = scope.line_comment_prefixes()?.first()?;
+            Some(prefix.trim_end().to_string())
+        }
+        _ => None,
+    }
+}
+
+fn file_path(file: &dyn File, cx: &AppContext) -> PathBuf {
+    match file.as_local() {
+        Some(file) => file.abs_path(cx),
+        None => file.full_path(cx),
+    }
+}
+
+/// The current time in the local time zone.
+fn now(cx: &AppContext) -> OffsetDateTime {
+    OffsetDateTime::now_utc().to_offset(cx.local_timezone())
+}
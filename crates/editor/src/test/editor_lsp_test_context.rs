@@ -148,6 +148,14 @@ impl EditorLspTestContext {
                 ("<" @open ">" @close)
                 ("\"" @open "\"" @close)
                 (closure_parameters "|" @open "|" @close)"#})),
+            textobjects: Some(Cow::from(indoc! {r#"
+                (function_item
+                    body: (_) @function.inside) @function.around
+                (impl_item
+                    body: (_) @class.inside) @class.around
+                (parameters (_) @parameter.inside)
+                (arguments (_) @parameter.inside)
+                (line_comment) @comment.around"#})),
             ..Default::default()
         })
         .expect("Could not parse queries");
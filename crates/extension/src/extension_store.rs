@@ -12,6 +12,7 @@ use language::{
 };
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use snippet_provider::{is_snippets_file, SnippetRegistry};
 use std::cmp::Ordering;
 use std::{
     ffi::OsStr,
@@ -76,6 +77,7 @@ pub struct ExtensionStore {
     manifest_path: PathBuf,
     language_registry: Arc<LanguageRegistry>,
     theme_registry: Arc<ThemeRegistry>,
+    snippet_registry: Arc<SnippetRegistry>,
     extension_changes: ExtensionChanges,
     reload_task: Option<Task<Option<()>>>,
     needs_reload: bool,
@@ -92,6 +94,9 @@ pub struct Manifest {
     pub grammars: BTreeMap<Arc<str>, GrammarManifestEntry>,
     pub languages: BTreeMap<Arc<str>, LanguageManifestEntry>,
     pub themes: BTreeMap<Arc<str>, ThemeManifestEntry>,
+    /// Snippets files, keyed by their path relative to the extensions directory.
+    #[serde(default)]
+    pub snippets: BTreeMap<Arc<str>, SnippetManifestEntry>,
 }
 
 #[derive(PartialEq, Eq, Debug, PartialOrd, Ord, Deserialize, Serialize)]
@@ -114,11 +119,18 @@ pub struct ThemeManifestEntry {
     path: PathBuf,
 }
 
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct SnippetManifestEntry {
+    extension: String,
+    path: PathBuf,
+}
+
 #[derive(Default)]
 struct ExtensionChanges {
     languages: HashSet<Arc<str>>,
     grammars: HashSet<Arc<str>>,
     themes: HashSet<Arc<str>>,
+    snippets: HashSet<Arc<str>>,
 }
 
 actions!(zed, [ReloadExtensions]);
@@ -128,6 +140,7 @@ pub fn init(
     http_client: Arc<HttpClientWithUrl>,
     language_registry: Arc<LanguageRegistry>,
     theme_registry: Arc<ThemeRegistry>,
+    snippet_registry: Arc<SnippetRegistry>,
     cx: &mut AppContext,
 ) {
     let store = cx.new_model(|cx| {
@@ -137,6 +150,7 @@ pub fn init(
             http_client.clone(),
             language_registry.clone(),
             theme_registry,
+            snippet_registry,
             cx,
         )
     });
@@ -160,6 +174,7 @@ impl ExtensionStore {
         http_client: Arc<HttpClientWithUrl>,
         language_registry: Arc<LanguageRegistry>,
         theme_registry: Arc<ThemeRegistry>,
+        snippet_registry: Arc<SnippetRegistry>,
         cx: &mut ModelContext<Self>,
     ) -> Self {
         let mut this = Self {
@@ -175,6 +190,7 @@ impl ExtensionStore {
             http_client,
             language_registry,
             theme_registry,
+            snippet_registry,
             _watch_extensions_dir: [Task::ready(()), Task::ready(())],
         };
         this._watch_extensions_dir = this.watch_extensions_dir(cx);
@@ -316,6 +332,7 @@ impl ExtensionStore {
                 RemoveOptions {
                     recursive: true,
                     ignore_if_not_exists: true,
+                    trash: false,
                 },
             )
             .await?;
@@ -331,10 +348,10 @@ impl ExtensionStore {
 
     /// Updates the set of installed extensions.
     ///
-    /// First, this unloads any themes, languages, or grammars that are
-    /// no longer in the manifest, or whose files have changed on disk.
-    /// Then it loads any themes, languages, or grammars that are newly
-    /// added to the manifest, or whose files have changed on disk.
+    /// First, this unloads any themes, languages, grammars, or snippets that
+    /// are no longer in the manifest, or whose files have changed on disk.
+    /// Then it loads any themes, languages, grammars, or snippets that are
+    /// newly added to the manifest, or whose files have changed on disk.
     fn manifest_updated(&mut self, manifest: Manifest, cx: &mut ModelContext<Self>) {
         fn diff<'a, T, I1, I2>(
             old_keys: I1,
@@ -395,6 +412,18 @@ impl ExtensionStore {
             manifest.themes.iter(),
             &self.extension_changes.themes,
         );
+        let (snippets_to_remove, snippets_to_add) = diff(
+            old_manifest.snippets.iter(),
+            manifest.snippets.iter(),
+            &self.extension_changes.snippets,
+        );
+        for snippets_key in &snippets_to_remove {
+            if let Some(snippets) = old_manifest.snippets.get(snippets_key) {
+                let mut snippets_path = self.extensions_dir.clone();
+                snippets_path.extend([snippets.extension.as_ref(), snippets.path.as_path()]);
+                self.snippet_registry.remove_file(&snippets_path);
+            }
+        }
         self.extension_changes.clear();
         drop(old_manifest);
 
@@ -460,6 +489,28 @@ impl ExtensionStore {
             })
             .detach();
 
+        let fs = self.fs.clone();
+        let root_dir = self.extensions_dir.clone();
+        let snippet_registry = self.snippet_registry.clone();
+        let snippets = snippets_to_add
+            .iter()
+            .filter_map(|key| manifest.snippets.get(key).cloned())
+            .collect::<Vec<_>>();
+        cx.background_executor()
+            .spawn(async move {
+                for snippets in &snippets {
+                    let mut snippets_path = root_dir.clone();
+                    snippets_path.extend([snippets.extension.as_ref(), snippets.path.as_path()]);
+
+                    if let Some(content) = fs.load(&snippets_path).await.log_err() {
+                        snippet_registry
+                            .insert_file(snippets_path.into(), None, &content)
+                            .log_err();
+                    }
+                }
+            })
+            .detach();
+
         cx.spawn(|_, cx| async move {
             while let Some(_) = reload_theme_rx.next().await {
                 if cx
@@ -489,6 +540,7 @@ impl ExtensionStore {
                 let mut changed_grammars = HashSet::default();
                 let mut changed_languages = HashSet::default();
                 let mut changed_themes = HashSet::default();
+                let mut changed_snippets = HashSet::default();
 
                 {
                     let manifest = manifest.read();
@@ -519,6 +571,15 @@ impl ExtensionStore {
                                 changed_themes.insert(theme_name.clone());
                             }
                         }
+
+                        for (snippets_key, snippets) in &manifest.snippets {
+                            let mut snippets_path = extensions_dir.clone();
+                            snippets_path
+                                .extend([snippets.extension.as_ref(), snippets.path.as_path()]);
+                            if event.path == snippets_path {
+                                changed_snippets.insert(snippets_key.clone());
+                            }
+                        }
                     }
                 }
 
@@ -527,6 +588,7 @@ impl ExtensionStore {
                         languages: changed_languages,
                         grammars: changed_grammars,
                         themes: changed_themes,
+                        snippets: changed_snippets,
                     })
                     .ok();
             }
@@ -710,6 +772,27 @@ impl ExtensionStore {
             }
         }
 
+        if let Ok(mut snippets_paths) = fs.read_dir(&extension_dir.join("snippets")).await {
+            while let Some(snippets_path) = snippets_paths.next().await {
+                let snippets_path = snippets_path?;
+                let Ok(relative_path) = snippets_path.strip_prefix(&extension_dir) else {
+                    continue;
+                };
+                if !is_snippets_file(&snippets_path) {
+                    continue;
+                }
+
+                let key = Path::new(extension_name).join(relative_path);
+                manifest.snippets.insert(
+                    key.to_string_lossy().into(),
+                    SnippetManifestEntry {
+                        extension: extension_name.into(),
+                        path: relative_path.into(),
+                    },
+                );
+            }
+        }
+
         Ok(())
     }
 }
@@ -719,12 +802,14 @@ impl ExtensionChanges {
         self.grammars.clear();
         self.languages.clear();
         self.themes.clear();
+        self.snippets.clear();
     }
 
     fn merge(&mut self, other: Self) {
         self.grammars.extend(other.grammars);
         self.languages.extend(other.languages);
         self.themes.extend(other.themes);
+        self.snippets.extend(other.snippets);
     }
 }
 
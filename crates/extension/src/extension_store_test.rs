@@ -1,11 +1,13 @@
 use crate::{
-    ExtensionStore, GrammarManifestEntry, LanguageManifestEntry, Manifest, ThemeManifestEntry,
+    ExtensionStore, GrammarManifestEntry, LanguageManifestEntry, Manifest, SnippetManifestEntry,
+    ThemeManifestEntry,
 };
 use fs::FakeFs;
 use gpui::{Context, TestAppContext};
 use language::{LanguageMatcher, LanguageRegistry};
 use serde_json::json;
 use settings::SettingsStore;
+use snippet_provider::SnippetRegistry;
 use std::{path::PathBuf, sync::Arc};
 use theme::ThemeRegistry;
 use util::http::FakeHttpClient;
@@ -94,6 +96,11 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                             "highlights.scm": "",
                         }
                     },
+                    "snippets": {
+                        "ruby.json": r#"{
+                            "Method": { "prefix": "def", "body": "def $1\n  $0\nend" }
+                        }"#,
+                    },
                 }
             }
         }),
@@ -185,10 +192,20 @@ async fn test_extension_store(cx: &mut TestAppContext) {
         ]
         .into_iter()
         .collect(),
+        snippets: [(
+            "zed-ruby/snippets/ruby.json".into(),
+            SnippetManifestEntry {
+                extension: "zed-ruby".into(),
+                path: "snippets/ruby.json".into(),
+            },
+        )]
+        .into_iter()
+        .collect(),
     };
 
     let language_registry = Arc::new(LanguageRegistry::test());
     let theme_registry = Arc::new(ThemeRegistry::new(Box::new(())));
+    let snippet_registry = Arc::new(SnippetRegistry::default());
 
     let store = cx.new_model(|cx| {
         ExtensionStore::new(
@@ -197,6 +214,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
             http_client.clone(),
             language_registry.clone(),
             theme_registry.clone(),
+            snippet_registry.clone(),
             cx,
         )
     });
@@ -207,11 +225,20 @@ async fn test_extension_store(cx: &mut TestAppContext) {
         assert_eq!(manifest.grammars, expected_manifest.grammars);
         assert_eq!(manifest.languages, expected_manifest.languages);
         assert_eq!(manifest.themes, expected_manifest.themes);
+        assert_eq!(manifest.snippets, expected_manifest.snippets);
 
         assert_eq!(
             language_registry.language_names(),
             ["ERB", "Plain Text", "Ruby"]
         );
+        assert_eq!(
+            snippet_registry
+                .snippets_for(Some("Ruby"), None)
+                .iter()
+                .map(|snippet| snippet.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Method"]
+        );
         assert_eq!(
             theme_registry.list_names(false),
             [
@@ -291,6 +318,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
             http_client.clone(),
             language_registry.clone(),
             theme_registry.clone(),
+            snippet_registry.clone(),
             cx,
         )
     });
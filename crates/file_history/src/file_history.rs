@@ -0,0 +1,366 @@
+mod file_history_picker;
+mod file_history_settings;
+mod persistence;
+
+use file_history_picker::FileHistoryPicker;
+pub use file_history_settings::FileHistorySettings;
+use fs::Fs;
+use gpui::{actions, AppContext, Global, Model, Task};
+use language::Buffer;
+use persistence::DB;
+use project::{Project, ProjectEntryId};
+use settings::Settings;
+use std::{path::PathBuf, sync::Arc};
+use time::OffsetDateTime;
+use util::ResultExt;
+use workspace::Workspace;
+
+actions!(file_history, [ShowFileHistory, ShowDeletedFiles]);
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// The most files recorded when a directory is deleted.
+const MAX_DELETED_FILES: usize = 1000;
+
+/// The share of the history's maximum size that the files of a deleted directory can take, so
+/// that deleting a large directory doesn't evict the history of every other file.
+const MAX_DELETED_SIZE_RATIO: i64 = 4;
+
+/// Keeps a copy of every version of a file saved through the project, and of the
+/// files deleted from the project panel, in a bounded local store.
+pub struct FileHistory;
+
+impl Global for FileHistory {}
+
+pub fn init(cx: &mut AppContext) {
+    FileHistorySettings::register(cx);
+    cx.set_global(FileHistory);
+
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        FileHistoryPicker::register(workspace, cx);
+
+        cx.subscribe(workspace.project(), |_, _, event, cx| {
+            if let project::Event::BufferSaved(buffer) = event {
+                FileHistory::record_save(buffer, cx).detach();
+            }
+        })
+        .detach();
+    })
+    .detach();
+}
+
+impl FileHistory {
+    /// Records the text the buffer was last saved with as a new version of its file.
+    pub fn record_save(buffer: &Model<Buffer>, cx: &AppContext) -> Task<()> {
+        if !FileHistorySettings::get_global(cx).enabled {
+            return Task::ready(());
+        }
+        let buffer = buffer.read(cx);
+        let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+            return Task::ready(());
+        };
+        let abs_path = file.abs_path(cx);
+        let contents = buffer.text_for_version(buffer.saved_version()).to_string();
+        Self::record_versions(vec![(abs_path, contents)], false, cx)
+    }
+
+    /// Records the current contents of the files under an entry that is about to be
+    /// deleted, leaving out ignored files and stopping at [`MAX_DELETED_FILES`] files. The
+    /// returned task must be awaited before deleting the entry.
+    pub fn record_deleted_entry(
+        project: &Model<Project>,
+        entry_id: ProjectEntryId,
+        cx: &AppContext,
+    ) -> Task<()> {
+        if !cx.has_global::<Self>() || !FileHistorySettings::get_global(cx).enabled {
+            return Task::ready(());
+        }
+        let project = project.read(cx);
+        let Some(worktree) = project.worktree_for_entry(entry_id, cx) else {
+            return Task::ready(());
+        };
+        let Some(worktree) = worktree.read(cx).as_local() else {
+            return Task::ready(());
+        };
+        let Some(entry) = worktree.entry_for_id(entry_id) else {
+            return Task::ready(());
+        };
+
+        let abs_paths = if entry.is_file() {
+            vec![worktree.absolutize(&entry.path)]
+        } else {
+            worktree
+                .files(false, 0)
+                .skip_while(|file| !file.path.starts_with(&entry.path))
+                .take_while(|file| file.path.starts_with(&entry.path))
+                .take(MAX_DELETED_FILES)
+                .map(|file| worktree.absolutize(&file.path))
+                .collect()
+        };
+        let abs_paths = abs_paths
+            .into_iter()
+            .filter_map(|abs_path| abs_path.log_err())
+            .collect::<Vec<_>>();
+
+        let fs = project.fs().clone();
+        let max_size = max_size(cx);
+        cx.spawn(|cx| async move {
+            let mut versions = Vec::new();
+            let mut remaining_size = max_size / MAX_DELETED_SIZE_RATIO;
+            for abs_path in abs_paths {
+                // Files that aren't text, or that don't fit in the share of the store left for
+                // the deletion, are skipped.
+                if let Ok(contents) = fs.load(&abs_path).await {
+                    if (contents.len() as i64) <= remaining_size {
+                        remaining_size -= contents.len() as i64;
+                        versions.push((abs_path, contents));
+                    }
+                }
+            }
+            if let Some(task) = cx
+                .update(|cx| Self::record_versions(versions, true, cx))
+                .log_err()
+            {
+                task.await;
+            }
+        })
+    }
+
+    fn record_versions(
+        versions: Vec<(PathBuf, String)>,
+        deleted: bool,
+        cx: &AppContext,
+    ) -> Task<()> {
+        let max_age = FileHistorySettings::get_global(cx).max_age_days as i64 * SECONDS_PER_DAY;
+        let max_size = max_size(cx);
+        cx.background_executor().spawn(async move {
+            let now = OffsetDateTime::now_utc().unix_timestamp();
+            for (abs_path, contents) in versions {
+                // Saving a file without changing it doesn't add a version, but deleting it does.
+                if !deleted
+                    && DB
+                        .latest_contents(abs_path.clone())
+                        .log_err()
+                        .flatten()
+                        .as_ref()
+                        == Some(&contents)
+                {
+                    continue;
+                }
+                DB.insert_version(abs_path, now, contents, deleted)
+                    .await
+                    .log_err();
+            }
+
+            DB.delete_versions_before(now - max_age).await.log_err();
+            if DB.total_size().log_err().unwrap_or(0) > max_size {
+                DB.delete_versions_exceeding_size(max_size).await.log_err();
+            }
+        })
+    }
+}
+
+/// Returns the versions of a file, newest first, without loading their contents.
+fn load_versions(abs_path: PathBuf, cx: &AppContext) -> Task<Vec<Version>> {
+    cx.background_executor().spawn(async move {
+        DB.versions(abs_path)
+            .log_err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id, timestamp, deleted)| Version {
+                id,
+                timestamp,
+                deleted,
+            })
+            .collect()
+    })
+}
+
+/// Returns the last version of every deleted file, most recently deleted first.
+fn load_deleted_files(cx: &AppContext) -> Task<Vec<(PathBuf, Version)>> {
+    cx.background_executor().spawn(async move {
+        DB.deleted_files()
+            .log_err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(abs_path, id, timestamp)| {
+                (
+                    abs_path,
+                    Version {
+                        id,
+                        timestamp,
+                        deleted: true,
+                    },
+                )
+            })
+            .collect()
+    })
+}
+
+fn load_contents(version: &Version, cx: &AppContext) -> Task<Option<Arc<str>>> {
+    let id = version.id;
+    cx.background_executor()
+        .spawn(async move { DB.contents(id).log_err().flatten().map(Arc::from) })
+}
+
+fn max_size(cx: &AppContext) -> i64 {
+    FileHistorySettings::get_global(cx).max_size_mb as i64 * 1024 * 1024
+}
+
+/// A version of a file in the history.
+#[derive(Clone, Copy, Debug)]
+struct Version {
+    id: i64,
+    timestamp: i64,
+    deleted: bool,
+}
+
+/// Writes a version of a deleted file back to disk.
+async fn restore_deleted_file(
+    fs: Arc<dyn Fs>,
+    abs_path: PathBuf,
+    contents: Arc<str>,
+) -> anyhow::Result<()> {
+    if let Some(parent) = abs_path.parent() {
+        fs.create_dir(parent).await?;
+    }
+    fs.atomic_write(abs_path, contents.to_string()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::{FakeFs, RemoveOptions};
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+
+    #[gpui::test]
+    async fn test_record_save(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/test_record_save", json!({ "a.txt": "one" }))
+            .await;
+        let project = Project::test(fs, ["/test_record_save".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/test_record_save/a.txt", cx)
+            })
+            .await
+            .unwrap();
+        let abs_path = PathBuf::from("/test_record_save/a.txt");
+
+        let save = |text: &'static str, cx: &mut TestAppContext| {
+            buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+            let save = project.update(cx, |project, cx| project.save_buffer(buffer.clone(), cx));
+            async move { save.await.unwrap() }
+        };
+
+        // Saving records the saved text as a new version.
+        save("two", cx).await;
+        cx.update(|cx| FileHistory::record_save(&buffer, cx)).await;
+        assert_eq!(
+            DB.latest_contents(abs_path.clone()).unwrap().as_deref(),
+            Some("two")
+        );
+
+        // Saving the same text again doesn't add a version.
+        cx.update(|cx| FileHistory::record_save(&buffer, cx)).await;
+        assert_eq!(DB.versions(abs_path.clone()).unwrap().len(), 1);
+
+        // Unsaved edits aren't recorded, only the text the buffer was saved with.
+        save("three", cx).await;
+        buffer.update(cx, |buffer, cx| buffer.set_text("four", cx));
+        cx.update(|cx| FileHistory::record_save(&buffer, cx)).await;
+        let versions = DB.versions(abs_path.clone()).unwrap();
+        assert_eq!(
+            versions
+                .iter()
+                .map(|(id, _, deleted)| (DB.contents(*id).unwrap().unwrap(), *deleted))
+                .collect::<Vec<_>>(),
+            [("three".to_string(), false), ("two".to_string(), false)]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_record_and_restore_deleted_entry(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/test_deleted_entry",
+            json!({
+                ".gitignore": "ignored",
+                "dir": {
+                    "a.txt": "a",
+                    "ignored": { "b.txt": "b" },
+                },
+                "c.txt": "c",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/test_deleted_entry".as_ref()], cx).await;
+        let worktree = project.read_with(cx, |project, _| project.worktrees().next().unwrap());
+        let (worktree_id, dir_id, ignored_dir_id) = worktree.read_with(cx, |worktree, _| {
+            (
+                worktree.id(),
+                worktree.entry_for_path("dir").unwrap().id,
+                worktree.entry_for_path("dir/ignored").unwrap().id,
+            )
+        });
+        project
+            .update(cx, |project, cx| {
+                project.expand_entry(worktree_id, ignored_dir_id, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+        // The files under the directory are recorded as deleted, except for the ignored ones.
+        cx.update(|cx| FileHistory::record_deleted_entry(&project, dir_id, cx))
+            .await;
+        let versions = |path: &str| {
+            DB.versions(Path::new("/test_deleted_entry").join(path))
+                .unwrap()
+                .into_iter()
+                .map(|(_, _, deleted)| deleted)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(versions("dir/a.txt"), [true]);
+        assert!(versions("dir/ignored/b.txt").is_empty());
+        assert!(versions("c.txt").is_empty());
+
+        // Once the directory is gone, its files can be restored from the history.
+        fs.remove_dir(
+            "/test_deleted_entry/dir".as_ref(),
+            RemoveOptions {
+                recursive: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let deleted_files = cx.update(|cx| load_deleted_files(cx)).await;
+        let (abs_path, version) = deleted_files
+            .into_iter()
+            .find(|(abs_path, _)| abs_path.starts_with("/test_deleted_entry"))
+            .unwrap();
+        assert_eq!(abs_path, Path::new("/test_deleted_entry/dir/a.txt"));
+        let contents = cx.update(|cx| load_contents(&version, cx)).await.unwrap();
+        restore_deleted_file(fs.clone(), abs_path.clone(), contents)
+            .await
+            .unwrap();
+        assert_eq!(fs.load(&abs_path).await.unwrap(), "a");
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            FileHistorySettings::register(cx);
+            cx.set_global(FileHistory);
+        });
+    }
+}
@@ -0,0 +1,398 @@
+use diff_view::{DiffBase, DiffView};
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    View, WeakView,
+};
+use language::Buffer;
+use picker::{Picker, PickerDelegate};
+use std::{path::PathBuf, sync::Arc};
+use time::OffsetDateTime;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{
+    load_contents, load_deleted_files, load_versions, restore_deleted_file, ShowDeletedFiles,
+    ShowFileHistory, Version,
+};
+
+/// A modal that lists the saved versions of a file, or the deleted files of the project,
+/// to compare them with the current contents or to restore them.
+pub struct FileHistoryPicker {
+    picker: View<Picker<FileHistoryPickerDelegate>>,
+}
+
+/// What the versions listed in a [`FileHistoryPicker`] belong to.
+enum Target {
+    /// The saved versions of the buffer's file.
+    Buffer(Model<Buffer>),
+    /// The files of the project that were deleted.
+    DeletedFiles,
+}
+
+struct Candidate {
+    abs_path: PathBuf,
+    version: Version,
+    label: String,
+    timestamp: String,
+}
+
+impl FileHistoryPicker {
+    pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &ShowFileHistory, cx| {
+            let Some(buffer) = workspace
+                .active_item_as::<Editor>(cx)
+                .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+            else {
+                return;
+            };
+            let Some(abs_path) = buffer
+                .read(cx)
+                .file()
+                .and_then(|file| file.as_local())
+                .map(|file| file.abs_path(cx))
+            else {
+                return;
+            };
+
+            let versions = load_versions(abs_path.clone(), cx);
+            cx.spawn(|workspace, mut cx| async move {
+                let versions = versions.await;
+                workspace.update(&mut cx, |workspace, cx| {
+                    let candidates = versions
+                        .into_iter()
+                        .map(|version| {
+                            let timestamp = format_timestamp(&version, cx);
+                            let label = if version.deleted {
+                                format!("{timestamp} (deleted)")
+                            } else {
+                                timestamp.clone()
+                            };
+                            Candidate {
+                                abs_path: abs_path.clone(),
+                                version,
+                                label,
+                                timestamp,
+                            }
+                        })
+                        .collect();
+                    Self::toggle(Target::Buffer(buffer), candidates, workspace, cx);
+                })
+            })
+            .detach_and_log_err(cx);
+        });
+
+        workspace.register_action(|workspace, _: &ShowDeletedFiles, cx| {
+            let deleted_files = load_deleted_files(cx);
+            cx.spawn(|workspace, mut cx| async move {
+                let deleted_files = deleted_files.await;
+                workspace.update(&mut cx, |workspace, cx| {
+                    let project = workspace.project().read(cx);
+                    let candidates = deleted_files
+                        .into_iter()
+                        .filter_map(|(abs_path, version)| {
+                            // Only the files of the project that haven't been recreated are listed.
+                            let label = project.visible_worktrees(cx).find_map(|worktree| {
+                                let worktree = worktree.read(cx);
+                                let path = abs_path.strip_prefix(worktree.abs_path()).ok()?;
+                                if worktree.entry_for_path(path).is_some() {
+                                    return None;
+                                }
+                                Some(
+                                    PathBuf::from(worktree.root_name())
+                                        .join(path)
+                                        .to_string_lossy()
+                                        .to_string(),
+                                )
+                            })?;
+                            Some(Candidate {
+                                timestamp: format_timestamp(&version, cx),
+                                abs_path,
+                                version,
+                                label,
+                            })
+                        })
+                        .collect();
+                    Self::toggle(Target::DeletedFiles, candidates, workspace, cx);
+                })
+            })
+            .detach_and_log_err(cx);
+        });
+    }
+
+    fn toggle(
+        target: Target,
+        candidates: Vec<Candidate>,
+        workspace: &mut Workspace,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let workspace_handle = cx.view().downgrade();
+        workspace.toggle_modal(cx, move |cx| {
+            let delegate = FileHistoryPickerDelegate {
+                file_history_picker: cx.view().downgrade(),
+                workspace: workspace_handle,
+                target,
+                string_candidates: candidates
+                    .iter()
+                    .enumerate()
+                    .map(|(id, candidate)| StringMatchCandidate::new(id, candidate.label.clone()))
+                    .collect(),
+                candidates,
+                matches: Vec::new(),
+                selected_index: 0,
+            };
+            let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+            Self { picker }
+        });
+    }
+}
+
+fn format_timestamp(version: &Version, cx: &AppContext) -> String {
+    match OffsetDateTime::from_unix_timestamp(version.timestamp) {
+        Ok(timestamp) => time_format::format_localized_timestamp(
+            OffsetDateTime::now_utc(),
+            timestamp,
+            cx.local_timezone(),
+        ),
+        Err(_) => String::new(),
+    }
+}
+
+impl Render for FileHistoryPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for FileHistoryPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for FileHistoryPicker {}
+impl ModalView for FileHistoryPicker {}
+
+pub struct FileHistoryPickerDelegate {
+    file_history_picker: WeakView<FileHistoryPicker>,
+    workspace: WeakView<Workspace>,
+    target: Target,
+    candidates: Vec<Candidate>,
+    string_candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl FileHistoryPickerDelegate {
+    /// Opens a diff of the buffer against the selected version.
+    fn compare(&self, candidate: &Candidate, buffer: Model<Buffer>, cx: &mut WindowContext) {
+        let contents = load_contents(&candidate.version, cx);
+        let file_name = candidate
+            .abs_path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let title = format!("{file_name} ({})", candidate.timestamp);
+        let workspace = self.workspace.clone();
+        cx.spawn(|mut cx| async move {
+            let Some(text) = contents.await else {
+                return;
+            };
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    let base = DiffBase::Text {
+                        title: title.into(),
+                        text,
+                    };
+                    DiffView::deploy(buffer, base, workspace, cx);
+                })
+                .log_err();
+        })
+        .detach();
+    }
+
+    /// Replaces the contents of the buffer with the selected version, as an edit that can
+    /// be undone and that still needs to be saved.
+    fn restore(&self, candidate: &Candidate, buffer: Model<Buffer>, cx: &mut WindowContext) {
+        let contents = load_contents(&candidate.version, cx);
+        cx.spawn(|mut cx| async move {
+            let Some(text) = contents.await else {
+                return;
+            };
+            let Some(diff) = buffer
+                .update(&mut cx, |buffer, cx| buffer.diff(text.to_string(), cx))
+                .log_err()
+            else {
+                return;
+            };
+            let diff = diff.await;
+            buffer
+                .update(&mut cx, |buffer, cx| buffer.apply_diff(diff, cx))
+                .log_err();
+        })
+        .detach();
+    }
+
+    /// Writes the selected deleted file back to disk and opens it.
+    fn restore_file(&self, candidate: &Candidate, cx: &mut WindowContext) {
+        let Some(fs) = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                workspace.project().read(cx).fs().clone()
+            })
+            .log_err()
+        else {
+            return;
+        };
+        let contents = load_contents(&candidate.version, cx);
+        let abs_path = candidate.abs_path.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn(|mut cx| async move {
+            let Some(contents) = contents.await else {
+                return Ok(());
+            };
+            restore_deleted_file(fs, abs_path.clone(), contents).await?;
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    workspace.open_abs_path(abs_path, true, cx)
+                })?
+                .await?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl PickerDelegate for FileHistoryPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        match self.target {
+            Target::Buffer(_) => "Search saved versions...".into(),
+            Target::DeletedFiles => "Search deleted files...".into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let candidate = &self.candidates[mat.candidate_id];
+            match &self.target {
+                Target::Buffer(buffer) if secondary => self.restore(candidate, buffer.clone(), cx),
+                Target::Buffer(buffer) => self.compare(candidate, buffer.clone(), cx),
+                Target::DeletedFiles => self.restore_file(candidate, cx),
+            }
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.file_history_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.string_candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let candidate = &self.candidates[mat.candidate_id];
+        let mut item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(selected)
+            .child(HighlightedLabel::new(
+                mat.string.clone(),
+                mat.positions.clone(),
+            ));
+        if let Target::DeletedFiles = self.target {
+            item = item.end_slot(
+                Label::new(candidate.timestamp.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            );
+        }
+        Some(item)
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        let Target::Buffer(_) = self.target else {
+            return None;
+        };
+        Some(
+            h_flex()
+                .w_full()
+                .p_2()
+                .gap_2()
+                .justify_end()
+                .child(
+                    Button::new("compare-version", "Compare")
+                        .on_click(cx.listener(|picker, _, cx| picker.delegate.confirm(false, cx))),
+                )
+                .child(
+                    Button::new("restore-version", "Restore")
+                        .on_click(cx.listener(|picker, _, cx| picker.delegate.confirm(true, cx))),
+                )
+                .into_any_element(),
+        )
+    }
+}
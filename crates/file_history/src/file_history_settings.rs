@@ -0,0 +1,42 @@
+use schemars::JsonSchema;
+use serde_derive::{Deserialize, Serialize};
+use settings::Settings;
+
+#[derive(Deserialize, Debug)]
+pub struct FileHistorySettings {
+    pub enabled: bool,
+    pub max_age_days: u64,
+    pub max_size_mb: u64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct FileHistorySettingsContent {
+    /// Whether to keep a copy of every saved version of a file, and of the files
+    /// deleted from the project panel.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// For how many days saved versions are kept.
+    ///
+    /// Default: 30
+    pub max_age_days: Option<u64>,
+    /// The maximum total size of the saved versions in megabytes. The oldest
+    /// versions are removed first when it is exceeded.
+    ///
+    /// Default: 256
+    pub max_size_mb: Option<u64>,
+}
+
+impl Settings for FileHistorySettings {
+    const KEY: Option<&'static str> = Some("file_history");
+
+    type FileContent = FileHistorySettingsContent;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        Self::load_via_json_merge(default_value, user_values)
+    }
+}
@@ -0,0 +1,192 @@
+use std::path::PathBuf;
+
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // file_history(
+    //   id: usize,
+    //   abs_path: PathBuf,
+    //   timestamp: i64, // Seconds since the Unix epoch
+    //   contents: String,
+    //   deleted: bool, // Whether the file was deleted after this version
+    //   size: i64, // The size of the contents in bytes
+    // )
+    pub static ref DB: FileHistoryDb<()> =
+        &[sql! (
+            CREATE TABLE file_history(
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                abs_path BLOB NOT NULL,
+                timestamp INTEGER NOT NULL,
+                contents TEXT NOT NULL,
+                deleted INTEGER NOT NULL,
+                size INTEGER NOT NULL
+            ) STRICT;
+
+            CREATE INDEX file_history_abs_path ON file_history(abs_path);
+            CREATE INDEX file_history_size ON file_history(size);
+        )];
+);
+
+impl FileHistoryDb {
+    query! {
+        pub fn latest_contents(abs_path: PathBuf) -> Result<Option<String>> {
+            SELECT contents FROM file_history
+            WHERE abs_path = ?
+            ORDER BY id DESC
+            LIMIT 1
+        }
+    }
+
+    query! {
+        pub async fn insert_version(
+            abs_path: PathBuf,
+            timestamp: i64,
+            contents: String,
+            deleted: bool
+        ) -> Result<()> {
+            INSERT INTO file_history
+                (abs_path, timestamp, contents, deleted, size)
+            VALUES
+                (?1, ?2, ?3, ?4, LENGTH(CAST(?3 AS BLOB)))
+        }
+    }
+
+    // Returns the id, timestamp and deletion flag of the versions of a file, newest first
+    query! {
+        pub fn versions(abs_path: PathBuf) -> Result<Vec<(i64, i64, bool)>> {
+            SELECT id, timestamp, deleted FROM file_history
+            WHERE abs_path = ?
+            ORDER BY id DESC
+        }
+    }
+
+    // Returns the path, id and timestamp of the last version of every deleted file
+    query! {
+        pub fn deleted_files() -> Result<Vec<(PathBuf, i64, i64)>> {
+            SELECT abs_path, MAX(id), timestamp FROM file_history
+            GROUP BY abs_path
+            HAVING deleted = 1
+            ORDER BY MAX(id) DESC
+        }
+    }
+
+    query! {
+        pub fn contents(id: i64) -> Result<Option<String>> {
+            SELECT contents FROM file_history
+            WHERE id = ?
+        }
+    }
+
+    query! {
+        pub async fn delete_versions_before(timestamp: i64) -> Result<()> {
+            DELETE FROM file_history
+            WHERE timestamp < ?
+        }
+    }
+
+    query! {
+        pub fn total_size() -> Result<i64> {
+            SELECT COALESCE(SUM(size), 0) FROM file_history
+        }
+    }
+
+    // Deletes the oldest versions until the size of the remaining ones is at most `max_size`
+    query! {
+        pub async fn delete_versions_exceeding_size(max_size: i64) -> Result<()> {
+            DELETE FROM file_history
+            WHERE id IN (
+                SELECT id FROM (
+                    SELECT id, SUM(size) OVER (ORDER BY id DESC) AS total_size
+                    FROM file_history
+                )
+                WHERE total_size > ?
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::open_test_db;
+
+    #[gpui::test]
+    async fn test_file_history_queries() {
+        let db = FileHistoryDb(open_test_db("test_file_history_queries").await);
+        let path = PathBuf::from("/root/a.txt");
+
+        db.insert_version(path.clone(), 10, "one".into(), false)
+            .await
+            .unwrap();
+        db.insert_version(path.clone(), 20, "two".into(), false)
+            .await
+            .unwrap();
+        db.insert_version("/root/b.txt".into(), 30, "three".into(), true)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            db.latest_contents(path.clone()).unwrap().as_deref(),
+            Some("two")
+        );
+        let versions = db.versions(path.clone()).unwrap();
+        assert_eq!(
+            versions
+                .iter()
+                .map(|(_, timestamp, deleted)| (*timestamp, *deleted))
+                .collect::<Vec<_>>(),
+            [(20, false), (10, false)]
+        );
+        assert_eq!(db.contents(versions[1].0).unwrap().as_deref(), Some("one"));
+        assert_eq!(
+            db.deleted_files()
+                .unwrap()
+                .into_iter()
+                .map(|(path, _, timestamp)| (path, timestamp))
+                .collect::<Vec<_>>(),
+            [(PathBuf::from("/root/b.txt"), 30)]
+        );
+
+        db.delete_versions_before(15).await.unwrap();
+        assert_eq!(db.versions(path.clone()).unwrap().len(), 1);
+        assert_eq!(db.total_size().unwrap(), 8);
+
+        db.delete_versions_exceeding_size(5).await.unwrap();
+        assert_eq!(db.versions(path.clone()).unwrap().len(), 0);
+        assert_eq!(db.deleted_files().unwrap().len(), 1);
+        assert_eq!(db.total_size().unwrap(), 5);
+    }
+
+    #[gpui::test]
+    async fn test_file_history_pruning_by_size() {
+        let db = FileHistoryDb(open_test_db("test_file_history_pruning_by_size").await);
+        let path = PathBuf::from("/root/a.txt");
+        assert_eq!(db.total_size().unwrap(), 0);
+
+        // Sizes are counted in bytes, not characters.
+        for (timestamp, contents) in [(10, "ü"), (20, "abc"), (30, "def"), (40, "gh")] {
+            db.insert_version(path.clone(), timestamp, contents.into(), false)
+                .await
+                .unwrap();
+        }
+        assert_eq!(db.total_size().unwrap(), 10);
+
+        // The oldest versions are deleted first, keeping the newest ones that fit.
+        db.delete_versions_exceeding_size(4).await.unwrap();
+        assert_eq!(
+            db.versions(path.clone())
+                .unwrap()
+                .into_iter()
+                .map(|(_, timestamp, _)| timestamp)
+                .collect::<Vec<_>>(),
+            [40]
+        );
+        assert_eq!(db.total_size().unwrap(), 2);
+
+        // Nothing is deleted while the versions fit.
+        db.delete_versions_exceeding_size(2).await.unwrap();
+        assert_eq!(db.versions(path).unwrap().len(), 1);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod repository;
+mod trash;
 
 use anyhow::{anyhow, Result};
 pub use fsevent::Event;
@@ -47,6 +48,8 @@ pub trait Fs: Send + Sync {
     async fn rename(&self, source: &Path, target: &Path, options: RenameOptions) -> Result<()>;
     async fn remove_dir(&self, path: &Path, options: RemoveOptions) -> Result<()>;
     async fn remove_file(&self, path: &Path, options: RemoveOptions) -> Result<()>;
+    /// Moves the item most recently moved to the trash from `path` back to it.
+    async fn restore_from_trash(&self, path: &Path) -> Result<()>;
     async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read>>;
     async fn load(&self, path: &Path) -> Result<String>;
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
@@ -95,6 +98,8 @@ pub struct RenameOptions {
 pub struct RemoveOptions {
     pub recursive: bool,
     pub ignore_if_not_exists: bool,
+    /// Move the item to the system trash instead of deleting it permanently.
+    pub trash: bool,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -107,6 +112,16 @@ pub struct Metadata {
 
 pub struct RealFs;
 
+impl RealFs {
+    async fn trash(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        if options.ignore_if_not_exists && smol::fs::symlink_metadata(path).await.is_err() {
+            return Ok(());
+        }
+        let path = path.to_path_buf();
+        smol::unblock(move || trash::trash(&path)).await
+    }
+}
+
 #[async_trait::async_trait]
 impl Fs for RealFs {
     async fn create_dir(&self, path: &Path) -> Result<()> {
@@ -152,6 +167,17 @@ impl Fs for RealFs {
     }
 
     async fn remove_dir(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        if options.trash {
+            if !options.recursive {
+                if let Ok(mut children) = smol::fs::read_dir(path).await {
+                    if children.next().await.is_some() {
+                        return Err(anyhow!("{path:?} is not empty"));
+                    }
+                }
+            }
+            return self.trash(path, options).await;
+        }
+
         let result = if options.recursive {
             smol::fs::remove_dir_all(path).await
         } else {
@@ -167,6 +193,10 @@ impl Fs for RealFs {
     }
 
     async fn remove_file(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        if options.trash {
+            return self.trash(path, options).await;
+        }
+
         match smol::fs::remove_file(path).await {
             Ok(()) => Ok(()),
             Err(err) if err.kind() == io::ErrorKind::NotFound && options.ignore_if_not_exists => {
@@ -176,6 +206,11 @@ impl Fs for RealFs {
         }
     }
 
+    async fn restore_from_trash(&self, path: &Path) -> Result<()> {
+        let path = path.to_path_buf();
+        smol::unblock(move || trash::restore(&path)).await
+    }
+
     async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read>> {
         Ok(Box::new(std::fs::File::open(path)?))
     }
@@ -421,6 +456,7 @@ struct FakeFsState {
     buffered_events: Vec<fsevent::Event>,
     metadata_call_count: usize,
     read_dir_call_count: usize,
+    trash: Vec<(PathBuf, Arc<Mutex<FakeFsEntry>>)>,
 }
 
 #[cfg(any(test, feature = "test-support"))]
@@ -571,6 +607,7 @@ impl FakeFs {
                 events_paused: false,
                 read_dir_call_count: 0,
                 metadata_call_count: 0,
+                trash: Vec::new(),
             }),
         })
     }
@@ -711,6 +748,26 @@ impl FakeFs {
         });
     }
 
+    pub fn set_head_for_repo(&self, dot_git: &Path, head_state: &[(&Path, String)]) {
+        self.with_git_state(dot_git, true, |state| {
+            state.head_contents.clear();
+            state.head_contents.extend(
+                head_state
+                    .iter()
+                    .map(|(path, content)| (path.to_path_buf(), content.clone())),
+            );
+        });
+    }
+
+    pub fn set_blame_for_repo(&self, dot_git: &Path, blames: Vec<(&Path, git::blame::Blame)>) {
+        self.with_git_state(dot_git, true, |state| {
+            state.blames.clear();
+            state
+                .blames
+                .extend(blames.into_iter().map(|(path, blame)| (path.into(), blame)));
+        });
+    }
+
     pub fn set_status_for_repo_via_working_copy_change(
         &self,
         dot_git: &Path,
@@ -807,6 +864,16 @@ impl FakeFs {
         result
     }
 
+    /// The original paths of the items in the trash, in the order they were trashed.
+    pub fn trashed_paths(&self) -> Vec<PathBuf> {
+        self.state
+            .lock()
+            .trash
+            .iter()
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
     /// How many `read_dir` calls have been issued.
     pub fn read_dir_call_count(&self) -> usize {
         self.state.lock().read_dir_call_count
@@ -1042,7 +1109,10 @@ impl Fs for FakeFs {
                         return Err(anyhow!("{path:?} is not empty"));
                     }
                 }
-                e.remove();
+                let entry = e.remove();
+                if options.trash {
+                    state.trash.push((path.clone(), entry));
+                }
             }
         }
         state.emit_event(&[path]);
@@ -1071,7 +1141,40 @@ impl Fs for FakeFs {
             }
             btree_map::Entry::Occupied(e) => {
                 e.get().lock().file_content(&path)?;
-                e.remove();
+                let entry = e.remove();
+                if options.trash {
+                    state.trash.push((path.clone(), entry));
+                }
+            }
+        }
+        state.emit_event(&[path]);
+        Ok(())
+    }
+
+    async fn restore_from_trash(&self, path: &Path) -> Result<()> {
+        self.simulate_random_delay().await;
+
+        let path = normalize_path(path);
+        let parent_path = path
+            .parent()
+            .ok_or_else(|| anyhow!("cannot restore the root"))?;
+        let base_name = path.file_name().unwrap();
+        let mut state = self.state.lock();
+        let ix = state
+            .trash
+            .iter()
+            .rposition(|(trashed_path, _)| *trashed_path == path)
+            .ok_or_else(|| anyhow!("{path:?} is not in the trash"))?;
+        let parent_entry = state.read_path(parent_path)?;
+        let mut parent_entry = parent_entry.lock();
+        match parent_entry
+            .dir_entries(parent_path)?
+            .entry(base_name.to_str().unwrap().into())
+        {
+            btree_map::Entry::Occupied(_) => return Err(anyhow!("{path:?} already exists")),
+            btree_map::Entry::Vacant(e) => {
+                let (_, entry) = state.trash.remove(ix);
+                e.insert(entry);
             }
         }
         state.emit_event(&[path]);
@@ -1321,6 +1424,7 @@ pub fn copy_recursive<'a>(
                     RemoveOptions {
                         recursive: true,
                         ignore_if_not_exists: true,
+                        trash: false,
                     },
                 )
                 .await;
@@ -1434,4 +1538,45 @@ mod tests {
             "D",
         );
     }
+
+    #[gpui::test]
+    async fn test_fake_fs_trash(executor: BackgroundExecutor) {
+        let fs = FakeFs::new(executor.clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a": "A",
+                "dir": {
+                    "b": "B"
+                }
+            }),
+        )
+        .await;
+
+        let trash = RemoveOptions {
+            recursive: true,
+            trash: true,
+            ..Default::default()
+        };
+        fs.remove_file("/root/a".as_ref(), trash).await.unwrap();
+        fs.remove_dir("/root/dir".as_ref(), trash).await.unwrap();
+        assert_eq!(fs.files(), Vec::<PathBuf>::new());
+        assert_eq!(
+            fs.trashed_paths(),
+            vec![PathBuf::from("/root/a"), PathBuf::from("/root/dir")]
+        );
+
+        fs.restore_from_trash("/root/dir".as_ref()).await.unwrap();
+        assert_eq!(fs.files(), vec![PathBuf::from("/root/dir/b")]);
+        assert!(fs.restore_from_trash("/root/dir".as_ref()).await.is_err());
+
+        fs.insert_file("/root/a", "new A".into()).await;
+        assert!(fs.restore_from_trash("/root/a".as_ref()).await.is_err());
+        fs.remove_file("/root/a".as_ref(), Default::default())
+            .await
+            .unwrap();
+        fs.restore_from_trash("/root/a".as_ref()).await.unwrap();
+        assert_eq!(fs.load("/root/a".as_ref()).await.unwrap(), "A");
+        assert_eq!(fs.trashed_paths(), Vec::<PathBuf>::new());
+    }
 }
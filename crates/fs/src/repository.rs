@@ -1,7 +1,9 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use collections::HashMap;
-use git2::{BranchType, StatusShow};
+use git::blame::{Blame, BlameEntry};
+use git2::{BranchType, ErrorCode, ObjectType, StatusShow};
 use parking_lot::Mutex;
+use rope::Rope;
 use serde_derive::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
@@ -21,10 +23,20 @@ pub struct Branch {
     pub unix_timestamp: Option<i64>,
 }
 
+/// Blames a file as if it had the given contents, as returned by [`GitRepository::blame`].
+pub type BlameFn = Box<dyn FnOnce(Rope) -> Result<Blame> + Send>;
+
 pub trait GitRepository: Send {
     fn reload_index(&self);
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Returns the contents of the file at the given path in the HEAD commit.
+    fn load_head_text(&self, relative_file_path: &Path) -> Option<String>;
+
+    /// Replaces the contents of the file at the given path in the index, removing it
+    /// from the index when `content` is `None`, and writes the index to disk.
+    fn set_index_text(&self, relative_file_path: &Path, content: Option<String>) -> Result<()>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
@@ -53,6 +65,31 @@ pub trait GitRepository: Send {
     fn branches(&self) -> Result<Vec<Branch>>;
     fn change_branch(&self, _: &str) -> Result<()>;
     fn create_branch(&self, _: &str) -> Result<()>;
+
+    /// Prepares to blame the file at the given path, relative to the working directory.
+    /// The returned function blames the file as if it had the given contents without
+    /// accessing the repository, so it can run after the repository's lock is released.
+    fn blame(&self, path: &Path) -> Result<BlameFn>;
+
+    /// Updates the index entries of the given paths to match the working directory,
+    /// removing the paths that were deleted from it.
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Resets the index entries of the given paths to match the HEAD commit.
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Returns the message of the HEAD commit.
+    fn head_commit_message(&self) -> Option<String>;
+
+    /// Commits the index with the given message, or replaces the HEAD commit with one
+    /// that has the index and the given message when `amend` is true. The pre-commit
+    /// hook runs first and its output is returned as an error when it fails.
+    ///
+    /// Returns the SHA of the new commit.
+    fn commit(&self, message: &str, amend: bool) -> Result<String>;
+
+    /// Pushes the current branch to the branch with the same name in the given remote.
+    fn push(&self, remote_name: &str) -> Result<()>;
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -92,6 +129,65 @@ impl GitRepository for LibGitRepository {
         None
     }
 
+    fn load_head_text(&self, relative_file_path: &Path) -> Option<String> {
+        fn logic(repo: &LibGitRepository, relative_file_path: &Path) -> Result<Option<String>> {
+            check_path_to_repo_path_errors(relative_file_path)?;
+
+            let tree = repo.head()?.peel_to_tree()?;
+            let entry = match tree.get_path(relative_file_path) {
+                Ok(entry) => entry,
+                Err(error) if error.code() == git2::ErrorCode::NotFound => return Ok(None),
+                Err(error) => return Err(error.into()),
+            };
+
+            let content = entry.to_object(repo)?.peel_to_blob()?.content().to_owned();
+            Ok(Some(String::from_utf8(content)?))
+        }
+
+        match logic(self, relative_file_path) {
+            Ok(value) => return value,
+            Err(err) => log::error!("Error loading head text: {:?}", err),
+        }
+        None
+    }
+
+    fn set_index_text(&self, relative_file_path: &Path, content: Option<String>) -> Result<()> {
+        const STAGE_NORMAL: i32 = 0;
+        check_path_to_repo_path_errors(relative_file_path)?;
+
+        let mut index = self.index()?;
+        if let Some(content) = content {
+            let mode = index
+                .get_path(relative_file_path, STAGE_NORMAL)
+                .map_or(0o100644, |entry| entry.mode);
+            // The stat fields are left empty so that the file in the working
+            // directory is never assumed to match the new index entry.
+            let entry = git2::IndexEntry {
+                ctime: git2::IndexTime::new(0, 0),
+                mtime: git2::IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode,
+                uid: 0,
+                gid: 0,
+                file_size: content.len() as u32,
+                id: git2::Oid::zero(),
+                flags: 0,
+                flags_extended: 0,
+                path: relative_file_path
+                    .to_str()
+                    .context("invalid path")?
+                    .as_bytes()
+                    .to_vec(),
+            };
+            index.add_frombuffer(&entry, content.as_bytes())?;
+        } else {
+            index.remove_path(relative_file_path)?;
+        }
+        index.write()?;
+        Ok(())
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let remote = self.find_remote(name).ok()?;
         remote.url().map(|url| url.to_string())
@@ -209,6 +305,172 @@ impl GitRepository for LibGitRepository {
 
         Ok(())
     }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let working_directory = self
+            .workdir()
+            .context("failed to read git work directory")?;
+        let mut index = self.index()?;
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+            if working_directory.join(path).exists() {
+                index.add_path(path)?;
+            } else {
+                index.remove_path(path)?;
+            }
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+        }
+        match self.head().and_then(|head| head.peel(ObjectType::Commit)) {
+            Ok(head) => {
+                self.reset_default(Some(&head), paths.iter().map(|path| path.as_path()))?;
+            }
+            // Before the first commit, unstaging a path removes it from the index.
+            Err(error) if error.code() == ErrorCode::UnbornBranch => {
+                let mut index = self.index()?;
+                for path in paths {
+                    index.remove_path(path)?;
+                }
+                index.write()?;
+            }
+            Err(error) => return Err(error.into()),
+        }
+        Ok(())
+    }
+
+    fn head_commit_message(&self) -> Option<String> {
+        let commit = self.head().ok()?.peel_to_commit().ok()?;
+        let message = commit.message()?;
+        Some(message.to_string())
+    }
+
+    fn commit(&self, message: &str, amend: bool) -> Result<String> {
+        run_hook(self, "pre-commit")?;
+
+        let mut index = self.index()?;
+        let tree = self.find_tree(index.write_tree()?)?;
+        let signature = self.signature().context("failed to read the git user")?;
+        let oid = if amend {
+            let head = self
+                .head()
+                .and_then(|head| head.peel_to_commit())
+                .context("there is no commit to amend")?;
+            head.amend(
+                Some("HEAD"),
+                None,
+                Some(&signature),
+                None,
+                Some(message),
+                Some(&tree),
+            )?
+        } else {
+            let parent = match self.head() {
+                Ok(head) => Some(head.peel_to_commit()?),
+                Err(error) if error.code() == ErrorCode::UnbornBranch => None,
+                Err(error) => return Err(error.into()),
+            };
+            let parents = parent.iter().collect::<Vec<_>>();
+            git2::Repository::commit(
+                self,
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents,
+            )?
+        };
+        Ok(oid.to_string())
+    }
+
+    fn push(&self, remote_name: &str) -> Result<()> {
+        let head = self.head()?;
+        if !head.is_branch() {
+            return Err(anyhow!("HEAD is not a branch"));
+        }
+        let branch_ref = head.name().context("invalid branch name")?;
+        let refspec = format!("{branch_ref}:{branch_ref}");
+
+        let mut remote = self
+            .find_remote(remote_name)
+            .with_context(|| format!("failed to find remote {remote_name:?}"))?;
+        // References that the remote refuses to update don't fail the push itself.
+        let mut rejection = None;
+        {
+            let mut callbacks = git2::RemoteCallbacks::new();
+            callbacks.push_update_reference(|reference, status| {
+                if let Some(status) = status {
+                    rejection = Some(format!("failed to push {reference}: {status}"));
+                }
+                Ok(())
+            });
+            let mut options = git2::PushOptions::new();
+            options.remote_callbacks(callbacks);
+            remote.push(&[refspec.as_str()], Some(&mut options))?;
+        }
+        match rejection {
+            Some(rejection) => Err(anyhow!(rejection)),
+            None => Ok(()),
+        }
+    }
+
+    fn blame(&self, path: &Path) -> Result<BlameFn> {
+        let working_directory = self
+            .workdir()
+            .with_context(|| {
+                format!("failed to blame {path:?}: repository has no working directory")
+            })?
+            .to_path_buf();
+        let path = path.to_path_buf();
+        Ok(Box::new(move |content| {
+            Blame::for_path(&working_directory, &path, &content)
+        }))
+    }
+}
+
+/// Runs the git hook with the given name, if it exists, returning its output as an
+/// error if it fails.
+fn run_hook(repo: &LibGitRepository, name: &str) -> Result<()> {
+    let working_directory = repo
+        .workdir()
+        .context("failed to read git work directory")?;
+    let hooks_directory = match repo.config()?.get_path("core.hooksPath") {
+        Ok(path) => working_directory.join(path),
+        Err(_) => repo.path().join("hooks"),
+    };
+    let hook_path = hooks_directory.join(name);
+    if !hook_path.is_file() {
+        return Ok(());
+    }
+    // Like git, ignore hooks that aren't executable.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if hook_path.metadata()?.permissions().mode() & 0o111 == 0 {
+            return Ok(());
+        }
+    }
+
+    let output = std::process::Command::new(&hook_path)
+        .current_dir(working_directory)
+        .output()
+        .with_context(|| format!("failed to run the {name} hook"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(anyhow!(
+            "the {name} hook failed:\n{}",
+            format!("{stdout}{stderr}").trim()
+        ))
+    }
 }
 
 fn matches_index(repo: &LibGitRepository, path: &RepoPath, mtime: SystemTime) -> bool {
@@ -251,8 +513,11 @@ pub struct FakeGitRepository {
 #[derive(Debug, Clone, Default)]
 pub struct FakeGitRepositoryState {
     pub index_contents: HashMap<PathBuf, String>,
+    pub head_contents: HashMap<PathBuf, String>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub branch_name: Option<String>,
+    pub blames: HashMap<RepoPath, Blame>,
+    pub head_commit_message: Option<String>,
 }
 
 impl FakeGitRepository {
@@ -269,6 +534,21 @@ impl GitRepository for FakeGitRepository {
         state.index_contents.get(path).cloned()
     }
 
+    fn load_head_text(&self, path: &Path) -> Option<String> {
+        let state = self.state.lock();
+        state.head_contents.get(path).cloned()
+    }
+
+    fn set_index_text(&self, path: &Path, content: Option<String>) -> Result<()> {
+        let mut state = self.state.lock();
+        if let Some(content) = content {
+            state.index_contents.insert(path.to_path_buf(), content);
+        } else {
+            state.index_contents.remove(path);
+        }
+        Ok(())
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
@@ -317,6 +597,112 @@ impl GitRepository for FakeGitRepository {
         state.branch_name = Some(name.to_owned());
         Ok(())
     }
+
+    fn stage_paths(&self, _paths: &[RepoPath]) -> Result<()> {
+        // The fake repository has no access to the working directory.
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            match state.head_contents.get(&path.0).cloned() {
+                Some(content) => state.index_contents.insert(path.0.clone(), content),
+                None => state.index_contents.remove(&path.0),
+            };
+        }
+        Ok(())
+    }
+
+    fn head_commit_message(&self) -> Option<String> {
+        let state = self.state.lock();
+        state.head_commit_message.clone()
+    }
+
+    fn commit(&self, message: &str, amend: bool) -> Result<String> {
+        let mut state = self.state.lock();
+        if amend && state.head_commit_message.is_none() {
+            return Err(anyhow!("there is no commit to amend"));
+        }
+        state.head_contents = state.index_contents.clone();
+        state.head_commit_message = Some(message.to_string());
+        Ok(String::new())
+    }
+
+    fn push(&self, _remote_name: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn blame(&self, path: &Path) -> Result<BlameFn> {
+        let state = self.state.clone();
+        let path = path.to_path_buf();
+        Ok(Box::new(move |content| {
+            let state = state.lock();
+            let blame = state
+                .blames
+                .get(&RepoPath::from(path.as_path()))
+                .cloned()
+                .ok_or_else(|| anyhow!("failed to blame {path:?}: no blame set"))?;
+            // The blame that was set is the blame of the committed contents, when they
+            // are known, so shift it past the lines that changed since.
+            Ok(match state.head_contents.get(&path) {
+                Some(head_text) => blame_edited_contents(blame, head_text, &content.to_string()),
+                None => blame,
+            })
+        }))
+    }
+}
+
+/// Adjusts the blame of `head_text` to `content`, blaming the lines between the common
+/// prefix and suffix of both texts on a commit whose SHA is all zeros.
+fn blame_edited_contents(blame: Blame, head_text: &str, content: &str) -> Blame {
+    let head_lines = head_text.lines().collect::<Vec<_>>();
+    let lines = content.lines().collect::<Vec<_>>();
+    let prefix_len = head_lines
+        .iter()
+        .zip(&lines)
+        .take_while(|(head_line, line)| head_line == line)
+        .count();
+    let suffix_len = head_lines[prefix_len..]
+        .iter()
+        .rev()
+        .zip(lines[prefix_len..].iter().rev())
+        .take_while(|(head_line, line)| head_line == line)
+        .count();
+    let removed_rows = prefix_len as u32..(head_lines.len() - suffix_len) as u32;
+    let inserted_rows = prefix_len as u32..(lines.len() - suffix_len) as u32;
+
+    let mut entries = Vec::new();
+    for entry in blame.entries {
+        let before = entry.range.start..entry.range.end.min(removed_rows.start);
+        if !before.is_empty() {
+            entries.push(BlameEntry {
+                range: before,
+                ..entry.clone()
+            });
+        }
+        let after = entry.range.start.max(removed_rows.end)..entry.range.end;
+        if !after.is_empty() {
+            let original_line_number =
+                entry.original_line_number + (after.start - entry.range.start);
+            let shift = |row: u32| row - removed_rows.end + inserted_rows.end;
+            entries.push(BlameEntry {
+                range: shift(after.start)..shift(after.end),
+                original_line_number,
+                ..entry
+            });
+        }
+    }
+    if !inserted_rows.is_empty() {
+        entries.push(BlameEntry {
+            sha: "0".repeat(40),
+            range: inserted_rows.clone(),
+            original_line_number: inserted_rows.start + 1,
+            ..Default::default()
+        });
+    }
+    entries.sort_unstable_by_key(|entry| entry.range.start);
+    Blame { entries }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -435,3 +821,107 @@ impl<'a> MapSeekTarget<RepoPath> for RepoPathDescendants<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn init_repository(path: &Path) -> LibGitRepository {
+        let repo = LibGitRepository::init(path).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test User").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        repo
+    }
+
+    fn staged_paths(repo: &LibGitRepository) -> Vec<(RepoPath, GitFileStatus)> {
+        repo.staged_statuses(Path::new(""))
+            .iter()
+            .map(|(path, status)| (path.clone(), *status))
+            .collect()
+    }
+
+    #[test]
+    fn test_stage_unstage_and_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repository(dir.path());
+        let a_txt = RepoPath::new("a.txt".into());
+
+        fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        repo.stage_paths(&[a_txt.clone()]).unwrap();
+        assert_eq!(staged_paths(&repo), [(a_txt.clone(), GitFileStatus::Added)]);
+
+        GitRepository::commit(&repo, "Add a.txt", false).unwrap();
+        assert_eq!(repo.head_commit_message().as_deref(), Some("Add a.txt"));
+        assert!(staged_paths(&repo).is_empty());
+
+        fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+        repo.stage_paths(&[a_txt.clone()]).unwrap();
+        assert_eq!(
+            staged_paths(&repo),
+            [(a_txt.clone(), GitFileStatus::Modified)]
+        );
+        repo.unstage_paths(&[a_txt.clone()]).unwrap();
+        assert!(staged_paths(&repo).is_empty());
+        assert_eq!(repo.load_index_text(&a_txt).as_deref(), Some("one\n"));
+
+        // Amending replaces the HEAD commit instead of adding a new one.
+        repo.stage_paths(&[a_txt.clone()]).unwrap();
+        let sha = GitRepository::commit(&repo, "Add a.txt with two", true).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id().to_string(), sha);
+        assert_eq!(head.parent_count(), 0);
+        assert_eq!(head.message(), Some("Add a.txt with two"));
+        assert_eq!(repo.load_head_text(&a_txt).as_deref(), Some("two\n"));
+
+        // Staging a deleted file removes it from the index.
+        fs::remove_file(dir.path().join("a.txt")).unwrap();
+        repo.stage_paths(&[a_txt.clone()]).unwrap();
+        assert_eq!(repo.load_index_text(&a_txt), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_commit_with_failing_pre_commit_hook() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repository(dir.path());
+        let hook_path = repo.path().join("hooks").join("pre-commit");
+        fs::create_dir_all(hook_path.parent().unwrap()).unwrap();
+        fs::write(
+            &hook_path,
+            "#!/bin/sh\necho 'a.txt is not formatted' >&2\nexit 1\n",
+        )
+        .unwrap();
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        repo.stage_paths(&[RepoPath::new("a.txt".into())]).unwrap();
+        let error = GitRepository::commit(&repo, "Add a.txt", false).unwrap_err();
+        assert!(error.to_string().contains("a.txt is not formatted"));
+        assert_eq!(repo.head_commit_message(), None);
+    }
+
+    #[test]
+    fn test_push_to_local_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote_dir = tempfile::tempdir().unwrap();
+        let remote_repo = LibGitRepository::init_bare(remote_dir.path()).unwrap();
+        let repo = init_repository(dir.path());
+        repo.remote("origin", remote_dir.path().to_str().unwrap())
+            .unwrap();
+
+        fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        repo.stage_paths(&[RepoPath::new("a.txt".into())]).unwrap();
+        let sha = GitRepository::commit(&repo, "Add a.txt", false).unwrap();
+        repo.push("origin").unwrap();
+
+        let branch_ref = repo.head().unwrap().name().unwrap().to_string();
+        let remote_head = remote_repo.find_reference(&branch_ref).unwrap();
+        assert_eq!(remote_head.target().unwrap().to_string(), sha);
+
+        assert!(repo.push("upstream").is_err());
+    }
+}
@@ -0,0 +1,384 @@
+//! Moving files and directories to the system trash, and restoring them from it.
+//!
+//! On Linux and the BSDs this follows the freedesktop.org trash specification: items are moved
+//! to `$XDG_DATA_HOME/Trash/files`, or to `$topdir/.Trash-$uid/files` when they live on another
+//! file system, along with a `.trashinfo` file recording their original path and deletion date.
+//! On macOS items are moved to `~/.Trash`, which has no record of where items came from, so
+//! only items trashed by this process can be restored.
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) use freedesktop::{restore, trash};
+#[cfg(target_os = "macos")]
+pub(crate) use macos::{restore, trash};
+#[cfg(target_os = "windows")]
+pub(crate) use windows::{restore, trash};
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod freedesktop {
+    use anyhow::{anyhow, Context as _, Result};
+    use std::{
+        ffi::{OsStr, OsString},
+        fs, io,
+        io::Write as _,
+        os::unix::{
+            ffi::{OsStrExt as _, OsStringExt as _},
+            fs::{DirBuilderExt as _, MetadataExt as _},
+        },
+        path::{Path, PathBuf},
+        time::SystemTime,
+    };
+
+    pub(crate) struct TrashDir {
+        pub root: PathBuf,
+        /// The directory that paths in `.trashinfo` files are relative to, for trash directories
+        /// at the top of a mount point. Paths in the home trash are absolute.
+        pub top_dir: Option<PathBuf>,
+    }
+
+    impl TrashDir {
+        fn files(&self) -> PathBuf {
+            self.root.join("files")
+        }
+
+        fn info(&self) -> PathBuf {
+            self.root.join("info")
+        }
+
+        fn info_path(&self, name: &OsStr) -> PathBuf {
+            let mut file_name = name.to_os_string();
+            file_name.push(".trashinfo");
+            self.info().join(file_name)
+        }
+
+        fn original_path<'a>(&self, path: &'a Path) -> &'a Path {
+            match &self.top_dir {
+                Some(top_dir) => path.strip_prefix(top_dir).unwrap_or(path),
+                None => path,
+            }
+        }
+    }
+
+    pub(crate) fn trash(path: &Path) -> Result<()> {
+        trash_in(&trash_dir_for(path)?, path)
+    }
+
+    pub(crate) fn restore(path: &Path) -> Result<()> {
+        restore_from(&trash_dir_for(path)?, path)
+    }
+
+    /// Returns the home trash if `path` is on the same file system as the home directory,
+    /// and the trash at the top of `path`'s mount point otherwise.
+    fn trash_dir_for(path: &Path) -> Result<TrashDir> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .unwrap_or_else(|| util::paths::HOME.join(".local/share"));
+        let home_trash = data_home.join("Trash");
+
+        let device = device(path)?;
+        if device == self::device(&home_trash)? {
+            return Ok(TrashDir {
+                root: home_trash,
+                top_dir: None,
+            });
+        }
+
+        let mut top_dir = existing_ancestor(path)?;
+        while let Some(parent) = top_dir.parent() {
+            if self::device(parent)? != device {
+                break;
+            }
+            top_dir = parent;
+        }
+        let uid = unsafe { libc::getuid() };
+        Ok(TrashDir {
+            root: top_dir.join(format!(".Trash-{uid}")),
+            top_dir: Some(top_dir.to_path_buf()),
+        })
+    }
+
+    fn existing_ancestor(path: &Path) -> Result<&Path> {
+        path.ancestors()
+            .find(|ancestor| fs::symlink_metadata(ancestor).is_ok())
+            .ok_or_else(|| anyhow!("no ancestor of {path:?} exists"))
+    }
+
+    fn device(path: &Path) -> Result<u64> {
+        Ok(fs::symlink_metadata(existing_ancestor(path)?)?.dev())
+    }
+
+    pub(crate) fn trash_in(trash_dir: &TrashDir, path: &Path) -> Result<()> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| anyhow!("cannot move {path:?} to the trash"))?;
+        for dir in [trash_dir.files(), trash_dir.info()] {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(&dir)
+                .with_context(|| format!("creating trash directory {dir:?}"))?;
+        }
+
+        let info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(trash_dir.original_path(path)),
+            deletion_date()
+        );
+
+        // Reserve a name by creating its info file, then move the item next to it.
+        for ix in 1.. {
+            let mut name = file_name.to_os_string();
+            if ix > 1 {
+                name.push(format!(".{ix}"));
+            }
+            let trashed_path = trash_dir.files().join(&name);
+            let info_path = trash_dir.info_path(&name);
+            let mut info_file = match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&info_path)
+            {
+                Ok(file) => file,
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(error.into()),
+            };
+            if fs::symlink_metadata(&trashed_path).is_ok() {
+                fs::remove_file(&info_path).ok();
+                continue;
+            }
+
+            let result = info_file
+                .write_all(info.as_bytes())
+                .map_err(anyhow::Error::from)
+                .and_then(|_| Ok(fs::rename(path, &trashed_path)?));
+            if let Err(error) = result {
+                fs::remove_file(&info_path).ok();
+                return Err(error.context(format!("moving {path:?} to the trash")));
+            }
+            return Ok(());
+        }
+        unreachable!()
+    }
+
+    /// Moves the most recently trashed item that was at `path` back to it.
+    pub(crate) fn restore_from(trash_dir: &TrashDir, path: &Path) -> Result<()> {
+        if fs::symlink_metadata(path).is_ok() {
+            return Err(anyhow!("{path:?} already exists"));
+        }
+
+        let original_path = trash_dir.original_path(path);
+        // Deletion dates only have a resolution of one second, so ties are broken with the
+        // modification time of the info files.
+        let mut latest: Option<((String, Option<SystemTime>), PathBuf)> = None;
+        let info_entries = fs::read_dir(trash_dir.info())
+            .with_context(|| format!("{path:?} is not in the trash"))?;
+        for info_entry in info_entries {
+            let info_path = info_entry?.path();
+            if info_path.extension() != Some(OsStr::new("trashinfo")) {
+                continue;
+            }
+            let Some((trashed_path, deletion_date)) = fs::read_to_string(&info_path)
+                .ok()
+                .and_then(|info| parse_trash_info(&info))
+            else {
+                continue;
+            };
+            if trashed_path != original_path {
+                continue;
+            }
+            let modified = fs::metadata(&info_path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            let date = (deletion_date, modified);
+            if latest
+                .as_ref()
+                .map_or(true, |(latest_date, _)| date >= *latest_date)
+            {
+                latest = Some((date, info_path));
+            }
+        }
+
+        let (_, info_path) = latest.ok_or_else(|| anyhow!("{path:?} is not in the trash"))?;
+        let name = info_path
+            .file_stem()
+            .ok_or_else(|| anyhow!("invalid trash info file {info_path:?}"))?;
+        fs::rename(trash_dir.files().join(name), path)
+            .with_context(|| format!("restoring {path:?} from the trash"))?;
+        fs::remove_file(&info_path)?;
+        Ok(())
+    }
+
+    fn parse_trash_info(info: &str) -> Option<(PathBuf, String)> {
+        let mut lines = info.lines();
+        if lines.next()?.trim() != "[Trash Info]" {
+            return None;
+        }
+        let mut path = None;
+        let mut deletion_date = None;
+        for line in lines {
+            if let Some(value) = line.strip_prefix("Path=") {
+                path = Some(decode_path(value)?);
+            } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+                deletion_date = Some(value.to_string());
+            }
+        }
+        Some((path?, deletion_date?))
+    }
+
+    /// Percent-encodes a path, as the specification requires for the `Path` key.
+    fn encode_path(path: &Path) -> String {
+        let mut encoded = String::new();
+        for &byte in path.as_os_str().as_bytes() {
+            if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+                encoded.push(byte as char);
+            } else {
+                encoded.push_str(&format!("%{byte:02X}"));
+            }
+        }
+        encoded
+    }
+
+    fn decode_path(encoded: &str) -> Option<PathBuf> {
+        let mut bytes = Vec::new();
+        let mut chars = encoded.bytes();
+        while let Some(byte) = chars.next() {
+            if byte == b'%' {
+                let hex = [chars.next()?, chars.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            } else {
+                bytes.push(byte);
+            }
+        }
+        Some(OsString::from_vec(bytes).into())
+    }
+
+    /// The current local time, in the `YYYY-MM-DDThh:mm:ss` format used by `DeletionDate`.
+    fn deletion_date() -> String {
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        unsafe {
+            let now = libc::time(std::ptr::null_mut());
+            libc::localtime_r(&now, &mut tm);
+        }
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            tm.tm_year + 1900,
+            tm.tm_mon + 1,
+            tm.tm_mday,
+            tm.tm_hour,
+            tm.tm_min,
+            tm.tm_sec
+        )
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use tempfile::TempDir;
+
+        #[test]
+        fn test_encode_path() {
+            let path = Path::new("/home/user/a file%.txt");
+            assert_eq!(encode_path(path), "/home/user/a%20file%25.txt");
+            assert_eq!(decode_path(&encode_path(path)).unwrap(), path);
+        }
+
+        #[test]
+        fn test_trash_and_restore() {
+            let dir = TempDir::new().unwrap();
+            let trash_dir = TrashDir {
+                root: dir.path().join("Trash"),
+                top_dir: None,
+            };
+            let file_path = dir.path().join("project/a.txt");
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+
+            fs::write(&file_path, "one").unwrap();
+            trash_in(&trash_dir, &file_path).unwrap();
+            fs::write(&file_path, "two").unwrap();
+            trash_in(&trash_dir, &file_path).unwrap();
+            assert!(!file_path.exists());
+            assert!(trash_dir.files().join("a.txt").exists());
+            assert!(trash_dir.files().join("a.txt.2").exists());
+
+            let info = fs::read_to_string(trash_dir.info_path(OsStr::new("a.txt"))).unwrap();
+            let (trashed_path, _) = parse_trash_info(&info).unwrap();
+            assert_eq!(trashed_path, file_path);
+
+            restore_from(&trash_dir, &file_path).unwrap();
+            assert_eq!(fs::read_to_string(&file_path).unwrap(), "two");
+            assert!(restore_from(&trash_dir, &file_path).is_err());
+
+            fs::remove_file(&file_path).unwrap();
+            restore_from(&trash_dir, &file_path).unwrap();
+            assert_eq!(fs::read_to_string(&file_path).unwrap(), "one");
+            assert!(restore_from(&trash_dir, &file_path).is_err());
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use anyhow::{anyhow, Context as _, Result};
+    use parking_lot::Mutex;
+    use std::path::{Path, PathBuf};
+
+    lazy_static::lazy_static! {
+        /// The original and trashed paths of the items trashed by this process.
+        static ref TRASHED_PATHS: Mutex<Vec<(PathBuf, PathBuf)>> = Default::default();
+    }
+
+    pub(crate) fn trash(path: &Path) -> Result<()> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| anyhow!("cannot move {path:?} to the trash"))?;
+        let trash_dir = util::paths::HOME.join(".Trash");
+        let mut trashed_path = trash_dir.join(file_name);
+        let mut ix = 2;
+        while trashed_path.symlink_metadata().is_ok() {
+            let mut name = file_name.to_os_string();
+            name.push(format!(" {ix}"));
+            trashed_path = trash_dir.join(name);
+            ix += 1;
+        }
+        std::fs::rename(path, &trashed_path)
+            .with_context(|| format!("moving {path:?} to the trash"))?;
+        TRASHED_PATHS
+            .lock()
+            .push((path.to_path_buf(), trashed_path));
+        Ok(())
+    }
+
+    pub(crate) fn restore(path: &Path) -> Result<()> {
+        if path.symlink_metadata().is_ok() {
+            return Err(anyhow!("{path:?} already exists"));
+        }
+        let mut trashed_paths = TRASHED_PATHS.lock();
+        let ix = trashed_paths
+            .iter()
+            .rposition(|(original_path, _)| original_path == path)
+            .ok_or_else(|| anyhow!("{path:?} is not in the trash"))?;
+        std::fs::rename(&trashed_paths[ix].1, path)
+            .with_context(|| format!("restoring {path:?} from the trash"))?;
+        trashed_paths.remove(ix);
+        Ok(())
+    }
+}
+
+// todo!(windows)
+#[cfg(target_os = "windows")]
+mod windows {
+    use anyhow::{anyhow, Result};
+    use std::path::Path;
+
+    pub(crate) fn trash(path: &Path) -> Result<()> {
+        Err(anyhow!(
+            "cannot move {path:?} to the trash on this platform"
+        ))
+    }
+
+    pub(crate) fn restore(path: &Path) -> Result<()> {
+        Err(anyhow!(
+            "cannot restore {path:?} from the trash on this platform"
+        ))
+    }
+}
@@ -0,0 +1,224 @@
+use anyhow::{anyhow, Context, Result};
+use collections::HashMap;
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use text::Rope;
+use time::{OffsetDateTime, UtcOffset};
+
+/// The blame of a file, as computed by `git blame`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Blame {
+    /// The blamed hunks of the file, ordered by row.
+    pub entries: Vec<BlameEntry>,
+}
+
+/// A range of consecutive lines that were last changed by the same commit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlameEntry {
+    pub sha: String,
+    /// The zero-based rows of this entry in the blamed contents.
+    pub range: Range<u32>,
+    /// The one-based line number of the first line of this entry in the commit.
+    pub original_line_number: u32,
+    pub author: Option<String>,
+    pub author_mail: Option<String>,
+    /// The author time, as a Unix timestamp.
+    pub author_time: Option<i64>,
+    /// The author timezone, e.g. `+0100`.
+    pub author_tz: Option<String>,
+    pub summary: Option<String>,
+}
+
+impl Blame {
+    /// Blames the file at `path`, relative to `working_directory`, with the given
+    /// contents. Lines that differ from the committed file are blamed on a commit
+    /// whose SHA is all zeros.
+    pub fn for_path(working_directory: &Path, path: &Path, contents: &Rope) -> Result<Self> {
+        let mut child = Command::new("git")
+            .current_dir(working_directory)
+            .arg("blame")
+            .arg("--incremental")
+            .arg("--contents")
+            .arg("-")
+            .arg("--")
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("failed to start git blame")?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .context("failed to get stdin of git blame")?;
+        for chunk in contents.chunks() {
+            stdin.write_all(chunk.as_bytes())?;
+        }
+        stdin.flush()?;
+        drop(stdin);
+
+        let output = child
+            .wait_with_output()
+            .context("failed to read output of git blame")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("git blame failed: {}", stderr.trim()));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut entries = parse_git_blame(&stdout)?;
+        entries.sort_unstable_by_key(|entry| entry.range.start);
+        Ok(Self { entries })
+    }
+}
+
+impl BlameEntry {
+    /// Returns whether the lines of this entry have not been committed yet.
+    pub fn is_uncommitted(&self) -> bool {
+        self.sha.chars().all(|c| c == '0')
+    }
+
+    /// Returns the author time of this entry in the author's timezone.
+    pub fn author_offset_date_time(&self) -> Result<OffsetDateTime> {
+        let timestamp = self.author_time.context("missing author time")?;
+        let date_time = OffsetDateTime::from_unix_timestamp(timestamp)?;
+        let offset = match self.author_tz.as_deref() {
+            Some(tz) => parse_timezone(tz)?,
+            None => UtcOffset::UTC,
+        };
+        Ok(date_time.to_offset(offset))
+    }
+
+    // Parses the header line of an entry, `<sha> <original line> <final line> <lines>`.
+    fn new_from_header_line(line: &str) -> Result<Self> {
+        let mut parts = line.split_whitespace();
+        let sha = parts.next().context("missing sha")?.to_string();
+        let original_line_number = parts.next().context("missing original line")?.parse()?;
+        let final_line_number: u32 = parts.next().context("missing final line")?.parse()?;
+        let line_count: u32 = parts.next().context("missing line count")?.parse()?;
+        let start = final_line_number.saturating_sub(1);
+        Ok(Self {
+            sha,
+            range: start..start + line_count,
+            original_line_number,
+            ..Default::default()
+        })
+    }
+}
+
+fn parse_timezone(tz: &str) -> Result<UtcOffset> {
+    let (sign, digits) = match tz.split_at(1) {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return Err(anyhow!("invalid timezone {tz:?}")),
+    };
+    if digits.len() != 4 {
+        return Err(anyhow!("invalid timezone {tz:?}"));
+    }
+    let hours: i8 = digits[..2].parse()?;
+    let minutes: i8 = digits[2..].parse()?;
+    Ok(UtcOffset::from_hms(sign * hours, sign * minutes, 0)?)
+}
+
+/// Parses the output of `git blame --incremental`.
+///
+/// In the incremental format, the header fields of a commit are only printed the
+/// first time that commit is blamed, so later entries copy them from the first one.
+fn parse_git_blame(output: &str) -> Result<Vec<BlameEntry>> {
+    let mut entries = Vec::new();
+    let mut commits: HashMap<String, BlameEntry> = HashMap::default();
+    let mut current_entry: Option<BlameEntry> = None;
+
+    for line in output.lines() {
+        let Some(entry) = current_entry.as_mut() else {
+            if !line.is_empty() {
+                current_entry = Some(BlameEntry::new_from_header_line(line)?);
+            }
+            continue;
+        };
+
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "author" => entry.author = Some(value.to_string()),
+            "author-mail" => entry.author_mail = Some(value.to_string()),
+            "author-time" => entry.author_time = Some(value.parse()?),
+            "author-tz" => entry.author_tz = Some(value.to_string()),
+            "summary" => entry.summary = Some(value.to_string()),
+            // The filename line ends each entry.
+            "filename" => {
+                let mut entry = current_entry.take().unwrap();
+                if let Some(commit) = commits.get(&entry.sha) {
+                    entry.author = entry.author.or_else(|| commit.author.clone());
+                    entry.author_mail = entry.author_mail.or_else(|| commit.author_mail.clone());
+                    entry.author_time = entry.author_time.or(commit.author_time);
+                    entry.author_tz = entry.author_tz.or_else(|| commit.author_tz.clone());
+                    entry.summary = entry.summary.or_else(|| commit.summary.clone());
+                } else {
+                    commits.insert(entry.sha.clone(), entry.clone());
+                }
+                entries.push(entry);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_blame() {
+        let output = "\
+1b1a1e4b0e2d3c4f5a6b7c8d9e0f1a2b3c4d5e6f 3 3 2
+author Alice
+author-mail <alice@example.com>
+author-time 1700000000
+author-tz +0100
+committer Alice
+committer-mail <alice@example.com>
+committer-time 1700000000
+committer-tz +0100
+summary Add the greeting
+boundary
+filename src/main.rs
+0000000000000000000000000000000000000000 2 2 1
+author Not Committed Yet
+author-mail <not.committed.yet>
+author-time 1710000000
+author-tz +0000
+committer Not Committed Yet
+committer-mail <not.committed.yet>
+committer-time 1710000000
+committer-tz +0000
+summary Version of src/main.rs from -
+previous 1b1a1e4b0e2d3c4f5a6b7c8d9e0f1a2b3c4d5e6f src/main.rs
+filename src/main.rs
+1b1a1e4b0e2d3c4f5a6b7c8d9e0f1a2b3c4d5e6f 1 1 1
+previous 1b1a1e4b0e2d3c4f5a6b7c8d9e0f1a2b3c4d5e6f src/main.rs
+filename src/main.rs
+";
+        let mut entries = parse_git_blame(output).unwrap();
+        entries.sort_unstable_by_key(|entry| entry.range.start);
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.range.clone(), entry.is_uncommitted()))
+                .collect::<Vec<_>>(),
+            [(0..1, false), (1..2, true), (2..4, false)]
+        );
+        assert_eq!(entries[0].author.as_deref(), Some("Alice"));
+        assert_eq!(entries[0].summary.as_deref(), Some("Add the greeting"));
+        assert_eq!(entries[0].original_line_number, 1);
+
+        let date_time = entries[2].author_offset_date_time().unwrap();
+        assert_eq!(date_time.offset(), UtcOffset::from_hms(1, 0, 0).unwrap());
+        assert_eq!(date_time.unix_timestamp(), 1700000000);
+    }
+}
@@ -170,7 +170,13 @@ impl BufferDiff {
         if let Some(patch) = patch {
             let mut divergence = 0;
             for hunk_index in 0..patch.num_hunks() {
-                let hunk = Self::process_patch_hunk(&patch, hunk_index, buffer, &mut divergence);
+                let hunk = Self::process_patch_hunk(
+                    &patch,
+                    hunk_index,
+                    diff_base,
+                    buffer,
+                    &mut divergence,
+                );
                 tree.push(hunk, buffer);
             }
         }
@@ -211,6 +217,7 @@ impl BufferDiff {
     fn process_patch_hunk<'a>(
         patch: &GitPatch<'a>,
         hunk_index: usize,
+        diff_base: &str,
         buffer: &text::BufferSnapshot,
         buffer_row_divergence: &mut i64,
     ) -> DiffHunk<Anchor> {
@@ -262,8 +269,13 @@ impl BufferDiff {
             row..row
         });
 
-        //unwrap_or addition without deletion
-        let diff_base_byte_range = diff_base_byte_range.unwrap_or(0..0);
+        //unwrap_or addition without deletion, whose lines are inserted after the
+        //hunk's old start line
+        let diff_base_byte_range = diff_base_byte_range.unwrap_or_else(|| {
+            let (hunk, _) = patch.hunk(hunk_index).unwrap();
+            let offset = line_start_offset(diff_base, hunk.old_start());
+            offset..offset
+        });
 
         let start = Point::new(buffer_row_range.start, 0);
         let end = Point::new(buffer_row_range.end, 0);
@@ -275,6 +287,111 @@ impl BufferDiff {
     }
 }
 
+/// Returns the diff base with the given hunks replaced by their text in the buffer.
+///
+/// This is how hunks are staged, when the diff base is the index text.
+pub fn apply_hunks_to_diff_base(
+    diff_base: &str,
+    buffer: &BufferSnapshot,
+    hunks: impl IntoIterator<Item = DiffHunk<u32>>,
+) -> String {
+    let mut hunks = hunks.into_iter().collect::<Vec<_>>();
+    hunks.sort_by_key(|hunk| hunk.diff_base_byte_range.start);
+
+    let mut text = String::new();
+    let mut offset = 0;
+    for hunk in hunks {
+        if hunk.diff_base_byte_range.start < offset {
+            continue;
+        }
+        text.push_str(&diff_base[offset..hunk.diff_base_byte_range.start]);
+        text.extend(buffer.text_for_range(buffer_range_for_rows(&hunk.buffer_range, buffer)));
+        offset = hunk.diff_base_byte_range.end;
+    }
+    text.push_str(&diff_base[offset..]);
+    text
+}
+
+/// Returns the text of the buffer with the given hunks replaced by their text in the
+/// diff base.
+///
+/// This is how staged hunks are unstaged, when the buffer holds the index text and
+/// the diff base is the text in HEAD.
+pub fn revert_hunks_to_diff_base(
+    diff_base: &str,
+    buffer: &BufferSnapshot,
+    hunks: impl IntoIterator<Item = DiffHunk<u32>>,
+) -> String {
+    let mut hunks = hunks.into_iter().collect::<Vec<_>>();
+    hunks.sort_by_key(|hunk| hunk.buffer_range.start);
+
+    let mut text = String::new();
+    let mut offset = 0;
+    for hunk in hunks {
+        let range = buffer_range_for_rows(&hunk.buffer_range, buffer).to_offset(buffer);
+        if range.start < offset {
+            continue;
+        }
+        text.extend(buffer.text_for_range(offset..range.start));
+        text.push_str(&diff_base[hunk.diff_base_byte_range]);
+        offset = range.end;
+    }
+    text.extend(buffer.text_for_range(offset..buffer.len()));
+    text
+}
+
+/// Returns the row of the diff base that corresponds to the given buffer row.
+///
+/// Rows inside of a hunk correspond to the first row of the hunk in the diff base.
+pub fn diff_base_row_for_buffer_row(
+    buffer_row: u32,
+    diff_base: &str,
+    hunks: impl IntoIterator<Item = DiffHunk<u32>>,
+) -> u32 {
+    let mut diff_base_row = buffer_row;
+    for hunk in hunks {
+        if hunk.buffer_range.start > buffer_row {
+            break;
+        }
+
+        let start_row = diff_base[..hunk.diff_base_byte_range.start]
+            .matches('\n')
+            .count() as u32;
+        if buffer_row < hunk.buffer_range.end {
+            return start_row;
+        }
+        let end_row = start_row
+            + diff_base[hunk.diff_base_byte_range.clone()]
+                .matches('\n')
+                .count() as u32;
+        diff_base_row = end_row + (buffer_row - hunk.buffer_range.end);
+    }
+    diff_base_row
+}
+
+/// Returns the range of the buffer spanned by the given rows, where the end row is
+/// exclusive and may be past the last row of the buffer.
+fn buffer_range_for_rows(rows: &Range<u32>, buffer: &BufferSnapshot) -> Range<Point> {
+    let max_point = buffer.max_point();
+    let point_for_row = |row: u32| {
+        if row > max_point.row {
+            max_point
+        } else {
+            Point::new(row, 0)
+        }
+    };
+    point_for_row(rows.start)..point_for_row(rows.end)
+}
+
+fn line_start_offset(text: &str, row: u32) -> usize {
+    if row == 0 {
+        return 0;
+    }
+    text.match_indices('\n')
+        .nth(row as usize - 1)
+        .map_or(text.len(), |(ix, _)| ix + 1)
+}
+
 /// Range (crossing new lines), old, new
 #[cfg(any(test, feature = "test-support"))]
 #[track_caller]
@@ -409,4 +526,61 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_apply_and_revert_hunks() {
+        let diff_base = "
+            one
+            two
+            three
+            four
+        "
+        .unindent();
+
+        let buffer_text = "
+            zero
+            one
+            TWO
+            three
+        "
+        .unindent();
+
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), buffer_text.clone());
+        let mut diff = BufferDiff::new();
+        smol::block_on(diff.update(&diff_base, &buffer));
+        let hunks = diff.hunks(&buffer).collect::<Vec<_>>();
+        assert_hunks(
+            hunks.iter().cloned(),
+            &buffer,
+            &diff_base,
+            &[
+                (0..1, "", "zero\n"),
+                (2..3, "two\n", "TWO\n"),
+                (4..4, "four\n", ""),
+            ],
+        );
+        assert_eq!(hunks[0].diff_base_byte_range, 0..0);
+
+        assert_eq!(
+            apply_hunks_to_diff_base(&diff_base, &buffer, hunks.iter().cloned()),
+            buffer_text
+        );
+        assert_eq!(
+            apply_hunks_to_diff_base(&diff_base, &buffer, [hunks[1].clone()]),
+            "one\nTWO\nthree\nfour\n"
+        );
+        assert_eq!(
+            revert_hunks_to_diff_base(&diff_base, &buffer, hunks.iter().cloned()),
+            diff_base
+        );
+        assert_eq!(
+            revert_hunks_to_diff_base(&diff_base, &buffer, [hunks[0].clone()]),
+            "one\nTWO\nthree\n"
+        );
+
+        let diff_base_rows = (0..5)
+            .map(|row| diff_base_row_for_buffer_row(row, &diff_base, hunks.iter().cloned()))
+            .collect::<Vec<_>>();
+        assert_eq!(diff_base_rows, [0, 0, 1, 2, 4]);
+    }
 }
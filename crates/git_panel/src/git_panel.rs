@@ -0,0 +1,862 @@
+mod git_panel_settings;
+
+use anyhow::{anyhow, Result};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{Editor, EditorEvent};
+use git_panel_settings::GitPanelSettings;
+use gpui::{
+    actions, uniform_list, Action, AppContext, AsyncWindowContext, EventEmitter, FocusHandle,
+    FocusableView, InteractiveElement, KeyContext, Model, ParentElement, Pixels, Render,
+    SharedString, Styled, Subscription, Task, UniformListScrollHandle, View, ViewContext, WeakView,
+    WindowContext,
+};
+use menu::{Confirm, SelectNext, SelectPrev};
+use parking_lot::Mutex;
+use project::{
+    repository::{GitFileStatus, GitRepository, RepoPath},
+    Fs, LocalSnapshot, Project, ProjectEntryId, ProjectPath, WorktreeId,
+};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::{path::Path, sync::Arc};
+use ui::{prelude::*, Checkbox, CheckboxWithLabel, ListItem};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+const GIT_PANEL_KEY: &'static str = "GitPanel";
+
+actions!(
+    git_panel,
+    [
+        ToggleFocus,
+        ToggleStaged,
+        StageAll,
+        UnstageAll,
+        ToggleAmend,
+        Commit,
+        Push,
+    ]
+);
+
+pub fn init_settings(cx: &mut AppContext) {
+    GitPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut AppContext) {
+    init_settings(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<GitPanel>(cx);
+        });
+    })
+    .detach();
+}
+
+/// The git repository whose changes are shown in the panel.
+#[derive(Clone)]
+struct Repository {
+    worktree_id: WorktreeId,
+    work_directory_id: ProjectEntryId,
+    /// The path of the work directory, relative to the root of the worktree.
+    work_directory: Arc<Path>,
+    branch: Option<Arc<str>>,
+    git_repo: Arc<Mutex<dyn GitRepository>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct StatusEntry {
+    repo_path: RepoPath,
+    status: GitFileStatus,
+    staged: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ListEntry {
+    Header { staged: bool },
+    Status(usize),
+}
+
+pub struct GitPanel {
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    list: UniformListScrollHandle,
+    commit_editor: View<Editor>,
+    repository: Option<Repository>,
+    /// The staged entries, followed by the unstaged ones.
+    statuses: Vec<StatusEntry>,
+    list_entries: Vec<ListEntry>,
+    selected_entry: Option<usize>,
+    amend: bool,
+    error: Option<SharedString>,
+    pending_statuses: Task<Option<()>>,
+    pending_operation: Option<Task<()>>,
+    width: Option<Pixels>,
+    pending_serialization: Task<Option<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedGitPanel {
+    width: Option<Pixels>,
+}
+
+impl GitPanel {
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let language_registry = workspace.app_state().languages.clone();
+        let workspace = workspace.weak_handle();
+        cx.new_view(|cx| {
+            let commit_editor = cx.new_view(|cx| {
+                let mut editor = Editor::auto_height(10, cx);
+                editor.set_placeholder_text("Commit message", cx);
+                editor
+            });
+            if let Some(buffer) = commit_editor.read(cx).buffer().read(cx).as_singleton() {
+                let git_commit = language_registry.language_for_name("Git Commit");
+                cx.spawn(|_, mut cx| async move {
+                    let git_commit = git_commit.await?;
+                    buffer.update(&mut cx, |buffer, cx| {
+                        buffer.set_language(Some(git_commit), cx)
+                    })
+                })
+                .detach_and_log_err(cx);
+            }
+
+            let subscriptions = vec![
+                cx.subscribe(&project, |this, _, event, cx| match event {
+                    project::Event::WorktreeAdded
+                    | project::Event::WorktreeRemoved(_)
+                    | project::Event::WorktreeUpdatedEntries(_, _)
+                    | project::Event::WorktreeUpdatedGitRepositories
+                    | project::Event::ActiveEntryChanged(_) => this.update_statuses(cx),
+                    _ => {}
+                }),
+                cx.subscribe(&commit_editor, |this, _, event, cx| {
+                    if let EditorEvent::BufferEdited = event {
+                        if this.error.take().is_some() {
+                            cx.notify();
+                        }
+                    }
+                }),
+            ];
+
+            let mut this = Self {
+                project,
+                workspace,
+                fs,
+                focus_handle: cx.focus_handle(),
+                list: UniformListScrollHandle::new(),
+                commit_editor,
+                repository: None,
+                statuses: Vec::new(),
+                list_entries: Vec::new(),
+                selected_entry: None,
+                amend: false,
+                error: None,
+                pending_statuses: Task::ready(None),
+                pending_operation: None,
+                width: None,
+                pending_serialization: Task::ready(None),
+                _subscriptions: subscriptions,
+            };
+            this.update_statuses(cx);
+            this
+        })
+    }
+
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(GIT_PANEL_KEY) })
+            .await
+            .map_err(|e| anyhow!("Failed to load git panel: {}", e))
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedGitPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = GitPanel::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width;
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        GIT_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedGitPanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Returns the repository containing the active entry of the project, or the
+    /// first repository of the project if the active entry isn't in one.
+    fn active_repository(&self, cx: &AppContext) -> Option<(Repository, LocalSnapshot)> {
+        let project = self.project.read(cx);
+        let active_path = project
+            .active_entry()
+            .and_then(|entry_id| project.path_for_entry(entry_id, cx));
+        let mut first_repository = None;
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            let Some(local_worktree) = worktree.as_local() else {
+                continue;
+            };
+            let snapshot = local_worktree.snapshot();
+            let repository_entry = match &active_path {
+                Some(active_path) if active_path.worktree_id == worktree.id() => snapshot
+                    .repository_and_work_directory_for_path(&active_path.path)
+                    .map(|(_, repository_entry)| repository_entry),
+                _ => None,
+            };
+            let is_active = repository_entry.is_some();
+            let Some(repository_entry) = repository_entry.or_else(|| {
+                snapshot
+                    .repositories()
+                    .next()
+                    .map(|(_, repository_entry)| repository_entry.clone())
+            }) else {
+                continue;
+            };
+            let Some(work_directory) = repository_entry.work_directory(&snapshot) else {
+                continue;
+            };
+            let Some(local_repository) = snapshot.get_local_repo(&repository_entry) else {
+                continue;
+            };
+            let repository = Repository {
+                worktree_id: worktree.id(),
+                work_directory_id: repository_entry.work_directory_id(),
+                work_directory: work_directory.as_ref().into(),
+                branch: repository_entry.branch(),
+                git_repo: local_repository.repo().clone(),
+            };
+            if is_active {
+                return Some((repository, snapshot));
+            }
+            first_repository.get_or_insert((repository, snapshot));
+        }
+        first_repository
+    }
+
+    fn update_statuses(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((repository, snapshot)) = self.active_repository(cx) else {
+            self.repository = None;
+            self.statuses.clear();
+            self.update_list_entries(cx);
+            return;
+        };
+
+        self.repository = Some(repository.clone());
+        let statuses = cx
+            .background_executor()
+            .spawn(async move { repository_statuses(&repository, &snapshot) });
+        self.pending_statuses = cx.spawn(|this, mut cx| async move {
+            let statuses = statuses.await;
+            this.update(&mut cx, |this, cx| {
+                this.statuses = statuses;
+                this.update_list_entries(cx);
+            })
+            .ok()
+        });
+        cx.notify();
+    }
+
+    fn update_list_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let selected_status =
+            self.selected_entry
+                .and_then(|ix| match self.list_entries.get(ix)? {
+                    ListEntry::Status(status_ix) => self.statuses.get(*status_ix).cloned(),
+                    ListEntry::Header { .. } => None,
+                });
+
+        self.list_entries.clear();
+        let staged_count = self.staged_count();
+        if staged_count > 0 {
+            self.list_entries.push(ListEntry::Header { staged: true });
+            self.list_entries
+                .extend((0..staged_count).map(ListEntry::Status));
+        }
+        if staged_count < self.statuses.len() {
+            self.list_entries.push(ListEntry::Header { staged: false });
+            self.list_entries
+                .extend((staged_count..self.statuses.len()).map(ListEntry::Status));
+        }
+
+        // Keep the same status selected, or the entry at the same position if it went away.
+        self.selected_entry = selected_status
+            .and_then(|selected_status| {
+                self.list_entries.iter().position(|entry| match entry {
+                    ListEntry::Status(ix) => {
+                        self.statuses[*ix].repo_path == selected_status.repo_path
+                            && self.statuses[*ix].staged == selected_status.staged
+                    }
+                    ListEntry::Header { .. } => false,
+                })
+            })
+            .or_else(|| {
+                let ix = self.selected_entry?;
+                self.list_entries
+                    .len()
+                    .checked_sub(1)
+                    .map(|last_ix| ix.min(last_ix))
+            });
+        cx.notify();
+    }
+
+    fn staged_count(&self) -> usize {
+        self.statuses.partition_point(|status| status.staged)
+    }
+
+    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
+        let next_ix = self.selected_entry.map_or(0, |ix| ix + 1);
+        if next_ix < self.list_entries.len() {
+            self.select(next_ix, cx);
+        }
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
+        let prev_ix = self.selected_entry.map_or(0, |ix| ix.saturating_sub(1));
+        if prev_ix < self.list_entries.len() {
+            self.select(prev_ix, cx);
+        }
+    }
+
+    fn select(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        self.selected_entry = Some(ix);
+        self.list.scroll_to_item(ix);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.selected_entry {
+            self.open_entry(ix, cx);
+        }
+    }
+
+    fn toggle_staged(&mut self, _: &ToggleStaged, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.selected_entry {
+            self.toggle_entry_staged(ix, cx);
+        }
+    }
+
+    fn stage_all(&mut self, _: &StageAll, cx: &mut ViewContext<Self>) {
+        let paths = self.statuses[self.staged_count()..]
+            .iter()
+            .map(|status| status.repo_path.clone())
+            .collect();
+        self.set_staged(paths, true, cx);
+    }
+
+    fn unstage_all(&mut self, _: &UnstageAll, cx: &mut ViewContext<Self>) {
+        let paths = self.statuses[..self.staged_count()]
+            .iter()
+            .map(|status| status.repo_path.clone())
+            .collect();
+        self.set_staged(paths, false, cx);
+    }
+
+    /// Stages the file of an unstaged entry, or unstages the file of a staged entry.
+    /// Toggling a header stages or unstages all of the files below it.
+    fn toggle_entry_staged(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        match self.list_entries.get(ix) {
+            Some(ListEntry::Status(status_ix)) => {
+                let status = &self.statuses[*status_ix];
+                self.set_staged(vec![status.repo_path.clone()], !status.staged, cx);
+            }
+            Some(ListEntry::Header { staged: true }) => self.unstage_all(&UnstageAll, cx),
+            Some(ListEntry::Header { staged: false }) => self.stage_all(&StageAll, cx),
+            None => {}
+        }
+    }
+
+    fn set_staged(&mut self, paths: Vec<RepoPath>, staged: bool, cx: &mut ViewContext<Self>) {
+        if paths.is_empty() {
+            return;
+        }
+        self.run_operation(
+            move |git_repo| {
+                if staged {
+                    git_repo.stage_paths(&paths)
+                } else {
+                    git_repo.unstage_paths(&paths)
+                }
+            },
+            |_, _| {},
+            cx,
+        );
+    }
+
+    fn toggle_amend(&mut self, _: &ToggleAmend, cx: &mut ViewContext<Self>) {
+        self.amend = !self.amend;
+        cx.notify();
+
+        // Start from the message of the amended commit, unless one was written already.
+        if !self.amend || !self.commit_editor.read(cx).text(cx).trim().is_empty() {
+            return;
+        }
+        let Some(repository) = self.repository.clone() else {
+            return;
+        };
+        let message = cx
+            .background_executor()
+            .spawn(async move { repository.git_repo.lock().head_commit_message() });
+        cx.spawn(|this, mut cx| async move {
+            if let Some(message) = message.await {
+                this.update(&mut cx, |this, cx| {
+                    this.commit_editor
+                        .update(cx, |editor, cx| editor.set_text(message.trim_end(), cx));
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+
+    fn commit(&mut self, _: &Commit, cx: &mut ViewContext<Self>) {
+        let message = commit_message(&self.commit_editor.read(cx).text(cx));
+        if message.is_empty() {
+            self.error = Some("The commit message is empty".into());
+            cx.notify();
+            return;
+        }
+        if !self.amend && self.staged_count() == 0 {
+            self.error = Some("There are no staged changes to commit".into());
+            cx.notify();
+            return;
+        }
+
+        let amend = self.amend;
+        self.run_operation(
+            move |git_repo| {
+                git_repo.commit(&message, amend)?;
+                Ok(())
+            },
+            |this, cx| {
+                this.amend = false;
+                this.commit_editor.update(cx, |editor, cx| editor.clear(cx));
+            },
+            cx,
+        );
+    }
+
+    fn push(&mut self, _: &Push, cx: &mut ViewContext<Self>) {
+        let remote_name = GitPanelSettings::get_global(cx).default_remote.clone();
+        self.run_operation(move |git_repo| git_repo.push(&remote_name), |_, _| {}, cx);
+    }
+
+    /// Runs the given operation on the repository in the background, showing its error
+    /// if it fails. Only one operation runs at a time.
+    fn run_operation(
+        &mut self,
+        operation: impl 'static + Send + FnOnce(&dyn GitRepository) -> Result<()>,
+        on_success: impl 'static + FnOnce(&mut Self, &mut ViewContext<Self>),
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.pending_operation.is_some() {
+            return;
+        }
+        let Some(repository) = self.repository.clone() else {
+            return;
+        };
+
+        let result = cx
+            .background_executor()
+            .spawn(async move { operation(&*repository.git_repo.lock()) });
+        self.error = None;
+        self.pending_operation = Some(cx.spawn(|this, mut cx| async move {
+            let result = result.await;
+            this.update(&mut cx, |this, cx| {
+                this.pending_operation = None;
+                match result {
+                    Ok(()) => on_success(this, cx),
+                    Err(error) => this.error = Some(format!("{error:#}").into()),
+                }
+                this.update_statuses(cx);
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn open_entry(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let (Some(ListEntry::Status(status_ix)), Some(repository)) =
+            (self.list_entries.get(ix), self.repository.as_ref())
+        else {
+            return;
+        };
+        let project_path = ProjectPath {
+            worktree_id: repository.worktree_id,
+            path: repository
+                .work_directory
+                .join(&self.statuses[*status_ix].repo_path.0)
+                .into(),
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_path(project_path, None, true, cx)
+                    .detach_and_log_err(cx);
+            })
+            .ok();
+    }
+
+    fn dispatch_context(&self, cx: &ViewContext<Self>) -> KeyContext {
+        let mut dispatch_context = KeyContext::default();
+        dispatch_context.add("GitPanel");
+        dispatch_context.add("menu");
+
+        let identifier = if self.commit_editor.focus_handle(cx).is_focused(cx) {
+            "editing"
+        } else {
+            "not_editing"
+        };
+
+        dispatch_context.add(identifier);
+        dispatch_context
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let branch = self
+            .repository
+            .as_ref()
+            .and_then(|repository| repository.branch.clone());
+        h_flex()
+            .p_1()
+            .gap_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(Icon::new(IconName::FileGit).color(Color::Muted))
+                    .child(Label::new(
+                        branch.map_or("Detached HEAD".into(), |branch| {
+                            SharedString::from(branch.to_string())
+                        }),
+                    )),
+            )
+            .child(
+                Button::new("push", "Push")
+                    .disabled(self.pending_operation.is_some())
+                    .on_click(cx.listener(|this, _, cx| this.push(&Push, cx))),
+            )
+    }
+
+    fn render_commit_box(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .p_2()
+            .gap_2()
+            .border_t_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                div()
+                    .p_1()
+                    .border_1()
+                    .rounded_md()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.commit_editor.clone()),
+            )
+            .children(
+                self.error
+                    .clone()
+                    .map(|error| Label::new(error).size(LabelSize::Small).color(Color::Error)),
+            )
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(CheckboxWithLabel::new(
+                        "amend",
+                        Label::new("Amend").size(LabelSize::Small),
+                        if self.amend {
+                            Selection::Selected
+                        } else {
+                            Selection::Unselected
+                        },
+                        cx.listener(|this, _, cx| this.toggle_amend(&ToggleAmend, cx)),
+                    ))
+                    .child(
+                        Button::new("commit", if self.amend { "Amend" } else { "Commit" })
+                            .style(ButtonStyle::Filled)
+                            .disabled(self.pending_operation.is_some())
+                            .on_click(cx.listener(|this, _, cx| this.commit(&Commit, cx))),
+                    ),
+            )
+    }
+
+    fn render_list_entry(&self, ix: usize, cx: &mut ViewContext<Self>) -> ListItem {
+        let selected = self.selected_entry == Some(ix);
+        match self.list_entries[ix] {
+            ListEntry::Header { staged } => {
+                let (label, count) = if staged {
+                    ("Staged Changes", self.staged_count())
+                } else {
+                    ("Changes", self.statuses.len() - self.staged_count())
+                };
+                ListItem::new(ix)
+                    .selected(selected)
+                    .start_slot(
+                        Checkbox::new(
+                            ("header-staged", ix),
+                            if staged {
+                                Selection::Selected
+                            } else {
+                                Selection::Unselected
+                            },
+                        )
+                        .on_click(cx.listener(move |this, _, cx| this.toggle_entry_staged(ix, cx))),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(label))
+                            .child(Label::new(count.to_string()).color(Color::Muted)),
+                    )
+                    .on_click(cx.listener(move |this, _, cx| this.select(ix, cx)))
+            }
+            ListEntry::Status(status_ix) => {
+                let status = &self.statuses[status_ix];
+                let file_name = status
+                    .repo_path
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let directory = status
+                    .repo_path
+                    .parent()
+                    .map(|parent| parent.to_string_lossy().to_string())
+                    .filter(|parent| !parent.is_empty());
+                let color = match status.status {
+                    GitFileStatus::Added => Color::Created,
+                    GitFileStatus::Modified => Color::Modified,
+                    GitFileStatus::Conflict => Color::Conflict,
+                };
+                ListItem::new(ix)
+                    .indent_level(1)
+                    .selected(selected)
+                    .start_slot(
+                        Checkbox::new(
+                            ("status-staged", ix),
+                            if status.staged {
+                                Selection::Selected
+                            } else {
+                                Selection::Unselected
+                            },
+                        )
+                        .on_click(cx.listener(move |this, _, cx| this.toggle_entry_staged(ix, cx))),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(file_name).color(color))
+                            .children(directory.map(|directory| {
+                                Label::new(directory)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                            })),
+                    )
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.select(ix, cx);
+                        this.open_entry(ix, cx);
+                    }))
+            }
+        }
+    }
+}
+
+/// Returns the staged changes of the repository, followed by its unstaged changes.
+fn repository_statuses(repository: &Repository, snapshot: &LocalSnapshot) -> Vec<StatusEntry> {
+    let git_repo = repository.git_repo.lock();
+    let mut statuses = git_repo
+        .staged_statuses(Path::new(""))
+        .iter()
+        .map(|(repo_path, status)| StatusEntry {
+            repo_path: repo_path.clone(),
+            status: *status,
+            staged: true,
+        })
+        .collect::<Vec<_>>();
+
+    // Only the files that have a git status in the worktree can have unstaged changes.
+    for entry in snapshot.files(false, 0) {
+        if entry.git_status.is_none() {
+            continue;
+        }
+        let Ok(repo_path) = entry.path.strip_prefix(&repository.work_directory) else {
+            continue;
+        };
+        let in_repository = snapshot
+            .repository_for_path(&entry.path)
+            .map_or(false, |repository_entry| {
+                repository_entry.work_directory_id() == repository.work_directory_id
+            });
+        if !in_repository {
+            continue;
+        }
+        let repo_path = RepoPath::from(repo_path);
+        if let Some(status) = git_repo.unstaged_status(&repo_path, entry.mtime) {
+            statuses.push(StatusEntry {
+                repo_path,
+                status,
+                staged: false,
+            });
+        }
+    }
+    statuses
+}
+
+/// Removes the comment lines and surrounding whitespace from a commit message, like
+/// git does by default.
+fn commit_message(text: &str) -> String {
+    let lines = text
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>();
+    let mut message = lines.join("\n").trim().to_string();
+    if !message.is_empty() {
+        message.push('\n');
+    }
+    message
+}
+
+impl Render for GitPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .id("git-panel")
+            .size_full()
+            .key_context(self.dispatch_context(cx))
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::toggle_staged))
+            .on_action(cx.listener(Self::stage_all))
+            .on_action(cx.listener(Self::unstage_all))
+            .on_action(cx.listener(Self::toggle_amend))
+            .on_action(cx.listener(Self::commit))
+            .on_action(cx.listener(Self::push))
+            .child(self.render_header(cx))
+            .child(if self.repository.is_none() {
+                v_flex()
+                    .size_full()
+                    .p_4()
+                    .child(Label::new("No git repository in this project.").color(Color::Muted))
+                    .into_any_element()
+            } else if self.list_entries.is_empty() {
+                v_flex()
+                    .size_full()
+                    .p_4()
+                    .child(Label::new("No changes.").color(Color::Muted))
+                    .into_any_element()
+            } else {
+                uniform_list(
+                    cx.view().clone(),
+                    "git-panel-entries",
+                    self.list_entries.len(),
+                    |this, range, cx| range.map(|ix| this.render_list_entry(ix, cx)).collect(),
+                )
+                .size_full()
+                .track_scroll(self.list.clone())
+                .into_any_element()
+            })
+            .when(self.repository.is_some(), |this| {
+                this.child(self.render_commit_box(cx))
+            })
+    }
+}
+
+impl EventEmitter<PanelEvent> for GitPanel {}
+
+impl Panel for GitPanel {
+    fn persistent_name() -> &'static str {
+        "GitPanel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        GitPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<GitPanelSettings>(self.fs.clone(), cx, move |settings| {
+            settings.dock = Some(position)
+        });
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| GitPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        GitPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::FileGit)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Git Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+impl FocusableView for GitPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_message() {
+        assert_eq!(commit_message(""), "");
+        assert_eq!(commit_message("# Please enter a message\n\n"), "");
+        assert_eq!(
+            commit_message("\nFix the build  \n\n# On branch main\nMore details\n\n"),
+            "Fix the build\n\nMore details\n"
+        );
+    }
+}
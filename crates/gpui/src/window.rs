@@ -1340,6 +1340,7 @@ impl<'a> WindowContext<'a> {
                 self.window.pending_input = Some(currently_pending);
 
                 self.propagate_event = false;
+                self.dispatch_keystroke_observers(event, None);
                 return;
             } else if let Some(currently_pending) = self.window.pending_input.take() {
                 if bindings
@@ -0,0 +1,577 @@
+mod hierarchy_panel_settings;
+
+use anyhow::{anyhow, Result};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    actions, px, uniform_list, Action, AppContext, AsyncWindowContext, EventEmitter, FocusHandle,
+    FocusableView, InteractiveElement, KeyContext, Model, ParentElement, Pixels, Render, Styled,
+    Task, UniformListScrollHandle, View, ViewContext, WeakView, WindowContext,
+};
+use hierarchy_panel_settings::HierarchyPanelSettings;
+use language::{Anchor, Bias, Buffer, PointUtf16, Unclipped};
+use menu::{Confirm, SelectNext, SelectPrev};
+use project::{Fs, HierarchyDirection, HierarchyItem, Project};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::{ops::Range, sync::Arc};
+use ui::{prelude::*, v_flex, Label, ListItem};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+const HIERARCHY_PANEL_KEY: &'static str = "HierarchyPanel";
+
+actions!(
+    hierarchy_panel,
+    [
+        ToggleFocus,
+        ShowIncomingCalls,
+        ShowOutgoingCalls,
+        ShowSupertypes,
+        ShowSubtypes,
+        ExpandSelectedEntry,
+        CollapseSelectedEntry,
+    ]
+);
+
+pub fn init_settings(cx: &mut AppContext) {
+    HierarchyPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut AppContext) {
+    init_settings(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &ToggleFocus, cx| {
+                workspace.toggle_panel_focus::<HierarchyPanel>(cx);
+            })
+            .register_action(|workspace, _: &ShowIncomingCalls, cx| {
+                show_hierarchy(workspace, HierarchyDirection::IncomingCalls, cx);
+            })
+            .register_action(|workspace, _: &ShowOutgoingCalls, cx| {
+                show_hierarchy(workspace, HierarchyDirection::OutgoingCalls, cx);
+            })
+            .register_action(|workspace, _: &ShowSupertypes, cx| {
+                show_hierarchy(workspace, HierarchyDirection::Supertypes, cx);
+            })
+            .register_action(|workspace, _: &ShowSubtypes, cx| {
+                show_hierarchy(workspace, HierarchyDirection::Subtypes, cx);
+            });
+    })
+    .detach();
+}
+
+/// Shows the hierarchy of the function or type under the newest cursor of the active editor.
+fn show_hierarchy(
+    workspace: &mut Workspace,
+    direction: HierarchyDirection,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let editor = editor.read(cx);
+    let head = editor.selections.newest::<usize>(cx).head();
+    let Some((buffer, position)) = editor.buffer().read(cx).text_anchor_for_position(head, cx)
+    else {
+        return;
+    };
+    if let Some(panel) = workspace.focus_panel::<HierarchyPanel>(cx) {
+        panel.update(cx, |panel, cx| panel.show(buffer, position, direction, cx));
+    }
+}
+
+pub struct HierarchyPanel {
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    list: UniformListScrollHandle,
+    direction: HierarchyDirection,
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+    visible_nodes: Vec<usize>,
+    selected_node: Option<usize>,
+    pending_roots: Task<Option<()>>,
+    width: Option<Pixels>,
+    pending_serialization: Task<Option<()>>,
+}
+
+struct Node {
+    item: HierarchyItem,
+    /// Where the calls between this item and its parent are made, see [`project::HierarchyCall`].
+    call_ranges: Vec<Range<Unclipped<PointUtf16>>>,
+    parent: Option<usize>,
+    depth: usize,
+    children: Children,
+    expanded: bool,
+}
+
+/// Children are only requested from the language server once their parent gets expanded.
+enum Children {
+    Unloaded,
+    Loading(Task<Option<()>>),
+    Loaded(Vec<usize>),
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedHierarchyPanel {
+    width: Option<Pixels>,
+}
+
+impl HierarchyPanel {
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        let workspace = workspace.weak_handle();
+        cx.new_view(|cx| Self {
+            project,
+            workspace,
+            fs,
+            focus_handle: cx.focus_handle(),
+            list: UniformListScrollHandle::new(),
+            direction: HierarchyDirection::IncomingCalls,
+            nodes: Vec::new(),
+            roots: Vec::new(),
+            visible_nodes: Vec::new(),
+            selected_node: None,
+            pending_roots: Task::ready(None),
+            width: None,
+            pending_serialization: Task::ready(None),
+        })
+    }
+
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(HIERARCHY_PANEL_KEY) })
+            .await
+            .map_err(|e| anyhow!("Failed to load hierarchy panel: {}", e))
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedHierarchyPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = HierarchyPanel::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width;
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        HIERARCHY_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedHierarchyPanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Replaces the shown hierarchy with the one of the function or type at the given position.
+    pub fn show(
+        &mut self,
+        buffer: Model<Buffer>,
+        position: Anchor,
+        direction: HierarchyDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let roots = self.project.update(cx, |project, cx| match direction {
+            HierarchyDirection::IncomingCalls | HierarchyDirection::OutgoingCalls => {
+                project.prepare_call_hierarchy(&buffer, position, cx)
+            }
+            HierarchyDirection::Supertypes | HierarchyDirection::Subtypes => {
+                project.prepare_type_hierarchy(&buffer, position, cx)
+            }
+        });
+        self.pending_roots = cx.spawn(|this, mut cx| {
+            async move {
+                let roots = roots.await?;
+                this.update(&mut cx, |this, cx| this.set_roots(roots, direction, cx))
+            }
+            .log_err()
+        });
+    }
+
+    /// Shows the neighbours of the current roots in another direction, such as the
+    /// outgoing calls of the functions whose incoming calls are shown.
+    fn set_direction(&mut self, direction: HierarchyDirection, cx: &mut ViewContext<Self>) {
+        if direction == self.direction {
+            return;
+        }
+        let roots = self
+            .roots
+            .iter()
+            .map(|ix| self.nodes[*ix].item.clone())
+            .collect();
+        self.set_roots(roots, direction, cx);
+    }
+
+    fn set_roots(
+        &mut self,
+        roots: Vec<HierarchyItem>,
+        direction: HierarchyDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.direction = direction;
+        self.nodes = roots
+            .into_iter()
+            .map(|item| Node {
+                item,
+                call_ranges: Vec::new(),
+                parent: None,
+                depth: 0,
+                children: Children::Unloaded,
+                expanded: false,
+            })
+            .collect();
+        self.roots = (0..self.nodes.len()).collect();
+        self.selected_node = self.roots.first().copied();
+        for ix in self.roots.clone() {
+            self.expand(ix, cx);
+        }
+        self.update_visible_nodes();
+        cx.notify();
+    }
+
+    fn expand(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        self.nodes[ix].expanded = true;
+        if let Children::Unloaded = self.nodes[ix].children {
+            let direction = self.direction;
+            let children = self.project.update(cx, |project, cx| {
+                project.hierarchy_children(&self.nodes[ix].item, direction, cx)
+            });
+            self.nodes[ix].children = Children::Loading(cx.spawn(|this, mut cx| {
+                async move {
+                    let calls = children.await?;
+                    this.update(&mut cx, |this, cx| {
+                        let depth = this.nodes[ix].depth + 1;
+                        let mut child_ids = Vec::with_capacity(calls.len());
+                        for call in calls {
+                            child_ids.push(this.nodes.len());
+                            this.nodes.push(Node {
+                                item: call.item,
+                                call_ranges: call.ranges,
+                                parent: Some(ix),
+                                depth,
+                                children: Children::Unloaded,
+                                expanded: false,
+                            });
+                        }
+                        this.nodes[ix].children = Children::Loaded(child_ids);
+                        this.update_visible_nodes();
+                        cx.notify();
+                    })
+                }
+                .log_err()
+            }));
+        }
+        self.update_visible_nodes();
+        cx.notify();
+    }
+
+    fn collapse(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        self.nodes[ix].expanded = false;
+        self.update_visible_nodes();
+        cx.notify();
+    }
+
+    fn toggle_expanded(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if self.nodes[ix].expanded {
+            self.collapse(ix, cx);
+        } else {
+            self.expand(ix, cx);
+        }
+    }
+
+    fn update_visible_nodes(&mut self) {
+        self.visible_nodes.clear();
+        let mut stack = self.roots.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(ix) = stack.pop() {
+            self.visible_nodes.push(ix);
+            let node = &self.nodes[ix];
+            if let (true, Children::Loaded(children)) = (node.expanded, &node.children) {
+                stack.extend(children.iter().rev());
+            }
+        }
+        if let Some(selected_node) = self.selected_node {
+            if !self.visible_nodes.contains(&selected_node) {
+                self.selected_node = self.nodes[selected_node].parent;
+            }
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
+        let next_position = self.selected_position().map_or(0, |position| position + 1);
+        if let Some(ix) = self.visible_nodes.get(next_position) {
+            self.select(*ix, cx);
+        }
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
+        let prev_position = self
+            .selected_position()
+            .map_or(0, |position| position.saturating_sub(1));
+        if let Some(ix) = self.visible_nodes.get(prev_position) {
+            self.select(*ix, cx);
+        }
+    }
+
+    fn expand_selected_entry(&mut self, _: &ExpandSelectedEntry, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.selected_node else {
+            return;
+        };
+        let node = &self.nodes[ix];
+        if !node.expanded {
+            self.expand(ix, cx);
+        } else if let Children::Loaded(children) = &node.children {
+            if let Some(child) = children.first().copied() {
+                self.select(child, cx);
+            }
+        }
+    }
+
+    fn collapse_selected_entry(&mut self, _: &CollapseSelectedEntry, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.selected_node else {
+            return;
+        };
+        let node = &self.nodes[ix];
+        if node.expanded {
+            self.collapse(ix, cx);
+        } else if let Some(parent) = node.parent {
+            self.select(parent, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.selected_node {
+            self.open_node(ix, cx);
+        }
+    }
+
+    fn selected_position(&self) -> Option<usize> {
+        let selected_node = self.selected_node?;
+        self.visible_nodes
+            .iter()
+            .position(|ix| *ix == selected_node)
+    }
+
+    fn select(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        self.selected_node = Some(ix);
+        if let Some(position) = self.selected_position() {
+            self.list.scroll_to_item(position);
+        }
+        cx.notify();
+    }
+
+    /// Opens the call site of the node, or the item itself for the roots and types.
+    fn open_node(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let node = &self.nodes[ix];
+        let (symbol, position) = match (node.call_ranges.first(), node.parent) {
+            // Outgoing calls are made from the parent's file.
+            (Some(range), Some(parent)) if self.direction == HierarchyDirection::OutgoingCalls => {
+                (&self.nodes[parent].item.symbol, range.start)
+            }
+            (Some(range), _) => (&node.item.symbol, range.start),
+            (None, _) => (&node.item.symbol, node.item.symbol.range.start),
+        };
+        let symbol = symbol.clone();
+        let open_buffer = self.project.update(cx, |project, cx| {
+            project.open_buffer_for_symbol(&symbol, cx)
+        });
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            let buffer = open_buffer.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let position = buffer.read(cx).clip_point_utf16(position, Bias::Left);
+                let pane = workspace.active_pane().clone();
+                let editor = workspace.open_project_item::<Editor>(pane, buffer, cx);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([position..position])
+                    });
+                });
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::default();
+        dispatch_context.add("HierarchyPanel");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let directions = match self.direction {
+            HierarchyDirection::IncomingCalls | HierarchyDirection::OutgoingCalls => [
+                ("Incoming Calls", HierarchyDirection::IncomingCalls),
+                ("Outgoing Calls", HierarchyDirection::OutgoingCalls),
+            ],
+            HierarchyDirection::Supertypes | HierarchyDirection::Subtypes => [
+                ("Supertypes", HierarchyDirection::Supertypes),
+                ("Subtypes", HierarchyDirection::Subtypes),
+            ],
+        };
+        h_flex()
+            .p_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .children(directions.into_iter().map(|(label, direction)| {
+                Button::new(label, label)
+                    .selected(self.direction == direction)
+                    .on_click(cx.listener(move |this, _, cx| this.set_direction(direction, cx)))
+            }))
+    }
+
+    fn render_node(&self, ix: usize, cx: &mut ViewContext<Self>) -> ListItem {
+        let node = &self.nodes[ix];
+        let settings = HierarchyPanelSettings::get_global(cx);
+        let toggle = match &node.children {
+            Children::Loaded(children) if children.is_empty() => None,
+            _ => Some(node.expanded),
+        };
+        ListItem::new(ix)
+            .indent_level(node.depth)
+            .indent_step_size(px(settings.indent_size))
+            .selected(self.selected_node == Some(ix))
+            .toggle(toggle)
+            .on_toggle(cx.listener(move |this, _, cx| this.toggle_expanded(ix, cx)))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(node.item.symbol.label.text.clone()))
+                    .children(node.item.detail.clone().map(|detail| {
+                        Label::new(detail)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                    })),
+            )
+            .on_click(cx.listener(move |this, _, cx| {
+                this.select(ix, cx);
+                this.open_node(ix, cx);
+            }))
+    }
+}
+
+impl Render for HierarchyPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .id("hierarchy-panel")
+            .size_full()
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .on_action(cx.listener(Self::confirm))
+            .child(self.render_header(cx))
+            .child(if self.visible_nodes.is_empty() {
+                v_flex()
+                    .size_full()
+                    .p_4()
+                    .child(
+                        Label::new(
+                            "Run a hierarchy command on a function or type to show it here.",
+                        )
+                        .color(Color::Muted),
+                    )
+                    .into_any_element()
+            } else {
+                uniform_list(
+                    cx.view().clone(),
+                    "hierarchy-nodes",
+                    self.visible_nodes.len(),
+                    |this, range, cx| {
+                        this.visible_nodes[range]
+                            .iter()
+                            .map(|ix| this.render_node(*ix, cx))
+                            .collect()
+                    },
+                )
+                .size_full()
+                .track_scroll(self.list.clone())
+                .into_any_element()
+            })
+    }
+}
+
+impl EventEmitter<PanelEvent> for HierarchyPanel {}
+
+impl Panel for HierarchyPanel {
+    fn persistent_name() -> &'static str {
+        "HierarchyPanel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        HierarchyPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<HierarchyPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings| settings.dock = Some(position),
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| HierarchyPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        HierarchyPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::Link)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Hierarchy Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+impl FocusableView for HierarchyPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
@@ -108,6 +108,8 @@ pub struct Buffer {
     git_diff_update_count: usize,
     completion_triggers: Vec<String>,
     completion_triggers_timestamp: clock::Lamport,
+    signature_help_triggers: Vec<String>,
+    signature_help_triggers_timestamp: clock::Lamport,
     deferred_ops: OperationQueue<Operation>,
     capability: Capability,
 }
@@ -277,6 +279,53 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// The kind of code in a [`FoldRange`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FoldKind {
+    /// A block of code, such as the body of a function.
+    #[default]
+    Region,
+    /// A comment, or a run of consecutive line comments.
+    Comment,
+    /// A group of imports.
+    Imports,
+}
+
+/// A range of a buffer that can be folded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FoldRange<T> {
+    /// The range of the buffer spanned by the foldable code.
+    pub range: Range<T>,
+    /// The kind of code in the range.
+    pub kind: FoldKind,
+}
+
+/// The kind of syntax node selected by a text object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextObjectKind {
+    /// A function or method definition.
+    Function,
+    /// A class, struct, trait or other type definition.
+    Class,
+    /// A parameter of a function, or an argument of a call.
+    Parameter,
+    /// A comment, or a run of consecutive line comments.
+    Comment,
+}
+
+/// The ranges of a syntax node that can be selected as a whole.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextObjectRange<T> {
+    /// The kind of syntax node.
+    pub kind: TextObjectKind,
+    /// The range of the whole node, such as a function with its signature, or a
+    /// parameter with the comma that separates it from the next one.
+    pub around: Range<T>,
+    /// The range of the node's contents, such as the body of a function without
+    /// its braces.
+    pub inside: Range<T>,
+}
+
 /// An operation used to synchronize this buffer with its other replicas.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Operation {
@@ -313,6 +362,15 @@ pub enum Operation {
         /// The buffer's lamport timestamp.
         lamport_timestamp: clock::Lamport,
     },
+
+    /// An update to the characters that should trigger signature help
+    /// for this buffer.
+    UpdateSignatureHelpTriggers {
+        /// The characters that trigger signature help.
+        triggers: Vec<String>,
+        /// The buffer's lamport timestamp.
+        lamport_timestamp: clock::Lamport,
+    },
 }
 
 /// An event that occurs in a buffer.
@@ -625,6 +683,12 @@ impl Buffer {
                 lamport_timestamp: self.completion_triggers_timestamp,
             },
         ));
+        operations.push(proto::serialize_operation(
+            &Operation::UpdateSignatureHelpTriggers {
+                triggers: self.signature_help_triggers.clone(),
+                lamport_timestamp: self.signature_help_triggers_timestamp,
+            },
+        ));
 
         let text_operations = self.text.operations().clone();
         cx.background_executor().spawn(async move {
@@ -697,6 +761,8 @@ impl Buffer {
             git_diff_update_count: 0,
             completion_triggers: Default::default(),
             completion_triggers_timestamp: Default::default(),
+            signature_help_triggers: Default::default(),
+            signature_help_triggers_timestamp: Default::default(),
             deferred_ops: OperationQueue::new(),
         }
     }
@@ -1891,7 +1957,8 @@ impl Buffer {
             Operation::UpdateSelections { selections, .. } => selections
                 .iter()
                 .all(|s| self.can_resolve(&s.start) && self.can_resolve(&s.end)),
-            Operation::UpdateCompletionTriggers { .. } => true,
+            Operation::UpdateCompletionTriggers { .. }
+            | Operation::UpdateSignatureHelpTriggers { .. } => true,
         }
     }
 
@@ -1944,6 +2011,13 @@ impl Buffer {
                 self.completion_triggers = triggers;
                 self.text.lamport_clock.observe(lamport_timestamp);
             }
+            Operation::UpdateSignatureHelpTriggers {
+                triggers,
+                lamport_timestamp,
+            } => {
+                self.signature_help_triggers = triggers;
+                self.text.lamport_clock.observe(lamport_timestamp);
+            }
         }
     }
 
@@ -2088,6 +2162,30 @@ impl Buffer {
     pub fn completion_triggers(&self) -> &[String] {
         &self.completion_triggers
     }
+
+    /// Override current signature help triggers with the ones of the buffer's language server.
+    pub fn set_signature_help_triggers(
+        &mut self,
+        triggers: Vec<String>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.signature_help_triggers = triggers.clone();
+        self.signature_help_triggers_timestamp = self.text.lamport_clock.tick();
+        self.send_operation(
+            Operation::UpdateSignatureHelpTriggers {
+                triggers,
+                lamport_timestamp: self.signature_help_triggers_timestamp,
+            },
+            cx,
+        );
+        cx.notify();
+    }
+
+    /// Returns the characters which open or update signature help, as reported by the
+    /// buffer's language server.
+    pub fn signature_help_triggers(&self) -> &[String] {
+        &self.signature_help_triggers
+    }
 }
 
 #[doc(hidden)]
@@ -2932,6 +3030,191 @@ impl BufferSnapshot {
         result
     }
 
+    /// Returns the ranges of the syntax nodes that can be folded and that start in
+    /// the given range, ordered by their start, according to the folds query of
+    /// each language in the range.
+    ///
+    /// Consecutive comments or imports of the same kind are folded together, in a
+    /// single range that starts at the first of them.
+    ///
+    /// Returns `None` if none of these languages have a folds query.
+    pub fn syntax_fold_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> Option<Vec<FoldRange<usize>>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut matches = self.syntax.matches(range.clone(), &self.text, |grammar| {
+            grammar.folds_config.as_ref().map(|config| &config.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.folds_config.as_ref().unwrap())
+            .collect::<Vec<_>>();
+        if configs.is_empty() {
+            return None;
+        }
+
+        let mut fold_ranges = Vec::<FoldRange<usize>>::new();
+        while let Some(mat) = matches.peek() {
+            let config = &configs[mat.grammar_index];
+            for capture in mat.captures {
+                let kind = if Some(capture.index) == config.fold_capture_ix {
+                    FoldKind::Region
+                } else if Some(capture.index) == config.comment_capture_ix {
+                    FoldKind::Comment
+                } else if Some(capture.index) == config.imports_capture_ix {
+                    FoldKind::Imports
+                } else {
+                    continue;
+                };
+
+                let node = capture.node;
+                let range = if kind == FoldKind::Region {
+                    node.byte_range()
+                } else {
+                    if node
+                        .prev_named_sibling()
+                        .map_or(false, |prev| continues_fold_run(prev, node))
+                    {
+                        continue;
+                    }
+                    let mut last = node;
+                    while let Some(next) = last.next_named_sibling() {
+                        if !continues_fold_run(last, next) {
+                            break;
+                        }
+                        last = next;
+                    }
+                    node.start_byte()..last.end_byte()
+                };
+                fold_ranges.push(FoldRange { range, kind });
+            }
+            matches.advance();
+        }
+
+        fold_ranges.sort_unstable_by_key(|fold_range| {
+            (fold_range.range.start, cmp::Reverse(fold_range.range.end))
+        });
+        fold_ranges.dedup();
+        fold_ranges.retain(|fold_range| {
+            range.start <= fold_range.range.start && fold_range.range.start <= range.end
+        });
+        Some(fold_ranges)
+    }
+
+    /// Returns the text objects that intersect the given range, ordered by their start,
+    /// according to the textobjects query of each language in the range.
+    ///
+    /// Returns `None` if none of these languages have a textobjects query.
+    pub fn text_objects<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> Option<Vec<TextObjectRange<usize>>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        // Find objects that *inclusively* contain the given range.
+        let query_range = range.start.saturating_sub(1)..self.len().min(range.end + 1);
+        let mut matches = self.syntax.matches(query_range, &self.text, |grammar| {
+            grammar
+                .text_object_config
+                .as_ref()
+                .map(|config| &config.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.text_object_config.as_ref().unwrap())
+            .collect::<Vec<_>>();
+        if configs.is_empty() {
+            return None;
+        }
+
+        let mut text_objects = Vec::<TextObjectRange<usize>>::new();
+        while let Some(mat) = matches.peek() {
+            let config = &configs[mat.grammar_index];
+
+            // A pattern can capture the node around an object, the node inside it, or both.
+            let mut nodes_by_kind: SmallVec<[(TextObjectKind, Option<_>, Option<_>); 2]> =
+                SmallVec::new();
+            for capture in mat.captures {
+                let Some(&(_, kind, around)) = config
+                    .text_objects_by_capture_ix
+                    .iter()
+                    .find(|(capture_ix, _, _)| *capture_ix == capture.index)
+                else {
+                    continue;
+                };
+                let ix = match nodes_by_kind.iter().position(|(k, _, _)| *k == kind) {
+                    Some(ix) => ix,
+                    None => {
+                        nodes_by_kind.push((kind, None, None));
+                        nodes_by_kind.len() - 1
+                    }
+                };
+                if around {
+                    nodes_by_kind[ix].1 = Some(capture.node);
+                } else {
+                    nodes_by_kind[ix].2 = Some(capture.node);
+                }
+            }
+
+            for (kind, around_node, inside_node) in nodes_by_kind {
+                let around = match (kind, around_node, inside_node) {
+                    (TextObjectKind::Comment, Some(node), _)
+                    | (TextObjectKind::Comment, None, Some(node)) => comment_run_range(node),
+                    (_, Some(node), _) => node.byte_range(),
+                    (TextObjectKind::Parameter, None, Some(node)) => {
+                        parameter_range_with_separator(node)
+                    }
+                    (_, None, Some(node)) => node.byte_range(),
+                    (_, None, None) => continue,
+                };
+                let inside = match inside_node {
+                    Some(node) if kind != TextObjectKind::Comment => range_inside_brackets(node),
+                    _ => around.clone(),
+                };
+                text_objects.push(TextObjectRange {
+                    kind,
+                    around,
+                    inside,
+                });
+            }
+            matches.advance();
+        }
+
+        text_objects.sort_unstable_by_key(|text_object| {
+            (
+                text_object.around.start,
+                cmp::Reverse(text_object.around.end),
+            )
+        });
+        text_objects.dedup();
+        text_objects.retain(|text_object| {
+            text_object.around.start <= range.end && range.start <= text_object.around.end
+        });
+        Some(text_objects)
+    }
+
+    /// Returns the innermost text object of the given kind that contains the given
+    /// range and is larger than it, so that repeatedly selecting the enclosing object
+    /// expands the selection outwards.
+    pub fn enclosing_text_object<T: ToOffset>(
+        &self,
+        range: Range<T>,
+        kind: TextObjectKind,
+    ) -> Option<TextObjectRange<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        self.text_objects(range.clone())?
+            .into_iter()
+            .filter(|text_object| {
+                text_object.kind == kind
+                    && text_object.around.start <= range.start
+                    && range.end <= text_object.around.end
+                    && text_object.around.len() > range.len()
+            })
+            .min_by_key(|text_object| text_object.around.len())
+    }
+
     /// Returns anchor ranges for any matches of the redaction query.
     /// The buffer can be associated with multiple languages, and the redaction query associated with each
     /// will be run on the relevant section of the buffer.
@@ -3410,6 +3693,9 @@ impl operation_queue::Operation for Operation {
             }
             | Operation::UpdateCompletionTriggers {
                 lamport_timestamp, ..
+            }
+            | Operation::UpdateSignatureHelpTriggers {
+                lamport_timestamp, ..
             } => *lamport_timestamp,
         }
     }
@@ -3585,3 +3871,78 @@ pub fn trailing_whitespace_ranges(rope: &Rope) -> Vec<Range<usize>> {
 
     ranges
 }
+
+/// Returns the range of the run of consecutive comments that contains the given comment.
+fn comment_run_range(node: tree_sitter::Node) -> Range<usize> {
+    let mut first = node;
+    while let Some(prev) = first.prev_named_sibling() {
+        if !continues_fold_run(prev, first) {
+            break;
+        }
+        first = prev;
+    }
+    let mut last = node;
+    while let Some(next) = last.next_named_sibling() {
+        if !continues_fold_run(last, next) {
+            break;
+        }
+        last = next;
+    }
+    first.start_byte()..last.end_byte()
+}
+
+/// Returns the range of a parameter or argument with the comma that separates it from
+/// the next one, up to the start of the next one, or with the comma that precedes it
+/// if it is the last one.
+fn parameter_range_with_separator(node: tree_sitter::Node) -> Range<usize> {
+    if let Some(comma) = node.next_sibling().filter(|next| next.kind() == ",") {
+        let end = comma
+            .next_sibling()
+            .filter(|next| next.is_named())
+            .map_or(comma.end_byte(), |next| next.start_byte());
+        node.start_byte()..end
+    } else if let Some(comma) = node.prev_sibling().filter(|prev| prev.kind() == ",") {
+        comma.start_byte()..node.end_byte()
+    } else {
+        node.byte_range()
+    }
+}
+
+/// Returns the range of a node without the brackets that delimit it, such as the
+/// braces of a block or of an interface.
+fn range_inside_brackets(node: tree_sitter::Node) -> Range<usize> {
+    let child_count = node.child_count();
+    let Some(close) = child_count
+        .checked_sub(1)
+        .and_then(|ix| node.child(ix))
+        .filter(|close| !close.is_named())
+    else {
+        return node.byte_range();
+    };
+    let open_kind = match close.kind() {
+        "}" => "{",
+        ")" => "(",
+        "]" => "[",
+        _ => return node.byte_range(),
+    };
+    (0..child_count)
+        .filter_map(|ix| node.child(ix))
+        .find(|open| !open.is_named() && open.kind() == open_kind)
+        .map_or(node.byte_range(), |open| {
+            open.end_byte()..close.start_byte()
+        })
+}
+
+/// Returns whether `next` directly follows `prev` in a run of nodes of the same kind,
+/// such as consecutive line comments.
+fn continues_fold_run(prev: tree_sitter::Node, next: tree_sitter::Node) -> bool {
+    let prev_start = prev.start_position();
+    let prev_end = prev.end_position();
+    // Nodes such as line comments can include the newline that ends them.
+    let prev_last_row = if prev_end.column == 0 && prev_end.row > prev_start.row {
+        prev_end.row - 1
+    } else {
+        prev_end.row
+    };
+    next.kind_id() == prev.kind_id() && next.start_position().row <= prev_last_row + 1
+}
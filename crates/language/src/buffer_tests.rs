@@ -613,6 +613,174 @@ async fn test_outline_nodes_with_newlines(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_syntax_fold_ranges(cx: &mut gpui::TestAppContext) {
+    let text = r#"
+        use a::b;
+        use c::d;
+
+        // one
+        // two
+        fn main() {
+            let x = [
+                1,
+            ];
+        }
+    "#
+    .unindent();
+
+    let language = rust_lang()
+        .with_folds_query(
+            r#"
+            (_ "{" "}") @fold
+            (_ "[" "]") @fold
+            (_ "(" ")") @fold
+            (line_comment) @fold.comment
+            (use_declaration) @fold.imports
+            "#,
+        )
+        .unwrap();
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(Arc::new(language), cx)
+    });
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    let fold_ranges = snapshot.syntax_fold_ranges(0..snapshot.len()).unwrap();
+
+    assert_eq!(
+        fold_ranges
+            .iter()
+            .map(|fold_range| {
+                let text = snapshot
+                    .text_for_range(fold_range.range.clone())
+                    .collect::<String>();
+                (text.trim_end().to_string(), fold_range.kind)
+            })
+            .collect::<Vec<_>>(),
+        &[
+            ("use a::b;\nuse c::d;".to_string(), FoldKind::Imports),
+            ("// one\n// two".to_string(), FoldKind::Comment),
+            ("()".to_string(), FoldKind::Region),
+            (
+                "{\n    let x = [\n        1,\n    ];\n}".to_string(),
+                FoldKind::Region
+            ),
+            ("[\n        1,\n    ]".to_string(), FoldKind::Region),
+        ]
+    );
+
+    // Languages without a folds query fall back to other folding ranges.
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(
+            0,
+            BufferId::new(cx.entity_id().as_u64()).unwrap(),
+            "fn a() {}",
+        )
+        .with_language(Arc::new(rust_lang()), cx)
+    });
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(snapshot.syntax_fold_ranges(0..snapshot.len()), None);
+}
+
+#[gpui::test]
+async fn test_text_objects(cx: &mut gpui::TestAppContext) {
+    let text = r#"
+        impl Foo {
+            // one
+            // two
+            fn bar(a: u32, b: u32) {
+                baz(a);
+            }
+        }
+    "#
+    .unindent();
+
+    let language = rust_lang()
+        .with_text_object_query(
+            r#"
+            (function_item body: (_) @function.inside) @function.around
+            (impl_item body: (_) @class.inside) @class.around
+            (parameters (_) @parameter.inside)
+            (line_comment) @comment.around
+            "#,
+        )
+        .unwrap();
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(
+            0,
+            BufferId::new(cx.entity_id().as_u64()).unwrap(),
+            text.clone(),
+        )
+        .with_language(Arc::new(language), cx)
+    });
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    let text_for_ranges = |text_object: TextObjectRange<usize>| {
+        (
+            snapshot
+                .text_for_range(text_object.around)
+                .collect::<String>(),
+            snapshot
+                .text_for_range(text_object.inside)
+                .collect::<String>(),
+        )
+    };
+
+    let offset = snapshot.text().find("baz").unwrap();
+    assert_eq!(
+        snapshot
+            .enclosing_text_object(offset..offset, TextObjectKind::Function)
+            .map(text_for_ranges),
+        Some((
+            "fn bar(a: u32, b: u32) {\n        baz(a);\n    }".to_string(),
+            "\n        baz(a);\n    ".to_string()
+        ))
+    );
+
+    // Selecting the enclosing object again expands to the next enclosing object.
+    let function = snapshot
+        .enclosing_text_object(offset..offset, TextObjectKind::Function)
+        .unwrap();
+    assert_eq!(
+        snapshot.enclosing_text_object(function.around.clone(), TextObjectKind::Function),
+        None
+    );
+    assert_eq!(
+        snapshot
+            .enclosing_text_object(function.around, TextObjectKind::Class)
+            .map(|class| snapshot.text_for_range(class.around).collect::<String>()),
+        Some(text.trim_end().to_string())
+    );
+
+    // Parameters include the comma that separates them from the next one, or from the previous one.
+    let offset = snapshot.text().find("a: u32").unwrap();
+    assert_eq!(
+        snapshot
+            .enclosing_text_object(offset..offset, TextObjectKind::Parameter)
+            .map(text_for_ranges),
+        Some(("a: u32, ".to_string(), "a: u32".to_string()))
+    );
+    let offset = snapshot.text().find("b: u32").unwrap();
+    assert_eq!(
+        snapshot
+            .enclosing_text_object(offset..offset, TextObjectKind::Parameter)
+            .map(text_for_ranges),
+        Some((", b: u32".to_string(), "b: u32".to_string()))
+    );
+
+    // Consecutive line comments form a single object.
+    let offset = snapshot.text().find("two").unwrap();
+    assert_eq!(
+        snapshot
+            .enclosing_text_object(offset..offset, TextObjectKind::Comment)
+            .map(|comment| snapshot
+                .text_for_range(comment.around)
+                .collect::<String>()
+                .trim_end()
+                .to_string()),
+        Some("// one\n    // two".to_string())
+    );
+}
+
 #[gpui::test]
 async fn test_outline_with_extra_context(cx: &mut gpui::TestAppContext) {
     let language = javascript_lang()
@@ -684,6 +684,8 @@ pub struct Grammar {
     pub embedding_config: Option<EmbeddingConfig>,
     pub(crate) injection_config: Option<InjectionConfig>,
     pub(crate) override_config: Option<OverrideConfig>,
+    pub(crate) folds_config: Option<FoldConfig>,
+    pub(crate) text_object_config: Option<TextObjectConfig>,
     pub(crate) highlight_map: Mutex<HighlightMap>,
 }
 
@@ -742,6 +744,20 @@ struct BracketConfig {
     close_capture_ix: u32,
 }
 
+struct FoldConfig {
+    query: Query,
+    fold_capture_ix: Option<u32>,
+    comment_capture_ix: Option<u32>,
+    imports_capture_ix: Option<u32>,
+}
+
+struct TextObjectConfig {
+    query: Query,
+    /// The kind of text object of each capture, and whether the capture is the
+    /// range around the object rather than inside it.
+    text_objects_by_capture_ix: Vec<(u32, TextObjectKind, bool)>,
+}
+
 impl Language {
     pub fn new(config: LanguageConfig, ts_language: Option<tree_sitter::Language>) -> Self {
         Self::new_with_id(
@@ -770,6 +786,8 @@ impl Language {
                     injection_config: None,
                     override_config: None,
                     redactions_config: None,
+                    folds_config: None,
+                    text_object_config: None,
                     error_query: Query::new(&ts_language, "(ERROR) @error").unwrap(),
                     ts_language,
                     highlight_map: Default::default(),
@@ -827,6 +845,16 @@ impl Language {
                 .with_redaction_query(query.as_ref())
                 .context("Error loading redaction query")?;
         }
+        if let Some(query) = queries.folds {
+            self = self
+                .with_folds_query(query.as_ref())
+                .context("Error loading folds query")?;
+        }
+        if let Some(query) = queries.textobjects {
+            self = self
+                .with_text_object_query(query.as_ref())
+                .context("Error loading textobjects query")?;
+        }
         Ok(self)
     }
 
@@ -1073,6 +1101,63 @@ impl Language {
         Ok(self)
     }
 
+    pub fn with_folds_query(mut self, source: &str) -> Result<Self> {
+        let grammar = self.grammar_mut();
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut fold_capture_ix = None;
+        let mut comment_capture_ix = None;
+        let mut imports_capture_ix = None;
+        get_capture_indices(
+            &query,
+            &mut [
+                ("fold", &mut fold_capture_ix),
+                ("fold.comment", &mut comment_capture_ix),
+                ("fold.imports", &mut imports_capture_ix),
+            ],
+        );
+        if fold_capture_ix.is_some() || comment_capture_ix.is_some() || imports_capture_ix.is_some()
+        {
+            grammar.folds_config = Some(FoldConfig {
+                query,
+                fold_capture_ix,
+                comment_capture_ix,
+                imports_capture_ix,
+            });
+        }
+        Ok(self)
+    }
+
+    pub fn with_text_object_query(mut self, source: &str) -> Result<Self> {
+        let grammar = self.grammar_mut();
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut text_objects_by_capture_ix = Vec::new();
+        for (ix, name) in query.capture_names().iter().enumerate() {
+            let Some((kind, part)) = name.split_once('.') else {
+                continue;
+            };
+            let kind = match kind {
+                "function" => TextObjectKind::Function,
+                "class" => TextObjectKind::Class,
+                "parameter" => TextObjectKind::Parameter,
+                "comment" => TextObjectKind::Comment,
+                _ => continue,
+            };
+            let around = match part {
+                "around" => true,
+                "inside" => false,
+                _ => continue,
+            };
+            text_objects_by_capture_ix.push((ix as u32, kind, around));
+        }
+        if !text_objects_by_capture_ix.is_empty() {
+            grammar.text_object_config = Some(TextObjectConfig {
+                query,
+                text_objects_by_capture_ix,
+            });
+        }
+        Ok(self)
+    }
+
     fn grammar_mut(&mut self) -> &mut Grammar {
         Arc::get_mut(self.grammar.as_mut().unwrap()).unwrap()
     }
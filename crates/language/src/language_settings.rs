@@ -87,6 +87,8 @@ pub struct LanguageSettings {
     pub show_copilot_suggestions: bool,
     /// Whether to show tabs and spaces in the editor.
     pub show_whitespaces: ShowWhitespaceSetting,
+    /// Where the editor's folding ranges come from.
+    pub folding_ranges: FoldingRanges,
     /// Whether to start a new line with a comment when a previous line is a comment as well.
     pub extend_comment_on_newline: bool,
     /// Inlay hint related settings.
@@ -204,6 +206,11 @@ pub struct LanguageSettingsContent {
     /// Whether to show tabs and spaces in the editor.
     #[serde(default)]
     pub show_whitespaces: Option<ShowWhitespaceSetting>,
+    /// Where the editor's folding ranges come from.
+    ///
+    /// Default: auto
+    #[serde(default)]
+    pub folding_ranges: Option<FoldingRanges>,
     /// Whether to start a new line with a comment when a previous line is a comment as well.
     ///
     /// Default: true
@@ -283,6 +290,21 @@ pub enum ShowWhitespaceSetting {
     All,
 }
 
+/// Controls where the editor's folding ranges come from.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FoldingRanges {
+    /// Use the language's folds query if it has one, then the folding ranges
+    /// of the language server, then indentation.
+    #[default]
+    Auto,
+    /// Use the folding ranges of the language server if it provides any, then
+    /// the language's folds query, then indentation.
+    LanguageServer,
+    /// Fold by indentation only.
+    Indentation,
+}
+
 /// Controls which formatter should be used when formatting code.
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -608,6 +630,7 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.show_copilot_suggestions,
     );
     merge(&mut settings.show_whitespaces, src.show_whitespaces);
+    merge(&mut settings.folding_ranges, src.folding_ranges);
     merge(
         &mut settings.extend_comment_on_newline,
         src.extend_comment_on_newline,
@@ -99,6 +99,17 @@ pub fn serialize_operation(operation: &crate::Operation) -> proto::Operation {
                     triggers: triggers.clone(),
                 },
             ),
+
+            crate::Operation::UpdateSignatureHelpTriggers {
+                triggers,
+                lamport_timestamp,
+            } => proto::operation::Variant::UpdateSignatureHelpTriggers(
+                proto::operation::UpdateSignatureHelpTriggers {
+                    replica_id: lamport_timestamp.replica_id as u32,
+                    lamport_timestamp: lamport_timestamp.value,
+                    triggers: triggers.clone(),
+                },
+            ),
         }),
     }
 }
@@ -325,6 +336,15 @@ pub fn deserialize_operation(message: proto::Operation) -> Result<crate::Operati
                     },
                 }
             }
+            proto::operation::Variant::UpdateSignatureHelpTriggers(message) => {
+                crate::Operation::UpdateSignatureHelpTriggers {
+                    triggers: message.triggers,
+                    lamport_timestamp: clock::Lamport {
+                        replica_id: message.replica_id as ReplicaId,
+                        value: message.lamport_timestamp,
+                    },
+                }
+            }
         },
     )
 }
@@ -464,6 +484,10 @@ pub fn lamport_timestamp_for_operation(operation: &proto::Operation) -> Option<c
             replica_id = op.replica_id;
             value = op.lamport_timestamp;
         }
+        proto::operation::Variant::UpdateSignatureHelpTriggers(op) => {
+            replica_id = op.replica_id;
+            value = op.lamport_timestamp;
+        }
     }
 
     Some(clock::Lamport {
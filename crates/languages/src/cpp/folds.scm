@@ -0,0 +1,7 @@
+(_ "{" "}") @fold
+(_ "[" "]") @fold
+(_ "(" ")") @fold
+
+(comment) @fold.comment
+
+(preproc_include) @fold.imports
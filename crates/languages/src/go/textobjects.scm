@@ -0,0 +1,22 @@
+(function_declaration
+    body: (_) @function.inside) @function.around
+
+(method_declaration
+    body: (_) @function.inside) @function.around
+
+(func_literal
+    body: (_) @function.inside) @function.around
+
+(type_declaration
+    (type_spec
+        type: (struct_type
+            (field_declaration_list) @class.inside))) @class.around
+
+(type_declaration
+    (type_spec
+        type: (interface_type) @class.inside)) @class.around
+
+(parameter_list (_) @parameter.inside)
+(argument_list (_) @parameter.inside)
+
+(comment) @comment.around
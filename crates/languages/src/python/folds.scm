@@ -0,0 +1,17 @@
+[
+  (function_definition)
+  (class_definition)
+  (if_statement)
+  (for_statement)
+  (while_statement)
+  (with_statement)
+  (try_statement)
+] @fold
+
+(_ "[" "]") @fold
+(_ "{" "}") @fold
+(_ "(" ")") @fold
+
+(comment) @fold.comment
+
+[(import_statement) (import_from_statement)] @fold.imports
@@ -0,0 +1 @@
+../typescript/folds.scm
\ No newline at end of file
@@ -0,0 +1 @@
+../typescript/textobjects.scm
\ No newline at end of file
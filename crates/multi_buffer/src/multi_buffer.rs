@@ -12,9 +12,10 @@ use language::{
     char_kind,
     language_settings::{language_settings, LanguageSettings},
     AutoindentMode, Buffer, BufferChunks, BufferSnapshot, Capability, CharKind, Chunk, CursorShape,
-    DiagnosticEntry, File, IndentSize, Language, LanguageScope, OffsetRangeExt, OffsetUtf16,
-    Outline, OutlineItem, Point, PointUtf16, Selection, TextDimension, ToOffset as _,
-    ToOffsetUtf16 as _, ToPoint as _, ToPointUtf16 as _, TransactionId, Unclipped,
+    DiagnosticEntry, File, FoldRange, IndentSize, Language, LanguageScope, OffsetRangeExt,
+    OffsetUtf16, Outline, OutlineItem, Point, PointUtf16, Selection, TextDimension, TextObjectKind,
+    TextObjectRange, ToOffset as _, ToOffsetUtf16 as _, ToPoint as _, ToPointUtf16 as _,
+    TransactionId, Unclipped,
 };
 use std::{
     borrow::Cow,
@@ -2998,6 +2999,55 @@ impl MultiBufferSnapshot {
         )
     }
 
+    /// Returns the ranges that can be folded according to the folds query and that start
+    /// in the given `range`, or returns None if the `range` is not contained in a single
+    /// excerpt or if the excerpt's languages have no folds query.
+    pub fn syntax_fold_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> Option<Vec<FoldRange<usize>>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let excerpt = self.excerpt_containing(range.clone())?;
+        let fold_ranges = excerpt
+            .buffer()
+            .syntax_fold_ranges(excerpt.map_range_to_buffer(range))?;
+
+        Some(
+            fold_ranges
+                .into_iter()
+                .filter(|fold_range| excerpt.contains_buffer_range(fold_range.range.clone()))
+                .map(|fold_range| FoldRange {
+                    range: excerpt.map_range_from_buffer(fold_range.range),
+                    kind: fold_range.kind,
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the innermost text object of the given kind that contains the given `range`
+    /// and is larger than it, or returns None if there is no such object in the excerpt
+    /// containing the `range`.
+    pub fn enclosing_text_object<T: ToOffset>(
+        &self,
+        range: Range<T>,
+        kind: TextObjectKind,
+    ) -> Option<TextObjectRange<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let excerpt = self.excerpt_containing(range.clone())?;
+        let text_object = excerpt
+            .buffer()
+            .enclosing_text_object(excerpt.map_range_to_buffer(range), kind)?;
+        if !excerpt.contains_buffer_range(text_object.around.clone()) {
+            return None;
+        }
+
+        Some(TextObjectRange {
+            kind,
+            around: excerpt.map_range_from_buffer(text_object.around),
+            inside: excerpt.map_range_from_buffer(text_object.inside),
+        })
+    }
+
     pub fn redacted_ranges<'a, T: ToOffset>(
         &'a self,
         range: Range<T>,
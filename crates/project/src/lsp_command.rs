@@ -1,7 +1,9 @@
 use crate::{
-    DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint, InlayHintLabel,
-    InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location, LocationLink,
-    MarkupContent, Project, ProjectTransaction, ResolveState,
+    serialize_hierarchy_item, CodeLens, DocumentHighlight, File, HierarchyItem, Hover, HoverBlock,
+    HoverBlockKind, InlayHint, InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip,
+    InlayHintTooltip, Location, LocationLink, MarkupContent, ParameterInformation, Project,
+    ProjectTransaction, ResolveState, SemanticToken, SemanticTokensLegend, SignatureHelp,
+    SignatureInformation,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -13,8 +15,8 @@ use language::{
     point_from_lsp, point_to_lsp, prepare_completion_documentation,
     proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
     range_from_lsp, range_to_lsp, Anchor, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CharKind,
-    CodeAction, Completion, OffsetRangeExt, PointUtf16, ToOffset, ToPointUtf16, Transaction,
-    Unclipped,
+    CodeAction, Completion, FoldKind, FoldRange, OffsetRangeExt, PointUtf16, ToOffset,
+    ToPointUtf16, Transaction, Unclipped,
 };
 use lsp::{
     CompletionListItemDefaultsEditRange, DocumentHighlightKind, LanguageServer, LanguageServerId,
@@ -121,6 +123,10 @@ pub(crate) struct GetHover {
     pub position: PointUtf16,
 }
 
+pub(crate) struct GetSignatureHelp {
+    pub position: PointUtf16,
+}
+
 pub(crate) struct GetCompletions {
     pub position: PointUtf16,
 }
@@ -141,6 +147,50 @@ pub(crate) struct InlayHints {
     pub range: Range<Anchor>,
 }
 
+#[derive(Default)]
+pub(crate) struct GetSemanticTokens {
+    /// The version of the buffer when the request of a guest was received, which is the
+    /// one the language server computes the tokens for.
+    pub version: Option<clock::Global>,
+}
+
+pub(crate) struct GetCodeLens;
+
+pub(crate) struct GetFoldingRanges;
+
+pub(crate) struct PrepareCallHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct PrepareTypeHierarchy {
+    pub position: PointUtf16,
+}
+
+/// The fields shared by call and type hierarchy items, which the protocol defines with the
+/// same shape, along with the whole item to send back when asking for its neighbours.
+pub(crate) struct LspHierarchyItem {
+    pub name: String,
+    pub kind: lsp::SymbolKind,
+    pub detail: Option<String>,
+    pub uri: lsp::Url,
+    pub selection_range: lsp::Range,
+    pub json: serde_json::Value,
+}
+
+/// Semantic tokens for a whole buffer, still encoded the way the language server sent them,
+/// so that later delta responses can be applied on top of them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SemanticTokensResponse {
+    /// The server that produced the tokens; `None` when they came from the host of a remote project.
+    pub server_id: Option<LanguageServerId>,
+    pub result_id: Option<String>,
+    pub data: Vec<u32>,
+    pub legend: SemanticTokensLegend,
+    /// The version of the buffer the tokens were computed for, when it may not be the one
+    /// they were requested at, as for the tokens sent by the host of a remote project.
+    pub version: Option<clock::Global>,
+}
+
 pub(crate) struct FormattingOptions {
     tab_size: u32,
 }
@@ -1425,6 +1475,270 @@ impl LspCommand for GetHover {
     }
 }
 
+#[async_trait(?Send)]
+impl LspCommand for GetSignatureHelp {
+    type Response = Option<SignatureHelp>;
+    type LspRequest = lsp::request::SignatureHelpRequest;
+    type ProtoRequest = proto::GetSignatureHelp;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        capabilities.signature_help_provider.is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SignatureHelpParams {
+        lsp::SignatureHelpParams {
+            context: None,
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SignatureHelp>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<Self::Response> {
+        let Some(message) = message else {
+            return Ok(None);
+        };
+
+        let signatures = message
+            .signatures
+            .into_iter()
+            .map(|signature| {
+                let parameters = signature
+                    .parameters
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|parameter| {
+                        let label = match parameter.label {
+                            lsp::ParameterLabel::Simple(label) => {
+                                let start = signature.label.find(&label)?;
+                                start..start + label.len()
+                            }
+                            lsp::ParameterLabel::LabelOffsets([start, end]) => {
+                                utf16_range_to_byte_range(&signature.label, start..end)?
+                            }
+                        };
+                        Some(ParameterInformation {
+                            label,
+                            documentation: parameter.documentation.map(markup_content_from_lsp),
+                        })
+                    })
+                    .collect();
+                SignatureInformation {
+                    label: signature.label,
+                    documentation: signature.documentation.map(markup_content_from_lsp),
+                    parameters,
+                    active_parameter: signature.active_parameter.map(|ix| ix as usize),
+                }
+            })
+            .collect::<Vec<_>>();
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+
+        let active_signature = message
+            .active_signature
+            .map(|ix| ix as usize)
+            .filter(|ix| *ix < signatures.len())
+            .unwrap_or(0);
+        Ok(Some(SignatureHelp {
+            signatures,
+            active_signature,
+            active_parameter: message.active_parameter.map(|ix| ix as usize),
+        }))
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> Self::ProtoRequest {
+        proto::GetSignatureHelp {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version),
+        }
+    }
+
+    async fn from_proto(
+        message: Self::ProtoRequest,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Self::Response,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSignatureHelpResponse {
+        let Some(response) = response else {
+            return proto::GetSignatureHelpResponse {
+                signatures: Vec::new(),
+                active_signature: 0,
+                active_parameter: None,
+            };
+        };
+
+        proto::GetSignatureHelpResponse {
+            signatures: response
+                .signatures
+                .into_iter()
+                .map(|signature| proto::SignatureInformation {
+                    label: signature.label,
+                    documentation: signature.documentation.map(markup_content_to_proto),
+                    parameters: signature
+                        .parameters
+                        .into_iter()
+                        .map(|parameter| proto::ParameterInformation {
+                            label_start: parameter.label.start as u64,
+                            label_end: parameter.label.end as u64,
+                            documentation: parameter.documentation.map(markup_content_to_proto),
+                        })
+                        .collect(),
+                    active_parameter: signature.active_parameter.map(|ix| ix as u64),
+                })
+                .collect(),
+            active_signature: response.active_signature as u64,
+            active_parameter: response.active_parameter.map(|ix| ix as u64),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSignatureHelpResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self::Response> {
+        if message.signatures.is_empty() {
+            return Ok(None);
+        }
+
+        let signatures = message
+            .signatures
+            .into_iter()
+            .map(|signature| {
+                let label_len = signature.label.len();
+                SignatureInformation {
+                    documentation: signature.documentation.map(markup_content_from_proto),
+                    parameters: signature
+                        .parameters
+                        .into_iter()
+                        .filter(|parameter| {
+                            parameter.label_start <= parameter.label_end
+                                && parameter.label_end as usize <= label_len
+                        })
+                        .map(|parameter| ParameterInformation {
+                            label: parameter.label_start as usize..parameter.label_end as usize,
+                            documentation: parameter.documentation.map(markup_content_from_proto),
+                        })
+                        .collect(),
+                    active_parameter: signature.active_parameter.map(|ix| ix as usize),
+                    label: signature.label,
+                }
+            })
+            .collect::<Vec<_>>();
+        let active_signature = (message.active_signature as usize).min(signatures.len() - 1);
+        Ok(Some(SignatureHelp {
+            signatures,
+            active_signature,
+            active_parameter: message.active_parameter.map(|ix| ix as usize),
+        }))
+    }
+
+    fn buffer_id_from_proto(message: &Self::ProtoRequest) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+fn markup_content_from_lsp(documentation: lsp::Documentation) -> MarkupContent {
+    match documentation {
+        lsp::Documentation::String(value) => MarkupContent {
+            kind: HoverBlockKind::PlainText,
+            value,
+        },
+        lsp::Documentation::MarkupContent(markup_content) => MarkupContent {
+            kind: match markup_content.kind {
+                lsp::MarkupKind::PlainText => HoverBlockKind::PlainText,
+                lsp::MarkupKind::Markdown => HoverBlockKind::Markdown,
+            },
+            value: markup_content.value,
+        },
+    }
+}
+
+fn markup_content_to_proto(markup_content: MarkupContent) -> proto::MarkupContent {
+    proto::MarkupContent {
+        is_markdown: markup_content.kind == HoverBlockKind::Markdown,
+        value: markup_content.value,
+    }
+}
+
+fn markup_content_from_proto(markup_content: proto::MarkupContent) -> MarkupContent {
+    MarkupContent {
+        kind: if markup_content.is_markdown {
+            HoverBlockKind::Markdown
+        } else {
+            HoverBlockKind::PlainText
+        },
+        value: markup_content.value,
+    }
+}
+
+/// Converts a range of UTF-16 code units within `text` into a range of bytes,
+/// returning `None` if the range does not fall on character boundaries of `text`.
+fn utf16_range_to_byte_range(text: &str, range: Range<u32>) -> Option<Range<usize>> {
+    let mut utf16_offset = 0;
+    let mut start = None;
+    let mut end = None;
+    for (byte_offset, ch) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), '\0')))
+    {
+        if utf16_offset == range.start {
+            start = Some(byte_offset);
+        }
+        if utf16_offset == range.end {
+            end = Some(byte_offset);
+            break;
+        }
+        utf16_offset += ch.len_utf16() as u32;
+    }
+    let (start, end) = (start?, end?);
+    (start <= end).then_some(start..end)
+}
+
 #[async_trait(?Send)]
 impl LspCommand for GetCompletions {
     type Response = Vec<Completion>;
@@ -1954,6 +2268,846 @@ impl LspCommand for OnTypeFormatting {
     }
 }
 
+impl SemanticTokensResponse {
+    /// Applies the result of a `textDocument/semanticTokens/full/delta` request.
+    pub fn apply_delta(mut self, delta: Option<lsp::SemanticTokensFullDeltaResult>) -> Self {
+        match delta {
+            None => {
+                self.result_id = None;
+                self.data.clear();
+            }
+            Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => {
+                self.result_id = tokens.result_id;
+                self.data = flatten_semantic_tokens(&tokens.data);
+            }
+            Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+                self.result_id = delta.result_id;
+                self.apply_edits(delta.edits);
+            }
+            Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => {
+                self.result_id = None;
+                self.apply_edits(edits);
+            }
+        }
+        self
+    }
+
+    fn apply_edits(&mut self, mut edits: Vec<lsp::SemanticTokensEdit>) {
+        // Edit offsets refer to the original data, so apply them back to front.
+        edits.sort_by_key(|edit| Reverse(edit.start));
+        for edit in edits {
+            let start = (edit.start as usize).min(self.data.len());
+            let end = (start + edit.delete_count as usize).min(self.data.len());
+            let new_data = edit
+                .data
+                .as_deref()
+                .map(flatten_semantic_tokens)
+                .unwrap_or_default();
+            self.data.splice(start..end, new_data);
+        }
+    }
+
+    /// Resolves the relative token positions in the version of the buffer the tokens were
+    /// computed for, then moves them past the edits made to the buffer since. Tokens that were
+    /// edited are dropped.
+    pub fn decode(
+        &self,
+        snapshot: &text::BufferSnapshot,
+        version: &clock::Global,
+    ) -> Vec<SemanticToken> {
+        let mut edits = snapshot.edits_since::<PointUtf16>(version).peekable();
+        let mut last_edit_before = None::<text::Edit<PointUtf16>>;
+        let mut tokens = Vec::with_capacity(self.data.len() / 5);
+        let mut row = 0;
+        let mut column = 0;
+        for token in self.data.chunks_exact(5) {
+            let (delta_line, delta_start, length) = (token[0], token[1], token[2]);
+            let (token_type, modifiers_bitset) = (token[3], token[4]);
+            if delta_line > 0 {
+                row += delta_line;
+                column = delta_start;
+            } else {
+                column += delta_start;
+            }
+
+            let old_start = PointUtf16::new(row, column);
+            let old_end = PointUtf16::new(row, column + length);
+            while let Some(edit) = edits.next_if(|edit| edit.old.end <= old_start) {
+                last_edit_before = Some(edit);
+            }
+            if edits.peek().map_or(false, |edit| edit.old.start < old_end) {
+                continue;
+            }
+            let Some(token_type) = self.legend.token_types.get(token_type as usize) else {
+                continue;
+            };
+            let token_modifiers = self
+                .legend
+                .token_modifiers
+                .iter()
+                .take(u32::BITS as usize)
+                .enumerate()
+                .filter(|(ix, _)| modifiers_bitset & (1 << ix) != 0)
+                .map(|(_, modifier)| modifier.clone())
+                .collect();
+
+            let new_point = |point: PointUtf16| match &last_edit_before {
+                Some(edit) if point.row == edit.old.end.row => PointUtf16::new(
+                    edit.new.end.row,
+                    edit.new.end.column + point.column - edit.old.end.column,
+                ),
+                Some(edit) => PointUtf16::new(
+                    point.row - edit.old.end.row + edit.new.end.row,
+                    point.column,
+                ),
+                None => point,
+            };
+            let start = snapshot.clip_point_utf16(Unclipped(new_point(old_start)), Bias::Left);
+            let end = snapshot.clip_point_utf16(Unclipped(new_point(old_end)), Bias::Left);
+            tokens.push(SemanticToken {
+                range: snapshot.anchor_after(start)..snapshot.anchor_before(end),
+                token_type: token_type.clone(),
+                token_modifiers,
+            });
+        }
+        tokens
+    }
+}
+
+fn flatten_semantic_tokens(tokens: &[lsp::SemanticToken]) -> Vec<u32> {
+    tokens
+        .iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect()
+}
+
+pub(crate) fn semantic_tokens_options(
+    capabilities: &ServerCapabilities,
+) -> Option<&lsp::SemanticTokensOptions> {
+    match capabilities.semantic_tokens_provider.as_ref()? {
+        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => Some(options),
+        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            Some(&options.semantic_tokens_options)
+        }
+    }
+}
+
+pub(crate) fn supports_semantic_tokens_delta(capabilities: &ServerCapabilities) -> bool {
+    matches!(
+        semantic_tokens_options(capabilities).and_then(|options| options.full.as_ref()),
+        Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) })
+    )
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSemanticTokens {
+    type Response = SemanticTokensResponse;
+    type LspRequest = lsp::request::SemanticTokensFullRequest;
+    type ProtoRequest = proto::GetSemanticTokens;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        match semantic_tokens_options(capabilities).and_then(|options| options.full.as_ref()) {
+            Some(lsp::SemanticTokensFullOptions::Bool(full)) => *full,
+            Some(lsp::SemanticTokensFullOptions::Delta { .. }) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SemanticTokensParams {
+        lsp::SemanticTokensParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SemanticTokensResult>,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<SemanticTokensResponse> {
+        let legend = project.update(&mut cx, |project, _| {
+            project
+                .language_server_for_id(server_id)
+                .and_then(|server| {
+                    semantic_tokens_options(server.capabilities())
+                        .map(|options| SemanticTokensLegend::from_lsp(&options.legend))
+                })
+                .unwrap_or_default()
+        })?;
+        let (result_id, data) = match message {
+            Some(lsp::SemanticTokensResult::Tokens(tokens)) => {
+                (tokens.result_id, flatten_semantic_tokens(&tokens.data))
+            }
+            Some(lsp::SemanticTokensResult::Partial(partial)) => {
+                (None, flatten_semantic_tokens(&partial.data))
+            }
+            None => (None, Vec::new()),
+        };
+        Ok(SemanticTokensResponse {
+            server_id: Some(server_id),
+            result_id,
+            data,
+            legend,
+            version: self.version,
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSemanticTokens {
+        proto::GetSemanticTokens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSemanticTokens,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            version: Some(buffer.update(&mut cx, |buffer, _| buffer.version())?),
+        })
+    }
+
+    fn response_to_proto(
+        response: SemanticTokensResponse,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSemanticTokensResponse {
+        proto::GetSemanticTokensResponse {
+            data: response.data,
+            token_types: response
+                .legend
+                .token_types
+                .iter()
+                .map(|token_type| token_type.to_string())
+                .collect(),
+            token_modifiers: response
+                .legend
+                .token_modifiers
+                .iter()
+                .map(|modifier| modifier.to_string())
+                .collect(),
+            version: serialize_version(response.version.as_ref().unwrap_or(buffer_version)),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSemanticTokensResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<SemanticTokensResponse> {
+        let version = deserialize_version(&message.version);
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(version.clone())
+            })?
+            .await?;
+        Ok(SemanticTokensResponse {
+            server_id: None,
+            result_id: None,
+            data: message.data,
+            legend: SemanticTokensLegend {
+                token_types: message.token_types.into_iter().map(Arc::from).collect(),
+                token_modifiers: message.token_modifiers.into_iter().map(Arc::from).collect(),
+            },
+            version: Some(version),
+        })
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSemanticTokens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Vec<FoldRange<Anchor>>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        match &capabilities.folding_range_provider {
+            Some(lsp::FoldingRangeProviderCapability::Simple(enabled)) => *enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::FoldingRangeParams {
+        lsp::FoldingRangeParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::FoldingRange>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldRange<Anchor>>> {
+        buffer.update(&mut cx, |buffer, _| {
+            let max_point = buffer.max_point_utf16();
+            let point_for_line = |line: u32, character: Option<u32>| {
+                // Ranges without a character span whole lines.
+                let character = character.unwrap_or(u32::MAX);
+                let point = PointUtf16::new(line.min(max_point.row), character);
+                buffer.clip_point_utf16(Unclipped(point), Bias::Left)
+            };
+
+            let mut ranges = message
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|lsp_range| lsp_range.start_line < lsp_range.end_line)
+                .map(|lsp_range| {
+                    let start = point_for_line(lsp_range.start_line, lsp_range.start_character);
+                    let end = point_for_line(lsp_range.end_line, lsp_range.end_character);
+                    FoldRange {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        kind: match lsp_range.kind {
+                            Some(lsp::FoldingRangeKind::Comment) => FoldKind::Comment,
+                            Some(lsp::FoldingRangeKind::Imports) => FoldKind::Imports,
+                            _ => FoldKind::Region,
+                        },
+                    }
+                })
+                .collect::<Vec<_>>();
+            ranges.sort_by(|a, b| a.range.start.cmp(&b.range.start, buffer));
+            ranges
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        ranges: Vec<FoldRange<Anchor>>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetFoldingRangesResponse {
+        proto::GetFoldingRangesResponse {
+            ranges: ranges
+                .into_iter()
+                .map(|range| proto::FoldingRange {
+                    start: Some(serialize_anchor(&range.range.start)),
+                    end: Some(serialize_anchor(&range.range.end)),
+                    kind: match range.kind {
+                        FoldKind::Region => proto::folding_range::Kind::Region,
+                        FoldKind::Comment => proto::folding_range::Kind::Comment,
+                        FoldKind::Imports => proto::folding_range::Kind::Imports,
+                    } as i32,
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldRange<Anchor>>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .ranges
+            .into_iter()
+            .map(|range| {
+                let start = range
+                    .start
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid start"))?;
+                let end = range
+                    .end
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid end"))?;
+                let kind = match proto::folding_range::Kind::from_i32(range.kind) {
+                    Some(proto::folding_range::Kind::Comment) => FoldKind::Comment,
+                    Some(proto::folding_range::Kind::Imports) => FoldKind::Imports,
+                    _ => FoldKind::Region,
+                };
+                Ok(FoldRange {
+                    range: start..end,
+                    kind,
+                })
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetCodeLens {
+    pub fn project_to_proto_lens(lens: &CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: lens.server_id.0 as u64,
+            start: Some(serialize_anchor(&lens.range.start)),
+            end: Some(serialize_anchor(&lens.range.end)),
+            lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+        }
+    }
+
+    pub fn proto_to_project_lens(lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        Ok(CodeLens {
+            server_id: LanguageServerId(lens.server_id as usize),
+            range: start..end,
+            lsp_lens: serde_json::from_slice(&lens.lsp_lens)?,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetCodeLens {
+    type Response = Vec<CodeLens>;
+    type LspRequest = lsp::request::CodeLensRequest;
+    type ProtoRequest = proto::GetCodeLens;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        capabilities.code_lens_provider.is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CodeLensParams {
+        lsp::CodeLensParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CodeLens>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        buffer.update(&mut cx, |buffer, _| {
+            message
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lsp_lens| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_lens.range.start), Bias::Left);
+                    let end =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_lens.range.end), Bias::Left);
+                    CodeLens {
+                        server_id,
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        lsp_lens,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetCodeLens {
+        proto::GetCodeLens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetCodeLens,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        lenses: Vec<CodeLens>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetCodeLensResponse {
+        proto::GetCodeLensResponse {
+            lenses: lenses.iter().map(Self::project_to_proto_lens).collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCodeLensResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .lenses
+            .into_iter()
+            .map(Self::proto_to_project_lens)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetCodeLens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl From<lsp::CallHierarchyItem> for LspHierarchyItem {
+    fn from(item: lsp::CallHierarchyItem) -> Self {
+        Self {
+            json: serde_json::to_value(&item).unwrap(),
+            name: item.name,
+            kind: item.kind,
+            detail: item.detail,
+            uri: item.uri,
+            selection_range: item.selection_range,
+        }
+    }
+}
+
+impl From<lsp::TypeHierarchyItem> for LspHierarchyItem {
+    fn from(item: lsp::TypeHierarchyItem) -> Self {
+        Self {
+            json: serde_json::to_value(&item).unwrap(),
+            name: item.name,
+            kind: item.kind,
+            detail: item.detail,
+            uri: item.uri,
+            selection_range: item.selection_range,
+        }
+    }
+}
+
+async fn hierarchy_items_for_buffer(
+    lsp_items: Vec<LspHierarchyItem>,
+    project: Model<Project>,
+    buffer: Model<Buffer>,
+    server_id: LanguageServerId,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<HierarchyItem>> {
+    let source_worktree_id = buffer
+        .update(&mut cx, |buffer, cx| {
+            File::from_dyn(buffer.file()).map(|file| file.worktree_id(cx))
+        })?
+        .ok_or_else(|| anyhow!("buffer is not part of a worktree"))?;
+    let items = project.update(&mut cx, |project, cx| {
+        project.hierarchy_items_from_lsp(lsp_items, server_id, source_worktree_id, cx)
+    })?;
+    Ok(items.await)
+}
+
+async fn hierarchy_items_from_proto(
+    items: Vec<proto::HierarchyItem>,
+    project: Model<Project>,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<HierarchyItem>> {
+    let items = project.update(&mut cx, |project, _| {
+        items
+            .into_iter()
+            .map(|item| project.deserialize_hierarchy_item(item))
+            .collect::<Vec<_>>()
+    })?;
+    future::try_join_all(items).await
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareCallHierarchy {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::PrepareCallHierarchy;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        !matches!(
+            capabilities.call_hierarchy_provider,
+            None | Some(lsp::CallHierarchyServerCapability::Simple(false))
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyPrepareParams {
+        lsp::CallHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let lsp_items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(LspHierarchyItem::from)
+            .collect();
+        hierarchy_items_for_buffer(lsp_items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareCallHierarchy {
+        proto::PrepareCallHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareCallHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        items: Vec<HierarchyItem>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::PrepareCallHierarchyResponse {
+        proto::PrepareCallHierarchyResponse {
+            items: items.iter().map(serialize_hierarchy_item).collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareCallHierarchyResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message.items, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareCallHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareTypeHierarchy {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::PrepareTypeHierarchy;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        capabilities.type_hierarchy_provider.is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchyPrepareParams {
+        lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let lsp_items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(LspHierarchyItem::from)
+            .collect();
+        hierarchy_items_for_buffer(lsp_items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareTypeHierarchy {
+        proto::PrepareTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareTypeHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        items: Vec<HierarchyItem>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::PrepareTypeHierarchyResponse {
+        proto::PrepareTypeHierarchyResponse {
+            items: items.iter().map(serialize_hierarchy_item).collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareTypeHierarchyResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message.items, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
 impl InlayHints {
     pub async fn lsp_to_project_hint(
         lsp_hint: lsp::InlayHint,
@@ -22,6 +22,7 @@ use futures::{
     stream::FuturesUnordered,
     AsyncWriteExt, Future, FutureExt, StreamExt, TryFutureExt,
 };
+use git::blame::{Blame, BlameEntry};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use gpui::{
     AnyModel, AppContext, AsyncAppContext, BackgroundExecutor, Context, Entity, EventEmitter,
@@ -37,9 +38,10 @@ use language::{
     },
     range_from_lsp, range_to_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, Capability,
     CodeAction, CodeLabel, Completion, Diagnostic, DiagnosticEntry, DiagnosticSet, Diff,
-    Documentation, Event as BufferEvent, File as _, Language, LanguageRegistry, LanguageServerName,
-    LocalFile, LspAdapterDelegate, OffsetRangeExt, Operation, Patch, PendingLanguageServer,
-    PointUtf16, TextBufferSnapshot, ToOffset, ToPointUtf16, Transaction, Unclipped,
+    Documentation, Event as BufferEvent, File as _, FoldRange, Language, LanguageRegistry,
+    LanguageServerName, LocalFile, LspAdapterDelegate, OffsetRangeExt, Operation, Patch,
+    PendingLanguageServer, PointUtf16, TextBufferSnapshot, ToOffset, ToPointUtf16, Transaction,
+    Unclipped,
 };
 use log::error;
 use lsp::{
@@ -64,6 +66,7 @@ use sha2::{Digest, Sha256};
 use similar::{ChangeTag, TextDiff};
 use smol::channel::{Receiver, Sender};
 use smol::lock::Semaphore;
+use snippet_provider::{is_snippets_file, SnippetRegistry};
 use std::{
     cmp::{self, Ordering},
     convert::TryInto,
@@ -82,14 +85,16 @@ use std::{
     },
     time::{Duration, Instant},
 };
-use task::static_source::StaticSource;
+use task::{static_source::StaticSource, TaskId};
 use terminals::Terminals;
 use text::{Anchor, BufferId};
 use util::{
     debug_panic, defer,
     http::HttpClient,
     merge_json_value_into,
-    paths::{LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH},
+    paths::{
+        LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_SNIPPETS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH,
+    },
     post_inc, ResultExt, TryFutureExt as _,
 };
 
@@ -101,7 +106,7 @@ pub use project_core::project_settings;
 pub use project_core::worktree::{self, *};
 #[cfg(feature = "test-support")]
 pub use task_inventory::test_inventory::*;
-pub use task_inventory::{Inventory, TaskSourceKind};
+pub use task_inventory::{Inventory, RemoteTask, TaskSourceKind};
 
 const MAX_SERVER_REINSTALL_ATTEMPT_COUNT: u64 = 4;
 const SERVER_REINSTALL_DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
@@ -151,6 +156,7 @@ pub struct Project {
     /// Used for re-issuing buffer requests when peers temporarily disconnect
     incomplete_remote_buffers: HashMap<BufferId, Option<Model<Buffer>>>,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    semantic_tokens: HashMap<BufferId, BufferSemanticTokens>,
     buffers_being_formatted: HashSet<BufferId>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
     git_diff_debouncer: DebouncedDelay,
@@ -264,6 +270,7 @@ pub enum Event {
     WorktreeAdded,
     WorktreeRemoved(WorktreeId),
     WorktreeUpdatedEntries(WorktreeId, UpdatedEntriesSet),
+    WorktreeUpdatedGitRepositories,
     DiskBasedDiagnosticsStarted {
         language_server_id: LanguageServerId,
     },
@@ -278,6 +285,7 @@ pub enum Event {
     DisconnectedFromHost,
     Closed,
     DeletedEntry(ProjectEntryId),
+    BufferSaved(Model<Buffer>),
     CollaboratorUpdated {
         old_peer_id: proto::PeerId,
         new_peer_id: proto::PeerId,
@@ -285,7 +293,17 @@ pub enum Event {
     CollaboratorJoined(proto::PeerId),
     CollaboratorLeft(proto::PeerId),
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     RevealInProjectPanel(ProjectEntryId),
+    /// A guest asked the host to run one of its tasks. The `task_run_id`, unique to this request,
+    /// should be either passed to [`Project::accept_task_run`] or to [`Project::decline_task_run`].
+    TaskRunRequested {
+        requester: proto::PeerId,
+        task_run_id: u64,
+        task_id: TaskId,
+        label: String,
+    },
 }
 
 pub enum LanguageServerState {
@@ -430,6 +448,112 @@ impl Hover {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureHelp {
+    pub signatures: Vec<SignatureInformation>,
+    pub active_signature: usize,
+    pub active_parameter: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureInformation {
+    pub label: String,
+    pub documentation: Option<MarkupContent>,
+    pub parameters: Vec<ParameterInformation>,
+    pub active_parameter: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterInformation {
+    /// The byte range of this parameter within its signature's label.
+    pub label: Range<usize>,
+    pub documentation: Option<MarkupContent>,
+}
+
+impl SignatureHelp {
+    pub fn active_signature(&self) -> Option<&SignatureInformation> {
+        self.signatures.get(self.active_signature)
+    }
+
+    /// The parameter currently being typed, preferring the signature's own
+    /// active parameter over the one reported for the whole response.
+    pub fn active_parameter(&self) -> Option<&ParameterInformation> {
+        let signature = self.active_signature()?;
+        let ix = signature.active_parameter.or(self.active_parameter)?;
+        signature.parameters.get(ix)
+    }
+}
+
+/// A command shown inline above the code it applies to, such as "Run test" or "3 references".
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    pub server_id: LanguageServerId,
+    pub range: Range<Anchor>,
+    pub lsp_lens: lsp::CodeLens,
+}
+
+/// A function or a type in a call or type hierarchy.
+#[derive(Clone, Debug)]
+pub struct HierarchyItem {
+    pub symbol: Symbol,
+    pub detail: Option<String>,
+    /// The item as the language server sent it, which it expects back when asked for the
+    /// item's callers, callees, supertypes or subtypes.
+    lsp_item: serde_json::Value,
+}
+
+/// A neighbour of an item in a call or type hierarchy. For calls, `ranges` are the call
+/// sites: in the neighbour's file for incoming calls, in the item's file for outgoing ones.
+#[derive(Clone, Debug)]
+pub struct HierarchyCall {
+    pub item: HierarchyItem,
+    pub ranges: Vec<Range<Unclipped<PointUtf16>>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HierarchyDirection {
+    IncomingCalls,
+    OutgoingCalls,
+    Supertypes,
+    Subtypes,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SemanticTokensLegend {
+    pub token_types: Vec<Arc<str>>,
+    pub token_modifiers: Vec<Arc<str>>,
+}
+
+impl SemanticTokensLegend {
+    fn from_lsp(legend: &lsp::SemanticTokensLegend) -> Self {
+        Self {
+            token_types: legend
+                .token_types
+                .iter()
+                .map(|token_type| Arc::from(token_type.as_str()))
+                .collect(),
+            token_modifiers: legend
+                .token_modifiers
+                .iter()
+                .map(|modifier| Arc::from(modifier.as_str()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub range: Range<language::Anchor>,
+    pub token_type: Arc<str>,
+    pub token_modifiers: Vec<Arc<str>>,
+}
+
+struct BufferSemanticTokens {
+    version: clock::Global,
+    response: SemanticTokensResponse,
+    tokens: Arc<[SemanticToken]>,
+}
+
 #[derive(Default)]
 pub struct ProjectTransaction(pub HashMap<Model<Buffer>, language::Transaction>);
 
@@ -507,20 +631,29 @@ impl Project {
         client.add_model_request_handler(Self::handle_rename_project_entry);
         client.add_model_request_handler(Self::handle_copy_project_entry);
         client.add_model_request_handler(Self::handle_delete_project_entry);
+        client.add_model_request_handler(Self::handle_restore_project_entry);
         client.add_model_request_handler(Self::handle_expand_project_entry);
         client.add_model_request_handler(Self::handle_apply_additional_edits_for_completion);
         client.add_model_request_handler(Self::handle_resolve_completion_documentation);
         client.add_model_request_handler(Self::handle_apply_code_action);
+        client.add_model_request_handler(Self::handle_resolve_code_lens);
+        client.add_model_request_handler(Self::handle_apply_code_lens);
+        client.add_model_request_handler(Self::handle_refresh_code_lens);
         client.add_model_request_handler(Self::handle_on_type_formatting);
         client.add_model_request_handler(Self::handle_inlay_hints);
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
+        client.add_model_request_handler(Self::handle_refresh_semantic_tokens);
         client.add_model_request_handler(Self::handle_reload_buffers);
         client.add_model_request_handler(Self::handle_synchronize_buffers);
         client.add_model_request_handler(Self::handle_format_buffers);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCodeActions>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCompletions>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetHover>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSignatureHelp>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSemanticTokens>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCodeLens>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetTypeDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentHighlights>);
@@ -528,12 +661,19 @@ impl Project {
         client.add_model_request_handler(Self::handle_lsp_command::<PrepareRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_model_request_handler(Self::handle_search_project);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_get_hierarchy_children);
         client.add_model_request_handler(Self::handle_get_project_symbols);
         client.add_model_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_model_request_handler(Self::handle_open_buffer_by_id);
         client.add_model_request_handler(Self::handle_open_buffer_by_path);
         client.add_model_request_handler(Self::handle_save_buffer);
+        client.add_model_request_handler(Self::handle_blame_buffer);
         client.add_model_message_handler(Self::handle_update_diff_base);
+        client.add_model_request_handler(Self::handle_get_project_tasks);
+        client.add_model_request_handler(Self::handle_run_project_task);
+        client.add_model_message_handler(Self::handle_update_task_terminal);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
     }
 
@@ -566,6 +706,7 @@ impl Project {
                 local_buffer_ids_by_path: Default::default(),
                 local_buffer_ids_by_entry_id: Default::default(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
                 opened_buffer: watch::channel(),
@@ -593,9 +734,7 @@ impl Project {
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 nonce: StdRng::from_entropy().gen(),
-                terminals: Terminals {
-                    local_handles: Vec::new(),
-                },
+                terminals: Terminals::default(),
                 copilot_lsp_subscription,
                 copilot_log_subscription: None,
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
@@ -701,10 +840,9 @@ impl Project {
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
                 nonce: StdRng::from_entropy().gen(),
-                terminals: Terminals {
-                    local_handles: Vec::new(),
-                },
+                terminals: Terminals::default(),
                 copilot_lsp_subscription,
                 copilot_log_subscription: None,
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
@@ -1216,6 +1354,7 @@ impl Project {
     pub fn delete_entry(
         &mut self,
         entry_id: ProjectEntryId,
+        trash: bool,
         cx: &mut ModelContext<Self>,
     ) -> Option<Task<Result<()>>> {
         let worktree = self.worktree_for_entry(entry_id, cx)?;
@@ -1224,7 +1363,10 @@ impl Project {
 
         if self.is_local() {
             worktree.update(cx, |worktree, cx| {
-                worktree.as_local_mut().unwrap().delete_entry(entry_id, cx)
+                worktree
+                    .as_local_mut()
+                    .unwrap()
+                    .delete_entry(entry_id, trash, cx)
             })
         } else {
             let client = self.client.clone();
@@ -1234,6 +1376,7 @@ impl Project {
                     .request(proto::DeleteProjectEntry {
                         project_id,
                         entry_id: entry_id.to_proto(),
+                        use_trash: trash,
                     })
                     .await?;
                 worktree
@@ -1249,6 +1392,51 @@ impl Project {
         }
     }
 
+    /// Restores the entry most recently moved to the trash from the given path.
+    pub fn restore_entry(
+        &mut self,
+        project_path: impl Into<ProjectPath>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<Entry>>> {
+        let project_path = project_path.into();
+        let Some(worktree) = self.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Ok(None));
+        };
+        if self.is_local() {
+            worktree.update(cx, |worktree, cx| {
+                worktree
+                    .as_local_mut()
+                    .unwrap()
+                    .restore_entry(project_path.path, cx)
+            })
+        } else {
+            let client = self.client.clone();
+            let project_id = self.remote_id().unwrap();
+            cx.spawn(move |_, mut cx| async move {
+                let response = client
+                    .request(proto::RestoreProjectEntry {
+                        project_id,
+                        worktree_id: project_path.worktree_id.to_proto(),
+                        path: project_path.path.to_string_lossy().into(),
+                    })
+                    .await?;
+                match response.entry {
+                    Some(entry) => worktree
+                        .update(&mut cx, |worktree, cx| {
+                            worktree.as_remote_mut().unwrap().insert_entry(
+                                entry,
+                                response.worktree_scan_id as usize,
+                                cx,
+                            )
+                        })?
+                        .await
+                        .map(Some),
+                    None => Ok(None),
+                }
+            })
+        }
+    }
+
     pub fn expand_entry(
         &mut self,
         worktree_id: WorktreeId,
@@ -1878,9 +2066,14 @@ impl Project {
         };
         let worktree = file.worktree.clone();
         let path = file.path.clone();
-        worktree.update(cx, |worktree, cx| match worktree {
-            Worktree::Local(worktree) => worktree.save_buffer(buffer, path, false, cx),
-            Worktree::Remote(worktree) => worktree.save_buffer(buffer, cx),
+        let save = worktree.update(cx, |worktree, cx| match worktree {
+            Worktree::Local(worktree) => worktree.save_buffer(buffer.clone(), path, false, cx),
+            Worktree::Remote(worktree) => worktree.save_buffer(buffer.clone(), cx),
+        });
+        cx.spawn(move |this, mut cx| async move {
+            save.await?;
+            this.update(&mut cx, |_, cx| cx.emit(Event::BufferSaved(buffer)))?;
+            Ok(())
         })
     }
 
@@ -1913,6 +2106,7 @@ impl Project {
             this.update(&mut cx, |this, cx| {
                 this.detect_language_for_buffer(&buffer, cx);
                 this.register_buffer_with_language_servers(&buffer, cx);
+                cx.emit(Event::BufferSaved(buffer.clone()));
             })?;
             Ok(())
         })
@@ -2088,6 +2282,7 @@ impl Project {
                                 .unwrap_or_default(),
                             cx,
                         );
+                        buffer.set_signature_help_triggers(signature_help_triggers(&server), cx);
                     });
 
                     let snapshot = LspBufferSnapshot {
@@ -2125,6 +2320,7 @@ impl Project {
             }
 
             self.buffer_snapshots.remove(&buffer.remote_id());
+            self.semantic_tokens.remove(&buffer.remote_id());
             let file_url = lsp::Url::from_file_path(old_path).unwrap();
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server
@@ -3090,6 +3286,49 @@ impl Project {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::CodeLensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            cx.emit(Event::RefreshCodeLens);
+                            project.remote_id().map(|project_id| {
+                                project.client.send(proto::RefreshCodeLens { project_id })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            project
+                                .semantic_tokens
+                                .retain(|_, tokens| tokens.response.server_id != Some(server_id));
+                            cx.emit(Event::RefreshSemanticTokens);
+                            project.remote_id().map(|project_id| {
+                                project
+                                    .client
+                                    .send(proto::RefreshSemanticTokens { project_id })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -3290,7 +3529,9 @@ impl Project {
                             .and_then(|provider| provider.trigger_characters.clone())
                             .unwrap_or_default(),
                         cx,
-                    )
+                    );
+                    buffer
+                        .set_signature_help_triggers(signature_help_triggers(&language_server), cx);
                 });
             }
         }
@@ -3340,6 +3581,8 @@ impl Project {
             }
 
             self.language_server_statuses.remove(&server_id);
+            self.semantic_tokens
+                .retain(|_, tokens| tokens.response.server_id != Some(server_id));
             cx.notify();
 
             let server_state = self.language_servers.remove(&server_id);
@@ -3715,8 +3958,10 @@ impl Project {
         token: String,
         cx: &mut ModelContext<Self>,
     ) {
+        self.invalidate_semantic_tokens(language_server_id);
         if let Some(status) = self.language_server_statuses.get_mut(&language_server_id) {
             cx.emit(Event::RefreshInlayHints);
+            cx.emit(Event::RefreshSemanticTokens);
             status.pending_work.remove(&token);
             cx.notify();
         }
@@ -4900,110 +5145,503 @@ impl Project {
         }
     }
 
-    fn hover_impl(
+    /// Returns the functions at the given position, to be used as roots of a call hierarchy.
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
         &self,
         buffer: &Model<Buffer>,
-        position: PointUtf16,
+        position: T,
         cx: &mut ModelContext<Self>,
-    ) -> Task<Result<Option<Hover>>> {
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
         self.request_lsp(
             buffer.clone(),
             LanguageServerToQuery::Primary,
-            GetHover { position },
+            PrepareCallHierarchy { position },
             cx,
         )
     }
-    pub fn hover<T: ToPointUtf16>(
+
+    /// Returns the types at the given position, to be used as roots of a type hierarchy.
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
         &self,
         buffer: &Model<Buffer>,
         position: T,
         cx: &mut ModelContext<Self>,
-    ) -> Task<Result<Option<Hover>>> {
+    ) -> Task<Result<Vec<HierarchyItem>>> {
         let position = position.to_point_utf16(buffer.read(cx));
-        self.hover_impl(buffer, position, cx)
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
     }
 
-    #[inline(never)]
-    fn completions_impl(
+    /// Asks the language server that produced a hierarchy item for the item's neighbours
+    /// in the given direction.
+    pub fn hierarchy_children(
         &self,
-        buffer: &Model<Buffer>,
-        position: PointUtf16,
+        item: &HierarchyItem,
+        direction: HierarchyDirection,
         cx: &mut ModelContext<Self>,
-    ) -> Task<Result<Vec<Completion>>> {
+    ) -> Task<Result<Vec<HierarchyCall>>> {
         if self.is_local() {
-            let snapshot = buffer.read(cx).snapshot();
-            let offset = position.to_offset(&snapshot);
-            let scope = snapshot.language_scope_at(offset);
-
-            let server_ids: Vec<_> = self
-                .language_servers_for_buffer(buffer.read(cx), cx)
-                .filter(|(_, server)| server.capabilities().completion_provider.is_some())
-                .filter(|(adapter, _)| {
-                    scope
-                        .as_ref()
-                        .map(|scope| scope.language_allowed(&adapter.name))
-                        .unwrap_or(true)
-                })
-                .map(|(_, server)| server.server_id())
-                .collect();
+            let source_worktree_id = item.symbol.source_worktree_id;
+            let Some(server_id) = self
+                .language_server_ids
+                .get(&(source_worktree_id, item.symbol.language_server_name.clone()))
+                .copied()
+            else {
+                return Task::ready(Err(anyhow!(
+                    "language server for worktree and language not found"
+                )));
+            };
+            let Some(server) = self.language_server_for_id(server_id) else {
+                return Task::ready(Ok(Vec::new()));
+            };
 
-            let buffer = buffer.clone();
+            let lsp_item = item.lsp_item.clone();
             cx.spawn(move |this, mut cx| async move {
-                let mut tasks = Vec::with_capacity(server_ids.len());
-                this.update(&mut cx, |this, cx| {
-                    for server_id in server_ids {
-                        tasks.push(this.request_lsp(
-                            buffer.clone(),
-                            LanguageServerToQuery::Other(server_id),
-                            GetCompletions { position },
-                            cx,
-                        ));
-                    }
-                })?;
-
-                let mut completions = Vec::new();
-                for task in tasks {
-                    if let Ok(new_completions) = task.await {
-                        completions.extend_from_slice(&new_completions);
-                    }
-                }
+                let lsp_calls = match direction {
+                    HierarchyDirection::IncomingCalls => server
+                        .request::<lsp::request::CallHierarchyIncomingCalls>(
+                            lsp::CallHierarchyIncomingCallsParams {
+                                item: serde_json::from_value(lsp_item)?,
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await?
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|call| (LspHierarchyItem::from(call.from), call.from_ranges))
+                        .collect::<Vec<_>>(),
+                    HierarchyDirection::OutgoingCalls => server
+                        .request::<lsp::request::CallHierarchyOutgoingCalls>(
+                            lsp::CallHierarchyOutgoingCallsParams {
+                                item: serde_json::from_value(lsp_item)?,
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await?
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|call| (LspHierarchyItem::from(call.to), call.from_ranges))
+                        .collect(),
+                    HierarchyDirection::Supertypes => server
+                        .request::<lsp::request::TypeHierarchySupertypes>(
+                            lsp::TypeHierarchySupertypesParams {
+                                item: serde_json::from_value(lsp_item)?,
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await?
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|item| (LspHierarchyItem::from(item), Vec::new()))
+                        .collect(),
+                    HierarchyDirection::Subtypes => server
+                        .request::<lsp::request::TypeHierarchySubtypes>(
+                            lsp::TypeHierarchySubtypesParams {
+                                item: serde_json::from_value(lsp_item)?,
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await?
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|item| (LspHierarchyItem::from(item), Vec::new()))
+                        .collect(),
+                };
 
-                Ok(completions)
+                let (lsp_items, lsp_ranges): (Vec<_>, Vec<_>) = lsp_calls.into_iter().unzip();
+                let items = this
+                    .update(&mut cx, |this, cx| {
+                        this.hierarchy_items_from_lsp(lsp_items, server_id, source_worktree_id, cx)
+                    })?
+                    .await;
+                Ok(items
+                    .into_iter()
+                    .zip(lsp_ranges)
+                    .map(|(item, ranges)| HierarchyCall {
+                        item,
+                        ranges: ranges.into_iter().map(range_from_lsp).collect(),
+                    })
+                    .collect())
             })
         } else if let Some(project_id) = self.remote_id() {
-            self.send_lsp_proto_request(buffer.clone(), project_id, GetCompletions { position }, cx)
+            let direction = match direction {
+                HierarchyDirection::IncomingCalls => {
+                    proto::get_hierarchy_children::Direction::IncomingCalls
+                }
+                HierarchyDirection::OutgoingCalls => {
+                    proto::get_hierarchy_children::Direction::OutgoingCalls
+                }
+                HierarchyDirection::Supertypes => {
+                    proto::get_hierarchy_children::Direction::Supertypes
+                }
+                HierarchyDirection::Subtypes => proto::get_hierarchy_children::Direction::Subtypes,
+            };
+            let request = self.client.request(proto::GetHierarchyChildren {
+                project_id,
+                item: Some(serialize_hierarchy_item(item)),
+                direction: direction as i32,
+            });
+            cx.spawn(move |this, mut cx| async move {
+                let response = request.await?;
+                let calls = this.update(&mut cx, |this, _| {
+                    response
+                        .calls
+                        .into_iter()
+                        .map(|call| this.deserialize_hierarchy_call(call))
+                        .collect::<Vec<_>>()
+                })?;
+                futures::future::try_join_all(calls).await
+            })
         } else {
-            Task::ready(Ok(Default::default()))
+            Task::ready(Err(anyhow!("project does not have a remote id")))
         }
     }
-    pub fn completions<T: ToOffset + ToPointUtf16>(
+
+    /// Points the language server's copy of a hierarchy item sent by a guest at the file and
+    /// position of the item's symbol, whose signature has been checked, so that guests can't
+    /// make the language server read files outside of the project.
+    fn hierarchy_item_from_guest(
         &self,
-        buffer: &Model<Buffer>,
-        position: T,
-        cx: &mut ModelContext<Self>,
-    ) -> Task<Result<Vec<Completion>>> {
-        let position = position.to_point_utf16(buffer.read(cx));
-        self.completions_impl(buffer, position, cx)
+        mut item: HierarchyItem,
+        cx: &AppContext,
+    ) -> Result<HierarchyItem> {
+        let worktree = self
+            .worktree_for_id(item.symbol.path.worktree_id, cx)
+            .ok_or_else(|| anyhow!("worktree not found"))?;
+        let abs_path = worktree
+            .read(cx)
+            .as_local()
+            .ok_or_else(|| anyhow!("worktree is not local"))?
+            .abs_path()
+            .join(&item.symbol.path.path);
+        let uri = lsp::Url::from_file_path(&abs_path)
+            .map_err(|_| anyhow!("invalid symbol path {abs_path:?}"))?;
+        let selection_range = range_to_lsp(item.symbol.range.start.0..item.symbol.range.end.0);
+
+        let lsp_item = item
+            .lsp_item
+            .as_object_mut()
+            .ok_or_else(|| anyhow!("invalid hierarchy item"))?;
+        // The item's full range is kept only if it encloses the symbol.
+        let range = lsp_item
+            .get("range")
+            .and_then(|range| serde_json::from_value::<lsp::Range>(range.clone()).ok())
+            .filter(|range| {
+                range.start <= selection_range.start && selection_range.end <= range.end
+            })
+            .unwrap_or(selection_range);
+        lsp_item.insert("name".into(), item.symbol.name.clone().into());
+        lsp_item.insert("kind".into(), serde_json::to_value(item.symbol.kind)?);
+        lsp_item.insert("uri".into(), uri.to_string().into());
+        lsp_item.insert("range".into(), serde_json::to_value(range)?);
+        lsp_item.insert(
+            "selectionRange".into(),
+            serde_json::to_value(selection_range)?,
+        );
+        Ok(item)
     }
 
-    pub fn resolve_completions(
+    /// Turns hierarchy items sent by a language server into items pointing into the project,
+    /// the same way workspace symbols are. Items outside of any worktree are made relative to
+    /// the worktree the server was started for.
+    fn hierarchy_items_from_lsp(
         &self,
-        completion_indices: Vec<usize>,
-        completions: Arc<RwLock<Box<[Completion]>>>,
-        cx: &mut ModelContext<Self>,
-    ) -> Task<Result<bool>> {
-        let client = self.client();
-        let language_registry = self.languages().clone();
-
-        let is_remote = self.is_remote();
-        let project_id = self.remote_id();
-
-        cx.spawn(move |this, mut cx| async move {
-            let mut did_resolve = false;
-            if is_remote {
-                let project_id =
-                    project_id.ok_or_else(|| anyhow!("Remote project without remote_id"))?;
-
+        lsp_items: Vec<LspHierarchyItem>,
+        server_id: LanguageServerId,
+        source_worktree_id: WorktreeId,
+        cx: &AppContext,
+    ) -> impl Future<Output = Vec<HierarchyItem>> {
+        let mut items = Vec::new();
+        let worktree_abs_path = self
+            .worktree_for_id(source_worktree_id, cx)
+            .and_then(|worktree| worktree.read(cx).as_local())
+            .map(|worktree| worktree.abs_path().clone());
+        if let (
+            Some(LanguageServerState::Running {
+                adapter, language, ..
+            }),
+            Some(worktree_abs_path),
+        ) = (self.language_servers.get(&server_id), worktree_abs_path)
+        {
+            for lsp_item in lsp_items {
+                let Ok(abs_path) = lsp_item.uri.to_file_path() else {
+                    continue;
+                };
+                let project_path = match self.find_local_worktree(&abs_path, cx) {
+                    Some((worktree, rel_path)) => ProjectPath {
+                        worktree_id: worktree.read(cx).id(),
+                        path: rel_path.into(),
+                    },
+                    None => ProjectPath {
+                        worktree_id: source_worktree_id,
+                        path: relativize_path(&worktree_abs_path, &abs_path).into(),
+                    },
+                };
+                let signature = self.symbol_signature(&project_path);
+                let adapter_language = language.clone();
+                let language = self
+                    .languages
+                    .language_for_file(&project_path.path, None)
+                    .unwrap_or_else(move |_| adapter_language);
+                let language_server_name = adapter.name.clone();
+                items.push(async move {
+                    let language = language.await;
+                    let label = language
+                        .label_for_symbol(&lsp_item.name, lsp_item.kind)
+                        .await;
+                    HierarchyItem {
+                        symbol: Symbol {
+                            language_server_name,
+                            source_worktree_id,
+                            path: project_path,
+                            label: label
+                                .unwrap_or_else(|| CodeLabel::plain(lsp_item.name.clone(), None)),
+                            kind: lsp_item.kind,
+                            name: lsp_item.name,
+                            range: range_from_lsp(lsp_item.selection_range),
+                            signature,
+                        },
+                        detail: lsp_item.detail,
+                        lsp_item: lsp_item.json,
+                    }
+                });
+            }
+        }
+        futures::future::join_all(items)
+    }
+
+    fn hover_impl(
+        &self,
+        buffer: &Model<Buffer>,
+        position: PointUtf16,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<Hover>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetHover { position },
+            cx,
+        )
+    }
+    pub fn hover<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<Hover>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.hover_impl(buffer, position, cx)
+    }
+
+    pub fn signature_help<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<SignatureHelp>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSignatureHelp { position },
+            cx,
+        )
+    }
+
+    /// Whether typing `text` in the given buffer should open signature help. The
+    /// triggers come from the buffer's language server, and are replicated to guests.
+    pub fn is_signature_help_trigger(
+        &self,
+        buffer: &Model<Buffer>,
+        text: &str,
+        cx: &AppContext,
+    ) -> bool {
+        buffer
+            .read(cx)
+            .signature_help_triggers()
+            .iter()
+            .any(|trigger| trigger == text)
+    }
+
+    /// Returns the semantic tokens for the whole buffer, reusing the previous response when
+    /// the buffer hasn't changed and requesting only a delta when the server supports it.
+    pub fn semantic_tokens(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Arc<[SemanticToken]>>> {
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+        let snapshot = buffer.text_snapshot();
+        if let Some(cached) = self.semantic_tokens.get(&buffer_id) {
+            if cached.version == *snapshot.version() {
+                return Task::ready(Ok(cached.tokens.clone()));
+            }
+        }
+
+        let request = self
+            .semantic_tokens_delta(buffer_handle, cx)
+            .unwrap_or_else(|| {
+                self.request_lsp(
+                    buffer_handle.clone(),
+                    LanguageServerToQuery::Primary,
+                    GetSemanticTokens::default(),
+                    cx,
+                )
+            });
+        let buffer_handle = buffer_handle.clone();
+        cx.spawn(move |this, mut cx| async move {
+            let response = request.await?;
+            let version = response
+                .version
+                .clone()
+                .unwrap_or_else(|| snapshot.version().clone());
+            let tokens: Arc<[SemanticToken]> = buffer_handle
+                .update(&mut cx, |buffer, _| {
+                    response.decode(&buffer.text_snapshot(), &version)
+                })?
+                .into();
+            // An empty legend means no server answered, e.g. because it's still starting.
+            if response.legend.token_types.is_empty() {
+                return Ok(tokens);
+            }
+            this.update(&mut cx, |this, _| {
+                this.semantic_tokens.insert(
+                    buffer_id,
+                    BufferSemanticTokens {
+                        version,
+                        response,
+                        tokens: tokens.clone(),
+                    },
+                );
+            })?;
+            Ok(tokens)
+        })
+    }
+
+    fn semantic_tokens_delta(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<Task<Result<SemanticTokensResponse>>> {
+        let buffer = buffer_handle.read(cx);
+        let cached = self.semantic_tokens.get(&buffer.remote_id())?;
+        let previous_result_id = cached.response.result_id.clone()?;
+        let server_id = cached.response.server_id?;
+        let (_, server) = self.language_server_for_buffer(buffer, server_id, cx)?;
+        if !supports_semantic_tokens_delta(server.capabilities()) {
+            return None;
+        }
+
+        let abs_path = File::from_dyn(buffer.file())?.as_local()?.abs_path(cx);
+        let params = lsp::SemanticTokensDeltaParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(abs_path).ok()?,
+            ),
+            previous_result_id,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let server = server.clone();
+        let previous = cached.response.clone();
+        Some(cx.background_executor().spawn(async move {
+            let delta = server
+                .request::<lsp::request::SemanticTokensFullDeltaRequest>(params)
+                .await?;
+            Ok(previous.apply_delta(delta))
+        }))
+    }
+
+    #[inline(never)]
+    fn completions_impl(
+        &self,
+        buffer: &Model<Buffer>,
+        position: PointUtf16,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<Completion>>> {
+        if self.is_local() {
+            let snapshot = buffer.read(cx).snapshot();
+            let offset = position.to_offset(&snapshot);
+            let scope = snapshot.language_scope_at(offset);
+
+            let server_ids: Vec<_> = self
+                .language_servers_for_buffer(buffer.read(cx), cx)
+                .filter(|(_, server)| server.capabilities().completion_provider.is_some())
+                .filter(|(adapter, _)| {
+                    scope
+                        .as_ref()
+                        .map(|scope| scope.language_allowed(&adapter.name))
+                        .unwrap_or(true)
+                })
+                .map(|(_, server)| server.server_id())
+                .collect();
+
+            let buffer = buffer.clone();
+            cx.spawn(move |this, mut cx| async move {
+                let mut tasks = Vec::with_capacity(server_ids.len());
+                this.update(&mut cx, |this, cx| {
+                    for server_id in server_ids {
+                        tasks.push(this.request_lsp(
+                            buffer.clone(),
+                            LanguageServerToQuery::Other(server_id),
+                            GetCompletions { position },
+                            cx,
+                        ));
+                    }
+                })?;
+
+                let mut completions = Vec::new();
+                for task in tasks {
+                    if let Ok(new_completions) = task.await {
+                        completions.extend_from_slice(&new_completions);
+                    }
+                }
+
+                Ok(completions)
+            })
+        } else if let Some(project_id) = self.remote_id() {
+            self.send_lsp_proto_request(buffer.clone(), project_id, GetCompletions { position }, cx)
+        } else {
+            Task::ready(Ok(Default::default()))
+        }
+    }
+    pub fn completions<T: ToOffset + ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<Completion>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.completions_impl(buffer, position, cx)
+    }
+
+    pub fn resolve_completions(
+        &self,
+        completion_indices: Vec<usize>,
+        completions: Arc<RwLock<Box<[Completion]>>>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<bool>> {
+        let client = self.client();
+        let language_registry = self.languages().clone();
+
+        let is_remote = self.is_remote();
+        let project_id = self.remote_id();
+
+        cx.spawn(move |this, mut cx| async move {
+            let mut did_resolve = false;
+            if is_remote {
+                let project_id =
+                    project_id.ok_or_else(|| anyhow!("Remote project without remote_id"))?;
+
                 for completion_index in completion_indices {
                     let completions_guard = completions.read();
                     let completion = &completions_guard[completion_index];
@@ -5344,29 +5982,7 @@ impl Project {
                 }
 
                 if let Some(command) = action.lsp_action.command {
-                    this.update(&mut cx, |this, _| {
-                        this.last_workspace_edits_by_language_server
-                            .remove(&lang_server.server_id());
-                    })?;
-
-                    let result = lang_server
-                        .request::<lsp::request::ExecuteCommand>(lsp::ExecuteCommandParams {
-                            command: command.command,
-                            arguments: command.arguments.unwrap_or_default(),
-                            ..Default::default()
-                        })
-                        .await;
-
-                    if let Err(err) = result {
-                        // TODO: LSP ERROR
-                        return Err(err);
-                    }
-
-                    return Ok(this.update(&mut cx, |this, _| {
-                        this.last_workspace_edits_by_language_server
-                            .remove(&lang_server.server_id())
-                            .unwrap_or_default()
-                    })?);
+                    return Self::execute_lsp_command(this, lang_server, command, &mut cx).await;
                 }
 
                 Ok(ProjectTransaction::default())
@@ -5394,6 +6010,158 @@ impl Project {
         }
     }
 
+    /// Runs a command on the language server, collecting the workspace edits
+    /// it applies while doing so.
+    async fn execute_lsp_command(
+        this: WeakModel<Self>,
+        lang_server: Arc<LanguageServer>,
+        command: lsp::Command,
+        cx: &mut AsyncAppContext,
+    ) -> Result<ProjectTransaction> {
+        this.update(cx, |this, _| {
+            this.last_workspace_edits_by_language_server
+                .remove(&lang_server.server_id());
+        })?;
+
+        let result = lang_server
+            .request::<lsp::request::ExecuteCommand>(lsp::ExecuteCommandParams {
+                command: command.command,
+                arguments: command.arguments.unwrap_or_default(),
+                ..Default::default()
+            })
+            .await;
+
+        if let Err(err) = result {
+            // TODO: LSP ERROR
+            return Err(err);
+        }
+
+        Ok(this.update(cx, |this, _| {
+            this.last_workspace_edits_by_language_server
+                .remove(&lang_server.server_id())
+                .unwrap_or_default()
+        })?)
+    }
+
+    pub fn code_lens(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        self.request_lsp(
+            buffer_handle.clone(),
+            LanguageServerToQuery::Primary,
+            GetCodeLens,
+            cx,
+        )
+    }
+
+    pub fn folding_ranges(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<FoldRange<Anchor>>>> {
+        self.request_lsp(
+            buffer_handle.clone(),
+            LanguageServerToQuery::Primary,
+            GetFoldingRanges,
+            cx,
+        )
+    }
+
+    /// Fills in the command of a lens that the language server sent without one.
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        if lens.lsp_lens.command.is_some() {
+            return Task::ready(Ok(lens));
+        }
+
+        if self.is_local() {
+            let Some(lang_server) = self.language_server_for_id(lens.server_id) else {
+                return Task::ready(Ok(lens));
+            };
+            let can_resolve = lang_server
+                .capabilities()
+                .code_lens_provider
+                .as_ref()
+                .and_then(|options| options.resolve_provider)
+                .unwrap_or(false);
+            if !can_resolve {
+                return Task::ready(Ok(lens));
+            }
+
+            cx.background_executor().spawn(async move {
+                let lsp_lens = lang_server
+                    .request::<lsp::request::CodeLensResolve>(lens.lsp_lens.clone())
+                    .await?;
+                Ok(CodeLens { lsp_lens, ..lens })
+            })
+        } else if let Some(project_id) = self.remote_id() {
+            let client = self.client.clone();
+            let request = proto::ResolveCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(GetCodeLens::project_to_proto_lens(&lens)),
+            };
+            cx.background_executor().spawn(async move {
+                let response = client.request(request).await?;
+                GetCodeLens::proto_to_project_lens(
+                    response.lens.ok_or_else(|| anyhow!("missing lens"))?,
+                )
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    /// Executes the lens command on the language server that provided the lens.
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        push_to_history: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        if self.is_local() {
+            let Some(command) = lens.lsp_lens.command else {
+                return Task::ready(Ok(Default::default()));
+            };
+            let Some((_, lang_server)) =
+                self.language_server_for_buffer(buffer_handle.read(cx), lens.server_id, cx)
+            else {
+                return Task::ready(Ok(Default::default()));
+            };
+            let lang_server = lang_server.clone();
+            cx.spawn(move |this, mut cx| async move {
+                Self::execute_lsp_command(this, lang_server, command, &mut cx).await
+            })
+        } else if let Some(project_id) = self.remote_id() {
+            let client = self.client.clone();
+            let request = proto::ApplyCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(GetCodeLens::project_to_proto_lens(&lens)),
+            };
+            cx.spawn(move |this, mut cx| async move {
+                let response = client
+                    .request(request)
+                    .await?
+                    .transaction
+                    .ok_or_else(|| anyhow!("missing transaction"))?;
+                this.update(&mut cx, |this, cx| {
+                    this.deserialize_project_transaction(response, push_to_history, cx)
+                })?
+                .await
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
     fn apply_on_type_formatting(
         &self,
         buffer: Model<Buffer>,
@@ -5587,6 +6355,7 @@ impl Project {
                         .map(|options| fs::RemoveOptions {
                             recursive: options.recursive.unwrap_or(false),
                             ignore_if_not_exists: options.ignore_if_not_exists.unwrap_or(false),
+                            trash: false,
                         })
                         .unwrap_or_default();
                     if abs_path.ends_with("/") {
@@ -6586,6 +7355,8 @@ impl Project {
             self.language_server_ids
                 .remove(&(id_to_remove, server_name));
             self.language_server_statuses.remove(&server_id_to_remove);
+            self.semantic_tokens
+                .retain(|_, tokens| tokens.response.server_id != Some(server_id_to_remove));
             self.last_workspace_edits_by_language_server
                 .remove(&server_id_to_remove);
             self.language_servers.remove(&server_id_to_remove);
@@ -6656,7 +7427,8 @@ impl Project {
                     ));
                 }
                 worktree::Event::UpdatedGitRepositories(updated_repos) => {
-                    this.update_local_worktree_buffers_git_repos(worktree, updated_repos, cx)
+                    this.update_local_worktree_buffers_git_repos(worktree, updated_repos, cx);
+                    cx.emit(Event::WorktreeUpdatedGitRepositories);
                 }
             })
             .detach();
@@ -6682,6 +7454,7 @@ impl Project {
                     .clear_local_settings(handle_id.as_u64() as usize, cx)
                     .log_err()
             });
+            SnippetRegistry::default_global(cx).remove_worktree(handle_id.as_u64() as usize);
         })
         .detach();
 
@@ -7042,6 +7815,28 @@ impl Project {
                         );
                     }
                 })
+            } else if path
+                .parent()
+                .map_or(false, |dir| dir.ends_with(&*LOCAL_SNIPPETS_RELATIVE_PATH))
+                && is_snippets_file(path)
+            {
+                let snippet_registry = SnippetRegistry::default_global(cx);
+                if removed {
+                    snippet_registry.remove_file(&abs_path);
+                } else {
+                    let fs = self.fs.clone();
+                    let worktree_root_id = worktree_id.as_u64() as usize;
+                    cx.background_executor()
+                        .spawn(async move {
+                            let content = fs.load(&abs_path).await?;
+                            snippet_registry.insert_file(
+                                abs_path.into(),
+                                Some(worktree_root_id),
+                                &content,
+                            )
+                        })
+                        .detach_and_log_err(cx);
+                }
             }
         }
 
@@ -7083,6 +7878,120 @@ impl Project {
         .detach();
     }
 
+    /// Blames the given buffer with its current contents, so that unsaved edits are
+    /// blamed as uncommitted changes.
+    pub fn blame_buffer(&self, buffer: &Model<Buffer>, cx: &AppContext) -> Task<Result<Blame>> {
+        if self.is_local() {
+            let repo = self.local_git_repository_for_buffer(buffer, cx);
+            let content = buffer.read(cx).as_rope().clone();
+            cx.background_executor().spawn(async move {
+                let (repo, relative_path) = repo?;
+                // Release the repository's lock before running `git blame`, which can be
+                // slow for large files.
+                let blame = repo.lock().blame(&relative_path)?;
+                blame(content)
+            })
+        } else {
+            let project_id = self.remote_id();
+            let buffer = buffer.read(cx);
+            let request = proto::BlameBuffer {
+                project_id: project_id.unwrap_or_default(),
+                buffer_id: buffer.remote_id().into(),
+                version: serialize_version(&buffer.version()),
+            };
+            let client = self.client.clone();
+            cx.spawn(|_| async move {
+                project_id.context("project is not shared")?;
+                let response = client.request(request).await?;
+                Ok(deserialize_blame_buffer_response(response))
+            })
+        }
+    }
+
+    /// Loads the contents of the buffer's file in the HEAD commit of its git repository.
+    pub fn load_head_text(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        let repo = self.local_git_repository_for_buffer(buffer, cx);
+        cx.background_executor().spawn(async move {
+            let (repo, relative_path) = repo?;
+            let head_text = repo.lock().load_head_text(&relative_path);
+            Ok(head_text)
+        })
+    }
+
+    /// Writes the given text to the git index as the contents of the buffer's file,
+    /// and then uses it as the buffer's diff base.
+    pub fn set_index_text(
+        &mut self,
+        buffer: &Model<Buffer>,
+        index_text: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let repo = self.local_git_repository_for_buffer(buffer, cx);
+        let buffer = buffer.clone();
+        let remote_id = self.remote_id();
+        let client = self.client.clone();
+        cx.spawn(move |_, mut cx| async move {
+            let (repo, relative_path) = repo?;
+            cx.background_executor()
+                .spawn({
+                    let index_text = index_text.clone();
+                    async move { repo.lock().set_index_text(&relative_path, index_text) }
+                })
+                .await?;
+
+            // Update the diff base right away, rather than waiting for the worktree
+            // to observe the change to the index.
+            let buffer_id = buffer.update(&mut cx, |buffer, cx| {
+                buffer.set_diff_base(index_text.clone(), cx);
+                buffer.remote_id().into()
+            })?;
+            if let Some(project_id) = remote_id {
+                client
+                    .send(proto::UpdateDiffBase {
+                        project_id,
+                        buffer_id,
+                        diff_base: index_text,
+                    })
+                    .log_err();
+            }
+            Ok(())
+        })
+    }
+
+    fn local_git_repository_for_buffer(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Result<(Arc<Mutex<dyn repository::GitRepository>>, PathBuf)> {
+        if !self.is_local() {
+            return Err(anyhow!(
+                "git repositories of remote projects cannot be accessed"
+            ));
+        }
+
+        let file = File::from_dyn(buffer.read(cx).file()).context("buffer has no file")?;
+        let snapshot = file
+            .worktree
+            .read(cx)
+            .as_local()
+            .context("worktree was not local")?
+            .snapshot();
+        let (work_directory, repo) = snapshot
+            .repository_and_work_directory_for_path(&file.path)
+            .context("buffer is not in a git repository")?;
+        let repo = snapshot
+            .get_local_repo(&repo)
+            .context("failed to get the git repository of the buffer")?
+            .repo()
+            .clone();
+        let relative_path = file.path.strip_prefix(&work_directory)?.to_path_buf();
+        Ok((repo, relative_path))
+    }
+
     pub fn set_active_path(&mut self, entry: Option<ProjectPath>, cx: &mut ModelContext<Self>) {
         let new_active_entry = entry.and_then(|project_path| {
             let worktree = self.worktree_for_id(project_path.worktree_id, cx)?;
@@ -7158,9 +8067,22 @@ impl Project {
         language_server_id: LanguageServerId,
         cx: &mut ModelContext<Self>,
     ) {
+        self.invalidate_semantic_tokens(language_server_id);
         cx.emit(Event::DiskBasedDiagnosticsFinished { language_server_id });
     }
 
+    /// Forgets the semantic tokens reported by a language server, which can change once it's
+    /// done with its work, e.g. indexing or checking the project on disk, even if the buffers
+    /// didn't. Tokens of remote projects are forgotten for every server of the host.
+    fn invalidate_semantic_tokens(&mut self, language_server_id: LanguageServerId) {
+        self.semantic_tokens.retain(|_, tokens| {
+            tokens
+                .response
+                .server_id
+                .map_or(false, |server_id| server_id != language_server_id)
+        });
+    }
+
     pub fn active_entry(&self) -> Option<ProjectEntryId> {
         self.active_entry
     }
@@ -7471,7 +8393,7 @@ impl Project {
                 worktree
                     .as_local_mut()
                     .unwrap()
-                    .delete_entry(entry_id, cx)
+                    .delete_entry(entry_id, envelope.payload.use_trash, cx)
                     .ok_or_else(|| anyhow!("invalid entry"))
             })??
             .await?;
@@ -7481,6 +8403,30 @@ impl Project {
         })
     }
 
+    async fn handle_restore_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RestoreProjectEntry>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let worktree = this.update(&mut cx, |this, cx| {
+            let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+            this.worktree_for_id(worktree_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))
+        })??;
+        let worktree_scan_id = worktree.update(&mut cx, |worktree, _| worktree.scan_id())?;
+        let entry = worktree
+            .update(&mut cx, |worktree, cx| {
+                let path = PathBuf::from(envelope.payload.path);
+                worktree.as_local_mut().unwrap().restore_entry(path, cx)
+            })?
+            .await?;
+        Ok(proto::ProjectEntryResponse {
+            entry: entry.as_ref().map(|e| e.into()),
+            worktree_scan_id: worktree_scan_id as u64,
+        })
+    }
+
     async fn handle_expand_project_entry(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::ExpandProjectEntry>,
@@ -7811,6 +8757,30 @@ impl Project {
         })?)
     }
 
+    async fn handle_blame_buffer(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::BlameBuffer>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::BlameBufferResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, _| {
+            this.opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await?;
+        let blame = this
+            .update(&mut cx, |this, cx| this.blame_buffer(&buffer, cx))?
+            .await?;
+        Ok(serialize_blame_buffer_response(blame))
+    }
+
     async fn handle_reload_buffers(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::ReloadBuffers>,
@@ -8070,6 +9040,66 @@ impl Project {
         })
     }
 
+    async fn handle_resolve_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveCodeLens>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveCodeLensResponse> {
+        let lens = GetCodeLens::proto_to_project_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid lens"))?,
+        )?;
+        let resolve_code_lens = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this
+                .opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))?;
+            Ok::<_, anyhow::Error>(this.resolve_code_lens(&buffer, lens, cx))
+        })??;
+
+        let lens = resolve_code_lens.await?;
+        Ok(proto::ResolveCodeLensResponse {
+            lens: Some(GetCodeLens::project_to_proto_lens(&lens)),
+        })
+    }
+
+    async fn handle_apply_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ApplyCodeLens>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ApplyCodeLensResponse> {
+        let sender_id = envelope.original_sender_id()?;
+        let lens = GetCodeLens::proto_to_project_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid lens"))?,
+        )?;
+        let apply_code_lens = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this
+                .opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))?;
+            Ok::<_, anyhow::Error>(this.apply_code_lens(buffer, lens, false, cx))
+        })??;
+
+        let project_transaction = apply_code_lens.await?;
+        let project_transaction = this.update(&mut cx, |this, cx| {
+            this.serialize_project_transaction_for_peer(project_transaction, sender_id, cx)
+        })?;
+        Ok(proto::ApplyCodeLensResponse {
+            transaction: Some(project_transaction),
+        })
+    }
+
     async fn handle_on_type_formatting(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::OnTypeFormatting>,
@@ -8199,6 +9229,31 @@ impl Project {
         Ok(proto::Ack {})
     }
 
+    async fn handle_refresh_code_lens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshCodeLens>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(Event::RefreshCodeLens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_refresh_semantic_tokens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |this, cx| {
+            this.semantic_tokens.clear();
+            cx.emit(Event::RefreshSemanticTokens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_lsp_command<T: LspCommand>(
         this: Model<Self>,
         envelope: TypedEnvelope<T::ProtoRequest>,
@@ -8262,6 +9317,46 @@ impl Project {
         })
     }
 
+    async fn handle_get_hierarchy_children(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetHierarchyChildren>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetHierarchyChildrenResponse> {
+        let direction = match envelope.payload.direction() {
+            proto::get_hierarchy_children::Direction::IncomingCalls => {
+                HierarchyDirection::IncomingCalls
+            }
+            proto::get_hierarchy_children::Direction::OutgoingCalls => {
+                HierarchyDirection::OutgoingCalls
+            }
+            proto::get_hierarchy_children::Direction::Supertypes => HierarchyDirection::Supertypes,
+            proto::get_hierarchy_children::Direction::Subtypes => HierarchyDirection::Subtypes,
+        };
+        let item = envelope
+            .payload
+            .item
+            .ok_or_else(|| anyhow!("invalid item"))?;
+        let item = this
+            .update(&mut cx, |this, _| this.deserialize_hierarchy_item(item))?
+            .await?;
+        let calls = this
+            .update(&mut cx, |this, cx| {
+                let signature = this.symbol_signature(&item.symbol.path);
+                if signature == item.symbol.signature {
+                    let item = this.hierarchy_item_from_guest(item, cx)?;
+                    Ok(this.hierarchy_children(&item, direction, cx))
+                } else {
+                    Err(anyhow!("invalid symbol signature"))
+                }
+            })??
+            .await?;
+
+        Ok(proto::GetHierarchyChildrenResponse {
+            calls: calls.iter().map(serialize_hierarchy_call).collect(),
+        })
+    }
+
     async fn handle_search_project(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::SearchProject>,
@@ -8743,6 +9838,46 @@ impl Project {
         }
     }
 
+    fn deserialize_hierarchy_item(
+        &self,
+        serialized_item: proto::HierarchyItem,
+    ) -> impl Future<Output = Result<HierarchyItem>> {
+        let symbol = serialized_item
+            .symbol
+            .map(|symbol| self.deserialize_symbol(symbol));
+        async move {
+            Ok(HierarchyItem {
+                symbol: symbol.ok_or_else(|| anyhow!("invalid symbol"))?.await?,
+                detail: serialized_item.detail,
+                lsp_item: serde_json::from_slice(&serialized_item.lsp_item)?,
+            })
+        }
+    }
+
+    fn deserialize_hierarchy_call(
+        &self,
+        serialized_call: proto::HierarchyCall,
+    ) -> impl Future<Output = Result<HierarchyCall>> {
+        let item = serialized_call
+            .item
+            .map(|item| self.deserialize_hierarchy_item(item));
+        async move {
+            let mut ranges = Vec::new();
+            for range in serialized_call.ranges {
+                let start = range.start.ok_or_else(|| anyhow!("invalid start"))?;
+                let end = range.end.ok_or_else(|| anyhow!("invalid end"))?;
+                ranges.push(
+                    Unclipped(PointUtf16::new(start.row, start.column))
+                        ..Unclipped(PointUtf16::new(end.row, end.column)),
+                );
+            }
+            Ok(HierarchyCall {
+                item: item.ok_or_else(|| anyhow!("invalid item"))?.await?,
+                ranges,
+            })
+        }
+    }
+
     async fn handle_buffer_saved(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::BufferSaved>,
@@ -9252,6 +10387,73 @@ impl LspAdapterDelegate for ProjectLspAdapterDelegate {
     }
 }
 
+fn serialize_hierarchy_item(item: &HierarchyItem) -> proto::HierarchyItem {
+    proto::HierarchyItem {
+        symbol: Some(serialize_symbol(&item.symbol)),
+        detail: item.detail.clone(),
+        lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+    }
+}
+
+fn serialize_hierarchy_call(call: &HierarchyCall) -> proto::HierarchyCall {
+    proto::HierarchyCall {
+        item: Some(serialize_hierarchy_item(&call.item)),
+        ranges: call
+            .ranges
+            .iter()
+            .map(|range| proto::HierarchyCallRange {
+                start: Some(proto::PointUtf16 {
+                    row: range.start.0.row,
+                    column: range.start.0.column,
+                }),
+                end: Some(proto::PointUtf16 {
+                    row: range.end.0.row,
+                    column: range.end.0.column,
+                }),
+            })
+            .collect(),
+    }
+}
+
+fn serialize_blame_buffer_response(blame: Blame) -> proto::BlameBufferResponse {
+    proto::BlameBufferResponse {
+        entries: blame
+            .entries
+            .into_iter()
+            .map(|entry| proto::BlameEntry {
+                sha: entry.sha,
+                start_line: entry.range.start,
+                end_line: entry.range.end,
+                original_line_number: entry.original_line_number,
+                author: entry.author,
+                author_mail: entry.author_mail,
+                author_time: entry.author_time,
+                author_tz: entry.author_tz,
+                summary: entry.summary,
+            })
+            .collect(),
+    }
+}
+
+fn deserialize_blame_buffer_response(response: proto::BlameBufferResponse) -> Blame {
+    Blame {
+        entries: response
+            .entries
+            .into_iter()
+            .map(|entry| BlameEntry {
+                sha: entry.sha,
+                range: entry.start_line..entry.end_line,
+                original_line_number: entry.original_line_number,
+                author: entry.author,
+                author_mail: entry.author_mail,
+                author_time: entry.author_time,
+                author_tz: entry.author_tz,
+                summary: entry.summary,
+            })
+            .collect(),
+    }
+}
+
 fn serialize_symbol(symbol: &Symbol) -> proto::Symbol {
     proto::Symbol {
         language_server_name: symbol.language_server_name.0.to_string(),
@@ -9358,6 +10560,23 @@ fn include_text(server: &lsp::LanguageServer) -> bool {
         .unwrap_or(false)
 }
 
+/// The characters that open signature help, followed by the ones that update it.
+fn signature_help_triggers(server: &lsp::LanguageServer) -> Vec<String> {
+    server
+        .capabilities()
+        .signature_help_provider
+        .iter()
+        .flat_map(|options| {
+            options
+                .trigger_characters
+                .iter()
+                .chain(options.retrigger_characters.iter())
+                .flatten()
+                .cloned()
+        })
+        .collect()
+}
+
 async fn load_shell_environment(dir: &Path) -> Result<HashMap<String, String>> {
     let marker = "ZED_SHELL_START";
     let shell = env::var("SHELL").context(
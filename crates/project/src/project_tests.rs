@@ -2422,6 +2422,184 @@ async fn test_definition(cx: &mut gpui::TestAppContext) {
     }
 }
 
+#[gpui::test]
+async fn test_call_and_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: serde_json::from_value(json!({
+                "callHierarchyProvider": {},
+                "typeHierarchyProvider": {},
+            }))
+            .unwrap(),
+            ..Default::default()
+        }))
+        .await;
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "main.rs": "struct Base;\nstruct Derived;\n\nfn main() {\n    one();\n}\n\nfn one() {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    project.update(cx, |project, _| project.languages.add(Arc::new(language)));
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/main.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let uri = Url::from_file_path("/dir/main.rs").unwrap();
+    let range = |line: u32, column: u32, len: u32| {
+        lsp::Range::new(
+            lsp::Position::new(line, column),
+            lsp::Position::new(line, column + len),
+        )
+    };
+    let call_item = |name: &str, line: u32| lsp::CallHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri: uri.clone(),
+        range: range(line, 0, 11),
+        selection_range: range(line, 3, name.len() as u32),
+        data: Some(json!(name)),
+    };
+    let type_item = |name: &str, line: u32| lsp::TypeHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::STRUCT,
+        tags: None,
+        detail: None,
+        uri: uri.clone(),
+        range: range(line, 0, 8 + name.len() as u32),
+        selection_range: range(line, 7, name.len() as u32),
+        data: Some(json!(name)),
+    };
+    fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+        let main = call_item("main", 3);
+        move |_, _| {
+            let main = main.clone();
+            async move { Ok(Some(vec![main])) }
+        }
+    });
+    fake_server.handle_request::<lsp::request::CallHierarchyOutgoingCalls, _, _>({
+        let one = call_item("one", 7);
+        move |params, _| {
+            let one = one.clone();
+            async move {
+                assert_eq!(params.item.data, Some(json!("main")));
+                Ok(Some(vec![lsp::CallHierarchyOutgoingCall {
+                    to: one,
+                    from_ranges: vec![range(4, 4, 3)],
+                }]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>({
+        let derived = type_item("Derived", 1);
+        move |_, _| {
+            let derived = derived.clone();
+            async move { Ok(Some(vec![derived])) }
+        }
+    });
+    fake_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>({
+        let base = type_item("Base", 0);
+        move |params, _| {
+            let base = base.clone();
+            async move {
+                assert_eq!(params.item.data, Some(json!("Derived")));
+                Ok(Some(vec![base]))
+            }
+        }
+    });
+
+    // Expand the functions called by `main`.
+    let roots = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, Point::new(3, 4), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].symbol.name, "main");
+    let calls = project
+        .update(cx, |project, cx| {
+            project.hierarchy_children(&roots[0], HierarchyDirection::OutgoingCalls, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].item.symbol.name, "one");
+    assert_eq!(&*calls[0].item.symbol.path.path, Path::new("main.rs"));
+    assert_eq!(
+        calls[0].item.symbol.range,
+        Unclipped(PointUtf16::new(7, 3))..Unclipped(PointUtf16::new(7, 6))
+    );
+    assert_eq!(
+        calls[0]
+            .ranges
+            .iter()
+            .map(|range| range.start.0..range.end.0)
+            .collect::<Vec<_>>(),
+        vec![PointUtf16::new(4, 4)..PointUtf16::new(4, 7)]
+    );
+
+    // Expand the supertypes of `Derived`.
+    let roots = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, Point::new(1, 8), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].symbol.name, "Derived");
+    let supertypes = project
+        .update(cx, |project, cx| {
+            project.hierarchy_children(&roots[0], HierarchyDirection::Supertypes, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].item.symbol.name, "Base");
+    assert!(supertypes[0].ranges.is_empty());
+
+    // Items sent by guests point at their symbol's file and position, whatever the guest
+    // put in the language server's copy of the item.
+    let mut item = roots[0].clone();
+    item.lsp_item["uri"] = json!("file:///etc/passwd");
+    item.lsp_item["selectionRange"] = serde_json::to_value(range(20, 0, 3)).unwrap();
+    let item = project
+        .read_with(cx, |project, cx| {
+            project.hierarchy_item_from_guest(item, cx)
+        })
+        .unwrap();
+    assert_eq!(item.lsp_item["uri"], json!(uri.to_string()));
+    assert_eq!(
+        item.lsp_item["selectionRange"],
+        serde_json::to_value(range(1, 7, 7)).unwrap()
+    );
+    assert_eq!(item.lsp_item["data"], json!("Derived"));
+}
+
 #[gpui::test]
 async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4462,6 +4640,305 @@ async fn search(
         .collect())
 }
 
+#[gpui::test]
+async fn test_semantic_tokens_invalidated_after_disk_based_diagnostics(
+    cx: &mut gpui::TestAppContext,
+) {
+    init_test(cx);
+
+    let progress_token = "the-progress-token";
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![lsp::SemanticTokenType::FUNCTION],
+                                token_modifiers: Vec::new(),
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Bool(true)),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                ..Default::default()
+            },
+            disk_based_diagnostics_progress_token: Some(progress_token.into()),
+            disk_based_diagnostics_sources: vec!["disk".into()],
+            ..Default::default()
+        }))
+        .await;
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "fn main() {}" }))
+        .await;
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    project.update(cx, |project, _| project.languages.add(Arc::new(language)));
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let requests = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>({
+        let requests = requests.clone();
+        move |_, _| {
+            requests.fetch_add(1, SeqCst);
+            async move {
+                Ok(Some(lsp::SemanticTokensResult::Tokens(
+                    lsp::SemanticTokens {
+                        result_id: None,
+                        data: vec![lsp::SemanticToken {
+                            delta_line: 0,
+                            delta_start: 3,
+                            length: 4,
+                            token_type: 0,
+                            token_modifiers_bitset: 0,
+                        }],
+                    },
+                )))
+            }
+        }
+    });
+
+    for _ in 0..2 {
+        let tokens = project
+            .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+            .await
+            .unwrap();
+        assert_eq!(tokens.len(), 1);
+    }
+    assert_eq!(
+        requests.load(SeqCst),
+        1,
+        "tokens of an unchanged buffer should be reused"
+    );
+
+    fake_server
+        .start_progress(format!("{}/0", progress_token))
+        .await;
+    fake_server.end_progress(format!("{}/0", progress_token));
+    cx.executor().run_until_parked();
+    project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        requests.load(SeqCst),
+        2,
+        "tokens should be requested again once disk-based diagnostics are finished"
+    );
+}
+
+#[test]
+fn test_apply_semantic_tokens_delta() {
+    let token = |delta_line, delta_start, token_type| lsp::SemanticToken {
+        delta_line,
+        delta_start,
+        length: 1,
+        token_type,
+        token_modifiers_bitset: 0,
+    };
+    let response = SemanticTokensResponse {
+        result_id: Some("1".into()),
+        data: vec![0, 1, 1, 0, 0, 1, 2, 1, 1, 0],
+        ..Default::default()
+    };
+
+    // Edits refer to the original data, whatever the order they're sent in.
+    let response = response.apply_delta(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+        lsp::SemanticTokensDelta {
+            result_id: Some("2".into()),
+            edits: vec![
+                lsp::SemanticTokensEdit {
+                    start: 0,
+                    delete_count: 0,
+                    data: Some(vec![token(0, 0, 2)]),
+                },
+                lsp::SemanticTokensEdit {
+                    start: 5,
+                    delete_count: 5,
+                    data: Some(vec![token(2, 3, 1), token(0, 2, 0)]),
+                },
+            ],
+        },
+    )));
+    assert_eq!(response.result_id.as_deref(), Some("2"));
+    assert_eq!(
+        response.data,
+        [0, 0, 1, 2, 0, 0, 1, 1, 0, 0, 2, 3, 1, 1, 0, 0, 2, 1, 0, 0]
+    );
+
+    // Deletions past the end of the data are clamped.
+    let response = response.apply_delta(Some(
+        lsp::SemanticTokensFullDeltaResult::PartialTokensDelta {
+            edits: vec![lsp::SemanticTokensEdit {
+                start: 15,
+                delete_count: 10,
+                data: None,
+            }],
+        },
+    ));
+    assert_eq!(response.result_id, None);
+    assert_eq!(response.data, [0, 0, 1, 2, 0, 0, 1, 1, 0, 0, 2, 3, 1, 1, 0]);
+
+    // A full response replaces the data.
+    let response = response.apply_delta(Some(lsp::SemanticTokensFullDeltaResult::Tokens(
+        lsp::SemanticTokens {
+            result_id: Some("3".into()),
+            data: vec![token(4, 2, 1)],
+        },
+    )));
+    assert_eq!(response.result_id.as_deref(), Some("3"));
+    assert_eq!(response.data, [4, 2, 1, 1, 0]);
+
+    let response = response.apply_delta(None);
+    assert!(response.data.is_empty());
+}
+
+#[test]
+fn test_decode_semantic_tokens() {
+    let mut buffer = text::Buffer::new(
+        0,
+        BufferId::new(1).unwrap(),
+        "fn main() {\n    let x = 1;\n}\n".into(),
+    );
+    let response = SemanticTokensResponse {
+        data: vec![0, 3, 4, 0, 1, 1, 8, 1, 1, 3, 0, 4, 1, 2, 0, 0, 2, 1, 5, 0],
+        legend: SemanticTokensLegend {
+            token_types: vec!["function".into(), "variable".into(), "number".into()],
+            token_modifiers: vec!["declaration".into(), "readonly".into()],
+        },
+        ..Default::default()
+    };
+    let decode = |buffer: &text::Buffer, version: &clock::Global| {
+        response
+            .decode(buffer, version)
+            .into_iter()
+            .map(|token| {
+                let range = token.range.to_point(buffer);
+                (range, token.token_type, token.token_modifiers)
+            })
+            .collect::<Vec<_>>()
+    };
+    let token = |range: Range<Point>, token_type: &str, modifiers: &[&str]| {
+        let modifiers = modifiers.iter().map(|modifier| Arc::from(*modifier));
+        (
+            range,
+            Arc::<str>::from(token_type),
+            modifiers.collect::<Vec<_>>(),
+        )
+    };
+
+    // Positions are relative to the previous token, and unknown token types are skipped.
+    let version = buffer.version();
+    assert_eq!(
+        decode(&buffer, &version),
+        [
+            token(
+                Point::new(0, 3)..Point::new(0, 7),
+                "function",
+                &["declaration"]
+            ),
+            token(
+                Point::new(1, 8)..Point::new(1, 9),
+                "variable",
+                &["declaration", "readonly"]
+            ),
+            token(Point::new(1, 12)..Point::new(1, 13), "number", &[]),
+        ]
+    );
+
+    // Tokens computed for an older version are moved past the edits made since, and the
+    // tokens that were edited are dropped.
+    buffer.edit([(0..0, "pub "), (20..21, "value")]);
+    assert_eq!(
+        decode(&buffer, &version),
+        [
+            token(
+                Point::new(0, 7)..Point::new(0, 11),
+                "function",
+                &["declaration"]
+            ),
+            token(Point::new(1, 16)..Point::new(1, 17), "number", &[]),
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_blame_buffer_with_unsaved_edits(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "a.txt": "one\ntwo\nthree\nfour\n",
+        }),
+    )
+    .await;
+    fs.set_head_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), "one\ntwo\nthree\nfour\n".into())],
+    );
+    let entry = |sha: &str, range: Range<u32>, original_line_number: u32| BlameEntry {
+        sha: sha.into(),
+        range,
+        original_line_number,
+        ..Default::default()
+    };
+    let blame = Blame {
+        entries: vec![
+            entry("1111111111111111111111111111111111111111", 0..2, 1),
+            entry("2222222222222222222222222222222222222222", 2..4, 1),
+        ],
+    };
+    fs.set_blame_for_repo(
+        Path::new("/dir/.git"),
+        vec![(Path::new("a.txt"), blame.clone())],
+    );
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/a.txt", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let blame_buffer = |cx: &mut gpui::TestAppContext| {
+        project.update(cx, |project, cx| project.blame_buffer(&buffer, cx))
+    };
+    assert_eq!(blame_buffer(cx).await.unwrap(), blame);
+
+    // The unsaved contents of the buffer are blamed, not the file on disk.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(4..7, "TWO\nTWO AND A HALF")], None, cx)
+    });
+    assert_eq!(
+        blame_buffer(cx).await.unwrap().entries,
+        [
+            entry("1111111111111111111111111111111111111111", 0..1, 1),
+            entry("0000000000000000000000000000000000000000", 1..3, 2),
+            entry("2222222222222222222222222222222222222222", 3..5, 1),
+        ]
+    );
+}
+
 fn init_test(cx: &mut gpui::TestAppContext) {
     if std::env::var("RUST_LOG").is_ok() {
         env_logger::try_init().ok();
@@ -10,7 +10,7 @@ use collections::{HashMap, VecDeque};
 use gpui::{AppContext, Context, Model, ModelContext, Subscription};
 use itertools::Itertools;
 use project_core::worktree::WorktreeId;
-use task::{Task, TaskId, TaskSource};
+use task::{SpawnInTerminal, Task, TaskId, TaskSource};
 use util::{post_inc, NumericPrefixWithSuffix};
 
 /// Inventory tracks available tasks for a given project.
@@ -35,13 +35,15 @@ pub enum TaskSourceKind {
     AbsPath(PathBuf),
     /// Worktree-specific task definitions, e.g. dynamic tasks from open worktree file, or tasks from the worktree's .zed/task.json
     Worktree { id: WorktreeId, abs_path: PathBuf },
+    /// Tasks of the host of a shared project, listed by guests and run on the host's machine
+    Remote,
 }
 
 impl TaskSourceKind {
     fn abs_path(&self) -> Option<&Path> {
         match self {
             Self::AbsPath(abs_path) | Self::Worktree { abs_path, .. } => Some(abs_path),
-            Self::UserInput => None,
+            Self::UserInput | Self::Remote => None,
         }
     }
 
@@ -53,6 +55,45 @@ impl TaskSourceKind {
     }
 }
 
+/// A task of the host of a shared project, as listed by guests with [`crate::Project::remote_tasks`].
+///
+/// Its command is only known to the host: spawning it in a guest's project asks the host to run
+/// it, see [`crate::Project::run_remote_task`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteTask {
+    pub id: TaskId,
+    pub label: String,
+}
+
+impl Task for RemoteTask {
+    fn id(&self) -> &TaskId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.label
+    }
+
+    fn cwd(&self) -> Option<&Path> {
+        None
+    }
+
+    fn exec(&self, _cwd: Option<PathBuf>) -> Option<SpawnInTerminal> {
+        Some(SpawnInTerminal {
+            id: self.id.clone(),
+            label: self.label.clone(),
+            command: String::new(),
+            args: Vec::new(),
+            cwd: None,
+            env: Default::default(),
+            use_new_terminal: true,
+            allow_concurrent_runs: true,
+            separate_shell: false,
+            task_run_id: None,
+        })
+    }
+}
+
 impl Inventory {
     pub fn new(cx: &mut AppContext) -> Model<Self> {
         cx.new_model(|_| Self {
@@ -1,18 +1,43 @@
-use crate::Project;
-use gpui::{AnyWindowHandle, Context, Entity, Model, ModelContext, WeakModel};
+use crate::{Event, Project, RemoteTask, TaskSourceKind};
+use anyhow::{anyhow, Context as _, Result};
+use client::{proto, Client, TypedEnvelope};
+use collections::HashMap;
+use futures::{channel::oneshot, FutureExt, StreamExt};
+use gpui::{
+    AnyWindowHandle, AsyncAppContext, Context, Entity, Model, ModelContext, Task, WeakModel,
+};
 use settings::Settings;
 use smol::channel::bounded;
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use task::TaskId;
 use terminal::{
+    proto::{deserialize_screen, serialize_screen},
     terminal_settings::{self, Shell, TerminalSettings, VenvSettingsContent},
     SpawnTask, TaskState, Terminal, TerminalBuilder,
 };
+use util::post_inc;
 
 // #[cfg(target_os = "macos")]
 // use std::os::unix::ffi::OsStrExt;
 
+/// The minimum delay between two screens of a task terminal sent to the guest that ran the task.
+const TASK_TERMINAL_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
+    /// The id given to the next task run requested by a guest.
+    next_task_run_id: u64,
+    /// Task runs requested by guests that the host hasn't accepted or declined yet.
+    requested_task_runs: HashMap<u64, oneshot::Sender<Model<Terminal>>>,
+    /// Task runs accepted by the host, waiting for the terminal spawned for them to be created.
+    accepted_task_runs: HashMap<u64, oneshot::Sender<Model<Terminal>>>,
+    /// Read-only mirrors of the host's terminals running the tasks requested by this guest.
+    remote_task_terminals: HashMap<u64, WeakModel<Terminal>>,
 }
 
 impl Project {
@@ -28,6 +53,9 @@ impl Project {
             "creating terminals as a guest is not supported yet"
         );
 
+        let task_run_id = spawn_task
+            .as_ref()
+            .and_then(|spawn_task| spawn_task.task_run_id);
         let settings = TerminalSettings::get_global(cx);
         let python_settings = settings.detect_venv.clone();
         let (completion_tx, completion_rx) = bounded(1);
@@ -82,6 +110,10 @@ impl Project {
             })
             .detach();
 
+            if let Some(task_run_id) = task_run_id {
+                self.task_run_started(task_run_id, &terminal_handle);
+            }
+
             if let Some(python_settings) = &python_settings.as_option() {
                 let activate_command = Project::get_activate_command(python_settings);
                 let activate_script_path =
@@ -96,9 +128,283 @@ impl Project {
             terminal_handle
         });
 
+        if terminal.is_err() {
+            if let Some(task_run_id) = task_run_id {
+                self.decline_task_run(task_run_id);
+            }
+        }
         terminal
     }
 
+    /// Lists the tasks that the host of this shared project lets its guests run.
+    pub fn remote_tasks(&self, cx: &mut ModelContext<Self>) -> Task<Result<Vec<RemoteTask>>> {
+        let Some(project_id) = self.remote_id().filter(|_| self.is_remote()) else {
+            return Task::ready(Err(anyhow!("project is not a guest of a shared project")));
+        };
+        let request = self.client.request(proto::GetProjectTasks { project_id });
+        cx.background_executor().spawn(async move {
+            let response = request.await?;
+            Ok(response
+                .tasks
+                .into_iter()
+                .map(|task| RemoteTask {
+                    id: TaskId(task.id),
+                    label: task.label,
+                })
+                .collect())
+        })
+    }
+
+    /// Asks the host of this shared project to run one of its tasks. Once the host accepts, the
+    /// returned terminal mirrors the host's terminal running the task.
+    pub fn run_remote_task(
+        &self,
+        task_id: TaskId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Terminal>>> {
+        let Some(project_id) = self.remote_id().filter(|_| self.is_remote()) else {
+            return Task::ready(Err(anyhow!("project is not a guest of a shared project")));
+        };
+        let request = self.client.request(proto::RunProjectTask {
+            project_id,
+            task_id: task_id.0.clone(),
+        });
+        cx.spawn(move |this, mut cx| async move {
+            let response = request.await?;
+            let screen = deserialize_screen(
+                response
+                    .screen
+                    .ok_or_else(|| anyhow!("missing terminal screen"))?,
+            )?;
+            this.update(&mut cx, |this, cx| {
+                // Guests can't write to the terminal, so its input is dropped.
+                let (input_tx, _) = futures::channel::mpsc::unbounded();
+                let terminal = cx.new_model(|_| {
+                    let mut terminal =
+                        Terminal::remote(response.title.clone(), true, screen, input_tx);
+                    terminal.set_remote_task(task_id, response.title);
+                    terminal
+                });
+                this.terminals
+                    .remote_task_terminals
+                    .insert(response.terminal_id, terminal.downgrade());
+                terminal
+            })
+        })
+    }
+
+    /// Lets the terminal panel run the task requested by a guest, see [`Event::TaskRunRequested`].
+    /// The guest starts receiving the task's output once the terminal spawned with the same
+    /// `task_run_id` is created.
+    pub fn accept_task_run(&mut self, task_run_id: u64) {
+        if let Some(task_run) = self.terminals.requested_task_runs.remove(&task_run_id) {
+            self.terminals
+                .accepted_task_runs
+                .insert(task_run_id, task_run);
+        }
+    }
+
+    /// Shares the terminal running a task run accepted with [`Project::accept_task_run`] with
+    /// the guest that requested it. This happens when the terminal is spawned with the run's
+    /// `task_run_id`.
+    pub fn task_run_started(&mut self, task_run_id: u64, terminal: &Model<Terminal>) {
+        if let Some(task_run) = self.terminals.accepted_task_runs.remove(&task_run_id) {
+            task_run.send(terminal.clone()).ok();
+        }
+    }
+
+    /// Refuses to run the task requested by a guest, or gives up on running it if it was
+    /// accepted but couldn't be spawned, see [`Event::TaskRunRequested`].
+    pub fn decline_task_run(&mut self, task_run_id: u64) {
+        self.terminals.requested_task_runs.remove(&task_run_id);
+        self.terminals.accepted_task_runs.remove(&task_run_id);
+    }
+
+    /// Sends the screen of a terminal running a task to the guest that requested it whenever it
+    /// changes, at most once per [`TASK_TERMINAL_UPDATE_INTERVAL`], until the task completes.
+    fn share_task_terminal(
+        &mut self,
+        terminal: Model<Terminal>,
+        peer_id: proto::PeerId,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<proto::RunProjectTaskResponse> {
+        let project_id = self.remote_id().context("project is not shared")?;
+        let terminal_id = terminal.entity_id().as_u64();
+        let (title, screen) = {
+            let terminal = terminal.read(cx);
+            (terminal.title(false), serialize_screen(&terminal.screen()))
+        };
+
+        let (changes_tx, mut changes_rx) = futures::channel::mpsc::unbounded();
+        let subscription = cx.subscribe(&terminal, move |_, _, event, _| match event {
+            terminal::Event::Wakeup | terminal::Event::TitleChanged => {
+                changes_tx.unbounded_send(()).ok();
+            }
+            _ => {}
+        });
+        let completion = terminal.update(cx, |terminal, cx| terminal.wait_for_completed_task(cx));
+        let terminal = terminal.downgrade();
+        cx.spawn(move |this, mut cx| async move {
+            let _subscription = subscription;
+            let mut completion = completion.fuse();
+            loop {
+                let completed = futures::select_biased! {
+                    _ = completion => true,
+                    change = changes_rx.next() => change.is_none(),
+                };
+                if !completed {
+                    cx.background_executor()
+                        .timer(TASK_TERMINAL_UPDATE_INTERVAL)
+                        .await;
+                    while let Ok(Some(())) = changes_rx.try_next() {}
+                }
+
+                let sent = this.update(&mut cx, |this, cx| {
+                    let terminal = terminal.upgrade();
+                    let (title, screen) = terminal
+                        .as_ref()
+                        .map(|terminal| {
+                            let terminal = terminal.read(cx);
+                            (
+                                terminal.title(false),
+                                Some(serialize_screen(&terminal.screen())),
+                            )
+                        })
+                        .unwrap_or_default();
+                    this.remote_id().map(|_| {
+                        this.client.send(proto::UpdateTaskTerminal {
+                            project_id,
+                            peer_id: Some(peer_id),
+                            terminal_id,
+                            title,
+                            screen,
+                            completed: completed || terminal.is_none(),
+                        })
+                    })
+                });
+                match sent {
+                    Ok(Some(Ok(()))) if !completed => {}
+                    _ => break,
+                }
+            }
+        })
+        .detach();
+
+        Ok(proto::RunProjectTaskResponse {
+            terminal_id,
+            title,
+            screen: Some(screen),
+        })
+    }
+
+    pub(crate) async fn handle_get_project_tasks(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::GetProjectTasks>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetProjectTasksResponse> {
+        this.update(&mut cx, |this, cx| {
+            let tasks = this.shared_tasks(cx);
+            proto::GetProjectTasksResponse {
+                tasks: tasks
+                    .into_iter()
+                    .map(|task| proto::ProjectTask {
+                        id: task.id.0,
+                        label: task.label,
+                    })
+                    .collect(),
+            }
+        })
+    }
+
+    pub(crate) async fn handle_run_project_task(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RunProjectTask>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::RunProjectTaskResponse> {
+        let requester = envelope.original_sender_id()?;
+        let task_id = TaskId(envelope.payload.task_id);
+        let task_run = this.update(&mut cx, |this, cx| {
+            let task = this
+                .shared_tasks(cx)
+                .into_iter()
+                .find(|task| task.id == task_id)
+                .ok_or_else(|| anyhow!("unknown task {:?}", task_id.0))?;
+            let (task_run_tx, task_run_rx) = oneshot::channel();
+            let task_run_id = post_inc(&mut this.terminals.next_task_run_id);
+            this.terminals
+                .requested_task_runs
+                .insert(task_run_id, task_run_tx);
+            cx.emit(Event::TaskRunRequested {
+                requester,
+                task_run_id,
+                task_id: task.id,
+                label: task.label,
+            });
+            anyhow::Ok(task_run_rx)
+        })??;
+        let terminal = task_run
+            .await
+            .map_err(|_| anyhow!("the host declined or failed to run the task"))?;
+        this.update(&mut cx, |this, cx| {
+            this.share_task_terminal(terminal, requester, cx)
+        })?
+    }
+
+    pub(crate) async fn handle_update_task_terminal(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdateTaskTerminal>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            let message = envelope.payload;
+            let terminals = &mut this.terminals.remote_task_terminals;
+            let Some(terminal) = terminals
+                .get(&message.terminal_id)
+                .and_then(|terminal| terminal.upgrade())
+            else {
+                terminals.remove(&message.terminal_id);
+                return Ok(());
+            };
+            if message.completed {
+                terminals.remove(&message.terminal_id);
+            }
+
+            let screen = message.screen.map(deserialize_screen).transpose()?;
+            terminal.update(cx, |terminal, cx| {
+                if let Some(screen) = screen {
+                    terminal.set_remote_screen(message.title, true, screen, cx);
+                }
+                if message.completed {
+                    terminal.complete_remote_task();
+                }
+            });
+            Ok(())
+        })?
+    }
+
+    /// The tasks that guests can ask the host to run: the ones defined in `tasks.json` files.
+    fn shared_tasks(&self, cx: &mut ModelContext<Self>) -> Vec<RemoteTask> {
+        self.task_inventory()
+            .update(cx, |inventory, cx| {
+                inventory.list_tasks(None, None, false, cx)
+            })
+            .into_iter()
+            .filter(|(source_kind, _)| {
+                matches!(
+                    source_kind,
+                    TaskSourceKind::AbsPath(_) | TaskSourceKind::Worktree { .. }
+                )
+            })
+            .map(|(_, task)| RemoteTask {
+                id: task.id().clone(),
+                label: task.name().to_string(),
+            })
+            .collect()
+    }
+
     pub fn find_activate_script_path(
         &mut self,
         settings: &VenvSettingsContent,
@@ -48,6 +48,29 @@ pub struct GitSettings {
     /// Default: tracked_files
     pub git_gutter: Option<GitGutterSetting>,
     pub gutter_debounce: Option<u64>,
+    /// Whether or not to show git blame data inline at the end of the line
+    /// containing the cursor.
+    ///
+    /// Default: disabled
+    pub inline_blame: Option<InlineBlameSettings>,
+}
+
+impl GitSettings {
+    pub fn inline_blame_enabled(&self) -> bool {
+        self.inline_blame
+            .map_or(false, |inline_blame| inline_blame.enabled)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InlineBlameSettings {
+    /// Whether or not to show git blame data inline at the end of the line
+    /// containing the cursor.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub enabled: bool,
 }
 
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -266,6 +266,10 @@ impl LocalRepositoryEntry {
     pub fn load_index_text(&self, relative_file_path: &Path) -> Option<String> {
         self.repo_ptr.lock().load_index_text(relative_file_path)
     }
+
+    pub fn repo(&self) -> &Arc<Mutex<dyn GitRepository>> {
+        &self.repo_ptr
+    }
 }
 
 impl Deref for LocalSnapshot {
@@ -1272,6 +1276,7 @@ impl LocalWorktree {
     pub fn delete_entry(
         &self,
         entry_id: ProjectEntryId,
+        trash: bool,
         cx: &mut ModelContext<Worktree>,
     ) -> Option<Task<Result<()>>> {
         let entry = self.entry_for_id(entry_id)?.clone();
@@ -1280,13 +1285,21 @@ impl LocalWorktree {
 
         let delete = cx.background_executor().spawn(async move {
             if entry.is_file() {
-                fs.remove_file(&abs_path?, Default::default()).await?;
+                fs.remove_file(
+                    &abs_path?,
+                    RemoveOptions {
+                        trash,
+                        ..Default::default()
+                    },
+                )
+                .await?;
             } else {
                 fs.remove_dir(
                     &abs_path?,
                     RemoveOptions {
                         recursive: true,
                         ignore_if_not_exists: false,
+                        trash,
                     },
                 )
                 .await?;
@@ -1307,6 +1320,28 @@ impl LocalWorktree {
         }))
     }
 
+    /// Moves the entry most recently deleted from `path` with `trash` set back out of the trash.
+    pub fn restore_entry(
+        &self,
+        path: impl Into<Arc<Path>>,
+        cx: &mut ModelContext<Worktree>,
+    ) -> Task<Result<Option<Entry>>> {
+        let path = path.into();
+        let abs_path = self.absolutize(&path);
+        let fs = self.fs.clone();
+        let restore = cx
+            .background_executor()
+            .spawn(async move { fs.restore_from_trash(&abs_path?).await });
+
+        cx.spawn(|this, mut cx| async move {
+            restore.await?;
+            this.update(&mut cx, |this, cx| {
+                this.as_local_mut().unwrap().refresh_entry(path, None, cx)
+            })?
+            .await
+        })
+    }
+
     pub fn rename_entry(
         &self,
         entry_id: ProjectEntryId,
@@ -2020,7 +2055,7 @@ impl Snapshot {
             .map(|entry| &entry.path)
     }
 
-    fn child_entries<'a>(&'a self, parent_path: &'a Path) -> ChildEntriesIter<'a> {
+    pub fn child_entries<'a>(&'a self, parent_path: &'a Path) -> ChildEntriesIter<'a> {
         let mut cursor = self.entries_by_path.cursor();
         cursor.seek(&TraversalTarget::Path(parent_path), Bias::Right, &());
         let traversal = Traversal {
@@ -4613,7 +4648,7 @@ impl<'a, 'b> SeekTarget<'a, EntrySummary, (TraversalProgress<'a>, GitStatuses)>
     }
 }
 
-struct ChildEntriesIter<'a> {
+pub struct ChildEntriesIter<'a> {
     parent_path: &'a Path,
     traversal: Traversal<'a>,
 }
@@ -0,0 +1,441 @@
+use crate::{undo::FileOperation, ProjectPanel};
+use anyhow::{anyhow, Result};
+use collections::HashSet;
+use editor::Editor;
+use gpui::{
+    rems, AppContext, AsyncAppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView,
+    Model, Render, SharedString, Task, View, ViewContext, WeakView,
+};
+use project::{Project, ProjectEntryId, WorktreeId};
+use std::{
+    path::{Component, Path},
+    sync::Arc,
+};
+use ui::{prelude::*, Button, ButtonStyle, KeyBinding};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, ModalView};
+
+/// An entry renamed by a [`BulkRename`] modal, along with its path at the time the
+/// modal was opened.
+#[derive(Clone, Debug)]
+pub(crate) struct BulkRenameEntry {
+    pub worktree_id: WorktreeId,
+    pub entry_id: ProjectEntryId,
+    pub path: Arc<Path>,
+}
+
+/// Renames several entries at once. The worktree-relative path of each entry is listed on
+/// its own line of a temporary buffer, and editing a line renames the corresponding entry.
+pub(crate) struct BulkRename {
+    project: Model<Project>,
+    project_panel: WeakView<ProjectPanel>,
+    entries: Vec<BulkRenameEntry>,
+    editor: View<Editor>,
+    error: Option<SharedString>,
+}
+
+impl ModalView for BulkRename {}
+
+impl EventEmitter<DismissEvent> for BulkRename {}
+
+impl FocusableView for BulkRename {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl BulkRename {
+    pub fn new(
+        project: Model<Project>,
+        project_panel: WeakView<ProjectPanel>,
+        entries: Vec<BulkRenameEntry>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let text = entries
+            .iter()
+            .map(|entry| entry.path.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::multi_line(cx);
+            editor.set_text(text, cx);
+            editor.set_show_copilot_suggestions(false);
+            editor
+        });
+
+        Self {
+            project,
+            project_panel,
+            entries,
+            editor,
+            error: None,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let text = self.editor.read(cx).text(cx);
+        match planned_renames(&self.entries, &text) {
+            Ok(renames) => {
+                let operations = renames
+                    .iter()
+                    .map(|(entry, new_path)| FileOperation::Renamed {
+                        worktree_id: entry.worktree_id,
+                        old_path: entry.path.clone(),
+                        new_path: new_path.clone(),
+                    })
+                    .collect();
+                let rename = rename_entries(self.project.clone(), renames, cx);
+                let project_panel = self.project_panel.clone();
+                cx.spawn(|_, mut cx| async move {
+                    rename.await?;
+                    project_panel.update(&mut cx, |project_panel, _| {
+                        project_panel.undo_stack.push(operations)
+                    })
+                })
+                .detach_and_prompt_err("Failed to rename entries", cx, |_, _| None);
+                cx.emit(DismissEvent);
+            }
+            Err(error) => {
+                self.error = Some(error.to_string().into());
+                cx.notify();
+            }
+        }
+    }
+}
+
+/// Matches the edited lines with the entries they rename, returning the entries whose path
+/// changed along with their new path.
+fn planned_renames(
+    entries: &[BulkRenameEntry],
+    text: &str,
+) -> Result<Vec<(BulkRenameEntry, Arc<Path>)>> {
+    let lines = text.lines().collect::<Vec<_>>();
+    if lines.len() != entries.len() {
+        return Err(anyhow!(
+            "Expected {} lines, one per entry, but found {}",
+            entries.len(),
+            lines.len()
+        ));
+    }
+
+    let mut new_paths = HashSet::default();
+    let mut renames = Vec::new();
+    for (row, (entry, line)) in entries.iter().zip(lines).enumerate() {
+        let new_path: Arc<Path> = Path::new(line.trim()).into();
+        if new_path.as_os_str().is_empty() {
+            return Err(anyhow!("Line {} is empty", row + 1));
+        }
+        if new_path
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            return Err(anyhow!(
+                "Line {} must be a path relative to its worktree",
+                row + 1
+            ));
+        }
+        if !new_paths.insert((entry.worktree_id, new_path.clone())) {
+            return Err(anyhow!(
+                "Line {} renames more than one entry to {:?}",
+                row + 1,
+                new_path
+            ));
+        }
+        if new_path != entry.path {
+            renames.push((entry.clone(), new_path));
+        }
+    }
+    Ok(renames)
+}
+
+/// Renames the entries one after the other through the project, so that the renames are
+/// forwarded to the host when the project is shared. If a rename fails, the renames that
+/// were already performed are reverted before the error is returned.
+pub(crate) fn rename_entries(
+    project: Model<Project>,
+    renames: Vec<(BulkRenameEntry, Arc<Path>)>,
+    cx: &mut AppContext,
+) -> Task<Result<()>> {
+    // When an entry takes the path of another entry in the batch (for example when two
+    // files are swapped), move every entry to a temporary name first.
+    let old_paths = renames
+        .iter()
+        .map(|(entry, _)| (entry.worktree_id, entry.path.clone()))
+        .collect::<HashSet<_>>();
+    let needs_staging = renames
+        .iter()
+        .any(|(entry, new_path)| old_paths.contains(&(entry.worktree_id, new_path.clone())));
+
+    cx.spawn(|mut cx| async move {
+        // The renamed entries along with the path they had before each rename, in the
+        // order the renames were performed.
+        let mut performed_renames = Vec::new();
+        let result = async {
+            let mut staged_renames = Vec::new();
+            for (ix, (entry, new_path)) in renames.into_iter().enumerate() {
+                let mut entry_id = entry.entry_id;
+                let mut path = entry.path.clone();
+                if needs_staging {
+                    let file_name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+                    let temporary_path: Arc<Path> = entry
+                        .path
+                        .with_file_name(format!(".{file_name}.rename-{ix}"))
+                        .into();
+                    entry_id =
+                        rename_entry(&project, entry_id, temporary_path.clone(), &mut cx).await?;
+                    performed_renames.push((entry_id, path));
+                    path = temporary_path;
+                }
+                staged_renames.push((entry_id, path, new_path));
+            }
+
+            for (entry_id, path, new_path) in staged_renames {
+                let entry_id = rename_entry(&project, entry_id, new_path, &mut cx).await?;
+                performed_renames.push((entry_id, path));
+            }
+            anyhow::Ok(())
+        }
+        .await;
+
+        if result.is_err() {
+            for (entry_id, path) in performed_renames.into_iter().rev() {
+                rename_entry(&project, entry_id, path, &mut cx)
+                    .await
+                    .log_err();
+            }
+        }
+        result
+    })
+}
+
+async fn rename_entry(
+    project: &Model<Project>,
+    entry_id: ProjectEntryId,
+    new_path: Arc<Path>,
+    cx: &mut AsyncAppContext,
+) -> Result<ProjectEntryId> {
+    let entry = project
+        .update(cx, |project, cx| {
+            project.rename_entry(entry_id, new_path, cx)
+        })?
+        .await?;
+    Ok(entry.map_or(entry_id, |entry| entry.id))
+}
+
+impl Render for BulkRename {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let focus_handle = self.editor.focus_handle(cx);
+        v_flex()
+            .elevation_3(cx)
+            .key_context("BulkRename")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w(rems(34.))
+            .h(rems(24.))
+            .p_4()
+            .gap_2()
+            .child(Headline::new(format!(
+                "Rename {} Entries",
+                self.entries.len()
+            )))
+            .child(
+                Label::new("Edit the paths below. Each line renames the entry it started as.")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .bg(cx.theme().colors().editor_background)
+                    .p_2()
+                    .border()
+                    .rounded_md()
+                    .border_color(if self.error.is_some() {
+                        cx.theme().status().error_border
+                    } else {
+                        cx.theme().colors().border
+                    })
+                    .child(self.editor.clone()),
+            )
+            .children(
+                self.error
+                    .clone()
+                    .map(|error| Label::new(error).size(LabelSize::Small).color(Color::Error)),
+            )
+            .child(
+                h_flex()
+                    .justify_end()
+                    .gap_1()
+                    .child(
+                        Button::new("cancel_bulk_rename", "Cancel")
+                            .style(ButtonStyle::Subtle)
+                            .color(Color::Muted)
+                            .on_click(cx.listener(|_, _, cx| cx.emit(DismissEvent))),
+                    )
+                    .child(
+                        Button::new("confirm_bulk_rename", "Rename")
+                            .color(Color::Accent)
+                            .style(ButtonStyle::Filled)
+                            .key_binding(KeyBinding::for_action_in(
+                                &menu::Confirm,
+                                &focus_handle,
+                                cx,
+                            ))
+                            .on_click(cx.listener(|this, _, cx| this.confirm(&menu::Confirm, cx))),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_planned_renames() {
+        let entries = ["a.txt", "b.txt", "dir/c.txt"]
+            .into_iter()
+            .enumerate()
+            .map(|(ix, path)| BulkRenameEntry {
+                worktree_id: WorktreeId::from_usize(1),
+                entry_id: ProjectEntryId::from_proto(ix as u64 + 1),
+                path: Path::new(path).into(),
+            })
+            .collect::<Vec<_>>();
+
+        let renames = planned_renames(&entries, "b.txt\na.txt\ndir/c.txt\n").unwrap();
+        assert_eq!(
+            renames
+                .iter()
+                .map(|(entry, new_path)| (entry.path.as_ref(), new_path.as_ref()))
+                .collect::<Vec<_>>(),
+            [
+                (Path::new("a.txt"), Path::new("b.txt")),
+                (Path::new("b.txt"), Path::new("a.txt")),
+            ]
+        );
+
+        assert!(planned_renames(&entries, "a.txt\nb.txt").is_err());
+        assert!(planned_renames(&entries, "a.txt\n\ndir/c.txt").is_err());
+        assert!(planned_renames(&entries, "a.txt\n../b.txt\ndir/c.txt").is_err());
+        assert!(planned_renames(&entries, "a.txt\na.txt\ndir/c.txt").is_err());
+    }
+
+    #[gpui::test]
+    async fn test_rename_entries_swapping_paths(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.txt": "A",
+                "b.txt": "B",
+                "c.txt": "C",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+
+        let entries = project.read_with(cx, |project, cx| {
+            let worktree = project.worktrees().next().unwrap().read(cx);
+            ["a.txt", "b.txt", "c.txt"]
+                .into_iter()
+                .map(|path| BulkRenameEntry {
+                    worktree_id: worktree.id(),
+                    entry_id: worktree.entry_for_path(path).unwrap().id,
+                    path: Path::new(path).into(),
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let renames = planned_renames(&entries, "b.txt\na.txt\nd.txt").unwrap();
+        cx.update(|cx| rename_entries(project.clone(), renames, cx))
+            .await
+            .unwrap();
+        cx.executor().run_until_parked();
+
+        assert_eq!(fs.load("/root/a.txt".as_ref()).await.unwrap(), "B");
+        assert_eq!(fs.load("/root/b.txt".as_ref()).await.unwrap(), "A");
+        assert_eq!(fs.load("/root/d.txt".as_ref()).await.unwrap(), "C");
+        assert_eq!(
+            fs.files(),
+            [
+                PathBuf::from("/root/a.txt"),
+                PathBuf::from("/root/b.txt"),
+                PathBuf::from("/root/d.txt"),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rename_entries_rolls_back_on_failure(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.txt": "A",
+                "b.txt": "B",
+                "c.txt": "C",
+                "e.txt": "E",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+
+        let entries = project.read_with(cx, |project, cx| {
+            let worktree = project.worktrees().next().unwrap().read(cx);
+            ["a.txt", "b.txt", "c.txt"]
+                .into_iter()
+                .map(|path| BulkRenameEntry {
+                    worktree_id: worktree.id(),
+                    entry_id: worktree.entry_for_path(path).unwrap().id,
+                    path: Path::new(path).into(),
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // Renaming the last entry fails because `e.txt` already exists, after the other
+        // entries were already swapped.
+        let renames = planned_renames(&entries, "b.txt\na.txt\ne.txt").unwrap();
+        cx.update(|cx| rename_entries(project.clone(), renames, cx))
+            .await
+            .unwrap_err();
+        cx.executor().run_until_parked();
+
+        assert_eq!(fs.load("/root/a.txt".as_ref()).await.unwrap(), "A");
+        assert_eq!(fs.load("/root/b.txt".as_ref()).await.unwrap(), "B");
+        assert_eq!(fs.load("/root/c.txt".as_ref()).await.unwrap(), "C");
+        assert_eq!(fs.load("/root/e.txt".as_ref()).await.unwrap(), "E");
+        assert_eq!(
+            fs.files(),
+            [
+                PathBuf::from("/root/a.txt"),
+                PathBuf::from("/root/b.txt"),
+                PathBuf::from("/root/c.txt"),
+                PathBuf::from("/root/e.txt"),
+            ]
+        );
+    }
+}
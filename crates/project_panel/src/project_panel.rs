@@ -1,14 +1,19 @@
+mod bulk_rename;
 pub mod file_associations;
 mod project_panel_settings;
+mod undo;
 use client::{ErrorCode, ErrorExt};
-use settings::Settings;
+use settings::{Settings, SettingsStore};
 
 use db::kvp::KEY_VALUE_STORE;
 use editor::{actions::Cancel, scroll::Autoscroll, Editor};
 use file_associations::FileAssociations;
+use file_history::FileHistory;
+use fuzzy::{CharBag, PathMatchCandidate};
 
 use anyhow::{anyhow, Result};
-use collections::{hash_map, HashMap};
+use bulk_rename::{BulkRename, BulkRenameEntry};
+use collections::{hash_map, HashMap, HashSet};
 use gpui::{
     actions, div, overlay, px, uniform_list, Action, AppContext, AssetSource, AsyncWindowContext,
     ClipboardItem, DismissEvent, Div, EventEmitter, FocusHandle, FocusableView, InteractiveElement,
@@ -19,13 +24,14 @@ use gpui::{
 use menu::{Confirm, SelectNext, SelectPrev};
 use project::{
     repository::GitFileStatus, Entry, EntryKind, Fs, Project, ProjectEntryId, ProjectPath,
-    Worktree, WorktreeId,
+    Snapshot, Worktree, WorktreeId,
 };
 use project_panel_settings::{ProjectPanelDockPosition, ProjectPanelSettings};
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, ffi::OsStr, ops::Range, path::Path, sync::Arc};
+use std::{cmp::Ordering, collections::BTreeSet, ffi::OsStr, ops::Range, path::Path, sync::Arc};
 use theme::ThemeSettings;
 use ui::{prelude::*, v_flex, ContextMenu, Icon, KeyBinding, Label, ListItem};
+use undo::{undo_operations, FileOperation, UndoError, UndoStack};
 use unicase::UniCase;
 use util::{maybe, NumericPrefixWithSuffix, ResultExt, TryFutureExt};
 use workspace::{
@@ -36,6 +42,8 @@ use workspace::{
 
 const PROJECT_PANEL_KEY: &'static str = "ProjectPanel";
 const NEW_ENTRY_ID: ProjectEntryId = ProjectEntryId::MAX;
+/// The maximum number of entries per worktree that match the filter query.
+const MAX_FILTER_MATCHES: usize = 1000;
 
 pub struct ProjectPanel {
     project: Model<Project>,
@@ -46,17 +54,87 @@ pub struct ProjectPanel {
     last_worktree_root_id: Option<ProjectEntryId>,
     expanded_dir_ids: HashMap<WorktreeId, Vec<ProjectEntryId>>,
     selection: Option<Selection>,
+    /// Entries marked with cmd- or shift-click. When any entry is marked, clipboard, delete,
+    /// rename and open actions apply to all of them rather than just the selected entry.
+    marked_entries: BTreeSet<Selection>,
     context_menu: Option<(View<ContextMenu>, Point<Pixels>, Subscription)>,
     edit_state: Option<EditState>,
     filename_editor: View<Editor>,
     clipboard_entry: Option<ClipboardEntry>,
+    undo_stack: UndoStack,
+    filter_editor: View<Editor>,
+    filter_query: String,
+    show_filter: bool,
+    filter_matches: HashMap<WorktreeId, FilterMatches>,
+    /// Directories that were expanded only to show the entries matching the filter query, and
+    /// are collapsed again once the filter is cleared.
+    filter_expanded_dir_ids: HashMap<WorktreeId, Vec<ProjectEntryId>>,
+    pending_filter: Option<Task<()>>,
+    /// For entries displayed in place of a chain of single-child directories when
+    /// `compact_folders` is enabled, the number of directories merged into the entry.
+    folded_ancestor_counts: HashMap<ProjectEntryId, usize>,
     _dragged_entry_destination: Option<Arc<Path>>,
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
 }
 
-#[derive(Copy, Clone, Debug)]
+/// The entries of a worktree that match the filter query, as of one of its scans.
+struct FilterMatches {
+    query: String,
+    scan_id: usize,
+    /// The matching entries and their ancestors, which are the entries shown while filtering.
+    visible_paths: HashSet<Arc<Path>>,
+    /// The directories containing the matching entries, other than the worktree root.
+    ancestor_dir_ids: Vec<ProjectEntryId>,
+}
+
+impl FilterMatches {
+    fn new(query: String, snapshot: &Snapshot) -> Self {
+        let candidates = snapshot
+            .entries(false)
+            .filter(|entry| entry.path.parent().is_some())
+            .map(|entry| PathMatchCandidate {
+                path: &entry.path,
+                char_bag: CharBag::from_iter(entry.path.to_string_lossy().to_lowercase().chars()),
+            })
+            .collect::<Vec<_>>();
+        let path_matches = fuzzy::match_fixed_path_set(
+            candidates,
+            snapshot.id().to_usize(),
+            &query,
+            false,
+            MAX_FILTER_MATCHES,
+        );
+
+        let mut visible_paths = HashSet::default();
+        let mut ancestor_paths = HashSet::default();
+        let mut ancestor_dir_ids = Vec::new();
+        for path_match in path_matches {
+            for ancestor in path_match.path.ancestors().skip(1) {
+                if !ancestor_paths.insert(Arc::<Path>::from(ancestor)) {
+                    break;
+                }
+                if ancestor.parent().is_some() {
+                    if let Some(entry) = snapshot.entry_for_path(ancestor) {
+                        ancestor_dir_ids.push(entry.id);
+                    }
+                }
+            }
+            visible_paths.insert(path_match.path);
+        }
+        visible_paths.extend(ancestor_paths);
+
+        Self {
+            query,
+            scan_id: snapshot.scan_id(),
+            visible_paths,
+            ancestor_dir_ids,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct Selection {
     worktree_id: WorktreeId,
     entry_id: ProjectEntryId,
@@ -71,16 +149,10 @@ struct EditState {
     processing_filename: Option<String>,
 }
 
-#[derive(Copy, Clone)]
-pub enum ClipboardEntry {
-    Copied {
-        worktree_id: WorktreeId,
-        entry_id: ProjectEntryId,
-    },
-    Cut {
-        worktree_id: WorktreeId,
-        entry_id: ProjectEntryId,
-    },
+#[derive(Clone)]
+enum ClipboardEntry {
+    Copied(Vec<Selection>),
+    Cut(Vec<Selection>),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -120,6 +192,8 @@ actions!(
         Open,
         ToggleFocus,
         NewSearchInDirectory,
+        UndoFileOperation,
+        Filter,
     ]
 );
 
@@ -135,6 +209,11 @@ pub fn init(assets: impl AssetSource, cx: &mut AppContext) {
         workspace.register_action(|workspace, _: &ToggleFocus, cx| {
             workspace.toggle_panel_focus::<ProjectPanel>(cx);
         });
+        workspace.register_action(|workspace, _: &UndoFileOperation, cx| {
+            if let Some(project_panel) = workspace.panel::<ProjectPanel>(cx) {
+                project_panel.update(cx, |project_panel, cx| project_panel.undo(cx));
+            }
+        });
     })
     .detach();
 }
@@ -190,6 +269,8 @@ impl ProjectPanel {
                 }
                 project::Event::WorktreeRemoved(id) => {
                     this.expanded_dir_ids.remove(id);
+                    this.filter_matches.remove(id);
+                    this.filter_expanded_dir_ids.remove(id);
                     this.update_visible_entries(None, cx);
                     cx.notify();
                 }
@@ -218,11 +299,34 @@ impl ProjectPanel {
             })
             .detach();
 
+            let filter_editor = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Filter…", cx);
+                editor
+            });
+            cx.subscribe(&filter_editor, |this, _, event, cx| {
+                if let editor::EditorEvent::BufferEdited = event {
+                    this.filter_changed(cx);
+                }
+            })
+            .detach();
+
             cx.observe_global::<FileAssociations>(|_, cx| {
                 cx.notify();
             })
             .detach();
 
+            let mut compact_folders = ProjectPanelSettings::get_global(cx).compact_folders;
+            cx.observe_global::<SettingsStore>(move |this, cx| {
+                let new_compact_folders = ProjectPanelSettings::get_global(cx).compact_folders;
+                if new_compact_folders != compact_folders {
+                    compact_folders = new_compact_folders;
+                    this.update_visible_entries(None, cx);
+                    cx.notify();
+                }
+            })
+            .detach();
+
             let mut this = Self {
                 project: project.clone(),
                 fs: workspace.app_state().fs.clone(),
@@ -232,10 +336,19 @@ impl ProjectPanel {
                 last_worktree_root_id: Default::default(),
                 expanded_dir_ids: Default::default(),
                 selection: None,
+                marked_entries: Default::default(),
                 edit_state: None,
                 context_menu: None,
                 filename_editor,
                 clipboard_entry: None,
+                undo_stack: UndoStack::default(),
+                filter_editor,
+                filter_query: String::new(),
+                show_filter: false,
+                filter_matches: Default::default(),
+                filter_expanded_dir_ids: Default::default(),
+                pending_filter: None,
+                folded_ancestor_counts: Default::default(),
                 _dragged_entry_destination: None,
                 workspace: workspace.weak_handle(),
                 width: None,
@@ -383,10 +496,14 @@ impl ProjectPanel {
             return;
         };
 
-        self.selection = Some(Selection {
+        let selection = Selection {
             worktree_id,
             entry_id,
-        });
+        };
+        if !self.marked_entries.contains(&selection) {
+            self.marked_entries.clear();
+        }
+        self.selection = Some(selection);
 
         if let Some((worktree, entry)) = self.selected_entry(cx) {
             let is_root = Some(entry) == worktree.root_entry();
@@ -394,58 +511,68 @@ impl ProjectPanel {
             let worktree_id = worktree.id();
             let is_local = project.is_local();
             let is_read_only = project.is_read_only();
+            let has_multiple_marked = self.marked_entries.len() > 1;
+            let can_paste = self
+                .clipboard_entry
+                .as_ref()
+                .map_or(false, |clipboard_entry| {
+                    clipboard_entry
+                        .items()
+                        .iter()
+                        .any(|item| item.worktree_id == worktree_id)
+                });
 
             let context_menu = ContextMenu::build(cx, |menu, cx| {
-                menu.context(self.focus_handle.clone()).when_else(
-                    is_read_only,
-                    |menu| {
-                        menu.action("Copy Relative Path", Box::new(CopyRelativePath))
-                            .when(is_dir, |menu| {
-                                menu.action("Search Inside", Box::new(NewSearchInDirectory))
-                            })
-                    },
-                    |menu| {
-                        menu.when(is_local, |menu| {
-                            menu.action(
-                                "Add Folder to Project",
-                                Box::new(workspace::AddFolderToProject),
-                            )
-                            .when(is_root, |menu| {
-                                menu.entry(
-                                    "Remove from Project",
-                                    None,
-                                    cx.handler_for(&this, move |this, cx| {
-                                        this.project.update(cx, |project, cx| {
-                                            project.remove_worktree(worktree_id, cx)
-                                        });
-                                    }),
+                menu.context(self.focus_handle.clone())
+                    .when(has_multiple_marked, |menu| {
+                        menu.action("Open Selected", Box::new(Open)).separator()
+                    })
+                    .when_else(
+                        is_read_only,
+                        |menu| {
+                            menu.action("Copy Relative Path", Box::new(CopyRelativePath))
+                                .when(is_dir, |menu| {
+                                    menu.action("Search Inside", Box::new(NewSearchInDirectory))
+                                })
+                        },
+                        |menu| {
+                            menu.when(is_local, |menu| {
+                                menu.action(
+                                    "Add Folder to Project",
+                                    Box::new(workspace::AddFolderToProject),
                                 )
+                                .when(is_root, |menu| {
+                                    menu.entry(
+                                        "Remove from Project",
+                                        None,
+                                        cx.handler_for(&this, move |this, cx| {
+                                            this.project.update(cx, |project, cx| {
+                                                project.remove_worktree(worktree_id, cx)
+                                            });
+                                        }),
+                                    )
+                                })
                             })
-                        })
-                        .action("New File", Box::new(NewFile))
-                        .action("New Folder", Box::new(NewDirectory))
-                        .separator()
-                        .action("Cut", Box::new(Cut))
-                        .action("Copy", Box::new(Copy))
-                        .when_some(self.clipboard_entry, |menu, entry| {
-                            menu.when(entry.worktree_id() == worktree_id, |menu| {
-                                menu.action("Paste", Box::new(Paste))
+                            .action("New File", Box::new(NewFile))
+                            .action("New Folder", Box::new(NewDirectory))
+                            .separator()
+                            .action("Cut", Box::new(Cut))
+                            .action("Copy", Box::new(Copy))
+                            .when(can_paste, |menu| menu.action("Paste", Box::new(Paste)))
+                            .separator()
+                            .action("Copy Path", Box::new(CopyPath))
+                            .action("Copy Relative Path", Box::new(CopyRelativePath))
+                            .separator()
+                            .action("Reveal in Finder", Box::new(RevealInFinder))
+                            .when(is_dir, |menu| {
+                                menu.action("Open in Terminal", Box::new(OpenInTerminal))
+                                    .action("Search Inside", Box::new(NewSearchInDirectory))
                             })
-                        })
-                        .separator()
-                        .action("Copy Path", Box::new(CopyPath))
-                        .action("Copy Relative Path", Box::new(CopyRelativePath))
-                        .separator()
-                        .action("Reveal in Finder", Box::new(RevealInFinder))
-                        .when(is_dir, |menu| {
-                            menu.action("Open in Terminal", Box::new(OpenInTerminal))
-                                .action("Search Inside", Box::new(NewSearchInDirectory))
-                        })
-                        .separator()
-                        .action("Rename", Box::new(Rename))
-                        .when(!is_root, |menu| menu.action("Delete", Box::new(Delete)))
-                    },
-                )
+                            .separator()
+                            .action("Rename", Box::new(Rename))
+                            .when(!is_root, |menu| menu.action("Delete", Box::new(Delete)))
+                        },
+                    )
             });
 
             cx.focus_view(&context_menu);
@@ -479,6 +606,7 @@ impl ProjectPanel {
                         });
 
                         expanded_dir_ids.insert(ix, entry_id);
+                        self.keep_expanded_after_filter(worktree_id, entry_id);
                         self.update_visible_entries(None, cx);
                         cx.notify();
                     }
@@ -540,6 +668,7 @@ impl ProjectPanel {
                         }
                     }
                 });
+                self.keep_expanded_after_filter(worktree_id, entry_id);
                 self.update_visible_entries(Some((worktree_id, entry_id)), cx);
                 cx.focus(&self.focus_handle);
                 cx.notify();
@@ -565,6 +694,7 @@ impl ProjectPanel {
                 worktree_id: *worktree_id,
                 entry_id: worktree_entries[entry_ix].id,
             });
+            self.marked_entries.clear();
             self.autoscroll(cx);
             cx.notify();
         } else {
@@ -579,6 +709,19 @@ impl ProjectPanel {
     }
 
     fn open(&mut self, _: &Open, cx: &mut ViewContext<Self>) {
+        if !self.marked_entries.is_empty() {
+            let file_ids = self
+                .marked_or_selected_entries(cx)
+                .into_iter()
+                .filter(|(_, entry)| entry.is_file())
+                .map(|(_, entry)| entry.id)
+                .collect::<Vec<_>>();
+            for entry_id in file_ids {
+                self.open_entry(entry_id, false, cx);
+            }
+            return;
+        }
+
         if let Some((_, entry)) = self.selected_entry(cx) {
             if entry.is_file() {
                 self.open_entry(entry.id, true, cx);
@@ -597,6 +740,7 @@ impl ProjectPanel {
         let is_dir = edit_state.is_dir;
         let worktree = self.project.read(cx).worktree_for_id(worktree_id, cx)?;
         let entry = worktree.read(cx).entry_for_id(edit_state.entry_id)?.clone();
+        let old_path = entry.path.clone();
         let filename = self.filename_editor.read(cx).text(cx);
 
         let path_already_exists = |path| worktree.read(cx).entry_for_path(path).is_some();
@@ -644,6 +788,15 @@ impl ProjectPanel {
 
             if let Some(new_entry) = new_entry? {
                 this.update(&mut cx, |this, cx| {
+                    this.undo_stack.push(vec![if is_new_entry {
+                        FileOperation::Created((worktree_id, new_entry.path.clone()).into())
+                    } else {
+                        FileOperation::Renamed {
+                            worktree_id,
+                            old_path,
+                            new_path: new_entry.path.clone(),
+                        }
+                    }]);
                     if let Some(selection) = &mut this.selection {
                         if selection.entry_id == edited_entry_id {
                             selection.worktree_id = worktree_id;
@@ -663,7 +816,13 @@ impl ProjectPanel {
     }
 
     fn cancel(&mut self, _: &Cancel, cx: &mut ViewContext<Self>) {
+        if self.filter_editor.focus_handle(cx).is_focused(cx) {
+            self.clear_filter(cx);
+            return;
+        }
+
         self.edit_state = None;
+        self.marked_entries.clear();
         self.update_visible_entries(None, cx);
         cx.focus(&self.focus_handle);
         cx.notify();
@@ -750,6 +909,11 @@ impl ProjectPanel {
     }
 
     fn rename(&mut self, _: &Rename, cx: &mut ViewContext<Self>) {
+        if self.marked_entries.len() > 1 {
+            self.bulk_rename(cx);
+            return;
+        }
+
         if let Some(Selection {
             worktree_id,
             entry_id,
@@ -788,17 +952,70 @@ impl ProjectPanel {
         }
     }
 
+    fn bulk_rename(&mut self, cx: &mut ViewContext<Self>) {
+        let mut entries = self.marked_or_selected_entries(cx);
+        entries.retain(|(worktree, entry)| Some(*entry) != worktree.root_entry());
+        let entries = Self::disjoint_entries(entries)
+            .into_iter()
+            .map(|(worktree, entry)| BulkRenameEntry {
+                worktree_id: worktree.id(),
+                entry_id: entry.id,
+                path: entry.path.clone(),
+            })
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            return;
+        }
+
+        let project = self.project.clone();
+        let project_panel = cx.view().downgrade();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| {
+                    BulkRename::new(project, project_panel, entries, cx)
+                })
+            })
+            .ok();
+    }
+
     fn delete(&mut self, _: &Delete, cx: &mut ViewContext<Self>) {
         maybe!({
-            let Selection { entry_id, .. } = self.selection?;
-            let path = self.project.read(cx).path_for_entry(entry_id, cx)?.path;
-            let file_name = path.file_name()?;
+            let mut entries = self.marked_or_selected_entries(cx);
+            entries.retain(|(worktree, entry)| Some(*entry) != worktree.root_entry());
+            let entries = Self::disjoint_entries(entries)
+                .into_iter()
+                .filter_map(|(worktree, entry)| {
+                    let project_path = ProjectPath {
+                        worktree_id: worktree.id(),
+                        path: entry.path.clone(),
+                    };
+                    Some((
+                        entry.id,
+                        project_path,
+                        entry.path.file_name()?.to_os_string(),
+                    ))
+                })
+                .collect::<Vec<_>>();
+            let (message, detail) = match entries.as_slice() {
+                [] => return None,
+                [(_, _, file_name)] => (format!("Move {file_name:?} to the trash?"), None),
+                entries => (
+                    format!("Move {} entries to the trash?", entries.len()),
+                    Some(
+                        entries
+                            .iter()
+                            .map(|(_, _, file_name)| file_name.to_string_lossy())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    ),
+                ),
+            };
 
             let answer = cx.prompt(
                 PromptLevel::Info,
-                &format!("Delete {file_name:?}?"),
-                None,
-                &["Delete", "Cancel"],
+                &message,
+                detail.as_deref(),
+                &["Move to Trash", "Cancel"],
             );
 
             cx.spawn(|this, mut cx| async move {
@@ -806,11 +1023,29 @@ impl ProjectPanel {
                     return Ok(());
                 }
                 this.update(&mut cx, |this, cx| {
-                    this.project
-                        .update(cx, |project, cx| project.delete_entry(entry_id, cx))
-                        .ok_or_else(|| anyhow!("no such entry"))
-                })??
-                .await
+                    this.marked_entries.clear();
+                    cx.notify();
+                })?;
+                let mut operations = Vec::new();
+                let result = async {
+                    for (entry_id, project_path, _) in entries {
+                        this.update(&mut cx, |this, cx| {
+                            FileHistory::record_deleted_entry(&this.project, entry_id, cx)
+                        })?
+                        .await;
+                        this.update(&mut cx, |this, cx| {
+                            this.project
+                                .update(cx, |project, cx| project.delete_entry(entry_id, true, cx))
+                                .ok_or_else(|| anyhow!("no such entry"))
+                        })??
+                        .await?;
+                        operations.push(FileOperation::Deleted(project_path));
+                    }
+                    anyhow::Ok(())
+                }
+                .await;
+                this.update(&mut cx, |this, _| this.undo_stack.push(operations))?;
+                result
             })
             .detach_and_log_err(cx);
             Some(())
@@ -836,6 +1071,7 @@ impl ProjectPanel {
                         worktree_id: *worktree_id,
                         entry_id: entry.id,
                     });
+                    self.marked_entries.clear();
                     self.autoscroll(cx);
                     cx.notify();
                 }
@@ -872,21 +1108,17 @@ impl ProjectPanel {
     }
 
     fn cut(&mut self, _: &Cut, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            self.clipboard_entry = Some(ClipboardEntry::Cut {
-                worktree_id: worktree.id(),
-                entry_id: entry.id,
-            });
+        let items = self.marked_or_selected_selections(cx);
+        if !items.is_empty() {
+            self.clipboard_entry = Some(ClipboardEntry::Cut(items));
             cx.notify();
         }
     }
 
     fn copy(&mut self, _: &Copy, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            self.clipboard_entry = Some(ClipboardEntry::Copied {
-                worktree_id: worktree.id(),
-                entry_id: entry.id,
-            });
+        let items = self.marked_or_selected_selections(cx);
+        if !items.is_empty() {
+            self.clipboard_entry = Some(ClipboardEntry::Copied(items));
             cx.notify();
         }
     }
@@ -894,79 +1126,116 @@ impl ProjectPanel {
     fn paste(&mut self, _: &Paste, cx: &mut ViewContext<Self>) {
         maybe!({
             let (worktree, entry) = self.selected_entry(cx)?;
-            let clipboard_entry = self.clipboard_entry?;
-            if clipboard_entry.worktree_id() != worktree.id() {
-                return None;
-            }
-
-            let clipboard_entry_file_name = self
-                .project
-                .read(cx)
-                .path_for_entry(clipboard_entry.entry_id(), cx)?
-                .path
-                .file_name()?
-                .to_os_string();
-
-            let mut new_path = entry.path.to_path_buf();
-            // If we're pasting into a file, or a directory into itself, go up one level.
-            if entry.is_file() || (entry.is_dir() && entry.id == clipboard_entry.entry_id()) {
-                new_path.pop();
-            }
+            let clipboard_entry = self.clipboard_entry.as_ref()?;
+            let is_cut = clipboard_entry.is_cut();
+            let clipboard_entries = Self::disjoint_entries(
+                clipboard_entry
+                    .items()
+                    .iter()
+                    .filter(|item| item.worktree_id == worktree.id())
+                    .filter_map(|item| Some((worktree, worktree.entry_for_id(item.entry_id)?)))
+                    .collect(),
+            );
 
-            new_path.push(&clipboard_entry_file_name);
-            let extension = new_path.extension().map(|e| e.to_os_string());
-            let file_name_without_extension = Path::new(&clipboard_entry_file_name).file_stem()?;
-            let mut ix = 0;
-            while worktree.entry_for_path(&new_path).is_some() {
-                new_path.pop();
-
-                let mut new_file_name = file_name_without_extension.to_os_string();
-                new_file_name.push(" copy");
-                if ix > 0 {
-                    new_file_name.push(format!(" {}", ix));
+            // Paths claimed by earlier entries of this batch, which don't exist in the
+            // worktree until their tasks complete.
+            let mut pasted_paths = HashSet::default();
+            let mut new_paths = Vec::new();
+            for (_, clipboard_entry) in clipboard_entries {
+                let clipboard_entry_file_name = clipboard_entry.path.file_name()?.to_os_string();
+
+                let mut new_path = entry.path.to_path_buf();
+                // If we're pasting into a file, or a directory into itself, go up one level.
+                if entry.is_file() || (entry.is_dir() && entry.id == clipboard_entry.id) {
+                    new_path.pop();
                 }
-                if let Some(extension) = extension.as_ref() {
-                    new_file_name.push(".");
-                    new_file_name.push(extension);
+
+                new_path.push(&clipboard_entry_file_name);
+                let extension = new_path.extension().map(|e| e.to_os_string());
+                let file_name_without_extension =
+                    Path::new(&clipboard_entry_file_name).file_stem()?;
+                let mut ix = 0;
+                while worktree.entry_for_path(&new_path).is_some()
+                    || pasted_paths.contains(&new_path)
+                {
+                    new_path.pop();
+
+                    let mut new_file_name = file_name_without_extension.to_os_string();
+                    new_file_name.push(" copy");
+                    if ix > 0 {
+                        new_file_name.push(format!(" {}", ix));
+                    }
+                    if let Some(extension) = extension.as_ref() {
+                        new_file_name.push(".");
+                        new_file_name.push(extension);
+                    }
+
+                    new_path.push(new_file_name);
+                    ix += 1;
                 }
 
-                new_path.push(new_file_name);
-                ix += 1;
+                pasted_paths.insert(new_path.clone());
+                new_paths.push((clipboard_entry.id, clipboard_entry.path.clone(), new_path));
             }
 
-            if clipboard_entry.is_cut() {
-                self.project
-                    .update(cx, |project, cx| {
-                        project.rename_entry(clipboard_entry.entry_id(), new_path, cx)
-                    })
-                    .detach_and_log_err(cx)
-            } else {
-                self.project
-                    .update(cx, |project, cx| {
-                        project.copy_entry(clipboard_entry.entry_id(), new_path, cx)
-                    })
-                    .detach_and_log_err(cx)
-            }
+            let worktree_id = worktree.id();
+            let operations = new_paths
+                .into_iter()
+                .map(|(entry_id, old_path, new_path)| {
+                    if is_cut {
+                        let task = self.project.update(cx, |project, cx| {
+                            project.rename_entry(entry_id, new_path, cx)
+                        });
+                        cx.background_executor().spawn(async move {
+                            Ok(task.await?.map(|entry| FileOperation::Renamed {
+                                worktree_id,
+                                old_path,
+                                new_path: entry.path,
+                            }))
+                        })
+                    } else {
+                        let task = self
+                            .project
+                            .update(cx, |project, cx| project.copy_entry(entry_id, new_path, cx));
+                        cx.background_executor().spawn(async move {
+                            Ok(task.await?.map(|entry| {
+                                FileOperation::Created((worktree_id, entry.path).into())
+                            }))
+                        })
+                    }
+                })
+                .collect();
+            self.record_operations(operations, cx);
 
             Some(())
         });
     }
 
     fn copy_path(&mut self, _: &CopyPath, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            cx.write_to_clipboard(ClipboardItem::new(
+        let paths = self
+            .marked_or_selected_entries(cx)
+            .into_iter()
+            .map(|(worktree, entry)| {
                 worktree
                     .abs_path()
                     .join(&entry.path)
                     .to_string_lossy()
-                    .to_string(),
-            ));
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        if !paths.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new(paths.join("\n")));
         }
     }
 
     fn copy_relative_path(&mut self, _: &CopyRelativePath, cx: &mut ViewContext<Self>) {
-        if let Some((_, entry)) = self.selected_entry(cx) {
-            cx.write_to_clipboard(ClipboardItem::new(entry.path.to_string_lossy().to_string()));
+        let paths = self
+            .marked_or_selected_entries(cx)
+            .into_iter()
+            .map(|(_, entry)| entry.path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        if !paths.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new(paths.join("\n")));
         }
     }
 
@@ -1011,8 +1280,8 @@ impl ProjectPanel {
         destination: ProjectEntryId,
         destination_is_file: bool,
         cx: &mut ViewContext<Self>,
-    ) {
-        let destination_worktree = self.project.update(cx, |project, cx| {
+    ) -> Option<Task<Result<Option<FileOperation>>>> {
+        let (destination_worktree, task) = self.project.update(cx, |project, cx| {
             let entry_path = project.path_for_entry(entry_to_move, cx)?;
             let destination_entry_path = project.path_for_entry(destination, cx)?.path.clone();
 
@@ -1023,17 +1292,111 @@ impl ProjectPanel {
 
             let mut new_path = destination_path.to_path_buf();
             new_path.push(entry_path.path.file_name()?);
-            if new_path != entry_path.path.as_ref() {
+            let task = if new_path != entry_path.path.as_ref() {
                 let task = project.rename_entry(entry_to_move, new_path, cx);
-                cx.foreground_executor().spawn(task).detach_and_log_err(cx);
-            }
+                Some(cx.background_executor().spawn(async move {
+                    Ok(task.await?.map(|entry| FileOperation::Renamed {
+                        worktree_id: entry_path.worktree_id,
+                        old_path: entry_path.path,
+                        new_path: entry.path,
+                    }))
+                }))
+            } else {
+                None
+            };
 
-            Some(project.worktree_id_for_entry(destination, cx)?)
-        });
+            Some((project.worktree_id_for_entry(destination, cx)?, task))
+        })?;
+
+        self.expand_entry(destination_worktree, destination, cx);
+        task
+    }
+
+    /// Moves the dragged entry into the destination. If the dragged entry is marked, all the
+    /// marked entries in the destination's worktree are moved along with it.
+    fn drop_entry(
+        &mut self,
+        dragged_entry_id: ProjectEntryId,
+        destination: ProjectEntryId,
+        destination_is_file: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let is_marked = self
+            .marked_entries
+            .iter()
+            .any(|selection| selection.entry_id == dragged_entry_id);
+        if !is_marked {
+            let operations = self
+                .move_entry(dragged_entry_id, destination, destination_is_file, cx)
+                .into_iter()
+                .collect();
+            self.record_operations(operations, cx);
+            return;
+        }
+
+        let Some(destination_worktree_id) =
+            self.project.read(cx).worktree_id_for_entry(destination, cx)
+        else {
+            return;
+        };
+        let entries_to_move = Self::disjoint_entries(self.marked_or_selected_entries(cx))
+            .into_iter()
+            .filter(|(worktree, entry)| {
+                worktree.id() == destination_worktree_id && entry.id != destination
+            })
+            .map(|(_, entry)| entry.id)
+            .collect::<Vec<_>>();
+        let operations = entries_to_move
+            .into_iter()
+            .filter_map(|entry_id| self.move_entry(entry_id, destination, destination_is_file, cx))
+            .collect();
+        self.record_operations(operations, cx);
+    }
 
-        if let Some(destination_worktree) = destination_worktree {
-            self.expand_entry(destination_worktree, destination, cx);
+    /// Pushes the operations that succeed onto the undo stack as a single group, once they
+    /// all complete.
+    fn record_operations(
+        &mut self,
+        operations: Vec<Task<Result<Option<FileOperation>>>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if operations.is_empty() {
+            return;
         }
+        cx.spawn(|this, mut cx| async move {
+            let mut completed_operations = Vec::new();
+            for operation in operations {
+                if let Some(operation) = operation.await.log_err().flatten() {
+                    completed_operations.push(operation);
+                }
+            }
+            this.update(&mut cx, |this, _| {
+                this.undo_stack.push(completed_operations)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn undo(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(operations) = self.undo_stack.pop() else {
+            return;
+        };
+        let undo = undo_operations(self.project.clone(), operations, cx);
+        cx.spawn(|this, mut cx| async move {
+            if let Err(UndoError {
+                remaining_operations,
+                error,
+            }) = undo.await
+            {
+                // Keep the operations that weren't undone, so that undoing can be retried.
+                this.update(&mut cx, |this, _| {
+                    this.undo_stack.push(remaining_operations)
+                })?;
+                return Err(error);
+            }
+            Ok(())
+        })
+        .detach_and_prompt_err("Failed to undo file operation", cx, |_, _| None);
     }
 
     fn index_for_selection(&self, selection: Selection) -> Option<(usize, usize, usize)> {
@@ -1078,6 +1441,242 @@ impl ProjectPanel {
         Some((worktree, entry))
     }
 
+    /// Returns the entries that actions apply to: every marked entry, or the selected entry
+    /// if nothing is marked. Entries are ordered by worktree and then by path.
+    fn marked_or_selected_entries<'a>(
+        &self,
+        cx: &'a AppContext,
+    ) -> Vec<(&'a Worktree, &'a project::Entry)> {
+        let project = self.project.read(cx);
+        let mut entries = if self.marked_entries.is_empty() {
+            self.selected_entry(cx).into_iter().collect::<Vec<_>>()
+        } else {
+            self.marked_entries
+                .iter()
+                .filter_map(|selection| {
+                    let worktree = project.worktree_for_id(selection.worktree_id, cx)?;
+                    let worktree = worktree.read(cx);
+                    Some((worktree, worktree.entry_for_id(selection.entry_id)?))
+                })
+                .collect()
+        };
+        entries.sort_by(|(worktree_a, entry_a), (worktree_b, entry_b)| {
+            worktree_a
+                .id()
+                .cmp(&worktree_b.id())
+                .then_with(|| entry_a.path.cmp(&entry_b.path))
+        });
+        entries
+    }
+
+    fn marked_or_selected_selections(&self, cx: &AppContext) -> Vec<Selection> {
+        self.marked_or_selected_entries(cx)
+            .into_iter()
+            .map(|(worktree, entry)| Selection {
+                worktree_id: worktree.id(),
+                entry_id: entry.id,
+            })
+            .collect()
+    }
+
+    /// Drops the entries that are contained in a directory that is also in the list, so that
+    /// moving, pasting or deleting the directory takes care of them. Expects the entries to be
+    /// ordered by worktree and path.
+    fn disjoint_entries<'a>(
+        entries: Vec<(&'a Worktree, &'a project::Entry)>,
+    ) -> Vec<(&'a Worktree, &'a project::Entry)> {
+        let mut disjoint_entries: Vec<(&Worktree, &project::Entry)> = Vec::new();
+        for (worktree, entry) in entries {
+            let is_nested = disjoint_entries
+                .iter()
+                .any(|(ancestor_worktree, ancestor)| {
+                    ancestor_worktree.id() == worktree.id()
+                        && ancestor.is_dir()
+                        && entry.path.starts_with(&ancestor.path)
+                });
+            if !is_nested {
+                disjoint_entries.push((worktree, entry));
+            }
+        }
+        disjoint_entries
+    }
+
+    fn toggle_marked(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
+        let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx) else {
+            return;
+        };
+        let selection = Selection {
+            worktree_id,
+            entry_id,
+        };
+
+        // The first toggle starts a multi-selection that includes the selected entry.
+        if self.marked_entries.is_empty() {
+            self.marked_entries.extend(self.selection);
+        }
+        if !self.marked_entries.remove(&selection) {
+            self.marked_entries.insert(selection);
+        }
+        self.selection = Some(selection);
+        cx.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    /// Marks every visible entry between the selected entry and the given one. The selection
+    /// stays where it is, so that the range can be adjusted with further shift-clicks.
+    fn mark_range(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
+        let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx) else {
+            return;
+        };
+        let target = Selection {
+            worktree_id,
+            entry_id,
+        };
+        let anchor = *self.selection.get_or_insert(target);
+        let Some(((_, _, anchor_ix), (_, _, target_ix))) = self
+            .index_for_selection(anchor)
+            .zip(self.index_for_selection(target))
+        else {
+            return;
+        };
+
+        let range = anchor_ix.min(target_ix)..anchor_ix.max(target_ix) + 1;
+        self.marked_entries = self
+            .visible_entries
+            .iter()
+            .flat_map(|(worktree_id, entries)| {
+                entries.iter().map(|entry| Selection {
+                    worktree_id: *worktree_id,
+                    entry_id: entry.id,
+                })
+            })
+            .skip(range.start)
+            .take(range.len())
+            .filter(|selection| selection.entry_id != NEW_ENTRY_ID)
+            .collect();
+        cx.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    fn filter(&mut self, _: &Filter, cx: &mut ViewContext<Self>) {
+        self.show_filter = true;
+        self.filter_editor.update(cx, |editor, cx| {
+            editor.select_all(&editor::actions::SelectAll, cx)
+        });
+        cx.focus_view(&self.filter_editor);
+        cx.notify();
+    }
+
+    fn clear_filter(&mut self, cx: &mut ViewContext<Self>) {
+        self.show_filter = false;
+        self.filter_editor
+            .update(cx, |editor, cx| editor.set_text("", cx));
+        cx.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    fn filter_changed(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self.filter_editor.read(cx).text(cx).trim().to_string();
+        if query == self.filter_query {
+            return;
+        }
+
+        self.filter_query = query;
+        self.pending_filter = None;
+        if self.filter_query.is_empty() {
+            self.filter_matches.clear();
+            self.collapse_filter_expansions();
+            self.expand_to_selection(cx);
+        }
+        self.update_visible_entries(None, cx);
+        cx.notify();
+    }
+
+    /// Matches the filter query on the background executor, against the worktrees whose
+    /// entries changed since they were last matched.
+    fn update_filter_matches(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self.filter_query.clone();
+        let snapshots = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).snapshot())
+            .filter(|snapshot| {
+                self.filter_matches
+                    .get(&snapshot.id())
+                    .map_or(true, |matches| {
+                        matches.query != query || matches.scan_id != snapshot.scan_id()
+                    })
+            })
+            .collect::<Vec<_>>();
+        if snapshots.is_empty() {
+            return;
+        }
+
+        let matches = cx.background_executor().spawn(async move {
+            snapshots
+                .iter()
+                .map(|snapshot| (snapshot.id(), FilterMatches::new(query.clone(), snapshot)))
+                .collect::<Vec<_>>()
+        });
+        self.pending_filter = Some(cx.spawn(|this, mut cx| async move {
+            let matches = matches.await;
+            this.update(&mut cx, |this, cx| {
+                this.pending_filter = None;
+                for (worktree_id, matches) in matches {
+                    this.expand_filter_matches(worktree_id, &matches.ancestor_dir_ids);
+                    this.filter_matches.insert(worktree_id, matches);
+                }
+                this.update_visible_entries(None, cx);
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    /// Expands the directories containing the entries matching the filter query, remembering
+    /// the ones that weren't expanded yet so they can be collapsed with the filter.
+    fn expand_filter_matches(&mut self, worktree_id: WorktreeId, dir_ids: &[ProjectEntryId]) {
+        let expanded_dir_ids = self.expanded_dir_ids.entry(worktree_id).or_default();
+        let filter_expanded_dir_ids = self.filter_expanded_dir_ids.entry(worktree_id).or_default();
+        for &dir_id in dir_ids {
+            if let Err(ix) = expanded_dir_ids.binary_search(&dir_id) {
+                expanded_dir_ids.insert(ix, dir_id);
+                filter_expanded_dir_ids.push(dir_id);
+            }
+        }
+    }
+
+    fn collapse_filter_expansions(&mut self) {
+        for (worktree_id, mut dir_ids) in self.filter_expanded_dir_ids.drain() {
+            if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
+                dir_ids.sort_unstable();
+                expanded_dir_ids.retain(|dir_id| dir_ids.binary_search(dir_id).is_err());
+            }
+        }
+    }
+
+    /// Keeps a directory expanded by the user while filtering expanded once the filter is
+    /// cleared.
+    fn keep_expanded_after_filter(&mut self, worktree_id: WorktreeId, entry_id: ProjectEntryId) {
+        if let Some(dir_ids) = self.filter_expanded_dir_ids.get_mut(&worktree_id) {
+            dir_ids.retain(|dir_id| *dir_id != entry_id);
+        }
+    }
+
+    /// Returns the only child of a directory when it is itself a directory, in which case
+    /// the directory is merged into it when `compact_folders` is enabled.
+    fn folded_child<'a>(snapshot: &'a Snapshot, entry: &Entry) -> Option<&'a Entry> {
+        if entry.kind != EntryKind::Dir || entry.path.parent().is_none() {
+            return None;
+        }
+        let mut children = snapshot.child_entries(&entry.path);
+        match (children.next(), children.next()) {
+            (Some(child), None) if child.is_dir() => Some(child),
+            _ => None,
+        }
+    }
+
     fn expand_to_selection(&mut self, cx: &mut ViewContext<Self>) -> Option<()> {
         let (worktree, entry) = self.selected_entry(cx)?;
         let expanded_dir_ids = self.expanded_dir_ids.entry(worktree.id()).or_default();
@@ -1101,7 +1700,11 @@ impl ProjectPanel {
         new_selected_entry: Option<(WorktreeId, ProjectEntryId)>,
         cx: &mut ViewContext<Self>,
     ) {
-        let project = self.project.read(cx);
+        if !self.filter_query.is_empty() && self.pending_filter.is_none() {
+            self.update_filter_matches(cx);
+        }
+
+        let project = self.project.read(cx);
         self.last_worktree_root_id = project
             .visible_worktrees(cx)
             .rev()
@@ -1109,11 +1712,28 @@ impl ProjectPanel {
             .and_then(|worktree| worktree.read(cx).root_entry())
             .map(|entry| entry.id);
 
+        let compact_folders = ProjectPanelSettings::get_global(cx).compact_folders;
         self.visible_entries.clear();
+        self.folded_ancestor_counts.clear();
         for worktree in project.visible_worktrees(cx) {
             let snapshot = worktree.read(cx).snapshot();
             let worktree_id = snapshot.id();
 
+            // When filtering, only the matching entries and their ancestors are visible. Until
+            // a worktree is first matched against the query, all of its entries are.
+            let filtered_paths = if self.filter_query.is_empty() {
+                None
+            } else {
+                self.filter_matches
+                    .get(&worktree_id)
+                    .map(|matches| &matches.visible_paths)
+            };
+            let is_visible = |entry: &Entry| {
+                filtered_paths
+                    .as_ref()
+                    .map_or(true, |paths| paths.contains(&entry.path))
+            };
+
             let expanded_dir_ids = match self.expanded_dir_ids.entry(worktree_id) {
                 hash_map::Entry::Occupied(e) => e.into_mut(),
                 hash_map::Entry::Vacant(e) => {
@@ -1128,8 +1748,12 @@ impl ProjectPanel {
             };
 
             let mut new_entry_parent_id = None;
+            let mut edited_entry_id = None;
             let mut new_entry_kind = EntryKind::Dir;
             if let Some(edit_state) = &self.edit_state {
+                if edit_state.worktree_id == worktree_id {
+                    edited_entry_id = Some(edit_state.entry_id);
+                }
                 if edit_state.worktree_id == worktree_id && edit_state.is_new_entry {
                     new_entry_parent_id = Some(edit_state.entry_id);
                     new_entry_kind = if edit_state.is_dir {
@@ -1142,8 +1766,31 @@ impl ProjectPanel {
 
             let mut visible_worktree_entries = Vec::new();
             let mut entry_iter = snapshot.entries(true);
+            let mut folded_ancestor_count = 0;
 
             while let Some(entry) = entry_iter.entry() {
+                if !is_visible(entry) && Some(entry.id) != edited_entry_id {
+                    if !entry_iter.advance_to_sibling() {
+                        entry_iter.advance();
+                    }
+                    continue;
+                }
+
+                if compact_folders && Some(entry.id) != edited_entry_id {
+                    if let Some(child) = Self::folded_child(&snapshot, entry) {
+                        if is_visible(child) {
+                            folded_ancestor_count += 1;
+                            entry_iter.advance();
+                            continue;
+                        }
+                    }
+                }
+                if folded_ancestor_count > 0 {
+                    self.folded_ancestor_counts
+                        .insert(entry.id, folded_ancestor_count);
+                    folded_ancestor_count = 0;
+                }
+
                 visible_worktree_entries.push(entry.clone());
                 if Some(entry.id) == new_entry_parent_id {
                     visible_worktree_entries.push(Entry {
@@ -1315,27 +1962,58 @@ impl ProjectPanel {
                         }
                     };
 
-                    let mut details = EntryDetails {
-                        filename: entry
+                    let folded_ancestor_count = self
+                        .folded_ancestor_counts
+                        .get(&entry.id)
+                        .copied()
+                        .unwrap_or(0);
+                    let filename = match entry.path.ancestors().nth(folded_ancestor_count + 1) {
+                        Some(display_root) if folded_ancestor_count > 0 => entry
+                            .path
+                            .strip_prefix(display_root)
+                            .unwrap_or(&entry.path)
+                            .to_string_lossy()
+                            .to_string(),
+                        _ => entry
                             .path
                             .file_name()
                             .unwrap_or(root_name)
                             .to_string_lossy()
                             .to_string(),
+                    };
+
+                    // Entries nested in merged directories are indented as if the merged
+                    // directories were a single one.
+                    let folded_dir_count = if self.folded_ancestor_counts.is_empty() {
+                        0
+                    } else {
+                        entry
+                            .path
+                            .ancestors()
+                            .filter_map(|ancestor| snapshot.entry_for_path(ancestor))
+                            .filter_map(|ancestor| self.folded_ancestor_counts.get(&ancestor.id))
+                            .sum()
+                    };
+
+                    let mut details = EntryDetails {
+                        filename,
                         icon,
                         path: entry.path.clone(),
-                        depth: entry.path.components().count(),
+                        depth: entry.path.components().count() - folded_dir_count,
                         kind: entry.kind,
                         is_ignored: entry.is_ignored,
                         is_expanded,
                         is_selected: self.selection.map_or(false, |e| {
                             e.worktree_id == snapshot.id() && e.entry_id == entry.id
+                        }) || self.marked_entries.contains(&Selection {
+                            worktree_id: snapshot.id(),
+                            entry_id: entry.id,
                         }),
                         is_editing: false,
                         is_processing: false,
-                        is_cut: self
-                            .clipboard_entry
-                            .map_or(false, |e| e.is_cut() && e.entry_id() == entry.id),
+                        is_cut: self.clipboard_entry.as_ref().map_or(false, |e| {
+                            e.is_cut() && e.items().iter().any(|item| item.entry_id == entry.id)
+                        }),
                         git_status: status,
                         is_dotenv: entry.is_private,
                     };
@@ -1377,9 +2055,7 @@ impl ProjectPanel {
         let kind = details.kind;
         let settings = ProjectPanelSettings::get_global(cx);
         let show_editor = details.is_editing && !details.is_processing;
-        let is_selected = self
-            .selection
-            .map_or(false, |selection| selection.entry_id == entry_id);
+        let is_selected = details.is_selected;
         let width = self.width.unwrap_or(px(0.));
 
         let filename_text_color = details
@@ -1414,7 +2090,7 @@ impl ProjectPanel {
                 style.bg(cx.theme().colors().drop_target_background)
             })
             .on_drop(cx.listener(move |this, dragged_id: &ProjectEntryId, cx| {
-                this.move_entry(*dragged_id, entry_id, kind.is_file(), cx);
+                this.drop_entry(*dragged_id, entry_id, kind.is_file(), cx);
             }))
             .child(
                 ListItem::new(entry_id.to_proto() as usize)
@@ -1439,10 +2115,16 @@ impl ProjectPanel {
                             return;
                         }
                         if !show_editor {
-                            if kind.is_dir() {
+                            if event.down.modifiers.shift {
+                                this.mark_range(entry_id, cx);
+                            } else if event.down.modifiers.command {
+                                this.toggle_marked(entry_id, cx);
+                            } else if kind.is_dir() {
+                                this.marked_entries.clear();
                                 this.toggle_expanded(entry_id, cx);
                             } else {
-                                if event.down.modifiers.command {
+                                this.marked_entries.clear();
+                                if event.down.modifiers.alt {
                                     this.split_entry(entry_id, cx);
                                 } else {
                                     this.open_entry(entry_id, event.up.click_count > 1, cx);
@@ -1466,7 +2148,9 @@ impl ProjectPanel {
         dispatch_context.add("ProjectPanel");
         dispatch_context.add("menu");
 
-        let identifier = if self.filename_editor.focus_handle(cx).is_focused(cx) {
+        let identifier = if self.filename_editor.focus_handle(cx).is_focused(cx)
+            || self.filter_editor.focus_handle(cx).is_focused(cx)
+        {
             "editing"
         } else {
             "not_editing"
@@ -1508,7 +2192,7 @@ impl Render for ProjectPanel {
         let project = self.project.read(cx);
 
         if has_worktree {
-            div()
+            v_flex()
                 .id("project-panel")
                 .size_full()
                 .relative()
@@ -1524,6 +2208,7 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::copy_path))
                 .on_action(cx.listener(Self::copy_relative_path))
                 .on_action(cx.listener(Self::new_search_in_directory))
+                .on_action(cx.listener(Self::filter))
                 .when(!project.is_read_only(), |el| {
                     el.on_action(cx.listener(Self::new_file))
                         .on_action(cx.listener(Self::new_directory))
@@ -1548,6 +2233,16 @@ impl Render for ProjectPanel {
                     }),
                 )
                 .track_focus(&self.focus_handle)
+                .when(self.show_filter, |el| {
+                    el.child(
+                        div()
+                            .px_2()
+                            .py_1()
+                            .border_b_1()
+                            .border_color(cx.theme().colors().border)
+                            .child(self.filter_editor.clone()),
+                    )
+                })
                 .child(
                     uniform_list(
                         cx.view().clone(),
@@ -1566,7 +2261,8 @@ impl Render for ProjectPanel {
                             }
                         },
                     )
-                    .size_full()
+                    .w_full()
+                    .flex_1()
                     .track_scroll(self.list.clone()),
                 )
                 .children(self.context_menu.as_ref().map(|(menu, position, _)| {
@@ -1692,21 +2388,12 @@ impl FocusableView for ProjectPanel {
 
 impl ClipboardEntry {
     fn is_cut(&self) -> bool {
-        matches!(self, Self::Cut { .. })
-    }
-
-    fn entry_id(&self) -> ProjectEntryId {
-        match self {
-            ClipboardEntry::Copied { entry_id, .. } | ClipboardEntry::Cut { entry_id, .. } => {
-                *entry_id
-            }
-        }
+        matches!(self, Self::Cut(_))
     }
 
-    fn worktree_id(&self) -> WorktreeId {
+    fn items(&self) -> &[Selection] {
         match self {
-            ClipboardEntry::Copied { worktree_id, .. }
-            | ClipboardEntry::Cut { worktree_id, .. } => *worktree_id,
+            ClipboardEntry::Copied(items) | ClipboardEntry::Cut(items) => items,
         }
     }
 }
@@ -1717,7 +2404,7 @@ mod tests {
     use collections::HashSet;
     use gpui::{TestAppContext, View, VisualTestContext, WindowHandle};
     use pretty_assertions::assert_eq;
-    use project::{project_settings::ProjectSettings, FakeFs};
+    use project::{project_settings::ProjectSettings, FakeFs, Fs};
     use serde_json::json;
     use settings::SettingsStore;
     use std::path::{Path, PathBuf};
@@ -2379,139 +3066,586 @@ mod tests {
             ]
         );
 
-        let confirm = panel.update(cx, |panel, cx| {
-            panel.filename_editor.update(cx, |editor, cx| {
-                editor.set_text("/bdir1/dir2/the-new-filename", cx)
-            });
-            panel.confirm_edit(cx).unwrap()
-        });
+        let confirm = panel.update(cx, |panel, cx| {
+            panel.filename_editor.update(cx, |editor, cx| {
+                editor.set_text("/bdir1/dir2/the-new-filename", cx)
+            });
+            panel.confirm_edit(cx).unwrap()
+        });
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    > C",
+                "      [PROCESSING: '/bdir1/dir2/the-new-filename']  <== selected",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        confirm.await.unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..13, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    v bdir1",
+                "        v dir2",
+                "              the-new-filename  <== selected",
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_copy_paste(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                "one.two.txt": "",
+                "one.txt": ""
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        panel.update(cx, |panel, cx| {
+            panel.select_next(&Default::default(), cx);
+            panel.select_next(&Default::default(), cx);
+        });
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root1",
+                "      one.two.txt  <== selected",
+                "      one.txt",
+            ]
+        );
+
+        // Regression test - file name is created correctly when
+        // the copied file's name contains multiple dots.
+        panel.update(cx, |panel, cx| {
+            panel.copy(&Default::default(), cx);
+            panel.paste(&Default::default(), cx);
+        });
+        cx.executor().run_until_parked();
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root1",
+                "      one.two copy.txt",
+                "      one.two.txt  <== selected",
+                "      one.txt",
+            ]
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.paste(&Default::default(), cx);
+        });
+        cx.executor().run_until_parked();
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root1",
+                "      one.two copy 1.txt",
+                "      one.two copy.txt",
+                "      one.two.txt  <== selected",
+                "      one.txt",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_copy_paste_directory(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a": {
+                    "one.txt": "",
+                    "two.txt": "",
+                    "inner_dir": {
+                        "three.txt": "",
+                        "four.txt": "",
+                    }
+                },
+                "b": {}
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        select_path(&panel, "root/a", cx);
+        panel.update(cx, |panel, cx| {
+            panel.copy(&Default::default(), cx);
+            panel.select_next(&Default::default(), cx);
+            panel.paste(&Default::default(), cx);
+        });
+        cx.executor().run_until_parked();
+
+        let pasted_dir = find_project_entry(&panel, "root/b/a", cx);
+        assert_ne!(pasted_dir, None, "Pasted directory should have an entry");
+
+        let pasted_dir_file = find_project_entry(&panel, "root/b/a/one.txt", cx);
+        assert_ne!(
+            pasted_dir_file, None,
+            "Pasted directory file should have an entry"
+        );
+
+        let pasted_dir_inner_dir = find_project_entry(&panel, "root/b/a/inner_dir", cx);
+        assert_ne!(
+            pasted_dir_inner_dir, None,
+            "Directories inside pasted directory should have an entry"
+        );
+
+        toggle_expand_dir(&panel, "root/b", cx);
+        toggle_expand_dir(&panel, "root/b/a", cx);
+        toggle_expand_dir(&panel, "root/b/a/inner_dir", cx);
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root",
+                "    > a",
+                "    v b",
+                "        v a",
+                "            v inner_dir  <== selected",
+                "                  four.txt",
+                "                  three.txt",
+                "              one.txt",
+                "              two.txt",
+            ]
+        );
+
+        select_path(&panel, "root", cx);
+        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
+        cx.executor().run_until_parked();
+        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root  <== selected",
+                "    > a",
+                "    > a copy",
+                "    > a copy 1",
+                "    v b",
+                "        v a",
+                "            v inner_dir",
+                "                  four.txt",
+                "                  three.txt",
+                "              one.txt",
+                "              two.txt"
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_marking_entries(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a": {
+                    "one.txt": "",
+                    "two.txt": "",
+                },
+                "b": {
+                    "three.txt": "",
+                },
+                "four.txt": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        toggle_expand_dir(&panel, "root/a", cx);
+        select_path(&panel, "root/a/one.txt", cx);
+        toggle_marked_path(&panel, "root/four.txt", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root",
+                "    v a",
+                "          one.txt  <== selected",
+                "          two.txt",
+                "    > b",
+                "      four.txt  <== selected",
+            ]
+        );
+
+        toggle_marked_path(&panel, "root/a/one.txt", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root",
+                "    v a",
+                "          one.txt  <== selected",
+                "          two.txt",
+                "    > b",
+                "      four.txt  <== selected",
+            ],
+            "Unmarked entry should stay selected"
+        );
+
+        select_path(&panel, "root/a/two.txt", cx);
+        let four = find_project_entry(&panel, "root/four.txt", cx).unwrap();
+        panel.update(cx, |panel, cx| panel.mark_range(four, cx));
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root",
+                "    v a",
+                "          one.txt",
+                "          two.txt  <== selected",
+                "    > b  <== selected",
+                "      four.txt  <== selected",
+            ]
+        );
+
+        panel.update(cx, |panel, cx| panel.select_next(&SelectNext, cx));
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root",
+                "    v a",
+                "          one.txt",
+                "          two.txt",
+                "    > b  <== selected",
+                "      four.txt",
+            ],
+            "Moving the selection should clear the marked entries"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_bulk_copy_paste_and_delete(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a": {
+                    "one.txt": "",
+                    "two.txt": "",
+                    "three.txt": "",
+                },
+                "b": {},
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        toggle_expand_dir(&panel, "root/a", cx);
+        select_path(&panel, "root/a/one.txt", cx);
+        toggle_marked_path(&panel, "root/a/two.txt", cx);
+        panel.update(cx, |panel, cx| panel.copy(&Copy, cx));
+        select_path(&panel, "root/b", cx);
+        panel.update(cx, |panel, cx| panel.paste(&Paste, cx));
+        cx.executor().run_until_parked();
+        panel.update(cx, |panel, cx| panel.cancel(&Cancel, cx));
+        toggle_expand_dir(&panel, "root/b", cx);
 
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
+            visible_entries_as_strings(&panel, 0..50, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    > C",
-                "      [PROCESSING: '/bdir1/dir2/the-new-filename']  <== selected",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
+                "v root",
+                "    v a",
+                "          one.txt",
+                "          three.txt",
+                "          two.txt",
+                "    v b  <== selected",
+                "          one.txt",
+                "          two.txt",
             ]
         );
 
-        confirm.await.unwrap();
+        select_path(&panel, "root/a/one.txt", cx);
+        toggle_marked_path(&panel, "root/a/three.txt", cx);
+        submit_deletion(&panel, cx);
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..13, cx),
+            visible_entries_as_strings(&panel, 0..50, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    v bdir1",
-                "        v dir2",
-                "              the-new-filename  <== selected",
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
+                "v root",
+                "    v a",
+                "          two.txt",
+                "    v b",
+                "          one.txt",
+                "          two.txt",
             ]
         );
     }
 
     #[gpui::test]
-    async fn test_copy_paste(cx: &mut gpui::TestAppContext) {
+    async fn test_undo_file_operations(cx: &mut gpui::TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root1",
+            "/root",
             json!({
-                "one.two.txt": "",
-                "one.txt": ""
+                "a": {
+                    "one.txt": "",
+                    "two.txt": "",
+                },
+                "b": {},
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
 
-        panel.update(cx, |panel, cx| {
-            panel.select_next(&Default::default(), cx);
-            panel.select_next(&Default::default(), cx);
-        });
+        toggle_expand_dir(&panel, "root/a", cx);
+        select_path(&panel, "root/a/one.txt", cx);
+        panel.update(cx, |panel, cx| panel.cut(&Cut, cx));
+        select_path(&panel, "root/b", cx);
+        panel.update(cx, |panel, cx| panel.paste(&Paste, cx));
+        cx.executor().run_until_parked();
 
+        select_path(&panel, "root/a/two.txt", cx);
+        submit_deletion(&panel, cx);
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
-            &[
-                //
-                "v root1",
-                "      one.two.txt  <== selected",
-                "      one.txt",
-            ]
+            fs.files(),
+            [PathBuf::from("/root/b/one.txt")],
+            "the pasted file should be moved and the deleted file trashed"
         );
+        assert_eq!(fs.trashed_paths(), [PathBuf::from("/root/a/two.txt")]);
 
-        // Regression test - file name is created correctly when
-        // the copied file's name contains multiple dots.
-        panel.update(cx, |panel, cx| {
-            panel.copy(&Default::default(), cx);
-            panel.paste(&Default::default(), cx);
+        panel.update(cx, |panel, cx| panel.undo(cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            fs.files(),
+            [
+                PathBuf::from("/root/a/two.txt"),
+                PathBuf::from("/root/b/one.txt"),
+            ],
+            "undoing the deletion should restore the file from the trash"
+        );
+        assert!(fs.trashed_paths().is_empty());
+
+        panel.update(cx, |panel, cx| panel.undo(cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            fs.files(),
+            [
+                PathBuf::from("/root/a/one.txt"),
+                PathBuf::from("/root/a/two.txt"),
+            ],
+            "undoing the paste should move the file back"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_undo_file_operations_in_reverse_order(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root", json!({ "a": { "one.txt": "" } }))
+            .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        let worktree_id =
+            cx.update(|cx| project.read(cx).worktrees().next().unwrap().read(cx).id());
+
+        // A file is renamed, then a new file takes its old path.
+        fs.rename(
+            "/root/a/one.txt".as_ref(),
+            "/root/a/two.txt".as_ref(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        fs.insert_file("/root/a/one.txt", String::new()).await;
+        cx.executor().run_until_parked();
+        panel.update(cx, |panel, _| {
+            panel.undo_stack.push(vec![
+                FileOperation::Renamed {
+                    worktree_id,
+                    old_path: Path::new("a/one.txt").into(),
+                    new_path: Path::new("a/two.txt").into(),
+                },
+                FileOperation::Created((worktree_id, "a/one.txt").into()),
+            ])
         });
+
+        // Undoing fails when the created file is already gone, and keeps the group.
+        fs.remove_file("/root/a/one.txt".as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.executor().run_until_parked();
+        panel.update(cx, |panel, cx| panel.undo(cx));
+        cx.executor().run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(0);
+        assert_eq!(fs.files(), [PathBuf::from("/root/a/two.txt")]);
+
+        // Once the file is back, the creation is undone before the rename.
+        fs.insert_file("/root/a/one.txt", String::new()).await;
         cx.executor().run_until_parked();
+        panel.update(cx, |panel, cx| panel.undo(cx));
+        cx.executor().run_until_parked();
+        assert_eq!(fs.files(), [PathBuf::from("/root/a/one.txt")]);
+        assert_eq!(fs.trashed_paths(), [PathBuf::from("/root/a/one.txt")]);
+    }
+
+    #[gpui::test]
+    async fn test_compact_folders(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.compact_folders = Some(true)
+                });
+            })
+        });
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "src": {
+                    "main": {
+                        "java": {
+                            "com": {
+                                "Main.java": "",
+                                "Util.java": "",
+                            }
+                        }
+                    },
+                    "test": {
+                        "java": {}
+                    }
+                },
+                "README.md": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
 
+        toggle_expand_dir(&panel, "root/src", cx);
+        toggle_expand_dir(&panel, "root/src/main/java/com", cx);
         assert_eq!(
             visible_entries_as_strings(&panel, 0..50, cx),
             &[
-                //
-                "v root1",
-                "      one.two copy.txt",
-                "      one.two.txt  <== selected",
-                "      one.txt",
+                "v root",
+                "    v src",
+                "        v main/java/com",
+                "              Main.java",
+                "              Util.java",
+                "        > test/java",
+                "      README.md",
             ]
         );
 
-        panel.update(cx, |panel, cx| {
-            panel.paste(&Default::default(), cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.compact_folders = Some(false)
+                });
+            })
         });
-        cx.executor().run_until_parked();
-
         assert_eq!(
             visible_entries_as_strings(&panel, 0..50, cx),
             &[
-                //
-                "v root1",
-                "      one.two copy 1.txt",
-                "      one.two copy.txt",
-                "      one.two.txt  <== selected",
-                "      one.txt",
+                "v root",
+                "    v src",
+                "        > main",
+                "        > test",
+                "      README.md",
             ]
         );
     }
 
     #[gpui::test]
-    async fn test_copy_paste_directory(cx: &mut gpui::TestAppContext) {
+    async fn test_filter_entries(cx: &mut gpui::TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
             "/root",
             json!({
-                "a": {
-                    "one.txt": "",
-                    "two.txt": "",
-                    "inner_dir": {
-                        "three.txt": "",
-                        "four.txt": "",
+                "crates": {
+                    "editor": {
+                        "src": {
+                            "editor.rs": "",
+                            "element.rs": "",
+                        }
+                    },
+                    "project": {
+                        "src": {
+                            "project.rs": "",
+                        }
                     }
                 },
-                "b": {}
+                "README.md": "",
             }),
         )
         .await;
@@ -2523,69 +3657,70 @@ mod tests {
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
 
-        select_path(&panel, "root/a", cx);
         panel.update(cx, |panel, cx| {
-            panel.copy(&Default::default(), cx);
-            panel.select_next(&Default::default(), cx);
-            panel.paste(&Default::default(), cx);
+            panel.filter(&Filter, cx);
+            panel
+                .filter_editor
+                .update(cx, |editor, cx| editor.set_text("elemrs", cx));
         });
-        cx.executor().run_until_parked();
-
-        let pasted_dir = find_project_entry(&panel, "root/b/a", cx);
-        assert_ne!(pasted_dir, None, "Pasted directory should have an entry");
-
-        let pasted_dir_file = find_project_entry(&panel, "root/b/a/one.txt", cx);
-        assert_ne!(
-            pasted_dir_file, None,
-            "Pasted directory file should have an entry"
-        );
-
-        let pasted_dir_inner_dir = find_project_entry(&panel, "root/b/a/inner_dir", cx);
-        assert_ne!(
-            pasted_dir_inner_dir, None,
-            "Directories inside pasted directory should have an entry"
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root",
+                "    v crates",
+                "        v editor",
+                "            v src",
+                "                  element.rs",
+            ]
         );
 
-        toggle_expand_dir(&panel, "root/b", cx);
-        toggle_expand_dir(&panel, "root/b/a", cx);
-        toggle_expand_dir(&panel, "root/b/a/inner_dir", cx);
-
+        // Matches are updated when the worktree changes.
+        fs.insert_file("/root/crates/project/src/elements.rs", "".into())
+            .await;
+        cx.run_until_parked();
         assert_eq!(
             visible_entries_as_strings(&panel, 0..50, cx),
             &[
-                //
                 "v root",
-                "    > a",
-                "    v b",
-                "        v a",
-                "            v inner_dir  <== selected",
-                "                  four.txt",
-                "                  three.txt",
-                "              one.txt",
-                "              two.txt",
+                "    v crates",
+                "        v editor",
+                "            v src",
+                "                  element.rs",
+                "        v project",
+                "            v src",
+                "                  elements.rs",
             ]
         );
 
-        select_path(&panel, "root", cx);
-        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
-        cx.executor().run_until_parked();
-        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
-        cx.executor().run_until_parked();
+        panel.update(cx, |panel, cx| panel.clear_filter(cx));
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &["v root", "    > crates", "      README.md"],
+            "directories expanded only for the filter should be collapsed once it is cleared"
+        );
+
+        // Directories expanded by the user while filtering stay expanded.
+        panel.update(cx, |panel, cx| {
+            panel.filter(&Filter, cx);
+            panel
+                .filter_editor
+                .update(cx, |editor, cx| editor.set_text("elemrs", cx));
+        });
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "root/crates", cx);
+        toggle_expand_dir(&panel, "root/crates", cx);
+        panel.update(cx, |panel, cx| panel.clear_filter(cx));
+        cx.run_until_parked();
         assert_eq!(
             visible_entries_as_strings(&panel, 0..50, cx),
             &[
-                //
-                "v root  <== selected",
-                "    > a",
-                "    > a copy",
-                "    > a copy 1",
-                "    v b",
-                "        v a",
-                "            v inner_dir",
-                "                  four.txt",
-                "                  three.txt",
-                "              one.txt",
-                "              two.txt"
+                "v root",
+                "    v crates",
+                "        > editor",
+                "        > project",
+                "      README.md",
             ]
         );
     }
@@ -3499,6 +4634,15 @@ mod tests {
         });
     }
 
+    fn toggle_marked_path(
+        panel: &View<ProjectPanel>,
+        path: impl AsRef<Path>,
+        cx: &mut VisualTestContext,
+    ) {
+        let entry_id = find_project_entry(panel, path, cx).expect("no entry for path");
+        panel.update(cx, |panel, cx| panel.toggle_marked(entry_id, cx));
+    }
+
     fn find_project_entry(
         panel: &View<ProjectPanel>,
         path: impl AsRef<Path>,
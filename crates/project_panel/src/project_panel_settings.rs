@@ -20,6 +20,7 @@ pub struct ProjectPanelSettings {
     pub git_status: bool,
     pub indent_size: f32,
     pub auto_reveal_entries: bool,
+    pub compact_folders: bool,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
@@ -54,6 +55,11 @@ pub struct ProjectPanelSettingsContent {
     ///
     /// Default: true
     pub auto_reveal_entries: Option<bool>,
+    /// Whether to merge chains of directories that each contain a single directory
+    /// into one entry, e.g. `src/main/java`.
+    ///
+    /// Default: false
+    pub compact_folders: Option<bool>,
 }
 
 impl Settings for ProjectPanelSettings {
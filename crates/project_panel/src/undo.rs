@@ -0,0 +1,147 @@
+use crate::bulk_rename::{rename_entries, BulkRenameEntry};
+use anyhow::{anyhow, Result};
+use gpui::{AppContext, AsyncAppContext, Model, Task};
+use project::{Project, ProjectPath, WorktreeId};
+use std::{path::Path, sync::Arc};
+
+/// The number of groups of operations that can be undone.
+const MAX_UNDO_GROUPS: usize = 100;
+
+/// A file operation performed from the project panel, recorded so that it can be undone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum FileOperation {
+    Created(ProjectPath),
+    /// A rename or a move within a worktree.
+    Renamed {
+        worktree_id: WorktreeId,
+        old_path: Arc<Path>,
+        new_path: Arc<Path>,
+    },
+    /// A deletion that moved the entry to the trash.
+    Deleted(ProjectPath),
+}
+
+/// Groups of file operations, most recent last. Each group is the result of a single action,
+/// such as pasting or deleting several entries at once, and is undone as a whole.
+#[derive(Default)]
+pub(crate) struct UndoStack {
+    groups: Vec<Vec<FileOperation>>,
+}
+
+impl UndoStack {
+    pub fn push(&mut self, operations: Vec<FileOperation>) {
+        if operations.is_empty() {
+            return;
+        }
+        if self.groups.len() == MAX_UNDO_GROUPS {
+            self.groups.remove(0);
+        }
+        self.groups.push(operations);
+    }
+
+    pub fn pop(&mut self) -> Option<Vec<FileOperation>> {
+        self.groups.pop()
+    }
+}
+
+/// An undo that stopped at an operation that could not be reverted.
+pub(crate) struct UndoError {
+    /// The operations that were not reverted, in the order they were performed in.
+    pub remaining_operations: Vec<FileOperation>,
+    pub error: anyhow::Error,
+}
+
+/// Reverts a group of operations in the reverse order they were performed in: created entries
+/// are moved to the trash, renamed entries get their old path back and deleted entries are
+/// restored from the trash. Consecutive renames are reverted together, so that entries that
+/// swapped paths can get them back.
+pub(crate) fn undo_operations(
+    project: Model<Project>,
+    mut operations: Vec<FileOperation>,
+    cx: &mut AppContext,
+) -> Task<Result<(), UndoError>> {
+    cx.spawn(|mut cx| async move {
+        while let Some(operation) = operations.last() {
+            let (remaining_len, result) = match operation {
+                FileOperation::Renamed { .. } => {
+                    let renames_start = operations
+                        .iter()
+                        .rposition(|operation| !matches!(operation, FileOperation::Renamed { .. }))
+                        .map_or(0, |ix| ix + 1);
+                    let result =
+                        undo_renames(&project, &operations[renames_start..], &mut cx).await;
+                    (renames_start, result)
+                }
+                FileOperation::Created(project_path) => {
+                    let result = async {
+                        project
+                            .update(&mut cx, |project, cx| {
+                                let entry_id = project.entry_for_path(project_path, cx)?.id;
+                                project.delete_entry(entry_id, true, cx)
+                            })?
+                            .ok_or_else(|| anyhow!("{:?} no longer exists", project_path.path))?
+                            .await
+                    }
+                    .await;
+                    (operations.len() - 1, result)
+                }
+                FileOperation::Deleted(project_path) => {
+                    let result = async {
+                        project
+                            .update(&mut cx, |project, cx| {
+                                project.restore_entry(project_path.clone(), cx)
+                            })?
+                            .await?;
+                        Ok(())
+                    }
+                    .await;
+                    (operations.len() - 1, result)
+                }
+            };
+            if let Err(error) = result {
+                return Err(UndoError {
+                    remaining_operations: operations,
+                    error,
+                });
+            }
+            operations.truncate(remaining_len);
+        }
+        Ok(())
+    })
+}
+
+/// Gives the renamed entries their old path back, most recent rename first.
+async fn undo_renames(
+    project: &Model<Project>,
+    operations: &[FileOperation],
+    cx: &mut AsyncAppContext,
+) -> Result<()> {
+    let mut renames = Vec::new();
+    for operation in operations.iter().rev() {
+        let FileOperation::Renamed {
+            worktree_id,
+            old_path,
+            new_path,
+        } = operation
+        else {
+            continue;
+        };
+        let entry_id = project
+            .update(cx, |project, cx| {
+                project
+                    .entry_for_path(&(*worktree_id, new_path.clone()).into(), cx)
+                    .map(|entry| entry.id)
+            })?
+            .ok_or_else(|| anyhow!("{new_path:?} no longer exists"))?;
+        renames.push((
+            BulkRenameEntry {
+                worktree_id: *worktree_id,
+                entry_id,
+                path: new_path.clone(),
+            },
+            old_path.clone(),
+        ));
+    }
+    cx.update(|cx| rename_entries(project.clone(), renames, cx))?
+        .await
+}
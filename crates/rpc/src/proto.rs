@@ -140,8 +140,12 @@ messages!(
     (AddProjectCollaborator, Foreground),
     (ApplyCodeAction, Background),
     (ApplyCodeActionResponse, Background),
+    (ApplyCodeLens, Background),
+    (ApplyCodeLensResponse, Background),
     (ApplyCompletionAdditionalEdits, Background),
     (ApplyCompletionAdditionalEditsResponse, Background),
+    (BlameBuffer, Background),
+    (BlameBufferResponse, Background),
     (BufferReloaded, Foreground),
     (BufferSaved, Foreground),
     (Call, Foreground),
@@ -164,6 +168,7 @@ messages!(
     (ExpandProjectEntryResponse, Foreground),
     (Follow, Foreground),
     (FollowResponse, Foreground),
+    (FollowerInput, Foreground),
     (FormatBuffers, Foreground),
     (FormatBuffersResponse, Foreground),
     (FuzzySearchUsers, Foreground),
@@ -182,12 +187,24 @@ messages!(
     (GetDocumentHighlightsResponse, Background),
     (GetHover, Background),
     (GetHoverResponse, Background),
+    (GetSignatureHelp, Background),
+    (GetSignatureHelpResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetHierarchyChildren, Background),
+    (GetHierarchyChildrenResponse, Background),
     (GetNotifications, Foreground),
     (GetNotificationsResponse, Foreground),
     (GetPrivateUserInfo, Foreground),
     (GetPrivateUserInfoResponse, Foreground),
     (GetProjectSymbols, Background),
     (GetProjectSymbolsResponse, Background),
+    (GetProjectTasks, Foreground),
+    (GetProjectTasksResponse, Foreground),
     (GetReferences, Background),
     (GetReferencesResponse, Background),
     (GetTypeDefinition, Background),
@@ -227,8 +244,14 @@ messages!(
     (Ping, Foreground),
     (PrepareRename, Background),
     (PrepareRenameResponse, Background),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (PrepareTypeHierarchyResponse, Background),
     (ProjectEntryResponse, Foreground),
     (RefreshInlayHints, Foreground),
+    (RefreshSemanticTokens, Foreground),
+    (RefreshCodeLens, Foreground),
     (RejoinChannelBuffers, Foreground),
     (RejoinChannelBuffersResponse, Foreground),
     (RejoinRoom, Foreground),
@@ -242,13 +265,18 @@ messages!(
     (RenameChannel, Foreground),
     (RenameChannelResponse, Foreground),
     (RenameProjectEntry, Foreground),
+    (RestoreProjectEntry, Foreground),
     (RequestContact, Foreground),
     (ResolveCompletionDocumentation, Background),
     (ResolveCompletionDocumentationResponse, Background),
     (ResolveInlayHint, Background),
     (ResolveInlayHintResponse, Background),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
     (RespondToChannelInvite, Foreground),
     (RespondToContactRequest, Foreground),
+    (RunProjectTask, Foreground),
+    (RunProjectTaskResponse, Foreground),
     (RoomUpdated, Foreground),
     (SaveBuffer, Foreground),
     (SetChannelMemberRole, Foreground),
@@ -281,6 +309,7 @@ messages!(
     (UpdateParticipantLocation, Foreground),
     (UpdateProject, Foreground),
     (UpdateProjectCollaborator, Foreground),
+    (UpdateTaskTerminal, Foreground),
     (UpdateWorktree, Foreground),
     (UpdateWorktreeSettings, Foreground),
     (UsersResponse, Foreground),
@@ -291,10 +320,12 @@ messages!(
 
 request_messages!(
     (ApplyCodeAction, ApplyCodeActionResponse),
+    (ApplyCodeLens, ApplyCodeLensResponse),
     (
         ApplyCompletionAdditionalEdits,
         ApplyCompletionAdditionalEditsResponse
     ),
+    (BlameBuffer, BlameBufferResponse),
     (Call, Ack),
     (CancelCall, Ack),
     (CopyProjectEntry, ProjectEntryResponse),
@@ -304,6 +335,7 @@ request_messages!(
     (DeclineCall, Ack),
     (DeleteChannel, Ack),
     (DeleteProjectEntry, ProjectEntryResponse),
+    (RestoreProjectEntry, ProjectEntryResponse),
     (ExpandProjectEntry, ExpandProjectEntryResponse),
     (Follow, FollowResponse),
     (FormatBuffers, FormatBuffersResponse),
@@ -317,9 +349,15 @@ request_messages!(
     (GetImplementation, GetImplementationResponse),
     (GetDocumentHighlights, GetDocumentHighlightsResponse),
     (GetHover, GetHoverResponse),
+    (GetSignatureHelp, GetSignatureHelpResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (GetCodeLens, GetCodeLensResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetHierarchyChildren, GetHierarchyChildrenResponse),
     (GetNotifications, GetNotificationsResponse),
     (GetPrivateUserInfo, GetPrivateUserInfoResponse),
     (GetProjectSymbols, GetProjectSymbolsResponse),
+    (GetProjectTasks, GetProjectTasksResponse),
     (GetReferences, GetReferencesResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
     (GetUsers, UsersResponse),
@@ -342,7 +380,11 @@ request_messages!(
     (PerformRename, PerformRenameResponse),
     (Ping, Ack),
     (PrepareRename, PrepareRenameResponse),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse),
     (RefreshInlayHints, Ack),
+    (RefreshSemanticTokens, Ack),
+    (RefreshCodeLens, Ack),
     (RejoinChannelBuffers, RejoinChannelBuffersResponse),
     (RejoinRoom, RejoinRoomResponse),
     (ReloadBuffers, ReloadBuffersResponse),
@@ -357,8 +399,10 @@ request_messages!(
         ResolveCompletionDocumentationResponse
     ),
     (ResolveInlayHint, ResolveInlayHintResponse),
+    (ResolveCodeLens, ResolveCodeLensResponse),
     (RespondToChannelInvite, Ack),
     (RespondToContactRequest, Ack),
+    (RunProjectTask, RunProjectTaskResponse),
     (SaveBuffer, BufferSaved),
     (SearchProject, SearchProjectResponse),
     (SendChannelMessage, SendChannelMessageResponse),
@@ -379,13 +423,16 @@ entity_messages!(
     {project_id, ShareProject},
     AddProjectCollaborator,
     ApplyCodeAction,
+    ApplyCodeLens,
     ApplyCompletionAdditionalEdits,
+    BlameBuffer,
     BufferReloaded,
     BufferSaved,
     CopyProjectEntry,
     CreateBufferForPeer,
     CreateProjectEntry,
     DeleteProjectEntry,
+    RestoreProjectEntry,
     ExpandProjectEntry,
     FormatBuffers,
     GetCodeActions,
@@ -394,7 +441,13 @@ entity_messages!(
     GetImplementation,
     GetDocumentHighlights,
     GetHover,
+    GetSignatureHelp,
+    GetSemanticTokens,
+    GetCodeLens,
+    GetFoldingRanges,
+    GetHierarchyChildren,
     GetProjectSymbols,
+    GetProjectTasks,
     GetReferences,
     GetTypeDefinition,
     InlayHints,
@@ -406,12 +459,18 @@ entity_messages!(
     OpenBufferForSymbol,
     PerformRename,
     PrepareRename,
+    PrepareCallHierarchy,
+    PrepareTypeHierarchy,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
     ResolveCompletionDocumentation,
     ResolveInlayHint,
+    ResolveCodeLens,
+    RunProjectTask,
     SaveBuffer,
     SearchProject,
     StartLanguageServer,
@@ -424,6 +483,7 @@ entity_messages!(
     UpdateLanguageServer,
     UpdateProject,
     UpdateProjectCollaborator,
+    UpdateTaskTerminal,
     UpdateWorktree,
     UpdateWorktreeSettings,
     LspExtExpandMacro,
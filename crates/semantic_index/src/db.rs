@@ -40,14 +40,19 @@ pub struct FileRecord {
 #[derive(Clone)]
 pub struct VectorDatabase {
     path: Arc<Path>,
+    embedding_model: Arc<str>,
     transactions:
         smol::channel::Sender<Box<dyn 'static + Send + FnOnce(&mut rusqlite::Connection)>>,
 }
 
 impl VectorDatabase {
+    /// Opens the database at `path`, whose embeddings were computed by `embedding_model`.
+    /// When they were computed by another model, the database is emptied, since embeddings
+    /// of different models can't be compared.
     pub async fn new(
         fs: Arc<dyn Fs>,
         path: Arc<Path>,
+        embedding_model: Arc<str>,
         executor: BackgroundExecutor,
     ) -> Result<Self> {
         if let Some(db_directory) = path.parent() {
@@ -80,6 +85,7 @@ impl VectorDatabase {
         let this = Self {
             transactions: transactions_tx,
             path,
+            embedding_model,
         };
         this.initialize_database().await?;
         Ok(this)
@@ -89,6 +95,10 @@ impl VectorDatabase {
         &self.path
     }
 
+    pub fn embedding_model(&self) -> &Arc<str> {
+        &self.embedding_model
+    }
+
     fn transact<F, T>(&self, f: F) -> impl Future<Output = Result<T>>
     where
         F: 'static + Send + FnOnce(&rusqlite::Transaction) -> Result<T>,
@@ -119,19 +129,27 @@ impl VectorDatabase {
     }
 
     fn initialize_database(&self) -> impl Future<Output = Result<()>> {
-        self.transact(|db| {
+        let embedding_model = self.embedding_model.clone();
+        self.transact(move |db| {
             rusqlite::vtab::array::load_module(&db)?;
 
-            // Delete existing tables, if SEMANTIC_INDEX_VERSION is bumped
-            let version_query = db.prepare("SELECT version from semantic_index_config");
-            let version = version_query
-                .and_then(|mut query| query.query_row([], |row| Ok(row.get::<_, i64>(0)?)));
-            if version.map_or(false, |version| version == SEMANTIC_INDEX_VERSION as i64) {
+            // Delete existing tables, if SEMANTIC_INDEX_VERSION is bumped or the embedding
+            // model changed
+            let config_query =
+                db.prepare("SELECT version, embedding_model from semantic_index_config");
+            let config = config_query.and_then(|mut query| {
+                query.query_row([], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                })
+            });
+            if config.map_or(false, |(version, model)| {
+                version == SEMANTIC_INDEX_VERSION as i64 && *model == *embedding_model
+            }) {
                 log::trace!("vector database schema up to date");
                 return Ok(());
             }
 
-            log::trace!("vector database schema out of date. updating...");
+            log::trace!("vector database schema or embedding model out of date. updating...");
             // We renamed the `documents` table to `spans`, so we want to drop
             // `documents` without recreating it if it exists.
             db.execute("DROP TABLE IF EXISTS documents", [])
@@ -148,14 +166,15 @@ impl VectorDatabase {
             // Initialize Vector Databasing Tables
             db.execute(
                 "CREATE TABLE semantic_index_config (
-                    version INTEGER NOT NULL
+                    version INTEGER NOT NULL,
+                    embedding_model VARCHAR NOT NULL
                 )",
                 [],
             )?;
 
             db.execute(
-                "INSERT INTO semantic_index_config (version) VALUES (?1)",
-                params![SEMANTIC_INDEX_VERSION],
+                "INSERT INTO semantic_index_config (version, embedding_model) VALUES (?1, ?2)",
+                params![SEMANTIC_INDEX_VERSION, embedding_model.as_ref()],
             )?;
 
             db.execute(
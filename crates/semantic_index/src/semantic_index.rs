@@ -6,8 +6,9 @@ pub mod semantic_index_settings;
 #[cfg(test)]
 mod semantic_index_tests;
 
-use crate::semantic_index_settings::SemanticIndexSettings;
+use crate::semantic_index_settings::{EmbeddingProviderSettings, SemanticIndexSettings};
 use ai::embedding::{Embedding, EmbeddingProvider};
+use ai::providers::ollama::{OllamaEmbeddingProvider, OLLAMA_API_URL};
 use ai::providers::open_ai::{OpenAiEmbeddingProvider, OPEN_AI_API_URL};
 use anyhow::{anyhow, Context as _, Result};
 use collections::{BTreeMap, HashMap, HashSet};
@@ -15,8 +16,8 @@ use db::VectorDatabase;
 use embedding_queue::{EmbeddingQueue, FileToEmbed};
 use futures::{future, FutureExt, StreamExt};
 use gpui::{
-    AppContext, AsyncAppContext, BorrowWindow, Context, Global, Model, ModelContext, Task,
-    ViewContext, WeakModel,
+    AppContext, AsyncAppContext, BackgroundExecutor, BorrowWindow, Context, Global, Model,
+    ModelContext, Task, ViewContext, WeakModel,
 };
 use language::{Anchor, Bias, Buffer, Language, LanguageRegistry};
 use lazy_static::lazy_static;
@@ -26,7 +27,7 @@ use parsing::{CodeContextRetriever, Span, SpanDigest, PARSEABLE_ENTIRE_FILE_TYPE
 use postage::watch;
 use project::{Fs, PathChange, Project, ProjectEntryId, Worktree, WorktreeId};
 use release_channel::ReleaseChannel;
-use settings::Settings;
+use settings::{Settings, SettingsStore};
 use smol::channel;
 use std::{
     cmp::Reverse,
@@ -42,9 +43,11 @@ use util::paths::PathMatcher;
 use util::{http::HttpClient, paths::EMBEDDINGS_DIR, ResultExt};
 use workspace::Workspace;
 
-const SEMANTIC_INDEX_VERSION: usize = 11;
+const SEMANTIC_INDEX_VERSION: usize = 12;
 const BACKGROUND_INDEXING_DELAY: Duration = Duration::from_secs(5 * 60);
 const EMBEDDING_QUEUE_FLUSH_TIMEOUT: Duration = Duration::from_millis(250);
+const DEFAULT_OLLAMA_EMBEDDING_MODEL: &str = "nomic-embed-text";
+const DEFAULT_OLLAMA_EMBEDDING_MAX_TOKENS: usize = 2048;
 
 lazy_static! {
     static ref OPENAI_API_KEY: Option<String> = env::var("OPENAI_API_KEY").ok();
@@ -91,29 +94,91 @@ pub fn init(
     .detach();
 
     cx.spawn(move |cx| async move {
-        let embedding_provider = OpenAiEmbeddingProvider::new(
-            // TODO: We should read it from config, but I'm not sure whether to reuse `openai_api_url` in assistant settings or not
-            OPEN_AI_API_URL.to_string(),
-            http_client,
+        let mut embedding_provider_settings = cx.update(|cx| {
+            SemanticIndexSettings::get_global(cx)
+                .embedding_provider
+                .clone()
+        })?;
+        let embedding_provider = build_embedding_provider(
+            embedding_provider_settings.clone(),
+            http_client.clone(),
             cx.background_executor().clone(),
         )
         .await;
         let semantic_index = SemanticIndex::new(
             fs,
             db_file_path,
-            Arc::new(embedding_provider),
+            embedding_provider,
             language_registry,
             cx.clone(),
         )
         .await?;
 
-        cx.update(|cx| cx.set_global(GlobalSemanticIndex(semantic_index.clone())))?;
+        cx.update(|cx| {
+            cx.set_global(GlobalSemanticIndex(semantic_index.clone()));
+            cx.observe_global::<SettingsStore>(move |cx| {
+                let settings = &SemanticIndexSettings::get_global(cx).embedding_provider;
+                if *settings == embedding_provider_settings {
+                    return;
+                }
+                embedding_provider_settings = settings.clone();
+
+                let embedding_provider = build_embedding_provider(
+                    settings.clone(),
+                    http_client.clone(),
+                    cx.background_executor().clone(),
+                );
+                let semantic_index = semantic_index.clone();
+                cx.spawn(|mut cx| async move {
+                    let embedding_provider = embedding_provider.await;
+                    semantic_index
+                        .update(&mut cx, |index, cx| {
+                            index.set_embedding_provider(embedding_provider, cx)
+                        })?
+                        .await
+                })
+                .detach_and_log_err(cx);
+            })
+            .detach();
+        })?;
 
         anyhow::Ok(())
     })
     .detach();
 }
 
+async fn build_embedding_provider(
+    settings: EmbeddingProviderSettings,
+    http_client: Arc<dyn HttpClient>,
+    executor: BackgroundExecutor,
+) -> Arc<dyn EmbeddingProvider> {
+    match settings {
+        EmbeddingProviderSettings::OpenAi => Arc::new(
+            OpenAiEmbeddingProvider::new(
+                // TODO: We should read it from config, but I'm not sure whether to reuse `openai_api_url` in assistant settings or not
+                OPEN_AI_API_URL.to_string(),
+                http_client,
+                executor,
+            )
+            .await,
+        ),
+        EmbeddingProviderSettings::Ollama {
+            api_url,
+            model,
+            max_tokens,
+        } => Arc::new(
+            OllamaEmbeddingProvider::new(
+                api_url.unwrap_or_else(|| OLLAMA_API_URL.to_string()),
+                model.unwrap_or_else(|| DEFAULT_OLLAMA_EMBEDDING_MODEL.to_string()),
+                max_tokens.unwrap_or(DEFAULT_OLLAMA_EMBEDDING_MAX_TOKENS),
+                http_client,
+                executor,
+            )
+            .await,
+        ),
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum SemanticIndexStatus {
     NotAuthenticated,
@@ -347,8 +412,13 @@ impl SemanticIndex {
     ) -> Result<Model<Self>> {
         let t0 = Instant::now();
         let database_path = Arc::from(database_path);
-        let db = VectorDatabase::new(fs.clone(), database_path, cx.background_executor().clone())
-            .await?;
+        let db = VectorDatabase::new(
+            fs.clone(),
+            database_path,
+            embedding_provider.base_model().name().into(),
+            cx.background_executor().clone(),
+        )
+        .await?;
 
         log::trace!(
             "db initialization took {:?} milliseconds",
@@ -357,32 +427,108 @@ impl SemanticIndex {
 
         cx.new_model(|cx| {
             let t0 = Instant::now();
-            let embedding_queue =
-                EmbeddingQueue::new(embedding_provider.clone(), cx.background_executor().clone());
-            let _embedding_task = cx.background_executor().spawn({
-                let embedded_files = embedding_queue.finished_files();
-                let db = db.clone();
-                async move {
-                    while let Ok(file) = embedded_files.recv().await {
-                        db.insert_file(file.worktree_id, file.path, file.mtime, file.spans)
-                            .await
-                            .log_err();
-                    }
+            let (parsing_files_tx, _embedding_task, _parsing_files_tasks) =
+                Self::spawn_indexing_tasks(&fs, &db, &embedding_provider, cx);
+
+            log::trace!(
+                "semantic index task initialization took {:?} milliseconds",
+                t0.elapsed().as_millis()
+            );
+            Self {
+                fs,
+                db,
+                embedding_provider,
+                language_registry,
+                parsing_files_tx,
+                _embedding_task,
+                _parsing_files_tasks,
+                projects: Default::default(),
+            }
+        })
+    }
+
+    /// Replaces the provider that embeds spans, emptying the database of the embeddings
+    /// computed by the previous one and reindexing the projects that were indexed.
+    pub fn set_embedding_provider(
+        &mut self,
+        embedding_provider: Arc<dyn EmbeddingProvider>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        // Stop indexing with the previous provider before the database is emptied.
+        self._embedding_task = Task::ready(());
+        self._parsing_files_tasks.clear();
+        let projects = mem::take(&mut self.projects)
+            .into_keys()
+            .filter_map(|project| project.upgrade())
+            .collect::<Vec<_>>();
+
+        let fs = self.fs.clone();
+        let db_path = self.db.path().clone();
+        cx.spawn(|this, mut cx| async move {
+            let db = VectorDatabase::new(
+                fs,
+                db_path,
+                embedding_provider.base_model().name().into(),
+                cx.background_executor().clone(),
+            )
+            .await?;
+
+            let indexing = this.update(&mut cx, |this, cx| {
+                let (parsing_files_tx, embedding_task, parsing_files_tasks) =
+                    Self::spawn_indexing_tasks(&this.fs, &db, &embedding_provider, cx);
+                this.db = db;
+                this.embedding_provider = embedding_provider;
+                this.parsing_files_tx = parsing_files_tx;
+                this._embedding_task = embedding_task;
+                this._parsing_files_tasks = parsing_files_tasks;
+                projects
+                    .into_iter()
+                    .map(|project| this.index_project(project, cx))
+                    .collect::<Vec<_>>()
+            })?;
+            for result in future::join_all(indexing).await {
+                result?;
+            }
+            Ok(())
+        })
+    }
+
+    fn spawn_indexing_tasks(
+        fs: &Arc<dyn Fs>,
+        db: &VectorDatabase,
+        embedding_provider: &Arc<dyn EmbeddingProvider>,
+        cx: &mut ModelContext<Self>,
+    ) -> (
+        channel::Sender<(Arc<HashMap<SpanDigest, Embedding>>, PendingFile)>,
+        Task<()>,
+        Vec<Task<()>>,
+    ) {
+        let embedding_queue =
+            EmbeddingQueue::new(embedding_provider.clone(), cx.background_executor().clone());
+        let _embedding_task = cx.background_executor().spawn({
+            let embedded_files = embedding_queue.finished_files();
+            let db = db.clone();
+            async move {
+                while let Ok(file) = embedded_files.recv().await {
+                    db.insert_file(file.worktree_id, file.path, file.mtime, file.spans)
+                        .await
+                        .log_err();
                 }
-            });
+            }
+        });
 
-            // Parse files into embeddable spans.
-            let (parsing_files_tx, parsing_files_rx) =
-                channel::unbounded::<(Arc<HashMap<SpanDigest, Embedding>>, PendingFile)>();
-            let embedding_queue = Arc::new(Mutex::new(embedding_queue));
-            let mut _parsing_files_tasks = Vec::new();
-            for _ in 0..cx.background_executor().num_cpus() {
-                let fs = fs.clone();
-                let mut parsing_files_rx = parsing_files_rx.clone();
-                let embedding_provider = embedding_provider.clone();
-                let embedding_queue = embedding_queue.clone();
-                let background = cx.background_executor().clone();
-                _parsing_files_tasks.push(cx.background_executor().spawn(async move {
+        // Parse files into embeddable spans.
+        let (parsing_files_tx, parsing_files_rx) =
+            channel::unbounded::<(Arc<HashMap<SpanDigest, Embedding>>, PendingFile)>();
+        let embedding_queue = Arc::new(Mutex::new(embedding_queue));
+        let mut _parsing_files_tasks = Vec::new();
+        for _ in 0..cx.background_executor().num_cpus() {
+            let fs = fs.clone();
+            let mut parsing_files_rx = parsing_files_rx.clone();
+            let embedding_provider = embedding_provider.clone();
+            let embedding_queue = embedding_queue.clone();
+            let background = cx.background_executor().clone();
+            _parsing_files_tasks.push(cx.background_executor().spawn(async move {
                     let mut retriever = CodeContextRetriever::new(embedding_provider.clone());
                     loop {
                         let mut timer = background.timer(EMBEDDING_QUEUE_FLUSH_TIMEOUT).fuse();
@@ -408,23 +554,9 @@ impl SemanticIndex {
                         }
                     }
                 }));
-            }
+        }
 
-            log::trace!(
-                "semantic index task initialization took {:?} milliseconds",
-                t0.elapsed().as_millis()
-            );
-            Self {
-                fs,
-                db,
-                embedding_provider,
-                language_registry,
-                parsing_files_tx,
-                _embedding_task,
-                _parsing_files_tasks,
-                projects: Default::default(),
-            }
-        })
+        (parsing_files_tx, _embedding_task, _parsing_files_tasks)
     }
 
     async fn parse_file(
@@ -792,11 +924,13 @@ impl SemanticIndex {
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Vec<SearchResult>>> {
         let db_path = self.db.path().clone();
+        let embedding_model = self.db.embedding_model().clone();
         let fs = self.fs.clone();
         cx.spawn(|this, mut cx| async move {
             let database = VectorDatabase::new(
                 fs.clone(),
                 db_path.clone(),
+                embedding_model.clone(),
                 cx.background_executor().clone(),
             )
             .await?;
@@ -844,10 +978,14 @@ impl SemanticIndex {
                 let fs = fs.clone();
                 let db_path = db_path.clone();
                 let query = query.clone();
-                if let Some(db) =
-                    VectorDatabase::new(fs, db_path.clone(), cx.background_executor().clone())
-                        .await
-                        .log_err()
+                if let Some(db) = VectorDatabase::new(
+                    fs,
+                    db_path.clone(),
+                    embedding_model.clone(),
+                    cx.background_executor().clone(),
+                )
+                .await
+                .log_err()
                 {
                     batch_results.push(async move {
                         db.top_k_search(&query, limit, batch.as_slice()).await
@@ -965,9 +1103,10 @@ impl SemanticIndex {
         let embedding_provider = self.embedding_provider.clone();
         let fs = self.fs.clone();
         let db_path = self.db.path().clone();
+        let embedding_model = self.db.embedding_model().clone();
         let background = cx.background_executor().clone();
         cx.background_executor().spawn(async move {
-            let db = VectorDatabase::new(fs, db_path.clone(), background).await?;
+            let db = VectorDatabase::new(fs, db_path.clone(), embedding_model, background).await?;
             let mut results = Vec::<SearchResult>::new();
 
             let mut retriever = CodeContextRetriever::new(embedding_provider.clone());
@@ -6,6 +6,32 @@ use settings::Settings;
 #[derive(Deserialize, Debug)]
 pub struct SemanticIndexSettings {
     pub enabled: bool,
+    pub embedding_provider: EmbeddingProviderSettings,
+}
+
+/// The service that embeds the indexed code. Changing it reindexes every project.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum EmbeddingProviderSettings {
+    /// OpenAI's `text-embedding-ada-002`, which needs an OpenAI API key.
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// A model running locally in Ollama.
+    Ollama {
+        /// Ollama API base URL.
+        ///
+        /// Default: http://localhost:11434/api
+        api_url: Option<String>,
+        /// The embedding model, which must have been pulled into Ollama.
+        ///
+        /// Default: nomic-embed-text
+        model: Option<String>,
+        /// The number of tokens the model embeds, beyond which spans are truncated.
+        ///
+        /// Default: 2048
+        max_tokens: Option<usize>,
+    },
 }
 
 /// Configuration of semantic index, an alternate search engine available in
@@ -16,6 +42,10 @@ pub struct SemanticIndexSettingsContent {
     ///
     /// Default: true
     pub enabled: Option<bool>,
+    /// The service that embeds the indexed code.
+    ///
+    /// Default: { "provider": "openai" }
+    pub embedding_provider: Option<EmbeddingProviderSettings>,
 }
 
 impl Settings for SemanticIndexSettings {
@@ -1,4 +1,5 @@
 use crate::{
+    db::VectorDatabase,
     embedding_queue::EmbeddingQueue,
     parsing::{subtract_ranges, CodeContextRetriever, Span, SpanDigest},
     semantic_index_settings::SemanticIndexSettings,
@@ -1705,6 +1706,68 @@ fn elixir_lang() -> Arc<Language> {
     )
 }
 
+#[gpui::test]
+async fn test_changing_embedding_model_clears_index(cx: &mut TestAppContext) {
+    let fs = FakeFs::new(cx.background_executor.clone());
+    let db_dir = tempfile::Builder::new()
+        .prefix("vector-store")
+        .tempdir()
+        .unwrap();
+    let db_path: Arc<Path> = db_dir.path().join("db.sqlite").into();
+
+    let db = VectorDatabase::new(
+        fs.clone(),
+        db_path.clone(),
+        "text-embedding-ada-002".into(),
+        cx.background_executor.clone(),
+    )
+    .await
+    .unwrap();
+    let worktree_id = db
+        .find_or_create_worktree(Path::new("/the-root").into())
+        .await
+        .unwrap();
+    db.insert_file(
+        worktree_id,
+        Path::new("src/file1.rs").into(),
+        SystemTime::now(),
+        Vec::new(),
+    )
+    .await
+    .unwrap();
+    drop(db);
+
+    // Reopening with the same model keeps the embeddings.
+    let db = VectorDatabase::new(
+        fs.clone(),
+        db_path.clone(),
+        "text-embedding-ada-002".into(),
+        cx.background_executor.clone(),
+    )
+    .await
+    .unwrap();
+    assert!(db
+        .worktree_previously_indexed(Path::new("/the-root"))
+        .await
+        .unwrap());
+    drop(db);
+
+    // Embeddings from different models can't be compared, so switching models reindexes.
+    let db = VectorDatabase::new(
+        fs.clone(),
+        db_path.clone(),
+        "nomic-embed-text".into(),
+        cx.background_executor.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(db.embedding_model().as_ref(), "nomic-embed-text");
+    assert!(!db
+        .worktree_previously_indexed(Path::new("/the-root"))
+        .await
+        .unwrap());
+}
+
 #[gpui::test]
 fn test_subtract_ranges() {
     assert_eq!(
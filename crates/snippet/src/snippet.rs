@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Context, Result};
-use smallvec::SmallVec;
-use std::{collections::BTreeMap, ops::Range};
+use regex::{Captures, Regex, RegexBuilder};
+use smallvec::{smallvec, SmallVec};
+use std::{cell::RefCell, collections::BTreeMap, mem, ops::Range};
 
 #[derive(Default)]
 pub struct Snippet {
@@ -8,25 +9,147 @@ pub struct Snippet {
     pub tabstops: Vec<TabStop>,
 }
 
-type TabStop = SmallVec<[Range<isize>; 2]>;
+#[derive(Clone, Debug, Default)]
+pub struct TabStop {
+    /// The ranges that are selected together when this tab stop is active.
+    pub ranges: SmallVec<[Range<isize>; 2]>,
+    /// The alternatives offered at this tab stop, written as `${1|one,two,three|}`.
+    pub choices: Option<Vec<String>>,
+    /// Mirrors of this tab stop that are rewritten with a transform, written as
+    /// `${1/regex/format/flags}`. They are updated when the tab stop is left.
+    pub transforms: Vec<Mirror>,
+}
+
+/// A mirror of a tab stop, which holds a transform of the tab stop's text.
+#[derive(Clone, Debug)]
+pub struct Mirror {
+    /// Initially holds the transform of the tab stop's placeholder.
+    pub range: Range<isize>,
+    pub transform: Transform,
+    /// Whether a tab stop ends where the mirror starts, so that text typed there belongs
+    /// to that tab stop and goes before the mirror.
+    pub follows_tabstop: bool,
+}
+
+/// A regular expression replacement, as used by `${1/regex/format/flags}` and
+/// `${VARIABLE/regex/format/flags}`.
+#[derive(Clone, Debug)]
+pub struct Transform {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FormatItem {
+    Text(String),
+    Group(usize),
+    Case(usize, Case),
+    Conditional {
+        group: usize,
+        if_text: String,
+        else_text: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Case {
+    Upcase,
+    Downcase,
+    Capitalize,
+    Camelcase,
+    Pascalcase,
+}
+
+struct ParseState<'a> {
+    text: String,
+    tabstops: BTreeMap<usize, TabStop>,
+    variable_tabstops: Vec<TabStop>,
+    variables: &'a dyn Fn(&str) -> Option<String>,
+    /// The text of the tab stops, which the mirrors are transformed from.
+    placeholders: BTreeMap<usize, String>,
+}
 
 impl Snippet {
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
-            .context("failed to parse snippet")?;
+        Self::parse_with_variables(source, &|_| None)
+    }
+
+    /// Parses a snippet, resolving variables such as `$TM_FILENAME` with the given function.
+    /// Variables that it doesn't know are inserted by name and become placeholders.
+    pub fn parse_with_variables(
+        source: &str,
+        variables: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        // Variables are resolved once, even when the snippet is parsed twice.
+        let values = RefCell::new(BTreeMap::<String, Option<String>>::new());
+        let variables = |name: &str| {
+            values
+                .borrow_mut()
+                .entry(name.to_string())
+                .or_insert_with(|| variables(name))
+                .clone()
+        };
+        let mut state = ParseState {
+            text: String::with_capacity(source.len()),
+            tabstops: BTreeMap::new(),
+            variable_tabstops: Vec::new(),
+            variables: &variables,
+            placeholders: BTreeMap::new(),
+        };
+        parse_snippet(source, false, &mut state).context("failed to parse snippet")?;
+
+        // Mirrors are parsed again with the text of their tab stops, which can come after
+        // them, so that they are part of the text with the ranges around them.
+        if state
+            .tabstops
+            .values()
+            .any(|tabstop| !tabstop.transforms.is_empty())
+        {
+            let placeholders = state
+                .tabstops
+                .iter()
+                .filter_map(|(index, tabstop)| {
+                    let range = tabstop.ranges.first()?;
+                    Some((
+                        *index,
+                        state.text[range.start as usize..range.end as usize].to_string(),
+                    ))
+                })
+                .collect();
+            state = ParseState {
+                text: String::with_capacity(source.len()),
+                tabstops: BTreeMap::new(),
+                variable_tabstops: Vec::new(),
+                variables: &variables,
+                placeholders,
+            };
+            parse_snippet(source, false, &mut state).context("failed to parse snippet")?;
+        }
 
+        let ParseState {
+            text,
+            mut tabstops,
+            variable_tabstops,
+            ..
+        } = state;
         let len = text.len() as isize;
         let final_tabstop = tabstops.remove(&0);
-        let mut tabstops = tabstops.into_values().collect::<Vec<_>>();
+        let mut tabstops = tabstops
+            .into_values()
+            .chain(variable_tabstops)
+            .filter(|tabstop| !tabstop.ranges.is_empty())
+            .collect::<Vec<_>>();
 
         if let Some(final_tabstop) = final_tabstop {
             tabstops.push(final_tabstop);
         } else {
-            let end_tabstop = [len..len].into_iter().collect();
-            if !tabstops.last().map_or(false, |t| *t == end_tabstop) {
-                tabstops.push(end_tabstop);
+            let end_tabstop: SmallVec<_> = smallvec![len..len];
+            if !tabstops.last().map_or(false, |t| t.ranges == end_tabstop) {
+                tabstops.push(TabStop {
+                    ranges: end_tabstop,
+                    ..Default::default()
+                });
             }
         }
 
@@ -34,22 +157,86 @@ impl Snippet {
     }
 }
 
-fn parse_snippet<'a>(
-    mut source: &'a str,
-    nested: bool,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
+impl Transform {
+    /// Replaces the first match of the regex in the given text, or every match
+    /// when the transform has the `g` flag.
+    pub fn apply(&self, text: &str) -> String {
+        let replacement = |captures: &Captures| {
+            let group = |index: usize| captures.get(index).map_or("", |group| group.as_str());
+            let mut result = String::new();
+            for item in &self.format {
+                match item {
+                    FormatItem::Text(text) => result.push_str(text),
+                    FormatItem::Group(index) => result.push_str(group(*index)),
+                    FormatItem::Case(index, case) => result.push_str(&case.apply(group(*index))),
+                    FormatItem::Conditional {
+                        group: index,
+                        if_text,
+                        else_text,
+                    } => {
+                        if group(*index).is_empty() {
+                            result.push_str(else_text);
+                        } else {
+                            result.push_str(if_text);
+                        }
+                    }
+                }
+            }
+            result
+        };
+
+        if self.global {
+            self.regex.replace_all(text, replacement).into_owned()
+        } else {
+            self.regex.replace(text, replacement).into_owned()
+        }
+    }
+}
+
+impl Case {
+    fn apply(self, text: &str) -> String {
+        match self {
+            Case::Upcase => text.to_uppercase(),
+            Case::Downcase => text.to_lowercase(),
+            Case::Capitalize => capitalize(text),
+            Case::Camelcase | Case::Pascalcase => {
+                let mut words = text
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty());
+                let mut result = String::new();
+                if self == Case::Camelcase {
+                    if let Some(word) = words.next() {
+                        result.push_str(&word.to_lowercase());
+                    }
+                }
+                for word in words {
+                    result.push_str(&capitalize(&word.to_lowercase()));
+                }
+                result
+            }
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn parse_snippet<'a>(mut source: &'a str, nested: bool, state: &mut ParseState) -> Result<&'a str> {
     loop {
         match source.chars().next() {
             None => return Ok(""),
             Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
+                source = parse_tabstop(&source[1..], state)?;
             }
             Some('\\') => {
                 source = &source[1..];
                 if let Some(c) = source.chars().next() {
-                    text.push(c);
+                    state.text.push(c);
                     source = &source[c.len_utf8()..];
                 }
             }
@@ -57,54 +244,351 @@ fn parse_snippet<'a>(
                 if nested {
                     return Ok(source);
                 } else {
-                    text.push('}');
+                    state.text.push('}');
                     source = &source[1..];
                 }
             }
             Some(_) => {
                 let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
                 let (chunk, rest) = source.split_at(chunk_end);
-                text.push_str(chunk);
+                state.text.push_str(chunk);
                 source = rest;
             }
         }
     }
 }
 
-fn parse_tabstop<'a>(
-    mut source: &'a str,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    let tabstop_start = text.len();
+fn parse_tabstop<'a>(mut source: &'a str, state: &mut ParseState) -> Result<&'a str> {
+    let tabstop_start = state.text.len();
     let tabstop_index;
-    if source.starts_with('{') {
-        let (index, rest) = parse_int(&source[1..])?;
+    if let Some(rest) = source.strip_prefix('{') {
+        if let Some((name, rest)) = parse_variable_name(rest) {
+            return parse_variable(name, rest, true, state);
+        }
+
+        let (index, rest) = parse_int(rest)?;
         tabstop_index = index;
         source = rest;
 
-        if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+        if let Some(rest) = source.strip_prefix(':') {
+            source = parse_snippet(rest, true, state)?;
+        } else if let Some(rest) = source.strip_prefix('|') {
+            let (choices, rest) = parse_choices(rest)?;
+            if let Some(default_choice) = choices.first() {
+                state.text.push_str(default_choice);
+            }
+            state
+                .tabstops
+                .entry(tabstop_index)
+                .or_default()
+                .choices
+                .get_or_insert(choices);
+            source = rest;
+        } else if let Some(rest) = source.strip_prefix('/') {
+            let (transform, rest) = parse_transform(rest)?;
+            let follows_tabstop = state
+                .tabstops
+                .values()
+                .chain(&state.variable_tabstops)
+                .flat_map(|tabstop| &tabstop.ranges)
+                .any(|range| range.end == tabstop_start as isize);
+            if let Some(placeholder) = state.placeholders.get(&tabstop_index) {
+                let text = transform.apply(placeholder);
+                state.text.push_str(&text);
+            }
+            state
+                .tabstops
+                .entry(tabstop_index)
+                .or_default()
+                .transforms
+                .push(Mirror {
+                    range: tabstop_start as isize..state.text.len() as isize,
+                    transform,
+                    follows_tabstop,
+                });
+            source = rest;
+            return expect_closing_brace(source);
         }
 
-        if source.starts_with('}') {
-            source = &source[1..];
-        } else {
-            return Err(anyhow!("expected a closing brace"));
-        }
-    } else {
-        let (index, rest) = parse_int(source)?;
+        source = expect_closing_brace(source)?;
+    } else if let Some((name, rest)) = parse_variable_name(source) {
+        return parse_variable(name, rest, false, state);
+    } else if let Ok((index, rest)) = parse_int(source) {
         tabstop_index = index;
         source = rest;
+    } else {
+        state.text.push('$');
+        return Ok(source);
     }
 
-    tabstops
+    state
+        .tabstops
         .entry(tabstop_index)
         .or_default()
-        .push(tabstop_start as isize..text.len() as isize);
+        .ranges
+        .push(tabstop_start as isize..state.text.len() as isize);
     Ok(source)
 }
 
+fn parse_variable<'a>(
+    name: &str,
+    mut source: &'a str,
+    braced: bool,
+    state: &mut ParseState,
+) -> Result<&'a str> {
+    let start = state.text.len();
+    let value = (state.variables)(name);
+    if braced {
+        if let Some(rest) = source.strip_prefix(':') {
+            match value.filter(|value| !value.is_empty()) {
+                Some(value) => {
+                    // The default is only parsed to find where it ends.
+                    let mut default = ParseState {
+                        text: String::new(),
+                        tabstops: BTreeMap::new(),
+                        variable_tabstops: Vec::new(),
+                        variables: state.variables,
+                        placeholders: BTreeMap::new(),
+                    };
+                    source = parse_snippet(rest, true, &mut default)?;
+                    state.text.push_str(&value);
+                }
+                None => source = parse_snippet(rest, true, state)?,
+            }
+            return expect_closing_brace(source);
+        } else if let Some(rest) = source.strip_prefix('/') {
+            let (transform, rest) = parse_transform(rest)?;
+            source = expect_closing_brace(rest)?;
+            match value {
+                Some(value) => {
+                    state.text.push_str(&transform.apply(&value));
+                    return Ok(source);
+                }
+                None => {
+                    state.text.push_str(name);
+                }
+            }
+        } else {
+            source = expect_closing_brace(source)?;
+            match value {
+                Some(value) => {
+                    state.text.push_str(&value);
+                    return Ok(source);
+                }
+                None => state.text.push_str(name),
+            }
+        }
+    } else {
+        match value {
+            Some(value) => {
+                state.text.push_str(&value);
+                return Ok(source);
+            }
+            None => state.text.push_str(name),
+        }
+    }
+
+    state.variable_tabstops.push(TabStop {
+        ranges: smallvec![start as isize..state.text.len() as isize],
+        ..Default::default()
+    });
+    Ok(source)
+}
+
+fn parse_choices(mut source: &str) -> Result<(Vec<String>, &str)> {
+    let mut choices = Vec::new();
+    let mut choice = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected the end of the choices")),
+            Some('\\') => {
+                source = &source[1..];
+                if let Some(c) = source.chars().next() {
+                    choice.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            Some(',') => {
+                choices.push(mem::take(&mut choice));
+                source = &source[1..];
+            }
+            Some('|') => {
+                choices.push(choice);
+                return Ok((choices, &source[1..]));
+            }
+            Some(c) => {
+                choice.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+}
+
+fn parse_transform(source: &str) -> Result<(Transform, &str)> {
+    let (pattern, source) = parse_regex(source)?;
+    let (format, source) = parse_format(source)?;
+    let flags_len = source
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(source.len());
+    let (flags, source) = source.split_at(flags_len);
+
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(flags.contains('i'))
+        .multi_line(flags.contains('m'))
+        .dot_matches_new_line(flags.contains('s'))
+        .build()
+        .with_context(|| format!("invalid regex {pattern:?}"))?;
+    let transform = Transform {
+        regex,
+        format,
+        global: flags.contains('g'),
+    };
+    Ok((transform, source))
+}
+
+fn parse_regex(mut source: &str) -> Result<(String, &str)> {
+    let mut pattern = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected the end of the regex")),
+            Some('/') => return Ok((pattern, &source[1..])),
+            Some('\\') if source[1..].starts_with('/') => {
+                pattern.push('/');
+                source = &source[2..];
+            }
+            Some('\\') => {
+                pattern.push('\\');
+                source = &source[1..];
+                if let Some(c) = source.chars().next() {
+                    pattern.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            Some(c) => {
+                pattern.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+}
+
+fn parse_format(mut source: &str) -> Result<(Vec<FormatItem>, &str)> {
+    let mut items = Vec::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected the end of the format")),
+            Some('/') => return Ok((items, &source[1..])),
+            Some('$') => {
+                let rest = &source[1..];
+                if let Some(rest) = rest.strip_prefix('{') {
+                    let (item, rest) = parse_format_group(rest)?;
+                    items.push(item);
+                    source = rest;
+                } else if let Ok((group, rest)) = parse_int(rest) {
+                    items.push(FormatItem::Group(group));
+                    source = rest;
+                } else {
+                    push_format_text(&mut items, "$");
+                    source = rest;
+                }
+            }
+            Some(_) => {
+                let (text, rest) = parse_format_text(source, &['/', '$'])?;
+                push_format_text(&mut items, &text);
+                source = rest;
+            }
+        }
+    }
+}
+
+fn parse_format_group(source: &str) -> Result<(FormatItem, &str)> {
+    let (group, source) = parse_int(source)?;
+    let Some(source) = source.strip_prefix(':') else {
+        return Ok((FormatItem::Group(group), expect_closing_brace(source)?));
+    };
+
+    if let Some(rest) = source.strip_prefix('/') {
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let (name, rest) = rest.split_at(name_len);
+        let case = match name {
+            "upcase" => Case::Upcase,
+            "downcase" => Case::Downcase,
+            "capitalize" => Case::Capitalize,
+            "camelcase" => Case::Camelcase,
+            "pascalcase" => Case::Pascalcase,
+            _ => return Err(anyhow!("unknown format modifier {name:?}")),
+        };
+        return Ok((FormatItem::Case(group, case), expect_closing_brace(rest)?));
+    }
+
+    let (if_text, else_text, rest) = if let Some(rest) = source.strip_prefix('+') {
+        let (if_text, rest) = parse_format_text(rest, &['}'])?;
+        (if_text, String::new(), rest)
+    } else if let Some(rest) = source.strip_prefix('?') {
+        let (if_text, rest) = parse_format_text(rest, &[':'])?;
+        let (else_text, rest) = parse_format_text(&rest[1..], &['}'])?;
+        (if_text, else_text, rest)
+    } else {
+        let rest = source.strip_prefix('-').unwrap_or(source);
+        let (else_text, rest) = parse_format_text(rest, &['}'])?;
+        (String::new(), else_text, rest)
+    };
+    let item = FormatItem::Conditional {
+        group,
+        if_text,
+        else_text,
+    };
+    Ok((item, expect_closing_brace(rest)?))
+}
+
+/// Reads text up to one of the given terminators, which is left in the returned source.
+fn parse_format_text<'a>(mut source: &'a str, terminators: &[char]) -> Result<(String, &'a str)> {
+    let mut text = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected one of {terminators:?}")),
+            Some(c) if terminators.contains(&c) => return Ok((text, source)),
+            Some('\\') => {
+                source = &source[1..];
+                if let Some(c) = source.chars().next() {
+                    text.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            Some(c) => {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+}
+
+fn push_format_text(items: &mut Vec<FormatItem>, text: &str) {
+    if let Some(FormatItem::Text(last)) = items.last_mut() {
+        last.push_str(text);
+    } else {
+        items.push(FormatItem::Text(text.to_string()));
+    }
+}
+
+fn parse_variable_name(source: &str) -> Option<(&str, &str)> {
+    if !source.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let len = source
+        .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+        .unwrap_or(source.len());
+    Some(source.split_at(len))
+}
+
+fn expect_closing_brace(source: &str) -> Result<&str> {
+    source
+        .strip_prefix('}')
+        .ok_or_else(|| anyhow!("expected a closing brace"))
+}
+
 fn parse_int(source: &str) -> Result<(usize, &str)> {
     let len = source
         .find(|c: char| !c.is_ascii_digit())
@@ -199,7 +683,96 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![3..3]]);
     }
 
+    #[test]
+    fn test_snippet_with_choices() {
+        let snippet = Snippet::parse("let ${1|a,b\\,c,d\\|e|} = $0;").unwrap();
+        assert_eq!(snippet.text, "let a = ;");
+        assert_eq!(tabstops(&snippet), &[vec![4..5], vec![8..8]]);
+        assert_eq!(
+            snippet.tabstops[0].choices.as_deref(),
+            Some(&["a".to_string(), "b,c".to_string(), "d|e".to_string()][..])
+        );
+        assert_eq!(snippet.tabstops[1].choices, None);
+    }
+
+    #[test]
+    fn test_snippet_with_variables() {
+        let variables = |name: &str| match name {
+            "TM_FILENAME" => Some("main.rs".to_string()),
+            "TM_SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        };
+
+        let snippet = Snippet::parse_with_variables(
+            "// $TM_FILENAME: ${TM_SELECTED_TEXT:todo}$1",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "// main.rs: todo");
+        assert_eq!(tabstops(&snippet), &[vec![16..16]]);
+
+        let snippet =
+            Snippet::parse_with_variables("${TM_FILENAME:${1:name}} $1", &variables).unwrap();
+        assert_eq!(snippet.text, "main.rs ");
+        assert_eq!(tabstops(&snippet), &[vec![8..8]]);
+
+        // Unknown variables are inserted by name and become placeholders, after the numbered ones.
+        let snippet = Snippet::parse_with_variables("$UNKNOWN(${1:arg})", &variables).unwrap();
+        assert_eq!(snippet.text, "UNKNOWN(arg)");
+        assert_eq!(tabstops(&snippet), &[vec![8..11], vec![0..7], vec![12..12]]);
+    }
+
+    #[test]
+    fn test_snippet_with_transforms() {
+        let variables = |name: &str| match name {
+            "TM_FILENAME" => Some("my-component.tsx".to_string()),
+            _ => None,
+        };
+
+        let snippet = Snippet::parse_with_variables(
+            "class ${TM_FILENAME/(.*)\\..+$/${1:/pascalcase}/} {}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "class MyComponent {}");
+
+        // Mirrors hold the transform of their tab stop's placeholder, even before it.
+        let snippet = Snippet::parse("${1:name} ${1/(.)(.*)/${1:/upcase}$2/} $0").unwrap();
+        assert_eq!(snippet.text, "name Name ");
+        assert_eq!(tabstops(&snippet), &[vec![0..4], vec![10..10]]);
+        let mirror = &snippet.tabstops[0].transforms[0];
+        assert_eq!(mirror.range, 5..9);
+        assert_eq!(mirror.transform.apply("other"), "Other");
+        assert!(!mirror.follows_tabstop);
+
+        let snippet = Snippet::parse("${1/(.*)/${1:/upcase}/}=${1:name}$2").unwrap();
+        assert_eq!(snippet.text, "NAME=name");
+        assert_eq!(tabstops(&snippet), &[vec![5..9], vec![9..9]]);
+        assert_eq!(snippet.tabstops[0].transforms[0].range, 0..4);
+
+        // Typing at the end of a tab stop right before an empty mirror doesn't go in it.
+        let snippet = Snippet::parse("$1${1/(.*)/${1:/upcase}/}$2").unwrap();
+        assert_eq!(snippet.text, "");
+        assert!(snippet.tabstops[0].transforms[0].follows_tabstop);
+
+        let transform = parse_transform("a|b/${0:?yes:no}/g}").unwrap().0;
+        assert_eq!(transform.apply("a-c-b"), "yes-c-yes");
+        let transform = parse_transform("(x)?y/${1:+x}${1:-none}/}").unwrap().0;
+        assert_eq!(transform.apply("y"), "none");
+        assert_eq!(transform.apply("xy"), "x");
+        let transform = parse_transform("\\/| /_/gi}").unwrap().0;
+        assert_eq!(transform.apply("a/b c"), "a_b_c");
+        let transform = parse_transform(".*/${0:/camelcase}/}").unwrap().0;
+        assert_eq!(transform.apply("Foo_bar baz"), "fooBarBaz");
+    }
+
+    #[test]
+    fn test_snippet_with_literal_dollar_signs() {
+        let snippet = Snippet::parse("cost: $ 10$").unwrap();
+        assert_eq!(snippet.text, "cost: $ 10$");
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
-        snippet.tabstops.iter().map(|t| t.to_vec()).collect()
+        snippet.tabstops.iter().map(|t| t.ranges.to_vec()).collect()
     }
 }
@@ -0,0 +1,47 @@
+use collections::BTreeMap;
+use serde::Deserialize;
+
+/// A snippets file in the format used by VS Code, mapping the name of each
+/// snippet to its definition.
+#[derive(Deserialize)]
+#[serde(transparent)]
+pub(crate) struct VsSnippetsFile {
+    pub snippets: BTreeMap<String, VsCodeSnippet>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct VsCodeSnippet {
+    #[serde(default)]
+    pub prefix: StringOrList,
+    pub body: StringOrList,
+    pub description: Option<StringOrList>,
+    /// A comma-separated list of the languages the snippet applies to, only
+    /// used in files that aren't specific to one language.
+    pub scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum StringOrList {
+    String(String),
+    List(Vec<String>),
+}
+
+impl Default for StringOrList {
+    fn default() -> Self {
+        Self::List(Vec::new())
+    }
+}
+
+impl StringOrList {
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::String(string) => vec![string],
+            Self::List(list) => list,
+        }
+    }
+
+    pub fn join_lines(self) -> String {
+        self.into_vec().join("\n")
+    }
+}
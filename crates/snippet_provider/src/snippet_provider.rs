@@ -0,0 +1,277 @@
+//! Snippets defined by the user in VS Code-compatible JSON files.
+//!
+//! Snippets are read from the `snippets` directory of the config dir, from the
+//! `.zed/snippets` directory of each worktree, and from installed extensions. A file
+//! named after a language, such as `rust.json`, holds snippets for that language,
+//! while `snippets.json` and `*.code-snippets` files hold snippets for any language,
+//! optionally restricted with a `scope` field.
+
+mod format;
+
+use anyhow::{Context as _, Result};
+use collections::BTreeMap;
+use format::VsSnippetsFile;
+use fs::Fs;
+use futures::StreamExt;
+use gpui::{AppContext, Global};
+use parking_lot::RwLock;
+use std::{ffi::OsStr, path::Path, sync::Arc, time::Duration};
+use util::{paths, ResultExt};
+
+/// A snippet read from a snippets file.
+#[derive(Debug)]
+pub struct UserSnippet {
+    pub name: String,
+    /// The words that expand to this snippet in the completion menu.
+    pub prefixes: Vec<String>,
+    /// The body of the snippet, in the LSP snippet syntax.
+    pub body: String,
+    pub description: Option<String>,
+    /// The lowercased names of the languages this snippet applies to, or `None` if it
+    /// applies to every language.
+    languages: Option<Vec<String>>,
+}
+
+struct SnippetFile {
+    /// The root id of the worktree containing this file, for project snippets.
+    worktree_id: Option<usize>,
+    snippets: Vec<Arc<UserSnippet>>,
+}
+
+#[derive(Default)]
+struct GlobalSnippetRegistry(Arc<SnippetRegistry>);
+
+impl Global for GlobalSnippetRegistry {}
+
+/// The snippets of every snippets file that has been loaded, keyed by the path of
+/// the file.
+#[derive(Default)]
+pub struct SnippetRegistry {
+    files: RwLock<BTreeMap<Arc<Path>, SnippetFile>>,
+}
+
+/// Loads the user's snippets from the config dir and watches them for changes.
+pub fn init(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+    let registry = SnippetRegistry::default_global(cx);
+    cx.background_executor()
+        .spawn(async move {
+            let snippets_dir = paths::SNIPPETS_DIR.as_path();
+            fs.create_dir(snippets_dir).await.log_err();
+            registry.load_dir(snippets_dir, fs.clone()).await.log_err();
+
+            let mut events = fs.watch(snippets_dir, Duration::from_millis(100)).await;
+            while let Some(events) = events.next().await {
+                for event in events {
+                    if !is_snippets_file(&event.path) {
+                        continue;
+                    }
+                    if let Ok(content) = fs.load(&event.path).await {
+                        registry
+                            .insert_file(event.path.into(), None, &content)
+                            .log_err();
+                    } else {
+                        registry.remove_file(&event.path);
+                    }
+                }
+            }
+        })
+        .detach();
+}
+
+/// Returns whether the given path has the extension of a snippets file.
+pub fn is_snippets_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(OsStr::to_str),
+        Some("json" | "code-snippets")
+    )
+}
+
+impl SnippetRegistry {
+    /// Returns the global [`SnippetRegistry`].
+    pub fn global(cx: &AppContext) -> Arc<Self> {
+        cx.global::<GlobalSnippetRegistry>().0.clone()
+    }
+
+    /// Returns the global [`SnippetRegistry`].
+    ///
+    /// Inserts a default [`SnippetRegistry`] if one does not yet exist.
+    pub fn default_global(cx: &mut AppContext) -> Arc<Self> {
+        cx.default_global::<GlobalSnippetRegistry>().0.clone()
+    }
+
+    /// Loads every snippets file in the given directory.
+    pub async fn load_dir(&self, dir: &Path, fs: Arc<dyn Fs>) -> Result<()> {
+        let mut paths = fs.read_dir(dir).await?;
+        while let Some(path) = paths.next().await {
+            let Some(path) = path.log_err() else {
+                continue;
+            };
+            if !is_snippets_file(&path) {
+                continue;
+            }
+            let content = fs.load(&path).await?;
+            self.insert_file(path.into(), None, &content).log_err();
+        }
+        Ok(())
+    }
+
+    /// Parses a snippets file, replacing the snippets previously read from the same path.
+    pub fn insert_file(
+        &self,
+        path: Arc<Path>,
+        worktree_id: Option<usize>,
+        content: &str,
+    ) -> Result<()> {
+        let snippets = parse_snippets_file(&path, content)
+            .with_context(|| format!("invalid snippets file {path:?}"))?;
+        self.files.write().insert(
+            path,
+            SnippetFile {
+                worktree_id,
+                snippets,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn remove_file(&self, path: &Path) {
+        self.files.write().remove(path);
+    }
+
+    /// Removes the snippets of a worktree that is no longer part of its project.
+    pub fn remove_worktree(&self, worktree_id: usize) {
+        self.files
+            .write()
+            .retain(|_, file| file.worktree_id != Some(worktree_id));
+    }
+
+    /// Returns the snippets that apply to a buffer in the given language and worktree.
+    pub fn snippets_for(
+        &self,
+        language_name: Option<&str>,
+        worktree_id: Option<usize>,
+    ) -> Vec<Arc<UserSnippet>> {
+        let language_name = language_name.map(str::to_lowercase);
+        self.files
+            .read()
+            .values()
+            .filter(|file| file.worktree_id.is_none() || file.worktree_id == worktree_id)
+            .flat_map(|file| file.snippets.iter())
+            .filter(|snippet| match (&snippet.languages, &language_name) {
+                (None, _) => true,
+                (Some(languages), Some(language_name)) => languages.contains(language_name),
+                (Some(_), None) => false,
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+fn parse_snippets_file(path: &Path, content: &str) -> Result<Vec<Arc<UserSnippet>>> {
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let file: VsSnippetsFile = serde_json_lenient::from_str(content)?;
+    let file_language = match path.extension().and_then(OsStr::to_str) {
+        Some("json") => path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .filter(|stem| *stem != "snippets")
+            .map(str::to_lowercase),
+        _ => None,
+    };
+
+    Ok(file
+        .snippets
+        .into_iter()
+        .map(|(name, snippet)| {
+            let languages = match &file_language {
+                Some(language) => Some(vec![language.clone()]),
+                None => snippet.scope.map(|scope| {
+                    scope
+                        .split(',')
+                        .map(|language| language.trim().to_lowercase())
+                        .filter(|language| !language.is_empty())
+                        .collect()
+                }),
+            };
+            Arc::new(UserSnippet {
+                name,
+                prefixes: snippet.prefix.into_vec(),
+                body: snippet.body.join_lines(),
+                description: snippet
+                    .description
+                    .map(|description| description.join_lines()),
+                languages,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippets_for_language_and_worktree() {
+        let registry = SnippetRegistry::default();
+        registry
+            .insert_file(
+                Path::new("/config/snippets/rust.json").into(),
+                None,
+                r##"{
+                    // Comments are allowed, as in settings files.
+                    "Test function": {
+                        "prefix": ["test", "tst"],
+                        "body": ["#[test]", "fn ${1:name}() {", "    $0", "}"],
+                        "description": "A unit test"
+                    }
+                }"##,
+            )
+            .unwrap();
+        registry
+            .insert_file(
+                Path::new("/config/snippets/shared.code-snippets").into(),
+                None,
+                r#"{
+                    "Todo": { "prefix": "todo", "body": "TODO: $0" },
+                    "Log": { "prefix": "log", "body": "console.log($1)", "scope": "javascript, TypeScript" }
+                }"#,
+            )
+            .unwrap();
+        registry
+            .insert_file(
+                Path::new("/project/.zed/snippets/snippets.json").into(),
+                Some(1),
+                r#"{ "Header": { "prefix": "header", "body": "// Copyright $CURRENT_YEAR" } }"#,
+            )
+            .unwrap();
+
+        let names = |language_name: Option<&str>, worktree_id: Option<usize>| {
+            let mut names = registry
+                .snippets_for(language_name, worktree_id)
+                .iter()
+                .map(|snippet| snippet.name.clone())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        assert_eq!(names(Some("Rust"), None), ["Test function", "Todo"]);
+        assert_eq!(
+            names(Some("TypeScript"), Some(1)),
+            ["Header", "Log", "Todo"]
+        );
+        assert_eq!(names(None, Some(2)), ["Todo"]);
+
+        let snippet = &registry.snippets_for(Some("Rust"), None)[0];
+        assert_eq!(snippet.prefixes, ["test", "tst"]);
+        assert_eq!(snippet.body, "#[test]\nfn ${1:name}() {\n    $0\n}");
+        assert_eq!(snippet.description.as_deref(), Some("A unit test"));
+
+        registry.remove_worktree(1);
+        assert_eq!(names(Some("TypeScript"), Some(1)), ["Log", "Todo"]);
+        registry.remove_file(Path::new("/config/snippets/shared.code-snippets"));
+        assert_eq!(names(Some("TypeScript"), Some(1)), Vec::<String>::new());
+    }
+}
@@ -36,6 +36,8 @@ pub struct SpawnInTerminal {
     pub allow_concurrent_runs: bool,
     /// Whether the command should be spawned in a separate shell instance.
     pub separate_shell: bool,
+    /// The id of the run requested by a guest of the shared project that this spawn fulfills, if any.
+    pub task_run_id: Option<u64>,
 }
 
 /// Represents a short lived recipe of a task, whose main purpose
@@ -48,6 +48,7 @@ impl Task for OneshotTask {
             use_new_terminal: Default::default(),
             allow_concurrent_runs: Default::default(),
             separate_shell: true,
+            task_run_id: None,
         })
     }
 }
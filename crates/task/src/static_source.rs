@@ -35,6 +35,7 @@ impl Task for StaticTask {
             args: self.definition.args.clone(),
             env: self.definition.env.clone(),
             separate_shell: false,
+            task_run_id: None,
         })
     }
 
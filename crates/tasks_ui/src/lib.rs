@@ -1,8 +1,9 @@
 use std::path::PathBuf;
 
-use gpui::{AppContext, ViewContext, WindowContext};
+use gpui::{AppContext, PromptLevel, ViewContext, WindowContext};
 use modal::TasksModal;
-use task::Task;
+use project::TaskSourceKind;
+use task::{Task, TaskId};
 use util::ResultExt;
 use workspace::Workspace;
 
@@ -10,7 +11,31 @@ mod modal;
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(
-        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            cx.subscribe(workspace.project(), |workspace, project, event, cx| {
+                if let project::Event::TaskRunRequested {
+                    requester,
+                    task_run_id,
+                    task_id,
+                    label,
+                } = event
+                {
+                    let requester = project
+                        .read(cx)
+                        .collaborators()
+                        .get(requester)
+                        .and_then(|collaborator| {
+                            workspace
+                                .user_store()
+                                .read(cx)
+                                .get_cached_user(collaborator.user_id)
+                        })
+                        .map_or_else(|| "A guest".to_string(), |user| user.github_login.clone());
+                    confirm_task_run(requester, *task_run_id, task_id.clone(), label, cx);
+                }
+            })
+            .detach();
+
             workspace
                 .register_action(|workspace, _: &modal::Spawn, cx| {
                     let inventory = workspace.project().read(cx).task_inventory().clone();
@@ -33,19 +58,77 @@ pub fn init(cx: &mut AppContext) {
 }
 
 fn schedule_task(workspace: &Workspace, task: &dyn Task, cx: &mut ViewContext<'_, Workspace>) {
+    schedule_task_run(workspace, task, None, cx);
+}
+
+/// Schedules a task, on behalf of a guest's run request when `task_run_id` is set. Returns
+/// whether the task could be spawned.
+fn schedule_task_run(
+    workspace: &Workspace,
+    task: &dyn Task,
+    task_run_id: Option<u64>,
+    cx: &mut ViewContext<'_, Workspace>,
+) -> bool {
     let cwd = match task.cwd() {
         Some(cwd) => Some(cwd.to_path_buf()),
         None => task_cwd(workspace, cx).log_err().flatten(),
     };
-    let spawn_in_terminal = task.exec(cwd);
-    if let Some(spawn_in_terminal) = spawn_in_terminal {
-        workspace.project().update(cx, |project, cx| {
-            project.task_inventory().update(cx, |inventory, _| {
-                inventory.task_scheduled(task.id().clone());
-            })
-        });
-        cx.emit(workspace::Event::SpawnTask(spawn_in_terminal));
-    }
+    let Some(mut spawn_in_terminal) = task.exec(cwd) else {
+        return false;
+    };
+    spawn_in_terminal.task_run_id = task_run_id;
+    workspace.project().update(cx, |project, cx| {
+        project.task_inventory().update(cx, |inventory, _| {
+            inventory.task_scheduled(task.id().clone());
+        })
+    });
+    cx.emit(workspace::Event::SpawnTask(spawn_in_terminal));
+    true
+}
+
+/// Asks the host whether to run a task requested by a guest of the shared project.
+fn confirm_task_run(
+    requester: String,
+    task_run_id: u64,
+    task_id: TaskId,
+    label: &str,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let answer = cx.prompt(
+        PromptLevel::Warning,
+        &format!("{requester} wants to run the task \"{label}\" on your machine."),
+        Some("Its output will be shared with them."),
+        &["Run", "Cancel"],
+    );
+    cx.spawn(|workspace, mut cx| async move {
+        let run = answer.await.log_err() == Some(0);
+        workspace.update(&mut cx, |workspace, cx| {
+            let project = workspace.project().clone();
+            let task = if run {
+                project
+                    .read(cx)
+                    .task_inventory()
+                    .clone()
+                    .update(cx, |inventory, cx| {
+                        inventory.list_tasks(None, None, false, cx)
+                    })
+                    .into_iter()
+                    .find(|(source_kind, task)| {
+                        source_kind != &TaskSourceKind::UserInput && task.id() == &task_id
+                    })
+            } else {
+                None
+            };
+            let scheduled = task.map_or(false, |(_, task)| {
+                project.update(cx, |project, _| project.accept_task_run(task_run_id));
+                schedule_task_run(workspace, task.as_ref(), Some(task_run_id), cx)
+            });
+            if !scheduled {
+                project.update(cx, |project, _| project.decline_task_run(task_run_id));
+            }
+        })
+    })
+    .detach_and_log_err(cx);
 }
 
 fn task_cwd(workspace: &Workspace, cx: &mut WindowContext) -> anyhow::Result<Option<PathBuf>> {
@@ -10,7 +10,7 @@ use picker::{
     highlighted_match_with_paths::{HighlightedMatchWithPaths, HighlightedText},
     Picker, PickerDelegate,
 };
-use project::{Inventory, ProjectPath, TaskSourceKind};
+use project::{Inventory, ProjectPath, RemoteTask, TaskSourceKind};
 use task::{oneshot_source::OneshotSource, Task};
 use ui::{v_flex, ListItem, ListItemSpacing, RenderOnce, Selectable, WindowContext};
 use util::{paths::PathExt, ResultExt};
@@ -28,6 +28,8 @@ pub(crate) struct TasksModalDelegate {
     selected_index: usize,
     workspace: WeakView<Workspace>,
     prompt: String,
+    /// The tasks of the host, when the modal is opened by a guest of a shared project.
+    remote_tasks: Option<Vec<(TaskSourceKind, Arc<dyn Task>)>>,
 }
 
 impl TasksModalDelegate {
@@ -39,9 +41,28 @@ impl TasksModalDelegate {
             matches: Vec::new(),
             selected_index: 0,
             prompt: String::default(),
+            remote_tasks: None,
         }
     }
 
+    fn is_remote(&self, cx: &AppContext) -> bool {
+        self.workspace.upgrade().map_or(false, |workspace| {
+            workspace.read(cx).project().read(cx).is_remote()
+        })
+    }
+
+    /// Guests can only run the tasks of the host, which are fetched once per modal.
+    fn fetch_remote_tasks(
+        &self,
+        cx: &mut AppContext,
+    ) -> Option<gpui::Task<anyhow::Result<Vec<RemoteTask>>>> {
+        if self.remote_tasks.is_some() || !self.is_remote(cx) {
+            return None;
+        }
+        let project = self.workspace.upgrade()?.read(cx).project().clone();
+        Some(project.update(cx, |project, cx| project.remote_tasks(cx)))
+    }
+
     fn spawn_oneshot(&mut self, cx: &mut AppContext) -> Option<Arc<dyn Task>> {
         self.inventory
             .update(cx, |inventory, _| inventory.source::<OneshotSource>())?
@@ -148,18 +169,42 @@ impl PickerDelegate for TasksModalDelegate {
         cx: &mut ViewContext<picker::Picker<Self>>,
     ) -> gpui::Task<()> {
         cx.spawn(move |picker, mut cx| async move {
+            let fetch_remote_tasks = picker
+                .update(&mut cx, |picker, cx| picker.delegate.fetch_remote_tasks(cx))
+                .ok()
+                .flatten();
+            if let Some(fetch_remote_tasks) = fetch_remote_tasks {
+                let remote_tasks = fetch_remote_tasks.await.log_err().unwrap_or_default();
+                picker
+                    .update(&mut cx, |picker, _| {
+                        picker.delegate.remote_tasks = Some(
+                            remote_tasks
+                                .into_iter()
+                                .map(|task| {
+                                    (TaskSourceKind::Remote, Arc::new(task) as Arc<dyn Task>)
+                                })
+                                .collect(),
+                        );
+                    })
+                    .ok();
+            }
+
             let Some(candidates) = picker
                 .update(&mut cx, |picker, cx| {
-                    let (path, worktree) = match picker.delegate.active_item_path(cx) {
-                        Some((abs_path, project_path)) => {
-                            (Some(abs_path), Some(project_path.worktree_id))
-                        }
-                        None => (None, None),
-                    };
-                    picker.delegate.candidates =
-                        picker.delegate.inventory.update(cx, |inventory, cx| {
-                            inventory.list_tasks(path.as_deref(), worktree, true, cx)
-                        });
+                    if let Some(remote_tasks) = &picker.delegate.remote_tasks {
+                        picker.delegate.candidates = remote_tasks.clone();
+                    } else {
+                        let (path, worktree) = match picker.delegate.active_item_path(cx) {
+                            Some((abs_path, project_path)) => {
+                                (Some(abs_path), Some(project_path.worktree_id))
+                            }
+                            None => (None, None),
+                        };
+                        picker.delegate.candidates =
+                            picker.delegate.inventory.update(cx, |inventory, cx| {
+                                inventory.list_tasks(path.as_deref(), worktree, true, cx)
+                            });
+                    }
                     picker
                         .delegate
                         .candidates
@@ -205,7 +250,8 @@ impl PickerDelegate for TasksModalDelegate {
     fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<picker::Picker<Self>>) {
         let current_match_index = self.selected_index();
         let task = if secondary {
-            if !self.prompt.trim().is_empty() {
+            // Guests can't run their own commands on the host.
+            if !self.prompt.trim().is_empty() && !self.is_remote(cx) {
                 self.spawn_oneshot(cx)
             } else {
                 None
@@ -243,6 +289,7 @@ impl PickerDelegate for TasksModalDelegate {
         let (source_kind, _) = &self.candidates[hit.candidate_id];
         let details = match source_kind {
             TaskSourceKind::UserInput => "user input".to_string(),
+            TaskSourceKind::Remote => "host".to_string(),
             TaskSourceKind::Worktree { abs_path, .. } | TaskSourceKind::AbsPath(abs_path) => {
                 abs_path.compact().to_string_lossy().to_string()
             }
@@ -0,0 +1,240 @@
+//! Handles conversions of terminal screens to and from the [`rpc`] protocol, used to mirror
+//! terminals for collaborators.
+
+use crate::TerminalScreen;
+use alacritty_terminal::{
+    index::{Column, Line, Point},
+    term::{
+        cell::{Cell, Flags},
+        RenderableCursor, TermMode,
+    },
+    vte::ansi::{Color, CursorShape, NamedColor, Rgb},
+};
+use anyhow::{anyhow, Result};
+use rpc::proto;
+
+/// Colors are sent as a `u32`: named colors as their index, 8-bit colors with
+/// [`INDEXED_COLOR`] set and 24-bit colors with [`RGB_COLOR`] set.
+const INDEXED_COLOR: u32 = 1 << 24;
+const RGB_COLOR: u32 = 2 << 24;
+
+const NAMED_COLORS: [NamedColor; 29] = [
+    NamedColor::Black,
+    NamedColor::Red,
+    NamedColor::Green,
+    NamedColor::Yellow,
+    NamedColor::Blue,
+    NamedColor::Magenta,
+    NamedColor::Cyan,
+    NamedColor::White,
+    NamedColor::BrightBlack,
+    NamedColor::BrightRed,
+    NamedColor::BrightGreen,
+    NamedColor::BrightYellow,
+    NamedColor::BrightBlue,
+    NamedColor::BrightMagenta,
+    NamedColor::BrightCyan,
+    NamedColor::BrightWhite,
+    NamedColor::Foreground,
+    NamedColor::Background,
+    NamedColor::Cursor,
+    NamedColor::DimBlack,
+    NamedColor::DimRed,
+    NamedColor::DimGreen,
+    NamedColor::DimYellow,
+    NamedColor::DimBlue,
+    NamedColor::DimMagenta,
+    NamedColor::DimCyan,
+    NamedColor::DimWhite,
+    NamedColor::BrightForeground,
+    NamedColor::DimForeground,
+];
+
+/// Serializes a [`TerminalScreen`] to be sent over RPC.
+pub fn serialize_screen(screen: &TerminalScreen) -> proto::TerminalScreen {
+    let lines = screen
+        .cells
+        .chunks(screen.columns.max(1))
+        .map(|row| {
+            let mut line = proto::TerminalLine::default();
+            for cell in row {
+                let foreground = serialize_color(cell.fg);
+                let background = serialize_color(cell.bg);
+                let flags = cell.flags.bits() as u32;
+                line.text.push(cell.c);
+                match line.runs.last_mut() {
+                    Some(run)
+                        if run.foreground == foreground
+                            && run.background == background
+                            && run.flags == flags =>
+                    {
+                        run.len += 1;
+                    }
+                    _ => line.runs.push(proto::TerminalStyleRun {
+                        len: 1,
+                        foreground,
+                        background,
+                        flags,
+                    }),
+                }
+            }
+            line
+        })
+        .collect();
+
+    proto::TerminalScreen {
+        rows: screen.rows as u32,
+        columns: screen.columns as u32,
+        lines,
+        cursor_row: screen.cursor.point.line.0 as u32,
+        cursor_column: screen.cursor.point.column.0 as u32,
+        cursor_shape: serialize_cursor_shape(screen.cursor.shape),
+        mode: screen.mode.bits(),
+    }
+}
+
+/// Deserializes a [`TerminalScreen`] from the RPC representation, rejecting screens whose
+/// dimensions don't match their lines.
+pub fn deserialize_screen(screen: proto::TerminalScreen) -> Result<TerminalScreen> {
+    let rows = screen.rows as usize;
+    let columns = screen.columns as usize;
+    if rows == 0 || columns == 0 || screen.lines.len() != rows {
+        return Err(anyhow!("invalid terminal screen"));
+    }
+
+    let mut cells = Vec::with_capacity(rows * columns);
+    for line in screen.lines {
+        let row_end = cells.len() + columns;
+        let mut chars = line.text.chars();
+        for run in line.runs {
+            let fg = deserialize_color(run.foreground);
+            let bg = deserialize_color(run.background);
+            let flags = Flags::from_bits_truncate(run.flags as u16);
+            let len = (run.len as usize).min(row_end - cells.len());
+            for _ in 0..len {
+                let mut cell = Cell::default();
+                cell.c = chars.next().unwrap_or(' ');
+                cell.fg = fg;
+                cell.bg = bg;
+                cell.flags = flags;
+                cells.push(cell);
+            }
+        }
+        cells.resize(row_end, Cell::default());
+    }
+
+    Ok(TerminalScreen {
+        rows,
+        columns,
+        cells,
+        cursor: RenderableCursor {
+            shape: deserialize_cursor_shape(screen.cursor_shape),
+            point: Point::new(
+                Line((screen.cursor_row as usize).min(rows - 1) as i32),
+                Column((screen.cursor_column as usize).min(columns - 1)),
+            ),
+        },
+        mode: TermMode::from_bits_truncate(screen.mode),
+    })
+}
+
+fn serialize_color(color: Color) -> u32 {
+    match color {
+        Color::Named(name) => name as u32,
+        Color::Indexed(index) => INDEXED_COLOR | index as u32,
+        Color::Spec(rgb) => RGB_COLOR | (rgb.r as u32) << 16 | (rgb.g as u32) << 8 | rgb.b as u32,
+    }
+}
+
+fn deserialize_color(color: u32) -> Color {
+    match color & !0xffffff {
+        INDEXED_COLOR => Color::Indexed(color as u8),
+        RGB_COLOR => Color::Spec(Rgb {
+            r: (color >> 16) as u8,
+            g: (color >> 8) as u8,
+            b: color as u8,
+        }),
+        _ => Color::Named(
+            NAMED_COLORS
+                .into_iter()
+                .find(|name| *name as u32 == color)
+                .unwrap_or(NamedColor::Foreground),
+        ),
+    }
+}
+
+fn serialize_cursor_shape(shape: CursorShape) -> u32 {
+    match shape {
+        CursorShape::Block => 0,
+        CursorShape::Underline => 1,
+        CursorShape::Beam => 2,
+        CursorShape::HollowBlock => 3,
+        CursorShape::Hidden => 4,
+    }
+}
+
+fn deserialize_cursor_shape(shape: u32) -> CursorShape {
+    match shape {
+        1 => CursorShape::Underline,
+        2 => CursorShape::Beam,
+        3 => CursorShape::HollowBlock,
+        4 => CursorShape::Hidden,
+        _ => CursorShape::Block,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_screen() {
+        let colors = [
+            (Color::Named(NamedColor::Foreground), Flags::empty()),
+            (Color::Named(NamedColor::Foreground), Flags::empty()),
+            (Color::Named(NamedColor::BrightRed), Flags::BOLD),
+            (Color::Indexed(200), Flags::empty()),
+            (
+                Color::Spec(Rgb {
+                    r: 10,
+                    g: 20,
+                    b: 30,
+                }),
+                Flags::ITALIC,
+            ),
+            (Color::Named(NamedColor::DimForeground), Flags::empty()),
+        ];
+        let cells = "$ ls é"
+            .chars()
+            .zip(colors)
+            .map(|(c, (fg, flags))| {
+                let mut cell = Cell::default();
+                cell.c = c;
+                cell.fg = fg;
+                cell.flags = flags;
+                cell
+            })
+            .collect::<Vec<_>>();
+        let screen = TerminalScreen {
+            rows: 2,
+            columns: 3,
+            cells: cells.clone(),
+            cursor: RenderableCursor {
+                shape: CursorShape::Beam,
+                point: Point::new(Line(1), Column(2)),
+            },
+            mode: TermMode::SHOW_CURSOR | TermMode::ALT_SCREEN,
+        };
+
+        let message = serialize_screen(&screen);
+        assert_eq!(message.lines[0].text, "$ l");
+        assert_eq!(message.lines[0].runs.len(), 2);
+        assert_eq!(message.lines[1].runs.len(), 3);
+
+        let screen = deserialize_screen(message).unwrap();
+        assert_eq!(screen.cells, cells);
+        assert_eq!(screen.cursor.shape, CursorShape::Beam);
+        assert_eq!(screen.cursor.point, Point::new(Line(1), Column(2)));
+        assert_eq!(screen.mode, TermMode::SHOW_CURSOR | TermMode::ALT_SCREEN);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod mappings;
+pub mod proto;
 
 pub use alacritty_terminal;
 
@@ -17,7 +18,7 @@ use alacritty_terminal::{
         Config, RenderableCursor, TermMode,
     },
     tty::{self, setup_env},
-    vte::ansi::{ClearMode, Handler, NamedPrivateMode, PrivateMode, Rgb},
+    vte::ansi::{ClearMode, CursorShape, Handler, NamedPrivateMode, PrivateMode, Rgb},
     Term,
 };
 use anyhow::{bail, Result};
@@ -79,6 +80,9 @@ const DEBUG_TERMINAL_HEIGHT: Pixels = px(30.);
 const DEBUG_CELL_WIDTH: Pixels = px(5.);
 const DEBUG_LINE_HEIGHT: Pixels = px(5.);
 
+const URL_REGEX: &str = r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#;
+const WORD_REGEX: &str = r#"[\$\+\w.\[\]:/@\-~]+"#;
+
 ///Upward flowing events, for changing the title and such
 #[derive(Clone, Debug)]
 pub enum Event {
@@ -291,6 +295,8 @@ pub struct SpawnTask {
     pub command: String,
     pub args: Vec<String>,
     pub env: HashMap<String, String>,
+    /// The guest's task run that the terminal is spawned for, see [`task::SpawnInTerminal`].
+    pub task_run_id: Option<u64>,
 }
 
 // https://github.com/alacritty/alacritty/blob/cb3a79dbf6472740daca8440d5166c1d4af5029e/extra/man/alacritty.5.scd?plain=1#L207-L213
@@ -416,12 +422,12 @@ impl TerminalBuilder {
         let pty_tx = event_loop.channel();
         let _io_thread = event_loop.spawn(); // DANGER
 
-        let url_regex = RegexSearch::new(r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#).unwrap();
-        let word_regex = RegexSearch::new(r#"[\$\+\w.\[\]:/@\-~]+"#).unwrap();
+        let url_regex = RegexSearch::new(URL_REGEX).unwrap();
+        let word_regex = RegexSearch::new(WORD_REGEX).unwrap();
 
         let terminal = Terminal {
             task,
-            pty_tx: Notifier(pty_tx),
+            backend: TerminalBackend::Pty(Notifier(pty_tx)),
             completion_tx,
             term,
             events: VecDeque::with_capacity(10), //Should never get this high.
@@ -574,7 +580,7 @@ pub enum SelectionPhase {
 }
 
 pub struct Terminal {
-    pty_tx: Notifier,
+    backend: TerminalBackend,
     completion_tx: Sender<()>,
     term: Arc<FairMutex<Term<ZedListener>>>,
     events: VecDeque<InternalEvent>,
@@ -606,7 +612,243 @@ pub struct TaskState {
     pub completion_rx: Receiver<()>,
 }
 
+/// Where a terminal's content comes from and where its input goes.
+enum TerminalBackend {
+    /// A shell running in a local PTY.
+    Pty(Notifier),
+    /// A terminal shared by a collaborator, see [`Terminal::remote`].
+    Remote(RemoteTerminal),
+}
+
+struct RemoteTerminal {
+    title: String,
+    read_only: bool,
+    cursor: RenderableCursor,
+    mode: TermMode,
+    input_tx: UnboundedSender<Vec<u8>>,
+}
+
+/// The cells visible on a terminal's screen, used to mirror the terminal for collaborators.
+#[derive(Clone, Debug)]
+pub struct TerminalScreen {
+    pub rows: usize,
+    pub columns: usize,
+    /// The cells of every row, from top to bottom.
+    pub cells: Vec<Cell>,
+    pub cursor: RenderableCursor,
+    pub mode: TermMode,
+}
+
+/// The dimensions of a remote terminal's grid, which come from the collaborator sharing it
+/// rather than from the size of the element displaying it.
+struct ScreenSize {
+    rows: usize,
+    columns: usize,
+}
+
+impl Dimensions for ScreenSize {
+    fn total_lines(&self) -> usize {
+        self.rows
+    }
+
+    fn screen_lines(&self) -> usize {
+        self.rows
+    }
+
+    fn columns(&self) -> usize {
+        self.columns
+    }
+}
+
 impl Terminal {
+    /// Creates a mirror of a terminal shared by a collaborator. Instead of running a shell, the
+    /// mirror displays the screens passed to [`Terminal::set_remote_screen`] and, unless it is
+    /// read-only, sends the input written to it through `input_tx`.
+    pub fn remote(
+        title: String,
+        read_only: bool,
+        screen: TerminalScreen,
+        input_tx: UnboundedSender<Vec<u8>>,
+    ) -> Terminal {
+        let (events_tx, _) = unbounded();
+        let (completion_tx, _) = smol::channel::unbounded();
+        let config = Config {
+            scrolling_history: 0,
+            ..Config::default()
+        };
+        let term = Term::new(
+            config,
+            &ScreenSize {
+                rows: screen.rows,
+                columns: screen.columns,
+            },
+            ZedListener(events_tx),
+        );
+
+        let terminal = Terminal {
+            backend: TerminalBackend::Remote(RemoteTerminal {
+                title,
+                read_only,
+                cursor: screen.cursor,
+                mode: screen.mode,
+                input_tx,
+            }),
+            completion_tx,
+            term: Arc::new(FairMutex::new(term)),
+            events: VecDeque::with_capacity(10),
+            last_content: Default::default(),
+            last_mouse: None,
+            matches: Vec::new(),
+            selection_head: None,
+            shell_fd: 0,
+            shell_pid: 0,
+            foreground_process_info: None,
+            breadcrumb_text: String::new(),
+            scroll_px: px(0.),
+            last_mouse_position: None,
+            next_link_id: 0,
+            selection_phase: SelectionPhase::Ended,
+            cmd_pressed: false,
+            hovered_word: false,
+            url_regex: RegexSearch::new(URL_REGEX).unwrap(),
+            word_regex: RegexSearch::new(WORD_REGEX).unwrap(),
+            task: None,
+        };
+        terminal.write_remote_screen(&screen);
+        terminal
+    }
+
+    pub fn is_remote(&self) -> bool {
+        matches!(self.backend, TerminalBackend::Remote(_))
+    }
+
+    /// Whether this is a mirror of a collaborator's terminal that ignores input.
+    pub fn is_read_only(&self) -> bool {
+        match &self.backend {
+            TerminalBackend::Pty(_) => false,
+            TerminalBackend::Remote(remote) => remote.read_only,
+        }
+    }
+
+    /// Replaces the screen of a terminal created with [`Terminal::remote`].
+    pub fn set_remote_screen(
+        &mut self,
+        title: String,
+        read_only: bool,
+        screen: TerminalScreen,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let TerminalBackend::Remote(remote) = &mut self.backend else {
+            return;
+        };
+        let title_changed = remote.title != title;
+        remote.title = title;
+        remote.read_only = read_only;
+        remote.cursor = screen.cursor;
+        remote.mode = screen.mode;
+        self.write_remote_screen(&screen);
+
+        if title_changed {
+            cx.emit(Event::TitleChanged);
+        }
+        cx.emit(Event::Wakeup);
+    }
+
+    /// Marks a terminal created with [`Terminal::remote`] as displaying the output of a task
+    /// that the collaborator sharing it runs on their machine.
+    pub fn set_remote_task(&mut self, id: TaskId, label: String) {
+        if !self.is_remote() {
+            return;
+        }
+        let (completion_tx, completion_rx) = smol::channel::unbounded();
+        self.completion_tx = completion_tx;
+        self.task = Some(TaskState {
+            id,
+            label,
+            completed: false,
+            completion_rx,
+        });
+    }
+
+    /// Marks the task set with [`Terminal::set_remote_task`] as completed.
+    pub fn complete_remote_task(&mut self) {
+        if let Some(task) = &mut self.task {
+            if !task.completed {
+                task.completed = true;
+                self.completion_tx.try_send(()).ok();
+            }
+        }
+    }
+
+    fn write_remote_screen(&self, screen: &TerminalScreen) {
+        if screen.rows == 0 || screen.columns == 0 {
+            return;
+        }
+
+        let mut term = self.term.lock();
+        if term.screen_lines() != screen.rows || term.columns() != screen.columns {
+            term.resize(ScreenSize {
+                rows: screen.rows,
+                columns: screen.columns,
+            });
+        }
+        for (ix, cell) in screen
+            .cells
+            .iter()
+            .take(screen.rows * screen.columns)
+            .enumerate()
+        {
+            let point = AlacPoint::new(
+                Line((ix / screen.columns) as i32),
+                Column(ix % screen.columns),
+            );
+            term.grid_mut()[point] = cell.clone();
+        }
+    }
+
+    /// Returns the cells currently visible on screen, taking the scroll position into account.
+    pub fn screen(&self) -> TerminalScreen {
+        let term = self.term.lock();
+        let display_offset = term.grid().display_offset() as i32;
+        let rows = term.screen_lines();
+        let columns = term.columns();
+
+        let mut cells = Vec::with_capacity(rows * columns);
+        for row in 0..rows {
+            let line = Line(row as i32 - display_offset);
+            for column in 0..columns {
+                let cell = &term.grid()[line][Column(column)];
+                let mut screen_cell = Cell::default();
+                screen_cell.c = cell.c;
+                screen_cell.fg = cell.fg;
+                screen_cell.bg = cell.bg;
+                screen_cell.flags = cell.flags;
+                cells.push(screen_cell);
+            }
+        }
+
+        let (cursor, mode) = match &self.backend {
+            TerminalBackend::Pty(_) => {
+                let mut cursor = term.renderable_content().cursor;
+                cursor.point.line = Line(cursor.point.line.0 + display_offset);
+                if cursor.point.line.0 as usize >= rows {
+                    cursor.shape = CursorShape::Hidden;
+                    cursor.point.line = Line(0);
+                }
+                (cursor, *term.mode())
+            }
+            TerminalBackend::Remote(remote) => (remote.cursor, remote.mode),
+        };
+
+        TerminalScreen {
+            rows,
+            columns,
+            cells,
+            cursor,
+            mode,
+        }
+    }
+
     fn process_event(&mut self, event: &AlacTermEvent, cx: &mut ModelContext<Self>) {
         match event {
             AlacTermEvent::Title(title) => {
@@ -717,9 +959,12 @@ impl Terminal {
 
                 self.last_content.size = new_size.clone();
 
-                self.pty_tx.0.send(Msg::Resize(new_size.into())).ok();
+                // The grid of a remote terminal always has the size of the terminal it mirrors.
+                if let TerminalBackend::Pty(pty_tx) = &self.backend {
+                    pty_tx.0.send(Msg::Resize(new_size.into())).ok();
 
-                term.resize(new_size);
+                    term.resize(new_size);
+                }
             }
             InternalEvent::Clear => {
                 // Clear back buffer
@@ -944,6 +1189,17 @@ impl Terminal {
         &self.last_content
     }
 
+    /// Returns the text of the terminal's last `max_lines` lines, including the scrollback.
+    pub fn last_lines(&self, max_lines: usize) -> String {
+        let term = self.term.lock();
+        let end = AlacPoint::new(term.bottommost_line(), term.last_column());
+        let start_line = term
+            .topmost_line()
+            .max(end.line - max_lines.saturating_sub(1));
+        let start = AlacPoint::new(start_line, Column(0));
+        term.bounds_to_string(start, end).trim_end().to_string()
+    }
+
     //To test:
     //- Activate match on terminal (scrolling and selection)
     //- Editor search snapping behavior
@@ -1000,11 +1256,18 @@ impl Terminal {
 
     ///Write the Input payload to the tty.
     fn write_to_pty(&self, input: String) {
-        self.pty_tx.notify(input.into_bytes());
+        self.write_bytes_to_pty(input.into_bytes());
     }
 
     fn write_bytes_to_pty(&self, input: Vec<u8>) {
-        self.pty_tx.notify(input);
+        match &self.backend {
+            TerminalBackend::Pty(pty_tx) => pty_tx.notify(input),
+            TerminalBackend::Remote(remote) => {
+                if !remote.read_only {
+                    remote.input_tx.unbounded_send(input).ok();
+                }
+            }
+        }
     }
 
     pub fn input(&mut self, input: String) {
@@ -1062,6 +1325,12 @@ impl Terminal {
         }
 
         self.last_content = Self::make_content(&terminal, &self.last_content);
+        if let TerminalBackend::Remote(remote) = &self.backend {
+            // A remote terminal's grid only holds its cells, the rest comes from its host.
+            self.last_content.mode = remote.mode;
+            self.last_content.cursor = remote.cursor;
+            self.last_content.cursor_char = terminal.grid()[remote.cursor.point].c;
+        }
     }
 
     fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
@@ -1138,7 +1407,7 @@ impl Terminal {
 
             if self.mouse_changed(point, side) {
                 if let Some(bytes) = mouse_moved_report(point, e, self.last_content.mode) {
-                    self.pty_tx.notify(bytes);
+                    self.write_bytes_to_pty(bytes);
                 }
             }
         } else if self.cmd_pressed {
@@ -1212,7 +1481,7 @@ impl Terminal {
             if let Some(bytes) =
                 mouse_button_report(point, e.button, e.modifiers, true, self.last_content.mode)
             {
-                self.pty_tx.notify(bytes);
+                self.write_bytes_to_pty(bytes);
             }
         } else if e.button == MouseButton::Left {
             let position = e.position - origin;
@@ -1259,7 +1528,7 @@ impl Terminal {
             if let Some(bytes) =
                 mouse_button_report(point, e.button, e.modifiers, false, self.last_content.mode)
             {
-                self.pty_tx.notify(bytes);
+                self.write_bytes_to_pty(bytes);
             }
         } else {
             if e.button == MouseButton::Left && setting.copy_on_select {
@@ -1298,7 +1567,7 @@ impl Terminal {
                     scroll_report(point, scroll_lines as i32, e, self.last_content.mode)
                 {
                     for scroll in scrolls {
-                        self.pty_tx.notify(scroll);
+                        self.write_bytes_to_pty(scroll);
                     }
                 };
             } else if self
@@ -1307,7 +1576,7 @@ impl Terminal {
                 .contains(TermMode::ALT_SCREEN | TermMode::ALTERNATE_SCROLL)
                 && !e.shift
             {
-                self.pty_tx.notify(alt_scroll(scroll_lines))
+                self.write_bytes_to_pty(alt_scroll(scroll_lines))
             } else {
                 if scroll_lines != 0 {
                     let scroll = AlacScroll::Delta(scroll_lines);
@@ -1364,6 +1633,13 @@ impl Terminal {
 
     pub fn title(&self, truncate: bool) -> String {
         const MAX_CHARS: usize = 25;
+        if let TerminalBackend::Remote(remote) = &self.backend {
+            return if truncate {
+                truncate_and_trailoff(&remote.title, MAX_CHARS)
+            } else {
+                remote.title.clone()
+            };
+        }
         match &self.task {
             Some(task_state) => truncate_and_trailoff(&task_state.label, MAX_CHARS),
             None => self
@@ -1425,7 +1701,9 @@ impl Terminal {
 
 impl Drop for Terminal {
     fn drop(&mut self) {
-        self.pty_tx.0.send(Msg::Shutdown).ok();
+        if let TerminalBackend::Pty(pty_tx) = &self.backend {
+            pty_tx.0.send(Msg::Shutdown).ok();
+        }
     }
 }
 
@@ -1566,13 +1844,16 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
 mod tests {
     use alacritty_terminal::{
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        term::{cell::Cell, RenderableCursor, TermMode},
+        vte::ansi::CursorShape,
     };
+    use futures::channel::mpsc::unbounded;
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, rgb_for_index, IndexedCell, Terminal, TerminalContent,
+        TerminalScreen, TerminalSize,
     };
 
     #[test]
@@ -1584,6 +1865,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_remote_terminal() {
+        let screen = |text: &str| {
+            let cells = text
+                .chars()
+                .map(|c| {
+                    let mut cell = Cell::default();
+                    cell.c = c;
+                    cell
+                })
+                .collect();
+            TerminalScreen {
+                rows: 2,
+                columns: 3,
+                cells,
+                cursor: RenderableCursor {
+                    shape: CursorShape::Block,
+                    point: AlacPoint::new(Line(1), Column(2)),
+                },
+                mode: TermMode::default(),
+            }
+        };
+        let screen_text = |terminal: &Terminal| {
+            terminal
+                .screen()
+                .cells
+                .iter()
+                .map(|cell| cell.c)
+                .collect::<String>()
+        };
+
+        let (input_tx, mut input_rx) = unbounded();
+        let terminal = Terminal::remote("shell".into(), true, screen("ab$ cd"), input_tx);
+        assert_eq!(terminal.title(false), "shell");
+        assert_eq!(screen_text(&terminal), "ab$ cd");
+        assert_eq!(
+            terminal.screen().cursor.point,
+            AlacPoint::new(Line(1), Column(2))
+        );
+
+        terminal.write_remote_screen(&screen("xyz123"));
+        assert_eq!(screen_text(&terminal), "xyz123");
+
+        // Read-only terminals drop their input.
+        terminal.write_to_pty("ls".into());
+        assert!(input_rx.try_next().is_err());
+
+        let (input_tx, mut input_rx) = unbounded();
+        let terminal = Terminal::remote("shell".into(), false, screen("ab$ cd"), input_tx);
+        terminal.write_to_pty("ls".into());
+        assert_eq!(input_rx.try_next().unwrap(), Some(b"ls".to_vec()));
+    }
+
     #[test]
     fn test_mouse_to_cell_test() {
         let mut rng = thread_rng();
@@ -0,0 +1,213 @@
+use anyhow::{anyhow, Result};
+use futures::{channel::mpsc::UnboundedReceiver, StreamExt};
+use gpui::{Context, Model, Task, View, ViewContext, VisualContext, WindowContext};
+use project::Project;
+use rpc::proto::{self, PeerId};
+use smol::Timer;
+use std::time::Duration;
+use terminal::{
+    proto::{deserialize_screen, serialize_screen},
+    Terminal,
+};
+use workspace::{
+    item::{FollowEvent, FollowableItem, ItemEvent},
+    Pane, ViewId, Workspace,
+};
+
+use crate::{TerminalView, ToggleFollowerInput};
+
+/// The minimum delay between two screens sent to followers of a terminal.
+const FOLLOWER_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+impl TerminalView {
+    /// Notifies the workspace that the screen changed, at most once per
+    /// [`FOLLOWER_UPDATE_INTERVAL`], so that a busy terminal doesn't flood its followers.
+    pub(crate) fn schedule_follower_update(&mut self, cx: &mut ViewContext<Self>) {
+        if self.pending_follower_update.is_some() {
+            return;
+        }
+        let is_shared = self.workspace.upgrade().map_or(false, |workspace| {
+            workspace.read(cx).project().read(cx).remote_id().is_some()
+        });
+        if !is_shared {
+            return;
+        }
+
+        self.pending_follower_update = Some(cx.spawn(|this, mut cx| async move {
+            Timer::after(FOLLOWER_UPDATE_INTERVAL).await;
+            this.update(&mut cx, |this, cx| {
+                this.pending_follower_update = None;
+                cx.emit(ItemEvent::Edit);
+            })
+            .ok();
+        }));
+    }
+
+    pub(crate) fn toggle_follower_input(
+        &mut self,
+        _: &ToggleFollowerInput,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.allow_follower_input = !self.allow_follower_input;
+        self.schedule_follower_update(cx);
+    }
+
+    fn is_read_only_for_followers(&self, cx: &WindowContext) -> bool {
+        !self.allow_follower_input || self.terminal.read(cx).is_read_only()
+    }
+
+    fn forward_input_to_leader(
+        &self,
+        view_id: ViewId,
+        mut input_rx: UnboundedReceiver<Vec<u8>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        cx.spawn(|this, mut cx| async move {
+            while let Some(input) = input_rx.next().await {
+                let Ok(workspace) = this.update(&mut cx, |this, _| this.workspace.clone()) else {
+                    break;
+                };
+                workspace
+                    .update(&mut cx, |workspace, cx| {
+                        workspace.send_follower_input(view_id, input, cx)
+                    })
+                    .ok();
+            }
+        })
+        .detach();
+    }
+}
+
+impl FollowableItem for TerminalView {
+    fn remote_id(&self) -> Option<ViewId> {
+        self.remote_id
+    }
+
+    fn to_state_proto(&self, cx: &WindowContext) -> Option<proto::view::Variant> {
+        let terminal = self.terminal.read(cx);
+        Some(proto::view::Variant::Terminal(proto::view::Terminal {
+            title: terminal.title(false),
+            read_only: self.is_read_only_for_followers(cx),
+            screen: Some(serialize_screen(&terminal.screen())),
+        }))
+    }
+
+    fn from_state_proto(
+        pane: View<Pane>,
+        workspace: View<Workspace>,
+        remote_id: ViewId,
+        state: &mut Option<proto::view::Variant>,
+        cx: &mut WindowContext,
+    ) -> Option<Task<Result<View<Self>>>> {
+        let Some(proto::view::Variant::Terminal(_)) = state else {
+            return None;
+        };
+        let Some(proto::view::Variant::Terminal(state)) = state.take() else {
+            unreachable!()
+        };
+
+        let screen = match state
+            .screen
+            .ok_or_else(|| anyhow!("missing terminal screen"))
+            .and_then(deserialize_screen)
+        {
+            Ok(screen) => screen,
+            Err(error) => return Some(Task::ready(Err(error))),
+        };
+
+        let existing_view = pane
+            .read(cx)
+            .items_of_type::<Self>()
+            .find(|view| view.read(cx).remote_id == Some(remote_id));
+        if let Some(view) = existing_view {
+            view.update(cx, |view, cx| {
+                view.terminal.update(cx, |terminal, cx| {
+                    terminal.set_remote_screen(state.title, state.read_only, screen, cx)
+                })
+            });
+            return Some(Task::ready(Ok(view)));
+        }
+
+        let workspace_id = workspace.read(cx).database_id();
+        let (input_tx, input_rx) = futures::channel::mpsc::unbounded();
+        let terminal =
+            cx.new_model(|_| Terminal::remote(state.title, state.read_only, screen, input_tx));
+        let view = cx.new_view(|cx| {
+            let mut view = TerminalView::new(terminal, workspace.downgrade(), workspace_id, cx);
+            view.remote_id = Some(remote_id);
+            view.forward_input_to_leader(remote_id, input_rx, cx);
+            view
+        });
+        Some(Task::ready(Ok(view)))
+    }
+
+    fn to_follow_event(_: &ItemEvent) -> Option<FollowEvent> {
+        None
+    }
+
+    fn add_event_to_update_proto(
+        &self,
+        event: &ItemEvent,
+        update: &mut Option<proto::update_view::Variant>,
+        cx: &WindowContext,
+    ) -> bool {
+        match event {
+            ItemEvent::Edit => {
+                let terminal = self.terminal.read(cx);
+                *update = Some(proto::update_view::Variant::Terminal(
+                    proto::update_view::Terminal {
+                        title: terminal.title(false),
+                        read_only: self.is_read_only_for_followers(cx),
+                        screen: Some(serialize_screen(&terminal.screen())),
+                    },
+                ));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn apply_update_proto(
+        &mut self,
+        _: &Model<Project>,
+        message: proto::update_view::Variant,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let proto::update_view::Variant::Terminal(message) = message else {
+            return Task::ready(Err(anyhow!("invalid terminal update")));
+        };
+        let screen = match message
+            .screen
+            .ok_or_else(|| anyhow!("missing terminal screen"))
+            .and_then(deserialize_screen)
+        {
+            Ok(screen) => screen,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        self.terminal.update(cx, |terminal, cx| {
+            terminal.set_remote_screen(message.title, message.read_only, screen, cx)
+        });
+        Task::ready(Ok(()))
+    }
+
+    fn is_project_item(&self, _cx: &WindowContext) -> bool {
+        true
+    }
+
+    fn set_leader_peer_id(&mut self, _: Option<PeerId>, _: &mut ViewContext<Self>) {}
+
+    fn apply_follower_input(
+        &mut self,
+        _follower_id: PeerId,
+        input: Vec<u8>,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        if self.is_read_only_for_followers(cx) {
+            return Err(anyhow!("terminal is read-only for followers"));
+        }
+        self.terminal
+            .update(cx, |terminal, _| terminal.input_bytes(input));
+        Ok(())
+    }
+}
@@ -24,6 +24,7 @@ use util::{ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
     item::Item,
+    notifications::DetachAndPromptErr,
     pane,
     ui::IconName,
     DraggedTab, Pane, Workspace,
@@ -57,11 +58,13 @@ pub struct TerminalPanel {
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
     _subscriptions: Vec<Subscription>,
-    deferred_tasks: HashMap<TaskId, Task<()>>,
+    /// Tasks waiting for their previous run to complete, with the guest's task run they were
+    /// spawned for, if any.
+    deferred_tasks: HashMap<TaskId, (Option<u64>, Task<()>)>,
 }
 
 impl TerminalPanel {
-    fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
+    pub fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
         let terminal_panel = cx.view().downgrade();
         let pane = cx.new_view(|cx| {
             let mut pane = Pane::new(
@@ -264,7 +267,10 @@ impl TerminalPanel {
         cx: &mut ViewContext<Self>,
     ) {
         match event {
-            pane::Event::ActivateItem { .. } => self.serialize(cx),
+            pane::Event::ActivateItem { .. } => {
+                self.serialize(cx);
+                self.update_active_view_for_followers(cx);
+            }
             pane::Event::RemoveItem { .. } => self.serialize(cx),
             pane::Event::Remove => cx.emit(PanelEvent::Close),
             pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
@@ -281,6 +287,15 @@ impl TerminalPanel {
         }
     }
 
+    /// Lets followers see the terminal that became active while the panel is focused.
+    fn update_active_view_for_followers(&self, cx: &mut ViewContext<Self>) {
+        if let Some(workspace) = self.workspace.upgrade() {
+            workspace.update(cx, |workspace, cx| {
+                workspace.update_active_view_for_followers(cx)
+            })
+        }
+    }
+
     pub fn open_terminal(
         workspace: &mut Workspace,
         action: &workspace::OpenTerminal,
@@ -295,13 +310,27 @@ impl TerminalPanel {
         })
     }
 
+    /// Returns the terminal that is active in the panel, if any.
+    pub fn active_terminal(&self, cx: &AppContext) -> Option<View<TerminalView>> {
+        self.pane.read(cx).active_item()?.downcast::<TerminalView>()
+    }
+
     pub fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        let is_remote = self.workspace.upgrade().map_or(false, |workspace| {
+            workspace.read(cx).project().read(cx).is_remote()
+        });
+        if is_remote {
+            self.spawn_remote_task(spawn_in_terminal.id.clone(), cx);
+            return;
+        }
+
         let mut spawn_task = SpawnTask {
             id: spawn_in_terminal.id.clone(),
             label: spawn_in_terminal.label.clone(),
             command: spawn_in_terminal.command.clone(),
             args: spawn_in_terminal.args.clone(),
             env: spawn_in_terminal.env.clone(),
+            task_run_id: spawn_in_terminal.task_run_id,
         };
         if spawn_in_terminal.separate_shell {
             let Some((shell, mut user_args)) = (match TerminalSettings::get_global(cx).shell.clone()
@@ -310,6 +339,7 @@ impl TerminalPanel {
                 Shell::Program(shell) => Some((shell, vec![])),
                 Shell::WithArguments { program, args } => Some((program, args)),
             }) else {
+                self.decline_task_run(spawn_task.task_run_id, cx);
                 return;
             };
 
@@ -351,34 +381,48 @@ impl TerminalPanel {
                 cx,
             );
         } else {
-            self.deferred_tasks.insert(
-                spawn_in_terminal.id.clone(),
-                cx.spawn(|terminal_panel, mut cx| async move {
-                    wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
-                    terminal_panel
-                        .update(&mut cx, |terminal_panel, cx| {
-                            if use_new_terminal {
-                                terminal_panel.spawn_in_new_terminal(
-                                    spawn_task,
-                                    working_directory,
-                                    cx,
-                                );
-                            } else {
-                                terminal_panel.replace_terminal(
-                                    working_directory,
-                                    spawn_task,
-                                    existing_item_index,
-                                    existing_terminal,
-                                    cx,
-                                );
-                            }
-                        })
-                        .ok();
-                }),
-            );
+            let task_run_id = spawn_task.task_run_id;
+            let deferred_task = cx.spawn(|terminal_panel, mut cx| async move {
+                wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
+                terminal_panel
+                    .update(&mut cx, |terminal_panel, cx| {
+                        if use_new_terminal {
+                            terminal_panel.spawn_in_new_terminal(spawn_task, working_directory, cx);
+                        } else {
+                            terminal_panel.replace_terminal(
+                                working_directory,
+                                spawn_task,
+                                existing_item_index,
+                                existing_terminal,
+                                cx,
+                            );
+                        }
+                    })
+                    .ok();
+            });
+            // The previous deferred run of the task is dropped, so it won't ever be spawned.
+            if let Some((Some(replaced_task_run_id), _)) = self
+                .deferred_tasks
+                .insert(spawn_in_terminal.id.clone(), (task_run_id, deferred_task))
+            {
+                self.decline_task_run(Some(replaced_task_run_id), cx);
+            }
         }
     }
 
+    /// Gives up on the guest's task run that a task couldn't be spawned for, so that the guest
+    /// doesn't wait for it forever.
+    fn decline_task_run(&self, task_run_id: Option<u64>, cx: &mut AppContext) {
+        let (Some(task_run_id), Some(workspace)) = (task_run_id, self.workspace.upgrade()) else {
+            return;
+        };
+        workspace
+            .read(cx)
+            .project()
+            .clone()
+            .update(cx, |project, _| project.decline_task_run(task_run_id));
+    }
+
     fn spawn_in_new_terminal(
         &mut self,
         spawn_task: SpawnTask,
@@ -395,6 +439,37 @@ impl TerminalPanel {
         .detach();
     }
 
+    /// Asks the host of the shared project to run the task, and shows its output in a read-only
+    /// terminal once the host accepts.
+    fn spawn_remote_task(&mut self, task_id: TaskId, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let run_task = workspace.update(cx, |workspace, cx| {
+            workspace
+                .project()
+                .update(cx, |project, cx| project.run_remote_task(task_id, cx))
+        });
+        let pane = self.pane.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            let terminal = run_task.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let terminal = Box::new(cx.new_view(|cx| {
+                    TerminalView::new(
+                        terminal,
+                        workspace.weak_handle(),
+                        workspace.database_id(),
+                        cx,
+                    )
+                }));
+                pane.update(cx, |pane, cx| pane.add_item(terminal, true, true, None, cx));
+                workspace.focus_panel::<Self>(cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to run the task on the host", cx, |_, _| None);
+    }
+
     ///Create a new Terminal in the current working directory or the user's home directory
     fn new_terminal(
         workspace: &mut Workspace,
@@ -699,6 +774,10 @@ impl Panel for TerminalPanel {
     fn toggle_action(&self) -> Box<dyn gpui::Action> {
         Box::new(ToggleFocus)
     }
+
+    fn pane(&self) -> Option<View<Pane>> {
+        Some(self.pane.clone())
+    }
 }
 
 #[derive(Serialize, Deserialize)]
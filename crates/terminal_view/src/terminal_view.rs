@@ -1,3 +1,4 @@
+mod following;
 mod persistence;
 pub mod terminal_element;
 pub mod terminal_panel;
@@ -6,9 +7,9 @@ use collections::HashSet;
 use editor::{scroll::Autoscroll, Editor};
 use futures::{stream::FuturesUnordered, StreamExt};
 use gpui::{
-    div, impl_actions, overlay, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle,
-    FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton, MouseDownEvent, Pixels,
-    Render, Styled, Subscription, Task, View, VisualContext, WeakView,
+    actions, div, impl_actions, overlay, AnyElement, AppContext, DismissEvent, EventEmitter,
+    FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton,
+    MouseDownEvent, Pixels, Render, Styled, Subscription, Task, View, VisualContext, WeakView,
 };
 use language::Bias;
 use persistence::TERMINAL_DB;
@@ -27,9 +28,9 @@ use util::{paths::PathLikeWithPosition, ResultExt};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent},
     notifications::NotifyResultExt,
-    register_deserializable_item,
+    register_deserializable_item, register_followable_item,
     searchable::{SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
-    CloseActiveItem, NewCenterTerminal, OpenVisible, Pane, ToolbarItemLocation, Workspace,
+    CloseActiveItem, NewCenterTerminal, OpenVisible, Pane, ToolbarItemLocation, ViewId, Workspace,
     WorkspaceId,
 };
 
@@ -64,11 +65,14 @@ pub struct SendKeystroke(String);
 
 impl_actions!(terminal, [SendText, SendKeystroke]);
 
+actions!(terminal, [ToggleFollowerInput]);
+
 pub fn init(cx: &mut AppContext) {
     terminal_panel::init(cx);
     terminal::init(cx);
 
     register_deserializable_item::<TerminalView>(cx);
+    register_followable_item::<TerminalView>(cx);
 
     cx.observe_new_views(|workspace: &mut Workspace, _| {
         workspace.register_action(TerminalView::deploy);
@@ -90,6 +94,11 @@ pub struct TerminalView {
     blink_epoch: usize,
     can_navigate_to_selected_word: bool,
     workspace_id: WorkspaceId,
+    /// The id of the leader's view, if this view mirrors a terminal shared by a collaborator.
+    remote_id: Option<ViewId>,
+    /// Whether collaborators following this terminal are allowed to type into it.
+    allow_follower_input: bool,
+    pending_follower_update: Option<Task<()>>,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -165,6 +174,9 @@ impl TerminalView {
             blink_epoch: 0,
             can_navigate_to_selected_word: false,
             workspace_id,
+            remote_id: None,
+            allow_follower_input: false,
+            pending_follower_update: None,
             _subscriptions: vec![focus_in, focus_out],
             _terminal_subscriptions: terminal_subscriptions,
         }
@@ -188,9 +200,23 @@ impl TerminalView {
         position: gpui::Point<Pixels>,
         cx: &mut ViewContext<Self>,
     ) {
+        let follower_input_label = if self.terminal.read(cx).is_remote() {
+            None
+        } else if self.allow_follower_input {
+            Some("Make Read-Only for Followers")
+        } else {
+            Some("Allow Followers to Type")
+        };
         let context_menu = ContextMenu::build(cx, |menu, _| {
-            menu.action("Clear", Box::new(Clear))
-                .action("Close", Box::new(CloseActiveItem { save_intent: None }))
+            let menu = menu
+                .action("Clear", Box::new(Clear))
+                .action("Close", Box::new(CloseActiveItem { save_intent: None }));
+            match follower_input_label {
+                Some(label) => menu
+                    .separator()
+                    .action(label, Box::new(ToggleFollowerInput)),
+                None => menu,
+            }
         });
 
         cx.focus_view(&context_menu);
@@ -429,6 +455,7 @@ fn subscribe_for_terminal_events(
                 cx.emit(Event::Wakeup);
                 cx.emit(ItemEvent::UpdateTab);
                 cx.emit(SearchEvent::MatchesInvalidated);
+                this.schedule_follower_update(cx);
             }
 
             Event::Bell => {
@@ -440,6 +467,7 @@ fn subscribe_for_terminal_events(
 
             Event::TitleChanged => {
                 cx.emit(ItemEvent::UpdateTab);
+                this.schedule_follower_update(cx);
                 let terminal = this.terminal().read(cx);
                 if !terminal.task().is_some() {
                     if let Some(foreground_info) = &terminal.foreground_process_info {
@@ -732,6 +760,7 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::clear))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
+            .on_action(cx.listener(TerminalView::toggle_follower_input))
             .on_key_down(cx.listener(Self::key_down))
             .on_mouse_down(
                 MouseButton::Right,
@@ -883,7 +912,8 @@ impl Item for TerminalView {
     }
 
     fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
-        if !self.terminal().read(cx).task().is_some() {
+        let terminal = self.terminal().read(cx);
+        if !terminal.task().is_some() && !terminal.is_remote() {
             cx.background_executor()
                 .spawn(TERMINAL_DB.update_workspace_id(
                     workspace.database_id(),
@@ -524,6 +524,41 @@ fn test_undo_redo() {
     assert_eq!(buffer.text(), "1234");
 }
 
+#[test]
+fn test_text_for_version() {
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "1234".into());
+    buffer.set_group_interval(Duration::from_secs(0));
+    let initial_version = buffer.version();
+
+    buffer.edit([(1..3, "abc")]);
+    let edited_version = buffer.version();
+    buffer.edit([(0..1, ""), (4..4, "de")]);
+    assert_eq!(buffer.text(), "abcde4");
+    assert_eq!(
+        buffer.text_for_version(&initial_version).to_string(),
+        "1234"
+    );
+    assert_eq!(
+        buffer.text_for_version(&edited_version).to_string(),
+        "1abc4"
+    );
+    assert_eq!(
+        buffer.text_for_version(&buffer.version()).to_string(),
+        "abcde4"
+    );
+
+    buffer.undo();
+    assert_eq!(buffer.text(), "1abc4");
+    assert_eq!(
+        buffer.text_for_version(&initial_version).to_string(),
+        "1234"
+    );
+    assert_eq!(
+        buffer.text_for_version(&edited_version).to_string(),
+        "1abc4"
+    );
+}
+
 #[test]
 fn test_history() {
     let mut now = Instant::now();
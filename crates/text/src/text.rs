@@ -1708,6 +1708,31 @@ impl BufferSnapshot {
         self.deleted_text.to_string()
     }
 
+    /// Returns the text of the buffer as it was at the given version.
+    pub fn text_for_version(&self, version: &clock::Global) -> Rope {
+        let mut text = Rope::new();
+        let mut cursor = self.fragments.cursor::<FragmentTextSummary>();
+        cursor.next(&None);
+        while let Some(fragment) = cursor.item() {
+            if fragment.was_visible(version, &self.undo_map) {
+                let start = cursor.start();
+                if fragment.visible {
+                    text.append(
+                        self.visible_text
+                            .slice(start.visible..start.visible + fragment.len),
+                    );
+                } else {
+                    text.append(
+                        self.deleted_text
+                            .slice(start.deleted..start.deleted + fragment.len),
+                    );
+                }
+            }
+            cursor.next(&None);
+        }
+        text
+    }
+
     pub fn fragments(&self) -> impl Iterator<Item = &Fragment> {
         self.fragments.iter()
     }
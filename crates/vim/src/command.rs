@@ -1,17 +1,33 @@
+use std::{
+    ops::{Range, RangeInclusive},
+    path::Path,
+};
+
+use anyhow::{anyhow, bail, Result};
+use command_palette::CommandPalette;
 use command_palette_hooks::CommandInterceptResult;
-use editor::actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive};
-use gpui::{impl_actions, Action, AppContext, ViewContext};
+use editor::{
+    actions::{Indent, Outdent},
+    scroll::Autoscroll,
+    Anchor, Editor, MultiBufferSnapshot, SoftWrap, ToPoint,
+};
+use gpui::{actions, impl_actions, Action, AppContext, ViewContext, WindowContext};
+use language::{language_settings, Point};
+use regex::{Regex, RegexBuilder};
 use serde_derive::Deserialize;
-use workspace::{SaveIntent, Workspace};
+use workspace::{SaveIntent, Toast, Workspace};
 
 use crate::{
+    insert::NormalBefore,
     motion::{EndOfDocument, Motion, StartOfDocument},
     normal::{
+        mark::mark_anchor,
         move_cursor,
-        search::{FindCommand, ReplaceCommand},
-        JoinLines,
+        repeat::{parse_keystrokes, replay},
+        search::{split_on_delimiter, vim_regex, FindCommand, ReplaceCommand, Replacement},
     },
-    state::Mode,
+    state::{Mode, ReplayableAction},
+    utils::{copy_selections_content, yank_selections_content},
     Vim,
 };
 
@@ -20,7 +36,17 @@ pub struct GoToLine {
     pub line: u32,
 }
 
-impl_actions!(vim, [GoToLine]);
+/// Runs an ex command, like `:%s/a/b/g`, that is parsed when it runs.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExCommand {
+    pub command: String,
+}
+
+impl_actions!(vim, [GoToLine, ExCommand]);
+
+actions!(vim, [VisualCommand, CountCommand, EndNormalCommand]);
+
+const SUBSTITUTE_COUNT_TOAST_ID: usize = 0x5b5717e;
 
 pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     workspace.register_action(|_: &mut Workspace, action: &GoToLine, cx| {
@@ -29,18 +55,44 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
             move_cursor(vim, Motion::StartOfDocument, Some(action.line as usize), cx);
         });
     });
+
+    workspace.register_action(|workspace: &mut Workspace, _: &VisualCommand, cx| {
+        Vim::update(cx, |vim, cx| vim.switch_mode(Mode::Normal, false, cx));
+        CommandPalette::toggle(workspace, "'<,'>", cx);
+    });
+
+    workspace.register_action(|workspace: &mut Workspace, _: &CountCommand, cx| {
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx)).unwrap_or(1);
+        let query = if count > 1 {
+            format!(".,.+{}", count - 1)
+        } else {
+            ".".to_string()
+        };
+        CommandPalette::toggle(workspace, &query, cx);
+    });
+
+    workspace.register_action(|workspace: &mut Workspace, action: &ExCommand, cx| {
+        if let Err(error) = run_ex_command(workspace, &action.command, cx) {
+            workspace.show_error(&error, cx);
+        }
+    });
+
+    workspace.register_action(|_: &mut Workspace, _: &EndNormalCommand, cx| {
+        // like vim, leave whatever mode the keys of `:normal` ended in.
+        let mode = Vim::read(cx).state().mode;
+        match mode {
+            Mode::Insert => cx.dispatch_action(NormalBefore.boxed_clone()),
+            Mode::Normal => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
+            Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
+                Vim::update(cx, |vim, cx| vim.switch_mode(Mode::Normal, false, cx))
+            }
+        }
+    });
 }
 
-pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
-    // Note: this is a very poor simulation of vim's command palette.
-    // In the future we should adjust it to handle parsing range syntax,
-    // and then calling the appropriate commands with/without ranges.
-    //
-    // We also need to support passing arguments to commands like :w
-    // (ideally with filename autocompletion).
-    //
-    // For now, you can only do a replace on the % range, and you can
-    // only use a specific line number range to "go to line"
+pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Vec<CommandInterceptResult> {
+    // Commands that take no range or arguments are looked up below. Anything else is
+    // parsed as an ex command, and run by `ExCommand` once it's confirmed.
     while query.starts_with(":") {
         query = &query[1..];
     }
@@ -255,15 +307,6 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
             ("lNext", editor::actions::GoToPrevDiagnostic.boxed_clone())
         }
 
-        // modify the buffer (should accept [range])
-        "j" | "jo" | "joi" | "join" => ("join", JoinLines.boxed_clone()),
-        "d" | "de" | "del" | "dele" | "delet" | "delete" | "dl" | "dell" | "delel" | "deletl"
-        | "deletel" | "dp" | "dep" | "delp" | "delep" | "deletp" | "deletep" => {
-            ("delete", editor::actions::DeleteLine.boxed_clone())
-        }
-        "sor" | "sor " | "sort" | "sort " => ("sort", SortLinesCaseSensitive.boxed_clone()),
-        "sor i" | "sort i" => ("sort i", SortLinesCaseInsensitive.boxed_clone()),
-
         // Explore, etc.
         "E" | "Ex" | "Exp" | "Expl" | "Explo" | "Explor" | "Explore" => (
             "Explore",
@@ -321,39 +364,28 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
         "0" => ("0", StartOfDocument.boxed_clone()),
 
         _ => {
-            if query.starts_with("/") || query.starts_with("?") {
-                (
-                    query,
-                    FindCommand {
-                        query: query[1..].to_string(),
-                        backwards: query.starts_with("?"),
-                    }
-                    .boxed_clone(),
-                )
-            } else if query.starts_with("%") {
-                (
-                    query,
-                    ReplaceCommand {
-                        query: query.to_string(),
-                    }
-                    .boxed_clone(),
-                )
-            } else if let Ok(line) = query.parse::<u32>() {
+            if let Ok(line) = query.parse::<u32>() {
                 (query, GoToLine { line }.boxed_clone())
+            } else if let Some(find) = search_command(query) {
+                (query, find.boxed_clone())
             } else {
-                return None;
+                return ex_command_results(query, cx);
             }
         }
     };
 
+    vec![intercept_result(name, query, action)]
+}
+
+fn intercept_result(name: &str, query: &str, action: Box<dyn Action>) -> CommandInterceptResult {
     let string = ":".to_owned() + name;
     let positions = generate_positions(&string, query);
 
-    Some(CommandInterceptResult {
+    CommandInterceptResult {
         action,
         string,
         positions,
-    })
+    }
 }
 
 fn generate_positions(string: &str, query: &str) -> Vec<usize> {
@@ -378,11 +410,1095 @@ fn generate_positions(string: &str, query: &str) -> Vec<usize> {
     positions
 }
 
+/// `:/pattern` and `:?pattern` search like `/` and `?` when nothing follows the pattern.
+fn search_command(query: &str) -> Option<FindCommand> {
+    let delimiter = query.chars().next().filter(|c| matches!(c, '/' | '?'))?;
+    let (pattern, rest) = split_on_delimiter(&query[1..], delimiter);
+    if !rest.is_empty() {
+        return None;
+    }
+    Some(FindCommand {
+        query: vim_regex(&pattern),
+        backwards: delimiter == '?',
+    })
+}
+
+/// The maximum number of files that `:e` offers to complete a path to.
+const MAX_PATH_COMPLETIONS: usize = 20;
+
+fn ex_command_results(query: &str, cx: &AppContext) -> Vec<CommandInterceptResult> {
+    let Some(command) = ParsedCommand::parse(query) else {
+        return Vec::new();
+    };
+    let Some(name) = command_name(command.name) else {
+        return Vec::new();
+    };
+    if name.is_empty() && command.range.is_none() {
+        return Vec::new();
+    }
+
+    let mut commands = vec![query.to_string()];
+    if name == "edit" {
+        let bang = if command.bang { "!" } else { "" };
+        commands.extend(
+            path_completions(command.args, cx)
+                .into_iter()
+                .map(|path| format!("{}{bang} {path}", command.name)),
+        );
+    }
+    commands
+        .into_iter()
+        .map(|command| {
+            let action = ExCommand {
+                command: command.clone(),
+            }
+            .boxed_clone();
+            intercept_result(&command, query, action)
+        })
+        .collect()
+}
+
+/// Completes the path given to `:e` with the files in the directory it names, which is
+/// relative to the roots of the workspace.
+fn path_completions(path: &str, cx: &AppContext) -> Vec<String> {
+    let Some(workspace) = cx
+        .global::<Vim>()
+        .active_editor
+        .as_ref()
+        .and_then(|editor| editor.upgrade())
+        .and_then(|editor| editor.read(cx).workspace())
+    else {
+        return Vec::new();
+    };
+    let (directory, file_prefix) = match path.rfind('/') {
+        Some(ix) => path.split_at(ix + 1),
+        None => ("", path),
+    };
+
+    let mut completions = Vec::new();
+    for worktree in workspace.read(cx).visible_worktrees(cx) {
+        let worktree = worktree.read(cx);
+        for entry in worktree.child_entries(Path::new(directory)) {
+            let Some(file_name) = entry.path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if !entry.is_file() || !file_name.starts_with(file_prefix) || file_name == file_prefix {
+                continue;
+            }
+            completions.push(format!("{directory}{file_name}"));
+            if completions.len() == MAX_PATH_COMPLETIONS {
+                return completions;
+            }
+        }
+    }
+    completions
+}
+
+/// The ex commands that take a range or arguments, as the shortest abbreviation that vim
+/// accepts and the full name.
+const EX_COMMANDS: &[(&str, &str)] = &[
+    ("s", "substitute"),
+    ("g", "global"),
+    ("v", "vglobal"),
+    ("norm", "normal"),
+    ("sor", "sort"),
+    ("d", "delete"),
+    ("y", "yank"),
+    ("m", "move"),
+    ("t", "t"),
+    ("co", "copy"),
+    ("j", "join"),
+    ("e", "edit"),
+    ("se", "set"),
+];
+
+/// Returns the full name of an ex command from any abbreviation of it. A command with
+/// only a range, like `:5` or `:'a`, has an empty name.
+fn command_name(name: &str) -> Option<&'static str> {
+    if name.is_empty() {
+        return Some("");
+    }
+    if name.starts_with('>') {
+        return Some(">");
+    }
+    if name.starts_with('<') {
+        return Some("<");
+    }
+    EX_COMMANDS
+        .iter()
+        .find(|(short, full)| name.starts_with(short) && full.starts_with(name))
+        .map(|(_, full)| *full)
+}
+
+/// A line in an ex command's range, before the offset is applied.
+#[derive(Debug, Clone, PartialEq)]
+enum Address {
+    /// A line number, counted from 1.
+    Line(u32),
+    /// `.`
+    CurrentLine,
+    /// `$`
+    LastLine,
+    /// `'a`, including the `'<` and `'>` marks of the last visual selection.
+    Mark(char),
+    /// `/pattern/` searches forward from the current line, and `?pattern?` backward.
+    Pattern { query: String, backwards: bool },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Position {
+    address: Address,
+    offset: i32,
+}
+
+impl Position {
+    fn current_line() -> Self {
+        Position {
+            address: Address::CurrentLine,
+            offset: 0,
+        }
+    }
+
+    /// Parses the position at the start of `text`, like `'a+2`, advancing past it. An
+    /// offset on its own, like `+2`, is relative to the current line.
+    fn parse(text: &mut &str) -> Option<Self> {
+        let mut chars = text.chars();
+        let address = match chars.next()? {
+            '.' => {
+                *text = chars.as_str();
+                Address::CurrentLine
+            }
+            '$' => {
+                *text = chars.as_str();
+                Address::LastLine
+            }
+            '\'' => {
+                let mark = chars.next()?;
+                *text = chars.as_str();
+                Address::Mark(mark)
+            }
+            delimiter @ ('/' | '?') => {
+                let (query, rest) = split_on_delimiter(chars.as_str(), delimiter);
+                *text = rest;
+                Address::Pattern {
+                    query,
+                    backwards: delimiter == '?',
+                }
+            }
+            c if c.is_ascii_digit() => {
+                let len = text
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(text.len());
+                let line = text[..len].parse().ok()?;
+                *text = &text[len..];
+                Address::Line(line)
+            }
+            '+' | '-' => Address::CurrentLine,
+            _ => return None,
+        };
+
+        let mut offset = 0i32;
+        loop {
+            let mut chars = text.chars();
+            let sign = match chars.next() {
+                Some('+') => 1,
+                Some('-') => -1,
+                _ => break,
+            };
+            let rest = chars.as_str();
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let amount: i32 = if len == 0 {
+                1
+            } else {
+                rest[..len].parse().ok()?
+            };
+            offset = offset.checked_add(sign * amount)?;
+            *text = &rest[len..];
+        }
+
+        Some(Position { address, offset })
+    }
+
+    /// Returns the line number that the position refers to, counted from 1, where 0 is
+    /// the line before the first.
+    fn line_number(
+        &self,
+        vim: &mut Vim,
+        editor: &Editor,
+        current_row: u32,
+        cx: &AppContext,
+    ) -> Result<u32> {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let last_row = last_row(&snapshot);
+        let line = match &self.address {
+            Address::Line(line) => *line,
+            Address::CurrentLine => current_row + 1,
+            Address::LastLine => last_row + 1,
+            Address::Mark(mark) => {
+                let anchor = mark_anchor(vim, editor, *mark, cx)
+                    .ok_or_else(|| anyhow!("E20: Mark not set"))?;
+                anchor.to_point(&snapshot).row + 1
+            }
+            Address::Pattern { query, backwards } => {
+                let regex = build_regex(vim, &vim_regex(query), None)?;
+                let rows: Vec<u32> = if *backwards {
+                    (0..current_row)
+                        .rev()
+                        .chain((current_row..=last_row).rev())
+                        .collect()
+                } else {
+                    (current_row + 1..=last_row)
+                        .chain(0..=current_row.min(last_row))
+                        .collect()
+                };
+                let row = rows
+                    .into_iter()
+                    .find(|row| regex.is_match(&line_text(&snapshot, *row)))
+                    .ok_or_else(|| anyhow!("E486: Pattern not found: {query}"))?;
+                row + 1
+            }
+        };
+
+        let line = i64::from(line) + i64::from(self.offset);
+        if line < 0 || line > i64::from(last_row) + 1 {
+            bail!("E16: Invalid range");
+        }
+        Ok(line as u32)
+    }
+}
+
+/// The lines that an ex command runs on, like `.,+5` or `'<,'>`.
+#[derive(Debug, Clone, PartialEq)]
+struct CommandRange {
+    start: Position,
+    end: Option<Position>,
+    /// Whether the end is relative to the start rather than the current line, for `;`.
+    end_from_start: bool,
+}
+
+impl CommandRange {
+    /// Returns the rows in the range, swapping its ends if they're backwards.
+    fn rows(
+        &self,
+        vim: &mut Vim,
+        editor: &Editor,
+        current_row: u32,
+        cx: &AppContext,
+    ) -> Result<RangeInclusive<u32>> {
+        let start = self.start.line_number(vim, editor, current_row, cx)?;
+        let end = match &self.end {
+            Some(end) => {
+                let current_row = if self.end_from_start {
+                    start.saturating_sub(1)
+                } else {
+                    current_row
+                };
+                end.line_number(vim, editor, current_row, cx)?
+            }
+            None => start,
+        };
+        let (start, end) = (start.min(end), start.max(end));
+        Ok(start.saturating_sub(1)..=end.saturating_sub(1))
+    }
+}
+
+/// An ex command split into its range, name and arguments.
+#[derive(Debug, Clone, PartialEq)]
+struct ParsedCommand<'a> {
+    range: Option<CommandRange>,
+    name: &'a str,
+    bang: bool,
+    args: &'a str,
+}
+
+impl<'a> ParsedCommand<'a> {
+    fn parse(query: &'a str) -> Option<Self> {
+        let mut text = query.trim_start();
+        let range = if let Some(rest) = text.strip_prefix('%') {
+            text = rest;
+            Some(CommandRange {
+                start: Position {
+                    address: Address::Line(1),
+                    offset: 0,
+                },
+                end: Some(Position {
+                    address: Address::LastLine,
+                    offset: 0,
+                }),
+                end_from_start: false,
+            })
+        } else {
+            let start = Position::parse(&mut text);
+            match text.chars().next() {
+                Some(separator @ (',' | ';')) => {
+                    text = &text[1..];
+                    let end = Position::parse(&mut text).unwrap_or_else(Position::current_line);
+                    Some(CommandRange {
+                        start: start.unwrap_or_else(Position::current_line),
+                        end: Some(end),
+                        end_from_start: separator == ';',
+                    })
+                }
+                _ => start.map(|start| CommandRange {
+                    start,
+                    end: None,
+                    end_from_start: false,
+                }),
+            }
+        };
+
+        let text = text.trim_start();
+        let name_len = match text.chars().next() {
+            Some(c @ ('>' | '<')) => text.find(|other: char| other != c).unwrap_or(text.len()),
+            _ => text
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(text.len()),
+        };
+        let (name, rest) = text.split_at(name_len);
+        let (bang, rest) = match rest.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+
+        Some(ParsedCommand {
+            range,
+            name,
+            bang,
+            args: rest.trim(),
+        })
+    }
+}
+
+fn run_ex_command(
+    workspace: &mut Workspace,
+    query: &str,
+    cx: &mut ViewContext<Workspace>,
+) -> Result<()> {
+    let command = ParsedCommand::parse(query)
+        .ok_or_else(|| anyhow!("E492: Not an editor command: {query}"))?;
+    let name = command_name(command.name)
+        .ok_or_else(|| anyhow!("E492: Not an editor command: {query}"))?;
+    match name {
+        "edit" => return edit(workspace, &command, cx),
+        "set" => return set_options(command.args, cx),
+        "substitute"
+            if Replacement::parse(command.args)
+                .is_some_and(|replacement| replacement.count_only) =>
+        {
+            return count_matches(workspace, &command, cx);
+        }
+        "substitute"
+            if Replacement::parse(command.args)
+                .is_some_and(|replacement| !replacement.should_replace_all) =>
+        {
+            return confirm_substitutions(&command, cx);
+        }
+        "" => Vim::update(cx, |vim, cx| vim.record_jump(cx)),
+        _ => {}
+    }
+
+    let keys = Vim::update(cx, |vim, cx| {
+        if vim.state().mode != Mode::Normal {
+            vim.switch_mode(Mode::Normal, false, cx);
+        }
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let current_row = editor.selections.newest::<Point>(cx).head().row;
+            match name {
+                "global" | "vglobal" => global(
+                    vim,
+                    editor,
+                    &command,
+                    name == "vglobal" || command.bang,
+                    current_row,
+                    cx,
+                ),
+                "normal" => normal(vim, editor, &command, current_row, cx).map(Some),
+                _ => run_line_command(vim, editor, &command, name, current_row, cx).map(|_| None),
+            }
+        })
+    })
+    .transpose()?
+    .flatten();
+
+    if let Some(keys) = keys {
+        let mut actions: Vec<ReplayableAction> = parse_keystrokes(&keys)
+            .into_iter()
+            .map(ReplayableAction::Keystroke)
+            .collect();
+        actions.push(ReplayableAction::Action(EndNormalCommand.boxed_clone()));
+        replay(actions, cx);
+    }
+    Ok(())
+}
+
+/// Runs a command that works on a range of lines, like `:d` or `:s`, with `current_row`
+/// as the current line.
+/// Reports the number of matches of `:s` with the `n` flag, without replacing them.
+fn count_matches(
+    workspace: &mut Workspace,
+    command: &ParsedCommand,
+    cx: &mut ViewContext<Workspace>,
+) -> Result<()> {
+    let counts = Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let rows = substitution_rows(vim, editor, command, cx)?;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let (_, edits) = substitution_edits(vim, &snapshot, rows, command.args)?;
+            let mut rows: Vec<u32> = edits.iter().map(|(range, _)| range.start.row).collect();
+            rows.dedup();
+            anyhow::Ok((edits.len(), rows.len()))
+        })
+    })
+    .transpose()?;
+    let Some((matches, lines)) = counts else {
+        return Ok(());
+    };
+
+    let matches = if matches == 1 {
+        "1 match".to_string()
+    } else {
+        format!("{matches} matches")
+    };
+    let lines = if lines == 1 {
+        "1 line".to_string()
+    } else {
+        format!("{lines} lines")
+    };
+    workspace.show_toast(
+        Toast::new(SUBSTITUTE_COUNT_TOAST_ID, format!("{matches} on {lines}")),
+        cx,
+    );
+    Ok(())
+}
+
+/// Runs `:s` with the `c` flag in the search bar, which confirms each replacement. The
+/// search bar searches the whole file, so other ranges are refused.
+fn confirm_substitutions(command: &ParsedCommand, cx: &mut ViewContext<Workspace>) -> Result<()> {
+    let whole_file = Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let rows = substitution_rows(vim, editor, command, cx)?;
+            let last_row = last_row(&editor.buffer().read(cx).snapshot(cx));
+            anyhow::Ok(rows == (0..=last_row))
+        })
+    })
+    .transpose()?;
+    match whole_file {
+        Some(true) => {}
+        Some(false) => bail!("The c flag of :s is only supported on the whole file, as in :%s"),
+        None => return Ok(()),
+    }
+
+    cx.dispatch_action(
+        ReplaceCommand {
+            query: format!("%s{}", command.args),
+        }
+        .boxed_clone(),
+    );
+    Ok(())
+}
+
+fn substitution_rows(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    command: &ParsedCommand,
+    cx: &mut ViewContext<Editor>,
+) -> Result<RangeInclusive<u32>> {
+    let current_row = editor.selections.newest::<Point>(cx).head().row;
+    match &command.range {
+        Some(range) => range.rows(vim, editor, current_row, cx),
+        None => Ok(current_row..=current_row),
+    }
+}
+
+fn run_line_command(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    command: &ParsedCommand,
+    name: &str,
+    current_row: u32,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let rows = match &command.range {
+        Some(range) => range.rows(vim, editor, current_row, cx)?,
+        None if name == "sort" => 0..=last_row(&editor.buffer().read(cx).snapshot(cx)),
+        None => current_row..=current_row,
+    };
+
+    match name {
+        "" => move_to_row(editor, *rows.end(), cx),
+        "substitute" => substitute(vim, editor, rows, command.args, cx)?,
+        "delete" | "yank" => {
+            let (register, rows) = register_and_count(command.args, rows, editor, cx)?;
+            vim.update_state(|state| state.selected_register = register);
+            if name == "delete" {
+                delete_lines(vim, editor, rows, cx);
+            } else {
+                yank_lines(vim, editor, rows, cx);
+            }
+        }
+        "move" | "t" | "copy" => {
+            let mut args = command.args;
+            let target = Position::parse(&mut args)
+                .filter(|_| args.trim().is_empty())
+                .ok_or_else(|| anyhow!("E14: Invalid address"))?
+                .line_number(vim, editor, current_row, cx)?;
+            move_lines(editor, rows, target, name != "move", cx)?;
+        }
+        "join" => {
+            let (start, end) = (*rows.start(), *rows.end());
+            editor.change_selections(None, cx, |s| {
+                s.select_ranges([Point::new(start, 0)..Point::new(end, 0)])
+            });
+            editor.join_lines(&editor::actions::JoinLines, cx);
+        }
+        ">" | "<" => shift_lines(editor, rows, command.name.len(), name == "<", cx),
+        "sort" => sort_lines(editor, rows, command.args, command.bang, cx)?,
+        _ => bail!("E492: Not an editor command: {}", command.name),
+    }
+    Ok(())
+}
+
+/// Parses the optional register and count given to `:d` and `:y`. With a count, the
+/// command works on that many lines from the end of the range.
+fn register_and_count(
+    args: &str,
+    rows: RangeInclusive<u32>,
+    editor: &Editor,
+    cx: &AppContext,
+) -> Result<(Option<char>, RangeInclusive<u32>)> {
+    let mut args = args;
+    let register = args.chars().next().filter(|c| !c.is_ascii_digit());
+    if let Some(register) = register {
+        args = args[register.len_utf8()..].trim_start();
+    }
+    if args.is_empty() {
+        return Ok((register, rows));
+    }
+    let count: u32 = args
+        .parse()
+        .map_err(|_| anyhow!("E488: Trailing characters: {args}"))?;
+    let last_row = last_row(&editor.buffer().read(cx).snapshot(cx));
+    let start = *rows.end();
+    let end = (start + count.saturating_sub(1)).min(last_row);
+    Ok((register, start..=end))
+}
+
+/// Runs `:g/pattern/command` on each line that matches the pattern, or each line that
+/// doesn't for `:v`. When the command is `:normal`, a cursor is put on each of the lines
+/// and the keys to replay are returned.
+fn global(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    command: &ParsedCommand,
+    invert: bool,
+    current_row: u32,
+    cx: &mut ViewContext<Editor>,
+) -> Result<Option<String>> {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let rows = match &command.range {
+        Some(range) => range.rows(vim, editor, current_row, cx)?,
+        None => 0..=last_row(&snapshot),
+    };
+    let mut chars = command.args.chars();
+    let delimiter = chars
+        .next()
+        .ok_or_else(|| anyhow!("E35: No previous regular expression"))?;
+    if delimiter.is_alphanumeric() || matches!(delimiter, '\\' | '"' | '|') {
+        bail!("E146: Regular expressions can't be delimited by letters");
+    }
+    let (pattern, sub_command) = split_on_delimiter(chars.as_str(), delimiter);
+    let regex = build_regex(vim, &vim_regex(&pattern), None)?;
+    let matching_rows: Vec<u32> = rows
+        .filter(|row| regex.is_match(&line_text(&snapshot, *row)) != invert)
+        .collect();
+    let Some(last_match) = matching_rows.last().copied() else {
+        bail!("E486: Pattern not found: {pattern}");
+    };
+
+    let sub_command = ParsedCommand::parse(sub_command)
+        .ok_or_else(|| anyhow!("E492: Not an editor command: {sub_command}"))?;
+    let name = command_name(sub_command.name)
+        .ok_or_else(|| anyhow!("E492: Not an editor command: {}", sub_command.name))?;
+    match name {
+        "" if sub_command.range.is_none() => {
+            move_to_row(editor, last_match, cx);
+            return Ok(None);
+        }
+        "normal" => {
+            select_rows(editor, &matching_rows, cx);
+            return Ok(Some(sub_command.args.to_string()));
+        }
+        "global" | "vglobal" => bail!("E147: Cannot do :global recursive"),
+        "edit" | "set" => bail!("E492: Not an editor command: {}", sub_command.name),
+        _ => {}
+    }
+
+    // anchors keep track of the lines as the command changes the ones before them.
+    let anchors: Vec<Anchor> = matching_rows
+        .iter()
+        .map(|row| snapshot.anchor_before(Point::new(*row, 0)))
+        .collect();
+    let mut first_error = None;
+    let mut succeeded = false;
+    editor.transact(cx, |editor, cx| {
+        for anchor in anchors {
+            let row = anchor.to_point(&editor.buffer().read(cx).snapshot(cx)).row;
+            match run_line_command(vim, editor, &sub_command, name, row, cx) {
+                Ok(()) => succeeded = true,
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+    });
+    // like vim, a command that fails on some of the lines, like a substitution that
+    // doesn't match, only fails when it fails on all of them.
+    match first_error {
+        Some(error) if !succeeded => Err(error),
+        _ => Ok(None),
+    }
+}
+
+/// Puts a cursor at the start of each line in the range of `:normal`, and returns the
+/// keys to replay.
+fn normal(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    command: &ParsedCommand,
+    current_row: u32,
+    cx: &mut ViewContext<Editor>,
+) -> Result<String> {
+    if let Some(range) = &command.range {
+        let rows: Vec<u32> = range.rows(vim, editor, current_row, cx)?.collect();
+        select_rows(editor, &rows, cx);
+    }
+    Ok(command.args.to_string())
+}
+
+fn select_rows(editor: &mut Editor, rows: &[u32], cx: &mut ViewContext<Editor>) {
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_ranges(rows.iter().map(|row| {
+            let point = Point::new(*row, 0);
+            point..point
+        }))
+    });
+}
+
+fn substitute(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    args: &str,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let (replacement, edits) = substitution_edits(vim, &snapshot, rows, args)?;
+    let Some((last_range, _)) = edits.last().filter(|_| !replacement.count_only) else {
+        return Ok(());
+    };
+
+    let last_edit = snapshot.anchor_after(last_range.end);
+    editor.transact(cx, |editor, cx| editor.edit(edits, cx));
+    let row = last_edit
+        .to_point(&editor.buffer().read(cx).snapshot(cx))
+        .row;
+    move_to_row(editor, row, cx);
+    Ok(())
+}
+
+/// Parses the arguments of `:s` and returns the replacements it makes in `rows`, failing
+/// when nothing matches.
+fn substitution_edits(
+    vim: &mut Vim,
+    snapshot: &MultiBufferSnapshot,
+    rows: RangeInclusive<u32>,
+    args: &str,
+) -> Result<(Replacement, Vec<(Range<Point>, String)>)> {
+    if args.is_empty() {
+        bail!("E35: No previous regular expression");
+    }
+    let replacement = Replacement::parse(args)
+        .ok_or_else(|| anyhow!("E146: Regular expressions can't be delimited by letters"))?;
+    let regex = build_regex(vim, &replacement.search, replacement.is_case_sensitive)?;
+
+    let mut edits = Vec::new();
+    for row in rows {
+        let line = line_text(&snapshot, row);
+        for captures in regex.captures_iter(&line) {
+            let Some(found) = captures.get(0) else {
+                continue;
+            };
+            let mut new_text = String::new();
+            captures.expand(&replacement.replacement, &mut new_text);
+            edits.push((
+                Point::new(row, found.start() as u32)..Point::new(row, found.end() as u32),
+                new_text,
+            ));
+            if !replacement.replace_every_match {
+                break;
+            }
+        }
+    }
+    if edits.is_empty() {
+        bail!("E486: Pattern not found: {}", replacement.search);
+    }
+    Ok((replacement, edits))
+}
+
+fn delete_lines(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let range = line_deletion_range(&snapshot, &rows);
+    editor.transact(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| s.select_ranges([range.clone()]));
+        copy_selections_content(vim, editor, true, cx);
+        editor.edit([(range, "")], cx);
+    });
+    move_to_row(editor, *rows.start(), cx);
+}
+
+fn yank_lines(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let range = line_deletion_range(&snapshot, &rows);
+    let cursor = editor.selections.newest_anchor().head();
+    editor.change_selections(None, cx, |s| s.select_ranges([range]));
+    yank_selections_content(vim, editor, true, cx);
+    editor.change_selections(None, cx, |s| s.select_anchor_ranges([cursor..cursor]));
+}
+
+/// Moves, or copies, the lines in `rows` below the line numbered `target`, where 0 puts
+/// them above the first line.
+fn move_lines(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    target: u32,
+    copy: bool,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let (start, end) = (*rows.start(), *rows.end());
+    if !copy && target > start && target <= end {
+        bail!("E134: Cannot move a range of lines into itself");
+    }
+    if !copy && (target == start || target == end + 1) {
+        move_to_row(editor, end, cx);
+        return Ok(());
+    }
+
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let max_row = snapshot.max_point().row;
+    let text: String = snapshot
+        .text_for_range(Point::new(start, 0)..Point::new(end, snapshot.line_len(end)))
+        .collect();
+    let mut edits = if target <= max_row {
+        let point = Point::new(target, 0);
+        vec![(point..point, format!("{text}\n"))]
+    } else {
+        let point = snapshot.max_point();
+        vec![(point..point, format!("\n{text}"))]
+    };
+    if !copy {
+        edits.push((line_deletion_range(&snapshot, &rows), String::new()));
+    }
+    editor.transact(cx, |editor, cx| editor.edit(edits, cx));
+
+    // the cursor goes to the last of the moved lines.
+    let last_row = if !copy && target > end {
+        target - 1
+    } else {
+        target + end - start
+    };
+    move_to_row(editor, last_row, cx);
+    Ok(())
+}
+
+fn shift_lines(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    times: usize,
+    outdent: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    let (start, end) = (*rows.start(), *rows.end());
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    editor.transact(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(start, 0)..Point::new(end, snapshot.line_len(end))])
+        });
+        for _ in 0..times {
+            if outdent {
+                editor.outdent(&Outdent, cx);
+            } else {
+                editor.indent(&Indent, cx);
+            }
+        }
+    });
+    move_to_row(editor, end, cx);
+}
+
+/// Sorts the lines in `rows`, comparing them without case for the `i` flag, by the first
+/// number in them for `n`, and dropping duplicates for `u`.
+fn sort_lines(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    flags: &str,
+    reverse: bool,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let (mut ignore_case, mut numeric, mut unique) = (false, false, false);
+    for flag in flags.chars().filter(|c| !c.is_whitespace()) {
+        match flag {
+            'i' => ignore_case = true,
+            'n' => numeric = true,
+            'u' => unique = true,
+            _ => bail!("E474: Invalid argument: {flags}"),
+        }
+    }
+
+    let (start, end) = (*rows.start(), *rows.end());
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let range = Point::new(start, 0)..Point::new(end, snapshot.line_len(end));
+    let text: String = snapshot.text_for_range(range.clone()).collect();
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    if numeric {
+        lines.sort_by_key(|line| first_number(line));
+    } else if ignore_case {
+        lines.sort_by_key(|line| line.to_lowercase());
+    } else {
+        lines.sort();
+    }
+    if reverse {
+        lines.reverse();
+    }
+    if unique {
+        lines.dedup_by(|a, b| {
+            if numeric {
+                first_number(a) == first_number(b)
+            } else if ignore_case {
+                a.to_lowercase() == b.to_lowercase()
+            } else {
+                a == b
+            }
+        });
+    }
+
+    editor.transact(cx, |editor, cx| {
+        editor.edit([(range, lines.join("\n"))], cx)
+    });
+    move_to_row(editor, start, cx);
+    Ok(())
+}
+
+/// Returns the first number in a line, which `:sort n` sorts by. Lines without one sort
+/// before the others.
+fn first_number(line: &str) -> Option<i64> {
+    let start = line.find(|c: char| c.is_ascii_digit())?;
+    let len = line[start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(line.len() - start);
+    let number: i64 = line[start..start + len].parse().ok()?;
+    if line[..start].ends_with('-') {
+        Some(-number)
+    } else {
+        Some(number)
+    }
+}
+
+/// Opens the file given to `:e`, relative to the root of the active file's worktree, or
+/// reloads the active file when no path is given.
+fn edit(
+    workspace: &mut Workspace,
+    command: &ParsedCommand,
+    cx: &mut ViewContext<Workspace>,
+) -> Result<()> {
+    if command.args.is_empty() {
+        let Some(item) = workspace.active_item(cx) else {
+            return Ok(());
+        };
+        if item.is_dirty(cx) && !command.bang {
+            bail!("E37: No write since last change (add ! to override)");
+        }
+        item.reload(workspace.project().clone(), cx)
+            .detach_and_log_err(cx);
+        return Ok(());
+    }
+
+    let path = Path::new(command.args);
+    let abs_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        let project = workspace.project().read(cx);
+        let worktree = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+            .and_then(|project_path| project.worktree_for_id(project_path.worktree_id, cx))
+            .or_else(|| workspace.visible_worktrees(cx).next())
+            .ok_or_else(|| anyhow!("E32: No file name"))?;
+        worktree.read(cx).abs_path().join(path)
+    };
+    workspace
+        .open_abs_path(abs_path, true, cx)
+        .detach_and_log_err(cx);
+    Ok(())
+}
+
+/// The options that `:set` understands, as the abbreviation and the full name.
+const OPTIONS: &[(&str, &str)] = &[
+    ("nu", "number"),
+    ("rnu", "relativenumber"),
+    ("wrap", "wrap"),
+    ("ic", "ignorecase"),
+    ("scs", "smartcase"),
+];
+
+fn option_name(name: &str) -> Option<&'static str> {
+    OPTIONS
+        .iter()
+        .find(|(short, full)| name == *short || name == *full)
+        .map(|(_, full)| *full)
+}
+
+/// Sets the options given to `:set`, like `nu`, `nonu`, `invnu` or `nu!`.
+fn set_options(args: &str, cx: &mut WindowContext) -> Result<()> {
+    for option in args.split_whitespace() {
+        let (mut name, mut toggle) = match option.strip_suffix('!') {
+            Some(name) => (name, true),
+            None => (option, false),
+        };
+        let mut value = true;
+        if option_name(name).is_none() {
+            if let Some(rest) = name.strip_prefix("no") {
+                name = rest;
+                value = false;
+            } else if let Some(rest) = name.strip_prefix("inv") {
+                name = rest;
+                toggle = true;
+            }
+        }
+        let name = option_name(name).ok_or_else(|| anyhow!("E518: Unknown option: {option}"))?;
+        let new_value = |current: bool| if toggle { !current } else { value };
+
+        Vim::update(cx, |vim, cx| match name {
+            "ignorecase" => {
+                vim.workspace_state.ignore_case = new_value(vim.workspace_state.ignore_case)
+            }
+            "smartcase" => {
+                vim.workspace_state.smart_case = new_value(vim.workspace_state.smart_case)
+            }
+            _ => {
+                vim.update_active_editor(cx, |_, editor, cx| match name {
+                    "number" => {
+                        let current = editor.snapshot(cx).show_line_numbers(cx);
+                        editor.set_show_line_numbers(new_value(current), cx);
+                    }
+                    "relativenumber" => {
+                        let current = editor.snapshot(cx).use_relative_line_numbers(cx);
+                        editor.set_relative_line_numbers(new_value(current), cx);
+                    }
+                    _ => {
+                        let current = !matches!(editor.soft_wrap_mode(cx), SoftWrap::None);
+                        let mode = if new_value(current) {
+                            language_settings::SoftWrap::EditorWidth
+                        } else {
+                            language_settings::SoftWrap::None
+                        };
+                        editor.set_soft_wrap_mode(mode, cx);
+                    }
+                });
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Compiles the regex of an ex command, where an empty one repeats the last pattern. It
+/// ignores case as the `ignorecase` and `smartcase` options say, unless a flag decides.
+fn build_regex(vim: &mut Vim, regex: &str, is_case_sensitive: Option<bool>) -> Result<Regex> {
+    let regex = if regex.is_empty() {
+        vim.workspace_state
+            .last_pattern
+            .clone()
+            .ok_or_else(|| anyhow!("E35: No previous regular expression"))?
+    } else {
+        regex.to_string()
+    };
+    let state = &vim.workspace_state;
+    let is_case_sensitive = is_case_sensitive
+        .unwrap_or_else(|| !state.ignore_case || state.smart_case && has_uppercase(&regex));
+    let compiled = RegexBuilder::new(&regex)
+        .case_insensitive(!is_case_sensitive)
+        .build()?;
+    vim.workspace_state.last_pattern = Some(regex);
+    Ok(compiled)
+}
+
+/// Whether a regex has an uppercase letter, leaving out escapes like `\W`.
+fn has_uppercase(regex: &str) -> bool {
+    let mut escaped = false;
+    regex.chars().any(|c| {
+        let is_uppercase = !escaped && c.is_uppercase();
+        escaped = !escaped && c == '\\';
+        is_uppercase
+    })
+}
+
+/// The last line of the buffer, leaving out the empty line after a final newline.
+fn last_row(snapshot: &MultiBufferSnapshot) -> u32 {
+    let max_point = snapshot.max_point();
+    if max_point.column == 0 && max_point.row > 0 {
+        max_point.row - 1
+    } else {
+        max_point.row
+    }
+}
+
+fn line_text(snapshot: &MultiBufferSnapshot, row: u32) -> String {
+    snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+        .collect()
+}
+
+/// Returns the range that removes the lines in `rows` along with a newline.
+fn line_deletion_range(snapshot: &MultiBufferSnapshot, rows: &RangeInclusive<u32>) -> Range<Point> {
+    let (start, end) = (*rows.start(), *rows.end());
+    let max_point = snapshot.max_point();
+    if end < max_point.row {
+        Point::new(start, 0)..Point::new(end + 1, 0)
+    } else if start > 0 {
+        Point::new(start - 1, snapshot.line_len(start - 1))..max_point
+    } else {
+        Point::new(0, 0)..max_point
+    }
+}
+
+/// Moves the cursor to the first non-blank character of `row`.
+fn move_to_row(editor: &mut Editor, row: u32, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let row = row.min(snapshot.max_point().row);
+    let point = Point::new(row, snapshot.indent_size_for_line(row).len);
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_ranges([point..point])
+    });
+}
+
 #[cfg(test)]
 mod test {
     use std::path::Path;
 
-    use crate::test::{NeovimBackedTestContext, VimTestContext};
+    use crate::{
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+    };
     use gpui::TestAppContext;
     use indoc::indoc;
 
@@ -521,4 +1637,203 @@ mod test {
         cx.simulate_keystrokes([":", "q", "a", "enter"]);
         cx.workspace(|workspace, cx| assert_eq!(workspace.items(cx).count(), 0));
     }
+
+    #[gpui::test]
+    async fn test_command_ranges(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            a
+            ˇb
+            c
+            d
+            e"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes([":", ".", ",", "+", "d", "enter"]);
+        cx.assert_state(
+            indoc! {"
+            a
+            ˇd
+            e"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes([":", "$", "d", "enter"]);
+        cx.assert_state(
+            indoc! {"
+            a
+            ˇd"},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            aa
+            ˇaa
+            aa"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes([
+            ":", ".", ",", "$", "s", "/", "a", "/", "b", "/", "g", "enter",
+        ]);
+        cx.assert_state(
+            indoc! {"
+            aa
+            bb
+            ˇbb"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_command_visual_range(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            a
+            a"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["shift-v", "j", ":", "s", "/", "a", "/", "b", "enter"]);
+        cx.assert_state(
+            indoc! {"
+            b
+            ˇb
+            a"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_command_global(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa1
+            b
+            a2
+            c"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes([":", "g", "/", "a", "/", "d", "enter"]);
+        cx.assert_state(
+            indoc! {"
+            b
+            ˇc"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes([":", "v", "/", "b", "/", "d", "enter"]);
+        cx.assert_state("ˇb", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            b
+            c"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes([":", "%", "n", "o", "r", "m", "space", "i", "x", "enter"]);
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            ˇxa
+            ˇxb
+            ˇxc"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_command_substitute_count_and_confirm(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        // `n` only counts the matches.
+        cx.set_state(
+            indoc! {"
+            ˇaa
+            b
+            aa"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes([":", "%", "s", "/", "a", "/", "b", "/", "g", "n", "enter"]);
+        cx.assert_state(
+            indoc! {"
+            ˇaa
+            b
+            aa"},
+            Mode::Normal,
+        );
+
+        // `c` can't be confirmed on part of the file, so nothing is replaced.
+        cx.simulate_keystrokes([":", "3", "s", "/", "a", "/", "b", "/", "c", "enter"]);
+        cx.assert_state(
+            indoc! {"
+            ˇaa
+            b
+            aa"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_command_sort_move_copy(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            c
+            a
+            ˇb"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes([":", "s", "o", "r", "enter"]);
+        cx.assert_state(
+            indoc! {"
+            ˇa
+            b
+            c"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes([":", "m", "$", "enter"]);
+        cx.assert_state(
+            indoc! {"
+            b
+            c
+            ˇa"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes([":", "1", "t", ".", "enter"]);
+        cx.assert_state(
+            indoc! {"
+            b
+            c
+            a
+            ˇb"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_command_set(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇA", Mode::Normal);
+        cx.simulate_keystrokes([":", "s", "e", "t", "space", "i", "c", "enter"]);
+        cx.simulate_keystrokes([":", "s", "/", "a", "/", "b", "enter"]);
+        cx.assert_state("ˇb", Mode::Normal);
+
+        cx.simulate_keystrokes([":", "s", "e", "t", "space", "n", "o", "n", "u", "enter"]);
+        cx.update_editor(|editor, cx| assert!(!editor.snapshot(cx).show_line_numbers(cx)));
+        cx.simulate_keystrokes([":", "s", "e", "t", "space", "n", "u", "!", "enter"]);
+        cx.update_editor(|editor, cx| assert!(editor.snapshot(cx).show_line_numbers(cx)));
+    }
 }
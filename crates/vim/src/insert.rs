@@ -15,7 +15,13 @@ fn normal_before(_: &mut Workspace, action: &NormalBefore, cx: &mut ViewContext<
         let count = vim.take_count(cx).unwrap_or(1);
         vim.stop_recording_immediately(action.boxed_clone());
         if count <= 1 || vim.workspace_state.replaying {
-            vim.update_active_editor(cx, |_, editor, cx| {
+            vim.update_active_editor(cx, |vim, editor, cx| {
+                // `^` marks where insert mode was left, before the cursor moves back.
+                let head = editor.selections.newest_anchor().head();
+                if let Some((_, marks)) = vim.buffer_marks(editor, cx) {
+                    marks.marks.insert('^', head.text_anchor);
+                }
+
                 editor.dismiss_menus_and_popups(cx);
                 editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
                     s.move_cursors_with(|map, mut cursor, _| {
@@ -3,10 +3,10 @@ use editor::{
     movement::{
         self, find_boundary, find_preceding_boundary_display_point, FindRange, TextLayoutDetails,
     },
-    Bias, DisplayPoint, ToOffset,
+    Anchor, Bias, DisplayPoint, ToOffset,
 };
 use gpui::{actions, impl_actions, px, ViewContext, WindowContext};
-use language::{char_kind, CharKind, Point, Selection, SelectionGoal};
+use language::{char_kind, CharKind, Point, Selection, SelectionGoal, TextObjectKind};
 use serde::Deserialize;
 use workspace::Workspace;
 
@@ -80,6 +80,16 @@ pub enum Motion {
     WindowTop,
     WindowMiddle,
     WindowBottom,
+    NextTextObjectStart {
+        kind: TextObjectKind,
+    },
+    PreviousTextObjectStart {
+        kind: TextObjectKind,
+    },
+    Jump {
+        anchor: Anchor,
+        line: bool,
+    },
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -182,6 +192,10 @@ actions!(
         WindowTop,
         WindowMiddle,
         WindowBottom,
+        NextFunctionStart,
+        PreviousFunctionStart,
+        NextClassStart,
+        PreviousClassStart,
     ]
 );
 
@@ -309,6 +323,38 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
             motion(Motion::PreviousWordEnd { ignore_punctuation }, cx)
         },
     );
+    workspace.register_action(|_: &mut Workspace, &NextFunctionStart, cx: _| {
+        motion(
+            Motion::NextTextObjectStart {
+                kind: TextObjectKind::Function,
+            },
+            cx,
+        )
+    });
+    workspace.register_action(|_: &mut Workspace, &PreviousFunctionStart, cx: _| {
+        motion(
+            Motion::PreviousTextObjectStart {
+                kind: TextObjectKind::Function,
+            },
+            cx,
+        )
+    });
+    workspace.register_action(|_: &mut Workspace, &NextClassStart, cx: _| {
+        motion(
+            Motion::NextTextObjectStart {
+                kind: TextObjectKind::Class,
+            },
+            cx,
+        )
+    });
+    workspace.register_action(|_: &mut Workspace, &PreviousClassStart, cx: _| {
+        motion(
+            Motion::PreviousTextObjectStart {
+                kind: TextObjectKind::Class,
+            },
+            cx,
+        )
+    });
 }
 
 pub(crate) fn motion(motion: Motion, cx: &mut WindowContext) {
@@ -320,6 +366,9 @@ pub(crate) fn motion(motion: Motion, cx: &mut WindowContext) {
 
     let count = Vim::update(cx, |vim, cx| vim.take_count(cx));
     let operator = Vim::read(cx).active_operator();
+    if operator.is_none() && motion.is_jump() {
+        Vim::update(cx, |vim, cx| vim.record_jump(cx));
+    }
     match Vim::read(cx).state().mode {
         Mode::Normal => normal_motion(motion, operator, count, cx),
         Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_motion(motion, count, cx),
@@ -348,6 +397,7 @@ impl Motion {
             | WindowMiddle
             | WindowBottom
             | EndOfParagraph => true,
+            Jump { line, .. } => *line,
             EndOfLine { .. }
             | NextWordEnd { .. }
             | Matching
@@ -365,7 +415,49 @@ impl Motion {
             | FirstNonWhitespace { .. }
             | FindBackward { .. }
             | RepeatFind { .. }
-            | RepeatFindReversed { .. } => false,
+            | RepeatFindReversed { .. }
+            | NextTextObjectStart { .. }
+            | PreviousTextObjectStart { .. } => false,
+        }
+    }
+
+    /// Whether the motion adds the position it moves from to the jump list.
+    pub fn is_jump(&self) -> bool {
+        use Motion::*;
+        match self {
+            StartOfDocument
+            | EndOfDocument
+            | StartOfParagraph
+            | EndOfParagraph
+            | Matching
+            | WindowTop
+            | WindowMiddle
+            | WindowBottom
+            | Jump { .. } => true,
+            Left
+            | Backspace
+            | Down { .. }
+            | Up { .. }
+            | Right
+            | Space
+            | NextWordStart { .. }
+            | NextWordEnd { .. }
+            | PreviousWordStart { .. }
+            | PreviousWordEnd { .. }
+            | FirstNonWhitespace { .. }
+            | CurrentLine
+            | StartOfLine { .. }
+            | EndOfLine { .. }
+            | FindForward { .. }
+            | FindBackward { .. }
+            | RepeatFind { .. }
+            | RepeatFindReversed { .. }
+            | NextLineStart
+            | StartOfLineDownward
+            | EndOfLineDownward
+            | GoToColumn
+            | NextTextObjectStart { .. }
+            | PreviousTextObjectStart { .. } => false,
         }
     }
 
@@ -399,7 +491,10 @@ impl Motion {
             | WindowMiddle
             | WindowBottom
             | PreviousWordEnd { .. }
-            | NextLineStart => false,
+            | NextLineStart
+            | NextTextObjectStart { .. }
+            | PreviousTextObjectStart { .. }
+            | Jump { .. } => false,
         }
     }
 
@@ -433,7 +528,10 @@ impl Motion {
             | NextWordStart { .. }
             | PreviousWordStart { .. }
             | FirstNonWhitespace { .. }
-            | FindBackward { .. } => false,
+            | FindBackward { .. }
+            | NextTextObjectStart { .. }
+            | PreviousTextObjectStart { .. }
+            | Jump { .. } => false,
             RepeatFind { last_find: motion } | RepeatFindReversed { last_find: motion } => {
                 motion.inclusive()
             }
@@ -569,6 +667,25 @@ impl Motion {
             WindowTop => window_top(map, point, &text_layout_details, times - 1),
             WindowMiddle => window_middle(map, point, &text_layout_details),
             WindowBottom => window_bottom(map, point, &text_layout_details, times - 1),
+            NextTextObjectStart { kind } => (
+                next_text_object_start(map, point, *kind, times),
+                SelectionGoal::None,
+            ),
+            PreviousTextObjectStart { kind } => (
+                previous_text_object_start(map, point, *kind, times),
+                SelectionGoal::None,
+            ),
+            Jump { anchor, line } => {
+                let new_point = anchor.to_display_point(map);
+                if *line {
+                    (
+                        first_non_whitespace(map, false, new_point),
+                        SelectionGoal::None,
+                    )
+                } else {
+                    (new_point, SelectionGoal::None)
+                }
+            }
         };
 
         (new_point != point || infallible).then_some((new_point, goal))
@@ -1250,12 +1367,125 @@ fn previous_word_end(
     movement::saturating_left(map, point.to_display_point(map))
 }
 
+/// Moves to the start of the `times`-th next text object of the given kind, as captured by
+/// the language's textobjects query, or to the last one if there are fewer.
+fn next_text_object_start(
+    map: &DisplaySnapshot,
+    point: DisplayPoint,
+    kind: TextObjectKind,
+    times: usize,
+) -> DisplayPoint {
+    let offset = point.to_offset(map, Bias::Left);
+    text_object_starts(map, offset, kind)
+        .into_iter()
+        .filter(|start| *start > offset)
+        .take(times)
+        .last()
+        .map_or(point, |start| start.to_display_point(map))
+}
+
+/// Moves to the start of the `times`-th previous text object of the given kind, or to the
+/// first one if there are fewer.
+fn previous_text_object_start(
+    map: &DisplaySnapshot,
+    point: DisplayPoint,
+    kind: TextObjectKind,
+    times: usize,
+) -> DisplayPoint {
+    let offset = point.to_offset(map, Bias::Left);
+    text_object_starts(map, offset, kind)
+        .into_iter()
+        .rev()
+        .filter(|start| *start < offset)
+        .take(times)
+        .last()
+        .map_or(point, |start| start.to_display_point(map))
+}
+
+/// Returns the starts of the text objects of the given kind in the excerpt containing
+/// `offset`, in order.
+fn text_object_starts(map: &DisplaySnapshot, offset: usize, kind: TextObjectKind) -> Vec<usize> {
+    // Text objects come from the syntax tree, so we operate at the buffer level and map back
+    let Some(excerpt) = map.buffer_snapshot.excerpt_containing(offset..offset) else {
+        return Vec::new();
+    };
+    let buffer = excerpt.buffer();
+    let Some(text_objects) = buffer.text_objects(0..buffer.len()) else {
+        return Vec::new();
+    };
+
+    let mut starts = text_objects
+        .into_iter()
+        .filter(|text_object| {
+            text_object.kind == kind
+                && excerpt.contains_buffer_range(text_object.around.start..text_object.around.start)
+        })
+        .map(|text_object| excerpt.map_offset_from_buffer(text_object.around.start))
+        .collect::<Vec<_>>();
+    starts.dedup();
+    starts
+}
+
 #[cfg(test)]
 mod test {
 
-    use crate::test::NeovimBackedTestContext;
+    use crate::{
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+    };
     use indoc::indoc;
 
+    #[gpui::test]
+    async fn test_next_previous_function_start(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                fn a() {}
+                ˇ
+                impl B {
+                    fn c() {}
+                }
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["]", "f"]);
+        cx.assert_state(
+            indoc! {"
+                fn a() {}
+
+                impl B {
+                    ˇfn c() {}
+                }
+            "},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(["[", "f"]);
+        cx.assert_state(
+            indoc! {"
+                ˇfn a() {}
+
+                impl B {
+                    fn c() {}
+                }
+            "},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(["]", "c"]);
+        cx.assert_state(
+            indoc! {"
+                fn a() {}
+
+                ˇimpl B {
+                    fn c() {}
+                }
+            "},
+            Mode::Normal,
+        );
+    }
+
     #[gpui::test]
     async fn test_start_end_of_paragraph(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
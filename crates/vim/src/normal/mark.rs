@@ -0,0 +1,685 @@
+use std::{path::PathBuf, sync::Arc};
+
+use editor::{display_map::ToDisplayPoint, scroll::Autoscroll, Anchor, Bias, Editor};
+use gpui::{actions, AppContext, Model, View, ViewContext, WindowContext};
+use language::{Buffer, Point, ToPoint};
+use workspace::{Workspace, WorkspaceId};
+
+use crate::{
+    motion::{self, Motion},
+    persistence::DB,
+    state::GlobalMark,
+    Vim,
+};
+
+actions!(vim, [PreviousChange, NextChange]);
+
+/// The number of changes remembered per buffer for `g;` and `g,`.
+const MAX_CHANGE_LIST_LEN: usize = 100;
+
+pub(crate) fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    workspace.register_action(|_: &mut Workspace, _: &PreviousChange, cx| {
+        Vim::update(cx, |vim, cx| {
+            let times = vim.take_count(cx).unwrap_or(1);
+            move_in_change_list(vim, -(times as isize), cx);
+        })
+    });
+    workspace.register_action(|_: &mut Workspace, _: &NextChange, cx| {
+        Vim::update(cx, |vim, cx| {
+            let times = vim.take_count(cx).unwrap_or(1);
+            move_in_change_list(vim, times as isize, cx);
+        })
+    });
+
+    load_global_marks(workspace, cx);
+}
+
+/// Restores the uppercase marks set in the workspace during previous sessions, and saves
+/// and forgets them when the workspace is released.
+fn load_global_marks(workspace: &Workspace, cx: &mut ViewContext<Workspace>) {
+    let workspace_id = workspace.database_id();
+    let workspace_entity = cx.view().entity_id();
+    cx.on_release(move |_, _, cx| {
+        if !cx.has_global::<Vim>() {
+            return;
+        }
+        let marks = cx.update_global(|vim: &mut Vim, _| {
+            vim.workspace_state.global_marks.remove(&workspace_entity)
+        });
+        for (name, mark) in marks.into_iter().flatten() {
+            if mark.anchor.is_some() {
+                let position = mark.position(cx);
+                save_global_mark(
+                    workspace_id,
+                    name,
+                    mark.abs_path.to_path_buf(),
+                    position,
+                    cx,
+                );
+            }
+        }
+    })
+    .detach();
+
+    let marks = cx
+        .background_executor()
+        .spawn(async move { DB.get_global_marks(workspace_id) });
+    cx.spawn(|workspace, mut cx| async move {
+        let marks = marks.await?;
+        workspace.update(&mut cx, |_, cx| {
+            Vim::update(cx, |vim, _| {
+                let global_marks = vim
+                    .workspace_state
+                    .global_marks
+                    .entry(workspace_entity)
+                    .or_default();
+                for (name, path, row, column) in marks {
+                    if let Some(name) = name.chars().next() {
+                        // Marks set while loading are newer.
+                        global_marks.entry(name).or_insert(GlobalMark {
+                            abs_path: path.into(),
+                            position: Point::new(row, column),
+                            anchor: None,
+                        });
+                    }
+                }
+            })
+        })?;
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+fn save_global_mark(
+    workspace_id: WorkspaceId,
+    name: char,
+    abs_path: PathBuf,
+    position: Point,
+    cx: &AppContext,
+) {
+    cx.background_executor()
+        .spawn(async move {
+            DB.save_global_mark(
+                workspace_id,
+                name.to_string(),
+                abs_path,
+                position.row,
+                position.column,
+            )
+            .await
+        })
+        .detach_and_log_err(cx);
+}
+
+/// `'` is another name for the `` ` `` mark.
+fn mark_name(name: char) -> char {
+    if name == '\'' {
+        '`'
+    } else {
+        name
+    }
+}
+
+/// Sets the mark typed after `m` at the newest cursor.
+pub(crate) fn create_mark(vim: &mut Vim, text: Arc<str>, cx: &mut WindowContext) {
+    let Some(name) = text.chars().next().map(mark_name) else {
+        return;
+    };
+    if name.is_ascii_uppercase() {
+        create_global_mark(vim, name, cx);
+    } else if name.is_ascii_lowercase() || ['`', '[', ']'].contains(&name) {
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let head = editor.selections.newest_anchor().head();
+            if let Some((_, marks)) = vim.buffer_marks(editor, cx) {
+                marks.marks.insert(name, head.text_anchor);
+            }
+        });
+    }
+}
+
+fn create_global_mark(vim: &mut Vim, name: char, cx: &mut WindowContext) {
+    vim.update_active_editor(cx, |vim, editor, cx| {
+        let Some(workspace) = editor.workspace() else {
+            return;
+        };
+        let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let Some(abs_path) = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| Some(file.as_local()?.abs_path(cx)))
+        else {
+            return;
+        };
+        let position = editor.selections.newest::<Point>(cx).head();
+        let anchor = buffer.read(cx).anchor_before(position);
+        let workspace_id = workspace.read(cx).database_id();
+        save_global_mark(workspace_id, name, abs_path.clone(), position, cx);
+        set_global_mark(vim, &workspace, name, abs_path, &buffer, anchor, cx);
+    });
+}
+
+/// Anchors the uppercase marks of the editor's file in its buffer, so that they move with
+/// the text around them while it is open.
+pub(crate) fn anchor_global_marks(vim: &mut Vim, editor: &View<Editor>, cx: &mut AppContext) {
+    let editor = editor.read(cx);
+    let Some(workspace) = editor.workspace() else {
+        return;
+    };
+    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let Some(abs_path) = buffer
+        .read(cx)
+        .file()
+        .and_then(|file| Some(file.as_local()?.abs_path(cx)))
+    else {
+        return;
+    };
+    let Some(marks) = vim.workspace_state.global_marks.get(&workspace.entity_id()) else {
+        return;
+    };
+    let unanchored: Vec<(char, Point)> = marks
+        .iter()
+        .filter(|(_, mark)| mark.anchor.is_none() && *mark.abs_path == *abs_path)
+        .map(|(name, mark)| (*name, mark.position))
+        .collect();
+    for (name, position) in unanchored {
+        let anchor = {
+            let buffer = buffer.read(cx);
+            buffer.anchor_before(buffer.clip_point(position, Bias::Left))
+        };
+        set_global_mark(vim, &workspace, name, abs_path.clone(), &buffer, anchor, cx);
+    }
+}
+
+/// Sets the uppercase mark in the buffer of its file. When the buffer is released, the
+/// mark is kept at the position its anchor had reached, which is saved again.
+fn set_global_mark(
+    vim: &mut Vim,
+    workspace: &View<Workspace>,
+    name: char,
+    abs_path: PathBuf,
+    buffer: &Model<Buffer>,
+    anchor: language::Anchor,
+    cx: &mut AppContext,
+) {
+    let workspace_entity = workspace.entity_id();
+    let workspace_id = workspace.read(cx).database_id();
+    let buffer_entity = buffer.entity_id();
+    vim.workspace_state
+        .global_marks
+        .entry(workspace_entity)
+        .or_default()
+        .insert(
+            name,
+            GlobalMark {
+                abs_path: abs_path.into(),
+                position: anchor.to_point(&buffer.read(cx)),
+                anchor: Some((buffer.downgrade(), anchor)),
+            },
+        );
+
+    cx.observe_release(buffer, move |buffer, cx| {
+        if !cx.has_global::<Vim>() {
+            return;
+        }
+        let position = anchor.to_point(&*buffer);
+        let abs_path = cx.update_global(|vim: &mut Vim, _| {
+            let mark = vim
+                .workspace_state
+                .global_marks
+                .get_mut(&workspace_entity)?
+                .get_mut(&name)?;
+            // The mark may have been set elsewhere since.
+            let (mark_buffer, mark_anchor) = mark.anchor.as_ref()?;
+            if mark_buffer.entity_id() != buffer_entity || *mark_anchor != anchor {
+                return None;
+            }
+            mark.position = position;
+            mark.anchor = None;
+            Some(mark.abs_path.to_path_buf())
+        });
+        if let Some(abs_path) = abs_path {
+            save_global_mark(workspace_id, name, abs_path, position, cx);
+        }
+    })
+    .detach();
+}
+
+/// Returns where the mark of the buffer under the newest cursor is shown in the editor.
+pub(crate) fn mark_anchor(
+    vim: &Vim,
+    editor: &Editor,
+    name: char,
+    cx: &AppContext,
+) -> Option<Anchor> {
+    let head = editor.selections.newest_anchor().head();
+    let (_, buffer, _) = editor.buffer().read(cx).excerpt_containing(head, cx)?;
+    let marks = vim.workspace_state.buffer_marks.get(&buffer.entity_id())?;
+    anchor_in_editor(editor, *marks.marks.get(&name)?, cx)
+}
+
+/// Returns where an anchor of the buffer under the newest cursor is shown in the editor,
+/// preferring an excerpt that contains it over the one under the cursor.
+fn anchor_in_editor(editor: &Editor, anchor: language::Anchor, cx: &AppContext) -> Option<Anchor> {
+    let head = editor.selections.newest_anchor().head();
+    let multibuffer = editor.buffer().read(cx);
+    let (cursor_excerpt_id, _, _) = multibuffer.excerpt_containing(head, cx)?;
+    let snapshot = multibuffer.snapshot(cx);
+    let excerpt_id = snapshot
+        .excerpts()
+        .find(|(_, buffer, range)| {
+            anchor.buffer_id == Some(buffer.remote_id())
+                && range.context.start.cmp(&anchor, buffer).is_le()
+                && range.context.end.cmp(&anchor, buffer).is_ge()
+        })
+        .map_or(cursor_excerpt_id, |(excerpt_id, _, _)| excerpt_id);
+    Some(snapshot.anchor_in_excerpt(excerpt_id, anchor))
+}
+
+/// Moves to the mark typed after `'` or `` ` ``, either to the first non-blank character
+/// of its line or to its exact position.
+pub(crate) fn jump(text: Arc<str>, line: bool, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| vim.pop_operator(cx));
+    let Some(name) = text.chars().next().map(mark_name) else {
+        return;
+    };
+    if name.is_ascii_uppercase() {
+        jump_to_global_mark(name, line, cx);
+        return;
+    }
+
+    let anchor = Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |vim, editor, cx| mark_anchor(vim, editor, name, cx))
+    })
+    .flatten();
+    match anchor {
+        Some(anchor) => motion::motion(Motion::Jump { anchor, line }, cx),
+        None => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
+    }
+}
+
+fn jump_to_global_mark(name: char, line: bool, cx: &mut WindowContext) {
+    let Some((workspace, mark)) = Vim::update(cx, |vim, cx| {
+        let workspace = vim
+            .update_active_editor(cx, |_, editor, _| editor.workspace())
+            .flatten()?;
+        let mark = vim
+            .workspace_state
+            .global_marks
+            .get(&workspace.entity_id())?
+            .get(&name)?
+            .clone();
+        Some((workspace, mark))
+    }) else {
+        Vim::update(cx, |vim, cx| vim.clear_operator(cx));
+        return;
+    };
+
+    // Within its own file, the mark is a motion like any other.
+    let anchor = Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let buffer = editor.buffer().read(cx);
+            let file = buffer.as_singleton()?.read(cx).file()?.as_local()?;
+            (file.abs_path(cx).as_path() == &*mark.abs_path).then(|| {
+                let snapshot = buffer.snapshot(cx);
+                snapshot.anchor_before(snapshot.clip_point(mark.position(cx), Bias::Left))
+            })
+        })
+    })
+    .flatten();
+    if let Some(anchor) = anchor {
+        motion::motion(Motion::Jump { anchor, line }, cx);
+        return;
+    }
+
+    // Operators can't span several files.
+    let can_open = Vim::update(cx, |vim, cx| {
+        if vim.active_operator().is_some() {
+            vim.clear_operator(cx);
+            return false;
+        }
+        vim.record_jump(cx);
+        true
+    });
+    if !can_open {
+        return;
+    }
+
+    let open = workspace.update(cx, |workspace, cx| {
+        workspace.open_abs_path(mark.abs_path.to_path_buf(), true, cx)
+    });
+    cx.spawn(|mut cx| async move {
+        let item = open.await?;
+        if let Some(editor) = item.downcast::<Editor>() {
+            editor.update(&mut cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let mut point = snapshot.clip_point(mark.position(cx), Bias::Left);
+                if line {
+                    point.column = snapshot.indent_size_for_line(point.row).len;
+                }
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            })?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Records the newest cursor after an edit as the `.` mark and in the change list,
+/// where it replaces the previous change if both are on the same line.
+pub(crate) fn record_change(vim: &mut Vim, editor: &Editor, cx: &mut AppContext) {
+    let head = editor.selections.newest_anchor().head().text_anchor;
+    let Some((buffer, marks)) = vim.buffer_marks(editor, cx) else {
+        return;
+    };
+    let buffer = buffer.read(cx);
+    let row = head.to_point(buffer).row;
+
+    marks.marks.insert('.', head);
+    marks.change_list_position = None;
+    if marks
+        .change_list
+        .last()
+        .is_some_and(|last| last.to_point(buffer).row == row)
+    {
+        marks.change_list.pop();
+    }
+    marks.change_list.push(head);
+    if marks.change_list.len() > MAX_CHANGE_LIST_LEN {
+        marks.change_list.remove(0);
+    }
+}
+
+/// Moves to an older change for a negative `delta`, or to a newer one for a positive one.
+fn move_in_change_list(vim: &mut Vim, delta: isize, cx: &mut WindowContext) {
+    vim.update_active_editor(cx, |vim, editor, cx| {
+        let Some((_, marks)) = vim.buffer_marks(editor, cx) else {
+            return;
+        };
+        let len = marks.change_list.len();
+        if len == 0 {
+            return;
+        }
+        let current = marks.change_list_position.unwrap_or(len);
+        let position = (current as isize + delta).clamp(0, len as isize - 1) as usize;
+        if position == current {
+            return;
+        }
+        marks.change_list_position = Some(position);
+        let Some(anchor) = anchor_in_editor(editor, marks.change_list[position], cx) else {
+            return;
+        };
+
+        editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+            s.replace_cursors_with(|map| {
+                vec![map.clip_point(anchor.to_display_point(map), Bias::Left)]
+            })
+        });
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use editor::Editor;
+    use gpui::{Context, VisualContext};
+    use indoc::indoc;
+    use language::{Buffer, BufferId};
+
+    use crate::{state::Mode, test::VimTestContext, Vim};
+
+    #[gpui::test]
+    async fn test_lowercase_marks(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                one
+                  twˇo
+                three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["m", "a", "g", "g", "'", "a"]);
+        cx.assert_state(
+            indoc! {"
+                one
+                  ˇtwo
+                three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["g", "g", "`", "a"]);
+        cx.assert_state(
+            indoc! {"
+                one
+                  twˇo
+                three"},
+            Mode::Normal,
+        );
+
+        // Marks move with the text around them.
+        cx.simulate_keystrokes(["g", "g", "shift-o", "z", "e", "r", "o", "escape", "`", "a"]);
+        cx.assert_state(
+            indoc! {"
+                zero
+                one
+                  twˇo
+                three"},
+            Mode::Normal,
+        );
+
+        // Jumping to a mark is a motion.
+        cx.simulate_keystrokes(["g", "g", "d", "'", "a"]);
+        cx.assert_state("ˇthree", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_uppercase_marks(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                one
+                twˇo
+                three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["m", "shift-a", "shift-g", "`", "shift-a"]);
+        cx.assert_state(
+            indoc! {"
+                one
+                twˇo
+                three"},
+            Mode::Normal,
+        );
+
+        // Uppercase marks move with the text around them too.
+        cx.simulate_keystrokes(["g", "g", "shift-o", "z", "e", "r", "o", "escape"]);
+        cx.simulate_keystrokes(["`", "shift-a"]);
+        cx.assert_state(
+            indoc! {"
+                zero
+                one
+                twˇo
+                three"},
+            Mode::Normal,
+        );
+
+        // They belong to the workspace they were set in.
+        let workspace = cx.workspace(|_, cx| cx.view().entity_id());
+        cx.update(|cx| {
+            let global_marks = &Vim::read(cx).workspace_state.global_marks;
+            assert_eq!(global_marks.keys().collect::<Vec<_>>(), [&workspace]);
+            assert!(global_marks[&workspace].contains_key(&'A'));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_marks_are_per_buffer(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                one
+                twˇo
+                three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["m", "a"]);
+
+        // Another editor of the same buffer has the same marks.
+        let buffer = cx.multibuffer(|multibuffer, _| multibuffer.as_singleton().unwrap());
+        let editor = cx.workspace(|workspace, cx| {
+            let project = workspace.project().clone();
+            let editor = cx.new_view(|cx| Editor::for_buffer(buffer, Some(project), cx));
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), cx);
+            editor
+        });
+        cx.editor = editor;
+        cx.run_until_parked();
+        cx.simulate_keystrokes(["g", "g", "`", "a"]);
+        cx.assert_state(
+            indoc! {"
+                one
+                twˇo
+                three"},
+            Mode::Normal,
+        );
+
+        // Marks are forgotten along with their buffer.
+        let buffer = cx.new_model(|_| Buffer::new(0, BufferId::new(1).unwrap(), "a = 1\n"));
+        let buffer_id = buffer.entity_id();
+        let window = cx.add_window(|cx| Editor::for_buffer(buffer, None, cx));
+        cx.update(|cx| {
+            window
+                .update(cx, |editor, cx| {
+                    Vim::update(cx, |vim, cx| {
+                        let (_, marks) = vim.buffer_marks(editor, cx).unwrap();
+                        marks.marks.insert('a', language::Anchor::MIN);
+                    })
+                })
+                .unwrap()
+        });
+        let has_marks = |cx: &mut VimTestContext| {
+            cx.update(|cx| {
+                Vim::read(cx)
+                    .workspace_state
+                    .buffer_marks
+                    .contains_key(&buffer_id)
+            })
+        };
+        assert!(has_marks(&mut cx));
+        cx.update(|cx| window.update(cx, |_, cx| cx.remove_window()).unwrap());
+        cx.run_until_parked();
+        assert!(!has_marks(&mut cx));
+    }
+
+    #[gpui::test]
+    async fn test_jumps(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                one
+                twˇo
+                three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["shift-g", "`", "`"]);
+        cx.assert_state(
+            indoc! {"
+                one
+                twˇo
+                three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["'", "'"]);
+        cx.assert_state(
+            indoc! {"
+                one
+                two
+                ˇthree"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(["g", "g", "ctrl-o"]);
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+                one
+                two
+                ˇthree"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["ctrl-i"]);
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+                ˇone
+                two
+                three"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_special_marks(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes(["w", "y", "w", "`", "]"]);
+        cx.assert_state("one twoˇ three", Mode::Normal);
+        cx.simulate_keystrokes(["`", "["]);
+        cx.assert_state("one ˇtwo three", Mode::Normal);
+
+        cx.simulate_keystrokes(["i", "a", "escape", "0", "`", "^"]);
+        cx.assert_state("one aˇtwo three", Mode::Normal);
+        cx.simulate_keystrokes(["0", "`", "."]);
+        cx.assert_state("one aˇtwo three", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_change_list(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                ˇone
+                two
+                three
+                four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["x", "j", "j", "x", "x", "shift-g"]);
+        cx.simulate_keystrokes(["g", ";"]);
+        cx.assert_state(
+            indoc! {"
+                ne
+                two
+                ˇree
+                four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["g", ";"]);
+        cx.assert_state(
+            indoc! {"
+                ˇne
+                two
+                ree
+                four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["g", ","]);
+        cx.assert_state(
+            indoc! {"
+                ne
+                two
+                ˇree
+                four"},
+            Mode::Normal,
+        );
+    }
+}
@@ -44,8 +44,21 @@ fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
             editor.transact(cx, |editor, cx| {
                 editor.set_clip_at_line_ends(false, cx);
 
+                let selected_register = vim
+                    .update_state(|state| state.selected_register.take())
+                    .filter(|register| !matches!(register, '"' | '+' | '*'));
                 let (clipboard_text, clipboard_selections): (String, Option<_>) =
-                    if VimSettings::get_global(cx).use_system_clipboard == UseSystemClipboard::Never
+                    if let Some(register) = selected_register {
+                        (
+                            vim.workspace_state
+                                .registers
+                                .get(&register.to_ascii_lowercase().to_string())
+                                .cloned()
+                                .unwrap_or_default(),
+                            None,
+                        )
+                    } else if VimSettings::get_global(cx).use_system_clipboard
+                        == UseSystemClipboard::Never
                         || VimSettings::get_global(cx).use_system_clipboard
                             == UseSystemClipboard::OnYank
                             && !system_clipboard_is_newer(vim, cx)
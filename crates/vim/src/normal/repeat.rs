@@ -1,20 +1,31 @@
 use crate::{
     insert::NormalBefore,
     motion::Motion,
-    state::{Mode, RecordedSelection, ReplayableAction},
+    state::{Mode, Operator, RecordedSelection, Recording, ReplayQueue, ReplayableAction},
     visual::visual_motion,
     Vim,
 };
-use gpui::{actions, Action, ViewContext, WindowContext};
+use gpui::{actions, Action, Keystroke, Modifiers, ViewContext, WindowContext};
 use workspace::Workspace;
 
-actions!(vim, [Repeat, EndRepeat]);
+actions!(vim, [Repeat, EndRepeat, ToggleRecord]);
+
+/// Replaying stops after this many actions, so that a macro that replays itself ends.
+const MAX_REPLAYED_ACTIONS: usize = 10_000;
 
 fn should_replay(action: &Box<dyn Action>) -> bool {
     // skip so that we don't leave the character palette open
     if editor::actions::ShowCharacterPalette.partial_eq(&**action) {
         return false;
     }
+    // the completion that was confirmed is replayed as the insertion that followed it
+    if action
+        .as_any()
+        .downcast_ref::<editor::actions::ConfirmCompletion>()
+        .is_some()
+    {
+        return false;
+    }
     true
 }
 
@@ -35,7 +46,7 @@ fn repeatable_insert(action: &ReplayableAction) -> Option<Box<dyn Action>> {
                 None
             }
         }
-        ReplayableAction::Insertion { .. } => None,
+        ReplayableAction::Insertion { .. } | ReplayableAction::Keystroke(_) => None,
     }
 }
 
@@ -48,18 +59,251 @@ pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>
     });
 
     workspace.register_action(|_: &mut Workspace, _: &Repeat, cx| repeat(cx, false));
+
+    workspace.register_action(|_: &mut Workspace, _: &ToggleRecord, cx| {
+        Vim::update(cx, |vim, cx| {
+            if let Some(register) = vim.workspace_state.recording_register.take() {
+                let keystrokes = vim
+                    .workspace_state
+                    .recordings
+                    .get(&register)
+                    .map(|recording| recording.keystrokes.clone())
+                    .unwrap_or_default();
+                vim.workspace_state
+                    .registers
+                    .insert(register.to_string(), keystrokes);
+            } else {
+                vim.push_operator(Operator::RecordRegister, cx);
+            }
+        })
+    });
+}
+
+/// Starts recording the keystrokes typed into `register`, appending to its contents
+/// when the register is uppercase.
+pub(crate) fn record_register(register: char, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| vim.clear_operator(cx));
+    if !register.is_ascii_alphanumeric() && register != '"' {
+        return;
+    }
+
+    Vim::update(cx, |vim, _| {
+        let append = register.is_ascii_uppercase();
+        let register = register.to_ascii_lowercase();
+
+        let state = &mut vim.workspace_state;
+        let contents = state.registers.get(&register.to_string()).cloned();
+        let recording = state.recordings.entry(register).or_default();
+        if !append {
+            *recording = Recording {
+                keystrokes: String::new(),
+                actions: Some(Vec::new()),
+            };
+        } else if contents.as_ref() != Some(&recording.keystrokes) {
+            // the register was yanked into since it was recorded, so the actions that
+            // were recorded no longer match its contents.
+            let keystrokes = contents.unwrap_or_default();
+            *recording = Recording {
+                actions: keystrokes.is_empty().then(Vec::new),
+                keystrokes,
+            };
+        }
+    });
+
+    // the register itself was typed as an insertion, which shouldn't be recorded.
+    cx.defer(move |cx| {
+        Vim::update(cx, |vim, _| {
+            vim.workspace_state.recording_register = Some(register.to_ascii_lowercase())
+        })
+    });
+}
+
+/// Replays the macro in `register`, or the last replayed one for `@`, as many times as
+/// the count.
+pub(crate) fn replay_register(register: char, cx: &mut WindowContext) {
+    let Some(actions) = Vim::update(cx, |vim, cx| {
+        let count = vim.take_count(cx).unwrap_or(1);
+        vim.clear_operator(cx);
+
+        let register = if register == '@' {
+            vim.workspace_state.last_replayed_register?
+        } else {
+            register.to_ascii_lowercase()
+        };
+        vim.workspace_state.last_replayed_register = Some(register);
+
+        let contents = vim.workspace_state.registers.get(&register.to_string())?;
+        let actions = match vim.workspace_state.recordings.get(&register) {
+            Some(Recording {
+                keystrokes,
+                actions: Some(actions),
+            }) if keystrokes == contents => actions.clone(),
+            _ => parse_keystrokes(contents)
+                .into_iter()
+                .map(ReplayableAction::Keystroke)
+                .collect(),
+        };
+
+        let mut repeated_actions = Vec::with_capacity(actions.len() * count);
+        for _ in 0..count {
+            repeated_actions.extend(actions.iter().cloned());
+        }
+        Some(repeated_actions)
+    }) else {
+        return;
+    };
+
+    replay(actions, cx);
+}
+
+/// Returns how a keystroke is written in a register: as the character it typed, or as
+/// its name between angle brackets.
+pub(crate) fn keystroke_text(keystroke: &Keystroke) -> String {
+    let modifiers = keystroke.modifiers;
+    let typed_text = keystroke
+        .ime_key
+        .as_ref()
+        .filter(|_| !modifiers.control && !modifiers.alt && !modifiers.command)
+        .filter(|ime_key| !modifiers.function && ime_key.chars().count() == 1);
+    match typed_text.map(String::as_str) {
+        Some("<") => "<lt>".to_string(),
+        Some(text) => text.to_string(),
+        None => {
+            let mut text = "<".to_string();
+            for (pressed, name) in [
+                (modifiers.control, "ctrl-"),
+                (modifiers.alt, "alt-"),
+                (modifiers.shift, "shift-"),
+                (modifiers.command, "cmd-"),
+                (modifiers.function, "fn-"),
+            ] {
+                if pressed {
+                    text.push_str(name);
+                }
+            }
+            text.push_str(&keystroke.key);
+            text.push('>');
+            text
+        }
+    }
+}
+
+/// Parses keystrokes written as [`keystroke_text`] writes them, so that a register that
+/// was edited by hand, or the keys given to `:normal`, can be replayed.
+pub(crate) fn parse_keystrokes(text: &str) -> Vec<Keystroke> {
+    let mut keystrokes = Vec::new();
+    let mut rest = text;
+    while let Some(character) = rest.chars().next() {
+        if character == '<' {
+            let named_keystroke = rest.find('>').and_then(|end| {
+                let name = &rest[1..end];
+                if name == "lt" {
+                    return Some((char_keystroke('<'), end));
+                }
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    return None;
+                }
+                Some((Keystroke::parse(name).ok()?, end))
+            });
+            if let Some((keystroke, end)) = named_keystroke {
+                keystrokes.push(keystroke);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        keystrokes.push(char_keystroke(character));
+        rest = &rest[character.len_utf8()..];
+    }
+    keystrokes
+}
+
+fn char_keystroke(character: char) -> Keystroke {
+    let key = match character {
+        ' ' => "space".to_string(),
+        '\n' => "enter".to_string(),
+        '\t' => "tab".to_string(),
+        character => character.to_lowercase().collect(),
+    };
+    Keystroke {
+        modifiers: Modifiers {
+            shift: character.is_uppercase(),
+            ..Default::default()
+        },
+        key,
+        ime_key: Some(character.to_string()),
+    }
+}
+
+/// Replays actions one at a time. A replay started by one of them, like `.` in a macro,
+/// runs before the rest of the actions.
+pub(crate) fn replay(actions: Vec<ReplayableAction>, cx: &mut WindowContext) {
+    let is_replaying = Vim::update(cx, |vim, _| {
+        let is_replaying = vim.workspace_state.replay_queue.is_some();
+        let queue = vim
+            .workspace_state
+            .replay_queue
+            .get_or_insert_with(ReplayQueue::default);
+        for action in actions.into_iter().rev() {
+            queue.actions.push_front(action);
+        }
+        is_replaying
+    });
+    if !is_replaying {
+        cx.defer(replay_next_action);
+    }
+}
+
+fn replay_next_action(cx: &mut WindowContext) {
+    let Some((action, editor)) = Vim::update(cx, |vim, _| {
+        let queue = vim.workspace_state.replay_queue.as_mut()?;
+        if queue.replayed == MAX_REPLAYED_ACTIONS {
+            log::error!("stopped replaying after {MAX_REPLAYED_ACTIONS} actions");
+            queue.actions.clear();
+        }
+        let Some(action) = queue.actions.pop_front() else {
+            vim.workspace_state.replay_queue = None;
+            return None;
+        };
+        queue.replayed += 1;
+        Some((action, vim.active_editor.clone()))
+    }) else {
+        return;
+    };
+
+    match action {
+        ReplayableAction::Action(action) => {
+            if should_replay(&action) {
+                cx.dispatch_action(action.boxed_clone());
+                // replayed actions aren't keystrokes, so they're observed here instead
+                cx.defer(move |cx| crate::observe_action(action, cx));
+            }
+        }
+        ReplayableAction::Insertion {
+            text,
+            utf16_range_to_replace,
+        } => {
+            if let Some(editor) = editor {
+                editor
+                    .update(cx, |editor, cx| {
+                        editor.replay_insert_event(&text, utf16_range_to_replace, cx)
+                    })
+                    .ok();
+            }
+        }
+        ReplayableAction::Keystroke(keystroke) => {
+            cx.dispatch_keystroke(keystroke);
+        }
+    }
+    cx.defer(replay_next_action);
 }
 
 pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
-    let Some((mut actions, editor, selection)) = Vim::update(cx, |vim, cx| {
+    let Some((mut actions, selection)) = Vim::update(cx, |vim, cx| {
         let actions = vim.workspace_state.recorded_actions.clone();
-        if actions.is_empty() {
+        if actions.is_empty() || vim.active_editor.is_none() {
             return None;
         }
 
-        let Some(editor) = vim.active_editor.clone() else {
-            return None;
-        };
         let count = vim.take_count(cx);
 
         let selection = vim.workspace_state.recorded_selection.clone();
@@ -83,7 +327,7 @@ pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
             }
         }
 
-        Some((actions, editor, selection))
+        Some((actions, selection))
     }) else {
         return;
     };
@@ -166,34 +410,8 @@ pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
     }
 
     Vim::update(cx, |vim, _| vim.workspace_state.replaying = true);
-    let window = cx.window_handle();
-    cx.spawn(move |mut cx| async move {
-        editor.update(&mut cx, |editor, _| {
-            editor.show_local_selections = false;
-        })?;
-        for action in actions {
-            match action {
-                ReplayableAction::Action(action) => {
-                    if should_replay(&action) {
-                        window.update(&mut cx, |_, cx| cx.dispatch_action(action))
-                    } else {
-                        Ok(())
-                    }
-                }
-                ReplayableAction::Insertion {
-                    text,
-                    utf16_range_to_replace,
-                } => editor.update(&mut cx, |editor, cx| {
-                    editor.replay_insert_event(&text, utf16_range_to_replace.clone(), cx)
-                }),
-            }?
-        }
-        editor.update(&mut cx, |editor, _| {
-            editor.show_local_selections = true;
-        })?;
-        window.update(&mut cx, |_, cx| cx.dispatch_action(EndRepeat.boxed_clone()))
-    })
-    .detach_and_log_err(cx);
+    actions.push(ReplayableAction::Action(EndRepeat.boxed_clone()));
+    replay(actions, cx);
 }
 
 #[cfg(test)]
@@ -506,4 +724,50 @@ mod test {
         cx.simulate_shared_keystrokes(["."]).await;
         cx.assert_shared_state("ˇx hello\n").await;
     }
+
+    #[gpui::test]
+    async fn test_record_replay(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone\ntwo\nthree\nfour\nfive\n", Mode::Normal);
+        cx.simulate_keystrokes(["q", "w", "shift-a", "!", "escape", "0", "j", "q"]);
+        cx.assert_state("one!\nˇtwo\nthree\nfour\nfive\n", Mode::Normal);
+        cx.simulate_keystrokes(["@", "w"]);
+        cx.run_until_parked();
+        cx.assert_state("one!\ntwo!\nˇthree\nfour\nfive\n", Mode::Normal);
+        cx.simulate_keystrokes(["@", "@"]);
+        cx.run_until_parked();
+        cx.assert_state("one!\ntwo!\nthree!\nˇfour\nfive\n", Mode::Normal);
+        cx.simulate_keystrokes(["2", "@", "w"]);
+        cx.run_until_parked();
+        cx.assert_state("one!\ntwo!\nthree!\nfour!\nfive!\nˇ", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_replay_edited_register(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇAok<escape>\nabc\n", Mode::Normal);
+        cx.simulate_keystrokes(["\"", "a", "y", "$", "j"]);
+        cx.simulate_keystrokes(["@", "a"]);
+        cx.run_until_parked();
+        cx.assert_state("Aok<escape>\nabcoˇk\n", Mode::Normal);
+
+        cx.set_state("ˇabc\n", Mode::Normal);
+        cx.simulate_keystrokes(["q", "b", "x", "q", "\"", "b", "p"]);
+        cx.assert_state("bˇxc\n", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_replay_multicursor(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone\ntwo\n", Mode::Normal);
+        cx.simulate_keystrokes(["q", "a", "i", "-", "escape", "q"]);
+        cx.assert_state("ˇ-one\ntwo\n", Mode::Normal);
+        cx.set_state("ˇone\nˇtwo\n", Mode::Normal);
+        cx.simulate_keystrokes(["@", "a"]);
+        cx.run_until_parked();
+        cx.assert_state("ˇ-one\nˇ-two\n", Mode::Normal);
+    }
 }
@@ -42,11 +42,16 @@ pub struct ReplaceCommand {
 }
 
 #[derive(Debug, Default)]
-struct Replacement {
-    search: String,
-    replacement: String,
-    should_replace_all: bool,
-    is_case_sensitive: bool,
+pub(crate) struct Replacement {
+    pub(crate) search: String,
+    pub(crate) replacement: String,
+    pub(crate) should_replace_all: bool,
+    /// Set by the `n` flag, which reports the number of matches instead of replacing them.
+    pub(crate) count_only: bool,
+    /// Set by the `i` and `I` flags, and otherwise decided by the `ignorecase` option.
+    pub(crate) is_case_sensitive: Option<bool>,
+    /// Whether every match in a line is replaced rather than the first one, for `g`.
+    pub(crate) replace_every_match: bool,
 }
 
 actions!(vim, [SearchSubmit]);
@@ -83,6 +88,7 @@ fn search(workspace: &mut Workspace, action: &Search, cx: &mut ViewContext<Works
     };
     Vim::update(cx, |vim, cx| {
         let count = vim.take_count(cx).unwrap_or(1);
+        vim.record_jump(cx);
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
                 search_bar.update(cx, |search_bar, cx| {
@@ -150,6 +156,7 @@ pub fn move_to_internal(
     Vim::update(cx, |vim, cx| {
         let pane = workspace.active_pane().clone();
         let count = vim.take_count(cx).unwrap_or(1);
+        vim.record_jump(cx);
 
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
@@ -243,7 +250,7 @@ fn replace_command(
             }
 
             let mut options = SearchOptions::default();
-            if replacement.is_case_sensitive {
+            if replacement.is_case_sensitive.unwrap_or(true) {
                 options.set(SearchOptions::CASE_SENSITIVE, true)
             }
             let search = if replacement.search == "" {
@@ -284,79 +291,138 @@ fn replace_command(
 
 // convert a vim query into something more usable by zed.
 // we don't attempt to fully convert between the two regex syntaxes,
-// but we do flip \( and \) to ( and ) (and vice-versa) in the pattern,
-// and convert \0..\9 to $0..$9 in the replacement so that common idioms work.
+// but we do swap the characters that are special in one and literal in the
+// other, and convert \0..\9 and & in the replacement so that common idioms work.
 fn parse_replace_all(query: &str) -> Replacement {
     let mut chars = query.chars();
     if Some('%') != chars.next() || Some('s') != chars.next() {
         return Replacement::default();
     }
+    Replacement::parse(chars.as_str()).unwrap_or_default()
+}
 
-    let Some(delimiter) = chars.next() else {
-        return Replacement::default();
-    };
+impl Replacement {
+    /// Parses the arguments of `:s`, like `/pattern/replacement/flags`.
+    pub(crate) fn parse(query: &str) -> Option<Self> {
+        let mut chars = query.chars();
+        let delimiter = chars.next()?;
+        if delimiter.is_alphanumeric() || matches!(delimiter, '\\' | '"' | '|' | ' ') {
+            return None;
+        }
+        let (search, rest) = split_on_delimiter(chars.as_str(), delimiter);
+        let (replacement, flags) = split_on_delimiter(rest, delimiter);
+
+        let mut replacement = Replacement {
+            search: vim_regex(&search),
+            replacement: vim_replacement(&replacement),
+            should_replace_all: true,
+            count_only: false,
+            is_case_sensitive: None,
+            replace_every_match: false,
+        };
 
-    let mut search = String::new();
-    let mut replacement = String::new();
-    let mut flags = String::new();
+        for c in flags.chars() {
+            match c {
+                'g' => replacement.replace_every_match = true,
+                'I' => replacement.is_case_sensitive = Some(true),
+                'c' => replacement.should_replace_all = false,
+                'n' => replacement.count_only = true,
+                'i' => replacement.is_case_sensitive = Some(false),
+                _ => {}
+            }
+        }
 
-    let mut buffer = &mut search;
+        Some(replacement)
+    }
+}
 
+/// Splits `text` at the first `delimiter` that isn't escaped with a backslash, returning
+/// the text before it, with the delimiter unescaped, and the text after it.
+pub(crate) fn split_on_delimiter(text: &str, delimiter: char) -> (String, &str) {
+    let mut result = String::new();
     let mut escaped = false;
-    // 0 - parsing search
-    // 1 - parsing replacement
-    // 2 - parsing flags
-    let mut phase = 0;
-
-    for c in chars {
+    for (ix, c) in text.char_indices() {
         if escaped {
             escaped = false;
-            if phase == 1 && c.is_digit(10) {
-                buffer.push('$')
-            // unescape escaped parens
-            } else if phase == 0 && c == '(' || c == ')' {
-            } else if c != delimiter {
-                buffer.push('\\')
+            if c != delimiter {
+                result.push('\\');
             }
-            buffer.push(c)
+            result.push(c);
         } else if c == '\\' {
             escaped = true;
         } else if c == delimiter {
-            if phase == 0 {
-                buffer = &mut replacement;
-                phase = 1;
-            } else if phase == 1 {
-                buffer = &mut flags;
-                phase = 2;
-            } else {
-                break;
-            }
+            return (result, &text[ix + c.len_utf8()..]);
         } else {
-            // escape unescaped parens
-            if phase == 0 && c == '(' || c == ')' {
-                buffer.push('\\')
-            }
-            buffer.push(c)
+            result.push(c);
         }
     }
+    if escaped {
+        result.push('\\');
+    }
+    (result, "")
+}
 
-    let mut replacement = Replacement {
-        search,
-        replacement,
-        should_replace_all: true,
-        is_case_sensitive: true,
-    };
-
-    for c in flags.chars() {
+/// Converts a vim pattern into a regex, swapping the characters that are literal in vim
+/// unless escaped, like `(` and `|`, and turning `\<` and `\>` into word boundaries.
+pub(crate) fn vim_regex(pattern: &str) -> String {
+    let mut regex = String::new();
+    let mut in_braces = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
         match c {
-            'g' | 'I' => {}
-            'c' | 'n' => replacement.should_replace_all = false,
-            'i' => replacement.is_case_sensitive = false,
-            _ => {}
+            '\\' => match chars.next() {
+                Some('{') => {
+                    in_braces = true;
+                    regex.push('{');
+                }
+                Some(c @ ('(' | ')' | '|' | '+' | '?' | '}')) => regex.push(c),
+                Some('=') => regex.push('?'),
+                Some('<' | '>') => regex.push_str("\\b"),
+                Some(c) => {
+                    regex.push('\\');
+                    regex.push(c);
+                }
+                None => regex.push_str("\\\\"),
+            },
+            '}' if in_braces => {
+                in_braces = false;
+                regex.push('}');
+            }
+            '(' | ')' | '|' | '+' | '?' | '{' | '}' => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            c => regex.push(c),
         }
     }
+    regex
+}
 
-    replacement
+/// Converts the replacement of `:s`, where `&` and `\0` to `\9` refer to the match and
+/// its groups, into the syntax of regex replacements.
+fn vim_replacement(replacement: &str) -> String {
+    let mut result = String::new();
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c) if c.is_ascii_digit() => {
+                    result.push_str("${");
+                    result.push(c);
+                    result.push('}');
+                }
+                Some('n' | 'r') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('$') => result.push_str("$$"),
+                Some(c) => result.push(c),
+                None => result.push('\\'),
+            },
+            '&' => result.push_str("${0}"),
+            '$' => result.push_str("$$"),
+            c => result.push(c),
+        }
+    }
+    result
 }
 
 #[cfg(test)]
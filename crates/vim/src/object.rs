@@ -7,10 +7,10 @@ use crate::{
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement::{self, FindRange},
-    Bias, DisplayPoint,
+    Bias, DisplayPoint, MultiBufferSnapshot,
 };
 use gpui::{actions, impl_actions, ViewContext, WindowContext};
-use language::{char_kind, BufferSnapshot, CharKind, Selection};
+use language::{char_kind, BufferSnapshot, CharKind, Selection, TextObjectKind};
 use serde::Deserialize;
 use workspace::Workspace;
 
@@ -28,6 +28,9 @@ pub enum Object {
     AngleBrackets,
     Argument,
     Tag,
+    Function,
+    Class,
+    Comment,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -52,7 +55,10 @@ actions!(
         CurlyBrackets,
         AngleBrackets,
         Argument,
-        Tag
+        Tag,
+        Function,
+        Class,
+        Comment
     ]
 );
 
@@ -88,6 +94,10 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     });
     workspace
         .register_action(|_: &mut Workspace, _: &Argument, cx: _| object(Object::Argument, cx));
+    workspace
+        .register_action(|_: &mut Workspace, _: &Function, cx: _| object(Object::Function, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Class, cx: _| object(Object::Class, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Comment, cx: _| object(Object::Comment, cx));
 }
 
 fn object(object: Object, cx: &mut WindowContext) {
@@ -114,7 +124,10 @@ impl Object {
             | Object::AngleBrackets
             | Object::CurlyBrackets
             | Object::SquareBrackets
-            | Object::Argument => true,
+            | Object::Argument
+            | Object::Function
+            | Object::Class
+            | Object::Comment => true,
         }
     }
 
@@ -129,7 +142,10 @@ impl Object {
             | Object::SquareBrackets
             | Object::Tag
             | Object::CurlyBrackets
-            | Object::AngleBrackets => true,
+            | Object::AngleBrackets
+            | Object::Function
+            | Object::Class
+            | Object::Comment => true,
         }
     }
 
@@ -152,7 +168,10 @@ impl Object {
             | Object::AngleBrackets
             | Object::VerticalBars
             | Object::Tag
-            | Object::Argument => Mode::Visual,
+            | Object::Argument
+            | Object::Function
+            | Object::Class
+            | Object::Comment => Mode::Visual,
         }
     }
 
@@ -197,6 +216,9 @@ impl Object {
                 surrounding_markers(map, relative_to, around, self.is_multiline(), '<', '>')
             }
             Object::Argument => argument(map, relative_to, around),
+            Object::Function => text_object(map, relative_to, around, TextObjectKind::Function),
+            Object::Class => text_object(map, relative_to, around, TextObjectKind::Class),
+            Object::Comment => text_object(map, relative_to, around, TextObjectKind::Comment),
         }
     }
 
@@ -533,7 +555,23 @@ fn argument(
         Some(start..end)
     }
 
-    let result = comma_delimited_range_at(buffer, excerpt.map_offset_to_buffer(offset), around)?;
+    let buffer_offset = excerpt.map_offset_to_buffer(offset);
+    let comma_delimited_range = comma_delimited_range_at(buffer, buffer_offset, around);
+
+    // Prefer the parameters and arguments of the textobjects query, unless the cursor is
+    // in an inner list that the query doesn't capture, such as a tuple or a macro call.
+    let parameter = buffer
+        .enclosing_text_object(buffer_offset..buffer_offset, TextObjectKind::Parameter)
+        .filter(|parameter| {
+            comma_delimited_range_at(buffer, buffer_offset, false).map_or(true, |inner| {
+                inner.start <= parameter.inside.start && parameter.inside.end <= inner.end
+            })
+        });
+    let result = match parameter {
+        Some(parameter) if around => parameter.around,
+        Some(parameter) => parameter.inside,
+        None => comma_delimited_range?,
+    };
 
     if excerpt.contains_buffer_range(result.clone()) {
         let result = excerpt.map_range_from_buffer(result);
@@ -543,6 +581,83 @@ fn argument(
     }
 }
 
+/// Returns the range of the innermost text object of the given kind, as captured by the
+/// language's textobjects query, that contains `relative_to`.
+fn text_object(
+    map: &DisplaySnapshot,
+    relative_to: DisplayPoint,
+    around: bool,
+    kind: TextObjectKind,
+) -> Option<Range<DisplayPoint>> {
+    let snapshot = &map.buffer_snapshot;
+    let offset = relative_to.to_offset(map, Bias::Left);
+    let text_object = snapshot.enclosing_text_object(offset..offset, kind)?;
+
+    let range = if around {
+        expand_to_whole_lines(snapshot, text_object.around)
+    } else if kind == TextObjectKind::Comment {
+        text_object.inside
+    } else {
+        shrink_to_whole_lines(snapshot, text_object.inside)
+    };
+    Some(range.start.to_display_point(map)..range.end.to_display_point(map))
+}
+
+/// Expands a range to include the indentation before it and the newline after it, if
+/// it spans whole lines, so that deleting it doesn't leave a blank line behind.
+fn expand_to_whole_lines(snapshot: &MultiBufferSnapshot, range: Range<usize>) -> Range<usize> {
+    let mut start = range.start;
+    for ch in snapshot.reversed_chars_at(range.start) {
+        if ch == '\n' {
+            break;
+        } else if !ch.is_whitespace() {
+            return range;
+        }
+        start -= ch.len_utf8();
+    }
+
+    // Nodes such as line comments can include the newline that ends them.
+    let mut end = range.end;
+    if snapshot.reversed_chars_at(range.end).next() != Some('\n') {
+        for ch in snapshot.chars_at(range.end) {
+            if !ch.is_whitespace() {
+                return range;
+            }
+            end += ch.len_utf8();
+            if ch == '\n' {
+                break;
+            }
+        }
+    }
+
+    start..end
+}
+
+/// Shrinks the contents of a multi-line block to the lines between its delimiters, like
+/// `i{` does, so that they can be deleted or changed as whole lines.
+fn shrink_to_whole_lines(snapshot: &MultiBufferSnapshot, range: Range<usize>) -> Range<usize> {
+    let text = snapshot.text_for_range(range.clone()).collect::<String>();
+    let is_indentation = |ch: char| ch.is_whitespace() && ch != '\n';
+
+    let mut start = range.start;
+    let trimmed_start = text.trim_start_matches(is_indentation);
+    if trimmed_start.starts_with('\n') {
+        start = range.end - trimmed_start.len() + '\n'.len_utf8();
+    }
+
+    let mut end = range.end;
+    let trimmed_end = text.trim_end_matches(is_indentation);
+    if trimmed_end.ends_with('\n') {
+        end = range.start + trimmed_end.len();
+    }
+
+    if start <= end {
+        start..end
+    } else {
+        range
+    }
+}
+
 fn sentence(
     map: &DisplaySnapshot,
     relative_to: DisplayPoint,
@@ -1299,6 +1414,94 @@ mod test {
         cx.assert_state("let a = [«test::call(first_arg)ˇ»]", Mode::Visual);
     }
 
+    #[gpui::test]
+    async fn test_syntax_objects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        // Inside a function, the lines of its body
+        cx.set_state(
+            indoc! {"
+                impl A {
+                    fn b() {
+                        cˇ();
+                    }
+                }
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["d", "i", "f"]);
+        cx.assert_state(
+            indoc! {"
+                impl A {
+                    fn b() {
+                ˇ    }
+                }
+            "},
+            Mode::Normal,
+        );
+
+        // Around a function, its whole lines
+        cx.set_state(
+            indoc! {"
+                impl A {
+                    fn b() {
+                        cˇ();
+                    }
+                    fn d() {}
+                }
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["d", "a", "f"]);
+        cx.assert_state(
+            indoc! {"
+                impl A {
+                ˇ    fn d() {}
+                }
+            "},
+            Mode::Normal,
+        );
+
+        // Inside a class
+        cx.set_state(
+            indoc! {"
+                impl A {
+                    fn ˇb() {}
+                }
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["d", "i", "c"]);
+        cx.assert_state(
+            indoc! {"
+                impl A {
+                ˇ}
+            "},
+            Mode::Normal,
+        );
+
+        // Around consecutive line comments
+        cx.set_state(
+            indoc! {"
+                fn a() {
+                    // one
+                    // twˇo
+                    b();
+                }
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["d", "a", "g", "c"]);
+        cx.assert_state(
+            indoc! {"
+                fn a() {
+                ˇ    b();
+                }
+            "},
+            Mode::Normal,
+        );
+    }
+
     #[gpui::test]
     async fn test_delete_surrounding_character_objects(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // vim_global_marks(
+    //   workspace_id: usize,
+    //   mark_name: String,
+    //   path: PathBuf,
+    //   position_row: u32,
+    //   position_column: u32,
+    // )
+    pub static ref DB: VimDb<WorkspaceDb> =
+        &[sql! (
+            CREATE TABLE vim_global_marks(
+                workspace_id INTEGER NOT NULL,
+                mark_name TEXT NOT NULL,
+                path BLOB NOT NULL,
+                position_row INTEGER NOT NULL,
+                position_column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, mark_name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+);
+
+impl VimDb {
+    // Returns the name, path, row and column of the uppercase marks set in the workspace
+    query! {
+        pub fn get_global_marks(workspace_id: WorkspaceId) -> Result<Vec<(String, PathBuf, u32, u32)>> {
+            SELECT mark_name, path, position_row, position_column
+            FROM vim_global_marks
+            WHERE workspace_id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_global_mark(
+            workspace_id: WorkspaceId,
+            mark_name: String,
+            path: PathBuf,
+            row: u32,
+            column: u32
+        ) -> Result<()> {
+            INSERT INTO vim_global_marks
+                (workspace_id, mark_name, path, position_row, position_column)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT DO UPDATE SET
+                path = ?3,
+                position_row = ?4,
+                position_column = ?5
+        }
+    }
+}
@@ -1,8 +1,8 @@
-use std::{fmt::Display, ops::Range, sync::Arc};
+use std::{collections::VecDeque, fmt::Display, ops::Range, path::Path, sync::Arc};
 
 use collections::HashMap;
-use gpui::{Action, KeyContext};
-use language::CursorShape;
+use gpui::{Action, AppContext, EntityId, KeyContext, Keystroke, WeakModel};
+use language::{Anchor, Buffer, CursorShape, Point, ToPoint};
 use serde::{Deserialize, Serialize};
 use workspace::searchable::Direction;
 
@@ -53,6 +53,11 @@ pub enum Operator {
     Object { around: bool },
     FindForward { before: bool },
     FindBackward { after: bool },
+    Mark,
+    Jump { line: bool },
+    Register,
+    RecordRegister,
+    ReplayRegister,
 }
 
 #[derive(Default, Clone)]
@@ -66,6 +71,9 @@ pub struct EditorState {
     pub post_count: Option<usize>,
 
     pub operator_stack: Vec<Operator>,
+
+    /// The register chosen with `"` for the next yank, delete or paste.
+    pub selected_register: Option<char>,
 }
 
 #[derive(Default, Clone, Debug)]
@@ -101,6 +109,78 @@ pub struct WorkspaceState {
     pub recorded_selection: RecordedSelection,
 
     pub registers: HashMap<String, String>,
+
+    /// The register that `q` is recording a macro into.
+    pub recording_register: Option<char>,
+    /// The macros recorded with `q`, keyed by the register they were recorded into.
+    pub recordings: HashMap<char, Recording>,
+    /// The register last replayed with `@`, which `@@` replays again.
+    pub last_replayed_register: Option<char>,
+    /// What `.` and `@` have left to replay, or `None` when not replaying.
+    pub replay_queue: Option<ReplayQueue>,
+
+    /// The lowercase and special marks, keyed by the entity id of the buffer they were
+    /// set in. They are forgotten when the buffer is released.
+    pub buffer_marks: HashMap<EntityId, BufferMarks>,
+    /// The uppercase marks, which can be jumped to from any file of their workspace,
+    /// keyed by the entity id of the workspace.
+    pub global_marks: HashMap<EntityId, HashMap<char, GlobalMark>>,
+
+    /// The regex of the last pattern used by an ex command, which an empty pattern
+    /// refers to, as in `:g/pattern/s//replacement/`.
+    pub last_pattern: Option<String>,
+    /// Set with `:set ignorecase`, for the patterns of ex commands.
+    pub ignore_case: bool,
+    /// Set with `:set smartcase`, so that patterns with uppercase letters don't ignore case.
+    pub smart_case: bool,
+}
+
+#[derive(Default, Clone)]
+pub struct BufferMarks {
+    /// Anchored so that the marks move with the text around them.
+    pub marks: HashMap<char, Anchor>,
+    /// The positions of the latest changes, oldest first.
+    pub change_list: Vec<Anchor>,
+    /// How far back `g;` went in the change list since the last change.
+    pub change_list_position: Option<usize>,
+}
+
+#[derive(Clone)]
+pub struct GlobalMark {
+    pub abs_path: Arc<Path>,
+    /// Where the mark was when it was set or loaded, or when its buffer was last released.
+    pub position: Point,
+    /// The mark in its buffer while that is open, so that it moves with the text around it.
+    pub anchor: Option<(WeakModel<Buffer>, Anchor)>,
+}
+
+impl GlobalMark {
+    /// Returns where the mark is now.
+    pub fn position(&self, cx: &AppContext) -> Point {
+        self.anchor
+            .as_ref()
+            .and_then(|(buffer, anchor)| Some(anchor.to_point(&buffer.upgrade()?.read(cx))))
+            .unwrap_or(self.position)
+    }
+}
+
+/// A macro recorded with `q`.
+#[derive(Default, Clone)]
+pub struct Recording {
+    /// The keystrokes that were typed, as they are written in the register.
+    pub keystrokes: String,
+    /// The actions and insertions the keystrokes resulted in. They are replayed instead
+    /// of the keystrokes for as long as the register holds `keystrokes`, as they don't
+    /// depend on menus like completions being open. `None` when only the keystrokes are
+    /// known, after appending to a register that was yanked into.
+    pub actions: Option<Vec<ReplayableAction>>,
+}
+
+#[derive(Default, Clone)]
+pub struct ReplayQueue {
+    pub actions: VecDeque<ReplayableAction>,
+    /// How many actions were replayed so far, to stop a macro that replays itself.
+    pub replayed: usize,
 }
 
 #[derive(Debug)]
@@ -110,6 +190,7 @@ pub enum ReplayableAction {
         text: Arc<str>,
         utf16_range_to_replace: Option<Range<isize>>,
     },
+    Keystroke(Keystroke),
 }
 
 impl Clone for ReplayableAction {
@@ -123,6 +204,7 @@ impl Clone for ReplayableAction {
                 text: text.clone(),
                 utf16_range_to_replace: utf16_range_to_replace.clone(),
             },
+            Self::Keystroke(keystroke) => Self::Keystroke(keystroke.clone()),
         }
     }
 }
@@ -233,15 +315,26 @@ impl Operator {
             Operator::FindForward { before: true } => "t",
             Operator::FindBackward { after: false } => "F",
             Operator::FindBackward { after: true } => "T",
+            Operator::Mark => "m",
+            Operator::Jump { line: true } => "'",
+            Operator::Jump { line: false } => "`",
+            Operator::Register => "\"",
+            Operator::RecordRegister => "q",
+            Operator::ReplayRegister => "@",
         }
     }
 
     pub fn context_flags(&self) -> &'static [&'static str] {
         match self {
             Operator::Object { .. } => &["VimObject"],
-            Operator::FindForward { .. } | Operator::FindBackward { .. } | Operator::Replace => {
-                &["VimWaiting"]
-            }
+            Operator::FindForward { .. }
+            | Operator::FindBackward { .. }
+            | Operator::Replace
+            | Operator::Mark
+            | Operator::Jump { .. }
+            | Operator::Register
+            | Operator::RecordRegister
+            | Operator::ReplayRegister => &["VimWaiting"],
             _ => &[],
         }
     }
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use editor::{ClipboardSelection, Editor};
+use editor::{ClipboardSelection, Editor, ToOffset};
 use gpui::{ClipboardItem, ViewContext};
 use language::{CharKind, Point};
 use settings::Settings;
@@ -83,21 +83,60 @@ fn copy_selections_content_internal(
         }
     }
 
+    // `[` and `]` mark the first and last characters of the copied text.
+    if let (Some(first), Some(last)) = (selections.first(), selections.last()) {
+        let end = last.end.to_offset(&buffer);
+        let last_char_len = buffer
+            .reversed_chars_at(end)
+            .next()
+            .filter(|_| last.end > first.start)
+            .map_or(0, char::len_utf8);
+        if let Some((_, marks)) = vim.buffer_marks(editor, cx) {
+            let start = buffer.anchor_after(first.start).text_anchor;
+            marks.marks.insert('[', start);
+            let end = buffer.anchor_before(end - last_char_len).text_anchor;
+            marks.marks.insert(']', end);
+        }
+    }
+
     let setting = VimSettings::get_global(cx).use_system_clipboard;
-    if setting == UseSystemClipboard::Always || setting == UseSystemClipboard::OnYank && is_yank {
-        cx.write_to_clipboard(ClipboardItem::new(text.clone()).with_metadata(clipboard_selections));
-        vim.workspace_state
-            .registers
-            .insert(".system.".to_string(), text.clone());
-    } else {
-        vim.workspace_state.registers.insert(
-            ".system.".to_string(),
-            cx.read_from_clipboard()
-                .map(|item| item.text().clone())
-                .unwrap_or_default(),
-        );
+    match vim.update_state(|state| state.selected_register.take()) {
+        // the black hole register discards the text
+        Some('_') => {}
+        Some(register) if register != '"' && register != '+' && register != '*' => {
+            let registers = &mut vim.workspace_state.registers;
+            if register.is_ascii_uppercase() {
+                registers
+                    .entry(register.to_ascii_lowercase().to_string())
+                    .or_default()
+                    .push_str(&text);
+            } else {
+                registers.insert(register.to_string(), text.clone());
+            }
+            registers.insert("\"".to_string(), text);
+        }
+        register => {
+            if register.is_some()
+                || setting == UseSystemClipboard::Always
+                || setting == UseSystemClipboard::OnYank && is_yank
+            {
+                cx.write_to_clipboard(
+                    ClipboardItem::new(text.clone()).with_metadata(clipboard_selections),
+                );
+                vim.workspace_state
+                    .registers
+                    .insert(".system.".to_string(), text.clone());
+            } else {
+                vim.workspace_state.registers.insert(
+                    ".system.".to_string(),
+                    cx.read_from_clipboard()
+                        .map(|item| item.text().clone())
+                        .unwrap_or_default(),
+                );
+            }
+            vim.workspace_state.registers.insert("\"".to_string(), text);
+        }
     }
-    vim.workspace_state.registers.insert("\"".to_string(), text);
     if !is_yank || vim.state().mode == Mode::Visual {
         return;
     }
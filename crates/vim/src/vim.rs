@@ -10,6 +10,7 @@ mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod persistence;
 mod state;
 mod utils;
 mod visual;
@@ -22,18 +23,22 @@ use editor::{
     Editor, EditorEvent, EditorMode,
 };
 use gpui::{
-    actions, impl_actions, Action, AppContext, EntityId, Global, KeystrokeEvent, Subscription,
-    View, ViewContext, WeakView, WindowContext,
+    actions, impl_actions, Action, AppContext, EntityId, Global, Keystroke, KeystrokeEvent, Model,
+    Subscription, View, ViewContext, WeakView, WindowContext,
 };
-use language::{CursorShape, Point, Selection, SelectionGoal};
+use language::{Buffer, CursorShape, Point, Selection, SelectionGoal};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
-use normal::normal_replace;
+use normal::{
+    mark::{anchor_global_marks, create_mark, jump, record_change},
+    normal_replace,
+    repeat::{keystroke_text, record_register, replay_register},
+};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_derive::Serialize;
 use settings::{update_settings_file, Settings, SettingsStore};
-use state::{EditorState, Mode, Operator, RecordedSelection, WorkspaceState};
+use state::{BufferMarks, EditorState, Mode, Operator, RecordedSelection, WorkspaceState};
 use std::{ops::Range, sync::Arc};
 use visual::{visual_block_motion, visual_replace};
 use workspace::{self, Workspace};
@@ -139,23 +144,16 @@ fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
 /// Called whenever an keystroke is typed so vim can observe all actions
 /// and keystrokes accordingly.
 fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, _| {
+        vim.record_keystroke(&keystroke_event.keystroke)
+    });
+
     if let Some(action) = keystroke_event
         .action
         .as_ref()
         .map(|action| action.boxed_clone())
     {
-        Vim::update(cx, |vim, _| {
-            if vim.workspace_state.recording {
-                vim.workspace_state
-                    .recorded_actions
-                    .push(ReplayableAction::Action(action.boxed_clone()));
-
-                if vim.workspace_state.stop_recording_after_next_action {
-                    vim.workspace_state.recording = false;
-                    vim.workspace_state.stop_recording_after_next_action = false;
-                }
-            }
-        });
+        observe_action(action.boxed_clone(), cx);
 
         // Keystroke is handled by the vim system, so continue forward
         if action.name().starts_with("vim::") {
@@ -166,7 +164,16 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
     }
 
     Vim::update(cx, |vim, cx| match vim.active_operator() {
-        Some(Operator::FindForward { .. } | Operator::FindBackward { .. } | Operator::Replace) => {}
+        Some(
+            Operator::FindForward { .. }
+            | Operator::FindBackward { .. }
+            | Operator::Replace
+            | Operator::Mark
+            | Operator::Jump { .. }
+            | Operator::Register
+            | Operator::RecordRegister
+            | Operator::ReplayRegister,
+        ) => {}
         Some(_) => {
             vim.clear_operator(cx);
         }
@@ -174,6 +181,27 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
     });
 }
 
+/// Records an action that was dispatched, either by a keystroke or by a replay, so
+/// that `.` and `@` can replay it.
+pub(crate) fn observe_action(action: Box<dyn Action>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, _| {
+        if let Some(actions) = vim.recording_macro_actions() {
+            actions.push(ReplayableAction::Action(action.boxed_clone()));
+        }
+
+        if vim.workspace_state.recording {
+            vim.workspace_state
+                .recorded_actions
+                .push(ReplayableAction::Action(action.boxed_clone()));
+
+            if vim.workspace_state.stop_recording_after_next_action {
+                vim.workspace_state.recording = false;
+                vim.workspace_state.stop_recording_after_next_action = false;
+            }
+        }
+    });
+}
+
 /// The state pertaining to Vim mode.
 #[derive(Default)]
 struct Vim {
@@ -205,6 +233,7 @@ impl Vim {
         }
 
         self.active_editor = Some(editor.clone().downgrade());
+        anchor_global_marks(self, &editor, cx);
         self.editor_subscription = Some(cx.subscribe(&editor, |editor, event, cx| match event {
             EditorEvent::SelectionsChanged { local: true } => {
                 let editor = editor.read(cx);
@@ -214,6 +243,9 @@ impl Vim {
                     local_selections_changed(newest, is_multicursor, cx);
                 }
             }
+            EditorEvent::Edited => Vim::update(cx, |vim, cx| {
+                editor.update(cx, |editor, cx| record_change(vim, editor, cx))
+            }),
             EditorEvent::InputIgnored { text } => {
                 Vim::active_editor_input_ignored(text.clone(), cx);
                 Vim::record_insertion(text, None, cx)
@@ -247,6 +279,12 @@ impl Vim {
         cx: &mut WindowContext,
     ) {
         Vim::update(cx, |vim, _| {
+            if let Some(actions) = vim.recording_macro_actions() {
+                actions.push(ReplayableAction::Insertion {
+                    text: text.clone(),
+                    utf16_range_to_replace: range_to_replace.clone(),
+                });
+            }
             if vim.workspace_state.recording {
                 vim.workspace_state
                     .recorded_actions
@@ -262,6 +300,33 @@ impl Vim {
         });
     }
 
+    /// Appends a typed keystroke to the macro that `q` is recording.
+    fn record_keystroke(&mut self, keystroke: &Keystroke) {
+        if self.workspace_state.replay_queue.is_some() {
+            return;
+        }
+        let Some(register) = self.workspace_state.recording_register else {
+            return;
+        };
+        if let Some(recording) = self.workspace_state.recordings.get_mut(&register) {
+            recording.keystrokes.push_str(&keystroke_text(keystroke));
+        }
+    }
+
+    /// Returns the actions of the macro that `q` is recording, unless they are being
+    /// replayed rather than typed.
+    fn recording_macro_actions(&mut self) -> Option<&mut Vec<ReplayableAction>> {
+        if self.workspace_state.replay_queue.is_some() {
+            return None;
+        }
+        let register = self.workspace_state.recording_register?;
+        self.workspace_state
+            .recordings
+            .get_mut(&register)?
+            .actions
+            .as_mut()
+    }
+
     fn update_active_editor<S>(
         &mut self,
         cx: &mut WindowContext,
@@ -354,6 +419,7 @@ impl Vim {
             state.last_mode = last_mode;
             state.mode = mode;
             state.operator_stack.clear();
+            state.selected_register = None;
         });
         if mode != Mode::Insert {
             self.take_count(cx);
@@ -362,6 +428,27 @@ impl Vim {
         // Sync editor settings like clip mode
         self.sync_vim_settings(cx);
 
+        // `<` and `>` mark where the last visual selection started and ended.
+        if last_mode.is_visual() && !mode.is_visual() {
+            self.update_active_editor(cx, |vim, editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let selections = editor.selections.all::<Point>(cx);
+                let (Some(first), Some(last)) = (selections.first(), selections.last()) else {
+                    return;
+                };
+                let mut end = last.end;
+                if end.column == 0 && end > first.start {
+                    end = Point::new(end.row - 1, snapshot.line_len(end.row - 1));
+                }
+                if let Some((_, marks)) = vim.buffer_marks(editor, cx) {
+                    let start = snapshot.anchor_before(first.start).text_anchor;
+                    let end = snapshot.anchor_before(end).text_anchor;
+                    marks.marks.insert('<', start);
+                    marks.marks.insert('>', end);
+                }
+            });
+        }
+
         if leave_selections {
             return;
         }
@@ -464,7 +551,10 @@ impl Vim {
     }
     fn clear_operator(&mut self, cx: &mut WindowContext) {
         self.take_count(cx);
-        self.update_state(|state| state.operator_stack.clear());
+        self.update_state(|state| {
+            state.operator_stack.clear();
+            state.selected_register = None;
+        });
         self.sync_vim_settings(cx);
     }
 
@@ -513,10 +603,61 @@ impl Vim {
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_replace(text, cx),
                 _ => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
             },
+            Some(Operator::Mark) => Vim::update(cx, |vim, cx| {
+                vim.clear_operator(cx);
+                create_mark(vim, text, cx)
+            }),
+            Some(Operator::Jump { line }) => jump(text, line, cx),
+            Some(Operator::Register) => Vim::update(cx, |vim, cx| {
+                vim.pop_operator(cx);
+                vim.update_state(|state| state.selected_register = text.chars().next());
+            }),
+            Some(Operator::RecordRegister) => record_register(text.chars().next().unwrap(), cx),
+            Some(Operator::ReplayRegister) => replay_register(text.chars().next().unwrap(), cx),
             _ => {}
         }
     }
 
+    /// Returns the buffer under the newest cursor of the editor, along with its marks and
+    /// change list.
+    fn buffer_marks(
+        &mut self,
+        editor: &Editor,
+        cx: &mut AppContext,
+    ) -> Option<(Model<Buffer>, &mut BufferMarks)> {
+        let head = editor.selections.newest_anchor().head();
+        let (_, buffer, _) = editor.buffer().read(cx).excerpt_containing(head, cx)?;
+        let buffer_id = buffer.entity_id();
+        if !self.workspace_state.buffer_marks.contains_key(&buffer_id) {
+            cx.observe_release(&buffer, move |_, cx| {
+                if cx.has_global::<Vim>() {
+                    cx.update_global(|vim: &mut Vim, _| {
+                        vim.workspace_state.buffer_marks.remove(&buffer_id);
+                    });
+                }
+            })
+            .detach();
+        }
+        let marks = self
+            .workspace_state
+            .buffer_marks
+            .entry(buffer_id)
+            .or_default();
+        Some((buffer, marks))
+    }
+
+    /// Records the newest cursor before a jump as the `` ` `` mark, and in the navigation
+    /// history so that `ctrl-o` returns to it.
+    fn record_jump(&mut self, cx: &mut WindowContext) {
+        self.update_active_editor(cx, |vim, editor, cx| {
+            let head = editor.selections.newest_anchor().head();
+            editor.push_to_nav_history(head, None, cx);
+            if let Some((_, marks)) = vim.buffer_marks(editor, cx) {
+                marks.marks.insert('`', head.text_anchor);
+            }
+        });
+    }
+
     fn set_enabled(&mut self, enabled: bool, cx: &mut AppContext) {
         if self.enabled == enabled {
             return;
@@ -1,6 +1,6 @@
 use crate::persistence::model::DockData;
 use crate::DraggedDock;
-use crate::{status_bar::StatusItemView, Workspace};
+use crate::{status_bar::StatusItemView, Pane, Workspace};
 use gpui::{
     div, px, Action, AnchorCorner, AnyView, AppContext, Axis, ClickEvent, Entity, EntityId,
     EventEmitter, FocusHandle, FocusableView, IntoElement, KeyContext, MouseButton, ParentElement,
@@ -44,6 +44,10 @@ pub trait Panel: FocusableView + EventEmitter<PanelEvent> {
     }
     fn set_zoomed(&mut self, _zoomed: bool, _cx: &mut ViewContext<Self>) {}
     fn set_active(&mut self, _active: bool, _cx: &mut ViewContext<Self>) {}
+    /// The pane holding this panel's items, for panels that are backed by one.
+    fn pane(&self) -> Option<View<Pane>> {
+        None
+    }
 }
 
 pub trait PanelHandle: Send + Sync {
@@ -63,6 +67,7 @@ pub trait PanelHandle: Send + Sync {
     fn icon_label(&self, cx: &WindowContext) -> Option<String>;
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle;
     fn to_any(&self) -> AnyView;
+    fn pane(&self, cx: &WindowContext) -> Option<View<Pane>>;
 }
 
 impl<T> PanelHandle for View<T>
@@ -132,6 +137,10 @@ where
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
         self.read(cx).focus_handle(cx).clone()
     }
+
+    fn pane(&self, cx: &WindowContext) -> Option<View<Pane>> {
+        self.read(cx).pane()
+    }
 }
 
 impl From<&dyn PanelHandle> for AnyView {
@@ -486,6 +495,14 @@ impl Dock {
         Some(&self.panel_entries.get(self.active_panel_index)?.panel)
     }
 
+    /// Returns the panes of all panels in this dock that are backed by one.
+    pub fn panes(&self, cx: &WindowContext) -> Vec<View<Pane>> {
+        self.panel_entries
+            .iter()
+            .filter_map(|entry| entry.panel.pane(cx))
+            .collect()
+    }
+
     fn visible_entry(&self) -> Option<&PanelEntry> {
         if self.is_open {
             self.panel_entries.get(self.active_panel_index)
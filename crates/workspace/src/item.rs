@@ -6,7 +6,7 @@ use crate::{
     DelayedDebouncedEditAction, FollowableItemBuilders, ItemNavHistory, ToolbarItemLocation,
     ViewId, Workspace, WorkspaceId,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use client::{
     proto::{self, PeerId},
     Client,
@@ -143,6 +143,11 @@ pub trait Item: FocusableView + EventEmitter<Self::Event> {
     fn has_conflict(&self, _: &AppContext) -> bool {
         false
     }
+    /// Whether the unsaved changes of this item are restored when the application is
+    /// reopened, so that quitting doesn't need to prompt for saving them.
+    fn preserves_unsaved_changes(&self, _: &AppContext) -> bool {
+        false
+    }
     fn can_save(&self, _cx: &AppContext) -> bool {
         false
     }
@@ -257,6 +262,7 @@ pub trait ItemHandle: 'static + Send {
     fn to_any(&self) -> AnyView;
     fn is_dirty(&self, cx: &AppContext) -> bool;
     fn has_conflict(&self, cx: &AppContext) -> bool;
+    fn preserves_unsaved_changes(&self, cx: &AppContext) -> bool;
     fn can_save(&self, cx: &AppContext) -> bool;
     fn save(&self, project: Model<Project>, cx: &mut WindowContext) -> Task<Result<()>>;
     fn save_as(
@@ -562,6 +568,10 @@ impl<T: Item> ItemHandle for View<T> {
         self.read(cx).has_conflict(cx)
     }
 
+    fn preserves_unsaved_changes(&self, cx: &AppContext) -> bool {
+        self.read(cx).preserves_unsaved_changes(cx)
+    }
+
     fn can_save(&self, cx: &AppContext) -> bool {
         self.read(cx).can_save(cx)
     }
@@ -696,6 +706,17 @@ pub trait FollowableItem: Item {
     ) -> Task<Result<()>>;
     fn is_project_item(&self, cx: &WindowContext) -> bool;
     fn set_leader_peer_id(&mut self, leader_peer_id: Option<PeerId>, cx: &mut ViewContext<Self>);
+    /// Applies input that a follower typed into their copy of this item, such as keystrokes
+    /// sent to a shared terminal. The workspace only calls this for read-write collaborators
+    /// who are following the item while it is active.
+    fn apply_follower_input(
+        &mut self,
+        _follower_id: PeerId,
+        _input: Vec<u8>,
+        _cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        Err(anyhow!("this item does not accept input from followers"))
+    }
 }
 
 pub trait FollowableItemHandle: ItemHandle {
@@ -716,6 +737,12 @@ pub trait FollowableItemHandle: ItemHandle {
         cx: &mut WindowContext,
     ) -> Task<Result<()>>;
     fn is_project_item(&self, cx: &WindowContext) -> bool;
+    fn apply_follower_input(
+        &self,
+        follower_id: PeerId,
+        input: Vec<u8>,
+        cx: &mut WindowContext,
+    ) -> Result<()>;
 }
 
 impl<T: FollowableItem> FollowableItemHandle for View<T> {
@@ -765,6 +792,17 @@ impl<T: FollowableItem> FollowableItemHandle for View<T> {
     fn is_project_item(&self, cx: &WindowContext) -> bool {
         self.read(cx).is_project_item(cx)
     }
+
+    fn apply_follower_input(
+        &self,
+        follower_id: PeerId,
+        input: Vec<u8>,
+        cx: &mut WindowContext,
+    ) -> Result<()> {
+        self.update(cx, |this, cx| {
+            this.apply_follower_input(follower_id, input, cx)
+        })
+    }
 }
 
 #[cfg(any(test, feature = "test-support"))]
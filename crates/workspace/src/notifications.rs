@@ -150,6 +150,12 @@ impl Workspace {
         })
     }
 
+    /// The ids of the notifications shown in the workspace, oldest first.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn notification_ids(&self) -> Vec<usize> {
+        self.notifications.iter().map(|(_, id, _)| *id).collect()
+    }
+
     pub fn dismiss_toast(&mut self, id: usize, cx: &mut ViewContext<Self>) {
         self.dismiss_notification::<simple_message_notification::MessageNotification>(id, cx);
     }
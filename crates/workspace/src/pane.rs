@@ -1934,6 +1934,10 @@ impl Render for Pane {
 }
 
 impl ItemNavHistory {
+    pub fn mode(&self) -> NavigationMode {
+        self.history.mode()
+    }
+
     pub fn push<D: 'static + Send + Any>(&mut self, data: Option<D>, cx: &mut WindowContext) {
         self.history.push(data, self.item.clone(), cx);
     }
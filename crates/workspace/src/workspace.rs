@@ -1023,12 +1023,18 @@ impl Workspace {
                         let prev_active_item_index = pane.active_item_index();
                         pane.nav_history_mut().set_mode(mode);
                         pane.activate_item(index, true, true, cx);
-                        pane.nav_history_mut().set_mode(NavigationMode::Normal);
 
+                        // An item that was already active records the position it navigates
+                        // away from, while the position in a previously active item was
+                        // recorded when it got deactivated.
                         let mut navigated = prev_active_item_index != pane.active_item_index();
-                        if let Some(data) = entry.data {
-                            navigated |= pane.active_item()?.navigate(data, cx);
+                        if navigated {
+                            pane.nav_history_mut().disable();
+                        }
+                        if let Some((data, item)) = entry.data.zip(pane.active_item()) {
+                            navigated |= item.navigate(data, cx);
                         }
+                        pane.nav_history_mut().set_mode(NavigationMode::Normal);
 
                         if navigated {
                             break None;
@@ -1246,14 +1252,14 @@ impl Workspace {
 
             Ok(this
                 .update(&mut cx, |this, cx| {
-                    this.save_all_internal(SaveIntent::Close, cx)
+                    this.save_all_internal(SaveIntent::Close, quitting, cx)
                 })?
                 .await?)
         })
     }
 
     fn save_all(&mut self, action: &SaveAll, cx: &mut ViewContext<Self>) {
-        self.save_all_internal(action.save_intent.unwrap_or(SaveIntent::SaveAll), cx)
+        self.save_all_internal(action.save_intent.unwrap_or(SaveIntent::SaveAll), false, cx)
             .detach_and_log_err(cx);
     }
 
@@ -1297,9 +1303,12 @@ impl Workspace {
             .detach_and_log_err(cx);
     }
 
+    /// Saves the dirty items of the workspace. When quitting, the items whose unsaved
+    /// changes are restored on the next launch are left untouched.
     fn save_all_internal(
         &mut self,
         mut save_intent: SaveIntent,
+        quitting: bool,
         cx: &mut ViewContext<Self>,
     ) -> Task<Result<bool>> {
         if self.project.read(cx).is_disconnected() {
@@ -1310,7 +1319,7 @@ impl Workspace {
             .iter()
             .flat_map(|pane| {
                 pane.read(cx).items().filter_map(|item| {
-                    if item.is_dirty(cx) {
+                    if item.is_dirty(cx) && !(quitting && item.preserves_unsaved_changes(cx)) {
                         Some((pane.downgrade(), item.boxed_clone()))
                     } else {
                         None
@@ -1592,6 +1601,20 @@ impl Workspace {
         self.active_pane().read(cx).active_item()
     }
 
+    /// Returns the item that followers should see: the active item of a dock panel's pane
+    /// when that pane has focus, such as a terminal in the terminal panel, and the active
+    /// center item otherwise.
+    fn active_item_for_followers(&self, cx: &WindowContext) -> Option<Box<dyn ItemHandle>> {
+        let dock_item = [&self.left_dock, &self.bottom_dock, &self.right_dock]
+            .into_iter()
+            .find_map(|dock| {
+                let pane = dock.read(cx).visible_panel()?.pane(cx)?;
+                let item = pane.read(cx).active_item()?;
+                item.focus_handle(cx).contains_focused(cx).then_some(item)
+            });
+        dock_item.or_else(|| self.active_item(cx))
+    }
+
     pub fn active_item_as<I: 'static>(&self, cx: &AppContext) -> Option<View<I>> {
         let item = self.active_item(cx)?;
         item.to_any().downcast::<I>().ok()
@@ -2481,6 +2504,16 @@ impl Workspace {
         &self.active_pane
     }
 
+    /// Returns the center panes followed by the panes of dock panels, whose items can be
+    /// shared with followers as well.
+    fn followable_panes(&self, cx: &WindowContext) -> Vec<View<Pane>> {
+        let mut panes = self.panes.clone();
+        for dock in [&self.left_dock, &self.bottom_dock, &self.right_dock] {
+            panes.extend(dock.read(cx).panes(cx));
+        }
+        panes
+    }
+
     pub fn adjacent_pane(&mut self, cx: &mut ViewContext<Self>) -> View<Pane> {
         self.find_pane_in_direction(SplitDirection::Right, cx)
             .or_else(|| self.find_pane_in_direction(SplitDirection::Left, cx))
@@ -2675,6 +2708,27 @@ impl Workspace {
         Some(leader_id)
     }
 
+    /// Sends input typed into a follower's copy of a view, such as a shared terminal, to the
+    /// leader that created the view.
+    pub fn send_follower_input(
+        &self,
+        view_id: ViewId,
+        input: Vec<u8>,
+        cx: &AppContext,
+    ) -> Option<()> {
+        let room_id = self.active_call()?.read(cx).room()?.read(cx).id();
+        let project_id = self.project.read(cx).remote_id()?;
+        self.app_state
+            .client
+            .send(proto::FollowerInput {
+                room_id,
+                project_id,
+                view_id: Some(view_id.to_proto()),
+                input,
+            })
+            .log_err()
+    }
+
     pub fn is_being_followed(&self, peer_id: PeerId) -> bool {
         self.follower_states
             .values()
@@ -2775,7 +2829,7 @@ impl Workspace {
         follower_project_id: Option<u64>,
         cx: &mut ViewContext<Self>,
     ) -> Option<proto::View> {
-        let item = self.active_item(cx)?;
+        let item = self.active_item_for_followers(cx)?;
         let leader_id = self
             .pane_for(&*item)
             .and_then(|pane| self.leader_for_pane(&pane));
@@ -2816,7 +2870,7 @@ impl Workspace {
             // TODO: once v0.124.0 is retired we can stop sending these
             active_view_id,
             views: self
-                .panes()
+                .followable_panes(cx)
                 .iter()
                 .flat_map(|pane| {
                     let leader_id = self.leader_for_pane(pane);
@@ -2863,6 +2917,50 @@ impl Workspace {
             .ok();
     }
 
+    fn handle_follower_input(
+        &mut self,
+        follower_id: PeerId,
+        view_id: ViewId,
+        input: Vec<u8>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<()> {
+        if self.app_state.client.peer_id() != Some(view_id.creator) {
+            return None;
+        }
+
+        // Only read-write collaborators who are following us in this project may type into
+        // the view they are looking at.
+        let project = self.project.read(cx);
+        let project_id = project.remote_id()?;
+        let is_collaborator = project.collaborators().contains_key(&follower_id);
+        let room = self.active_call()?.read(cx).room()?.read(cx);
+        let is_following = room
+            .followers_for(view_id.creator, project_id)
+            .contains(&follower_id);
+        let can_write =
+            room.remote_participant_for_peer_id(follower_id)
+                .map_or(false, |participant| {
+                    matches!(
+                        participant.role,
+                        proto::ChannelRole::Admin | proto::ChannelRole::Member
+                    )
+                });
+        if !is_collaborator || !is_following || !can_write {
+            log::warn!("dropping input from follower {follower_id:?} without write access");
+            return None;
+        }
+
+        let item = self.followable_panes(cx).iter().find_map(|pane| {
+            let item = pane.read(cx).active_item()?;
+            if item.item_id().as_u64() == view_id.id {
+                item.to_followable_item_handle(cx)
+            } else {
+                None
+            }
+        })?;
+        item.apply_follower_input(follower_id, input, cx).log_err()
+    }
+
     async fn process_leader_update(
         this: &WeakView<Self>,
         leader_id: PeerId,
@@ -3055,7 +3153,7 @@ impl Workspace {
         let mut is_project_item = true;
         let mut update = proto::UpdateActiveView::default();
         if cx.is_window_active() {
-            if let Some(item) = self.active_item(cx) {
+            if let Some(item) = self.active_item_for_followers(cx) {
                 if item.focus_handle(cx).contains_focused(cx) {
                     let leader_id = self
                         .pane_for(&*item)
@@ -3947,6 +4045,7 @@ impl WorkspaceStore {
             _subscriptions: vec![
                 client.add_request_handler(cx.weak_model(), Self::handle_follow),
                 client.add_message_handler(cx.weak_model(), Self::handle_update_followers),
+                client.add_message_handler(cx.weak_model(), Self::handle_follower_input),
             ],
             client,
         }
@@ -4039,6 +4138,32 @@ impl WorkspaceStore {
             Ok(())
         })?
     }
+
+    async fn handle_follower_input(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::FollowerInput>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let follower_id = envelope.original_sender_id()?;
+        let view_id = ViewId::from_proto(
+            envelope
+                .payload
+                .view_id
+                .ok_or_else(|| anyhow!("missing view id"))?,
+        )?;
+        let input = envelope.payload.input;
+
+        this.update(&mut cx, |this, cx| {
+            this.workspaces.retain(|workspace| {
+                workspace
+                    .update(cx, |workspace, cx| {
+                        workspace.handle_follower_input(follower_id, view_id, input.clone(), cx);
+                    })
+                    .is_ok()
+            });
+        })
+    }
 }
 
 impl ViewId {
@@ -30,6 +30,7 @@ use settings::{
 };
 use simplelog::ConfigBuilder;
 use smol::process::Command;
+use snippet_provider::SnippetRegistry;
 use std::{
     env,
     ffi::OsStr,
@@ -167,6 +168,7 @@ fn main() {
         language::init(cx);
         editor::init(cx);
         diagnostics::init(cx);
+        diff_view::init(cx);
         copilot::init(
             copilot_language_server_id,
             http.clone(),
@@ -175,11 +177,13 @@ fn main() {
         );
         assistant::init(cx);
 
+        snippet_provider::init(fs.clone(), cx);
         extension::init(
             fs.clone(),
             http.clone(),
             languages.clone(),
             ThemeRegistry::global(cx),
+            SnippetRegistry::global(cx),
             cx,
         );
 
@@ -242,9 +246,12 @@ fn main() {
 
         go_to_line::init(cx);
         file_finder::init(cx);
+        file_history::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
         project_panel::init(Assets, cx);
+        git_panel::init(cx);
+        hierarchy_panel::init(cx);
         tasks_ui::init(cx);
         channel::init(&client, user_store.clone(), cx);
         search::init(cx);